@@ -64,6 +64,12 @@ async fn main() -> Result<()> {
                     LiveTradeUpdate::TradingState(trading_state) => {
                         println!("{trading_state}");
                     }
+                    LiveTradeUpdate::ExecutorWarning(warning) => {
+                        println!("Executor warning: {warning}");
+                    }
+                    LiveTradeUpdate::DeadManSwitchTriggered(trigger) => {
+                        println!("Dead man's switch triggered: {trigger}");
+                    }
                 },
                 Err(e) => {
                     eprint!("{:?}", e);
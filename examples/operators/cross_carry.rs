@@ -348,6 +348,19 @@ impl RawOperator for CrossCarryOperator {
 
         Ok(())
     }
+
+    fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        let rebalance_count = self.rebalance_count.load(AtomicOrdering::Relaxed);
+        Ok(Some(rebalance_count.to_be_bytes().to_vec()))
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        let bytes: [u8; 8] = state
+            .try_into()
+            .map_err(|_| "invalid rebalance count state")?;
+        self.rebalance_count = AtomicU64::new(u64::from_be_bytes(bytes));
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
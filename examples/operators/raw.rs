@@ -9,8 +9,13 @@ use async_trait::async_trait;
 
 use quantoxide::{
     error::Result,
-    models::{Lookback, MinIterationInterval, OhlcCandleRow, OhlcResolution},
-    trade::{RawOperator, TradeExecutor, TradingState},
+    models::{
+        IterationSchedule, Lookback, MinIterationInterval, OhlcCandleRow, OhlcResolution, Period,
+    },
+    trade::{
+        OperatorContext, PriceThreshold, RawOperator, TradeCloseReason, TradeClosed, TradeExecutor,
+        TradingState,
+    },
     tui::TuiLogger,
 };
 
@@ -88,7 +93,9 @@ impl RawOperator for RawOperatorTemplate {
     fn lookback(&self) -> Option<Lookback> {
         // None // Return no candles
 
-        // Use 15-minute candles with a 10-candle period
+        // Use 15-minute candles with a 10-candle period. Additional lookbacks with other
+        // resolutions can be requested by overriding `lookbacks`, e.g. 1-hour candles for trend
+        // confirmation
         Some(Lookback::new(OhlcResolution::FifteenMinutes, 10).expect("is valid"))
     }
 
@@ -97,9 +104,44 @@ impl RawOperator for RawOperatorTemplate {
         MinIterationInterval::MIN // Minimum iteration interval of 5 seconds
     }
 
-    async fn iterate(&self, candles: &[OhlcCandleRow]) -> Result<()> {
+    fn iteration_schedule(&self) -> IterationSchedule {
+        // Iterate once per closed candle of the primary lookback, 5 seconds after the close
+        // IterationSchedule::CandleClose { delay: chrono::Duration::seconds(5) }
+        IterationSchedule::MinInterval
+    }
+
+    fn price_thresholds(&self) -> Vec<PriceThreshold> {
+        // Iterate immediately if the market price reaches any of these levels
+        // vec![PriceThreshold::Above(120_000.), PriceThreshold::Below(80_000.)]
+        Vec::new()
+    }
+
+    fn funding_settlements_lookback(&self) -> Option<Period> {
+        // None // Return no funding settlement history
+
+        // Use the last 9 funding settlements (3 days, with 8-hour settlements)
+        Some(Period::try_from(9).expect("is valid"))
+    }
+
+    async fn iterate(&self, _candles: &[OhlcCandleRow]) -> Result<()> {
+        // Not called, since `iterate_with_context` is overridden below. Operators only needing the
+        // candles of the primary lookback can implement their logic here instead
+        Ok(())
+    }
+
+    async fn iterate_with_context(&self, context: &OperatorContext<'_>) -> Result<()> {
         let trade_executor = self.trade_executor()?;
 
+        // To access the iteration context. `context.time()` is the simulated time when
+        // backtesting, and should be used instead of `Utc::now()`:
+
+        let now = context.time();
+        let candles = context.candles().primary();
+        let is_newest_candle_stable = context.is_newest_candle_stable();
+        let latest_funding_rate = context.latest_funding_rate();
+        let funding_settlements = context.funding_settlements();
+        let next_funding_settlement_time = context.next_funding_settlement_time();
+
         // To access the current trading state:
 
         let trading_state: TradingState = trade_executor.trading_state().await?;
@@ -154,4 +196,23 @@ impl RawOperator for RawOperatorTemplate {
 
         Ok(())
     }
+
+    async fn on_trade_closed(
+        &self,
+        trade: &dyn TradeClosed,
+        reason: TradeCloseReason,
+    ) -> Result<()> {
+        // Called as soon as a trade is closed, e.g. when its stoploss is reached, before the next
+        // iteration. `on_start`, `on_funding_settlement`, `on_cross_liquidation` and `on_shutdown`
+        // hooks are also available.
+
+        self.log(format!(
+            "Trade closed: id={}, reason={reason}, pl={}",
+            trade.id(),
+            trade.pl()
+        ))
+        .await?;
+
+        Ok(())
+    }
 }
@@ -13,7 +13,10 @@ use chrono::{DateTime, Utc};
 
 use quantoxide::{
     error::Result,
-    models::{Lookback, MinIterationInterval, OhlcCandleRow, OhlcResolution},
+    models::{
+        FundingSettlementRow, Lookback, LookbackCandles, MinIterationInterval, OhlcCandleRow,
+        OhlcResolution,
+    },
     signal::{Signal, SignalEvaluator},
     tui::TuiLogger,
 };
@@ -166,4 +169,16 @@ where
         // Convert to target signal type and return
         Ok(signal.into())
     }
+
+    // To access funding settlements (see `funding_settlements_lookback`) or the candles of
+    // additional lookbacks, override `evaluate_with_context` instead. By default, it calls
+    // `evaluate` with the candles of the primary lookback
+    // async fn evaluate_with_context(
+    //     &self,
+    //     candles: &LookbackCandles<'_>,
+    //     funding_settlements: &[FundingSettlementRow],
+    // ) -> Result<S> {
+    //     let latest_funding_rate = funding_settlements.last().map(|s| s.funding_rate);
+    //     self.evaluate(candles.primary()).await
+    // }
 }
@@ -0,0 +1 @@
+DROP TABLE ohlc_candles_consolidated;
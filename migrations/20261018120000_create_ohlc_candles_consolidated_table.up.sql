@@ -0,0 +1,35 @@
+CREATE TABLE ohlc_candles_consolidated (
+    resolution INTEGER NOT NULL,
+    time TIMESTAMPTZ NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (resolution, time)
+);
+
+-- Materialize the stable buckets of the existing 1-minute history
+INSERT INTO ohlc_candles_consolidated (resolution, time, open, high, low, close, volume, created_at, updated_at)
+SELECT
+    minutes,
+    bucket_time,
+    (array_agg(open ORDER BY time ASC))[1],
+    MAX(high),
+    MIN(low),
+    (array_agg(close ORDER BY time DESC))[1],
+    SUM(volume)::BIGINT,
+    MIN(created_at),
+    MAX(updated_at)
+FROM (
+    SELECT
+        r.minutes,
+        to_timestamp(FLOOR(EXTRACT(EPOCH FROM c.time)::BIGINT / (r.minutes * 60)) * r.minutes * 60) AS bucket_time,
+        c.time, c.open, c.high, c.low, c.close, c.volume, c.created_at, c.updated_at, c.stable
+    FROM ohlc_candles c
+    CROSS JOIN unnest(ARRAY[3, 5, 10, 15, 30, 45, 60, 120, 180, 240, 1440]) AS r(minutes)
+) bucketed
+GROUP BY minutes, bucket_time
+HAVING BOOL_AND(stable) AND bucket_time + make_interval(mins => minutes) <= NOW();
@@ -0,0 +1 @@
+DROP TABLE executor_actions;
@@ -0,0 +1,16 @@
+CREATE TABLE executor_actions (
+    id BIGSERIAL PRIMARY KEY,
+    account_id UUID NOT NULL,
+    kind TEXT NOT NULL,
+    shadow BOOLEAN NOT NULL DEFAULT FALSE,
+    request JSONB NOT NULL,
+    response JSONB,
+    error TEXT,
+    latency_ms BIGINT NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW (),
+    CONSTRAINT response_or_error CHECK ((response IS NULL) <> (error IS NULL))
+);
+
+CREATE INDEX executor_actions_account_id_requested_at_idx
+    ON executor_actions (account_id, requested_at);
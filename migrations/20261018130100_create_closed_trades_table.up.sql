@@ -0,0 +1,26 @@
+CREATE TABLE closed_trades (
+    account_id UUID NOT NULL,
+    trade_id UUID NOT NULL,
+    side TEXT NOT NULL CHECK (side IN ('Buy', 'Sell')),
+    opening_fee BIGINT NOT NULL,
+    closing_fee BIGINT NOT NULL,
+    maintenance_margin BIGINT NOT NULL,
+    quantity BIGINT NOT NULL,
+    margin BIGINT NOT NULL,
+    leverage DOUBLE PRECISION NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    liquidation DOUBLE PRECISION NOT NULL,
+    stoploss DOUBLE PRECISION,
+    takeprofit DOUBLE PRECISION,
+    exit_price DOUBLE PRECISION,
+    sum_funding_fees BIGINT NOT NULL,
+    pl BIGINT NOT NULL,
+    client_id TEXT,
+    created_at TIMESTAMPTZ NOT NULL,
+    filled_at TIMESTAMPTZ,
+    closed_at TIMESTAMPTZ NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW (),
+    PRIMARY KEY (account_id, trade_id)
+);
+
+CREATE INDEX closed_trades_account_id_closed_at_idx ON closed_trades (account_id, closed_at);
@@ -0,0 +1,5 @@
+CREATE TABLE key_values (
+    key TEXT NOT NULL PRIMARY KEY,
+    value BYTEA NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW ()
+);
@@ -0,0 +1,13 @@
+CREATE TABLE signals (
+    id BIGSERIAL PRIMARY KEY,
+    run TEXT NOT NULL,
+    evaluator TEXT NOT NULL,
+    time TIMESTAMPTZ NOT NULL,
+    signal TEXT NOT NULL,
+    payload JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX signals_run_time_idx ON signals (run, time);
+
+CREATE INDEX signals_run_evaluator_time_idx ON signals (run, evaluator, time);
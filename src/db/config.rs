@@ -0,0 +1,157 @@
+use std::num::NonZeroU32;
+
+use tokio::time;
+
+use super::error::{DbError, Result};
+
+/// Configuration for the [`Database`](crate::Database) connection pool and schema.
+#[derive(Clone, Debug)]
+pub struct DatabaseConfig {
+    max_connections: NonZeroU32,
+    min_connections: u32,
+    acquire_timeout: time::Duration,
+    idle_timeout: Option<time::Duration>,
+    schema: Option<String>,
+    read_only: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 5.try_into().expect("not zero"),
+            min_connections: 0,
+            acquire_timeout: time::Duration::from_secs(30),
+            idle_timeout: Some(time::Duration::from_secs(10 * 60)),
+            schema: None,
+            read_only: false,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    /// Returns the maximum number of connections in the pool.
+    pub fn max_connections(&self) -> NonZeroU32 {
+        self.max_connections
+    }
+
+    /// Returns the minimum number of idle connections maintained by the pool.
+    pub fn min_connections(&self) -> u32 {
+        self.min_connections
+    }
+
+    /// Returns the timeout for acquiring a connection from the pool.
+    pub fn acquire_timeout(&self) -> time::Duration {
+        self.acquire_timeout
+    }
+
+    /// Returns the duration after which idle connections are closed, if any.
+    pub fn idle_timeout(&self) -> Option<time::Duration> {
+        self.idle_timeout
+    }
+
+    /// Returns the Postgres schema where tables are stored, if any. When `None`, the server's
+    /// default `search_path` is used.
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// Returns whether the database is opened in read-only mode.
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Sets the maximum number of connections in the pool.
+    ///
+    /// Default: `5`
+    pub fn with_max_connections(mut self, max_connections: NonZeroU32) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Sets the minimum number of idle connections maintained by the pool.
+    ///
+    /// Default: `0`
+    pub fn with_min_connections(mut self, min_connections: u32) -> Self {
+        self.min_connections = min_connections;
+        self
+    }
+
+    /// Sets the timeout for acquiring a connection from the pool.
+    ///
+    /// Default: `30` seconds
+    pub fn with_acquire_timeout(mut self, secs: u64) -> Self {
+        self.acquire_timeout = time::Duration::from_secs(secs);
+        self
+    }
+
+    /// Sets the duration after which idle connections are closed. If `None`, idle connections are
+    /// never closed.
+    ///
+    /// Default: `600` seconds (10 minutes)
+    pub fn with_idle_timeout(mut self, secs: Option<u64>) -> Self {
+        self.idle_timeout = secs.map(time::Duration::from_secs);
+        self
+    }
+
+    /// Sets the Postgres schema where tables are stored. Allows several independent datasets (e.g.
+    /// testnet and mainnet) to share the same server.
+    ///
+    /// The schema is created if it doesn't exist, unless the database is opened in read-only mode.
+    /// Names must start with a lowercase letter or underscore, and contain only lowercase letters,
+    /// digits and underscores (max 63 characters).
+    ///
+    /// Default: `None` (server's default `search_path`)
+    pub fn with_schema(mut self, schema: impl Into<String>) -> Result<Self> {
+        let schema = schema.into();
+
+        let mut chars = schema.chars();
+        let valid_first = chars
+            .next()
+            .is_some_and(|c| c.is_ascii_lowercase() || c == '_');
+        let valid_rest = chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+        if !valid_first || !valid_rest || schema.len() > 63 {
+            return Err(DbError::InvalidSchemaName { schema });
+        }
+
+        self.schema = Some(schema);
+        Ok(self)
+    }
+
+    /// Sets whether the database is opened in read-only mode. In read-only mode, migrations are
+    /// not run and all connections reject writes. Intended for backtest workers sharing a
+    /// database that is kept up-to-date by another process. Sync and live trade engines reject
+    /// read-only databases, and backtest and live signal engines reject them when configured to
+    /// persist operator state or signals.
+    ///
+    /// Default: `false`
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_schema_validates_name() {
+        assert!(DatabaseConfig::default().with_schema("mainnet").is_ok());
+        assert!(DatabaseConfig::default().with_schema("_testnet_2").is_ok());
+
+        assert!(DatabaseConfig::default().with_schema("").is_err());
+        assert!(DatabaseConfig::default().with_schema("2testnet").is_err());
+        assert!(DatabaseConfig::default().with_schema("Mainnet").is_err());
+        assert!(
+            DatabaseConfig::default()
+                .with_schema("main\"; DROP")
+                .is_err()
+        );
+        assert!(
+            DatabaseConfig::default()
+                .with_schema("a".repeat(64))
+                .is_err()
+        );
+    }
+}
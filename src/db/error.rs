@@ -46,6 +46,11 @@ pub enum DbError {
     #[error("New DB candles must be ordered by time desc. Inconsistency at: {inconsistency_at}")]
     NewDbCandlesNotOrderedByTimeDesc { inconsistency_at: DateTime<Utc> },
 
+    #[error(
+        "Invalid schema name `{schema}`. Must start with a lowercase letter or underscore, and contain only lowercase letters, digits and underscores (max 63 characters)"
+    )]
+    InvalidSchemaName { schema: String },
+
     #[error("Invalid funding settlement time: {time}")]
     InvalidFundingSettlementTime { time: DateTime<Utc> },
 }
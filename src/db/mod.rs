@@ -1,8 +1,10 @@
 use std::sync::Arc;
 
-use chrono::Duration;
-use sqlx::postgres::PgPoolOptions;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{AssertSqlSafe, Executor, postgres::PgPoolOptions};
+use uuid::Uuid;
 
+pub(crate) mod config;
 pub(crate) mod error;
 pub(crate) mod models;
 
@@ -10,28 +12,45 @@ pub(crate) mod models;
 /// past. The API may return slightly different OHLC values for recent candles across requests.
 pub(crate) const CANDLE_STABLE_AGE: Duration = Duration::hours(1);
 
+/// Prefix of the persisted keys reserved for internal state, such as the price tick downsampling
+/// bound. Keys chosen by users (operator state, paper ledger and kill switch keys) can't start with
+/// it.
+pub(crate) const RESERVED_KEY_PREFIX: &str = "quantoxide:";
+
 mod postgres;
 mod repositories;
 
+use config::DatabaseConfig;
 use error::{DbError, Result};
+use models::{ClosedTradeRow, ExecutorActionRow, SignalRow};
 use postgres::{
-    funding_settlements::PgFundingSettlementsRepo, ohlc_candles::PgOhlcCandlesRepo,
-    price_ticks::PgPriceTicksRepo, running_trades::PgRunningTradesRepo,
+    closed_trades::PgClosedTradesRepo, executor_actions::PgExecutorActionsRepo,
+    funding_settlements::PgFundingSettlementsRepo, key_values::PgKeyValuesRepo,
+    ohlc_candles::PgOhlcCandlesRepo, price_ticks::PgPriceTicksRepo,
+    running_trades::PgRunningTradesRepo, signals::PgSignalsRepo,
 };
 use repositories::{
-    FundingSettlementsRepository, OhlcCandlesRepository, PriceTicksRepository,
-    RunningTradesRepository,
+    ClosedTradesRepository, ExecutorActionsRepository, FundingSettlementsRepository,
+    KeyValuesRepository, OhlcCandlesRepository, PriceTicksRepository, RunningTradesRepository,
+    SignalsRepository,
 };
 
 /// Primary database interface for market data persistence and retrieval.
 ///
-/// Provides access to repositories for OHLC candle data, price tick data, and running trade
-/// information. Uses PostgreSQL as the underlying storage engine with automatic migrations.
+/// Provides access to repositories for OHLC candle data, price tick data, running trade
+/// information, the live trading audit log of executor actions and closed trades, persisted
+/// operator state and emitted signals. Uses PostgreSQL as the underlying storage engine with
+/// automatic migrations.
 pub struct Database {
     pub(crate) ohlc_candles: Box<dyn OhlcCandlesRepository>,
     pub(crate) price_ticks: Box<dyn PriceTicksRepository>,
     pub(crate) running_trades: Box<dyn RunningTradesRepository>,
     pub(crate) funding_settlements: Box<dyn FundingSettlementsRepository>,
+    pub(crate) executor_actions: Box<dyn ExecutorActionsRepository>,
+    pub(crate) closed_trades: Box<dyn ClosedTradesRepository>,
+    pub(crate) key_values: Box<dyn KeyValuesRepository>,
+    pub(crate) signals: Box<dyn SignalsRepository>,
+    read_only: bool,
 }
 
 impl Database {
@@ -41,28 +60,149 @@ impl Database {
     /// pending migrations. Returns an error if the connection fails or migrations cannot be
     /// applied.
     pub async fn new(postgres_db_url: &str) -> Result<Arc<Self>> {
+        Self::with_config(postgres_db_url, &DatabaseConfig::default()).await
+    }
+
+    /// Creates a new database instance with the given [`DatabaseConfig`].
+    ///
+    /// When a schema is configured, it is created if needed and set as the `search_path` of every
+    /// connection. Unless in read-only mode, pending migrations are applied to that schema. In
+    /// read-only mode, connections are set to reject writes and migrations are skipped.
+    pub async fn with_config(postgres_db_url: &str, config: &DatabaseConfig) -> Result<Arc<Self>> {
+        if let Some(schema) = config.schema()
+            && !config.read_only()
+        {
+            let setup_pool = PgPoolOptions::new()
+                .max_connections(1)
+                .connect(postgres_db_url)
+                .await
+                .map_err(DbError::Connection)?;
+
+            // Safe to interpolate, schema names are validated by `DatabaseConfig::with_schema`
+            setup_pool
+                .execute(AssertSqlSafe(format!(
+                    r#"CREATE SCHEMA IF NOT EXISTS "{schema}""#
+                )))
+                .await
+                .map_err(DbError::Query)?;
+
+            setup_pool.close().await;
+        }
+
+        let mut session_setup = Vec::new();
+        if let Some(schema) = config.schema() {
+            session_setup.push(format!(r#"SET search_path TO "{schema}""#));
+        }
+        if config.read_only() {
+            session_setup.push("SET default_transaction_read_only = on".to_string());
+        }
+        let session_setup = Arc::new(session_setup);
+
         let pool = PgPoolOptions::new()
-            .max_connections(5)
+            .max_connections(config.max_connections().get())
+            .min_connections(config.min_connections())
+            .acquire_timeout(config.acquire_timeout())
+            .idle_timeout(config.idle_timeout())
+            .after_connect(move |conn, _meta| {
+                let session_setup = session_setup.clone();
+                Box::pin(async move {
+                    for statement in session_setup.iter() {
+                        conn.execute(AssertSqlSafe(statement.clone())).await?;
+                    }
+                    Ok(())
+                })
+            })
             .connect(postgres_db_url)
             .await
             .map_err(DbError::Connection)?;
 
-        sqlx::migrate!("./migrations")
-            .run(&pool)
-            .await
-            .map_err(DbError::Migration)?;
+        if !config.read_only() {
+            sqlx::migrate!("./migrations")
+                .run(&pool)
+                .await
+                .map_err(DbError::Migration)?;
+        }
 
         let pool = Arc::new(pool);
         let ohlc_candles = Box::new(PgOhlcCandlesRepo::new(pool.clone()));
         let price_ticks = Box::new(PgPriceTicksRepo::new(pool.clone()));
         let running_trades = Box::new(PgRunningTradesRepo::new(pool.clone()));
         let funding_settlements = Box::new(PgFundingSettlementsRepo::new(pool.clone()));
+        let executor_actions = Box::new(PgExecutorActionsRepo::new(pool.clone()));
+        let closed_trades = Box::new(PgClosedTradesRepo::new(pool.clone()));
+        let key_values = Box::new(PgKeyValuesRepo::new(pool.clone()));
+        let signals = Box::new(PgSignalsRepo::new(pool.clone()));
 
         Ok(Arc::new(Self {
             ohlc_candles,
             price_ticks,
             running_trades,
             funding_settlements,
+            executor_actions,
+            closed_trades,
+            key_values,
+            signals,
+            read_only: config.read_only(),
         }))
     }
+
+    /// Returns whether the database was opened in read-only mode. See
+    /// [`DatabaseConfig::with_read_only`].
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Retrieves the executor actions sent for the given account within the specified time range,
+    /// ordered by request time ASC.
+    ///
+    /// Every action sent to the exchange API by a live trade executor is recorded, along with its
+    /// response or error and latency. Actions intercepted by a shadow executor on the same account
+    /// are recorded as well, marked as [`shadow`](ExecutorActionRow::shadow).
+    pub async fn get_executor_actions(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ExecutorActionRow>> {
+        self.executor_actions
+            .get_actions(account_id, from, to)
+            .await
+    }
+
+    /// Retrieves the trades of the given account closed during live trading at or after `from`,
+    /// ordered by closing time ASC.
+    pub async fn get_closed_trades(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<ClosedTradeRow>> {
+        self.closed_trades.get_closed_trades(account_id, from).await
+    }
+
+    /// Retrieves the signals emitted in the given run within the specified time range, optionally
+    /// filtered by evaluator name, ordered by time ASC.
+    ///
+    /// Signals are only persisted when a signal run is configured, via
+    /// [`LiveSignalConfig::with_signal_run`](crate::signal::LiveSignalConfig::with_signal_run),
+    /// [`LiveTradeConfig::with_signal_run`](crate::trade::LiveTradeConfig::with_signal_run) or
+    /// [`BacktestConfig::with_signal_run`](crate::trade::BacktestConfig::with_signal_run).
+    pub async fn get_signals(
+        &self,
+        run: &str,
+        evaluator: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<SignalRow>> {
+        self.signals.get_signals(run, evaluator, from, to).await
+    }
+
+    /// Retrieves the latest signal of each evaluator emitted in the given run at or before `time`,
+    /// ordered by evaluator name. Useful to review what each evaluator said before a trade.
+    pub async fn get_latest_signals(
+        &self,
+        run: &str,
+        time: DateTime<Utc>,
+    ) -> Result<Vec<SignalRow>> {
+        self.signals.get_latest_signals(run, time).await
+    }
 }
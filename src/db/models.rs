@@ -11,6 +11,111 @@ pub(crate) struct RunningTrade {
     pub trailing_stoploss: Option<f64>,
 }
 
+/// Database row representing an executor action sent to the exchange API by the live trade
+/// executor, or intercepted by a shadow executor.
+///
+/// Exactly one of `response` and `error` is set. The response of intercepted actions is the
+/// hypothetical result returned in place of the API response.
+#[derive(Debug, Clone)]
+pub struct ExecutorActionRow {
+    /// Sequential row identifier.
+    pub id: i64,
+    /// Identifier of the account the action was sent for.
+    pub account_id: Uuid,
+    /// Action kind, in snake case (e.g. `isolated_order`).
+    pub kind: String,
+    /// Whether the action was intercepted by a shadow executor instead of being sent to the API.
+    pub shadow: bool,
+    /// Structured JSON representation of the action request.
+    pub request: String,
+    /// Structured JSON representation of the API response, if the action succeeded.
+    pub response: Option<String>,
+    /// Error message, if the action failed.
+    pub error: Option<String>,
+    /// Time between sending the request and receiving the response, in milliseconds.
+    pub latency_ms: i64,
+    /// Time when the request was sent.
+    pub requested_at: DateTime<Utc>,
+    /// Time when the row was inserted.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Database row representing an isolated trade closed during live trading.
+#[derive(Debug, Clone)]
+pub struct ClosedTradeRow {
+    /// Identifier of the account the trade belongs to.
+    pub account_id: Uuid,
+    /// Trade identifier.
+    pub trade_id: Uuid,
+    /// Trade side (`Buy` or `Sell`).
+    pub side: String,
+    /// Opening fee in satoshis.
+    pub opening_fee: i64,
+    /// Closing fee in satoshis.
+    pub closing_fee: i64,
+    /// Maintenance margin in satoshis.
+    pub maintenance_margin: i64,
+    /// Trade quantity in USD.
+    pub quantity: i64,
+    /// Trade margin in satoshis.
+    pub margin: i64,
+    /// Trade leverage.
+    pub leverage: f64,
+    /// Entry price.
+    pub price: f64,
+    /// Liquidation price.
+    pub liquidation: f64,
+    /// Stoploss price, if any.
+    pub stoploss: Option<f64>,
+    /// Takeprofit price, if any.
+    pub takeprofit: Option<f64>,
+    /// Exit price, if the trade was filled.
+    pub exit_price: Option<f64>,
+    /// Sum of funding fees paid (positive) or received (negative) in satoshis.
+    pub sum_funding_fees: i64,
+    /// Realized profit/loss in satoshis.
+    pub pl: i64,
+    /// Client-provided order identifier, if any.
+    pub client_id: Option<String>,
+    /// Trade creation time.
+    pub created_at: DateTime<Utc>,
+    /// Trade fill time, if the trade was filled.
+    pub filled_at: Option<DateTime<Utc>>,
+    /// Trade closing time.
+    pub closed_at: DateTime<Utc>,
+    /// Time when the row was inserted.
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Database row representing a signal emitted by a signal evaluator, persisted when a signal run
+/// is configured.
+#[derive(Debug, Clone)]
+pub struct SignalRow {
+    /// Sequential row identifier.
+    pub id: i64,
+    /// Name of the run the signal was emitted in.
+    pub run: String,
+    /// Name of the evaluator that emitted the signal.
+    pub evaluator: String,
+    /// Time of the evaluation (simulated time when backtesting).
+    pub time: DateTime<Utc>,
+    /// `Display` text of the signal.
+    pub signal: String,
+    /// Structured JSON payload of the signal, if provided by the evaluator.
+    pub payload: Option<String>,
+    /// Time when the row was inserted.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Signal to be persisted.
+#[derive(Debug, Clone)]
+pub(crate) struct NewSignal {
+    pub evaluator: String,
+    pub time: DateTime<Utc>,
+    pub signal: String,
+    pub payload: Option<String>,
+}
+
 /// Database row representing a single OHLC (Open, High, Low, Close) candlestick.
 ///
 /// Contains aggregated price and volume data for a specific time period, along with metadata
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::Trade;
+
+use super::super::{
+    error::{DbError, Result},
+    models::ClosedTradeRow,
+    repositories::ClosedTradesRepository,
+};
+
+pub(crate) struct PgClosedTradesRepo {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl PgClosedTradesRepo {
+    pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
+        Self { pool }
+    }
+
+    fn pool(&self) -> &Pool<Postgres> {
+        self.pool.as_ref()
+    }
+}
+
+#[async_trait]
+impl ClosedTradesRepository for PgClosedTradesRepo {
+    async fn add_closed_trades(&self, account_id: Uuid, trades: &[Trade]) -> Result<()> {
+        let trades = trades
+            .iter()
+            .filter_map(|trade| trade.closed_at().map(|closed_at| (trade, closed_at)))
+            .collect::<Vec<_>>();
+
+        if trades.is_empty() {
+            return Ok(());
+        }
+
+        let mut trade_ids = Vec::with_capacity(trades.len());
+        let mut sides = Vec::with_capacity(trades.len());
+        let mut opening_fees = Vec::with_capacity(trades.len());
+        let mut closing_fees = Vec::with_capacity(trades.len());
+        let mut maintenance_margins = Vec::with_capacity(trades.len());
+        let mut quantities = Vec::with_capacity(trades.len());
+        let mut margins = Vec::with_capacity(trades.len());
+        let mut leverages = Vec::with_capacity(trades.len());
+        let mut prices = Vec::with_capacity(trades.len());
+        let mut liquidations = Vec::with_capacity(trades.len());
+        let mut stoplosses = Vec::with_capacity(trades.len());
+        let mut takeprofits = Vec::with_capacity(trades.len());
+        let mut exit_prices = Vec::with_capacity(trades.len());
+        let mut sum_funding_fees = Vec::with_capacity(trades.len());
+        let mut pls = Vec::with_capacity(trades.len());
+        let mut client_ids = Vec::with_capacity(trades.len());
+        let mut created_ats = Vec::with_capacity(trades.len());
+        let mut filled_ats = Vec::with_capacity(trades.len());
+        let mut closed_ats = Vec::with_capacity(trades.len());
+
+        for (trade, closed_at) in trades {
+            trade_ids.push(trade.id());
+            sides.push(trade.side().to_string());
+            opening_fees.push(trade.opening_fee() as i64);
+            closing_fees.push(trade.closing_fee() as i64);
+            maintenance_margins.push(trade.maintenance_margin());
+            quantities.push(trade.quantity().as_i64());
+            margins.push(trade.margin().as_i64());
+            leverages.push(trade.leverage().as_f64());
+            prices.push(trade.price().as_f64());
+            liquidations.push(trade.liquidation().as_f64());
+            stoplosses.push(trade.stoploss().map(|price| price.as_f64()));
+            takeprofits.push(trade.takeprofit().map(|price| price.as_f64()));
+            exit_prices.push(trade.exit_price().map(|price| price.as_f64()));
+            sum_funding_fees.push(trade.sum_funding_fees());
+            pls.push(trade.pl());
+            client_ids.push(trade.client_id().map(|id| id.as_str().to_string()));
+            created_ats.push(trade.created_at());
+            filled_ats.push(trade.filled_at());
+            closed_ats.push(closed_at);
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO closed_trades (
+                    account_id, trade_id, side, opening_fee, closing_fee, maintenance_margin,
+                    quantity, margin, leverage, price, liquidation, stoploss, takeprofit,
+                    exit_price, sum_funding_fees, pl, client_id, created_at, filled_at, closed_at
+                )
+                SELECT $1, * FROM unnest(
+                    $2::uuid[], $3::text[], $4::int8[], $5::int8[], $6::int8[], $7::int8[],
+                    $8::int8[], $9::float8[], $10::float8[], $11::float8[], $12::float8[],
+                    $13::float8[], $14::float8[], $15::int8[], $16::int8[], $17::text[],
+                    $18::timestamptz[], $19::timestamptz[], $20::timestamptz[]
+                )
+                ON CONFLICT (account_id, trade_id) DO NOTHING
+            "#,
+            account_id,
+            &trade_ids,
+            &sides,
+            &opening_fees,
+            &closing_fees,
+            &maintenance_margins,
+            &quantities,
+            &margins,
+            &leverages,
+            &prices,
+            &liquidations,
+            &stoplosses as &[Option<f64>],
+            &takeprofits as &[Option<f64>],
+            &exit_prices as &[Option<f64>],
+            &sum_funding_fees,
+            &pls,
+            &client_ids as &[Option<String>],
+            &created_ats,
+            &filled_ats as &[Option<DateTime<Utc>>],
+            &closed_ats,
+        )
+        .execute(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
+
+    async fn get_closed_trades(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<ClosedTradeRow>> {
+        let rows = sqlx::query_as!(
+            ClosedTradeRow,
+            r#"
+                SELECT account_id, trade_id, side, opening_fee, closing_fee, maintenance_margin,
+                    quantity, margin, leverage, price, liquidation, stoploss, takeprofit,
+                    exit_price, sum_funding_fees, pl, client_id, created_at, filled_at, closed_at,
+                    recorded_at
+                FROM closed_trades
+                WHERE account_id = $1 AND closed_at >= $2
+                ORDER BY closed_at ASC, trade_id ASC
+            "#,
+            account_id,
+            from
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(rows)
+    }
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use super::super::{
+    error::{DbError, Result},
+    models::ExecutorActionRow,
+    repositories::ExecutorActionsRepository,
+};
+
+pub(crate) struct PgExecutorActionsRepo {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl PgExecutorActionsRepo {
+    pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
+        Self { pool }
+    }
+
+    fn pool(&self) -> &Pool<Postgres> {
+        self.pool.as_ref()
+    }
+}
+
+#[async_trait]
+impl ExecutorActionsRepository for PgExecutorActionsRepo {
+    async fn add_action(
+        &self,
+        account_id: Uuid,
+        kind: &str,
+        shadow: bool,
+        request: &str,
+        response: Option<&str>,
+        error: Option<&str>,
+        latency: Duration,
+        requested_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO executor_actions
+                    (account_id, kind, shadow, request, response, error, latency_ms, requested_at)
+                VALUES ($1, $2, $3, $4::text::jsonb, $5::text::jsonb, $6, $7, $8)
+            "#,
+            account_id,
+            kind,
+            shadow,
+            request,
+            response,
+            error,
+            latency.num_milliseconds(),
+            requested_at,
+        )
+        .execute(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
+
+    async fn get_actions(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ExecutorActionRow>> {
+        let rows = sqlx::query_as!(
+            ExecutorActionRow,
+            r#"
+                SELECT id, account_id, kind, shadow, request::text AS "request!",
+                    response::text AS response, error, latency_ms, requested_at, created_at
+                FROM executor_actions
+                WHERE account_id = $1 AND requested_at >= $2 AND requested_at <= $3
+                ORDER BY requested_at ASC, id ASC
+            "#,
+            account_id,
+            from,
+            to
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(rows)
+    }
+}
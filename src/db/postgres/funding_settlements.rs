@@ -148,6 +148,20 @@ impl FundingSettlementsRepository for PgFundingSettlementsRepo {
         Ok(rows)
     }
 
+    async fn get_latest_settlements(
+        &self,
+        to: DateTime<Utc>,
+        count: u32,
+    ) -> Result<Vec<FundingSettlementRow>> {
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+
+        let from = to.step_back_funding_settlements(count - 1);
+
+        self.get_settlements(from, to).await
+    }
+
     async fn get_earliest_settlement_time(&self) -> Result<Option<DateTime<Utc>>> {
         struct TimeRow {
             pub time: DateTime<Utc>,
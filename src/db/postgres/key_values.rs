@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{Pool, Postgres};
+
+use super::super::{
+    error::{DbError, Result},
+    repositories::KeyValuesRepository,
+};
+
+pub(crate) struct PgKeyValuesRepo {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl PgKeyValuesRepo {
+    pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
+        Self { pool }
+    }
+
+    fn pool(&self) -> &Pool<Postgres> {
+        self.pool.as_ref()
+    }
+}
+
+#[async_trait]
+impl KeyValuesRepository for PgKeyValuesRepo {
+    async fn get_value(&self, key: &str) -> Result<Option<Vec<u8>>> {
+        let value = sqlx::query_scalar!("SELECT value FROM key_values WHERE key = $1", key)
+            .fetch_optional(self.pool())
+            .await
+            .map_err(DbError::Query)?;
+
+        Ok(value)
+    }
+
+    async fn set_value(&self, key: &str, value: &[u8]) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO key_values (key, value)
+                VALUES ($1, $2)
+                ON CONFLICT (key) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+            "#,
+            key,
+            value,
+        )
+        .execute(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
+
+    async fn remove_value(&self, key: &str) -> Result<()> {
+        sqlx::query!("DELETE FROM key_values WHERE key = $1", key)
+            .execute(self.pool())
+            .await
+            .map_err(DbError::Query)?;
+
+        Ok(())
+    }
+}
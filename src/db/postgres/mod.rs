@@ -1,4 +1,8 @@
+pub(super) mod closed_trades;
+pub(super) mod executor_actions;
 pub(super) mod funding_settlements;
+pub(super) mod key_values;
 pub(super) mod ohlc_candles;
 pub(super) mod price_ticks;
 pub(super) mod running_trades;
+pub(super) mod signals;
@@ -15,6 +15,22 @@ use super::super::{
     repositories::OhlcCandlesRepository,
 };
 
+/// Resolutions kept in the `ohlc_candles_consolidated` table. 1-minute candles are served
+/// directly from `ohlc_candles`.
+const MATERIALIZED_RESOLUTIONS: [OhlcResolution; 11] = [
+    OhlcResolution::ThreeMinutes,
+    OhlcResolution::FiveMinutes,
+    OhlcResolution::TenMinutes,
+    OhlcResolution::FifteenMinutes,
+    OhlcResolution::ThirtyMinutes,
+    OhlcResolution::FortyFiveMinutes,
+    OhlcResolution::OneHour,
+    OhlcResolution::TwoHours,
+    OhlcResolution::ThreeHours,
+    OhlcResolution::FourHours,
+    OhlcResolution::OneDay,
+];
+
 pub(crate) struct PgOhlcCandlesRepo {
     pool: Arc<Pool<Postgres>>,
 }
@@ -31,6 +47,125 @@ impl PgOhlcCandlesRepo {
     async fn start_transaction(&self) -> Result<Transaction<'static, Postgres>> {
         self.pool.begin().await.map_err(DbError::TransactionBegin)
     }
+
+    // Aggregates 1-minute candles with `from <= time < until` into buckets of the given
+    // resolution. Candles are aggregated using:
+    // - First open price in the period
+    // - Maximum high price
+    // - Minimum low price
+    // - Last close price in the period
+    // - Sum of volumes
+    // - `stable` is true only if all constituent candles are stable AND the candle's time period
+    //   has fully elapsed (bucket_time + resolution <= stable_to)
+    async fn aggregate_candles(
+        &self,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        stable_to: DateTime<Utc>,
+        resolution: OhlcResolution,
+    ) -> Result<Vec<OhlcCandleRow>> {
+        let resolution_seconds = resolution.as_seconds() as i64;
+        let rows = sqlx::query_as!(
+            OhlcCandleRow,
+            r#"
+                WITH bucketed AS (
+                    SELECT
+                        to_timestamp(FLOOR(EXTRACT(EPOCH FROM time)::BIGINT / $3) * $3) AS bucket_time,
+                        open, high, low, close, volume, created_at, updated_at, stable, time
+                    FROM ohlc_candles
+                    WHERE time >= $1 AND time < $2
+                )
+                SELECT
+                    bucket_time AS "time!",
+                    (array_agg(open ORDER BY time ASC))[1] AS "open!",
+                    MAX(high) AS "high!",
+                    MIN(low) AS "low!",
+                    (array_agg(close ORDER BY time DESC))[1] AS "close!",
+                    SUM(volume)::BIGINT AS "volume!",
+                    MIN(created_at) AS "created_at!",
+                    MAX(updated_at) AS "updated_at!",
+                    BOOL_AND(stable) AND (bucket_time + $3 * INTERVAL '1 second') <= $4 AS "stable!"
+                FROM bucketed
+                GROUP BY bucket_time
+                ORDER BY bucket_time ASC
+            "#,
+            from,
+            until,
+            resolution_seconds,
+            stable_to
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(rows)
+    }
+
+    // Recomputes the materialized buckets of every resolution overlapping the `from..=to` range.
+    // Only buckets whose candles are all stable and whose time period has fully elapsed are
+    // materialized. Others are served on the fly by `get_candles_consolidated`.
+    async fn refresh_consolidated_candles(
+        tx: &mut Transaction<'static, Postgres>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<()> {
+        let resolutions: Vec<i32> = MATERIALIZED_RESOLUTIONS
+            .iter()
+            .map(|resolution| resolution.as_minutes() as i32)
+            .collect();
+
+        sqlx::query!(
+            r#"
+                DELETE FROM ohlc_candles_consolidated c
+                USING unnest($3::int[]) AS r(minutes)
+                WHERE c.resolution = r.minutes
+                AND c.time >= to_timestamp(FLOOR(EXTRACT(EPOCH FROM $1::timestamptz)::BIGINT / (r.minutes * 60)) * r.minutes * 60)
+                AND c.time <= $2
+            "#,
+            from,
+            to,
+            &resolutions
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(DbError::Query)?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO ohlc_candles_consolidated (resolution, time, open, high, low, close, volume, created_at, updated_at)
+                SELECT
+                    minutes,
+                    bucket_time,
+                    (array_agg(open ORDER BY time ASC))[1],
+                    MAX(high),
+                    MIN(low),
+                    (array_agg(close ORDER BY time DESC))[1],
+                    SUM(volume)::BIGINT,
+                    MIN(created_at),
+                    MAX(updated_at)
+                FROM (
+                    SELECT
+                        r.minutes,
+                        to_timestamp(FLOOR(EXTRACT(EPOCH FROM c.time)::BIGINT / (r.minutes * 60)) * r.minutes * 60) AS bucket_time,
+                        c.time, c.open, c.high, c.low, c.close, c.volume, c.created_at, c.updated_at, c.stable
+                    FROM unnest($3::int[]) AS r(minutes)
+                    INNER JOIN ohlc_candles c
+                        ON c.time >= to_timestamp(FLOOR(EXTRACT(EPOCH FROM $1::timestamptz)::BIGINT / (r.minutes * 60)) * r.minutes * 60)
+                        AND c.time < to_timestamp((FLOOR(EXTRACT(EPOCH FROM $2::timestamptz)::BIGINT / (r.minutes * 60)) + 1) * r.minutes * 60)
+                ) bucketed
+                GROUP BY minutes, bucket_time
+                HAVING BOOL_AND(stable) AND bucket_time + make_interval(mins => minutes) <= NOW()
+            "#,
+            from,
+            to,
+            &resolutions
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -158,6 +293,9 @@ impl OhlcCandlesRepository for PgOhlcCandlesRepo {
             .await
             .map_err(DbError::Query)?;
 
+        let period_end = new_candles.first().expect("not empty").time();
+        Self::refresh_consolidated_candles(&mut tx, period_start, period_end).await?;
+
         tx.commit().await.map_err(DbError::TransactionCommit)?;
 
         Ok(())
@@ -186,14 +324,9 @@ impl OhlcCandlesRepository for PgOhlcCandlesRepo {
         Ok(rows)
     }
 
-    // Candles are aggregated using:
-    // - First open price in the period
-    // - Maximum high price
-    // - Minimum low price
-    // - Last close price in the period
-    // - Sum of volumes
-    // - `stable` is true only if all constituent candles are stable AND the candle's time period
-    //   has fully elapsed (bucket_time + resolution <= to)
+    // Stable buckets are read from the materialized `ohlc_candles_consolidated` table. Any bucket
+    // missing from it (the unstable tail, or regions around gaps) is aggregated on the fly from
+    // the 1-minute candles.
     async fn get_candles_consolidated(
         &self,
         from: DateTime<Utc>,
@@ -204,123 +337,49 @@ impl OhlcCandlesRepository for PgOhlcCandlesRepo {
             return self.get_candles(from, to).await;
         }
 
-        let rows = match resolution {
-            OhlcResolution::OneMinute => unreachable!("handled above"),
-
-            // Sub-hourly: bucket by minutes using epoch-based calculation
-            OhlcResolution::ThreeMinutes
-            | OhlcResolution::FiveMinutes
-            | OhlcResolution::TenMinutes
-            | OhlcResolution::FifteenMinutes
-            | OhlcResolution::ThirtyMinutes
-            | OhlcResolution::FortyFiveMinutes => {
-                let resolution_seconds = resolution.as_seconds() as i64;
-                sqlx::query_as!(
-                    OhlcCandleRow,
-                    r#"
-                        WITH bucketed AS (
-                            SELECT
-                                to_timestamp(FLOOR(EXTRACT(EPOCH FROM time)::BIGINT / $3) * $3) AS bucket_time,
-                                open, high, low, close, volume, created_at, updated_at, stable, time
-                            FROM ohlc_candles
-                            WHERE time >= $1 AND time <= $2
-                        )
-                        SELECT
-                            bucket_time AS "time!",
-                            (array_agg(open ORDER BY time ASC))[1] AS "open!",
-                            MAX(high) AS "high!",
-                            MIN(low) AS "low!",
-                            (array_agg(close ORDER BY time DESC))[1] AS "close!",
-                            SUM(volume)::BIGINT AS "volume!",
-                            MIN(created_at) AS "created_at!",
-                            MAX(updated_at) AS "updated_at!",
-                            BOOL_AND(stable) AND (bucket_time + $3 * INTERVAL '1 second') <= $2 AS "stable!"
-                        FROM bucketed
-                        GROUP BY bucket_time
-                        ORDER BY bucket_time ASC
-                    "#,
-                    from,
-                    to,
-                    resolution_seconds
-                )
-                .fetch_all(self.pool())
-                .await
-                .map_err(DbError::Query)?
-            }
+        let resolution_minutes = resolution.as_minutes() as i32;
+        let materialized = sqlx::query_as!(
+            OhlcCandleRow,
+            r#"
+                SELECT time, open, high, low, close, volume, created_at, updated_at, true AS "stable!"
+                FROM ohlc_candles_consolidated
+                WHERE resolution = $1
+                AND time >= $2
+                AND time + make_interval(mins => $1) <= $3
+                ORDER BY time ASC
+            "#,
+            resolution_minutes,
+            from,
+            to
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
 
-            // Hourly resolutions: bucket by hours using epoch-based calculation
-            OhlcResolution::OneHour
-            | OhlcResolution::TwoHours
-            | OhlcResolution::ThreeHours
-            | OhlcResolution::FourHours => {
-                let resolution_seconds = resolution.as_seconds() as i64;
-                sqlx::query_as!(
-                    OhlcCandleRow,
-                    r#"
-                        WITH bucketed AS (
-                            SELECT
-                                to_timestamp(FLOOR(EXTRACT(EPOCH FROM time)::BIGINT / $3) * $3) AS bucket_time,
-                                open, high, low, close, volume, created_at, updated_at, stable, time
-                            FROM ohlc_candles
-                            WHERE time >= $1 AND time <= $2
-                        )
-                        SELECT
-                            bucket_time AS "time!",
-                            (array_agg(open ORDER BY time ASC))[1] AS "open!",
-                            MAX(high) AS "high!",
-                            MIN(low) AS "low!",
-                            (array_agg(close ORDER BY time DESC))[1] AS "close!",
-                            SUM(volume)::BIGINT AS "volume!",
-                            MIN(created_at) AS "created_at!",
-                            MAX(updated_at) AS "updated_at!",
-                            BOOL_AND(stable) AND (bucket_time + $3 * INTERVAL '1 second') <= $2 AS "stable!"
-                        FROM bucketed
-                        GROUP BY bucket_time
-                        ORDER BY bucket_time ASC
-                    "#,
-                    from,
-                    to,
-                    resolution_seconds
-                )
-                .fetch_all(self.pool())
-                .await
-                .map_err(DbError::Query)?
+        let bucket_duration = Duration::minutes(resolution.as_minutes() as i64);
+        let mut candles = Vec::with_capacity(materialized.len() + 1);
+        let mut cursor = from;
+
+        for candle in materialized {
+            if candle.time > cursor {
+                let missing = self
+                    .aggregate_candles(cursor, candle.time, to, resolution)
+                    .await?;
+                candles.extend(missing);
             }
 
-            // Daily: bucket by day
-            OhlcResolution::OneDay => sqlx::query_as!(
-                OhlcCandleRow,
-                r#"
-                    WITH bucketed AS (
-                        SELECT
-                            date_trunc('day', time) AS bucket_time,
-                            open, high, low, close, volume, created_at, updated_at, stable, time
-                        FROM ohlc_candles
-                        WHERE time >= $1 AND time <= $2
-                    )
-                    SELECT
-                        bucket_time AS "time!",
-                        (array_agg(open ORDER BY time ASC))[1] AS "open!",
-                        MAX(high) AS "high!",
-                        MIN(low) AS "low!",
-                        (array_agg(close ORDER BY time DESC))[1] AS "close!",
-                        SUM(volume)::BIGINT AS "volume!",
-                        MIN(created_at) AS "created_at!",
-                        MAX(updated_at) AS "updated_at!",
-                        BOOL_AND(stable) AND (bucket_time + INTERVAL '1 day') <= $2 AS "stable!"
-                    FROM bucketed
-                    GROUP BY bucket_time
-                    ORDER BY bucket_time ASC
-                "#,
-                from,
-                to
-            )
-            .fetch_all(self.pool())
-            .await
-            .map_err(DbError::Query)?,
-        };
+            cursor = candle.time + bucket_duration;
+            candles.push(candle);
+        }
 
-        Ok(rows)
+        if cursor <= to {
+            let tail = self
+                .aggregate_candles(cursor, to + Duration::microseconds(1), to, resolution)
+                .await?;
+            candles.extend(tail);
+        }
+
+        Ok(candles)
     }
 
     async fn remove_gap_flag(&self, time: DateTime<Utc>) -> Result<()> {
@@ -573,6 +632,25 @@ impl OhlcCandlesRepository for PgOhlcCandlesRepo {
         .await
         .map_err(DbError::Query)?;
 
+        // Drop materialized buckets that now contain unstable candles. They will be aggregated on
+        // the fly until their candles are re-fetched.
+        sqlx::query!(
+            r#"
+                DELETE FROM ohlc_candles_consolidated c
+                WHERE c.time >= $1::timestamptz - INTERVAL '1 day'
+                AND EXISTS (
+                    SELECT 1 FROM ohlc_candles
+                    WHERE time >= c.time
+                    AND time < c.time + make_interval(mins => c.resolution)
+                    AND stable = false
+                )
+            "#,
+            cutoff_time
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(DbError::Query)?;
+
         tx.commit().await.map_err(DbError::TransactionCommit)?;
 
         Ok(())
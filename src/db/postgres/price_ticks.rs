@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use sqlx::{Pool, Postgres};
 
 use lnm_sdk::rest::v3::models::LastPrice;
@@ -201,4 +201,38 @@ impl PriceTicksRepository for PgPriceTicksRepo {
 
         Ok(())
     }
+
+    async fn downsample_ticks(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: Duration,
+    ) -> Result<()> {
+        let interval_secs = interval.as_seconds_f64();
+
+        sqlx::query!(
+            r#"
+                DELETE FROM price_ticks p
+                USING (
+                    SELECT
+                        time,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY FLOOR(EXTRACT(EPOCH FROM time)::float8 / $3)
+                            ORDER BY time DESC
+                        ) AS rank
+                    FROM price_ticks
+                    WHERE time >= $1 AND time < $2
+                ) ranked
+                WHERE p.time = ranked.time AND ranked.rank > 1
+            "#,
+            from,
+            to,
+            interval_secs
+        )
+        .execute(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
 }
@@ -1,6 +1,7 @@
 use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
@@ -92,6 +93,25 @@ impl RunningTradesRepository for PgRunningTradesRepo {
         Ok(running_trades_map)
     }
 
+    async fn get_earliest_registration_time(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>> {
+        let created_at = sqlx::query_scalar!(
+            r#"
+                SELECT MIN(created_at)
+                FROM running_trades
+                WHERE account_id = $1
+            "#,
+            account_id,
+        )
+        .fetch_one(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(created_at)
+    }
+
     async fn remove_running_trades(&self, account_id: Uuid, trade_ids: &[Uuid]) -> Result<()> {
         sqlx::query!(
             "DELETE FROM running_trades WHERE account_id = $1 AND trade_id = ANY($2)",
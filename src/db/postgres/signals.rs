@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use super::super::{
+    error::{DbError, Result},
+    models::{NewSignal, SignalRow},
+    repositories::SignalsRepository,
+};
+
+pub(crate) struct PgSignalsRepo {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl PgSignalsRepo {
+    pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
+        Self { pool }
+    }
+
+    fn pool(&self) -> &Pool<Postgres> {
+        self.pool.as_ref()
+    }
+}
+
+#[async_trait]
+impl SignalsRepository for PgSignalsRepo {
+    async fn add_signals(&self, run: &str, signals: &[NewSignal]) -> Result<()> {
+        if signals.is_empty() {
+            return Ok(());
+        }
+
+        let mut evaluators = Vec::with_capacity(signals.len());
+        let mut times = Vec::with_capacity(signals.len());
+        let mut texts = Vec::with_capacity(signals.len());
+        let mut payloads = Vec::with_capacity(signals.len());
+
+        for signal in signals {
+            evaluators.push(signal.evaluator.clone());
+            times.push(signal.time);
+            texts.push(signal.signal.clone());
+            payloads.push(signal.payload.clone());
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO signals (run, evaluator, time, signal, payload)
+                SELECT $1, s.evaluator, s.time, s.signal, s.payload::jsonb
+                FROM unnest($2::text[], $3::timestamptz[], $4::text[], $5::text[])
+                    AS s(evaluator, time, signal, payload)
+            "#,
+            run,
+            &evaluators,
+            &times,
+            &texts,
+            &payloads as &[Option<String>],
+        )
+        .execute(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(())
+    }
+
+    async fn get_signals(
+        &self,
+        run: &str,
+        evaluator: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<SignalRow>> {
+        let rows = sqlx::query_as!(
+            SignalRow,
+            r#"
+                SELECT id, run, evaluator, time, signal, payload::text AS payload, created_at
+                FROM signals
+                WHERE run = $1
+                    AND ($2::text IS NULL OR evaluator = $2)
+                    AND time >= $3 AND time <= $4
+                ORDER BY time ASC, id ASC
+            "#,
+            run,
+            evaluator,
+            from,
+            to
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(rows)
+    }
+
+    async fn get_latest_signals(&self, run: &str, time: DateTime<Utc>) -> Result<Vec<SignalRow>> {
+        let rows = sqlx::query_as!(
+            SignalRow,
+            r#"
+                SELECT DISTINCT ON (evaluator)
+                    id, run, evaluator, time, signal, payload::text AS payload, created_at
+                FROM signals
+                WHERE run = $1 AND time <= $2
+                ORDER BY evaluator ASC, time DESC, id DESC
+            "#,
+            run,
+            time
+        )
+        .fetch_all(self.pool())
+        .await
+        .map_err(DbError::Query)?;
+
+        Ok(rows)
+    }
+}
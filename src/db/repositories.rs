@@ -4,13 +4,16 @@ use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 
-use lnm_sdk::rest::v3::models::{FundingSettlement, LastPrice, OhlcCandle};
+use lnm_sdk::rest::v3::models::{FundingSettlement, LastPrice, OhlcCandle, Trade};
 
 use crate::{shared::OhlcResolution, trade::TradeTrailingStoploss};
 
 use super::{
     error::Result,
-    models::{FundingSettlementRow, OhlcCandleRow, PriceTickRow},
+    models::{
+        ClosedTradeRow, ExecutorActionRow, FundingSettlementRow, NewSignal, OhlcCandleRow,
+        PriceTickRow, SignalRow,
+    },
 };
 
 #[async_trait]
@@ -29,6 +32,15 @@ pub(crate) trait PriceTicksRepository: Send + Sync {
     ) -> Result<Option<(f64, f64, DateTime<Utc>, f64)>>;
 
     async fn remove_ticks(&self, before: DateTime<Utc>) -> Result<()>;
+
+    /// Downsamples the ticks with `from <= time < to`, keeping only the last tick of each
+    /// `interval` bucket.
+    async fn downsample_ticks(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        interval: Duration,
+    ) -> Result<()>;
 }
 
 #[async_trait]
@@ -45,9 +57,86 @@ pub(crate) trait RunningTradesRepository: Send + Sync {
         account_id: Uuid,
     ) -> Result<HashMap<Uuid, Option<TradeTrailingStoploss>>>;
 
+    /// Returns the registration time of the earliest running trade of the account.
+    async fn get_earliest_registration_time(
+        &self,
+        account_id: Uuid,
+    ) -> Result<Option<DateTime<Utc>>>;
+
     async fn remove_running_trades(&self, account_id: Uuid, trade_ids: &[Uuid]) -> Result<()>;
 }
 
+#[async_trait]
+pub(crate) trait ExecutorActionsRepository: Send + Sync {
+    /// Appends an executor action to the audit log. Exactly one of `response` and `error` must be
+    /// set. `shadow` marks actions intercepted by a shadow executor instead of being sent.
+    #[allow(clippy::too_many_arguments)]
+    async fn add_action(
+        &self,
+        account_id: Uuid,
+        kind: &str,
+        shadow: bool,
+        request: &str,
+        response: Option<&str>,
+        error: Option<&str>,
+        latency: Duration,
+        requested_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Retrieves the actions of the account requested within the specified time range, ordered by
+    /// request time ASC.
+    async fn get_actions(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<ExecutorActionRow>>;
+}
+
+#[async_trait]
+pub(crate) trait ClosedTradesRepository: Send + Sync {
+    /// Adds closed trades of the account to the database. Trades without a closing time are
+    /// skipped. Idempotent.
+    async fn add_closed_trades(&self, account_id: Uuid, trades: &[Trade]) -> Result<()>;
+
+    /// Retrieves the trades of the account closed at or after `from`, ordered by closing time ASC.
+    async fn get_closed_trades(
+        &self,
+        account_id: Uuid,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<ClosedTradeRow>>;
+}
+
+#[async_trait]
+pub(crate) trait SignalsRepository: Send + Sync {
+    /// Adds signals emitted in the given run to the database in a single batch operation.
+    async fn add_signals(&self, run: &str, signals: &[NewSignal]) -> Result<()>;
+
+    /// Retrieves the signals of the run emitted within the specified time range, optionally
+    /// filtered by evaluator, ordered by time ASC.
+    async fn get_signals(
+        &self,
+        run: &str,
+        evaluator: Option<&str>,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<SignalRow>>;
+
+    /// Retrieves the latest signal of each evaluator of the run emitted at or before `time`,
+    /// ordered by evaluator name.
+    async fn get_latest_signals(&self, run: &str, time: DateTime<Utc>) -> Result<Vec<SignalRow>>;
+}
+
+#[async_trait]
+pub(crate) trait KeyValuesRepository: Send + Sync {
+    async fn get_value(&self, key: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Inserts or replaces the value stored under `key`.
+    async fn set_value(&self, key: &str, value: &[u8]) -> Result<()>;
+
+    async fn remove_value(&self, key: &str) -> Result<()>;
+}
+
 #[async_trait]
 pub(crate) trait OhlcCandlesRepository: Send + Sync {
     /// Adds OHLC candles to the database, distinguishing between stable and unstable candles.
@@ -96,6 +185,14 @@ pub(crate) trait FundingSettlementsRepository: Send + Sync {
         to: DateTime<Utc>,
     ) -> Result<Vec<FundingSettlementRow>>;
 
+    /// Retrieves the settlements on the `count` most recent funding settlement grid points at or
+    /// before `to`, ordered by time ASC. Missing settlements are not replaced by older ones.
+    async fn get_latest_settlements(
+        &self,
+        to: DateTime<Utc>,
+        count: u32,
+    ) -> Result<Vec<FundingSettlementRow>>;
+
     /// Returns the earliest settlement time in the database.
     async fn get_earliest_settlement_time(&self) -> Result<Option<DateTime<Utc>>>;
 
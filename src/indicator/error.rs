@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+use crate::shared::Period;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum IndicatorError {
+    #[error("MACD fast period {fast} must be shorter than slow period {slow}")]
+    InvalidMacdPeriods { fast: Period, slow: Period },
+
+    #[error("Band multiplier must be a positive finite number. Value: {0}")]
+    InvalidBandMultiplier(f64),
+}
@@ -0,0 +1,280 @@
+use std::collections::VecDeque;
+
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+pub(crate) mod error;
+mod momentum;
+mod moving_average;
+mod trend;
+mod volatility;
+mod volume;
+
+pub use momentum::{Macd, MacdOutput, Rsi, Stochastic, StochasticOutput, macd, rsi, stochastic};
+pub use moving_average::{Ema, Sma, Wma, ema, sma, wma};
+pub use trend::{Adx, AdxOutput, adx};
+pub use volatility::{
+    Atr, Bands, BollingerBands, DonchianChannels, atr, bollinger_bands, donchian,
+};
+pub use volume::{Vwap, vwap};
+
+/// Technical indicator updated incrementally, one candle at a time.
+///
+/// Indicators can be kept as state between evaluations and fed only the newly closed candles, or
+/// created on the fly and fed the whole candle slice received by
+/// [`SignalEvaluator::evaluate`](crate::signal::SignalEvaluator::evaluate) or
+/// [`RawOperator::iterate`](crate::trade::RawOperator::iterate):
+///
+/// ```
+/// use quantoxide::{
+///     indicator::{Indicator, Rsi},
+///     models::{OhlcCandleRow, Period},
+/// };
+///
+/// fn is_oversold(candles: &[OhlcCandleRow]) -> bool {
+///     let period = Period::try_from(14).expect("valid period");
+///
+///     Rsi::new(period)
+///         .extend(candles)
+///         .is_some_and(|rsi| rsi < 30.)
+/// }
+/// ```
+pub trait Indicator {
+    /// Value produced by the indicator.
+    type Output: Copy;
+
+    /// Updates the indicator with a new candle, returning the latest value, or `None` if not
+    /// enough candles were provided yet.
+    ///
+    /// Candles must be provided in chronological order, each one exactly once.
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<Self::Output>;
+
+    /// Returns the latest value, or `None` if not enough candles were provided yet.
+    fn value(&self) -> Option<Self::Output>;
+
+    /// Resets the indicator to its initial state.
+    fn reset(&mut self);
+
+    /// Updates the indicator with the given candles, returning the latest value.
+    fn extend(&mut self, candles: &[OhlcCandleRow]) -> Option<Self::Output> {
+        for candle in candles {
+            self.update(candle);
+        }
+
+        self.value()
+    }
+
+    /// Updates the indicator with the given candles, returning the value after each candle.
+    fn batch(&mut self, candles: &[OhlcCandleRow]) -> Vec<Option<Self::Output>> {
+        candles.iter().map(|candle| self.update(candle)).collect()
+    }
+}
+
+/// Candle price used as input by single-series indicators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CandleSource {
+    /// Opening price.
+    Open,
+    /// Highest price.
+    High,
+    /// Lowest price.
+    Low,
+    /// Closing price.
+    #[default]
+    Close,
+    /// Typical price, the average of the high, low and closing prices.
+    Typical,
+}
+
+impl CandleSource {
+    /// Returns the price of the candle corresponding to this source.
+    pub fn price(&self, candle: &OhlcCandleRow) -> f64 {
+        match self {
+            Self::Open => candle.open,
+            Self::High => candle.high,
+            Self::Low => candle.low,
+            Self::Close => candle.close,
+            Self::Typical => typical_price(candle),
+        }
+    }
+}
+
+fn typical_price(candle: &OhlcCandleRow) -> f64 {
+    (candle.high + candle.low + candle.close) / 3.
+}
+
+/// Returns the true range of a candle, given the closing price of the previous candle, if any.
+fn true_range(candle: &OhlcCandleRow, prev_close: Option<f64>) -> f64 {
+    let range = candle.high - candle.low;
+
+    match prev_close {
+        Some(prev_close) => range
+            .max((candle.high - prev_close).abs())
+            .max((candle.low - prev_close).abs()),
+        None => range,
+    }
+}
+
+/// Fixed-size window over the most recent values of a series, keeping their running sum.
+#[derive(Debug, Clone)]
+struct Window {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Window {
+    fn new(period: Period) -> Self {
+        Self {
+            period: period.as_usize(),
+            values: VecDeque::with_capacity(period.as_usize()),
+            sum: 0.,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if self.values.len() == self.period
+            && let Some(oldest) = self.values.pop_front()
+        {
+            self.sum -= oldest;
+        }
+
+        self.values.push_back(value);
+        self.sum += value;
+    }
+
+    fn is_full(&self) -> bool {
+        self.values.len() == self.period
+    }
+
+    fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    fn mean(&self) -> f64 {
+        self.sum / self.values.len() as f64
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &f64> {
+        self.values.iter()
+    }
+
+    fn max(&self) -> f64 {
+        self.values.iter().copied().fold(f64::MIN, f64::max)
+    }
+
+    fn min(&self) -> f64 {
+        self.values.iter().copied().fold(f64::MAX, f64::min)
+    }
+
+    fn clear(&mut self) {
+        self.values.clear();
+        self.sum = 0.;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration};
+
+    use super::*;
+
+    /// Open, high, low, close and volume of the candles used to verify the indicators. Reference
+    /// values were computed independently from the textbook definitions of each indicator.
+    const OHLCV: [(f64, f64, f64, f64, i64); 40] = [
+        (98.0, 101.0, 95.0, 100.0, 1000),
+        (101.26, 105.7, 99.18, 103.02, 1037),
+        (104.67, 108.57, 102.84, 105.75, 1074),
+        (107.79, 109.21, 104.81, 107.93, 1111),
+        (110.21, 112.72, 107.07, 109.38, 1148),
+        (111.56, 114.48, 108.39, 109.96, 1185),
+        (111.65, 113.21, 106.75, 109.67, 1222),
+        (110.45, 112.76, 106.07, 108.58, 1259),
+        (108.17, 111.15, 105.57, 106.86, 1296),
+        (105.15, 106.97, 101.91, 104.73, 1333),
+        (101.91, 104.57, 99.23, 102.48, 1370),
+        (98.97, 103.39, 97.96, 100.39, 1407),
+        (96.83, 100.82, 94.14, 98.75, 1444),
+        (95.82, 99.61, 93.01, 97.77, 1481),
+        (96.1, 100.59, 94.83, 97.61, 1018),
+        (97.64, 100.63, 95.12, 98.33, 1055),
+        (100.18, 101.76, 96.97, 99.89, 1092),
+        (103.37, 106.29, 100.62, 102.17, 1129),
+        (106.78, 109.28, 102.64, 104.96, 1166),
+        (109.99, 111.29, 105.02, 108.0, 1203),
+        (112.67, 115.5, 109.17, 110.99, 1240),
+        (114.61, 117.28, 111.56, 113.66, 1277),
+        (115.73, 116.76, 112.73, 115.74, 1314),
+        (116.09, 119.75, 114.02, 117.06, 1351),
+        (115.82, 120.32, 113.97, 117.51, 1388),
+        (115.1, 118.36, 112.12, 117.1, 1425),
+        (114.09, 118.43, 111.8, 115.9, 1462),
+        (112.91, 117.01, 111.33, 114.1, 1499),
+        (111.66, 113.47, 108.73, 111.93, 1036),
+        (110.39, 112.72, 107.18, 109.68, 1073),
+        (109.17, 112.15, 106.34, 107.65, 1110),
+        (108.05, 109.86, 103.26, 106.09, 1147),
+        (107.15, 109.25, 102.56, 105.23, 1184),
+        (106.6, 109.6, 104.17, 105.2, 1221),
+        (106.6, 108.66, 103.35, 106.05, 1258),
+        (107.29, 109.59, 104.48, 107.73, 1295),
+        (108.79, 113.09, 107.53, 110.11, 1332),
+        (111.07, 115.24, 108.54, 112.95, 1369),
+        (114.02, 117.59, 111.11, 116.0, 1406),
+        (117.37, 121.89, 115.84, 118.96, 1443),
+    ];
+
+    pub(super) fn candles() -> Vec<OhlcCandleRow> {
+        let start = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+
+        OHLCV
+            .iter()
+            .enumerate()
+            .map(|(i, &(open, high, low, close, volume))| {
+                let time = start + Duration::minutes(i as i64);
+                let mut candle = OhlcCandleRow::new_simple(time, close, volume);
+                candle.open = open;
+                candle.high = high;
+                candle.low = low;
+                candle
+            })
+            .collect()
+    }
+
+    pub(super) fn period(value: u32) -> Period {
+        Period::try_from(value).unwrap()
+    }
+
+    pub(super) fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    /// Returns the index of the first candle for which the indicator produced a value.
+    pub(super) fn first_index<T>(values: &[Option<T>]) -> usize {
+        values.iter().position(Option::is_some).unwrap()
+    }
+
+    #[test]
+    fn test_streaming_matches_batch() {
+        let candles = candles();
+        let mut streaming = Sma::new(period(10));
+
+        let batch = sma(&candles, period(10));
+        for (candle, expected) in candles.iter().zip(batch) {
+            assert_eq!(streaming.update(candle), expected);
+        }
+
+        streaming.reset();
+        assert_eq!(streaming.value(), None);
+        assert_eq!(
+            streaming.extend(&candles[..20]),
+            sma(&candles[..20], period(10))[19]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+use super::{
+    CandleSource, Indicator, Window,
+    error::IndicatorError,
+    moving_average::{Ema, Sma},
+};
+
+/// Relative Strength Index, using Wilder's smoothing of average gains and losses. Ranges from `0`
+/// to `100`.
+///
+/// The first value is produced after `period + 1` candles.
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    source: CandleSource,
+    period: usize,
+    prev_price: Option<f64>,
+    seed_len: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+    value: Option<f64>,
+}
+
+impl Rsi {
+    /// Creates a new RSI over closing prices.
+    pub fn new(period: Period) -> Self {
+        Self {
+            source: CandleSource::Close,
+            period: period.as_usize(),
+            prev_price: None,
+            seed_len: 0,
+            avg_gain: 0.,
+            avg_loss: 0.,
+            value: None,
+        }
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        let price = self.source.price(candle);
+
+        let prev_price = self.prev_price.replace(price)?;
+
+        let change = price - prev_price;
+        let gain = change.max(0.);
+        let loss = (-change).max(0.);
+        let period = self.period as f64;
+
+        if self.seed_len < self.period {
+            self.seed_len += 1;
+            self.avg_gain += gain / period;
+            self.avg_loss += loss / period;
+
+            if self.seed_len < self.period {
+                return None;
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (period - 1.) + gain) / period;
+            self.avg_loss = (self.avg_loss * (period - 1.) + loss) / period;
+        }
+
+        let rsi = if self.avg_loss == 0. {
+            100.
+        } else {
+            100. - 100. / (1. + self.avg_gain / self.avg_loss)
+        };
+        self.value = Some(rsi);
+
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.prev_price = None;
+        self.seed_len = 0;
+        self.avg_gain = 0.;
+        self.avg_loss = 0.;
+        self.value = None;
+    }
+}
+
+/// Value of the [`Macd`] indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MacdOutput {
+    /// Difference between the fast and slow EMAs.
+    pub macd: f64,
+    /// EMA of the MACD line.
+    pub signal: f64,
+    /// Difference between the MACD and signal lines.
+    pub histogram: f64,
+}
+
+/// Moving Average Convergence Divergence.
+///
+/// The first value is produced once the signal line is available, after
+/// `slow_period + signal_period - 1` candles.
+#[derive(Debug, Clone)]
+pub struct Macd {
+    source: CandleSource,
+    fast: Ema,
+    slow: Ema,
+    signal: Ema,
+    value: Option<MacdOutput>,
+}
+
+impl Macd {
+    /// Creates a new MACD over closing prices. The fast period must be shorter than the slow
+    /// period.
+    pub fn new(
+        fast_period: Period,
+        slow_period: Period,
+        signal_period: Period,
+    ) -> Result<Self, IndicatorError> {
+        if fast_period >= slow_period {
+            return Err(IndicatorError::InvalidMacdPeriods {
+                fast: fast_period,
+                slow: slow_period,
+            });
+        }
+
+        Ok(Self {
+            source: CandleSource::Close,
+            fast: Ema::new(fast_period),
+            slow: Ema::new(slow_period),
+            signal: Ema::new(signal_period),
+            value: None,
+        })
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdOutput;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<MacdOutput> {
+        let price = self.source.price(candle);
+
+        let fast = self.fast.update_value(price);
+        let slow = self.slow.update_value(price);
+
+        let (Some(fast), Some(slow)) = (fast, slow) else {
+            return None;
+        };
+
+        let macd = fast - slow;
+        if let Some(signal) = self.signal.update_value(macd) {
+            self.value = Some(MacdOutput {
+                macd,
+                signal,
+                histogram: macd - signal,
+            });
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<MacdOutput> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.fast.reset();
+        self.slow.reset();
+        self.signal.reset();
+        self.value = None;
+    }
+}
+
+/// Value of the [`Stochastic`] oscillator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StochasticOutput {
+    /// Position of the closing price within the high-low range of the last `k_period` candles,
+    /// from `0` to `100`.
+    pub k: f64,
+    /// SMA of `%K` over the last `d_period` values.
+    pub d: f64,
+}
+
+/// Stochastic oscillator (`%K` and `%D`).
+///
+/// When the high-low range is empty, `%K` is `50`. The first value is produced after
+/// `k_period + d_period - 1` candles.
+#[derive(Debug, Clone)]
+pub struct Stochastic {
+    highs: Window,
+    lows: Window,
+    d: Sma,
+    value: Option<StochasticOutput>,
+}
+
+impl Stochastic {
+    /// Creates a new Stochastic oscillator.
+    pub fn new(k_period: Period, d_period: Period) -> Self {
+        Self {
+            highs: Window::new(k_period),
+            lows: Window::new(k_period),
+            d: Sma::new(d_period),
+            value: None,
+        }
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticOutput;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<StochasticOutput> {
+        self.highs.push(candle.high);
+        self.lows.push(candle.low);
+
+        if !self.highs.is_full() {
+            return None;
+        }
+
+        let highest = self.highs.max();
+        let lowest = self.lows.min();
+        let k = if highest > lowest {
+            100. * (candle.close - lowest) / (highest - lowest)
+        } else {
+            50.
+        };
+
+        if let Some(d) = self.d.update_value(k) {
+            self.value = Some(StochasticOutput { k, d });
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<StochasticOutput> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.d.reset();
+        self.value = None;
+    }
+}
+
+/// Returns the [`Rsi`] of closing prices after each candle.
+pub fn rsi(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<f64>> {
+    Rsi::new(period).batch(candles)
+}
+
+/// Returns the [`Macd`] of closing prices after each candle.
+pub fn macd(
+    candles: &[OhlcCandleRow],
+    fast_period: Period,
+    slow_period: Period,
+    signal_period: Period,
+) -> Result<Vec<Option<MacdOutput>>, IndicatorError> {
+    Ok(Macd::new(fast_period, slow_period, signal_period)?.batch(candles))
+}
+
+/// Returns the [`Stochastic`] oscillator after each candle.
+pub fn stochastic(
+    candles: &[OhlcCandleRow],
+    k_period: Period,
+    d_period: Period,
+) -> Vec<Option<StochasticOutput>> {
+    Stochastic::new(k_period, d_period).batch(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::tests::{assert_close, candles, first_index, period},
+        *,
+    };
+
+    #[test]
+    fn test_rsi() {
+        let values = rsi(&candles(), period(14));
+
+        assert_eq!(first_index(&values), 14);
+        assert_close(values[14].unwrap(), 44.64365755266696);
+        assert_close(values[39].unwrap(), 70.20028506638252);
+    }
+
+    #[test]
+    fn test_macd() {
+        let err = Macd::new(period(26), period(12), period(9)).unwrap_err();
+        assert!(matches!(err, IndicatorError::InvalidMacdPeriods { .. }));
+
+        let values = macd(&candles(), period(12), period(26), period(9)).unwrap();
+
+        assert_eq!(first_index(&values), 33);
+        let last = values[39].unwrap();
+        assert_close(last.macd, 2.255296774724485);
+        assert_close(last.signal, 2.0226352954276114);
+        assert_close(last.histogram, 0.23266147929687353);
+    }
+
+    #[test]
+    fn test_stochastic() {
+        let values = stochastic(&candles(), period(14), period(3));
+
+        assert_eq!(first_index(&values), 15);
+        let last = values[39].unwrap();
+        assert_close(last.k, 84.84221417485769);
+        assert_close(last.d, 76.01085238811667);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+use super::{CandleSource, Indicator, Window};
+
+/// Simple Moving Average, the arithmetic mean of the last `period` prices.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    source: CandleSource,
+    window: Window,
+    value: Option<f64>,
+}
+
+impl Sma {
+    /// Creates a new SMA over closing prices.
+    pub fn new(period: Period) -> Self {
+        Self {
+            source: CandleSource::Close,
+            window: Window::new(period),
+            value: None,
+        }
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub(super) fn update_value(&mut self, value: f64) -> Option<f64> {
+        self.window.push(value);
+
+        if self.window.is_full() {
+            self.value = Some(self.window.mean());
+        }
+
+        self.value
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        self.update_value(self.source.price(candle))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.value = None;
+    }
+}
+
+/// Exponential Moving Average, weighting recent prices with a smoothing factor of
+/// `2 / (period + 1)`. Seeded with the SMA of the first `period` prices.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    source: CandleSource,
+    period: usize,
+    alpha: f64,
+    seed_len: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Creates a new EMA over closing prices.
+    pub fn new(period: Period) -> Self {
+        Self {
+            source: CandleSource::Close,
+            period: period.as_usize(),
+            alpha: 2. / (period.as_f64() + 1.),
+            seed_len: 0,
+            seed_sum: 0.,
+            value: None,
+        }
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub(super) fn update_value(&mut self, value: f64) -> Option<f64> {
+        match self.value {
+            Some(ema) => self.value = Some(ema + self.alpha * (value - ema)),
+            None => {
+                self.seed_len += 1;
+                self.seed_sum += value;
+
+                if self.seed_len == self.period {
+                    self.value = Some(self.seed_sum / self.period as f64);
+                }
+            }
+        }
+
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        self.update_value(self.source.price(candle))
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.seed_len = 0;
+        self.seed_sum = 0.;
+        self.value = None;
+    }
+}
+
+/// Weighted Moving Average, weighting the last `period` prices linearly, with the most recent price
+/// having the highest weight.
+#[derive(Debug, Clone)]
+pub struct Wma {
+    source: CandleSource,
+    window: Window,
+    value: Option<f64>,
+}
+
+impl Wma {
+    /// Creates a new WMA over closing prices.
+    pub fn new(period: Period) -> Self {
+        Self {
+            source: CandleSource::Close,
+            window: Window::new(period),
+            value: None,
+        }
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for Wma {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        self.window.push(self.source.price(candle));
+
+        if self.window.is_full() {
+            let len = self.window.len() as f64;
+            let weighted_sum: f64 = self
+                .window
+                .iter()
+                .enumerate()
+                .map(|(i, value)| (i + 1) as f64 * value)
+                .sum();
+
+            self.value = Some(weighted_sum / (len * (len + 1.) / 2.));
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.value = None;
+    }
+}
+
+/// Returns the [`Sma`] of closing prices after each candle.
+pub fn sma(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<f64>> {
+    Sma::new(period).batch(candles)
+}
+
+/// Returns the [`Ema`] of closing prices after each candle.
+pub fn ema(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<f64>> {
+    Ema::new(period).batch(candles)
+}
+
+/// Returns the [`Wma`] of closing prices after each candle.
+pub fn wma(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<f64>> {
+    Wma::new(period).batch(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::tests::{assert_close, candles, first_index, period},
+        *,
+    };
+
+    #[test]
+    fn test_moving_averages() {
+        let candles = candles();
+
+        let values = sma(&candles, period(10));
+        assert_eq!(first_index(&values), 9);
+        assert_close(values[9].unwrap(), 106.588);
+        assert_close(values[39].unwrap(), 109.59700000000001);
+
+        let values = ema(&candles, period(10));
+        assert_eq!(first_index(&values), 9);
+        assert_close(values[9].unwrap(), 106.588);
+        assert_close(values[39].unwrap(), 112.05833908147657);
+
+        let values = wma(&candles, period(10));
+        assert_eq!(first_index(&values), 9);
+        assert_close(values[9].unwrap(), 107.40072727272727);
+        assert_close(values[39].unwrap(), 111.6530909090909);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+use super::{Indicator, true_range};
+
+/// Value of the [`Adx`] indicator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdxOutput {
+    /// Average Directional Index, the trend strength from `0` to `100`.
+    pub adx: f64,
+    /// Positive Directional Indicator.
+    pub plus_di: f64,
+    /// Negative Directional Indicator.
+    pub minus_di: f64,
+}
+
+/// Average Directional Index, using Wilder's smoothing of the true range, directional movements
+/// and directional index.
+///
+/// The first value is produced after `2 * period` candles, with the ADX and both directional
+/// indicators becoming available together.
+#[derive(Debug, Clone)]
+pub struct Adx {
+    period: usize,
+    prev_candle: Option<(f64, f64, f64)>,
+    dm_seed_len: usize,
+    smoothed_tr: f64,
+    smoothed_plus_dm: f64,
+    smoothed_minus_dm: f64,
+    dx_seed_len: usize,
+    dx_seed_sum: f64,
+    value: Option<AdxOutput>,
+}
+
+impl Adx {
+    /// Creates a new ADX.
+    pub fn new(period: Period) -> Self {
+        Self {
+            period: period.as_usize(),
+            prev_candle: None,
+            dm_seed_len: 0,
+            smoothed_tr: 0.,
+            smoothed_plus_dm: 0.,
+            smoothed_minus_dm: 0.,
+            dx_seed_len: 0,
+            dx_seed_sum: 0.,
+            value: None,
+        }
+    }
+}
+
+impl Indicator for Adx {
+    type Output = AdxOutput;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<AdxOutput> {
+        let (prev_high, prev_low, prev_close) =
+            self.prev_candle
+                .replace((candle.high, candle.low, candle.close))?;
+
+        let up_move = candle.high - prev_high;
+        let down_move = prev_low - candle.low;
+        let plus_dm = if up_move > down_move && up_move > 0. {
+            up_move
+        } else {
+            0.
+        };
+        let minus_dm = if down_move > up_move && down_move > 0. {
+            down_move
+        } else {
+            0.
+        };
+        let true_range = true_range(candle, Some(prev_close));
+        let period = self.period as f64;
+
+        if self.dm_seed_len < self.period {
+            self.dm_seed_len += 1;
+            self.smoothed_tr += true_range;
+            self.smoothed_plus_dm += plus_dm;
+            self.smoothed_minus_dm += minus_dm;
+
+            if self.dm_seed_len < self.period {
+                return None;
+            }
+        } else {
+            self.smoothed_tr += true_range - self.smoothed_tr / period;
+            self.smoothed_plus_dm += plus_dm - self.smoothed_plus_dm / period;
+            self.smoothed_minus_dm += minus_dm - self.smoothed_minus_dm / period;
+        }
+
+        let (plus_di, minus_di) = if self.smoothed_tr > 0. {
+            (
+                100. * self.smoothed_plus_dm / self.smoothed_tr,
+                100. * self.smoothed_minus_dm / self.smoothed_tr,
+            )
+        } else {
+            (0., 0.)
+        };
+        let di_sum = plus_di + minus_di;
+        let dx = if di_sum > 0. {
+            100. * (plus_di - minus_di).abs() / di_sum
+        } else {
+            0.
+        };
+
+        let adx = match self.value {
+            Some(prev) => (prev.adx * (period - 1.) + dx) / period,
+            None => {
+                self.dx_seed_len += 1;
+                self.dx_seed_sum += dx;
+
+                if self.dx_seed_len < self.period {
+                    return None;
+                }
+
+                self.dx_seed_sum / period
+            }
+        };
+
+        self.value = Some(AdxOutput {
+            adx,
+            plus_di,
+            minus_di,
+        });
+
+        self.value
+    }
+
+    fn value(&self) -> Option<AdxOutput> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        *self = Self {
+            period: self.period,
+            prev_candle: None,
+            dm_seed_len: 0,
+            smoothed_tr: 0.,
+            smoothed_plus_dm: 0.,
+            smoothed_minus_dm: 0.,
+            dx_seed_len: 0,
+            dx_seed_sum: 0.,
+            value: None,
+        };
+    }
+}
+
+/// Returns the [`Adx`] after each candle.
+pub fn adx(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<AdxOutput>> {
+    Adx::new(period).batch(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::tests::{assert_close, candles, first_index, period},
+        *,
+    };
+
+    #[test]
+    fn test_adx() {
+        let values = adx(&candles(), period(14));
+
+        assert_eq!(first_index(&values), 27);
+        let last = values[39].unwrap();
+        assert_close(last.adx, 21.182300673149257);
+        assert_close(last.plus_di, 22.940034932641044);
+        assert_close(last.minus_di, 10.070480993263173);
+    }
+}
@@ -0,0 +1,268 @@
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+use super::{CandleSource, Indicator, Window, error::IndicatorError, true_range};
+
+/// Average True Range, using Wilder's smoothing of the true range.
+///
+/// The true range of the first candle is its high-low range. The first value is produced after
+/// `period` candles.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    prev_close: Option<f64>,
+    seed_len: usize,
+    seed_sum: f64,
+    value: Option<f64>,
+}
+
+impl Atr {
+    /// Creates a new ATR.
+    pub fn new(period: Period) -> Self {
+        Self {
+            period: period.as_usize(),
+            prev_close: None,
+            seed_len: 0,
+            seed_sum: 0.,
+            value: None,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        let true_range = true_range(candle, self.prev_close.replace(candle.close));
+        let period = self.period as f64;
+
+        match self.value {
+            Some(atr) => self.value = Some((atr * (period - 1.) + true_range) / period),
+            None => {
+                self.seed_len += 1;
+                self.seed_sum += true_range;
+
+                if self.seed_len == self.period {
+                    self.value = Some(self.seed_sum / period);
+                }
+            }
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.prev_close = None;
+        self.seed_len = 0;
+        self.seed_sum = 0.;
+        self.value = None;
+    }
+}
+
+/// Value of band indicators ([`BollingerBands`] and [`DonchianChannels`]).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bands {
+    /// Upper band.
+    pub upper: f64,
+    /// Middle band.
+    pub middle: f64,
+    /// Lower band.
+    pub lower: f64,
+}
+
+impl Bands {
+    /// Returns the distance between the upper and lower bands.
+    pub fn width(&self) -> f64 {
+        self.upper - self.lower
+    }
+}
+
+/// Bollinger Bands, placed a multiple of the (population) standard deviation above and below the
+/// SMA of the last `period` prices.
+#[derive(Debug, Clone)]
+pub struct BollingerBands {
+    source: CandleSource,
+    multiplier: f64,
+    window: Window,
+    value: Option<Bands>,
+}
+
+impl BollingerBands {
+    /// Creates new Bollinger Bands over closing prices, placed `multiplier` standard deviations
+    /// from the middle band.
+    pub fn new(period: Period, multiplier: f64) -> Result<Self, IndicatorError> {
+        if !multiplier.is_finite() || multiplier <= 0. {
+            return Err(IndicatorError::InvalidBandMultiplier(multiplier));
+        }
+
+        Ok(Self {
+            source: CandleSource::Close,
+            multiplier,
+            window: Window::new(period),
+            value: None,
+        })
+    }
+
+    /// Sets the candle price used as input.
+    ///
+    /// Default: [`CandleSource::Close`]
+    pub fn with_source(mut self, source: CandleSource) -> Self {
+        self.source = source;
+        self
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = Bands;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<Bands> {
+        self.window.push(self.source.price(candle));
+
+        if self.window.is_full() {
+            let mean = self.window.mean();
+            let variance = self
+                .window
+                .iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>()
+                / self.window.len() as f64;
+            let offset = self.multiplier * variance.sqrt();
+
+            self.value = Some(Bands {
+                upper: mean + offset,
+                middle: mean,
+                lower: mean - offset,
+            });
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<Bands> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.window.clear();
+        self.value = None;
+    }
+}
+
+/// Donchian Channels, the highest high and lowest low of the last `period` candles. The middle
+/// band is their average.
+#[derive(Debug, Clone)]
+pub struct DonchianChannels {
+    highs: Window,
+    lows: Window,
+    value: Option<Bands>,
+}
+
+impl DonchianChannels {
+    /// Creates new Donchian Channels.
+    pub fn new(period: Period) -> Self {
+        Self {
+            highs: Window::new(period),
+            lows: Window::new(period),
+            value: None,
+        }
+    }
+}
+
+impl Indicator for DonchianChannels {
+    type Output = Bands;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<Bands> {
+        self.highs.push(candle.high);
+        self.lows.push(candle.low);
+
+        if self.highs.is_full() {
+            let upper = self.highs.max();
+            let lower = self.lows.min();
+
+            self.value = Some(Bands {
+                upper,
+                middle: (upper + lower) / 2.,
+                lower,
+            });
+        }
+
+        self.value
+    }
+
+    fn value(&self) -> Option<Bands> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        self.highs.clear();
+        self.lows.clear();
+        self.value = None;
+    }
+}
+
+/// Returns the [`Atr`] after each candle.
+pub fn atr(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<f64>> {
+    Atr::new(period).batch(candles)
+}
+
+/// Returns the [`BollingerBands`] of closing prices after each candle.
+pub fn bollinger_bands(
+    candles: &[OhlcCandleRow],
+    period: Period,
+    multiplier: f64,
+) -> Result<Vec<Option<Bands>>, IndicatorError> {
+    Ok(BollingerBands::new(period, multiplier)?.batch(candles))
+}
+
+/// Returns the [`DonchianChannels`] after each candle.
+pub fn donchian(candles: &[OhlcCandleRow], period: Period) -> Vec<Option<Bands>> {
+    DonchianChannels::new(period).batch(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::tests::{assert_close, candles, first_index, period},
+        *,
+    };
+
+    #[test]
+    fn test_atr() {
+        let values = atr(&candles(), period(14));
+
+        assert_eq!(first_index(&values), 13);
+        assert_close(values[13].unwrap(), 5.885);
+        assert_close(values[39].unwrap(), 5.950997375418395);
+    }
+
+    #[test]
+    fn test_bands() {
+        assert!(matches!(
+            BollingerBands::new(period(20), 0.),
+            Err(IndicatorError::InvalidBandMultiplier(_))
+        ));
+
+        let candles = candles();
+
+        let values = bollinger_bands(&candles, period(20), 2.).unwrap();
+        assert_eq!(first_index(&values), 19);
+        let last = values[39].unwrap();
+        assert_close(last.upper, 120.85006540345752);
+        assert_close(last.middle, 111.98200000000001);
+        assert_close(last.lower, 103.1139345965425);
+
+        let values = donchian(&candles, period(20));
+        assert_eq!(first_index(&values), 19);
+        assert_eq!(
+            values[39],
+            Some(Bands {
+                upper: 121.89,
+                middle: 112.225,
+                lower: 102.56
+            })
+        );
+    }
+}
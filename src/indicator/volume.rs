@@ -0,0 +1,124 @@
+use crate::{db::models::OhlcCandleRow, shared::Period};
+
+use super::{Indicator, Window, typical_price};
+
+/// Volume Weighted Average Price, the average of the typical price of each candle weighted by its
+/// volume.
+///
+/// Cumulative since the first candle (or the last [`reset`](Indicator::reset)) by default, or over
+/// the last `period` candles when created with [`Vwap::rolling`]. No value is produced while the
+/// accumulated volume is zero.
+#[derive(Debug, Clone)]
+pub struct Vwap {
+    weighted_prices: Option<Window>,
+    volumes: Option<Window>,
+    weighted_price_sum: f64,
+    volume_sum: f64,
+    value: Option<f64>,
+}
+
+impl Vwap {
+    /// Creates a new cumulative VWAP.
+    pub fn new() -> Self {
+        Self {
+            weighted_prices: None,
+            volumes: None,
+            weighted_price_sum: 0.,
+            volume_sum: 0.,
+            value: None,
+        }
+    }
+
+    /// Creates a new VWAP over the last `period` candles.
+    pub fn rolling(period: Period) -> Self {
+        Self {
+            weighted_prices: Some(Window::new(period)),
+            volumes: Some(Window::new(period)),
+            ..Self::new()
+        }
+    }
+}
+
+impl Default for Vwap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn update(&mut self, candle: &OhlcCandleRow) -> Option<f64> {
+        let volume = candle.volume as f64;
+        let weighted_price = typical_price(candle) * volume;
+
+        match (&mut self.weighted_prices, &mut self.volumes) {
+            (Some(weighted_prices), Some(volumes)) => {
+                weighted_prices.push(weighted_price);
+                volumes.push(volume);
+
+                if !volumes.is_full() {
+                    return None;
+                }
+
+                self.weighted_price_sum = weighted_prices.sum();
+                self.volume_sum = volumes.sum();
+            }
+            _ => {
+                self.weighted_price_sum += weighted_price;
+                self.volume_sum += volume;
+            }
+        }
+
+        self.value = (self.volume_sum > 0.).then(|| self.weighted_price_sum / self.volume_sum);
+
+        self.value
+    }
+
+    fn value(&self) -> Option<f64> {
+        self.value
+    }
+
+    fn reset(&mut self) {
+        if let Some(weighted_prices) = &mut self.weighted_prices {
+            weighted_prices.clear();
+        }
+        if let Some(volumes) = &mut self.volumes {
+            volumes.clear();
+        }
+        self.weighted_price_sum = 0.;
+        self.volume_sum = 0.;
+        self.value = None;
+    }
+}
+
+/// Returns the [`Vwap`] after each candle, cumulative or over the last `period` candles.
+pub fn vwap(candles: &[OhlcCandleRow], period: Option<Period>) -> Vec<Option<f64>> {
+    match period {
+        Some(period) => Vwap::rolling(period).batch(candles),
+        None => Vwap::new().batch(candles),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::tests::{assert_close, candles, first_index, period},
+        *,
+    };
+
+    #[test]
+    fn test_vwap() {
+        let candles = candles();
+
+        let values = vwap(&candles, None);
+        assert_eq!(first_index(&values), 0);
+        assert_close(values[0].unwrap(), 98.66666666666667);
+        assert_close(values[39].unwrap(), 108.13305354993983);
+
+        let values = vwap(&candles, Some(period(10)));
+        assert_eq!(first_index(&values), 9);
+        assert_close(values[9].unwrap(), 106.82435090727246);
+        assert_close(values[39].unwrap(), 109.95443478260869);
+    }
+}
@@ -1,6 +1,10 @@
 #![doc = include_str!("../README.md")]
 
 mod db;
+/// Exports the [`Indicator`] trait and built-in technical indicators computed over OHLC candles.
+///
+/// [`Indicator`]: crate::indicator::Indicator
+pub mod indicator;
 mod shared;
 /// Exports [`SignalEvaluator`] and other types related to signal evaluation.
 ///
@@ -10,6 +14,12 @@ pub mod signal;
 ///
 /// [`SyncEngine`]: crate::sync::SyncEngine
 pub mod sync;
+/// Exports [`MockLnmServer`] and other offline test doubles for the LN Markets API. Requires the
+/// `testing` feature.
+///
+/// [`MockLnmServer`]: crate::testing::MockLnmServer
+#[cfg(feature = "testing")]
+pub mod testing;
 /// Exports [`BacktestEngine`], [`LiveTradeEngine`], [`LiveTradeExecutor`], and other types related
 /// to trading execution.
 ///
@@ -26,16 +36,17 @@ pub mod trade;
 pub mod tui;
 mod util;
 
-pub use db::Database;
+pub use db::{Database, config::DatabaseConfig};
 
 /// Error types returned by `quantoxide`.
 pub mod error {
     pub use super::db::error::DbError;
+    pub use super::indicator::error::IndicatorError;
     pub use super::shared::error::{
         LookbackValidationError, MinIterationIntervalValidationError, PeriodValidationError,
     };
     pub use super::signal::{
-        error::{SignalError, SignalEvaluatorError, SignalOperatorError},
+        error::{SignalCombinatorError, SignalError, SignalEvaluatorError, SignalOperatorError},
         process::error::{
             SignalProcessError, SignalProcessFatalError, SignalProcessRecoverableError,
         },
@@ -56,12 +67,15 @@ pub mod error {
     };
     pub use super::trade::{
         backtest::error::BacktestError,
-        error::{IsolatedOrderValidationError, TradeCoreError, TradeExecutorError},
+        error::{
+            IsolatedOrderValidationError, PositionSizingError, RiskLimitError, TradeCoreError,
+            TradeExecutorError,
+        },
         live::{
             error::LiveError,
             executor::error::{
                 ExecutorActionError, ExecutorProcessFatalError, ExecutorProcessRecoverableError,
-                LiveTradeExecutorError,
+                LiveTradeExecutorError, LiveTradeExecutorWarning,
             },
             process::error::{
                 LiveProcessError, LiveProcessFatalError, LiveProcessRecoverableError,
@@ -85,8 +99,13 @@ pub mod error {
 
 /// Exports database models, shared configuration types, and selected `lnm-sdk` models.
 pub mod models {
-    pub use super::db::models::{FundingSettlementRow, OhlcCandleRow, PriceTickRow};
-    pub use super::shared::{Lookback, MinIterationInterval, OhlcResolution, Period};
+    pub use super::db::models::{
+        ClosedTradeRow, ExecutorActionRow, FundingSettlementRow, OhlcCandleRow, PriceTickRow,
+        SignalRow,
+    };
+    pub use super::shared::{
+        IterationSchedule, Lookback, LookbackCandles, MinIterationInterval, OhlcResolution, Period,
+    };
     // Re-export selected `lnm-sdk::rest::v3` models and utils for convenience
     pub use lnm_sdk::rest::v3::models::{
         ClientId, CrossExposure, CrossExposureRunning, CrossLeverage, CrossQuantity, Leverage,
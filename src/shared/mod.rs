@@ -1,6 +1,8 @@
-use std::{fmt, result::Result};
+use std::{collections::HashMap, fmt, result::Result};
 
-use chrono::Duration;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{db::models::OhlcCandleRow, util::DateTimeExt};
 
 pub mod error;
 
@@ -397,6 +399,99 @@ impl fmt::Display for MinIterationInterval {
     }
 }
 
+/// Determines when evaluators and operators are iterated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IterationSchedule {
+    /// Iterate whenever the minimum iteration interval has elapsed since the previous iteration.
+    #[default]
+    MinInterval,
+    /// Iterate once per newly closed candle of the primary [`Lookback`] resolution (or 1-minute
+    /// candles if no lookback is requested), `delay` after the candle closes. The delay gives the
+    /// sync process time to finalize the candle. Negative delays are treated as zero.
+    ///
+    /// The minimum iteration interval is ignored, and the provided candles end with the most
+    /// recently closed candle, so the in-progress candle is never included.
+    CandleClose {
+        /// Time to wait after each candle close before iterating.
+        delay: Duration,
+    },
+}
+
+impl IterationSchedule {
+    /// Returns the candle close tracker of this schedule, or `None` for
+    /// [`IterationSchedule::MinInterval`]. Candles closed up to `time` are considered handled.
+    pub(crate) fn candle_close_tracker(
+        &self,
+        lookbacks: &[Lookback],
+        time: DateTime<Utc>,
+    ) -> Option<CandleCloseTracker> {
+        match self {
+            Self::MinInterval => None,
+            Self::CandleClose { delay } => {
+                let resolution = lookbacks
+                    .first()
+                    .map_or(OhlcResolution::OneMinute, |lookback| lookback.resolution());
+
+                Some(CandleCloseTracker::new(resolution, *delay, time))
+            }
+        }
+    }
+}
+
+/// Tracks the candle closes of an [`IterationSchedule::CandleClose`] schedule.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CandleCloseTracker {
+    resolution: OhlcResolution,
+    delay: Duration,
+    last_close: DateTime<Utc>,
+}
+
+impl CandleCloseTracker {
+    fn new(resolution: OhlcResolution, delay: Duration, time: DateTime<Utc>) -> Self {
+        let mut tracker = Self {
+            resolution,
+            delay: delay.max(Duration::zero()),
+            last_close: DateTime::<Utc>::MIN_UTC,
+        };
+        tracker.last_close = tracker.latest_close(time);
+        tracker
+    }
+
+    /// Returns the close time of the latest candle that is due by `time`, accounting for the delay.
+    fn latest_close(&self, time: DateTime<Utc>) -> DateTime<Utc> {
+        (time - self.delay).floor_to_resolution(self.resolution)
+    }
+
+    /// Returns the time at which the next candle close is due.
+    pub fn next_due(&self) -> DateTime<Utc> {
+        self.last_close + Duration::seconds(self.resolution.as_seconds() as i64) + self.delay
+    }
+
+    /// Returns `true` if a new candle close is due by `time`, marking it as handled.
+    pub fn poll(&mut self, time: DateTime<Utc>) -> bool {
+        let latest_close = self.latest_close(time);
+
+        if latest_close > self.last_close {
+            self.last_close = latest_close;
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns the candles of `buffer`, of the given resolution, that closed by the latest handled
+    /// close. Candles of coarser resolutions still in progress at that time are excluded.
+    pub fn closed_candles<'a>(
+        &self,
+        resolution: OhlcResolution,
+        buffer: &'a [OhlcCandleRow],
+    ) -> &'a [OhlcCandleRow] {
+        let candle_duration = Duration::seconds(resolution.as_seconds() as i64);
+
+        &buffer[..buffer.partition_point(|candle| candle.time + candle_duration <= self.last_close)]
+    }
+}
+
 /// Historical candle data configuration specifying resolution and period.
 ///
 /// Combines resolution (candle size) and period (number of candles) into a single configuration.
@@ -448,6 +543,15 @@ impl Lookback {
     pub fn as_duration(&self) -> Duration {
         self.period.as_duration(self.resolution)
     }
+
+    /// Returns the lookback extended by one candle, so the most recent closed candles are still
+    /// available after excluding the in-progress one.
+    pub(crate) fn with_in_progress_candle(&self) -> Self {
+        Self {
+            resolution: self.resolution,
+            period: Period((self.period.0 + 1).min(Period::MAX.0)),
+        }
+    }
 }
 
 impl Default for Lookback {
@@ -464,3 +568,217 @@ impl fmt::Display for Lookback {
         write!(f, "{} x {}", self.period, self.resolution)
     }
 }
+
+/// Merges lookbacks sharing the same resolution, keeping the longest period. The order of first
+/// occurrence of each resolution is preserved.
+pub(crate) fn merge_lookbacks(lookbacks: impl IntoIterator<Item = Lookback>) -> Vec<Lookback> {
+    let mut merged: Vec<Lookback> = Vec::new();
+
+    for lookback in lookbacks {
+        match merged
+            .iter_mut()
+            .find(|existing| existing.resolution() == lookback.resolution())
+        {
+            Some(existing) => {
+                if lookback.period() > existing.period() {
+                    *existing = lookback;
+                }
+            }
+            None => merged.push(lookback),
+        }
+    }
+
+    merged
+}
+
+/// Returns the lookback covering the longest duration, if any.
+pub(crate) fn max_lookback<'a>(
+    lookbacks: impl IntoIterator<Item = &'a Lookback>,
+) -> Option<Lookback> {
+    lookbacks
+        .into_iter()
+        .max_by_key(|lookback| lookback.as_duration())
+        .copied()
+}
+
+/// Candles provided to evaluators and operators, with one chronological slice (most recent candle
+/// last) per requested [`Lookback`] resolution.
+///
+/// The slice of each resolution contains up to [`Lookback::period`] candles. The first requested
+/// lookback is the primary one, so single-lookback evaluators and operators can simply use
+/// [`primary`](Self::primary).
+#[derive(Debug, Clone, Default)]
+pub struct LookbackCandles<'a> {
+    primary: Option<OhlcResolution>,
+    candles: HashMap<OhlcResolution, &'a [OhlcCandleRow]>,
+}
+
+impl<'a> LookbackCandles<'a> {
+    /// Builds the candles of the given lookbacks from per-resolution buffers, keeping the most
+    /// recent `period` candles of each buffer.
+    pub(crate) fn from_buffers(
+        lookbacks: &[Lookback],
+        buffer: impl Fn(OhlcResolution) -> &'a [OhlcCandleRow],
+    ) -> Self {
+        let candles = lookbacks
+            .iter()
+            .map(|lookback| {
+                let buffer = buffer(lookback.resolution());
+                let start_idx = buffer.len().saturating_sub(lookback.period().as_usize());
+
+                (lookback.resolution(), &buffer[start_idx..])
+            })
+            .collect();
+
+        Self {
+            primary: lookbacks.first().map(|lookback| lookback.resolution()),
+            candles,
+        }
+    }
+
+    /// Returns the candles of the given resolution, or `None` if it wasn't requested.
+    pub fn get(&self, resolution: OhlcResolution) -> Option<&'a [OhlcCandleRow]> {
+        self.candles.get(&resolution).copied()
+    }
+
+    /// Returns the candles of the first requested lookback, or an empty slice if no lookback was
+    /// requested.
+    pub fn primary(&self) -> &'a [OhlcCandleRow] {
+        self.primary
+            .and_then(|resolution| self.get(resolution))
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over the requested resolutions and their candles.
+    pub fn iter(&self) -> impl Iterator<Item = (OhlcResolution, &'a [OhlcCandleRow])> + '_ {
+        self.candles
+            .iter()
+            .map(|(resolution, candles)| (*resolution, *candles))
+    }
+
+    /// Returns `true` if no lookback was requested.
+    pub fn is_empty(&self) -> bool {
+        self.candles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration};
+
+    use super::*;
+
+    #[test]
+    fn test_lookback_candles_from_buffers() {
+        let lookbacks = merge_lookbacks([
+            Lookback::new(OhlcResolution::FifteenMinutes, 3).unwrap(),
+            Lookback::new(OhlcResolution::OneHour, 2).unwrap(),
+            Lookback::new(OhlcResolution::FifteenMinutes, 5).unwrap(),
+        ]);
+        assert_eq!(
+            lookbacks,
+            vec![
+                Lookback::new(OhlcResolution::FifteenMinutes, 5).unwrap(),
+                Lookback::new(OhlcResolution::OneHour, 2).unwrap(),
+            ]
+        );
+
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let buffer: Vec<OhlcCandleRow> = (0..10)
+            .map(|i| OhlcCandleRow::new_simple(start + Duration::minutes(i), i as f64, 1))
+            .collect();
+
+        let candles = LookbackCandles::from_buffers(&lookbacks, |_| buffer.as_slice());
+        let closes =
+            |candles: &[OhlcCandleRow]| candles.iter().map(|c| c.close).collect::<Vec<_>>();
+        assert_eq!(closes(candles.primary()), vec![5., 6., 7., 8., 9.]);
+        assert_eq!(
+            candles.get(OhlcResolution::OneHour).map(closes),
+            Some(vec![8., 9.])
+        );
+        assert!(candles.get(OhlcResolution::OneMinute).is_none());
+
+        assert!(LookbackCandles::default().primary().is_empty());
+    }
+
+    #[test]
+    fn test_candle_close_tracker() {
+        let lookbacks = [Lookback::new(OhlcResolution::FifteenMinutes, 3).unwrap()];
+        let time = |hour: i64, min: i64, sec: i64| {
+            DateTime::from_timestamp(1_699_999_200, 0).unwrap() // 2023-11-14 22:00:00 UTC
+                + Duration::hours(hour)
+                + Duration::minutes(min)
+                + Duration::seconds(sec)
+        };
+
+        assert!(
+            IterationSchedule::MinInterval
+                .candle_close_tracker(&lookbacks, time(0, 7, 0))
+                .is_none()
+        );
+
+        let mut tracker = IterationSchedule::CandleClose {
+            delay: Duration::seconds(30),
+        }
+        .candle_close_tracker(&lookbacks, time(0, 7, 0))
+        .unwrap();
+        assert_eq!(tracker.next_due(), time(0, 15, 30));
+
+        assert!(!tracker.poll(time(0, 15, 10)));
+        assert!(tracker.poll(time(0, 15, 30)));
+        assert!(!tracker.poll(time(0, 16, 0)));
+        assert_eq!(tracker.next_due(), time(0, 30, 30));
+
+        // Missed closes are handled once
+        assert!(tracker.poll(time(1, 2, 0)));
+        assert!(!tracker.poll(time(1, 2, 5)));
+        assert_eq!(tracker.next_due(), time(1, 15, 30));
+
+        let buffer: Vec<OhlcCandleRow> = (0..6)
+            .map(|i| OhlcCandleRow::new_simple(time(0, 15 * i, 0), i as f64, 1))
+            .collect();
+        let closed = tracker.closed_candles(OhlcResolution::FifteenMinutes, &buffer);
+        assert_eq!(closed.last().map(|c| c.time), Some(time(0, 45, 0)));
+    }
+
+    #[test]
+    fn test_candle_close_tracker_multi_resolution() {
+        let lookbacks = [
+            Lookback::new(OhlcResolution::FiveMinutes, 3).unwrap(),
+            Lookback::new(OhlcResolution::FourHours, 2).unwrap(),
+        ];
+        let time = |hour: i64, min: i64| {
+            DateTime::from_timestamp(1_699_999_200, 0).unwrap() // 2023-11-14 22:00:00 UTC
+                + Duration::hours(hour)
+                + Duration::minutes(min)
+        };
+
+        let mut tracker = IterationSchedule::CandleClose {
+            delay: Duration::zero(),
+        }
+        .candle_close_tracker(&lookbacks, time(0, 0))
+        .unwrap();
+
+        // 5m candle closing at 22:10 UTC, while the 20:00-24:00 UTC 4h candle is in progress
+        assert!(tracker.poll(time(0, 10)));
+
+        let buffer_5m: Vec<OhlcCandleRow> = (0..3)
+            .map(|i| OhlcCandleRow::new_simple(time(0, 5 * i), i as f64, 1))
+            .collect();
+        let closed_5m = tracker.closed_candles(OhlcResolution::FiveMinutes, &buffer_5m);
+        assert_eq!(closed_5m.last().map(|c| c.time), Some(time(0, 5)));
+
+        let buffer_4h: Vec<OhlcCandleRow> = [-6, -2]
+            .into_iter()
+            .map(|hour| OhlcCandleRow::new_simple(time(hour, 0), 1., 1))
+            .collect();
+        let closed_4h = tracker.closed_candles(OhlcResolution::FourHours, &buffer_4h);
+        assert_eq!(closed_4h.len(), 1);
+        assert_eq!(closed_4h.last().map(|c| c.time), Some(time(-6, 0)));
+
+        // The 4h candle is included once closed
+        assert!(tracker.poll(time(2, 0)));
+        let closed_4h = tracker.closed_candles(OhlcResolution::FourHours, &buffer_4h);
+        assert_eq!(closed_4h.len(), 2);
+    }
+}
@@ -0,0 +1,639 @@
+use std::{fmt, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    db::models::{FundingSettlementRow, OhlcCandleRow},
+    error::Result,
+    shared::{IterationSchedule, Lookback, LookbackCandles, MinIterationInterval, Period},
+};
+
+use super::{
+    core::{Signal, SignalEvaluator},
+    error::SignalCombinatorError,
+};
+
+/// Market direction expressed by a signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignalDirection {
+    /// Favors long exposure.
+    Long,
+    /// Favors short exposure.
+    Short,
+    /// No directional bias.
+    Neutral,
+}
+
+impl SignalDirection {
+    /// Returns the direction as a vote: `1` for long, `-1` for short and `0` for neutral.
+    pub fn as_vote(&self) -> f64 {
+        match self {
+            Self::Long => 1.,
+            Self::Short => -1.,
+            Self::Neutral => 0.,
+        }
+    }
+}
+
+impl fmt::Display for SignalDirection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Long => write!(f, "Long"),
+            Self::Short => write!(f, "Short"),
+            Self::Neutral => write!(f, "Neutral"),
+        }
+    }
+}
+
+/// Signal types expressing a [`SignalDirection`], which can be combined with a
+/// [`SignalCombinator`].
+pub trait DirectionalSignal: Signal {
+    /// Returns the direction expressed by the signal.
+    fn direction(&self) -> SignalDirection;
+}
+
+/// Signal produced by a [`SignalCombinator`], with the combined direction and the signals of the
+/// underlying evaluators.
+#[derive(Debug, Clone)]
+pub struct CombinedSignal<S> {
+    direction: SignalDirection,
+    score: f64,
+    components: Vec<S>,
+}
+
+impl<S> CombinedSignal<S> {
+    /// Returns the combined direction, after debounce and cooldown are applied.
+    pub fn direction(&self) -> SignalDirection {
+        self.direction
+    }
+
+    /// Returns the combination score, between `-1` (all short) and `1` (all long). For sequential
+    /// confirmation, this is the fraction of confirmed steps, signed by direction.
+    pub fn score(&self) -> f64 {
+        self.score
+    }
+
+    /// Returns the signals of the underlying evaluators, in the order they were provided.
+    pub fn components(&self) -> &[S] {
+        &self.components
+    }
+}
+
+impl<S: Signal> DirectionalSignal for CombinedSignal<S> {
+    fn direction(&self) -> SignalDirection {
+        self.direction
+    }
+}
+
+impl<S: fmt::Display> fmt::Display for CombinedSignal<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (score: {:.2}) [", self.direction, self.score)?;
+        for (i, component) in self.components.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{component}")?;
+        }
+        write!(f, "]")
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum CombinationMode {
+    AllAgree,
+    Majority,
+    Weighted { threshold: f64 },
+    Sequential { window: Duration },
+}
+
+/// Evaluator entry along with its data requirements, queried once on creation.
+struct CombinedEvaluator<S: Signal> {
+    evaluator: Box<dyn SignalEvaluator<S>>,
+    weight: f64,
+    lookbacks: Vec<Lookback>,
+    funding_settlements_lookback: Option<Period>,
+    min_iteration_interval: MinIterationInterval,
+}
+
+#[derive(Debug, Default)]
+struct CombinatorState {
+    /// Confirmed steps, direction and start time of the ongoing sequential confirmation.
+    sequence: Option<(usize, SignalDirection, DateTime<Utc>)>,
+    /// Last direction before debounce, and number of consecutive evaluations it was repeated.
+    streak: Option<(SignalDirection, usize)>,
+    /// Time of the last non-neutral combined direction.
+    last_emitted: Option<DateTime<Utc>>,
+}
+
+/// [`SignalEvaluator`] combining the signals of several evaluators into a [`CombinedSignal`].
+///
+/// All underlying evaluators are evaluated every time the combinator is evaluated, each with its
+/// own lookbacks and funding settlements. The combinator requests the merged lookbacks and the
+/// shortest minimum iteration interval of the underlying evaluators.
+///
+/// Combinators are themselves [`DirectionalSignal`] producers, so they can be nested. Like other
+/// reusable evaluators, they implement `SignalEvaluator<T>` for any `T` where
+/// `CombinedSignal<S>: Into<T>`.
+///
+/// Sequential confirmation and cooldown measure time using the newest candle provided, so at
+/// least one lookback must be requested by the underlying evaluators when using them.
+pub struct SignalCombinator<S: DirectionalSignal> {
+    evaluators: Vec<CombinedEvaluator<S>>,
+    mode: CombinationMode,
+    debounce: usize,
+    cooldown: Option<Duration>,
+    iteration_schedule: IterationSchedule,
+    state: Mutex<CombinatorState>,
+}
+
+impl<S: DirectionalSignal> SignalCombinator<S> {
+    fn new(
+        evaluators: Vec<(Box<dyn SignalEvaluator<S>>, f64)>,
+        mode: CombinationMode,
+    ) -> std::result::Result<Self, SignalCombinatorError> {
+        if evaluators.is_empty() {
+            return Err(SignalCombinatorError::EmptyEvaluatorsVec);
+        }
+
+        let evaluators = evaluators
+            .into_iter()
+            .map(|(evaluator, weight)| {
+                if !weight.is_finite() || weight <= 0. {
+                    return Err(SignalCombinatorError::InvalidWeight(weight));
+                }
+
+                Ok(CombinedEvaluator {
+                    weight,
+                    lookbacks: evaluator.lookbacks(),
+                    funding_settlements_lookback: evaluator.funding_settlements_lookback(),
+                    min_iteration_interval: evaluator.min_iteration_interval(),
+                    evaluator,
+                })
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            evaluators,
+            mode,
+            debounce: 1,
+            cooldown: None,
+            iteration_schedule: IterationSchedule::MinInterval,
+            state: Mutex::new(CombinatorState::default()),
+        })
+    }
+
+    fn unweighted(
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+    ) -> Vec<(Box<dyn SignalEvaluator<S>>, f64)> {
+        evaluators
+            .into_iter()
+            .map(|evaluator| (evaluator, 1.))
+            .collect()
+    }
+
+    /// Creates a combinator signaling a direction only when all evaluators agree on it.
+    pub fn all_agree(
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+    ) -> std::result::Result<Self, SignalCombinatorError> {
+        Self::new(Self::unweighted(evaluators), CombinationMode::AllAgree)
+    }
+
+    /// Creates a combinator signaling a direction when more than half of the evaluators signal it.
+    pub fn majority(
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+    ) -> std::result::Result<Self, SignalCombinatorError> {
+        Self::new(Self::unweighted(evaluators), CombinationMode::Majority)
+    }
+
+    /// Creates a combinator computing the weighted average of the evaluators' votes (`1` for long,
+    /// `-1` for short and `0` for neutral). Long is signaled when the score is at least
+    /// `threshold`, and short when it is at most `-threshold`.
+    ///
+    /// Weights must be positive, and `threshold` must be within `(0, 1]`.
+    pub fn weighted(
+        evaluators: Vec<(Box<dyn SignalEvaluator<S>>, f64)>,
+        threshold: f64,
+    ) -> std::result::Result<Self, SignalCombinatorError> {
+        if !(threshold > 0. && threshold <= 1.) {
+            return Err(SignalCombinatorError::InvalidThreshold(threshold));
+        }
+
+        Self::new(evaluators, CombinationMode::Weighted { threshold })
+    }
+
+    /// Creates a combinator signaling a direction once the evaluators have signaled it one after
+    /// the other, in the order provided, within `window` of the first one. A step may be
+    /// confirmed in the same evaluation as the previous one. If the window expires, or the first
+    /// evaluator signals the opposite direction, the confirmation starts over.
+    pub fn sequential(
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        window: Duration,
+    ) -> std::result::Result<Self, SignalCombinatorError> {
+        if window <= Duration::zero() {
+            return Err(SignalCombinatorError::InvalidWindow(window));
+        }
+
+        Self::new(
+            Self::unweighted(evaluators),
+            CombinationMode::Sequential { window },
+        )
+    }
+
+    /// Sets the number of consecutive evaluations that must result in the same direction before
+    /// it is signaled. Until then, [`SignalDirection::Neutral`] is signaled.
+    ///
+    /// Default: `1`
+    pub fn with_debounce(mut self, evaluations: usize) -> Self {
+        self.debounce = evaluations.max(1);
+        self
+    }
+
+    /// Sets the period after a non-neutral direction is signaled during which
+    /// [`SignalDirection::Neutral`] is signaled instead.
+    ///
+    /// Default: `None`
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = Some(cooldown);
+        self
+    }
+
+    /// Sets the iteration schedule of the combinator. The schedules of the underlying evaluators
+    /// are ignored.
+    ///
+    /// Default: [`IterationSchedule::MinInterval`]
+    pub fn with_iteration_schedule(mut self, iteration_schedule: IterationSchedule) -> Self {
+        self.iteration_schedule = iteration_schedule;
+        self
+    }
+
+    /// Combines the directions of the given signals, returning the direction and score.
+    fn combine(
+        &self,
+        state: &mut CombinatorState,
+        components: &[S],
+        time: Option<DateTime<Utc>>,
+    ) -> Result<(SignalDirection, f64)> {
+        let count = components.len() as f64;
+        let mean_vote = || {
+            components
+                .iter()
+                .map(|signal| signal.direction().as_vote())
+                .sum::<f64>()
+                / count
+        };
+
+        let combined = match self.mode {
+            CombinationMode::AllAgree => {
+                let first = components[0].direction();
+                let direction = if components.iter().all(|s| s.direction() == first) {
+                    first
+                } else {
+                    SignalDirection::Neutral
+                };
+
+                (direction, mean_vote())
+            }
+            CombinationMode::Majority => {
+                let votes = |direction| {
+                    components
+                        .iter()
+                        .filter(|s| s.direction() == direction)
+                        .count() as f64
+                };
+
+                let direction = if votes(SignalDirection::Long) > count / 2. {
+                    SignalDirection::Long
+                } else if votes(SignalDirection::Short) > count / 2. {
+                    SignalDirection::Short
+                } else {
+                    SignalDirection::Neutral
+                };
+
+                (direction, mean_vote())
+            }
+            CombinationMode::Weighted { threshold } => {
+                let total_weight: f64 = self.evaluators.iter().map(|e| e.weight).sum();
+                let score = components
+                    .iter()
+                    .zip(&self.evaluators)
+                    .map(|(signal, entry)| signal.direction().as_vote() * entry.weight)
+                    .sum::<f64>()
+                    / total_weight;
+
+                let direction = if score >= threshold {
+                    SignalDirection::Long
+                } else if score <= -threshold {
+                    SignalDirection::Short
+                } else {
+                    SignalDirection::Neutral
+                };
+
+                (direction, score)
+            }
+            CombinationMode::Sequential { window } => {
+                let time = time.ok_or(SignalCombinatorError::MissingCandles)?;
+
+                if let Some((_, _, started)) = state.sequence
+                    && time > started + window
+                {
+                    state.sequence = None;
+                }
+
+                let first = components[0].direction();
+                if first != SignalDirection::Neutral
+                    && state
+                        .sequence
+                        .is_none_or(|(_, direction, _)| direction != first)
+                {
+                    state.sequence = Some((0, first, time));
+                }
+
+                let Some((mut confirmed, direction, started)) = state.sequence else {
+                    return Ok((SignalDirection::Neutral, 0.));
+                };
+
+                while confirmed < components.len() && components[confirmed].direction() == direction
+                {
+                    confirmed += 1;
+                }
+
+                let score = direction.as_vote() * confirmed as f64 / count;
+
+                if confirmed == components.len() {
+                    state.sequence = None;
+                    (direction, score)
+                } else {
+                    state.sequence = Some((confirmed, direction, started));
+                    (SignalDirection::Neutral, score)
+                }
+            }
+        };
+
+        Ok(combined)
+    }
+
+    /// Applies debounce and cooldown to the combined direction.
+    fn filter(
+        &self,
+        state: &mut CombinatorState,
+        direction: SignalDirection,
+        time: Option<DateTime<Utc>>,
+    ) -> Result<SignalDirection> {
+        let streak = match state.streak {
+            Some((last, streak)) if last == direction => streak + 1,
+            _ => 1,
+        };
+        state.streak = Some((direction, streak));
+
+        if direction == SignalDirection::Neutral || streak < self.debounce {
+            return Ok(SignalDirection::Neutral);
+        }
+
+        if let Some(cooldown) = self.cooldown {
+            let time = time.ok_or(SignalCombinatorError::MissingCandles)?;
+
+            if state
+                .last_emitted
+                .is_some_and(|last_emitted| time < last_emitted + cooldown)
+            {
+                return Ok(SignalDirection::Neutral);
+            }
+
+            state.last_emitted = Some(time);
+        }
+
+        Ok(direction)
+    }
+}
+
+#[async_trait]
+impl<S, T> SignalEvaluator<T> for SignalCombinator<S>
+where
+    S: DirectionalSignal,
+    T: Signal,
+    CombinedSignal<S>: Into<T>,
+{
+    fn lookback(&self) -> Option<Lookback> {
+        self.lookbacks().first().copied()
+    }
+
+    fn lookbacks(&self) -> Vec<Lookback> {
+        self.evaluators
+            .iter()
+            .flat_map(|entry| entry.lookbacks.iter().copied())
+            .collect()
+    }
+
+    fn min_iteration_interval(&self) -> MinIterationInterval {
+        self.evaluators
+            .iter()
+            .map(|entry| entry.min_iteration_interval)
+            .min()
+            .expect("not empty")
+    }
+
+    fn iteration_schedule(&self) -> IterationSchedule {
+        self.iteration_schedule
+    }
+
+    fn funding_settlements_lookback(&self) -> Option<Period> {
+        self.evaluators
+            .iter()
+            .filter_map(|entry| entry.funding_settlements_lookback)
+            .max()
+    }
+
+    /// Evaluates the underlying evaluators with the given candles as the primary lookback, and no
+    /// funding settlements.
+    async fn evaluate(&self, candles: &[OhlcCandleRow]) -> Result<T> {
+        let primary: Vec<Lookback> = self.lookbacks().into_iter().take(1).collect();
+        let candles = LookbackCandles::from_buffers(&primary, |_| candles);
+
+        self.evaluate_with_context(&candles, &[]).await
+    }
+
+    async fn evaluate_with_context(
+        &self,
+        candles: &LookbackCandles<'_>,
+        funding_settlements: &[FundingSettlementRow],
+    ) -> Result<T> {
+        let time = candles
+            .iter()
+            .filter_map(|(_, candles)| candles.last().map(|candle| candle.time))
+            .max();
+
+        let mut components = Vec::with_capacity(self.evaluators.len());
+
+        for entry in &self.evaluators {
+            let entry_candles = LookbackCandles::from_buffers(&entry.lookbacks, |resolution| {
+                candles.get(resolution).unwrap_or_default()
+            });
+
+            let settlements_start_idx = funding_settlements.len().saturating_sub(
+                entry
+                    .funding_settlements_lookback
+                    .map_or(0, |lookback| lookback.as_usize()),
+            );
+
+            let signal = entry
+                .evaluator
+                .evaluate_with_context(
+                    &entry_candles,
+                    &funding_settlements[settlements_start_idx..],
+                )
+                .await?;
+
+            components.push(signal);
+        }
+
+        let mut state = self.state.lock().expect("not poisoned");
+
+        let (direction, score) = self.combine(&mut state, &components, time)?;
+        let direction = self.filter(&mut state, direction, time)?;
+
+        Ok(CombinedSignal {
+            direction,
+            score,
+            components,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use crate::{db::models::OhlcCandleRow, shared::OhlcResolution};
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct TestSignal(SignalDirection);
+
+    impl fmt::Display for TestSignal {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    impl DirectionalSignal for TestSignal {
+        fn direction(&self) -> SignalDirection {
+            self.0
+        }
+    }
+
+    /// Evaluator replaying a fixed sequence of directions, one per evaluation.
+    struct ScriptedEvaluator {
+        directions: Vec<SignalDirection>,
+        cursor: Arc<AtomicUsize>,
+    }
+
+    fn scripted(directions: &[SignalDirection]) -> Box<dyn SignalEvaluator<TestSignal>> {
+        Box::new(ScriptedEvaluator {
+            directions: directions.to_vec(),
+            cursor: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    #[async_trait]
+    impl SignalEvaluator<TestSignal> for ScriptedEvaluator {
+        fn lookback(&self) -> Option<Lookback> {
+            Some(Lookback::new(OhlcResolution::OneMinute, 1).unwrap())
+        }
+
+        fn min_iteration_interval(&self) -> MinIterationInterval {
+            MinIterationInterval::MIN
+        }
+
+        async fn evaluate(&self, _candles: &[OhlcCandleRow]) -> Result<TestSignal> {
+            let idx = self.cursor.fetch_add(1, Ordering::SeqCst);
+            Ok(TestSignal(self.directions[idx]))
+        }
+    }
+
+    async fn run(combinator: SignalCombinator<TestSignal>, steps: usize) -> Vec<SignalDirection> {
+        let lookbacks = SignalEvaluator::<CombinedSignal<TestSignal>>::lookbacks(&combinator);
+        let start = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let mut directions = Vec::new();
+
+        for step in 0..steps {
+            let buffer = [OhlcCandleRow::new_simple(
+                start + Duration::minutes(step as i64),
+                100_000.,
+                1,
+            )];
+            let candles = LookbackCandles::from_buffers(&lookbacks, |_| buffer.as_slice());
+
+            let signal: CombinedSignal<TestSignal> = combinator
+                .evaluate_with_context(&candles, &[])
+                .await
+                .unwrap();
+            directions.push(signal.direction());
+        }
+
+        directions
+    }
+
+    use SignalDirection::{Long as L, Neutral as N, Short as S};
+
+    #[tokio::test]
+    async fn test_signal_combinator_modes() {
+        let all_agree =
+            SignalCombinator::all_agree(vec![scripted(&[L, L, S, N]), scripted(&[L, S, S, N])])
+                .unwrap();
+        assert_eq!(run(all_agree, 4).await, vec![L, N, S, N]);
+
+        let majority = SignalCombinator::majority(vec![
+            scripted(&[L, L, S]),
+            scripted(&[L, S, S]),
+            scripted(&[N, N, L]),
+        ])
+        .unwrap();
+        assert_eq!(run(majority, 3).await, vec![L, N, S]);
+
+        let weighted = SignalCombinator::weighted(
+            vec![(scripted(&[L, L, N]), 3.), (scripted(&[S, N, S]), 1.)],
+            0.5,
+        )
+        .unwrap();
+        assert_eq!(run(weighted, 3).await, vec![L, L, N]);
+
+        let sequential = SignalCombinator::sequential(
+            vec![
+                scripted(&[L, N, N, S, N, N, N]),
+                scripted(&[N, N, L, N, N, N, S]),
+            ],
+            Duration::minutes(2),
+        )
+        .unwrap();
+        // Long confirmed within the window, short confirmation expires
+        assert_eq!(run(sequential, 7).await, vec![N, N, L, N, N, N, N]);
+
+        assert!(matches!(
+            SignalCombinator::<TestSignal>::all_agree(Vec::new()),
+            Err(SignalCombinatorError::EmptyEvaluatorsVec)
+        ));
+        assert!(matches!(
+            SignalCombinator::weighted(vec![(scripted(&[L]), -1.)], 0.5),
+            Err(SignalCombinatorError::InvalidWeight(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_signal_combinator_debounce_and_cooldown() {
+        let debounced = SignalCombinator::all_agree(vec![scripted(&[L, L, S, S, S, N])])
+            .unwrap()
+            .with_debounce(2);
+        assert_eq!(run(debounced, 6).await, vec![N, L, N, S, S, N]);
+
+        let cooled_down = SignalCombinator::all_agree(vec![scripted(&[L, L, S, L, L])])
+            .unwrap()
+            .with_cooldown(Duration::minutes(3));
+        assert_eq!(run(cooled_down, 5).await, vec![L, N, N, L, N]);
+    }
+}
@@ -8,6 +8,7 @@ pub struct LiveSignalConfig {
     sync_update_timeout: time::Duration,
     restart_interval: time::Duration,
     shutdown_timeout: time::Duration,
+    signal_run: Option<String>,
 }
 
 impl Default for LiveSignalConfig {
@@ -16,6 +17,7 @@ impl Default for LiveSignalConfig {
             sync_update_timeout: time::Duration::from_secs(60),
             restart_interval: time::Duration::from_secs(10),
             shutdown_timeout: time::Duration::from_secs(6),
+            signal_run: None,
         }
     }
 }
@@ -36,6 +38,11 @@ impl LiveSignalConfig {
         self.shutdown_timeout
     }
 
+    /// Returns the run name under which emitted signals are persisted, if any.
+    pub fn signal_run(&self) -> Option<&str> {
+        self.signal_run.as_deref()
+    }
+
     /// Sets the timeout duration for waiting on sync status updates.
     ///
     /// Default: `5` seconds
@@ -59,6 +66,17 @@ impl LiveSignalConfig {
         self.shutdown_timeout = time::Duration::from_secs(secs);
         self
     }
+
+    /// Sets the run name under which emitted signals are persisted. When set, every signal is
+    /// stored in the database alongside its evaluator name and evaluation time, and can be
+    /// retrieved via [`Database::get_signals`](crate::db::Database::get_signals). If `None`,
+    /// signals are not persisted. Requires a database that isn't read-only.
+    ///
+    /// Default: `None`
+    pub fn with_signal_run(mut self, run: Option<String>) -> Self {
+        self.signal_run = run;
+        self
+    }
 }
 
 impl From<&LiveTradeConfig> for LiveSignalConfig {
@@ -67,6 +85,7 @@ impl From<&LiveTradeConfig> for LiveSignalConfig {
             sync_update_timeout: value.sync_update_timeout(),
             restart_interval: value.restart_interval(),
             shutdown_timeout: value.shutdown_timeout(),
+            signal_run: value.signal_run().map(str::to_string),
         }
     }
 }
@@ -94,6 +113,7 @@ impl From<&LiveSignalConfig> for LiveSignalControllerConfig {
 pub(super) struct LiveSignalProcessConfig {
     sync_update_timeout: time::Duration,
     restart_interval: time::Duration,
+    signal_run: Option<String>,
 }
 
 impl LiveSignalProcessConfig {
@@ -104,6 +124,10 @@ impl LiveSignalProcessConfig {
     pub fn restart_interval(&self) -> time::Duration {
         self.restart_interval
     }
+
+    pub fn signal_run(&self) -> Option<&str> {
+        self.signal_run.as_deref()
+    }
 }
 
 impl From<&LiveSignalConfig> for LiveSignalProcessConfig {
@@ -111,6 +135,7 @@ impl From<&LiveSignalConfig> for LiveSignalProcessConfig {
         Self {
             sync_update_timeout: value.sync_update_timeout(),
             restart_interval: value.restart_interval(),
+            signal_run: value.signal_run.clone(),
         }
     }
 }
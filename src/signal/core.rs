@@ -5,9 +5,14 @@ use std::{
 
 use async_trait::async_trait;
 use futures::FutureExt;
+use serde_json::Value;
 
 use crate::{
-    db::models::OhlcCandleRow, error::Result, shared::Lookback, shared::MinIterationInterval,
+    db::models::{FundingSettlementRow, OhlcCandleRow},
+    error::Result,
+    shared::{
+        IterationSchedule, Lookback, LookbackCandles, MinIterationInterval, Period, merge_lookbacks,
+    },
 };
 
 use super::error::{SignalEvaluatorError, SignalEvaluatorResult};
@@ -63,8 +68,9 @@ impl<T> Signal for T where T: Send + Sync + Clone + fmt::Display + 'static {}
 /// # use chrono::{DateTime, Utc};
 /// use quantoxide::{
 ///     error::Result,
+///     indicator::{Indicator, Sma},
 ///     models::{
-///         Lookback, MinIterationInterval, OhlcCandleRow, OhlcResolution
+///         Lookback, MinIterationInterval, OhlcCandleRow, OhlcResolution, Period
 ///     },
 ///     signal::{Signal, SignalEvaluator},
 /// };
@@ -85,12 +91,12 @@ impl<T> Signal for T where T: Send + Sync + Clone + fmt::Display + 'static {}
 ///
 /// // Evaluator struct is not generic, only the trait impl is
 /// pub struct MaCrossEvaluator {
-///     fast_period: usize,
-///     slow_period: usize,
+///     fast_period: Period,
+///     slow_period: Period,
 /// }
 ///
 /// impl MaCrossEvaluator {
-///     pub fn new(fast_period: usize, slow_period: usize) -> Box<Self> {
+///     pub fn new(fast_period: Period, slow_period: Period) -> Box<Self> {
 ///         Box::new(Self { fast_period, slow_period })
 ///     }
 /// }
@@ -101,7 +107,7 @@ impl<T> Signal for T where T: Send + Sync + Clone + fmt::Display + 'static {}
 ///     MaCrossSignal: Into<S>,
 /// {
 ///     fn lookback(&self) -> Option<Lookback> {
-///         Some(Lookback::new(OhlcResolution::FifteenMinutes, self.slow_period as u64)
+///         Some(Lookback::new(OhlcResolution::FifteenMinutes, self.slow_period)
 ///             .expect("valid lookback"))
 ///     }
 ///
@@ -110,10 +116,17 @@ impl<T> Signal for T where T: Send + Sync + Clone + fmt::Display + 'static {}
 ///     }
 ///
 ///     async fn evaluate(&self, candles: &[OhlcCandleRow]) -> Result<S> {
+///         let (Some(fast_ma), Some(slow_ma)) = (
+///             Sma::new(self.fast_period).extend(candles),
+///             Sma::new(self.slow_period).extend(candles),
+///         ) else {
+///             return Err("not enough candles".into());
+///         };
+///
 ///         let signal = MaCrossSignal {
-///             time: Utc::now(),
-///             fast_ma: 20.0, // Calculate actual MA
-///             slow_ma: 100.0,
+///             time: candles[candles.len() - 1].time,
+///             fast_ma,
+///             slow_ma,
 ///         };
 ///
 ///         Ok(signal.into()) // Convert to target type
@@ -126,21 +139,77 @@ pub trait SignalEvaluator<S: Signal>: Send + Sync {
     /// candle data is required.
     ///
     /// The framework uses this to fetch the appropriate historical candles before calling
-    /// [`evaluate`](Self::evaluate). When `None` is returned, an empty slice is provided to
-    /// `evaluate`.
+    /// [`evaluate`](Self::evaluate). Not used if [`lookbacks`](Self::lookbacks) is overridden.
     fn lookback(&self) -> Option<Lookback>;
 
+    /// Returns the candle resolutions and counts needed for evaluation. Default implementation
+    /// returns the [`lookback`](Self::lookback), if any.
+    ///
+    /// Multi-timeframe evaluators can override this to request several resolutions (e.g. 200
+    /// five-minute candles and 50 four-hour candles), the first one being the primary lookback.
+    /// Lookbacks sharing a resolution are merged, keeping the longest period.
+    fn lookbacks(&self) -> Vec<Lookback> {
+        self.lookback().into_iter().collect()
+    }
+
     /// Returns the minimum interval between successive evaluations.
     ///
     /// The framework will not call [`evaluate`](Self::evaluate) more frequently than this interval.
     fn min_iteration_interval(&self) -> MinIterationInterval;
 
+    /// Returns when the evaluator should be evaluated. Queried once when evaluation starts.
+    /// Default implementation returns [`IterationSchedule::MinInterval`].
+    ///
+    /// With [`IterationSchedule::CandleClose`], the evaluator is evaluated once per closed candle
+    /// of its primary lookback resolution, and
+    /// [`min_iteration_interval`](Self::min_iteration_interval) is ignored.
+    fn iteration_schedule(&self) -> IterationSchedule {
+        IterationSchedule::MinInterval
+    }
+
+    /// Returns the number of most recent funding settlements needed for evaluation, or `None` if
+    /// no funding settlement data is required. Default implementation returns `None`.
+    fn funding_settlements_lookback(&self) -> Option<Period> {
+        None
+    }
+
+    /// Returns the name identifying the evaluator in persisted signals. Default implementation
+    /// returns the evaluator's type name.
+    fn name(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Returns a structured JSON payload describing the given signal, stored alongside its
+    /// `Display` text when signals are persisted. Default implementation returns `None`.
+    fn signal_payload(&self, signal: &S) -> Option<Value> {
+        let _ = signal;
+        None
+    }
+
     /// Evaluates a series of OHLC candlesticks and returns a signal.
     ///
-    /// The candlestick slice is ordered chronologically, with the most recent candle last.
-    /// The number of candles provided is determined by the [`lookback`](Self::lookback)
-    /// configuration.
+    /// The candlestick slice is ordered chronologically, with the most recent candle last. It
+    /// contains the candles of the primary lookback, whose number is determined by the
+    /// [`lookback`](Self::lookback) configuration. Only called by the default implementation of
+    /// [`evaluate_with_context`](Self::evaluate_with_context).
     async fn evaluate(&self, candles: &[OhlcCandleRow]) -> Result<S>;
+
+    /// Evaluates OHLC candlesticks and funding settlements, and returns a signal. Default
+    /// implementation calls [`evaluate`](Self::evaluate) with the candles of the primary lookback.
+    ///
+    /// Evaluators needing several candle resolutions or funding settlements should override this.
+    /// One chronologically ordered candle slice is provided per resolution requested via
+    /// [`lookbacks`](Self::lookbacks), with the most recent candle last. Up to
+    /// [`funding_settlements_lookback`](Self::funding_settlements_lookback) funding settlements
+    /// are provided, also in chronological order.
+    async fn evaluate_with_context(
+        &self,
+        candles: &LookbackCandles<'_>,
+        funding_settlements: &[FundingSettlementRow],
+    ) -> Result<S> {
+        let _ = funding_settlements;
+        self.evaluate(candles.primary()).await
+    }
 }
 
 /// Internal wrapper that provides panic protection for signal evaluators.
@@ -151,9 +220,10 @@ impl<S: Signal> WrappedSignalEvaluator<S> {
         Self(evaluator)
     }
 
-    /// Returns the lookback configuration with panic protection.
-    pub fn lookback(&self) -> SignalEvaluatorResult<Option<Lookback>> {
-        panic::catch_unwind(AssertUnwindSafe(|| self.0.lookback()))
+    /// Returns the merged lookback configuration with panic protection.
+    pub fn lookbacks(&self) -> SignalEvaluatorResult<Vec<Lookback>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.lookbacks()))
+            .map(merge_lookbacks)
             .map_err(|e| SignalEvaluatorError::LookbackPanicked(e.into()))
     }
 
@@ -163,11 +233,41 @@ impl<S: Signal> WrappedSignalEvaluator<S> {
             .map_err(|e| SignalEvaluatorError::MinIterationIntervalPanicked(e.into()))
     }
 
-    /// Evaluates candlestick data with panic protection.
-    pub async fn evaluate(&self, candles: &[OhlcCandleRow]) -> SignalEvaluatorResult<S> {
-        FutureExt::catch_unwind(AssertUnwindSafe(self.0.evaluate(candles)))
-            .await
-            .map_err(|e| SignalEvaluatorError::EvaluatePanicked(e.into()))?
-            .map_err(|e| SignalEvaluatorError::EvaluateError(e.to_string()))
+    /// Returns the iteration schedule with panic protection.
+    pub fn iteration_schedule(&self) -> SignalEvaluatorResult<IterationSchedule> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.iteration_schedule()))
+            .map_err(|e| SignalEvaluatorError::IterationSchedulePanicked(e.into()))
+    }
+
+    /// Returns the funding settlements lookback with panic protection.
+    pub fn funding_settlements_lookback(&self) -> SignalEvaluatorResult<Option<Period>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.funding_settlements_lookback()))
+            .map_err(|e| SignalEvaluatorError::FundingSettlementsLookbackPanicked(e.into()))
+    }
+
+    /// Returns the evaluator name with panic protection.
+    pub fn name(&self) -> SignalEvaluatorResult<String> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.name()))
+            .map_err(|e| SignalEvaluatorError::NamePanicked(e.into()))
+    }
+
+    /// Returns the structured payload of a signal with panic protection.
+    pub fn signal_payload(&self, signal: &S) -> SignalEvaluatorResult<Option<Value>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.signal_payload(signal)))
+            .map_err(|e| SignalEvaluatorError::SignalPayloadPanicked(e.into()))
+    }
+
+    /// Evaluates candlestick and funding settlement data with panic protection.
+    pub async fn evaluate(
+        &self,
+        candles: &LookbackCandles<'_>,
+        funding_settlements: &[FundingSettlementRow],
+    ) -> SignalEvaluatorResult<S> {
+        FutureExt::catch_unwind(AssertUnwindSafe(
+            self.0.evaluate_with_context(candles, funding_settlements),
+        ))
+        .await
+        .map_err(|e| SignalEvaluatorError::EvaluatePanicked(e.into()))?
+        .map_err(|e| SignalEvaluatorError::EvaluateError(e.to_string()))
     }
 }
@@ -177,6 +177,7 @@ pub struct LiveSignalEngine<S: Signal> {
 
 impl<S: Signal> LiveSignalEngine<S> {
     /// Creates a new live signal engine with the specified configuration and signal evaluators.
+    /// Returns an error if a signal run is configured and the database is read-only.
     pub fn new(
         config: impl Into<LiveSignalConfig>,
         db: Arc<Database>,
@@ -189,6 +190,12 @@ impl<S: Signal> LiveSignalEngine<S> {
             ));
         }
 
+        let config: LiveSignalConfig = config.into();
+
+        if config.signal_run().is_some() && db.read_only() {
+            return Err(SignalError::ReadOnlyDatabase);
+        }
+
         let evaluators: Vec<_> = evaluators
             .into_iter()
             .map(WrappedSignalEvaluator::new)
@@ -199,7 +206,7 @@ impl<S: Signal> LiveSignalEngine<S> {
         let status_manager = LiveSignalStatusManager::new(update_tx.clone());
 
         Ok(Self {
-            config: config.into(),
+            config,
             db,
             sync_reader,
             evaluators,
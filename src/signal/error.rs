@@ -1,5 +1,6 @@
 use std::{result, sync::Arc};
 
+use chrono::Duration;
 use thiserror::Error;
 
 use crate::util::PanicPayload;
@@ -9,12 +10,24 @@ use super::{process::error::SignalProcessFatalError, state::LiveSignalStatus};
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum SignalEvaluatorError {
-    #[error("`SignalEvaluator::lookback` panicked: {0}")]
+    #[error("`SignalEvaluator::lookbacks` panicked: {0}")]
     LookbackPanicked(PanicPayload),
 
     #[error("`SignalEvaluator::min_iteration_interval` panicked: {0}")]
     MinIterationIntervalPanicked(PanicPayload),
 
+    #[error("`SignalEvaluator::iteration_schedule` panicked: {0}")]
+    IterationSchedulePanicked(PanicPayload),
+
+    #[error("`SignalEvaluator::funding_settlements_lookback` panicked: {0}")]
+    FundingSettlementsLookbackPanicked(PanicPayload),
+
+    #[error("`SignalEvaluator::name` panicked: {0}")]
+    NamePanicked(PanicPayload),
+
+    #[error("`SignalEvaluator::signal_payload` panicked: {0}")]
+    SignalPayloadPanicked(PanicPayload),
+
     #[error("`SignalEvaluator::evaluate` panicked: {0}")]
     EvaluatePanicked(PanicPayload),
 
@@ -31,6 +44,25 @@ pub enum SignalOperatorError {
     EmptyEvaluatorsVec,
 }
 
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SignalCombinatorError {
+    #[error("At least one signal evaluator must be provided")]
+    EmptyEvaluatorsVec,
+
+    #[error("Evaluator weight must be a positive finite number, got {0}")]
+    InvalidWeight(f64),
+
+    #[error("Weighted score threshold must be within (0, 1], got {0}")]
+    InvalidThreshold(f64),
+
+    #[error("Sequential confirmation window must be positive, got {0}")]
+    InvalidWindow(Duration),
+
+    #[error("Candles are required to measure time, but no lookback was requested")]
+    MissingCandles,
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum SignalError {
@@ -40,6 +72,9 @@ pub enum SignalError {
     #[error(transparent)]
     Operator(SignalOperatorError),
 
+    #[error("Database is read-only, so signals can't be persisted")]
+    ReadOnlyDatabase,
+
     #[error("Live Signal process already shutdown error")]
     LiveSignalAlreadyShutdown,
 
@@ -1,10 +1,13 @@
+mod combinator;
 mod config;
 mod core;
 mod engine;
 pub(crate) mod error;
 pub(crate) mod process;
+mod recorder;
 mod state;
 
+pub use combinator::{CombinedSignal, DirectionalSignal, SignalCombinator, SignalDirection};
 pub use config::LiveSignalConfig;
 pub use core::{Signal, SignalEvaluator};
 pub use engine::{LiveSignalController, LiveSignalEngine};
@@ -15,3 +18,4 @@ pub use state::{
 
 // Internal re-exports
 pub(crate) use core::WrappedSignalEvaluator;
+pub(crate) use recorder::SignalRecorder;
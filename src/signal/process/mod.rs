@@ -8,7 +8,7 @@ use tokio::{
 
 use crate::{
     db::{Database, models::OhlcCandleRow},
-    shared::{OhlcResolution, Period},
+    shared::{Lookback, LookbackCandles, OhlcResolution, Period},
     signal::LiveSignalReader,
     sync::{SyncReader, SyncStatus, SyncUpdate},
     util::{AbortOnDropHandle, DateTimeExt, Never},
@@ -17,6 +17,7 @@ use crate::{
 use super::{
     config::{LiveSignalConfig, LiveSignalProcessConfig},
     core::{Signal, WrappedSignalEvaluator},
+    recorder::SignalRecorder,
     state::{
         LiveSignalStatus, LiveSignalStatusManager, LiveSignalStatusNotRunning,
         LiveSignalTransmitter, LiveSignalUpdate,
@@ -29,22 +30,6 @@ use error::{
     ProcessResult, SignalProcessError, SignalProcessFatalError, SignalProcessRecoverableError,
 };
 
-/// Groups evaluators that share the same OHLC resolution.
-struct ResolutionGroup {
-    max_period: Period,
-    /// (last_eval_time, evaluator_index, period)
-    evaluators: Vec<(DateTime<Utc>, usize, Period)>,
-}
-
-impl ResolutionGroup {
-    fn new(initial_period: Period) -> Self {
-        Self {
-            max_period: initial_period,
-            evaluators: Vec::new(),
-        }
-    }
-}
-
 pub(super) struct LiveSignalProcess<S: Signal> {
     config: LiveSignalProcessConfig,
     db: Arc<Database>,
@@ -83,40 +68,62 @@ impl<S: Signal> LiveSignalProcess<S> {
         .into()
     }
 
-    async fn run(&self) -> ProcessResult<Never> {
+    async fn run(&self, signal_recorder: &mut Option<SignalRecorder>) -> ProcessResult<Never> {
         let mut min_iteration_interval = Duration::MAX;
 
-        let mut resolution_groups: HashMap<OhlcResolution, ResolutionGroup> = HashMap::new();
-        let mut no_lookback_evaluators: Vec<(DateTime<Utc>, usize)> = Vec::new();
+        let mut resolution_to_max_period: HashMap<OhlcResolution, Period> = HashMap::new();
+        let mut max_funding_settlements_lookback: Option<Period> = None;
+        // (last_eval_time, candle_close_tracker, evaluator_index, lookbacks,
+        // funding_settlements_lookback)
+        let mut evaluator_schedules = Vec::new();
 
         let now = Utc::now().ceil_sec();
         for (idx, evaluator) in self.evaluators.iter().enumerate() {
-            min_iteration_interval = min_iteration_interval.min(
-                evaluator
-                    .min_iteration_interval()
-                    .map_err(SignalProcessFatalError::Evaluator)?
-                    .as_duration(),
-            );
-
-            match evaluator
-                .lookback()
-                .map_err(SignalProcessFatalError::Evaluator)?
-            {
-                Some(lookback) => {
-                    let group = resolution_groups
-                        .entry(lookback.resolution())
-                        .or_insert_with(|| ResolutionGroup::new(lookback.period()));
+            let lookbacks = evaluator
+                .lookbacks()
+                .map_err(SignalProcessFatalError::Evaluator)?;
 
-                    if lookback.period() > group.max_period {
-                        group.max_period = lookback.period();
-                    }
+            let candle_close_tracker = evaluator
+                .iteration_schedule()
+                .map_err(SignalProcessFatalError::Evaluator)?
+                .candle_close_tracker(&lookbacks, now);
 
-                    group.evaluators.push((now, idx, lookback.period()));
+            match &candle_close_tracker {
+                Some(tracker) => {
+                    min_iteration_interval = min_iteration_interval.min(tracker.next_due() - now);
                 }
                 None => {
-                    no_lookback_evaluators.push((now, idx));
+                    min_iteration_interval = min_iteration_interval.min(
+                        evaluator
+                            .min_iteration_interval()
+                            .map_err(SignalProcessFatalError::Evaluator)?
+                            .as_duration(),
+                    );
                 }
             }
+
+            // Candle-close evaluators exclude the in-progress candle, so one extra is fetched
+            for lookback in lookbacks.iter().map(Lookback::with_in_progress_candle) {
+                resolution_to_max_period
+                    .entry(lookback.resolution())
+                    .and_modify(|max_period| *max_period = (*max_period).max(lookback.period()))
+                    .or_insert(lookback.period());
+            }
+
+            let funding_settlements_lookback = evaluator
+                .funding_settlements_lookback()
+                .map_err(SignalProcessFatalError::Evaluator)?;
+
+            max_funding_settlements_lookback =
+                max_funding_settlements_lookback.max(funding_settlements_lookback);
+
+            evaluator_schedules.push((
+                now,
+                candle_close_tracker,
+                idx,
+                lookbacks,
+                funding_settlements_lookback,
+            ));
         }
 
         let mut next_eval = now + min_iteration_interval;
@@ -210,10 +217,9 @@ impl<S: Signal> LiveSignalProcess<S> {
             let now = Utc::now();
             let mut candle_buffers: HashMap<OhlcResolution, Vec<OhlcCandleRow>> = HashMap::new();
 
-            for (resolution, group) in &resolution_groups {
+            for (resolution, max_period) in &resolution_to_max_period {
                 let current_bucket = now.floor_to_resolution(*resolution);
-                let from =
-                    current_bucket.step_back_candles(*resolution, group.max_period.as_u32() - 1);
+                let from = current_bucket.step_back_candles(*resolution, max_period.as_u32() - 1);
 
                 let candles = self
                     .db
@@ -225,71 +231,101 @@ impl<S: Signal> LiveSignalProcess<S> {
                 candle_buffers.insert(*resolution, candles);
             }
 
+            let funding_settlements = match max_funding_settlements_lookback {
+                Some(lookback) => self
+                    .db
+                    .funding_settlements
+                    .get_latest_settlements(now, lookback.as_u32())
+                    .await
+                    .map_err(SignalProcessRecoverableError::Db)?,
+                None => Vec::new(),
+            };
+
             next_eval = DateTime::<Utc>::MAX_UTC;
 
-            for (resolution, group) in resolution_groups.iter_mut() {
-                let candle_buffer = candle_buffers
-                    .get(resolution)
-                    .map(|v| v.as_slice())
-                    .expect("resolution must be available");
+            for (
+                last_eval,
+                candle_close_tracker,
+                evaluator_idx,
+                lookbacks,
+                funding_settlements_lookback,
+            ) in evaluator_schedules.iter_mut()
+            {
+                let evaluator = &self.evaluators[*evaluator_idx];
 
-                for (last_eval, evaluator_idx, period) in group.evaluators.iter_mut() {
-                    let evaluator = &self.evaluators[*evaluator_idx];
+                let evaluator_next_eval = match candle_close_tracker {
+                    Some(tracker) => {
+                        let is_due = tracker.poll(now);
+                        let evaluator_next_eval = tracker.next_due();
 
-                    let eval_interval = evaluator
-                        .min_iteration_interval()
-                        .map_err(SignalProcessFatalError::Evaluator)?
-                        .as_duration();
+                        if !is_due {
+                            next_eval = next_eval.min(evaluator_next_eval);
+                            continue;
+                        }
 
-                    if now < *last_eval + eval_interval {
-                        continue;
+                        evaluator_next_eval
                     }
+                    None => {
+                        let eval_interval = evaluator
+                            .min_iteration_interval()
+                            .map_err(SignalProcessFatalError::Evaluator)?
+                            .as_duration();
+
+                        if now < *last_eval + eval_interval {
+                            next_eval = next_eval.min(*last_eval + eval_interval);
+                            continue;
+                        }
 
-                    *last_eval = now;
-
-                    let evaluator_next_eval = now + eval_interval;
-                    if evaluator_next_eval < next_eval {
-                        next_eval = evaluator_next_eval;
+                        now + eval_interval
                     }
-
-                    let start_idx = candle_buffer.len().saturating_sub(period.as_usize());
-                    let candles = &candle_buffer[start_idx..];
-
-                    let signal = evaluator
-                        .evaluate(candles)
-                        .await
-                        .map_err(SignalProcessRecoverableError::Evaluator)?;
-
-                    let _ = self.update_tx.send(LiveSignalUpdate::Signal(signal));
-                }
-            }
-
-            for (last_eval, evaluator_idx) in no_lookback_evaluators.iter_mut() {
-                let evaluator = &self.evaluators[*evaluator_idx];
-
-                let eval_interval = evaluator
-                    .min_iteration_interval()
-                    .map_err(SignalProcessFatalError::Evaluator)?
-                    .as_duration();
-
-                if now < *last_eval + eval_interval {
-                    continue;
-                }
+                };
 
                 *last_eval = now;
+                next_eval = next_eval.min(evaluator_next_eval);
+
+                let candle_close_tracker = *candle_close_tracker;
+                let candles = LookbackCandles::from_buffers(lookbacks, |resolution| {
+                    let buffer = candle_buffers
+                        .get(&resolution)
+                        .map(|v| v.as_slice())
+                        .expect("resolution must be available");
+
+                    match candle_close_tracker {
+                        Some(tracker) => tracker.closed_candles(resolution, buffer),
+                        None => buffer,
+                    }
+                });
 
-                let evaluator_next_eval = now + eval_interval;
-                if evaluator_next_eval < next_eval {
-                    next_eval = evaluator_next_eval;
-                }
+                let settlements_start_idx = funding_settlements.len().saturating_sub(
+                    funding_settlements_lookback.map_or(0, |lookback| lookback.as_usize()),
+                );
 
                 let signal = evaluator
-                    .evaluate(&[])
+                    .evaluate(&candles, &funding_settlements[settlements_start_idx..])
                     .await
                     .map_err(SignalProcessRecoverableError::Evaluator)?;
 
+                if let Some(signal_recorder) = signal_recorder.as_mut() {
+                    signal_recorder
+                        .record(evaluator, now, &signal)
+                        .map_err(SignalProcessRecoverableError::Evaluator)?;
+                }
+
                 let _ = self.update_tx.send(LiveSignalUpdate::Signal(signal));
             }
+
+            // Persistence failures must not interrupt evaluation. Signals stay buffered and are
+            // retried with the next batch.
+            if let Some(signal_recorder) = signal_recorder.as_mut()
+                && signal_recorder.pending_len() > 0
+                && let Err(e) = signal_recorder.flush().await
+            {
+                let _ = self.update_tx.send(LiveSignalUpdate::PersistenceFailed {
+                    pending: signal_recorder.pending_len(),
+                    dropped: signal_recorder.dropped_count(),
+                    error: Arc::new(e),
+                });
+            }
         }
     }
 
@@ -299,9 +335,15 @@ impl<S: Signal> LiveSignalProcess<S> {
 
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
+        // Kept across restarts so signals buffered before a failure are not lost
+        let mut signal_recorder = self
+            .config
+            .signal_run()
+            .map(|run| SignalRecorder::new(self.db.clone(), run.to_string()));
+
         loop {
             let signal_process_error = tokio::select! {
-                Err(signal_error) = self.run() => signal_error,
+                Err(signal_error) = self.run(&mut signal_recorder) => signal_error,
                 shutdown_res = shutdown_rx.recv() => {
                     let Err(e) = shutdown_res else {
                        // Shutdown signal received
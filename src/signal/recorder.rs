@@ -0,0 +1,81 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::db::{Database, error::Result, models::NewSignal};
+
+use super::{
+    core::{Signal, WrappedSignalEvaluator},
+    error::SignalEvaluatorResult,
+};
+
+/// Maximum number of signals kept buffered while they can't be persisted. Once reached, the oldest
+/// buffered signals are dropped to bound memory usage during prolonged database outages.
+const MAX_PENDING_SIGNALS: usize = 10_000;
+
+/// Buffers the signals emitted by evaluators and persists them to the database under a run name.
+pub(crate) struct SignalRecorder {
+    db: Arc<Database>,
+    run: String,
+    pending: VecDeque<NewSignal>,
+    dropped: u64,
+}
+
+impl SignalRecorder {
+    pub fn new(db: Arc<Database>, run: String) -> Self {
+        Self {
+            db,
+            run,
+            pending: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Buffers a signal emitted by the evaluator at `time`, until the next [`flush`](Self::flush).
+    /// If the buffer is full, the oldest buffered signal is dropped.
+    pub fn record<S: Signal>(
+        &mut self,
+        evaluator: &WrappedSignalEvaluator<S>,
+        time: DateTime<Utc>,
+        signal: &S,
+    ) -> SignalEvaluatorResult<()> {
+        let new_signal = NewSignal {
+            evaluator: evaluator.name()?,
+            time,
+            signal: signal.to_string(),
+            payload: evaluator
+                .signal_payload(signal)?
+                .map(|payload| payload.to_string()),
+        };
+
+        if self.pending.len() >= MAX_PENDING_SIGNALS {
+            self.pending.pop_front();
+            self.dropped += 1;
+        }
+
+        self.pending.push_back(new_signal);
+
+        Ok(())
+    }
+
+    /// Returns the number of buffered signals.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns the total number of signals dropped because the buffer was full.
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Persists the buffered signals in a single batch. Signals are kept buffered on failure.
+    pub async fn flush(&mut self) -> Result<()> {
+        self.db
+            .signals
+            .add_signals(&self.run, self.pending.make_contiguous())
+            .await?;
+        self.pending.clear();
+
+        Ok(())
+    }
+}
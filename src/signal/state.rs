@@ -5,7 +5,7 @@ use std::{
 
 use tokio::sync::broadcast;
 
-use crate::sync::SyncStatusNotSynced;
+use crate::{db::error::DbError, sync::SyncStatusNotSynced};
 
 use super::{
     core::Signal,
@@ -141,6 +141,15 @@ pub enum LiveSignalUpdate<S: Signal> {
     Status(LiveSignalStatus),
     /// A new trading signal has been generated.
     Signal(S),
+    /// Emitted signals could not be persisted to the database. Evaluation continues, and the
+    /// `pending` signals are kept buffered and retried with the next batch. The buffer is bounded,
+    /// so during prolonged failures the oldest buffered signals are dropped. `dropped` is the total
+    /// number of signals dropped since the signal process started.
+    PersistenceFailed {
+        pending: usize,
+        dropped: u64,
+        error: Arc<DbError>,
+    },
 }
 
 impl<S: Signal> From<LiveSignalStatus> for LiveSignalUpdate<S> {
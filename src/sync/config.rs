@@ -15,9 +15,11 @@ use super::process::{
 /// Configuration for the synchronization engine.
 #[derive(Clone, Debug)]
 pub struct SyncConfig {
+    rest_api_endpoint: String,
     rest_api_timeout: time::Duration,
     rest_api_rate_limit_unauth_requests_per_second: NonZeroU32,
     stream_enabled: bool,
+    stream_api_endpoint: String,
     stream_api_disconnect_timeout: time::Duration,
     rest_api_error_cooldown: time::Duration,
     rest_api_error_max_trials: NonZeroU64,
@@ -29,6 +31,10 @@ pub struct SyncConfig {
     price_history_flag_gap_range: Option<Duration>,
     funding_settlement_flag_missing_range: Option<Duration>,
     live_price_tick_max_interval: time::Duration,
+    price_tick_full_resolution_window: Option<Duration>,
+    price_tick_downsample_interval: time::Duration,
+    price_tick_retention_range: Option<Duration>,
+    price_tick_retention_interval: time::Duration,
     funding_settlement_retry_interval: time::Duration,
     restart_interval: time::Duration,
     shutdown_timeout: time::Duration,
@@ -39,12 +45,14 @@ impl Default for SyncConfig {
         let rest_config_default = RestClientConfig::default();
         let stream_config_default = StreamClientConfig::default();
         Self {
+            rest_api_endpoint: rest_config_default.endpoint().to_string(),
             rest_api_timeout: rest_config_default.timeout(),
             rest_api_rate_limit_unauth_requests_per_second: rest_config_default
                 .rate_limit_unauth_requests_per_second()
                 .try_into()
                 .expect("not zero"),
             stream_enabled: true,
+            stream_api_endpoint: stream_config_default.endpoint().to_string(),
             stream_api_disconnect_timeout: stream_config_default.disconnect_timeout(),
             rest_api_error_cooldown: time::Duration::from_secs(10),
             rest_api_error_max_trials: 3.try_into().expect("not zero"),
@@ -57,6 +65,10 @@ impl Default for SyncConfig {
             price_history_flag_gap_range: Some(Duration::weeks(4)),
             funding_settlement_flag_missing_range: Some(Duration::weeks(4)),
             live_price_tick_max_interval: time::Duration::from_secs(3 * 60),
+            price_tick_full_resolution_window: None,
+            price_tick_downsample_interval: time::Duration::from_secs(1),
+            price_tick_retention_range: None,
+            price_tick_retention_interval: time::Duration::from_secs(10 * 60),
             funding_settlement_retry_interval: time::Duration::from_secs(60),
             restart_interval: time::Duration::from_secs(10),
             shutdown_timeout: time::Duration::from_secs(6),
@@ -65,6 +77,11 @@ impl Default for SyncConfig {
 }
 
 impl SyncConfig {
+    /// Returns the REST API endpoint.
+    pub fn rest_api_endpoint(&self) -> &str {
+        &self.rest_api_endpoint
+    }
+
     /// Returns the timeout duration for REST API requests.
     pub fn rest_api_timeout(&self) -> time::Duration {
         self.rest_api_timeout
@@ -80,6 +97,11 @@ impl SyncConfig {
         self.stream_enabled
     }
 
+    /// Returns the Stream API endpoint.
+    pub fn stream_api_endpoint(&self) -> &str {
+        &self.stream_api_endpoint
+    }
+
     /// Returns the disconnect timeout for Stream API connections.
     pub fn stream_api_disconnect_timeout(&self) -> time::Duration {
         self.stream_api_disconnect_timeout
@@ -140,6 +162,34 @@ impl SyncConfig {
         self.live_price_tick_max_interval
     }
 
+    /// Returns the time window (looking back from the current time) in which price ticks are kept
+    /// at full resolution. Older ticks are downsampled to one tick per
+    /// [`price_tick_downsample_interval`](Self::price_tick_downsample_interval).
+    ///
+    /// If `None`, price ticks are never downsampled.
+    pub fn price_tick_full_resolution_window(&self) -> Option<Duration> {
+        self.price_tick_full_resolution_window
+    }
+
+    /// Returns the interval to which price ticks older than the full resolution window are
+    /// downsampled. Only the last tick of each interval is kept.
+    pub fn price_tick_downsample_interval(&self) -> time::Duration {
+        self.price_tick_downsample_interval
+    }
+
+    /// Returns the time range (looking back from the current time) in which price ticks are
+    /// retained. Older ticks are removed.
+    ///
+    /// If `None`, price ticks are never removed by the retention policy.
+    pub fn price_tick_retention_range(&self) -> Option<Duration> {
+        self.price_tick_retention_range
+    }
+
+    /// Returns the interval for applying the price tick retention policy in live modes.
+    pub fn price_tick_retention_interval(&self) -> time::Duration {
+        self.price_tick_retention_interval
+    }
+
     /// Returns the retry interval for funding settlement sync when not yet caught up.
     pub fn funding_settlement_retry_interval(&self) -> time::Duration {
         self.funding_settlement_retry_interval
@@ -155,6 +205,15 @@ impl SyncConfig {
         self.shutdown_timeout
     }
 
+    /// Sets the REST API endpoint. Allows pointing the engine to a different server, such as a
+    /// local mock of the LN Markets API.
+    ///
+    /// Default: [`RestClientConfig`](lnm_sdk::rest::v3::RestClientConfig) default
+    pub fn with_rest_api_endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.rest_api_endpoint = endpoint.to_string();
+        self
+    }
+
     /// Sets the timeout duration for REST API requests.
     ///
     /// Default: [`RestClientConfig`](lnm_sdk::rest::v3::RestClientConfig) default
@@ -181,6 +240,15 @@ impl SyncConfig {
         self
     }
 
+    /// Sets the Stream API endpoint. Allows pointing the engine to a different server, such as a
+    /// local mock of the LN Markets API.
+    ///
+    /// Default: [`StreamClientConfig`](lnm_sdk::stream::v1::StreamClientConfig) default
+    pub fn with_stream_api_endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.stream_api_endpoint = endpoint.to_string();
+        self
+    }
+
     /// Sets the disconnect timeout for Stream API connections.
     ///
     /// Default: [`StreamClientConfig`](lnm_sdk::stream::v1::StreamClientConfig) default
@@ -291,6 +359,45 @@ impl SyncConfig {
         self
     }
 
+    /// Sets the time window (looking back from the current time) in which price ticks are kept at
+    /// full resolution. Older ticks are downsampled to one tick per downsample interval.
+    ///
+    /// If `None`, price ticks are never downsampled.
+    ///
+    /// Default: `None`
+    pub fn with_price_tick_full_resolution_window(mut self, hours: Option<u64>) -> Self {
+        self.price_tick_full_resolution_window = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
+    /// Sets the interval to which price ticks older than the full resolution window are
+    /// downsampled. Only the last tick of each interval is kept.
+    ///
+    /// Default: `1` second
+    pub fn with_price_tick_downsample_interval(mut self, secs: NonZeroU64) -> Self {
+        self.price_tick_downsample_interval = time::Duration::from_secs(secs.get());
+        self
+    }
+
+    /// Sets the time range (looking back from the current time) in which price ticks are
+    /// retained. Older ticks are removed.
+    ///
+    /// If `None`, price ticks are never removed by the retention policy.
+    ///
+    /// Default: `None`
+    pub fn with_price_tick_retention_range(mut self, hours: Option<u64>) -> Self {
+        self.price_tick_retention_range = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
+    /// Sets the interval for applying the price tick retention policy in live modes.
+    ///
+    /// Default: `600` seconds (10 minutes)
+    pub fn with_price_tick_retention_interval(mut self, secs: NonZeroU64) -> Self {
+        self.price_tick_retention_interval = time::Duration::from_secs(secs.get());
+        self
+    }
+
     /// Sets the retry interval for funding settlement sync when not yet caught up.
     ///
     /// Default: `60` seconds (1 minute)
@@ -320,6 +427,7 @@ impl From<&SyncConfig> for RestClientConfig {
     fn from(value: &SyncConfig) -> Self {
         // FIXME, when a more straighforward constructor is added to `RestClientConfig`
         RestClientConfig::new(value.rest_api_timeout())
+            .with_endpoint(value.rest_api_endpoint())
             .with_rate_limiter_active(true)
             .with_rate_limit_unauth_requests_per_second(
                 value.rest_api_rate_limit_unauth_requests_per_second(),
@@ -329,17 +437,21 @@ impl From<&SyncConfig> for RestClientConfig {
 
 impl From<&SyncConfig> for StreamClientConfig {
     fn from(value: &SyncConfig) -> Self {
-        StreamClientConfig::default().with_disconnect_timeout(value.stream_api_disconnect_timeout())
+        StreamClientConfig::default()
+            .with_endpoint(value.stream_api_endpoint())
+            .with_disconnect_timeout(value.stream_api_disconnect_timeout())
     }
 }
 
 impl From<&LiveTradeConfig> for SyncConfig {
     fn from(value: &LiveTradeConfig) -> Self {
         SyncConfig {
+            rest_api_endpoint: value.rest_api_endpoint().to_string(),
             rest_api_timeout: value.rest_api_timeout(),
             rest_api_rate_limit_unauth_requests_per_second: value
                 .rest_api_rate_limit_unauth_requests_per_second(),
             stream_enabled: value.stream_enabled(),
+            stream_api_endpoint: value.stream_api_endpoint().to_string(),
             stream_api_disconnect_timeout: value.stream_api_disconnect_timeout(),
             rest_api_error_cooldown: value.rest_api_error_cooldown(),
             rest_api_error_max_trials: value.rest_api_error_max_trials(),
@@ -351,6 +463,10 @@ impl From<&LiveTradeConfig> for SyncConfig {
             price_history_flag_gap_range: value.price_history_flag_gap_range(),
             funding_settlement_flag_missing_range: value.funding_settlement_flag_missing_range(),
             live_price_tick_max_interval: value.live_price_tick_max_interval(),
+            price_tick_full_resolution_window: value.price_tick_full_resolution_window(),
+            price_tick_downsample_interval: value.price_tick_downsample_interval(),
+            price_tick_retention_range: value.price_tick_retention_range(),
+            price_tick_retention_interval: value.price_tick_retention_interval(),
             funding_settlement_retry_interval: value.funding_sync_retry_interval(),
             restart_interval: value.restart_interval(),
             shutdown_timeout: value.shutdown_timeout(),
@@ -382,6 +498,8 @@ pub(crate) struct SyncProcessConfig {
     rest_api_error_cooldown: time::Duration,
     rest_api_error_max_trials: NonZeroU64,
     stream_enabled: bool,
+    #[cfg(feature = "testing")]
+    stream_api_endpoint: String,
     price_history_batch_size: NonZeroU64,
     price_history_reach: DateTime<Utc>,
     funding_settlement_reach: DateTime<Utc>,
@@ -390,6 +508,10 @@ pub(crate) struct SyncProcessConfig {
     price_history_flag_gap_range: Option<Duration>,
     funding_settlement_flag_missing_range: Option<Duration>,
     live_price_tick_max_interval: time::Duration,
+    price_tick_full_resolution_window: Option<Duration>,
+    price_tick_downsample_interval: time::Duration,
+    price_tick_retention_range: Option<Duration>,
+    price_tick_retention_interval: time::Duration,
     funding_settlement_retry_interval: time::Duration,
     restart_interval: time::Duration,
 }
@@ -399,6 +521,11 @@ impl SyncProcessConfig {
         self.stream_enabled
     }
 
+    #[cfg(feature = "testing")]
+    pub fn stream_api_endpoint(&self) -> &str {
+        &self.stream_api_endpoint
+    }
+
     pub fn price_history_re_sync_interval(&self) -> time::Duration {
         self.price_history_re_sync_interval
     }
@@ -423,6 +550,22 @@ impl SyncProcessConfig {
         self.live_price_tick_max_interval
     }
 
+    pub fn price_tick_full_resolution_window(&self) -> Option<Duration> {
+        self.price_tick_full_resolution_window
+    }
+
+    pub fn price_tick_downsample_interval(&self) -> time::Duration {
+        self.price_tick_downsample_interval
+    }
+
+    pub fn price_tick_retention_range(&self) -> Option<Duration> {
+        self.price_tick_retention_range
+    }
+
+    pub fn price_tick_retention_interval(&self) -> time::Duration {
+        self.price_tick_retention_interval
+    }
+
     pub fn funding_settlement_retry_interval(&self) -> time::Duration {
         self.funding_settlement_retry_interval
     }
@@ -438,6 +581,8 @@ impl From<&SyncConfig> for SyncProcessConfig {
             rest_api_error_cooldown: value.rest_api_error_cooldown,
             rest_api_error_max_trials: value.rest_api_error_max_trials,
             stream_enabled: value.stream_enabled,
+            #[cfg(feature = "testing")]
+            stream_api_endpoint: value.stream_api_endpoint.clone(),
             price_history_batch_size: value.price_history_batch_size,
             price_history_reach: value.price_history_reach,
             funding_settlement_reach: value.funding_settlement_reach,
@@ -446,6 +591,10 @@ impl From<&SyncConfig> for SyncProcessConfig {
             price_history_flag_gap_range: value.price_history_flag_gap_range,
             funding_settlement_flag_missing_range: value.funding_settlement_flag_missing_range,
             live_price_tick_max_interval: value.live_price_tick_max_interval,
+            price_tick_full_resolution_window: value.price_tick_full_resolution_window,
+            price_tick_downsample_interval: value.price_tick_downsample_interval,
+            price_tick_retention_range: value.price_tick_retention_range,
+            price_tick_retention_interval: value.price_tick_retention_interval,
             funding_settlement_retry_interval: value.funding_settlement_retry_interval,
             restart_interval: value.restart_interval,
         }
@@ -519,3 +668,33 @@ impl From<&SyncProcessConfig> for SyncFundingSettlementsTaskConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_tick_retention_is_opt_in() {
+        let config = SyncConfig::default();
+        assert!(config.price_tick_full_resolution_window().is_none());
+        assert!(config.price_tick_retention_range().is_none());
+
+        let live_config = LiveTradeConfig::default()
+            .with_price_tick_full_resolution_window(Some(1))
+            .with_price_tick_retention_range(Some(168))
+            .with_price_tick_retention_interval(60.try_into().unwrap());
+        let config = SyncConfig::from(&live_config);
+        assert_eq!(
+            config.price_tick_full_resolution_window(),
+            Some(Duration::hours(1))
+        );
+        assert_eq!(
+            config.price_tick_retention_range(),
+            Some(Duration::weeks(1))
+        );
+        assert_eq!(
+            config.price_tick_retention_interval(),
+            time::Duration::from_secs(60)
+        );
+    }
+}
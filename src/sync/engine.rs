@@ -351,7 +351,12 @@ impl SyncEngine {
     /// Creates a new sync engine with the specified configuration and mode.
     ///
     /// This constructor automatically initializes the required API clients based on the sync mode.
+    /// Returns an error if the database is read-only.
     pub fn new(config: impl Into<SyncConfig>, db: Arc<Database>, mode: SyncMode) -> Result<Self> {
+        if db.read_only() {
+            return Err(SyncError::ReadOnlyDatabase);
+        }
+
         let config: SyncConfig = config.into();
 
         let api_rest = RestClient::new(&config).map_err(SyncError::RestApiInit)?;
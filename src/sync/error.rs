@@ -12,6 +12,9 @@ pub enum SyncError {
     #[error("REST API client initialization error: {0}")]
     RestApiInit(RestApiError),
 
+    #[error("Database is read-only, so synced data can't be persisted")]
+    ReadOnlyDatabase,
+
     #[error("Sync already shutdown error")]
     SyncAlreadyShutdown,
 
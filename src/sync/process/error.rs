@@ -6,6 +6,8 @@ use tokio::{
     task::JoinError,
 };
 
+use crate::db::error::DbError;
+
 use super::{
     real_time_collection_task::error::RealTimeCollectionError,
     sync_funding_settlements_task::error::{
@@ -39,6 +41,9 @@ pub enum SyncProcessRecoverableError {
 
     #[error("Maximum interval between price ticks ({0:?}) was exceeded.")]
     MaxPriceTickIntevalExceeded(Duration),
+
+    #[error("[PriceTickRetention] {0}")]
+    PriceTickRetention(DbError),
 }
 
 #[derive(Error, Debug)]
@@ -1,7 +1,8 @@
 use std::{pin::Pin, sync::Arc};
 
-use chrono::Duration;
+use chrono::{DateTime, Duration, Utc};
 use futures::TryFutureExt;
+use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{broadcast, mpsc},
     time,
@@ -234,6 +235,10 @@ impl SyncProcess {
                 || Box::pin(time::sleep(self.config.live_price_tick_max_interval()));
             let mut tick_interval_timer = new_tick_interval_timer();
 
+            let new_tick_retention_timer =
+                || Box::pin(time::sleep(self.config.price_tick_retention_interval()));
+            let mut tick_retention_timer = new_tick_retention_timer();
+
             loop {
                 tokio::select! {
                     rt_res = &mut real_time_collection_handle => {
@@ -252,6 +257,10 @@ impl SyncProcess {
 
                         let _ = self.update_tx.send(tick.into());
                     }
+                    _ = &mut tick_retention_timer => {
+                        self.apply_price_tick_retention().await?;
+                        tick_retention_timer = new_tick_retention_timer();
+                    }
                     _ = &mut tick_interval_timer => {
                         // Maximum interval between Price Ticks was exceeded
                         return Err(SyncProcessRecoverableError::MaxPriceTickIntevalExceeded(
@@ -335,6 +344,10 @@ impl SyncProcess {
                 || Box::pin(time::sleep(self.config.live_price_tick_max_interval()));
             let mut tick_interval_timer = new_tick_interval_timer();
 
+            let new_tick_retention_timer =
+                || Box::pin(time::sleep(self.config.price_tick_retention_interval()));
+            let mut tick_retention_timer = new_tick_retention_timer();
+
             loop {
                 tokio::select! {
                     rt_res = &mut real_time_collection_handle => {
@@ -358,6 +371,10 @@ impl SyncProcess {
                         self.run_price_history_task_live(api_rest.clone(), history_state_tx.clone(), lookback).await?;
                         re_sync_timer = new_re_sync_timer();
                     }
+                    _ = &mut tick_retention_timer => {
+                        self.apply_price_tick_retention().await?;
+                        tick_retention_timer = new_tick_retention_timer();
+                    }
                     _ = &mut tick_interval_timer => {
                         // Maximum interval between Price Ticks was exceeded
                         return Err(SyncProcessRecoverableError::MaxPriceTickIntevalExceeded(
@@ -476,6 +493,10 @@ impl SyncProcess {
                 || Box::pin(time::sleep(self.config.live_price_tick_max_interval()));
             let mut tick_interval_timer = new_tick_interval_timer();
 
+            let new_tick_retention_timer =
+                || Box::pin(time::sleep(self.config.price_tick_retention_interval()));
+            let mut tick_retention_timer = new_tick_retention_timer();
+
             let mut funding_timer = new_funding_timer(true);
 
             loop {
@@ -505,6 +526,10 @@ impl SyncProcess {
                         let synced = self.run_funding_settlements_task_backfill(api_rest.clone(), funding_state_tx.clone(), None).await?;
                         funding_timer = new_funding_timer(synced);
                     }
+                    _ = &mut tick_retention_timer => {
+                        self.apply_price_tick_retention().await?;
+                        tick_retention_timer = new_tick_retention_timer();
+                    }
                     _ = &mut tick_interval_timer => {
                         // Maximum interval between Price Ticks was exceeded
                         return Err(SyncProcessRecoverableError::MaxPriceTickIntevalExceeded(
@@ -537,6 +562,57 @@ impl SyncProcess {
         }
     }
 
+    /// Applies the price tick retention policy. Ticks that left the full resolution window since
+    /// the last run are downsampled, and ticks beyond the retention range are removed.
+    ///
+    /// The downsampling bound is persisted, so that ticks that were already downsampled are not
+    /// rescanned after restarts.
+    async fn apply_price_tick_retention(&self) -> Result<()> {
+        let now = Utc::now();
+
+        if let Some(window) = self.config.price_tick_full_resolution_window() {
+            let interval = Duration::from_std(self.config.price_tick_downsample_interval())
+                .expect("must be a valid `Duration`");
+            let bound = downsample_bound(now, window, interval);
+
+            let downsampled_until = self
+                .db
+                .key_values
+                .get_value(TICKS_DOWNSAMPLED_UNTIL_KEY)
+                .await
+                .map_err(SyncProcessRecoverableError::PriceTickRetention)?
+                .and_then(|bytes| decode_downsampled_until(&bytes))
+                .unwrap_or(DateTime::UNIX_EPOCH);
+
+            if bound > downsampled_until {
+                self.db
+                    .price_ticks
+                    .downsample_ticks(downsampled_until, bound, interval)
+                    .await
+                    .map_err(SyncProcessRecoverableError::PriceTickRetention)?;
+
+                self.db
+                    .key_values
+                    .set_value(
+                        TICKS_DOWNSAMPLED_UNTIL_KEY,
+                        &encode_downsampled_until(bound),
+                    )
+                    .await
+                    .map_err(SyncProcessRecoverableError::PriceTickRetention)?;
+            }
+        }
+
+        if let Some(range) = self.config.price_tick_retention_range() {
+            self.db
+                .price_ticks
+                .remove_ticks(now - range)
+                .await
+                .map_err(SyncProcessRecoverableError::PriceTickRetention)?;
+        }
+
+        Ok(())
+    }
+
     async fn run_price_history_task_backfill(
         &self,
         api_rest: Arc<RestClient>,
@@ -632,6 +708,9 @@ impl SyncProcess {
             price_tick_tx,
         );
 
+        #[cfg(feature = "testing")]
+        let task = task.with_stream_api_endpoint(self.config.stream_api_endpoint().to_string());
+
         tokio::spawn(
             task.run()
                 .map_err(|e| SyncProcessRecoverableError::RealTimeCollection(e).into()),
@@ -639,3 +718,69 @@ impl SyncProcess {
         .into()
     }
 }
+
+/// Key under which the bound up to which price ticks were downsampled is persisted.
+const TICKS_DOWNSAMPLED_UNTIL_KEY: &str = "quantoxide:sync:price_ticks_downsampled_until";
+
+/// Persisted bound up to which price ticks were downsampled.
+#[derive(Serialize, Deserialize)]
+struct DownsampleRecord {
+    downsampled_until: DateTime<Utc>,
+}
+
+/// Returns the time before which ticks left the full resolution `window`, aligned to the
+/// downsample `interval` so that buckets are never split across runs.
+fn downsample_bound(now: DateTime<Utc>, window: Duration, interval: Duration) -> DateTime<Utc> {
+    let interval_secs = interval.num_seconds().max(1);
+    let bound_secs = (now - window).timestamp().div_euclid(interval_secs) * interval_secs;
+
+    DateTime::from_timestamp(bound_secs, 0).expect("must be a valid timestamp")
+}
+
+fn encode_downsampled_until(time: DateTime<Utc>) -> Vec<u8> {
+    let record = DownsampleRecord {
+        downsampled_until: time,
+    };
+
+    serde_json::to_vec(&record).expect("downsample record is serializable")
+}
+
+/// Records that can't be decoded are ignored, in which case all ticks are rescanned. Rescanning is
+/// safe, since downsampling ticks that were already downsampled doesn't change them.
+fn decode_downsampled_until(bytes: &[u8]) -> Option<DateTime<Utc>> {
+    serde_json::from_slice::<DownsampleRecord>(bytes)
+        .ok()
+        .map(|record| record.downsampled_until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downsample_bound_is_aligned_to_interval() {
+        let now = DateTime::from_timestamp(1_700_003_607, 0).unwrap();
+
+        let bound = downsample_bound(now, Duration::hours(1), Duration::seconds(10));
+        assert_eq!(bound, DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+
+        // Later runs within the same bucket don't move the bound
+        let bound_later = downsample_bound(
+            now + Duration::seconds(2),
+            Duration::hours(1),
+            Duration::seconds(10),
+        );
+        assert_eq!(bound_later, bound);
+    }
+
+    #[test]
+    fn test_downsampled_until_bytes_roundtrip() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        assert_eq!(
+            decode_downsampled_until(&encode_downsampled_until(time)),
+            Some(time)
+        );
+        assert_eq!(decode_downsampled_until(b"not a time"), None);
+    }
+}
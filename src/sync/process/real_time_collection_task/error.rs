@@ -6,6 +6,8 @@ use tokio::sync::broadcast::error::RecvError;
 use lnm_sdk::stream::v1::{StreamConnectionStatus, error::StreamApiError};
 
 use crate::db::error::DbError;
+#[cfg(feature = "testing")]
+use crate::testing::MockLnmError;
 
 #[derive(Error, Debug)]
 #[non_exhaustive]
@@ -19,6 +21,10 @@ pub enum RealTimeCollectionError {
     #[error("[Db] {0}")]
     Db(#[from] DbError),
 
+    #[cfg(feature = "testing")]
+    #[error("[MockStream] {0}")]
+    MockStream(#[from] MockLnmError),
+
     #[error("RecvLagged error, skipped: {skipped}")]
     StreamRecvLagged { skipped: u64 },
 
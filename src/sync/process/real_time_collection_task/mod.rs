@@ -11,6 +11,8 @@ use lnm_sdk::stream::v1::{
 };
 
 use crate::db::{Database, error::Result as DbResult, models::PriceTickRow};
+#[cfg(feature = "testing")]
+use crate::testing::stream as mock_stream;
 
 pub(crate) mod error;
 
@@ -21,6 +23,8 @@ pub(super) struct RealTimeCollectionTask {
     api_ws: Arc<StreamClient>,
     shutdown_tx: broadcast::Sender<()>,
     price_tick_tx: broadcast::Sender<PriceTickRow>,
+    #[cfg(feature = "testing")]
+    stream_api_endpoint: Option<String>,
 }
 
 impl RealTimeCollectionTask {
@@ -35,18 +39,40 @@ impl RealTimeCollectionTask {
             api_ws,
             shutdown_tx,
             price_tick_tx,
+            #[cfg(feature = "testing")]
+            stream_api_endpoint: None,
         }
     }
 
+    #[cfg(feature = "testing")]
+    pub fn with_stream_api_endpoint(mut self, endpoint: String) -> Self {
+        self.stream_api_endpoint = Some(endpoint);
+        self
+    }
+
     pub async fn run(self) -> Result<()> {
+        // The `lnm-sdk` stream client only connects to `wss` endpoints, so plain `ws` endpoints,
+        // such as the one served by `MockLnmServer`, are handled by the testing client
+        #[cfg(feature = "testing")]
+        if let Some(endpoint) = self.stream_api_endpoint.as_deref()
+            && mock_stream::is_plain_stream_endpoint(endpoint)
+        {
+            let stream_rx = mock_stream::subscribe_last_price(endpoint).await?;
+            return self.collect(stream_rx).await;
+        }
+
         let stream = self.api_ws.connect().await?;
 
-        let mut stream_rx = stream.receiver().await?;
+        let stream_rx = stream.receiver().await?;
 
         stream
             .subscribe(vec![StreamTopic::FuturesInverseBtcUsdLastPrice])
             .await?;
 
+        self.collect(stream_rx).await
+    }
+
+    async fn collect(&self, mut stream_rx: broadcast::Receiver<StreamUpdate>) -> Result<()> {
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         let mut pending_ticks: Vec<LastPrice> = Vec::new();
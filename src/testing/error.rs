@@ -0,0 +1,24 @@
+use std::{io, result};
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum MockLnmError {
+    #[error("Failed to bind the mock server: {0}")]
+    Bind(io::Error),
+
+    #[error("Mock exchange error ({status}): {message}")]
+    Exchange { status: u16, message: String },
+
+    #[error("Invalid mock exchange response: {0}")]
+    InvalidResponse(serde_json::Error),
+
+    #[error("Failed to connect to the mock stream: {0}")]
+    StreamConnect(io::Error),
+
+    #[error("Mock stream error: {0}")]
+    Stream(String),
+}
+
+pub(super) type Result<T> = result::Result<T, MockLnmError>;
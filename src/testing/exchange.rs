@@ -0,0 +1,598 @@
+use std::{fmt, num::NonZeroU64, sync::Arc};
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{
+    ClientId, CrossExposure, CrossLeverage, Leverage, Margin, OrderQuantity, Price, TradeSide,
+    TradeSize,
+};
+
+use crate::{
+    db::models::FundingSettlementRow,
+    trade::{
+        CrossOrderRequest, CrossPositionCore, IsolatedOrderRequest, Stoploss, TradeClosed,
+        TradeCore, TradeExecutor, TradeRunning, TradingState,
+        backtest::{config::SimulatedTradeExecutorConfig, executor::SimulatedTradeExecutor},
+    },
+    util::DateTimeExt,
+};
+
+use super::price_feed::MockPriceFeed;
+
+const DEFAULT_PAGE_LIMIT: u64 = 100;
+const MAX_PAGE_LIMIT: u64 = 1_000;
+
+/// Error returned by the mock exchange, served with the given HTTP status code.
+#[derive(Debug)]
+pub(super) struct ExchangeError {
+    pub status: u16,
+    pub message: String,
+}
+
+impl ExchangeError {
+    pub fn bad_request(message: impl fmt::Display) -> Self {
+        Self {
+            status: 400,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn not_found(message: impl fmt::Display) -> Self {
+        Self {
+            status: 404,
+            message: message.to_string(),
+        }
+    }
+}
+
+pub(super) type ExchangeResult<T> = Result<T, ExchangeError>;
+
+/// Simulated LN Markets account, driven by a [`MockPriceFeed`].
+///
+/// Isolated trades and the cross-margin position are managed by the same simulated executor used
+/// in backtests, so fills, fees, liquidations and funding settlements follow the backtest rules.
+/// Before serving each request, the account is brought up to date with the prices set since the
+/// previous request, closing trades whose stoploss, takeprofit or liquidation was reached.
+pub(super) struct MockExchange {
+    feed: MockPriceFeed,
+    funding_rate: f64,
+    account_id: Uuid,
+    cross_position_id: Uuid,
+    executor: Arc<SimulatedTradeExecutor>,
+    synced_until: DateTime<Utc>,
+}
+
+impl MockExchange {
+    pub fn new(feed: MockPriceFeed, balance: u64, funding_rate: f64) -> Self {
+        let start = feed.start();
+        let start_price = feed.price_at(start).expect("feed must have a start price");
+
+        let executor = SimulatedTradeExecutor::with_market_price(
+            SimulatedTradeExecutorConfig::default(),
+            start,
+            start_price,
+            balance,
+        );
+
+        Self {
+            feed,
+            funding_rate,
+            account_id: Uuid::new_v4(),
+            cross_position_id: Uuid::new_v4(),
+            executor,
+            synced_until: start,
+        }
+    }
+
+    fn funding_settlement(&self, time: DateTime<Utc>) -> FundingSettlementRow {
+        let fixing_price = self
+            .feed
+            .price_at(time)
+            .unwrap_or_else(|| self.feed.price_at(self.feed.start()).expect("not empty"));
+
+        FundingSettlementRow {
+            // Stable across requests, so repeated downloads return the same settlements
+            id: Uuid::from_u128(time.timestamp() as u128),
+            time,
+            fixing_price: Price::bounded(fixing_price).as_f64(),
+            funding_rate: self.funding_rate,
+            created_at: time,
+        }
+    }
+
+    /// Applies the prices and funding settlements in `(synced_until, now]` to the account.
+    pub async fn advance(&mut self, now: DateTime<Utc>) -> ExchangeResult<()> {
+        if now <= self.synced_until {
+            return Ok(());
+        }
+
+        let mut settlement_time = self.synced_until.ceil_funding_settlement_time();
+        if settlement_time <= self.synced_until {
+            settlement_time =
+                (self.synced_until + Duration::seconds(1)).ceil_funding_settlement_time();
+        }
+
+        let points: Vec<_> = self.feed.points_between(self.synced_until, now).collect();
+
+        for (time, price) in points {
+            while settlement_time <= time {
+                self.apply_funding_settlement(settlement_time).await?;
+                settlement_time =
+                    (settlement_time + Duration::seconds(1)).ceil_funding_settlement_time();
+            }
+
+            self.executor
+                .tick_update(time, price)
+                .await
+                .map_err(ExchangeError::bad_request)?;
+        }
+
+        while settlement_time <= now {
+            self.apply_funding_settlement(settlement_time).await?;
+            settlement_time =
+                (settlement_time + Duration::seconds(1)).ceil_funding_settlement_time();
+        }
+
+        self.executor
+            .update_time(now)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+        self.synced_until = now;
+
+        Ok(())
+    }
+
+    async fn apply_funding_settlement(&self, time: DateTime<Utc>) -> ExchangeResult<()> {
+        self.executor
+            .update_time(time)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+        self.executor
+            .apply_funding_settlement(&self.funding_settlement(time))
+            .await
+            .map_err(ExchangeError::bad_request)
+    }
+
+    pub async fn set_price(&mut self, now: DateTime<Utc>, price: f64) -> ExchangeResult<()> {
+        self.feed.insert(now, price);
+        self.advance(now).await
+    }
+
+    pub fn price(&self, now: DateTime<Utc>) -> f64 {
+        self.feed
+            .price_at(now)
+            .unwrap_or_else(|| self.feed.price_at(self.feed.start()).expect("not empty"))
+    }
+
+    pub async fn trading_state(&self) -> ExchangeResult<TradingState> {
+        self.executor
+            .trading_state()
+            .await
+            .map_err(ExchangeError::bad_request)
+    }
+
+    /// Returns up to `limit` one-minute candles before `cursor` (exclusive), newest first.
+    pub fn candles(
+        &self,
+        now: DateTime<Utc>,
+        cursor: Option<DateTime<Utc>>,
+        limit: Option<u64>,
+    ) -> Value {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        let upper = cursor.map_or(now, |cursor| (cursor - Duration::nanoseconds(1)).min(now));
+
+        let mut candles = Vec::new();
+        let mut time = upper.floor_minute();
+
+        while (candles.len() as u64) < limit && time >= self.feed.start().floor_minute() {
+            if let Some((open, high, low, close, volume)) = self.feed.candle(time, now) {
+                candles.push(json!({
+                    "time": time,
+                    "open": Price::bounded(open),
+                    "high": Price::bounded(high),
+                    "low": Price::bounded(low),
+                    "close": Price::bounded(close),
+                    "volume": volume,
+                }));
+            }
+
+            time -= Duration::minutes(1);
+        }
+
+        let next_cursor = (candles.len() as u64 == limit).then_some(time + Duration::minutes(1));
+
+        json!({ "data": candles, "nextCursor": next_cursor })
+    }
+
+    /// Returns up to `limit` funding settlements in `[from, to]`, newest first.
+    pub fn funding_settlements(
+        &self,
+        now: DateTime<Utc>,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+        limit: Option<u64>,
+    ) -> Value {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT);
+        let upper = to.map_or(now, |to| to.min(now));
+
+        let mut settlements = Vec::new();
+        let mut time = upper.floor_funding_settlement_time();
+
+        while (settlements.len() as u64) < limit && from.is_none_or(|from| time >= from) {
+            let settlement = self.funding_settlement(time);
+            settlements.push(json!({
+                "id": settlement.id,
+                "time": settlement.time,
+                "fixingPrice": settlement.fixing_price,
+                "fundingRate": settlement.funding_rate,
+            }));
+
+            let prev_time = time.step_back_funding_settlements(1);
+            if prev_time >= time {
+                break;
+            }
+            time = prev_time;
+        }
+
+        json!({ "data": settlements, "nextCursor": null })
+    }
+
+    pub fn ticker(&self, now: DateTime<Utc>) -> Value {
+        let price = Price::bounded(self.price(now));
+        let next_settlement = (now + Duration::seconds(1)).ceil_funding_settlement_time();
+
+        json!({
+            "index": price,
+            "lastPrice": price,
+            "prices": [{
+                "askPrice": price,
+                "bidPrice": price,
+                "minSize": 1,
+                "maxSize": 10_000_000,
+            }],
+            "fundingRate": self.funding_rate,
+            "fundingTime": next_settlement,
+        })
+    }
+
+    pub fn account_id(&self) -> Uuid {
+        self.account_id
+    }
+
+    pub async fn account(&self) -> ExchangeResult<Value> {
+        let state = self.trading_state().await?;
+
+        Ok(json!({
+            "id": self.account_id,
+            "username": "mock",
+            "email": "mock@localhost",
+            "syntheticUsdBalance": 0,
+            "balance": state.balance(),
+            "feeTier": 0,
+            "linkingPublicKey": null,
+        }))
+    }
+
+    pub async fn running_trades(&self) -> ExchangeResult<Value> {
+        let state = self.trading_state().await?;
+        let market_price = state.market_price();
+
+        let trades: Vec<_> = state
+            .running_map()
+            .trades_desc()
+            .map(|(trade, _)| running_trade_json(trade.as_ref(), market_price))
+            .collect();
+
+        Ok(Value::Array(trades))
+    }
+
+    pub async fn closed_trades(&self, limit: Option<u64>) -> ExchangeResult<Value> {
+        let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).min(MAX_PAGE_LIMIT) as usize;
+        let state = self.trading_state().await?;
+
+        let trades: Vec<_> = state
+            .closed_history()
+            .iter_desc()
+            .take(limit)
+            .map(|trade| closed_trade_json(trade.as_ref()))
+            .collect();
+
+        Ok(json!({ "data": trades, "nextCursor": null }))
+    }
+
+    async fn running_trade(&self, id: Uuid) -> ExchangeResult<Value> {
+        let state = self.trading_state().await?;
+        let (trade, _) = state
+            .running_map()
+            .get_by_id(id)
+            .ok_or_else(|| ExchangeError::not_found(format!("running trade {id} not found")))?;
+
+        Ok(running_trade_json(trade.as_ref(), state.market_price()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_trade(
+        &self,
+        side: TradeSide,
+        size: TradeSize,
+        leverage: Leverage,
+        stoploss: Option<Price>,
+        takeprofit: Option<Price>,
+        client_id: Option<ClientId>,
+    ) -> ExchangeResult<Value> {
+        let mut request = IsolatedOrderRequest::market(side, size, leverage);
+        if let Some(stoploss) = stoploss {
+            request = request
+                .with_stoploss(Stoploss::fixed(stoploss))
+                .map_err(ExchangeError::bad_request)?;
+        }
+        if let Some(takeprofit) = takeprofit {
+            request = request
+                .with_takeprofit(takeprofit)
+                .map_err(ExchangeError::bad_request)?;
+        }
+        if let Some(client_id) = client_id {
+            request = request.with_client_id(client_id);
+        }
+
+        let id = self
+            .executor
+            .isolated_order(request)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.running_trade(id).await
+    }
+
+    pub async fn update_stoploss(&self, id: Uuid, stoploss: Price) -> ExchangeResult<Value> {
+        self.executor
+            .update_stoploss(id, stoploss)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.running_trade(id).await
+    }
+
+    pub async fn add_margin(&self, id: Uuid, amount: NonZeroU64) -> ExchangeResult<Value> {
+        self.executor
+            .isolated_trade_add_margin(id, amount)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.running_trade(id).await
+    }
+
+    pub async fn cash_in(&self, id: Uuid, amount: NonZeroU64) -> ExchangeResult<Value> {
+        self.executor
+            .isolated_trade_cash_in(id, amount)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.running_trade(id).await
+    }
+
+    pub async fn close_trade(&self, id: Uuid) -> ExchangeResult<Value> {
+        // Fails if the trade is not running
+        self.running_trade(id).await?;
+
+        self.executor
+            .isolated_order_close(id)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        let state = self.trading_state().await?;
+        let trade = state
+            .closed_history()
+            .get_by_id(id)
+            .ok_or_else(|| ExchangeError::not_found(format!("closed trade {id} not found")))?;
+
+        Ok(closed_trade_json(trade.as_ref()))
+    }
+
+    pub async fn cross_position(&self) -> ExchangeResult<Value> {
+        let state = self.trading_state().await?;
+
+        Ok(cross_position_json(
+            self.cross_position_id,
+            state.cross_position(),
+            state.market_price(),
+        ))
+    }
+
+    pub async fn cross_deposit(&self, amount: NonZeroU64) -> ExchangeResult<Value> {
+        self.executor
+            .cross_deposit(amount)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.cross_position().await
+    }
+
+    pub async fn cross_withdraw(&self, amount: NonZeroU64) -> ExchangeResult<Value> {
+        self.executor
+            .cross_withdraw(amount)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.cross_position().await
+    }
+
+    pub async fn cross_set_leverage(&self, leverage: CrossLeverage) -> ExchangeResult<Value> {
+        self.executor
+            .cross_set_leverage(leverage)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        self.cross_position().await
+    }
+
+    pub async fn cross_order(
+        &self,
+        now: DateTime<Utc>,
+        side: TradeSide,
+        quantity: OrderQuantity,
+        client_id: Option<ClientId>,
+    ) -> ExchangeResult<Value> {
+        let prev_state = self.trading_state().await?;
+
+        let mut request = CrossOrderRequest::market(side, quantity);
+        if let Some(client_id) = client_id.clone() {
+            request = request.with_client_id(client_id);
+        }
+
+        let id = self
+            .executor
+            .cross_order(request)
+            .await
+            .map_err(ExchangeError::bad_request)?;
+
+        let state = self.trading_state().await?;
+
+        Ok(cross_order_json(
+            id,
+            now,
+            side,
+            quantity,
+            state.market_price(),
+            state.cross_position().trading_fees() - prev_state.cross_position().trading_fees(),
+            client_id,
+        ))
+    }
+
+    pub async fn cross_close_position(&self, now: DateTime<Utc>) -> ExchangeResult<Value> {
+        let prev_state = self.trading_state().await?;
+
+        let CrossExposure::Running(exposure) = prev_state.cross_position().exposure() else {
+            return Err(ExchangeError::bad_request("no running cross position"));
+        };
+
+        let side = match exposure.side() {
+            TradeSide::Buy => TradeSide::Sell,
+            TradeSide::Sell => TradeSide::Buy,
+        };
+        let quantity = OrderQuantity::try_from(exposure.quantity().as_u64())
+            .map_err(ExchangeError::bad_request)?;
+
+        let id = self
+            .executor
+            .cross_order_close_position()
+            .await
+            .map_err(ExchangeError::bad_request)?
+            .ok_or_else(|| ExchangeError::bad_request("no running cross position"))?;
+
+        let state = self.trading_state().await?;
+
+        Ok(cross_order_json(
+            id,
+            now,
+            side,
+            quantity,
+            state.market_price(),
+            state.cross_position().trading_fees() - prev_state.cross_position().trading_fees(),
+            None,
+        ))
+    }
+}
+
+fn trade_json(trade: &dyn TradeCore, pl: i64) -> Value {
+    json!({
+        "id": trade.id(),
+        "type": "market",
+        "side": trade.side(),
+        "openingFee": trade.opening_fee(),
+        "closingFee": trade.closing_fee(),
+        "maintenanceMargin": trade.maintenance_margin(),
+        "quantity": trade.quantity(),
+        "margin": trade.margin(),
+        "leverage": trade.leverage(),
+        "price": trade.price(),
+        "liquidation": trade.liquidation(),
+        "stoploss": trade.stoploss(),
+        "takeprofit": trade.takeprofit(),
+        "exitPrice": trade.exit_price(),
+        "pl": pl,
+        "createdAt": trade.created_at(),
+        "filledAt": trade.filled_at(),
+        "closedAt": trade.closed_at(),
+        "entryPrice": trade.price(),
+        "entryMargin": trade.margin(),
+        "open": false,
+        "running": !trade.closed(),
+        "canceled": false,
+        "closed": trade.closed(),
+        "sumFundingFees": 0,
+        "clientId": trade.client_id(),
+    })
+}
+
+fn running_trade_json(trade: &dyn TradeRunning, market_price: Price) -> Value {
+    trade_json(trade, trade.est_pl(market_price).floor() as i64)
+}
+
+fn closed_trade_json(trade: &dyn TradeClosed) -> Value {
+    trade_json(trade, trade.pl())
+}
+
+fn cross_position_json(id: Uuid, position: &dyn CrossPositionCore, market_price: Price) -> Value {
+    json!({
+        "id": id,
+        "margin": position.margin(),
+        "quantity": position.quantity(),
+        "leverage": position.leverage(),
+        "entryPrice": position.entry_price(),
+        "runningMargin": position.running_margin(),
+        "initialMargin": position.initial_margin(),
+        "maintenanceMargin": position.maintenance_margin(),
+        "liquidation": position.liquidation(),
+        "tradingFees": position.trading_fees(),
+        "fundingFees": position.session_funding_fees(),
+        "totalPl": position.realized_pl(),
+        "deltaPl": position.est_running_pl(market_price),
+    })
+}
+
+fn cross_order_json(
+    id: Uuid,
+    now: DateTime<Utc>,
+    side: TradeSide,
+    quantity: OrderQuantity,
+    price: Price,
+    trading_fee: u64,
+    client_id: Option<ClientId>,
+) -> Value {
+    json!({
+        "id": id,
+        "type": "market",
+        "side": side,
+        "quantity": quantity,
+        "price": price,
+        "tradingFee": trading_fee,
+        "createdAt": now,
+        "filledAt": now,
+        "canceledAt": null,
+        "open": false,
+        "filled": true,
+        "canceled": false,
+        "clientId": client_id,
+    })
+}
+
+/// Parses the size of a new isolated trade from a request body holding either a `quantity` or a
+/// `margin` field.
+pub(super) fn parse_trade_size(body: &Value) -> ExchangeResult<TradeSize> {
+    if let Some(quantity) = body.get("quantity") {
+        let quantity: OrderQuantity =
+            serde_json::from_value(quantity.clone()).map_err(ExchangeError::bad_request)?;
+        return Ok(quantity.into());
+    }
+
+    if let Some(margin) = body.get("margin") {
+        let margin: Margin =
+            serde_json::from_value(margin.clone()).map_err(ExchangeError::bad_request)?;
+        return Ok(margin.into());
+    }
+
+    Err(ExchangeError::bad_request("missing `quantity` or `margin`"))
+}
@@ -0,0 +1,9 @@
+mod error;
+mod exchange;
+mod price_feed;
+mod server;
+pub(crate) mod stream;
+
+pub use error::MockLnmError;
+pub use price_feed::MockPriceFeed;
+pub use server::{MockLnmConfig, MockLnmServer, MockRequest};
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{db::models::OhlcCandleRow, util::DateTimeExt};
+
+/// Price feed served by a [`MockLnmServer`](super::MockLnmServer).
+///
+/// The feed is a sequence of timestamped prices, where each price holds until the next one. OHLC
+/// candles, funding settlement fixing prices and the market price used to fill orders are all
+/// derived from it. Prices that fall in the future are only visible once their time is reached, so
+/// a scripted feed plays out in real time.
+#[derive(Debug, Clone)]
+pub struct MockPriceFeed {
+    points: BTreeMap<DateTime<Utc>, f64>,
+    volumes: BTreeMap<DateTime<Utc>, u64>,
+}
+
+impl MockPriceFeed {
+    /// Creates a feed that holds `price` from `start` onwards.
+    pub fn constant(start: DateTime<Utc>, price: f64) -> Self {
+        Self::scripted(vec![(start, price)])
+    }
+
+    /// Creates a feed from scripted `(time, price)` points. Each price holds until the time of the
+    /// next point. The earliest point marks the start of the available price history.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `points` is empty.
+    pub fn scripted(points: Vec<(DateTime<Utc>, f64)>) -> Self {
+        assert!(
+            !points.is_empty(),
+            "price feed must have at least one point"
+        );
+
+        Self {
+            points: points.into_iter().collect(),
+            volumes: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a feed replaying recorded OHLC candles, such as candles previously synced to a
+    /// database. Each candle is served with its original open, high, low, close and volume.
+    ///
+    /// Use [`shifted`](Self::shifted) to move a recording taken in the past up to the current time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `candles` is empty.
+    pub fn recorded(candles: &[OhlcCandleRow]) -> Self {
+        assert!(
+            !candles.is_empty(),
+            "price feed must have at least one candle"
+        );
+
+        let mut points = BTreeMap::new();
+        let mut volumes = BTreeMap::new();
+
+        for candle in candles {
+            let time = candle.time.floor_minute();
+
+            // Visit the extremes in the order that matches the candle's direction
+            let (first, second) = if candle.close >= candle.open {
+                (candle.low, candle.high)
+            } else {
+                (candle.high, candle.low)
+            };
+
+            points.insert(time, candle.open);
+            points.insert(time + Duration::seconds(15), first);
+            points.insert(time + Duration::seconds(30), second);
+            points.insert(time + Duration::seconds(45), candle.close);
+            volumes.insert(time, candle.volume.max(0) as u64);
+        }
+
+        Self { points, volumes }
+    }
+
+    /// Returns the feed with all its points moved by `offset`. Offsets are rounded down to whole
+    /// minutes, so recorded candles stay aligned to the minute.
+    pub fn shifted(self, offset: Duration) -> Self {
+        let offset = Duration::minutes(offset.num_minutes());
+
+        Self {
+            points: self
+                .points
+                .into_iter()
+                .map(|(time, price)| (time + offset, price))
+                .collect(),
+            volumes: self
+                .volumes
+                .into_iter()
+                .map(|(time, volume)| (time + offset, volume))
+                .collect(),
+        }
+    }
+
+    /// Returns the time of the earliest price in the feed.
+    pub fn start(&self) -> DateTime<Utc> {
+        *self.points.keys().next().expect("feed can't be empty")
+    }
+
+    /// Returns the price in effect at `time`, or `None` if `time` is before the start of the feed.
+    pub fn price_at(&self, time: DateTime<Utc>) -> Option<f64> {
+        self.points
+            .range(..=time)
+            .next_back()
+            .map(|(_, price)| *price)
+    }
+
+    pub(super) fn insert(&mut self, time: DateTime<Utc>, price: f64) {
+        self.points.insert(time, price);
+    }
+
+    /// Returns the prices set in `(from, to]`, in chronological order.
+    pub(super) fn points_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> impl Iterator<Item = (DateTime<Utc>, f64)> + '_ {
+        use std::ops::Bound::{Excluded, Included};
+
+        self.points
+            .range((Excluded(from), Included(to)))
+            .map(|(time, price)| (*time, *price))
+    }
+
+    /// Builds the one-minute candle starting at `time`, considering only prices set up to `now`.
+    /// Returns `None` if no price is available for that minute yet.
+    pub(super) fn candle(
+        &self,
+        time: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Option<(f64, f64, f64, f64, u64)> {
+        let end = (time + Duration::minutes(1)).min(now + Duration::nanoseconds(1));
+
+        let mut prices = self
+            .price_at(time)
+            .into_iter()
+            .chain(self.points.range(time..end).map(|(_, price)| *price))
+            .peekable();
+
+        let open = *prices.peek()?;
+        let (mut high, mut low, mut close) = (open, open, open);
+
+        for price in prices {
+            high = high.max(price);
+            low = low.min(price);
+            close = price;
+        }
+
+        let volume = self.volumes.get(&time).copied().unwrap_or(0);
+
+        Some((open, high, low, close, volume))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripted_feed_candles() {
+        let start = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let feed = MockPriceFeed::scripted(vec![
+            (start, 100_000.),
+            (start + Duration::seconds(20), 101_000.),
+            (start + Duration::seconds(40), 99_500.),
+            (start + Duration::seconds(90), 100_500.),
+        ]);
+        let now = start + Duration::hours(1);
+
+        assert_eq!(feed.price_at(start - Duration::seconds(1)), None);
+        assert_eq!(feed.price_at(start + Duration::seconds(30)), Some(101_000.));
+
+        assert_eq!(
+            feed.candle(start, now),
+            Some((100_000., 101_000., 99_500., 99_500., 0))
+        );
+        assert_eq!(
+            feed.candle(start + Duration::minutes(1), now),
+            Some((99_500., 100_500., 99_500., 100_500., 0))
+        );
+        assert_eq!(feed.candle(start - Duration::minutes(1), now), None);
+
+        // Prices after `now` are not visible yet
+        assert_eq!(
+            feed.candle(start + Duration::minutes(1), start + Duration::seconds(80)),
+            Some((99_500., 99_500., 99_500., 99_500., 0))
+        );
+    }
+
+    #[test]
+    fn test_recorded_feed_roundtrip() {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let candle = OhlcCandleRow {
+            time,
+            open: 100_000.,
+            high: 100_800.,
+            low: 99_200.,
+            close: 99_600.,
+            volume: 42,
+            created_at: time,
+            updated_at: time,
+            stable: true,
+        };
+
+        let feed = MockPriceFeed::recorded(&[candle]).shifted(Duration::minutes(10));
+        let shifted_time = time + Duration::minutes(10);
+
+        assert_eq!(feed.start(), shifted_time);
+        assert_eq!(
+            feed.candle(shifted_time, shifted_time + Duration::hours(1)),
+            Some((100_000., 100_800., 99_200., 99_600., 42))
+        );
+    }
+}
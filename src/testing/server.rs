@@ -0,0 +1,679 @@
+use std::{
+    convert::Infallible,
+    net::{Ipv4Addr, SocketAddr},
+    num::NonZeroU64,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use fastwebsockets::upgrade;
+use http_body_util::{BodyExt, Full};
+use hyper::{
+    Method, Request, Response, StatusCode,
+    body::{Bytes, Incoming},
+    server::conn::http1,
+    service::service_fn,
+};
+use hyper_util::rt::TokioIo;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, sync::Mutex as AsyncMutex, time};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{
+    ClientId, CrossLeverage, CrossPosition, Leverage, OrderQuantity, Price, Trade, TradeSide,
+};
+
+use crate::util::AbortOnDropHandle;
+
+use super::{
+    error::{MockLnmError, Result},
+    exchange::{ExchangeError, ExchangeResult, MockExchange, parse_trade_size},
+    price_feed::MockPriceFeed,
+    stream::{MockStream, STREAM_API_PATH},
+};
+
+/// Configuration for a [`MockLnmServer`].
+#[derive(Debug, Clone)]
+pub struct MockLnmConfig {
+    balance: u64,
+    funding_rate: f64,
+    stream_tick_interval: Duration,
+}
+
+impl Default for MockLnmConfig {
+    fn default() -> Self {
+        Self {
+            balance: 10_000_000,
+            funding_rate: 0.0001,
+            stream_tick_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl MockLnmConfig {
+    /// Returns the starting account balance in satoshis.
+    pub fn balance(&self) -> u64 {
+        self.balance
+    }
+
+    /// Returns the funding rate applied at every funding settlement.
+    pub fn funding_rate(&self) -> f64 {
+        self.funding_rate
+    }
+
+    /// Returns the interval at which the market price is published to the Stream API clients.
+    pub fn stream_tick_interval(&self) -> Duration {
+        self.stream_tick_interval
+    }
+
+    /// Sets the starting account balance in satoshis.
+    ///
+    /// Default: `10_000_000`
+    pub fn with_balance(mut self, balance: u64) -> Self {
+        self.balance = balance;
+        self
+    }
+
+    /// Sets the funding rate applied at every funding settlement.
+    ///
+    /// Default: `0.0001`
+    pub fn with_funding_rate(mut self, funding_rate: f64) -> Self {
+        self.funding_rate = funding_rate;
+        self
+    }
+
+    /// Sets the interval at which the market price is published to the Stream API clients. Prices
+    /// set with [`MockLnmServer::set_price`] are published immediately.
+    ///
+    /// Default: `1` second
+    pub fn with_stream_tick_interval(mut self, interval: Duration) -> Self {
+        self.stream_tick_interval = interval;
+        self
+    }
+}
+
+/// A request received by a [`MockLnmServer`].
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    method: Method,
+    path: String,
+    query: Vec<(String, String)>,
+    body: Option<Value>,
+    authenticated: bool,
+}
+
+impl MockRequest {
+    /// Returns the HTTP method of the request.
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the request path, such as `/futures/isolated/trade`. Stream API connections are
+    /// recorded as `GET` requests to `/v1`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the decoded query parameters of the request.
+    pub fn query(&self) -> &[(String, String)] {
+        &self.query
+    }
+
+    /// Returns the JSON body of the request, if any.
+    pub fn body(&self) -> Option<&Value> {
+        self.body.as_ref()
+    }
+
+    /// Returns `true` if the request carried LN Markets authentication headers.
+    pub fn authenticated(&self) -> bool {
+        self.authenticated
+    }
+
+    /// Returns `true` if the request changes the account state, such as opening a trade or
+    /// depositing to the cross-margin account.
+    pub fn is_trading_action(&self) -> bool {
+        self.method != Method::GET && Self::requires_auth(&self.path)
+    }
+
+    fn requires_auth(path: &str) -> bool {
+        path == "/account"
+            || path.starts_with("/futures/isolated/")
+            || path.starts_with("/futures/cross/")
+    }
+}
+
+struct SharedState {
+    exchange: AsyncMutex<MockExchange>,
+    requests: Mutex<Vec<MockRequest>>,
+    stream: MockStream,
+}
+
+impl SharedState {
+    fn record(&self, request: MockRequest) {
+        self.requests
+            .lock()
+            .expect("`MockLnmServer` mutex can't be poisoned")
+            .push(request);
+    }
+}
+
+/// Offline server mimicking the LN Markets v3 REST API and the last price feed of the Stream API.
+///
+/// The server serves OHLC candles, funding settlements and the ticker derived from a
+/// [`MockPriceFeed`], and manages a fake account whose isolated trades and cross-margin position
+/// are simulated with the same rules as backtests. Point a [`SyncConfig`] or [`LiveTradeConfig`] to
+/// it with `with_rest_api_endpoint(server.rest_api_endpoint())`. Any API credentials are accepted.
+///
+/// The market price is also published as last price updates on a WebSocket endpoint speaking the
+/// JSON-RPC protocol of the Stream API. Point engines to it with
+/// `with_stream_api_endpoint(server.stream_api_endpoint())`. Since the stream client of `lnm-sdk`
+/// only connects to `wss` endpoints authenticated with public root certificates, engines built
+/// with the `testing` feature connect to plain `ws` endpoints with a minimal client of their own,
+/// which subscribes to the last price topic the same way.
+///
+/// The server is stopped when dropped.
+///
+/// # Limitations
+///
+/// Only the last price topic of the Stream API is mocked. Only market orders are supported,
+/// trading fees are fixed at 0.1%, and funding settlements use a constant funding rate.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use chrono::{Duration, Utc};
+/// use quantoxide::{
+///     sync::SyncConfig,
+///     testing::{MockLnmConfig, MockLnmServer, MockPriceFeed},
+/// };
+///
+/// let feed = MockPriceFeed::constant(Utc::now() - Duration::days(1), 100_000.);
+/// let server = MockLnmServer::start(MockLnmConfig::default(), feed).await?;
+///
+/// let config = SyncConfig::default()
+///     .with_rest_api_endpoint(server.rest_api_endpoint())
+///     .with_stream_api_endpoint(server.stream_api_endpoint());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`SyncConfig`]: crate::sync::SyncConfig
+/// [`LiveTradeConfig`]: crate::trade::LiveTradeConfig
+pub struct MockLnmServer {
+    addr: SocketAddr,
+    state: Arc<SharedState>,
+    _handle: AbortOnDropHandle<()>,
+    _stream_handle: AbortOnDropHandle<()>,
+}
+
+impl MockLnmServer {
+    /// Starts a server listening on a random local port.
+    pub async fn start(config: MockLnmConfig, feed: MockPriceFeed) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .await
+            .map_err(MockLnmError::Bind)?;
+        let addr = listener.local_addr().map_err(MockLnmError::Bind)?;
+
+        let state = Arc::new(SharedState {
+            exchange: AsyncMutex::new(MockExchange::new(feed, config.balance, config.funding_rate)),
+            requests: Mutex::new(Vec::new()),
+            stream: MockStream::new(),
+        });
+
+        let handle = tokio::spawn({
+            let state = state.clone();
+            async move {
+                loop {
+                    let Ok((stream, _)) = listener.accept().await else {
+                        continue;
+                    };
+
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(|req| handle_request(state.clone(), req));
+
+                        let _ = http1::Builder::new()
+                            .serve_connection(TokioIo::new(stream), service)
+                            .with_upgrades()
+                            .await;
+                    });
+                }
+            }
+        });
+
+        let stream_handle = tokio::spawn({
+            let state = state.clone();
+            let mut interval = time::interval(config.stream_tick_interval);
+            async move {
+                loop {
+                    interval.tick().await;
+
+                    let now = Utc::now();
+                    let price = state.exchange.lock().await.price(now);
+                    state.stream.send_price(now, price);
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            _handle: handle.into(),
+            _stream_handle: stream_handle.into(),
+        })
+    }
+
+    /// Returns the REST API endpoint of the server, such as `http://127.0.0.1:41234`.
+    pub fn rest_api_endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns the Stream API endpoint of the server, such as `ws://127.0.0.1:41234/v1`.
+    pub fn stream_api_endpoint(&self) -> String {
+        format!("ws://{}{STREAM_API_PATH}", self.addr)
+    }
+
+    /// Closes the connections of all Stream API clients, as the LN Markets API does on
+    /// maintenance. Clients are expected to reconnect and subscribe again.
+    pub fn disconnect_stream_clients(&self) {
+        self.state.stream.disconnect_clients();
+    }
+
+    /// Sets the market price from now on and publishes it to the Stream API clients. Trades
+    /// reaching their stoploss, takeprofit or liquidation at `price` are closed.
+    pub async fn set_price(&self, price: f64) -> Result<()> {
+        let now = Utc::now();
+
+        self.state
+            .exchange
+            .lock()
+            .await
+            .set_price(now, price)
+            .await?;
+
+        self.state.stream.send_price(now, price);
+
+        Ok(())
+    }
+
+    /// Returns the current market price.
+    pub async fn price(&self) -> f64 {
+        self.state.exchange.lock().await.price(Utc::now())
+    }
+
+    /// Returns the identifier of the simulated account.
+    pub async fn account_id(&self) -> Uuid {
+        self.state.exchange.lock().await.account_id()
+    }
+
+    /// Returns the current account balance in satoshis.
+    pub async fn balance(&self) -> Result<u64> {
+        let mut exchange = self.state.exchange.lock().await;
+        exchange.advance(Utc::now()).await?;
+
+        Ok(exchange.trading_state().await?.balance())
+    }
+
+    /// Returns the running isolated trades of the account, newest first.
+    pub async fn running_trades(&self) -> Result<Vec<Trade>> {
+        let mut exchange = self.state.exchange.lock().await;
+        exchange.advance(Utc::now()).await?;
+
+        parse_response(exchange.running_trades().await?)
+    }
+
+    /// Returns the closed isolated trades of the account, newest first.
+    pub async fn closed_trades(&self) -> Result<Vec<Trade>> {
+        let mut exchange = self.state.exchange.lock().await;
+        exchange.advance(Utc::now()).await?;
+
+        let mut page = exchange.closed_trades(Some(u64::MAX)).await?;
+
+        parse_response(page["data"].take())
+    }
+
+    /// Returns the cross-margin position of the account.
+    pub async fn cross_position(&self) -> Result<CrossPosition> {
+        let mut exchange = self.state.exchange.lock().await;
+        exchange.advance(Utc::now()).await?;
+
+        parse_response(exchange.cross_position().await?)
+    }
+
+    /// Returns the requests received so far, in arrival order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.state
+            .requests
+            .lock()
+            .expect("`MockLnmServer` mutex can't be poisoned")
+            .clone()
+    }
+}
+
+impl Drop for MockLnmServer {
+    fn drop(&mut self) {
+        self.state.stream.disconnect_clients();
+    }
+}
+
+impl From<ExchangeError> for MockLnmError {
+    fn from(value: ExchangeError) -> Self {
+        Self::Exchange {
+            status: value.status,
+            message: value.message,
+        }
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value).map_err(MockLnmError::InvalidResponse)
+}
+
+async fn handle_request(
+    state: Arc<SharedState>,
+    mut req: Request<Incoming>,
+) -> std::result::Result<Response<Full<Bytes>>, Infallible> {
+    if upgrade::is_upgrade_request(&req) {
+        return Ok(upgrade_stream(&state, &mut req));
+    }
+
+    let (parts, body) = req.into_parts();
+
+    let path = parts.uri.path().to_string();
+    let query = parts.uri.query().map(parse_query).unwrap_or_default();
+    let authenticated = parts.headers.contains_key("lnm-access-key");
+
+    let body = match body.collect().await {
+        Ok(collected) => {
+            let bytes = collected.to_bytes();
+            (!bytes.is_empty())
+                .then(|| serde_json::from_slice::<Value>(&bytes).ok())
+                .flatten()
+        }
+        Err(_) => None,
+    };
+
+    let request = MockRequest {
+        method: parts.method,
+        path,
+        query,
+        body,
+        authenticated,
+    };
+
+    state.record(request.clone());
+
+    let (status, body) = match route(&state, &request).await {
+        Ok(value) => (StatusCode::OK, value),
+        Err(e) => (
+            StatusCode::from_u16(e.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            json!({ "message": e.message }),
+        ),
+    };
+
+    let response = Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Full::new(Bytes::from(body.to_string())))
+        .expect("valid response");
+
+    Ok(response)
+}
+
+/// Upgrades a Stream API connection, serving the client on a separate task.
+fn upgrade_stream(state: &Arc<SharedState>, req: &mut Request<Incoming>) -> Response<Full<Bytes>> {
+    let path = req.uri().path().to_string();
+
+    state.record(MockRequest {
+        method: req.method().clone(),
+        path: path.clone(),
+        query: req.uri().query().map(parse_query).unwrap_or_default(),
+        body: None,
+        authenticated: req.headers().contains_key("lnm-access-key"),
+    });
+
+    let empty_response = |status| {
+        Response::builder()
+            .status(status)
+            .body(Full::default())
+            .expect("valid response")
+    };
+
+    if path != STREAM_API_PATH {
+        return empty_response(StatusCode::NOT_FOUND);
+    }
+
+    let Ok((response, upgrade_fut)) = upgrade::upgrade(req) else {
+        return empty_response(StatusCode::BAD_REQUEST);
+    };
+
+    let state = state.clone();
+    tokio::spawn(async move { state.stream.serve(upgrade_fut).await });
+
+    let (parts, _) = response.into_parts();
+    Response::from_parts(parts, Full::default())
+}
+
+async fn route(state: &SharedState, request: &MockRequest) -> ExchangeResult<Value> {
+    if MockRequest::requires_auth(&request.path) && !request.authenticated {
+        return Err(ExchangeError {
+            status: 401,
+            message: "missing authentication headers".to_string(),
+        });
+    }
+
+    let now = Utc::now();
+
+    let mut exchange = state.exchange.lock().await;
+    exchange.advance(now).await?;
+
+    let query = Query(&request.query);
+    let body = Body(request.body.as_ref());
+
+    match (request.method.clone(), request.path.as_str()) {
+        (Method::GET, "/futures/candles") => {
+            Ok(exchange.candles(now, query.time("cursor")?, query.u64("limit")?))
+        }
+        (Method::GET, "/futures/funding-settlements") => Ok(exchange.funding_settlements(
+            now,
+            query.time("from")?,
+            query.time("to")?,
+            query.u64("limit")?,
+        )),
+        (Method::GET, "/futures/ticker") => Ok(exchange.ticker(now)),
+        (Method::GET, "/account") => exchange.account().await,
+        (Method::GET, "/futures/isolated/trades/running") => exchange.running_trades().await,
+        (Method::GET, "/futures/isolated/trades/closed") => {
+            exchange.closed_trades(query.u64("limit")?).await
+        }
+        (Method::POST, "/futures/isolated/trade") => {
+            let side: TradeSide = body.field("side")?;
+            let size = parse_trade_size(body.value()?)?;
+            let leverage: Leverage = body.field("leverage")?;
+            let stoploss: Option<Price> = body.price_option("stoploss")?;
+            let takeprofit: Option<Price> = body.price_option("takeprofit")?;
+            let client_id: Option<ClientId> = body.optional_field("clientId")?;
+
+            exchange
+                .new_trade(side, size, leverage, stoploss, takeprofit, client_id)
+                .await
+        }
+        (Method::PUT, "/futures/isolated/trade/stoploss") => {
+            let id: Uuid = body.field("id")?;
+            let stoploss: Price = body.field("value")?;
+
+            exchange.update_stoploss(id, stoploss).await
+        }
+        (Method::POST, "/futures/isolated/trade/add-margin") => {
+            let id: Uuid = body.field("id")?;
+            let amount: NonZeroU64 = body.field("amount")?;
+
+            exchange.add_margin(id, amount).await
+        }
+        (Method::POST, "/futures/isolated/trade/cash-in") => {
+            let id: Uuid = body.field("id")?;
+            let amount: NonZeroU64 = body.field("amount")?;
+
+            exchange.cash_in(id, amount).await
+        }
+        (Method::POST, "/futures/isolated/trade/close") => {
+            let id: Uuid = body.field("id")?;
+
+            exchange.close_trade(id).await
+        }
+        // Only market orders are supported, so there are never open orders to cancel
+        (Method::POST, "/futures/isolated/trades/cancel-all")
+        | (Method::POST, "/futures/cross/orders/cancel-all") => Ok(json!([])),
+        (Method::GET, "/futures/cross/position") => exchange.cross_position().await,
+        (Method::POST, "/futures/cross/deposit") => {
+            let amount: NonZeroU64 = body.field("amount")?;
+
+            exchange.cross_deposit(amount).await
+        }
+        (Method::POST, "/futures/cross/withdraw") => {
+            let amount: NonZeroU64 = body.field("amount")?;
+
+            exchange.cross_withdraw(amount).await
+        }
+        (Method::PUT, "/futures/cross/leverage") => {
+            let leverage: CrossLeverage = body.field("leverage")?;
+
+            exchange.cross_set_leverage(leverage).await
+        }
+        (Method::POST, "/futures/cross/order") => {
+            let side: TradeSide = body.field("side")?;
+            let quantity: OrderQuantity = body.field("quantity")?;
+            let client_id: Option<ClientId> = body.optional_field("clientId")?;
+
+            exchange.cross_order(now, side, quantity, client_id).await
+        }
+        (Method::POST, "/futures/cross/position/close") => exchange.cross_close_position(now).await,
+        (method, path) => Err(ExchangeError::not_found(format!(
+            "route {method} {path} is not supported by the mock server"
+        ))),
+    }
+}
+
+struct Query<'a>(&'a [(String, String)]);
+
+impl Query<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn time(&self, key: &str) -> ExchangeResult<Option<DateTime<Utc>>> {
+        self.get(key)
+            .map(|value| {
+                DateTime::parse_from_rfc3339(value)
+                    .map(|time| time.with_timezone(&Utc))
+                    .map_err(|e| ExchangeError::bad_request(format!("invalid `{key}`: {e}")))
+            })
+            .transpose()
+    }
+
+    fn u64(&self, key: &str) -> ExchangeResult<Option<u64>> {
+        self.get(key)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|e| ExchangeError::bad_request(format!("invalid `{key}`: {e}")))
+            })
+            .transpose()
+    }
+}
+
+struct Body<'a>(Option<&'a Value>);
+
+impl Body<'_> {
+    fn value(&self) -> ExchangeResult<&Value> {
+        self.0
+            .ok_or_else(|| ExchangeError::bad_request("missing JSON body"))
+    }
+
+    fn optional_field<T: DeserializeOwned>(&self, key: &str) -> ExchangeResult<Option<T>> {
+        match self.value()?.get(key) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => serde_json::from_value(value.clone())
+                .map(Some)
+                .map_err(|e| ExchangeError::bad_request(format!("invalid `{key}`: {e}"))),
+        }
+    }
+
+    fn field<T: DeserializeOwned>(&self, key: &str) -> ExchangeResult<T> {
+        self.optional_field(key)?
+            .ok_or_else(|| ExchangeError::bad_request(format!("missing `{key}`")))
+    }
+
+    /// Parses an optional price, where `0` stands for no price as in the LN Markets API.
+    fn price_option(&self, key: &str) -> ExchangeResult<Option<Price>> {
+        if self.value()?.get(key).and_then(Value::as_f64) == Some(0.) {
+            return Ok(None);
+        }
+
+        self.optional_field(key)
+    }
+}
+
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |byte: u8| (byte as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_decodes_values() {
+        let query = parse_query("cursor=2025-01-01T00%3A00%3A00.000Z&limit=10&range=1m");
+
+        assert_eq!(
+            query,
+            vec![
+                ("cursor".to_string(), "2025-01-01T00:00:00.000Z".to_string()),
+                ("limit".to_string(), "10".to_string()),
+                ("range".to_string(), "1m".to_string()),
+            ]
+        );
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("a%2"), "a%2");
+    }
+}
@@ -0,0 +1,425 @@
+use std::future::Future;
+
+use chrono::{DateTime, Utc};
+use fastwebsockets::{FragmentCollector, Frame, OpCode, Payload, handshake, upgrade::UpgradeFut};
+use http_body_util::Empty;
+use hyper::{
+    Request, Uri,
+    body::Bytes,
+    header::{CONNECTION, HOST, UPGRADE},
+    rt::Executor,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::{
+    net::TcpStream,
+    sync::broadcast::{self, error::RecvError},
+};
+
+use lnm_sdk::{
+    rest::v3::models::Price,
+    stream::v1::{
+        StreamConnectionStatus,
+        models::{LastPrice, StreamTopic, StreamUpdate},
+    },
+};
+
+use super::error::{MockLnmError, Result};
+
+/// Path of the Stream API endpoint served by a [`MockLnmServer`](super::MockLnmServer).
+pub(super) const STREAM_API_PATH: &str = "/v1";
+
+/// Id of the subscription request sent by [`subscribe_last_price`].
+const SUBSCRIBE_REQUEST_ID: &str = "subscribe";
+
+/// JSON-RPC request sent by a Stream API client.
+#[derive(Deserialize)]
+struct RpcRequest {
+    id: Value,
+    method: String,
+    params: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct TopicsParams {
+    topics: Vec<StreamTopic>,
+}
+
+/// JSON-RPC message sent by the Stream API, either a response or a subscription notification.
+#[derive(Deserialize)]
+struct RpcMessage {
+    id: Option<Value>,
+    method: Option<String>,
+    params: Option<Value>,
+    error: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct SubscriptionParams {
+    topic: StreamTopic,
+    data: Value,
+}
+
+/// Stream API of a [`MockLnmServer`](super::MockLnmServer).
+///
+/// Clients connect to a plain WebSocket endpoint and speak the JSON-RPC protocol of the LN Markets
+/// Stream v1 API. Only the last price topic is supported, and the market price of the server is
+/// published to every client subscribed to it.
+#[derive(Debug, Clone)]
+pub(super) struct MockStream {
+    price_tx: broadcast::Sender<(DateTime<Utc>, f64)>,
+    disconnect_tx: broadcast::Sender<()>,
+}
+
+impl MockStream {
+    pub fn new() -> Self {
+        let (price_tx, _) = broadcast::channel(1_000);
+        let (disconnect_tx, _) = broadcast::channel(1);
+
+        Self {
+            price_tx,
+            disconnect_tx,
+        }
+    }
+
+    pub fn send_price(&self, time: DateTime<Utc>, price: f64) {
+        // Nothing to do if no client is connected
+        let _ = self.price_tx.send((time, price));
+    }
+
+    /// Closes the connections of all clients, which are expected to reconnect.
+    pub fn disconnect_clients(&self) {
+        let _ = self.disconnect_tx.send(());
+    }
+
+    /// Serves a client once the WebSocket upgrade completes, until the client or the server closes
+    /// the connection.
+    pub async fn serve(&self, upgrade: UpgradeFut) {
+        let Ok(ws) = upgrade.await else {
+            return;
+        };
+        let mut ws = FragmentCollector::new(ws);
+
+        let mut price_rx = self.price_tx.subscribe();
+        let mut disconnect_rx = self.disconnect_tx.subscribe();
+        let mut subscribed = false;
+
+        loop {
+            tokio::select! {
+                _ = disconnect_rx.recv() => {
+                    let _ = ws.write_frame(Frame::close(1001, &[])).await;
+                    return;
+                }
+                price_res = price_rx.recv() => {
+                    let (time, price) = match price_res {
+                        Ok(update) => update,
+                        Err(RecvError::Lagged(_)) => continue,
+                        Err(RecvError::Closed) => return,
+                    };
+
+                    if !subscribed {
+                        continue;
+                    }
+
+                    let notification = last_price_notification(time, price);
+                    if ws.write_frame(text_frame(&notification)).await.is_err() {
+                        return;
+                    }
+                }
+                frame_res = ws.read_frame() => {
+                    let Ok(frame) = frame_res else {
+                        return;
+                    };
+
+                    match frame.opcode {
+                        OpCode::Text => {
+                            let response = handle_rpc(&frame.payload, &mut subscribed);
+                            if ws.write_frame(text_frame(&response)).await.is_err() {
+                                return;
+                            }
+                        }
+                        OpCode::Close => return,
+                        // Pings are answered automatically
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn text_frame(value: &Value) -> Frame<'static> {
+    Frame::text(Payload::Owned(value.to_string().into_bytes()))
+}
+
+fn last_price_notification(time: DateTime<Utc>, price: f64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "subscription",
+        "params": {
+            "topic": StreamTopic::FuturesInverseBtcUsdLastPrice,
+            "data": {
+                "time": time,
+                "lastPrice": Price::bounded(price),
+            },
+        },
+    })
+}
+
+/// Answers a JSON-RPC request, tracking whether the client is subscribed to the last price topic.
+fn handle_rpc(payload: &[u8], subscribed: &mut bool) -> Value {
+    let Ok(request) = serde_json::from_slice::<RpcRequest>(payload) else {
+        return rpc_error(Value::Null, -32700, "parse error");
+    };
+
+    let topics = || {
+        request
+            .params
+            .clone()
+            .and_then(|params| serde_json::from_value::<TopicsParams>(params).ok())
+            .map(|params| params.topics)
+            .filter(|topics| {
+                topics
+                    .iter()
+                    .all(|topic| *topic == StreamTopic::FuturesInverseBtcUsdLastPrice)
+            })
+    };
+
+    let result = match request.method.as_str() {
+        "hello" => json!({ "version": "mock" }),
+        "ping" => json!("pong"),
+        "time" => json!({ "time": Utc::now().timestamp_millis() }),
+        "subscribe" => match topics() {
+            Some(topics) => {
+                *subscribed |= !topics.is_empty();
+                json!({ "subscribed": topics })
+            }
+            None => return rpc_error(request.id, -32602, "only the last price topic is supported"),
+        },
+        "unsubscribe" => match topics() {
+            Some(topics) => {
+                *subscribed &= topics.is_empty();
+                json!({ "unsubscribed": topics })
+            }
+            None => return rpc_error(request.id, -32602, "only the last price topic is supported"),
+        },
+        "unsubscribeAll" => {
+            let unsubscribed = if *subscribed {
+                vec![StreamTopic::FuturesInverseBtcUsdLastPrice]
+            } else {
+                Vec::new()
+            };
+            *subscribed = false;
+            json!({ "unsubscribed": unsubscribed })
+        }
+        method => {
+            let message = format!("method `{method}` is not supported by the mock server");
+            return rpc_error(request.id, -32601, &message);
+        }
+    };
+
+    json!({ "jsonrpc": "2.0", "id": request.id, "result": result })
+}
+
+fn rpc_error(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+struct SpawnExecutor;
+
+impl<Fut> Executor<Fut> for SpawnExecutor
+where
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    fn execute(&self, fut: Fut) {
+        tokio::spawn(fut);
+    }
+}
+
+/// Returns `true` if the Stream API `endpoint` is a plain `ws` endpoint, such as the one served by
+/// a [`MockLnmServer`](super::MockLnmServer).
+pub(crate) fn is_plain_stream_endpoint(endpoint: &str) -> bool {
+    endpoint.starts_with("ws://")
+}
+
+/// Connects to a plain `ws` Stream API endpoint and subscribes to the last price topic, with the
+/// same JSON-RPC requests as the `lnm-sdk` stream client. Last price updates are forwarded to the
+/// returned receiver, followed by a [`StreamConnectionStatus::Disconnected`] update once the
+/// connection is closed. The connection is closed once all receivers are dropped.
+pub(crate) async fn subscribe_last_price(
+    endpoint: &str,
+) -> Result<broadcast::Receiver<StreamUpdate>> {
+    let uri: Uri = endpoint
+        .parse()
+        .map_err(|_| MockLnmError::Stream(format!("invalid endpoint `{endpoint}`")))?;
+    let (Some(host), Some(authority)) = (uri.host(), uri.authority()) else {
+        return Err(MockLnmError::Stream(format!(
+            "invalid endpoint `{endpoint}`"
+        )));
+    };
+
+    let tcp_stream = TcpStream::connect((host, uri.port_u16().unwrap_or(80)))
+        .await
+        .map_err(MockLnmError::StreamConnect)?;
+
+    let req = Request::builder()
+        .method("GET")
+        .uri(&uri)
+        .header(HOST, authority.as_str())
+        .header(UPGRADE, "websocket")
+        .header(CONNECTION, "upgrade")
+        .header("Sec-WebSocket-Key", handshake::generate_key())
+        .header("Sec-WebSocket-Version", "13")
+        .body(Empty::<Bytes>::new())
+        .map_err(|e| MockLnmError::Stream(e.to_string()))?;
+
+    let (ws, _) = handshake::client(&SpawnExecutor, req, tcp_stream)
+        .await
+        .map_err(|e| MockLnmError::Stream(e.to_string()))?;
+    let mut ws = FragmentCollector::new(ws);
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "method": "subscribe",
+        "id": SUBSCRIBE_REQUEST_ID,
+        "params": { "topics": [StreamTopic::FuturesInverseBtcUsdLastPrice] },
+    });
+    ws.write_frame(text_frame(&request))
+        .await
+        .map_err(|e| MockLnmError::Stream(e.to_string()))?;
+
+    loop {
+        let frame = ws
+            .read_frame()
+            .await
+            .map_err(|e| MockLnmError::Stream(e.to_string()))?;
+
+        match frame.opcode {
+            OpCode::Text => {
+                let message: RpcMessage = serde_json::from_slice(&frame.payload)
+                    .map_err(MockLnmError::InvalidResponse)?;
+
+                if message.id.as_ref().and_then(Value::as_str) != Some(SUBSCRIBE_REQUEST_ID) {
+                    continue;
+                }
+
+                if let Some(error) = message.error {
+                    return Err(MockLnmError::Stream(format!(
+                        "subscription rejected: {error}"
+                    )));
+                }
+
+                break;
+            }
+            OpCode::Close => {
+                return Err(MockLnmError::Stream(
+                    "connection closed before subscribing".to_string(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    let (update_tx, update_rx) = broadcast::channel(1_000);
+
+    tokio::spawn(async move {
+        loop {
+            let update = match ws.read_frame().await {
+                Ok(frame) if frame.opcode == OpCode::Text => {
+                    match parse_last_price(&frame.payload) {
+                        Some(last_price) => StreamUpdate::FuturesInverseBtcUsdLastPrice(last_price),
+                        None => continue,
+                    }
+                }
+                Ok(frame) if frame.opcode != OpCode::Close => continue,
+                _ => {
+                    let _ = update_tx.send(StreamUpdate::ConnectionStatus(
+                        StreamConnectionStatus::Disconnected,
+                    ));
+                    return;
+                }
+            };
+
+            if update_tx.send(update).is_err() {
+                let _ = ws.write_frame(Frame::close(1000, &[])).await;
+                return;
+            }
+        }
+    });
+
+    Ok(update_rx)
+}
+
+fn parse_last_price(payload: &[u8]) -> Option<LastPrice> {
+    let message: RpcMessage = serde_json::from_slice(payload).ok()?;
+    if message.method.as_deref() != Some("subscription") {
+        return None;
+    }
+
+    let params: SubscriptionParams = serde_json::from_value(message.params?).ok()?;
+    if params.topic != StreamTopic::FuturesInverseBtcUsdLastPrice {
+        return None;
+    }
+
+    serde_json::from_value(params.data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: &str, params: Value) -> Vec<u8> {
+        json!({ "jsonrpc": "2.0", "id": "1", "method": method, "params": params })
+            .to_string()
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_handle_rpc_tracks_last_price_subscription() {
+        let mut subscribed = false;
+        let topics = json!({ "topics": ["futures/inverse/btc_usd/lastPrice"] });
+
+        let response = handle_rpc(&request("subscribe", topics.clone()), &mut subscribed);
+        assert!(subscribed);
+        assert_eq!(response["id"], "1");
+        assert_eq!(
+            response["result"]["subscribed"],
+            json!(["futures/inverse/btc_usd/lastPrice"])
+        );
+
+        let response = handle_rpc(&request("unsubscribe", topics), &mut subscribed);
+        assert!(!subscribed);
+        assert_eq!(
+            response["result"]["unsubscribed"],
+            json!(["futures/inverse/btc_usd/lastPrice"])
+        );
+
+        let response = handle_rpc(&request("ping", Value::Null), &mut subscribed);
+        assert_eq!(response["result"], "pong");
+    }
+
+    #[test]
+    fn test_handle_rpc_rejects_unsupported_topics() {
+        let mut subscribed = false;
+        let topics = json!({ "topics": ["futures/inverse/btc_usd/ticker"] });
+
+        let response = handle_rpc(&request("subscribe", topics), &mut subscribed);
+        assert!(!subscribed);
+        assert_eq!(response["error"]["code"], -32602);
+    }
+
+    #[test]
+    fn test_last_price_notification_is_parsed() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let notification = last_price_notification(time, 100_000.);
+
+        let last_price = parse_last_price(notification.to_string().as_bytes()).unwrap();
+        assert_eq!(last_price.time(), time);
+        assert_eq!(last_price.last_price(), Price::bounded(100_000.));
+    }
+}
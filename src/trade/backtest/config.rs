@@ -1,8 +1,15 @@
+use chrono::Duration;
 use lnm_sdk::rest::v3::models::PercentageCapped;
 
-use crate::shared::Lookback;
+use crate::{
+    db::{Database, RESERVED_KEY_PREFIX},
+    shared::Lookback,
+};
 
-use super::error::{BacktestError, Result};
+use super::{
+    super::{live::config::LiveTradeConfig, risk::RiskLimits},
+    error::{BacktestError, Result},
+};
 
 /// Minimum number of candles loaded per database batch during backtests.
 ///
@@ -16,6 +23,9 @@ pub struct BacktestConfig {
     trade_max_running_qtd: usize,
     fee_perc: PercentageCapped,
     trade_tsl_step_size: PercentageCapped,
+    risk_limits: Option<RiskLimits>,
+    operator_state_key: Option<String>,
+    signal_run: Option<String>,
 }
 
 impl Default for BacktestConfig {
@@ -25,6 +35,9 @@ impl Default for BacktestConfig {
             trade_max_running_qtd: 50,
             fee_perc: 0.1.try_into().expect("must be a valid `PercentageCapped`"),
             trade_tsl_step_size: PercentageCapped::MIN,
+            risk_limits: None,
+            operator_state_key: None,
+            signal_run: None,
         }
     }
 }
@@ -50,6 +63,49 @@ impl BacktestConfig {
         self.trade_tsl_step_size
     }
 
+    /// Returns the portfolio-level risk limits enforced on the operators' orders, if any.
+    pub fn risk_limits(&self) -> Option<&RiskLimits> {
+        self.risk_limits.as_ref()
+    }
+
+    /// Returns the database key under which the operator state is persisted, if any.
+    pub fn operator_state_key(&self) -> Option<&str> {
+        self.operator_state_key.as_deref()
+    }
+
+    /// Returns the run name under which emitted signals are persisted, if any.
+    pub fn signal_run(&self) -> Option<&str> {
+        self.signal_run.as_deref()
+    }
+
+    /// Ensures the operator state key doesn't use the reserved prefix, and that the database
+    /// accepts the writes needed to persist operator state and signals, if configured.
+    pub(super) fn validate_persistence(&self, db: &Database) -> Result<()> {
+        if let Some(key) = self.operator_state_key.as_deref()
+            && key.starts_with(RESERVED_KEY_PREFIX)
+        {
+            return Err(BacktestError::InvalidConfigurationReservedKey {
+                key: key.to_string(),
+            });
+        }
+
+        if !db.read_only() {
+            return Ok(());
+        }
+
+        if self.operator_state_key.is_some() {
+            return Err(BacktestError::InvalidConfigurationReadOnlyDatabase {
+                setting: "operator state",
+            });
+        }
+
+        if self.signal_run.is_some() {
+            return Err(BacktestError::InvalidConfigurationReadOnlyDatabase { setting: "signals" });
+        }
+
+        Ok(())
+    }
+
     /// Sets the size of the candlestick buffer (minimum [`MIN_BUFFER_SIZE`](crate::trade::MIN_BUFFER_SIZE)).
     ///
     /// Default: [`MIN_BUFFER_SIZE`](crate::trade::MIN_BUFFER_SIZE)
@@ -90,9 +146,114 @@ impl BacktestConfig {
         self.trade_tsl_step_size = trade_tsl_step_size;
         self
     }
+
+    /// Sets the portfolio-level risk limits enforced on the operators' orders. When set, each
+    /// simulated executor is wrapped in a [`RiskManager`](crate::trade::RiskManager) before being
+    /// passed to its operator. If `None`, only the executor limits apply.
+    ///
+    /// Default: `None`
+    pub fn with_risk_limits(mut self, risk_limits: Option<RiskLimits>) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    /// Sets the database key under which the operator state is persisted. The operator state is
+    /// saved under the key at the end of each simulated day, as a checkpoint, and once the
+    /// simulation finishes, along with the time from which a backtest can resume with it (the
+    /// following midnight UTC, or the end time). Parallel backtests persist the state of each
+    /// operator under `"{key}:{operator_name}"`. If `None`, operator state is not persisted.
+    /// Requires a database that isn't read-only. Keys starting with `"quantoxide:"` are reserved for
+    /// internal state.
+    ///
+    /// When set, the state stored under the key (if any) is restored before the simulation starts.
+    /// Since the simulated trades are not persisted alongside it, the simulation fails with
+    /// [`BacktestError::OperatorStateCheckpointMismatch`] unless the state was saved for a backtest
+    /// starting at the same start time. Runs that shouldn't resume a previous one must use a
+    /// different key.
+    ///
+    /// Default: `None`
+    pub fn with_operator_state_key(mut self, key: Option<String>) -> Self {
+        self.operator_state_key = key;
+        self
+    }
+
+    /// Sets the run name under which the signals emitted by signal-based operators are persisted.
+    /// When set, every signal is stored in the database with its simulated evaluation time, and
+    /// can be retrieved via [`Database::get_signals`](crate::db::Database::get_signals). Parallel
+    /// backtests store the signals of each operator under `"{run}:{operator_name}"`. If `None`,
+    /// signals are not persisted. Requires a database that isn't read-only.
+    ///
+    /// Default: `None`
+    pub fn with_signal_run(mut self, run: Option<String>) -> Self {
+        self.signal_run = run;
+        self
+    }
+}
+
+/// Configuration for the [`SignalResearchEngine`](crate::trade::SignalResearchEngine) controlling
+/// how forward returns are measured.
+pub struct SignalResearchConfig {
+    buffer_size: usize,
+    horizons: Vec<Duration>,
 }
 
-pub(super) struct SimulatedTradeExecutorConfig {
+impl Default for SignalResearchConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: MIN_BUFFER_SIZE,
+            horizons: [5, 15, 60, 240, 1_440]
+                .into_iter()
+                .map(Duration::minutes)
+                .collect(),
+        }
+    }
+}
+
+impl SignalResearchConfig {
+    /// Returns the size of the candlestick buffer used during the research run.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Returns the horizons at which forward returns are measured, in ascending order.
+    pub fn horizons(&self) -> &[Duration] {
+        &self.horizons
+    }
+
+    /// Sets the size of the candlestick buffer (minimum [`MIN_BUFFER_SIZE`](crate::trade::MIN_BUFFER_SIZE)).
+    ///
+    /// Default: [`MIN_BUFFER_SIZE`](crate::trade::MIN_BUFFER_SIZE)
+    pub fn with_buffer_size(mut self, size: usize) -> Result<Self> {
+        if size < MIN_BUFFER_SIZE {
+            return Err(BacktestError::InvalidConfigurationBufferSize { size });
+        }
+        self.buffer_size = size;
+        Ok(self)
+    }
+
+    /// Sets the horizons (in minutes) at which forward returns are measured after each signal.
+    /// At least one horizon must be provided, and all of them must be greater than 0.
+    ///
+    /// Default: `[5, 15, 60, 240, 1440]` minutes
+    pub fn with_horizons(mut self, mins: Vec<u64>) -> Result<Self> {
+        if mins.is_empty() || mins.iter().any(|&min| min == 0 || min > i32::MAX as u64) {
+            return Err(BacktestError::InvalidConfigurationHorizons { horizons: mins });
+        }
+
+        let mut horizons: Vec<Duration> = mins
+            .into_iter()
+            .map(|min| Duration::minutes(min as i64))
+            .collect();
+        horizons.sort();
+        horizons.dedup();
+
+        self.horizons = horizons;
+        Ok(self)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct SimulatedTradeExecutorConfig {
     trade_max_running_qtd: usize,
     fee_perc: PercentageCapped,
     trade_tsl_step_size: PercentageCapped,
@@ -131,3 +292,13 @@ impl From<&BacktestConfig> for SimulatedTradeExecutorConfig {
         }
     }
 }
+
+impl From<&LiveTradeConfig> for SimulatedTradeExecutorConfig {
+    fn from(value: &LiveTradeConfig) -> Self {
+        Self {
+            trade_max_running_qtd: value.trade_max_running_qtd(),
+            fee_perc: value.trade_estimated_fee(),
+            trade_tsl_step_size: value.trailing_stoploss_step_size(),
+        }
+    }
+}
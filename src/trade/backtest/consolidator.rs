@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 
 use crate::{
-    db::models::OhlcCandleRow,
+    db::{Database, models::OhlcCandleRow},
     shared::{Lookback, OhlcResolution, Period},
     util::DateTimeExt,
 };
@@ -156,6 +156,35 @@ impl RuntimeConsolidator {
         Ok(consolidator)
     }
 
+    /// Creates a new runtime consolidator from already consolidated historical candles.
+    ///
+    /// `history` should contain the completed candles preceding the bucket of `time_cursor`,
+    /// ordered by time. Only the `minute_candles` belonging to the bucket of `time_cursor` are
+    /// consolidated, to build the current in-progress bucket.
+    pub fn with_history(
+        lookback: Lookback,
+        history: Vec<OhlcCandleRow>,
+        minute_candles: &[OhlcCandleRow],
+        time_cursor: DateTime<Utc>,
+    ) -> Result<Self> {
+        let bucket_start = time_cursor.floor_to_resolution(lookback.resolution());
+        let current_start_idx = minute_candles.partition_point(|c| c.time < bucket_start);
+
+        let mut consolidator =
+            Self::new(lookback, &minute_candles[current_start_idx..], time_cursor)?;
+
+        let mut candles: Vec<OhlcCandleRow> = history
+            .into_iter()
+            .filter(|c| c.time < bucket_start)
+            .collect();
+        candles.append(&mut consolidator.candles);
+
+        consolidator.candles = candles;
+        consolidator.trim_old_candles();
+
+        Ok(consolidator)
+    }
+
     /// Trims old candles if we exceed the lookback period.
     fn trim_old_candles(&mut self) {
         while self.candles.len() > self.lookback.period().as_usize() {
@@ -236,16 +265,46 @@ impl RuntimeConsolidator {
 pub(super) struct MultiResolutionConsolidator(HashMap<OhlcResolution, RuntimeConsolidator>);
 
 impl MultiResolutionConsolidator {
-    pub fn new(
+    /// Returns the earliest start of the buckets containing `time`, across the given resolutions.
+    ///
+    /// 1-minute candles from this time onwards are needed to build the current in-progress bucket
+    /// of every resolution.
+    pub fn earliest_bucket_start<'a>(
+        resolutions: impl IntoIterator<Item = &'a OhlcResolution>,
+        time: DateTime<Utc>,
+    ) -> DateTime<Utc> {
+        resolutions
+            .into_iter()
+            .map(|resolution| time.floor_to_resolution(*resolution))
+            .min()
+            .unwrap_or(time)
+    }
+
+    /// Loads the consolidated candle history of each resolution from the database, and builds
+    /// the current in-progress buckets from `minute_candles`.
+    ///
+    /// Completed candles are served from the materialized consolidated candle tables, so large
+    /// lookbacks don't require loading and consolidating their full 1-minute history.
+    pub async fn load(
+        db: &Database,
         resolution_to_max_period: HashMap<OhlcResolution, Period>,
-        initial_candles: &[OhlcCandleRow],
+        minute_candles: &[OhlcCandleRow],
         time_cursor: DateTime<Utc>,
     ) -> Result<Self> {
         let mut consolidators = HashMap::new();
 
         for (resolution, max_period) in resolution_to_max_period {
             let lookback = Lookback::new(resolution, max_period).expect("is valid");
-            let consolidator = RuntimeConsolidator::new(lookback, initial_candles, time_cursor)?;
+
+            let bucket_start = time_cursor.floor_to_resolution(resolution);
+            let history_from = bucket_start.step_back_candles(resolution, max_period.as_u32() - 1);
+            let history = db
+                .ohlc_candles
+                .get_candles_consolidated(history_from, bucket_start, resolution)
+                .await?;
+
+            let consolidator =
+                RuntimeConsolidator::with_history(lookback, history, minute_candles, time_cursor)?;
 
             consolidators.insert(resolution, consolidator);
         }
@@ -400,6 +459,47 @@ mod tests {
         assert!(!result[1].stable);
     }
 
+    #[test]
+    fn with_history_matches_full_consolidation() {
+        // Create candles from 06:00 to 10:30
+        let candles: Vec<OhlcCandleRow> = (0..270)
+            .map(|i| {
+                let time = Utc.with_ymd_and_hms(2026, 1, 15, 6, 0, 0).unwrap()
+                    + chrono::Duration::minutes(i);
+                make_candle(time, 90_000.0 + i as f64 * 10.0)
+            })
+            .collect();
+
+        let time_cursor = Utc.with_ymd_and_hms(2026, 1, 15, 10, 29, 59).unwrap();
+        let lookback = Lookback::new(OhlcResolution::OneHour, 3).unwrap();
+        let full = RuntimeConsolidator::new(lookback, &candles, time_cursor).unwrap();
+
+        // History as served by the database, including a candle for the current bucket that must
+        // be ignored in favor of the minute candles.
+        let history = full.get_candles()[..2]
+            .iter()
+            .cloned()
+            .chain(std::iter::once(make_candle(
+                Utc.with_ymd_and_hms(2026, 1, 15, 10, 0, 0).unwrap(),
+                0.0,
+            )))
+            .collect();
+
+        let seeded =
+            RuntimeConsolidator::with_history(lookback, history, &candles, time_cursor).unwrap();
+
+        let expected = full.get_candles();
+        let result = seeded.get_candles();
+        assert_eq!(result.len(), 3);
+
+        for (result, expected) in result.iter().zip(expected) {
+            assert_eq!(result.time, expected.time);
+            assert_eq!(result.open, expected.open);
+            assert_eq!(result.close, expected.close);
+            assert_eq!(result.stable, expected.stable);
+        }
+    }
+
     #[test]
     fn lookback_trimming() {
         let mut consolidator = empty_consolidator(OhlcResolution::FiveMinutes, 5);
@@ -1,4 +1,4 @@
-use std::result;
+use std::{result, sync::Arc};
 
 use chrono::{DateTime, Duration, Utc};
 use thiserror::Error;
@@ -34,6 +34,18 @@ pub enum BacktestError {
     #[error("Maximum running quantity must be at least 1, got {max}")]
     InvalidConfigurationMaxRunningQtd { max: usize },
 
+    #[error(
+        "Forward return horizons must be non-empty and between 1 and {} minutes, got {horizons:?}",
+        i32::MAX
+    )]
+    InvalidConfigurationHorizons { horizons: Vec<u64> },
+
+    #[error("Database is read-only, so {setting} can't be persisted")]
+    InvalidConfigurationReadOnlyDatabase { setting: &'static str },
+
+    #[error("Database key `{key}` starts with the reserved prefix `quantoxide:`")]
+    InvalidConfigurationReservedKey { key: String },
+
     #[error(
         "Start and end times must be rounded to minutes. Start time: {start_time}, end time: {end_time}"
     )]
@@ -83,6 +95,27 @@ pub enum BacktestError {
     #[error("Operator error: {0}")]
     OperatorError(TradeCoreError),
 
+    #[error("Operator hook error: {0}")]
+    OperatorHook(TradeCoreError),
+
+    #[error("Operator state restore error: {0}")]
+    OperatorRestoreState(TradeCoreError),
+
+    #[error("Operator state save error: {0}")]
+    OperatorSaveState(TradeCoreError),
+
+    #[error("Persisted operator state could not be decoded: {0}")]
+    OperatorStateDecode(serde_json::Error),
+
+    #[error(
+        "Operator state under `{key}` was saved for a backtest starting at {checkpoint}, not at {start_time}"
+    )]
+    OperatorStateCheckpointMismatch {
+        key: String,
+        checkpoint: DateTime<Utc>,
+        start_time: DateTime<Utc>,
+    },
+
     #[error("[Db] {0}")]
     Db(#[from] DbError),
 
@@ -124,6 +157,11 @@ pub enum BacktestError {
     #[error("Duplicate operator name: '{name}'")]
     ParallelDuplicateOperatorName { name: String },
 
+    #[error(
+        "Duplicate evaluator name: '{name}'. Evaluators of the same type must override `SignalEvaluator::name`"
+    )]
+    ResearchDuplicateEvaluatorName { name: String },
+
     #[error("Operator '{operator_name}' failed: {source}")]
     ParallelOperatorFailed {
         operator_name: String,
@@ -146,6 +184,18 @@ pub enum BacktestError {
 
     #[error("Funding settlement application error: {0}")]
     FundingSettlementApplication(SimulatedTradeExecutorError),
+
+    #[error("Parity replay backtest failed: {0}")]
+    ParityReplayFailed(Arc<BacktestError>),
+
+    #[error("Parity replay backtest was aborted")]
+    ParityReplayAborted,
+
+    #[error("Parity replay backtest finished without reporting a final trading state")]
+    ParityReplayMissingTradingState,
+
+    #[error("Parity replay missed {skipped} backtest updates")]
+    ParityReplayLagged { skipped: u64 },
 }
 
 pub(super) type Result<T> = result::Result<T, BacktestError>;
@@ -5,8 +5,8 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use lnm_sdk::rest::v3::error::{
-    CrossExposureValidationError, CrossQuantityValidationError, PriceValidationError,
-    TradeValidationError,
+    CrossExposureValidationError, CrossQuantityValidationError, PercentageCappedValidationError,
+    PriceValidationError, TradeValidationError,
 };
 
 use crate::db::error::DbError;
@@ -72,6 +72,9 @@ pub enum SimulatedTradeExecutorError {
 
     #[error("Closed history update error")]
     ClosedHistoryUpdate(TradeCoreError),
+
+    #[error("Invalid trailing stoploss {0}")]
+    InvalidTrailingStoploss(PercentageCappedValidationError),
 }
 
 pub type SimulatedTradeExecutorResult<T> = result::Result<T, SimulatedTradeExecutorError>;
@@ -2,11 +2,12 @@ use std::{num::NonZeroU64, sync::Arc};
 
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
 use lnm_sdk::rest::v3::models::{
-    ClientId, CrossLeverage, Leverage, OrderQuantity, Price, TradeSide, TradeSize,
+    ClientId, CrossLeverage, Leverage, OrderQuantity, PercentageCapped, Price, TradeSide, TradeSize,
 };
 
 use crate::db::models::{FundingSettlementRow, OhlcCandleRow};
@@ -16,18 +17,19 @@ use super::{
         core::{
             ClosedTradeHistory, CrossOrderRequest, CrossPositionCore, IsolatedOrderRequest,
             PriceTrigger, RunningTradesMap, Stoploss, TradeClosed, TradeCore, TradeExecutor,
-            TradeRunning, TradeRunningExt, TradingState,
+            TradeRunning, TradeRunningExt, TradeTrailingStoploss, TradingState,
         },
         error::TradeExecutorResult,
+        events::TradeCloseReason,
     },
     config::SimulatedTradeExecutorConfig,
 };
 
 pub(crate) mod error;
-mod models;
+pub(in crate::trade) mod models;
 
 use error::{SimulatedTradeExecutorError, SimulatedTradeExecutorResult};
-use models::{SimulatedCrossPosition, SimulatedTradeRunning};
+use models::{SimulatedCrossPosition, SimulatedTradeClosed, SimulatedTradeRunning};
 
 enum Close {
     Single(Uuid),
@@ -41,6 +43,12 @@ impl From<TradeSide> for Close {
     }
 }
 
+struct PriceRange {
+    low: f64,
+    high: f64,
+    close: f64,
+}
+
 struct SimulatedTradeExecutorState {
     time: DateTime<Utc>,
     market_price: f64,
@@ -55,7 +63,43 @@ struct SimulatedTradeExecutorState {
     cross_position: SimulatedCrossPosition,
 }
 
-pub(super) struct SimulatedTradeExecutor {
+/// Running trade of a [`SimulatedTradeExecutorSnapshot`], along with its trailing stoploss
+/// percentage, if any.
+#[derive(Serialize, Deserialize)]
+struct SnapshotRunningTrade {
+    trade: SimulatedTradeRunning,
+    trailing_stoploss: Option<f64>,
+}
+
+/// Serializable snapshot of the state of a [`SimulatedTradeExecutor`], used to persist paper
+/// trading ledgers.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SimulatedTradeExecutorSnapshot {
+    time: DateTime<Utc>,
+    market_price: f64,
+    balance: i64,
+    last_trade_time: Option<DateTime<Utc>>,
+    running: Vec<SnapshotRunningTrade>,
+    funding_fees: i64,
+    realized_pl: i64,
+    closed: Vec<SimulatedTradeClosed>,
+    closed_fees: u64,
+    cross_position: SimulatedCrossPosition,
+}
+
+impl SimulatedTradeExecutorSnapshot {
+    /// Returns the time of the last market price update of the snapshotted executor.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    /// Returns the IDs of the closed trades of the snapshotted executor.
+    pub fn closed_ids(&self) -> impl Iterator<Item = Uuid> {
+        self.closed.iter().map(|trade| trade.id())
+    }
+}
+
+pub(crate) struct SimulatedTradeExecutor {
     config: SimulatedTradeExecutorConfig,
     state: Arc<Mutex<SimulatedTradeExecutorState>>,
 }
@@ -65,10 +109,21 @@ impl SimulatedTradeExecutor {
         config: impl Into<SimulatedTradeExecutorConfig>,
         start_candle: &OhlcCandleRow,
         start_balance: u64,
+    ) -> Arc<Self> {
+        Self::with_market_price(config, start_candle.time, start_candle.open, start_balance)
+    }
+
+    /// Creates an executor starting at `time` with the given market price, for callers that are
+    /// not driven by candles.
+    pub fn with_market_price(
+        config: impl Into<SimulatedTradeExecutorConfig>,
+        time: DateTime<Utc>,
+        market_price: f64,
+        start_balance: u64,
     ) -> Arc<Self> {
         let initial_state = SimulatedTradeExecutorState {
-            time: start_candle.time,
-            market_price: start_candle.open,
+            time,
+            market_price,
             balance: start_balance as i64,
             last_trade_time: None,
             trigger: PriceTrigger::new(),
@@ -86,6 +141,101 @@ impl SimulatedTradeExecutor {
         })
     }
 
+    /// Restores an executor from a snapshot of its state, see
+    /// [`SimulatedTradeExecutor::snapshot`].
+    pub fn from_snapshot(
+        config: impl Into<SimulatedTradeExecutorConfig>,
+        snapshot: SimulatedTradeExecutorSnapshot,
+    ) -> SimulatedTradeExecutorResult<Arc<Self>> {
+        let config = config.into();
+
+        let mut trigger = PriceTrigger::new();
+        let mut running_map = RunningTradesMap::new();
+
+        for SnapshotRunningTrade {
+            trade,
+            trailing_stoploss,
+        } in snapshot.running
+        {
+            let trade_tsl = trailing_stoploss
+                .map(PercentageCapped::try_from)
+                .transpose()
+                .map_err(SimulatedTradeExecutorError::InvalidTrailingStoploss)?
+                .map(TradeTrailingStoploss::prev_validated);
+            let trade = Arc::new(trade);
+
+            trigger
+                .update(
+                    config.trailing_stoploss_step_size(),
+                    trade.as_ref(),
+                    trade_tsl,
+                )
+                .map_err(SimulatedTradeExecutorError::PriceTriggerUpdate)?;
+            running_map.add(trade, trade_tsl);
+        }
+
+        let mut closed_history = ClosedTradeHistory::new();
+        for closed_trade in snapshot.closed {
+            closed_history
+                .add(Arc::new(closed_trade))
+                .map_err(SimulatedTradeExecutorError::ClosedHistoryUpdate)?;
+        }
+
+        let state = SimulatedTradeExecutorState {
+            time: snapshot.time,
+            market_price: snapshot.market_price,
+            balance: snapshot.balance,
+            last_trade_time: snapshot.last_trade_time,
+            trigger,
+            running_map,
+            funding_fees: snapshot.funding_fees,
+            realized_pl: snapshot.realized_pl,
+            closed_history: Arc::new(closed_history),
+            closed_fees: snapshot.closed_fees,
+            cross_position: snapshot.cross_position,
+        };
+
+        Ok(Arc::new(Self {
+            config,
+            state: Arc::new(Mutex::new(state)),
+        }))
+    }
+
+    /// Returns a serializable snapshot of the current state, from which the executor can be
+    /// restored with [`SimulatedTradeExecutor::from_snapshot`].
+    pub async fn snapshot(&self) -> SimulatedTradeExecutorSnapshot {
+        let state_guard = self.state.lock().await;
+
+        let running = state_guard
+            .running_map
+            .values()
+            .map(|(trade, trade_tsl)| SnapshotRunningTrade {
+                trade: trade.as_ref().clone(),
+                trailing_stoploss: trade_tsl.map(|tsl| tsl.as_f64()),
+            })
+            .collect();
+
+        // Closed trades are only ever added to the history by the executor itself
+        let closed = state_guard
+            .closed_history
+            .iter()
+            .filter_map(|trade| SimulatedTradeClosed::from_closed(trade.as_ref()))
+            .collect();
+
+        SimulatedTradeExecutorSnapshot {
+            time: state_guard.time,
+            market_price: state_guard.market_price,
+            balance: state_guard.balance,
+            last_trade_time: state_guard.last_trade_time,
+            running,
+            funding_fees: state_guard.funding_fees,
+            realized_pl: state_guard.realized_pl,
+            closed,
+            closed_fees: state_guard.closed_fees,
+            cross_position: state_guard.cross_position,
+        }
+    }
+
     /// Updates only the time, assuming no market price changes.
     pub async fn update_time(&self, time: DateTime<Utc>) -> SimulatedTradeExecutorResult<()> {
         let mut state_guard = self.state.lock().await;
@@ -101,10 +251,95 @@ impl SimulatedTradeExecutor {
         Ok(())
     }
 
-    pub async fn candle_update(&self, candle: &OhlcCandleRow) -> SimulatedTradeExecutorResult<()> {
+    /// Returns the time of the last trade opening or closing, without evaluating the whole trading
+    /// state.
+    pub async fn last_trade_time(&self) -> Option<DateTime<Utc>> {
+        self.state.lock().await.last_trade_time
+    }
+
+    /// Replaces the stoploss of a running trade with a fixed price, validated against the current
+    /// market price. Any trailing stoploss configured for the trade is dropped.
+    pub async fn update_stoploss(
+        &self,
+        trade_id: Uuid,
+        stoploss: Price,
+    ) -> SimulatedTradeExecutorResult<()> {
         let mut state_guard = self.state.lock().await;
 
-        let time = candle.time + Duration::seconds(59);
+        let market_price = Price::bounded(state_guard.market_price);
+
+        let Some((trade, trade_tsl)) = state_guard.running_map.get_by_id_mut(trade_id) else {
+            return Err(SimulatedTradeExecutorError::TradeNotRunning { trade_id });
+        };
+
+        *trade = trade.with_new_stoploss(market_price, stoploss)?;
+        *trade_tsl = None;
+
+        let mut new_trigger = PriceTrigger::new();
+        for (trade, trade_tsl) in state_guard.running_map.trades_desc() {
+            new_trigger
+                .update(
+                    self.config.trailing_stoploss_step_size(),
+                    trade.as_ref(),
+                    *trade_tsl,
+                )
+                .map_err(SimulatedTradeExecutorError::PriceTriggerUpdate)?;
+        }
+
+        state_guard.trigger = new_trigger;
+
+        Ok(())
+    }
+
+    pub async fn candle_update(&self, candle: &OhlcCandleRow) -> SimulatedTradeExecutorResult<()> {
+        let range = PriceRange {
+            low: candle.low,
+            high: candle.high,
+            close: candle.close,
+        };
+
+        self.price_range_update(candle.time + Duration::seconds(59), candle.time, range)
+            .await
+    }
+
+    /// Updates the market price from a single real-time price tick. Trades reaching their
+    /// stoploss, takeprofit or liquidation at `price` are closed at `time`.
+    pub async fn tick_update(
+        &self,
+        time: DateTime<Utc>,
+        price: f64,
+    ) -> SimulatedTradeExecutorResult<()> {
+        let range = PriceRange {
+            low: price,
+            high: price,
+            close: price,
+        };
+
+        self.price_range_update(time, time, range).await
+    }
+
+    /// Updates the market price from the range of real-time price ticks observed up to `time`,
+    /// ending at `close`. Trades reaching their stoploss, takeprofit or liquidation within the
+    /// range are closed at `time`.
+    pub async fn tick_range_update(
+        &self,
+        time: DateTime<Utc>,
+        low: f64,
+        high: f64,
+        close: f64,
+    ) -> SimulatedTradeExecutorResult<()> {
+        let range = PriceRange { low, high, close };
+
+        self.price_range_update(time, time, range).await
+    }
+
+    async fn price_range_update(
+        &self,
+        time: DateTime<Utc>,
+        close_time: DateTime<Utc>,
+        candle: PriceRange,
+    ) -> SimulatedTradeExecutorResult<()> {
+        let mut state_guard = self.state.lock().await;
 
         if time < state_guard.time {
             return Err(SimulatedTradeExecutorError::TimeSequenceViolation {
@@ -116,7 +351,9 @@ impl SimulatedTradeExecutor {
         let mut new_last_trade_time = state_guard.last_trade_time;
         let mut new_cross_position = state_guard.cross_position;
 
-        let cross_liquidated = state_guard.cross_position.liquidation_reached(candle);
+        let cross_liquidated = state_guard
+            .cross_position
+            .liquidation_reached(candle.low, candle.high);
         if cross_liquidated {
             new_cross_position = state_guard
                 .cross_position
@@ -152,21 +389,24 @@ impl SimulatedTradeExecutor {
         for (trade, trade_tsl_opt) in state_guard.running_map.trades_desc_mut() {
             // Check if price reached stoploss or takeprofit
 
+            let stoploss_trigger = match trade.stoploss() {
+                Some(stoploss) => (stoploss, TradeCloseReason::Stoploss),
+                None => (trade.liquidation(), TradeCloseReason::Liquidation),
+            };
+            let takeprofit_trigger = trade
+                .takeprofit()
+                .map(|takeprofit| (takeprofit, TradeCloseReason::Takeprofit));
+
             let (trade_min_opt, trade_max_opt) = match trade.side() {
-                TradeSide::Buy => (
-                    Some(trade.stoploss().unwrap_or(trade.liquidation())),
-                    trade.takeprofit(),
-                ),
-                TradeSide::Sell => (
-                    trade.takeprofit(),
-                    Some(trade.stoploss().unwrap_or(trade.liquidation())),
-                ),
+                TradeSide::Buy => (Some(stoploss_trigger), takeprofit_trigger),
+                TradeSide::Sell => (takeprofit_trigger, Some(stoploss_trigger)),
             };
 
-            if let Some(trade_min) = trade_min_opt
+            if let Some((trade_min, reason)) = trade_min_opt
                 && candle.low <= trade_min.as_f64()
             {
-                let closed_trade = trade.to_closed(self.config.fee_perc(), candle.time, trade_min);
+                let closed_trade =
+                    trade.to_closed(self.config.fee_perc(), close_time, trade_min, reason);
 
                 new_balance += closed_trade.margin().as_i64() + closed_trade.maintenance_margin()
                     - closed_trade.closing_fee() as i64
@@ -180,10 +420,11 @@ impl SimulatedTradeExecutor {
                 continue;
             }
 
-            if let Some(trade_max) = trade_max_opt
+            if let Some((trade_max, reason)) = trade_max_opt
                 && candle.high >= trade_max.as_f64()
             {
-                let closed_trade = trade.to_closed(self.config.fee_perc(), candle.time, trade_max);
+                let closed_trade =
+                    trade.to_closed(self.config.fee_perc(), close_time, trade_max, reason);
 
                 new_balance += closed_trade.margin().as_i64() + closed_trade.maintenance_margin()
                     - closed_trade.closing_fee() as i64
@@ -333,8 +574,13 @@ impl SimulatedTradeExecutor {
                 // closed by market movements first.
                 let closing_price = Price::round(state_guard.market_price)
                     .map_err(SimulatedTradeExecutorError::InvalidMarketPrice)?;
-                let closed_trade =
-                    trade.to_closed(self.config.fee_perc(), settlement.time, closing_price);
+                // The margin can no longer cover the trade, which the exchange would liquidate
+                let closed_trade = trade.to_closed(
+                    self.config.fee_perc(),
+                    settlement.time,
+                    closing_price,
+                    TradeCloseReason::Liquidation,
+                );
 
                 new_balance += closed_trade.margin().as_i64() + closed_trade.maintenance_margin()
                     - closed_trade.closing_fee() as i64
@@ -407,7 +653,12 @@ impl SimulatedTradeExecutor {
             };
 
             if should_be_closed {
-                let closed_trade = trade.to_closed(self.config.fee_perc(), time, market_price);
+                let closed_trade = trade.to_closed(
+                    self.config.fee_perc(),
+                    time,
+                    market_price,
+                    TradeCloseReason::Market,
+                );
 
                 new_balance += closed_trade.margin().as_i64() + closed_trade.maintenance_margin()
                     - closed_trade.closing_fee() as i64
@@ -1,6 +1,7 @@
 use std::{cmp::Ordering, num::NonZeroU64, sync::Arc};
 
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use lnm_sdk::rest::v3::{
@@ -11,15 +12,22 @@ use lnm_sdk::rest::v3::{
     },
 };
 
-use crate::db::models::{FundingSettlementRow, OhlcCandleRow};
+use crate::db::models::FundingSettlementRow;
 
 use super::{
-    super::super::core::{CrossPositionCore, TradeClosed, TradeCore, TradeRunning},
+    super::super::{
+        core::{CrossPositionCore, TradeClosed, TradeCore, TradeRunning},
+        events::TradeCloseReason,
+    },
     error::{SimulatedTradeExecutorError, SimulatedTradeExecutorResult},
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub(super) struct SimulatedCrossPosition {
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(
+    into = "SimulatedCrossPositionParams",
+    try_from = "SimulatedCrossPositionParams"
+)]
+pub(in crate::trade) struct SimulatedCrossPosition {
     margin: u64,
     leverage: CrossLeverage,
     exposure: CrossExposure,
@@ -177,12 +185,12 @@ impl SimulatedCrossPosition {
         Ok((new_position, funding_fee, false))
     }
 
-    pub fn liquidation_reached(&self, candle: &OhlcCandleRow) -> bool {
+    pub fn liquidation_reached(&self, low: f64, high: f64) -> bool {
         match self.exposure {
             CrossExposure::Neutral => false,
             CrossExposure::Running(exposure) => match exposure.side() {
-                TradeSide::Buy => candle.low <= exposure.liquidation().as_f64(),
-                TradeSide::Sell => candle.high >= exposure.liquidation().as_f64(),
+                TradeSide::Buy => low <= exposure.liquidation().as_f64(),
+                TradeSide::Sell => high >= exposure.liquidation().as_f64(),
             },
         }
     }
@@ -405,6 +413,46 @@ impl SimulatedCrossPosition {
     }
 }
 
+/// Serialized form of a [`SimulatedCrossPosition`]. The exposure is rebuilt from its running
+/// params when deserializing.
+#[derive(Clone, Serialize, Deserialize)]
+pub(super) struct SimulatedCrossPositionParams {
+    margin: u64,
+    leverage: CrossLeverage,
+    exposure_running: Option<(TradeSide, CrossQuantity, Price)>,
+    trading_fees: u64,
+    session_funding_fees: i64,
+    realized_pl: i64,
+}
+
+impl From<SimulatedCrossPosition> for SimulatedCrossPositionParams {
+    fn from(value: SimulatedCrossPosition) -> Self {
+        Self {
+            margin: value.margin,
+            leverage: value.leverage,
+            exposure_running: value.exposure.as_running_params(),
+            trading_fees: value.trading_fees,
+            session_funding_fees: value.session_funding_fees,
+            realized_pl: value.realized_pl,
+        }
+    }
+}
+
+impl TryFrom<SimulatedCrossPositionParams> for SimulatedCrossPosition {
+    type Error = SimulatedTradeExecutorError;
+
+    fn try_from(value: SimulatedCrossPositionParams) -> Result<Self, Self::Error> {
+        Self::new(
+            value.margin,
+            value.leverage,
+            value.exposure_running,
+            value.trading_fees,
+            value.session_funding_fees,
+            value.realized_pl,
+        )
+    }
+}
+
 impl crate::sealed::Sealed for SimulatedCrossPosition {}
 
 impl CrossPositionCore for SimulatedCrossPosition {
@@ -433,8 +481,8 @@ impl CrossPositionCore for SimulatedCrossPosition {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(super) struct SimulatedTradeRunning {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(in crate::trade) struct SimulatedTradeRunning {
     id: Uuid,
     side: TradeSide,
     opening_fee: u64,
@@ -657,6 +705,7 @@ impl SimulatedTradeRunning {
         fee_perc: PercentageCapped,
         close_time: DateTime<Utc>,
         close_price: Price,
+        close_reason: TradeCloseReason,
     ) -> Arc<SimulatedTradeClosed> {
         let closing_fee = trade_util::evaluate_order_fee(fee_perc, self.quantity, close_price);
 
@@ -664,7 +713,6 @@ impl SimulatedTradeRunning {
             id: self.id,
             side: self.side,
             entry_time: self.entry_time,
-            entry_price: self.entry_price,
             price: self.price,
             liquidation: self.liquidation,
             stoploss: self.stoploss,
@@ -678,6 +726,7 @@ impl SimulatedTradeRunning {
             closing_fee_reserved: self.closing_fee_reserved,
             closing_fee,
             client_id: self.client_id.clone(),
+            close_reason: Some(close_reason),
         })
     }
 }
@@ -764,12 +813,11 @@ impl TradeRunning for SimulatedTradeRunning {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub(super) struct SimulatedTradeClosed {
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(in crate::trade) struct SimulatedTradeClosed {
     id: Uuid,
     side: TradeSide,
     entry_time: DateTime<Utc>,
-    entry_price: Price,
     price: Price,
     liquidation: Price,
     stoploss: Option<Price>,
@@ -783,6 +831,35 @@ pub(super) struct SimulatedTradeClosed {
     closing_fee_reserved: u64,
     closing_fee: u64,
     client_id: Option<ClientId>,
+    // Missing from paper ledger snapshots taken before close reasons were recorded
+    #[serde(default)]
+    close_reason: Option<TradeCloseReason>,
+}
+
+impl SimulatedTradeClosed {
+    /// Rebuilds a trade closed by the simulated ledger from its [`TradeClosed`] representation.
+    /// Returns `None` if the trade wasn't closed.
+    pub fn from_closed(trade: &dyn TradeClosed) -> Option<Self> {
+        Some(Self {
+            id: trade.id(),
+            side: trade.side(),
+            entry_time: trade.created_at(),
+            price: trade.price(),
+            liquidation: trade.liquidation(),
+            stoploss: trade.stoploss(),
+            takeprofit: trade.takeprofit(),
+            margin: trade.margin(),
+            quantity: trade.quantity(),
+            leverage: trade.leverage(),
+            close_time: trade.closed_at()?,
+            close_price: trade.exit_price()?,
+            opening_fee: trade.opening_fee(),
+            closing_fee_reserved: (trade.maintenance_margin() - trade.opening_fee() as i64) as u64,
+            closing_fee: trade.closing_fee(),
+            client_id: trade.client_id().cloned(),
+            close_reason: trade.close_reason(),
+        })
+    }
 }
 
 impl TradeCore for SimulatedTradeClosed {
@@ -866,4 +943,8 @@ impl TradeClosed for SimulatedTradeClosed {
         trade_util::estimate_pl(self.side(), self.quantity(), self.price(), self.close_price)
             .floor() as i64
     }
+
+    fn close_reason(&self) -> Option<TradeCloseReason> {
+        self.close_reason
+    }
 }
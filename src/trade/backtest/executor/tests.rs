@@ -120,6 +120,39 @@ async fn test_simulated_trade_executor_isolated_order_request_opens_long() -> Tr
     Ok(())
 }
 
+#[tokio::test]
+async fn test_simulated_trade_executor_update_stoploss() -> TradeExecutorResult<()> {
+    let candle = OhlcCandleRow::new_simple(Utc::now().floor_minute(), 100_000.0, 1_000);
+    let executor =
+        SimulatedTradeExecutor::new(SimulatedTradeExecutorConfig::default(), &candle, 1_000_000);
+
+    let size = OrderQuantity::try_from(500).unwrap().into();
+    let leverage = Leverage::try_from(1).unwrap();
+    let request = IsolatedOrderRequest::market(TradeSide::Buy, size, leverage)
+        .with_stoploss(Stoploss::fixed(Price::bounded(95_000.)))?;
+    let trade_id = executor.isolated_order(request).await?;
+
+    executor
+        .update_stoploss(trade_id, Price::bounded(99_000.))
+        .await?;
+
+    let state = executor.trading_state().await?;
+    let (running_trade, _) = state.running_map().get_by_id(trade_id).unwrap();
+    assert_eq!(running_trade.stoploss(), Some(Price::bounded(99_000.)));
+
+    // The new stoploss must be reached, not the original one
+    executor
+        .tick_update(candle.time + Duration::seconds(1), 98_900.)
+        .await?;
+
+    let state = executor.trading_state().await?;
+    assert_eq!(state.running_long_len(), 0);
+    let closed_trade = state.closed_history().get_by_id(trade_id).unwrap();
+    assert_eq!(closed_trade.exit_price(), Some(Price::bounded(99_000.)));
+
+    Ok(())
+}
+
 #[test]
 fn test_cross_order_request_stores_validated_values() {
     let quantity = OrderQuantity::try_from(500).unwrap();
@@ -1173,6 +1206,57 @@ async fn test_simulated_trade_executor_long_liquidation_reached() -> TradeExecut
     Ok(())
 }
 
+#[tokio::test]
+async fn test_simulated_trade_executor_tick_update_closes_at_tick_time() -> TradeExecutorResult<()>
+{
+    let start_time = Utc::now();
+    let start_balance = 100_000_000;
+    let config = SimulatedTradeExecutorConfig::default();
+
+    let executor =
+        SimulatedTradeExecutor::with_market_price(config, start_time, 100_000.0, start_balance);
+
+    let size = OrderQuantity::try_from(500).unwrap().into();
+    let leverage = Leverage::try_from(10).unwrap();
+    let stoploss = Some(Stoploss::Fixed(Price::try_from(99_000.0).unwrap()));
+
+    executor
+        .isolated_order_market_long(size, leverage, stoploss, None, None)
+        .await?;
+
+    // Tick above the stoploss keeps the trade running
+    let tick_time = start_time + Duration::seconds(3);
+    executor.tick_update(tick_time, 99_500.0).await?;
+
+    let state = executor.trading_state().await?;
+    assert_eq!(state.running_long_len(), 1);
+    assert_eq!(state.market_price().as_f64(), 99_500.0);
+
+    // Tick reaching the stoploss closes the trade at the tick time
+    let tick_time = tick_time + Duration::seconds(2);
+    executor.tick_update(tick_time, 98_900.0).await?;
+
+    let state = executor.trading_state().await?;
+    assert_eq!(state.running_long_len(), 0, "Long should be stopped out");
+    assert_eq!(state.closed_len(), 1);
+
+    let closed_trade = state.closed_history().iter().next().unwrap();
+    assert_eq!(closed_trade.closed_at(), Some(tick_time));
+    assert_eq!(
+        closed_trade.exit_price(),
+        Some(Price::try_from(99_000.0).unwrap())
+    );
+
+    // Ticks must not go back in time
+    let res = executor.tick_update(start_time, 99_000.0).await;
+    assert!(matches!(
+        res,
+        Err(SimulatedTradeExecutorError::TimeSequenceViolation { .. })
+    ));
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_simulated_trade_executor_long_liquidation_not_reached() -> TradeExecutorResult<()> {
     let candle = OhlcCandleRow::new_simple(Utc::now().floor_minute(), 100_000.0, 1_000);
@@ -2921,3 +3005,95 @@ async fn test_simulated_trade_executor_total_net_value_includes_isolated_and_cro
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_simulated_trade_executor_snapshot_round_trip() -> TradeExecutorResult<()> {
+    let candle = OhlcCandleRow::new_simple(Utc::now().floor_minute(), 100_000.0, 1_000);
+    let config = SimulatedTradeExecutorConfig::default();
+    let executor = SimulatedTradeExecutor::new(config.clone(), &candle, 10_000_000);
+
+    let size = OrderQuantity::try_from(500).unwrap().into();
+    let leverage = Leverage::try_from(1).unwrap();
+    let stoploss_perc = PercentageCapped::try_from(2.0).unwrap();
+
+    let trailing_id = executor
+        .isolated_order_market_long(
+            size,
+            leverage,
+            Some(Stoploss::trailing(stoploss_perc)),
+            None,
+            None,
+        )
+        .await?;
+    let closed_id = executor
+        .isolated_order_market_short(size, leverage, None, None, None)
+        .await?;
+    executor
+        .cross_deposit(NonZeroU64::new(500_000).unwrap())
+        .await?;
+    executor
+        .cross_set_leverage(CrossLeverage::try_from(10).unwrap())
+        .await?;
+    executor
+        .cross_order_market_long(OrderQuantity::try_from(1_000).unwrap())
+        .await?;
+
+    let candle = next_candle(&candle, 101_000.0);
+    executor.candle_update(&candle).await?;
+    executor.isolated_order_close(closed_id).await?;
+
+    let bytes = serde_json::to_vec(&executor.snapshot().await).unwrap();
+    let snapshot: SimulatedTradeExecutorSnapshot = serde_json::from_slice(&bytes).unwrap();
+    let restored = SimulatedTradeExecutor::from_snapshot(config, snapshot).unwrap();
+
+    let state = executor.trading_state().await?;
+    let restored_state = restored.trading_state().await?;
+
+    assert_eq!(restored_state.last_tick_time(), state.last_tick_time());
+    assert_eq!(restored_state.market_price(), state.market_price());
+    assert_eq!(restored_state.balance(), state.balance());
+    assert_eq!(restored_state.last_trade_time(), state.last_trade_time());
+    assert_eq!(restored_state.realized_pl(), state.realized_pl());
+    assert_eq!(restored_state.closed_fees(), state.closed_fees());
+    assert_eq!(restored_state.total_net_value(), state.total_net_value());
+    assert_eq!(
+        restored_state.cross_position().margin(),
+        state.cross_position().margin()
+    );
+    assert_eq!(
+        restored_state.cross_position().exposure(),
+        state.cross_position().exposure()
+    );
+
+    let (trade, tsl) = restored_state
+        .running_map()
+        .get_by_id(trailing_id)
+        .expect("trade is restored");
+    assert_eq!(restored_state.running_map().len(), 1);
+    assert_eq!(tsl.map(|tsl| tsl.as_f64()), Some(stoploss_perc.as_f64()));
+
+    let closed_trade = restored_state
+        .closed_history()
+        .get_by_id(closed_id)
+        .expect("closed trade is restored");
+    let original_closed_trade = state.closed_history().get_by_id(closed_id).unwrap();
+    assert_eq!(closed_trade.pl(), original_closed_trade.pl());
+    assert_eq!(
+        closed_trade.closing_fee(),
+        original_closed_trade.closing_fee()
+    );
+    assert_eq!(
+        closed_trade.maintenance_margin(),
+        original_closed_trade.maintenance_margin()
+    );
+
+    // The price trigger is rebuilt, so the trailing stoploss keeps following the price
+    let candle = next_candle(&candle, 103_000.0);
+    restored.candle_update(&candle).await?;
+
+    let restored_state = restored.trading_state().await?;
+    let (updated_trade, _) = restored_state.running_map().get_by_id(trailing_id).unwrap();
+    assert!(updated_trade.stoploss() > trade.stoploss());
+
+    Ok(())
+}
@@ -1,8 +1,9 @@
-pub(super) mod config;
+pub(crate) mod config;
 mod consolidator;
 pub(crate) mod error;
-pub(super) mod executor;
+pub(crate) mod executor;
 mod operator;
 pub(super) mod parallel;
+pub(super) mod research;
 pub(super) mod single;
 pub(super) mod state;
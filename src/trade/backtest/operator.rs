@@ -1,72 +1,222 @@
 use std::{collections::HashMap, sync::Arc};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    shared::{Lookback, OhlcResolution, Period},
-    signal::{Signal, SignalEvaluator, WrappedSignalEvaluator, error::SignalOperatorError},
-    trade::core::{TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+    db::{Database, models::OhlcCandleRow},
+    shared::{
+        CandleCloseTracker, IterationSchedule, Lookback, LookbackCandles, OhlcResolution, Period,
+        max_lookback,
+    },
+    signal::{
+        Signal, SignalEvaluator, SignalRecorder, WrappedSignalEvaluator, error::SignalOperatorError,
+    },
+    trade::{
+        context::OperatorContext,
+        core::{PriceThreshold, TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+        events::{OperatorEvent, TradeEventTracker},
+    },
 };
 
 use super::{
     consolidator::MultiResolutionConsolidator,
     error::{BacktestError, Result},
+    executor::SimulatedTradeExecutor,
 };
 
-/// Signal operator in pending state for a specific signal type.
-pub(super) struct SignalOperatorPending<S: Signal> {
-    evaluators: Vec<WrappedSignalEvaluator<S>>,
-    signal_operator: WrappedSignalOperator<S>,
+/// Number of buffered signals that triggers a flush to the database during a simulation.
+const SIGNAL_FLUSH_THRESHOLD: usize = 1_000;
+
+fn resolution_to_max_period<'a>(
+    lookbacks: impl IntoIterator<Item = &'a Lookback>,
+) -> HashMap<OhlcResolution, Period> {
+    let mut resolution_map: HashMap<OhlcResolution, Period> = HashMap::new();
+
+    for lookback in lookbacks {
+        resolution_map
+            .entry(lookback.resolution())
+            .and_modify(|existing| {
+                if lookback.period() > *existing {
+                    *existing = lookback.period();
+                }
+            })
+            .or_insert(lookback.period());
+    }
+
+    resolution_map
+}
+
+/// Returns the lookback candles available in the consolidator. With a candle close tracker, only
+/// candles closed as of its latest handled close are included.
+fn lookback_candles<'a>(
+    lookbacks: &[Lookback],
+    consolidator: Option<&'a MultiResolutionConsolidator>,
+    candle_close_tracker: Option<&CandleCloseTracker>,
+) -> LookbackCandles<'a> {
+    LookbackCandles::from_buffers(lookbacks, |resolution| {
+        let buffer = consolidator
+            .and_then(|c| c.get_candles(resolution))
+            .expect("not `None` for lookback resolutions");
+
+        match candle_close_tracker {
+            Some(tracker) => tracker.closed_candles(resolution, buffer),
+            None => buffer,
+        }
+    })
+}
+
+/// Returns the consolidator requirements of the given lookbacks. Under a candle-close schedule,
+/// one extra candle per resolution is kept so the in-progress candle can be excluded.
+fn required_lookbacks(lookbacks: &[Lookback], schedule: IterationSchedule) -> Vec<Lookback> {
+    match schedule {
+        IterationSchedule::MinInterval => lookbacks.to_vec(),
+        IterationSchedule::CandleClose { .. } => lookbacks
+            .iter()
+            .map(Lookback::with_in_progress_candle)
+            .collect(),
+    }
+}
+
+/// Returns the events that took place in the executor since the last poll. The trading state is
+/// only evaluated if trades were opened or closed in the meantime. `low` and `high` must cover the
+/// market prices since the last poll.
+pub(super) async fn poll_trade_events(
+    executor: &SimulatedTradeExecutor,
+    tracker: &mut TradeEventTracker,
+    low: f64,
+    high: f64,
+) -> Result<Vec<OperatorEvent>> {
+    if !tracker.is_outdated(executor.last_trade_time().await) {
+        return Ok(Vec::new());
+    }
+
+    let trading_state = executor
+        .trading_state()
+        .await
+        .map_err(BacktestError::ExecutorStateEvaluation)?;
+
+    Ok(tracker.update(&trading_state, low, high))
+}
+
+/// Operator state persisted by backtests, along with the checkpoint time from which a backtest can
+/// resume with it.
+#[derive(Serialize, Deserialize)]
+struct OperatorStateRecord {
+    checkpoint: DateTime<Utc>,
+    state: Vec<u8>,
+}
+
+/// Returns the operator state persisted under `key`, if any. Since the simulated executor is not
+/// persisted alongside it, the state is only restored if its checkpoint matches `start_time`.
+pub(super) async fn load_operator_state(
+    db: &Database,
+    key: &str,
+    start_time: DateTime<Utc>,
+) -> Result<Option<Vec<u8>>> {
+    let Some(bytes) = db.key_values.get_value(key).await? else {
+        return Ok(None);
+    };
+
+    let record: OperatorStateRecord =
+        serde_json::from_slice(&bytes).map_err(BacktestError::OperatorStateDecode)?;
+
+    if record.checkpoint != start_time {
+        return Err(BacktestError::OperatorStateCheckpointMismatch {
+            key: key.to_string(),
+            checkpoint: record.checkpoint,
+            start_time,
+        });
+    }
+
+    Ok(Some(record.state))
+}
+
+/// Persists the operator state under `key`, to be restored by backtests starting at `checkpoint`.
+pub(super) async fn store_operator_state(
+    db: &Database,
+    key: &str,
+    checkpoint: DateTime<Utc>,
+    state: Vec<u8>,
+) -> Result<()> {
+    let record = OperatorStateRecord { checkpoint, state };
+    let bytes = serde_json::to_vec(&record).expect("operator state record is serializable");
+
+    db.key_values.set_value(key, &bytes).await?;
+
+    Ok(())
+}
+
+/// Signal evaluator along with its data requirements, queried once on creation.
+struct EvaluatorEntry<S: Signal> {
+    evaluator: WrappedSignalEvaluator<S>,
+    lookbacks: Vec<Lookback>,
+    funding_settlements_lookback: Option<Period>,
+    iteration_schedule: IterationSchedule,
+}
+
+/// Signal evaluator in running state, along with its scheduling state.
+struct EvaluatorRunning<S: Signal> {
+    entry: EvaluatorEntry<S>,
+    last_eval: DateTime<Utc>,
+    candle_close_tracker: Option<CandleCloseTracker>,
+}
+
+/// Signal evaluators in pending state, along with their combined data requirements.
+pub(super) struct SignalEvaluatorsPending<S: Signal> {
+    evaluators: Vec<EvaluatorEntry<S>>,
     resolution_to_max_period: HashMap<OhlcResolution, Period>,
     max_lookback: Option<Lookback>,
+    funding_settlements_lookback: Option<Period>,
 }
 
-impl<S: Signal> SignalOperatorPending<S> {
-    pub(super) fn new(
-        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
-        signal_operator: WrappedSignalOperator<S>,
-    ) -> Result<Self> {
+impl<S: Signal> SignalEvaluatorsPending<S> {
+    pub(super) fn new(evaluators: Vec<Box<dyn SignalEvaluator<S>>>) -> Result<Self> {
         if evaluators.is_empty() {
             return Err(BacktestError::SignalOperator(
                 SignalOperatorError::EmptyEvaluatorsVec,
             ));
         }
 
-        let evaluators: Vec<_> = evaluators
+        let evaluators = evaluators
             .into_iter()
-            .map(WrappedSignalEvaluator::new)
+            .map(|evaluator| {
+                let evaluator = WrappedSignalEvaluator::new(evaluator);
+                let lookbacks = evaluator
+                    .lookbacks()
+                    .map_err(BacktestError::SignalEvaluator)?;
+                let funding_settlements_lookback = evaluator
+                    .funding_settlements_lookback()
+                    .map_err(BacktestError::SignalEvaluator)?;
+                let iteration_schedule = evaluator
+                    .iteration_schedule()
+                    .map_err(BacktestError::SignalEvaluator)?;
+
+                Ok(EvaluatorEntry {
+                    evaluator,
+                    lookbacks,
+                    funding_settlements_lookback,
+                    iteration_schedule,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let lookbacks: Vec<Lookback> = evaluators
+            .iter()
+            .flat_map(|entry| required_lookbacks(&entry.lookbacks, entry.iteration_schedule))
             .collect();
-
-        let mut resolution_map: HashMap<OhlcResolution, Period> = HashMap::new();
-        let mut max_lookback: Option<Lookback> = None;
-
-        for evaluator in &evaluators {
-            if let Some(lookback) = evaluator
-                .lookback()
-                .map_err(BacktestError::SignalEvaluator)?
-            {
-                resolution_map
-                    .entry(lookback.resolution())
-                    .and_modify(|existing| {
-                        if lookback.period() > *existing {
-                            *existing = lookback.period();
-                        }
-                    })
-                    .or_insert(lookback.period());
-
-                if max_lookback
-                    .is_none_or(|existing| existing.as_duration() < lookback.as_duration())
-                {
-                    max_lookback = Some(lookback);
-                }
-            }
-        }
+        let resolution_map = resolution_to_max_period(&lookbacks);
+        let max_lookback = max_lookback(&lookbacks);
+        let funding_settlements_lookback = evaluators
+            .iter()
+            .filter_map(|entry| entry.funding_settlements_lookback)
+            .max();
 
         Ok(Self {
             evaluators,
-            signal_operator,
             resolution_to_max_period: resolution_map,
             max_lookback,
+            funding_settlements_lookback,
         })
     }
 
@@ -78,107 +228,311 @@ impl<S: Signal> SignalOperatorPending<S> {
         self.max_lookback
     }
 
+    pub(super) fn funding_settlements_lookback(&self) -> Option<Period> {
+        self.funding_settlements_lookback
+    }
+
+    /// Returns the name of each evaluator, in the order they were provided.
+    pub(super) fn names(&self) -> Result<Vec<String>> {
+        self.evaluators
+            .iter()
+            .map(|entry| {
+                entry
+                    .evaluator
+                    .name()
+                    .map_err(BacktestError::SignalEvaluator)
+            })
+            .collect()
+    }
+
+    pub(super) fn start(
+        self,
+        start_time: DateTime<Utc>,
+        signal_recorder: Option<SignalRecorder>,
+    ) -> SignalEvaluatorsRunning<S> {
+        let evaluators = self
+            .evaluators
+            .into_iter()
+            .map(|entry| EvaluatorRunning {
+                candle_close_tracker: entry
+                    .iteration_schedule
+                    .candle_close_tracker(&entry.lookbacks, start_time),
+                last_eval: start_time,
+                entry,
+            })
+            .collect();
+
+        SignalEvaluatorsRunning {
+            evaluators,
+            signal_recorder,
+        }
+    }
+}
+
+/// Signal evaluators in running state, evaluated as they become due.
+pub(super) struct SignalEvaluatorsRunning<S: Signal> {
+    evaluators: Vec<EvaluatorRunning<S>>,
+    signal_recorder: Option<SignalRecorder>,
+}
+
+impl<S: Signal> SignalEvaluatorsRunning<S> {
+    /// Evaluates the evaluators due at the context time. Returns the emitted signals along with
+    /// the index of the evaluator that emitted them.
+    pub(super) async fn evaluate(
+        &mut self,
+        context: &OperatorContext<'_>,
+        consolidator: Option<&MultiResolutionConsolidator>,
+    ) -> Result<Vec<(usize, S)>> {
+        let time_cursor = context.time();
+        let mut signals = Vec::new();
+
+        for (idx, running) in self.evaluators.iter_mut().enumerate() {
+            let entry = &running.entry;
+            let evaluator = &entry.evaluator;
+
+            let is_due = match &mut running.candle_close_tracker {
+                // The time cursor is the last second of the minute, so candles closing at the end
+                // of this minute are due
+                Some(tracker) => tracker.poll(time_cursor + Duration::seconds(1)),
+                None => {
+                    let min_iteration_interval = evaluator
+                        .min_iteration_interval()
+                        .map_err(BacktestError::SignalEvaluator)?
+                        .as_duration();
+
+                    time_cursor >= running.last_eval + min_iteration_interval
+                }
+            };
+
+            if !is_due {
+                continue;
+            }
+
+            running.last_eval = time_cursor;
+
+            let eval_candles = lookback_candles(
+                &entry.lookbacks,
+                consolidator,
+                running.candle_close_tracker.as_ref(),
+            );
+            let funding_settlements = context
+                .clone()
+                .with_funding_settlements_lookback(entry.funding_settlements_lookback)
+                .funding_settlements();
+
+            let signal = evaluator
+                .evaluate(&eval_candles, funding_settlements)
+                .await
+                .map_err(BacktestError::SignalEvaluator)?;
+
+            if let Some(signal_recorder) = self.signal_recorder.as_mut() {
+                signal_recorder
+                    .record(evaluator, time_cursor, &signal)
+                    .map_err(BacktestError::SignalEvaluator)?;
+            }
+
+            signals.push((idx, signal));
+        }
+
+        if let Some(signal_recorder) = self.signal_recorder.as_mut()
+            && signal_recorder.pending_len() >= SIGNAL_FLUSH_THRESHOLD
+        {
+            signal_recorder.flush().await?;
+        }
+
+        Ok(signals)
+    }
+
+    /// Persists the signals still buffered, if signals are being recorded.
+    pub(super) async fn flush_signals(&mut self) -> Result<()> {
+        if let Some(signal_recorder) = self.signal_recorder.as_mut() {
+            signal_recorder.flush().await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Signal operator in pending state for a specific signal type.
+pub(super) struct SignalOperatorPending<S: Signal> {
+    evaluators: SignalEvaluatorsPending<S>,
+    signal_operator: WrappedSignalOperator<S>,
+}
+
+impl<S: Signal> SignalOperatorPending<S> {
+    pub(super) fn new(
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        signal_operator: WrappedSignalOperator<S>,
+    ) -> Result<Self> {
+        Ok(Self {
+            evaluators: SignalEvaluatorsPending::new(evaluators)?,
+            signal_operator,
+        })
+    }
+
+    pub(super) fn resolution_to_max_period(&self) -> &HashMap<OhlcResolution, Period> {
+        self.evaluators.resolution_to_max_period()
+    }
+
+    pub(super) fn max_lookback(&self) -> Option<Lookback> {
+        self.evaluators.max_lookback()
+    }
+
+    pub(super) fn funding_settlements_lookback(&self) -> Option<Period> {
+        self.evaluators.funding_settlements_lookback()
+    }
+
+    pub(super) fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        self.signal_operator
+            .restore_state(state)
+            .map_err(BacktestError::OperatorRestoreState)
+    }
+
     pub(super) fn start(
         mut self,
         start_time: DateTime<Utc>,
         trade_executor: Arc<dyn TradeExecutor>,
+        signal_recorder: Option<SignalRecorder>,
     ) -> Result<SignalOperatorRunning<S>> {
         self.signal_operator
             .set_trade_executor(trade_executor)
             .map_err(BacktestError::SetTradeExecutor)?;
 
-        let evaluators = self
-            .evaluators
-            .into_iter()
-            .map(|ev| (start_time, ev))
-            .collect();
-
         Ok(SignalOperatorRunning {
-            evaluators,
+            evaluators: self.evaluators.start(start_time, signal_recorder),
             signal_operator: self.signal_operator,
+            price_thresholds: None,
         })
     }
 }
 
 /// Signal operator in running state for a specific signal type.
 pub(super) struct SignalOperatorRunning<S: Signal> {
-    evaluators: Vec<(DateTime<Utc>, WrappedSignalEvaluator<S>)>,
+    evaluators: SignalEvaluatorsRunning<S>,
     signal_operator: WrappedSignalOperator<S>,
+    /// Thresholds that trigger the operator's price threshold hook, queried on the first candle,
+    /// and after each processed signal and each call to the hook.
+    price_thresholds: Option<Vec<PriceThreshold>>,
 }
 
 impl<S: Signal> SignalOperatorRunning<S> {
+    pub(super) fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        self.signal_operator
+            .save_state()
+            .map_err(BacktestError::OperatorSaveState)
+    }
+
+    pub(super) async fn on_start(&self) -> Result<()> {
+        self.signal_operator
+            .on_start()
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    pub(super) async fn on_event(&self, event: &OperatorEvent) -> Result<()> {
+        self.signal_operator
+            .on_event(event)
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    pub(super) async fn on_shutdown(&self) -> Result<()> {
+        self.signal_operator
+            .on_shutdown()
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    /// Calls the operator's price threshold hook if any of its thresholds was reached within the
+    /// given (just closed) minute candle, then processes the signals of the evaluators that are
+    /// due.
     pub(super) async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()> {
-        for (last_eval, evaluator) in &mut self.evaluators {
-            let min_iteration_interval = evaluator
-                .min_iteration_interval()
-                .map_err(BacktestError::SignalEvaluator)?
-                .as_duration();
-
-            if time_cursor < *last_eval + min_iteration_interval {
-                continue;
-            }
-
-            *last_eval = time_cursor;
+        let price_thresholds = match self.price_thresholds.take() {
+            Some(price_thresholds) => price_thresholds,
+            None => self
+                .signal_operator
+                .price_thresholds(candle.open)
+                .map_err(BacktestError::OperatorError)?,
+        };
 
-            let lookback = evaluator
-                .lookback()
-                .map_err(BacktestError::SignalEvaluator)?;
+        let threshold_reached = price_thresholds
+            .iter()
+            .any(|threshold| threshold.is_reached(candle.low, candle.high));
 
-            let eval_candles = match lookback {
-                Some(lb) => {
-                    let ctx_candles = consolidator
-                        .and_then(|c| c.get_candles(lb.resolution()))
-                        .expect("not `None` when evaluator has lookback");
-                    let start_idx = ctx_candles.len().saturating_sub(lb.period().as_usize());
-                    &ctx_candles[start_idx..]
-                }
-                None => &[],
-            };
-
-            let signal = evaluator
-                .evaluate(eval_candles)
+        if threshold_reached {
+            self.signal_operator
+                .on_price_threshold(context.last_price())
                 .await
-                .map_err(BacktestError::SignalEvaluator)?;
+                .map_err(BacktestError::OperatorError)?;
+        }
+
+        let signals = self.evaluators.evaluate(context, consolidator).await?;
 
+        for (_, signal) in &signals {
             self.signal_operator
-                .process_signal(&signal)
+                .process_signal(signal)
                 .await
                 .map_err(BacktestError::SignalProcessingError)?;
         }
 
+        self.price_thresholds = if threshold_reached || !signals.is_empty() {
+            Some(
+                self.signal_operator
+                    .price_thresholds(context.last_price())
+                    .map_err(BacktestError::OperatorError)?,
+            )
+        } else {
+            Some(price_thresholds)
+        };
+
         Ok(())
     }
+
+    /// Persists the signals still buffered, if signals are being recorded.
+    pub(super) async fn flush_signals(&mut self) -> Result<()> {
+        self.evaluators.flush_signals().await
+    }
 }
 
 /// Raw operator in pending state.
 pub(super) struct RawOperatorPending {
     raw_operator: WrappedRawOperator,
+    lookbacks: Vec<Lookback>,
+    iteration_schedule: IterationSchedule,
     resolution_to_max_period: HashMap<OhlcResolution, Period>,
     max_lookback: Option<Lookback>,
+    funding_settlements_lookback: Option<Period>,
 }
 
 impl RawOperatorPending {
     pub(super) fn new(raw_operator: WrappedRawOperator) -> Result<Self> {
-        let lookback = raw_operator
-            .lookback()
+        let lookbacks = raw_operator
+            .lookbacks()
             .map_err(BacktestError::OperatorError)?;
 
-        let (resolution_to_max_period, max_lookback) = if let Some(lb) = lookback {
-            let mut map = HashMap::new();
-            map.insert(lb.resolution(), lb.period());
-            (map, Some(lb))
-        } else {
-            (HashMap::new(), None)
-        };
+        let iteration_schedule = raw_operator
+            .iteration_schedule()
+            .map_err(BacktestError::OperatorError)?;
+
+        let required_lookbacks = required_lookbacks(&lookbacks, iteration_schedule);
+        let resolution_to_max_period = resolution_to_max_period(&required_lookbacks);
+        let max_lookback = max_lookback(&required_lookbacks);
+        let funding_settlements_lookback = raw_operator
+            .funding_settlements_lookback()
+            .map_err(BacktestError::OperatorError)?;
 
         Ok(Self {
             raw_operator,
+            lookbacks,
+            iteration_schedule,
             resolution_to_max_period,
             max_lookback,
+            funding_settlements_lookback,
         })
     }
 
@@ -190,6 +544,16 @@ impl RawOperatorPending {
         self.max_lookback
     }
 
+    pub(super) fn funding_settlements_lookback(&self) -> Option<Period> {
+        self.funding_settlements_lookback
+    }
+
+    pub(super) fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        self.raw_operator
+            .restore_state(state)
+            .map_err(BacktestError::OperatorRestoreState)
+    }
+
     pub(super) fn start(
         mut self,
         start_time: DateTime<Utc>,
@@ -201,7 +565,13 @@ impl RawOperatorPending {
 
         Ok(RawOperatorRunning {
             last_eval: start_time,
+            candle_close_tracker: self
+                .iteration_schedule
+                .candle_close_tracker(&self.lookbacks, start_time),
             raw_operator: self.raw_operator,
+            lookbacks: self.lookbacks,
+            funding_settlements_lookback: self.funding_settlements_lookback,
+            price_thresholds: None,
         })
     }
 }
@@ -209,42 +579,209 @@ impl RawOperatorPending {
 /// Raw operator in running state.
 pub(super) struct RawOperatorRunning {
     last_eval: DateTime<Utc>,
+    candle_close_tracker: Option<CandleCloseTracker>,
     raw_operator: WrappedRawOperator,
+    lookbacks: Vec<Lookback>,
+    funding_settlements_lookback: Option<Period>,
+    /// Thresholds that trigger an iteration, queried on the first candle and after each iteration.
+    price_thresholds: Option<Vec<PriceThreshold>>,
 }
 
 impl RawOperatorRunning {
+    pub(super) fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        self.raw_operator
+            .save_state()
+            .map_err(BacktestError::OperatorSaveState)
+    }
+
+    pub(super) async fn on_start(&self) -> Result<()> {
+        self.raw_operator
+            .on_start()
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    pub(super) async fn on_event(&self, event: &OperatorEvent) -> Result<()> {
+        self.raw_operator
+            .on_event(event)
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    pub(super) async fn on_shutdown(&self) -> Result<()> {
+        self.raw_operator
+            .on_shutdown()
+            .await
+            .map_err(BacktestError::OperatorHook)
+    }
+
+    /// Iterates the operator if its minimum iteration interval elapsed, or if any of its price
+    /// thresholds was reached within the given (just closed) minute candle.
     pub(super) async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()> {
-        let min_iteration_interval = self
-            .raw_operator
-            .min_iteration_interval()
-            .map_err(BacktestError::OperatorError)?
-            .as_duration();
-
-        if time_cursor >= self.last_eval + min_iteration_interval {
-            self.last_eval = time_cursor;
+        let time_cursor = context.time();
 
-            let lookback = self
+        let price_thresholds = match self.price_thresholds.take() {
+            Some(price_thresholds) => price_thresholds,
+            None => self
                 .raw_operator
-                .lookback()
-                .map_err(BacktestError::OperatorError)?;
+                .price_thresholds(candle.open)
+                .map_err(BacktestError::OperatorError)?,
+        };
 
-            let ctx_candles = match lookback {
-                Some(lb) => consolidator
-                    .and_then(|c| c.get_candles(lb.resolution()))
-                    .expect("not `None` when evaluator has lookback"),
-                None => &[],
-            };
+        let is_due = match &mut self.candle_close_tracker {
+            // The time cursor is the last second of the minute, so candles closing at the end of
+            // this minute are due
+            Some(tracker) => tracker.poll(time_cursor + Duration::seconds(1)),
+            None => {
+                let min_iteration_interval = self
+                    .raw_operator
+                    .min_iteration_interval()
+                    .map_err(BacktestError::OperatorError)?
+                    .as_duration();
+
+                time_cursor >= self.last_eval + min_iteration_interval
+            }
+        };
 
-            self.raw_operator
-                .iterate(ctx_candles)
-                .await
-                .map_err(BacktestError::OperatorError)?;
+        let threshold_reached = price_thresholds
+            .iter()
+            .any(|threshold| threshold.is_reached(candle.low, candle.high));
+
+        if !is_due && !threshold_reached {
+            self.price_thresholds = Some(price_thresholds);
+            return Ok(());
         }
 
+        self.last_eval = time_cursor;
+
+        let context = context
+            .clone()
+            .with_funding_settlements_lookback(self.funding_settlements_lookback)
+            .with_candles(lookback_candles(
+                &self.lookbacks,
+                consolidator,
+                self.candle_close_tracker.as_ref(),
+            ));
+
+        self.raw_operator
+            .iterate(&context)
+            .await
+            .map_err(BacktestError::OperatorError)?;
+
+        self.price_thresholds = Some(
+            self.raw_operator
+                .price_thresholds(context.last_price())
+                .map_err(BacktestError::OperatorError)?,
+        );
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+
+    use crate::{
+        error::Result as GeneralResult,
+        shared::MinIterationInterval,
+        trade::{BacktestConfig, SignalOperator},
+    };
+
+    use super::*;
+
+    struct TimeEvaluator;
+
+    #[async_trait]
+    impl SignalEvaluator<String> for TimeEvaluator {
+        fn lookback(&self) -> Option<Lookback> {
+            None
+        }
+
+        fn min_iteration_interval(&self) -> MinIterationInterval {
+            MinIterationInterval::MIN
+        }
+
+        async fn evaluate(&self, _candles: &[OhlcCandleRow]) -> GeneralResult<String> {
+            Ok("signal".to_string())
+        }
+    }
+
+    #[derive(Default)]
+    struct ThresholdOperator {
+        signals: Arc<Mutex<usize>>,
+        threshold_prices: Arc<Mutex<Vec<f64>>>,
+    }
+
+    #[async_trait]
+    impl SignalOperator<String> for ThresholdOperator {
+        fn set_trade_executor(
+            &mut self,
+            _trade_executor: Arc<dyn TradeExecutor>,
+        ) -> GeneralResult<()> {
+            Ok(())
+        }
+
+        async fn process_signal(&self, _signal: &String) -> GeneralResult<()> {
+            *self.signals.lock().unwrap() += 1;
+            Ok(())
+        }
+
+        fn price_thresholds(&self) -> Vec<PriceThreshold> {
+            vec![PriceThreshold::Below(95_000.)]
+        }
+
+        async fn on_price_threshold(&self, market_price: f64) -> GeneralResult<()> {
+            self.threshold_prices.lock().unwrap().push(market_price);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signal_operator_price_threshold_hook() {
+        let start_time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let start_candle = OhlcCandleRow::new_simple(start_time, 100_000., 1_000);
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 1_000_000);
+
+        let operator = ThresholdOperator::default();
+        let signals = operator.signals.clone();
+        let threshold_prices = operator.threshold_prices.clone();
+
+        let signal_operator: Box<dyn SignalOperator<String>> = Box::new(operator);
+        let mut running = SignalOperatorPending::new(
+            vec![Box::new(TimeEvaluator) as Box<dyn SignalEvaluator<String>>],
+            signal_operator.into(),
+        )
+        .unwrap()
+        .start(start_time, executor, None)
+        .unwrap();
+
+        // Only the second minute reaches the threshold, closing back above it
+        let candles = [
+            OhlcCandleRow::new_simple(start_time, 100_000., 1_000),
+            OhlcCandleRow {
+                low: 94_000.,
+                close: 96_000.,
+                ..OhlcCandleRow::new_simple(start_time + Duration::minutes(1), 99_000., 1_000)
+            },
+            OhlcCandleRow::new_simple(start_time + Duration::minutes(2), 96_000., 1_000),
+        ];
+
+        for candle in &candles {
+            let time_cursor = candle.time + Duration::seconds(59);
+            let context = OperatorContext::new(time_cursor, time_cursor, candle.close, &[]);
+            running.iterate(&context, None, candle).await.unwrap();
+        }
+
+        assert_eq!(*signals.lock().unwrap(), 3);
+        assert_eq!(*threshold_prices.lock().unwrap(), vec![96_000.]);
+    }
+}
@@ -6,25 +6,31 @@ use tokio::sync::broadcast;
 use crate::{
     db::{Database, models::FundingSettlementRow},
     shared::{Lookback, OhlcResolution, Period},
-    signal::{Signal, SignalEvaluator},
+    signal::{Signal, SignalEvaluator, SignalRecorder},
     sync::{FundingSettlementsState, LNM_SETTLEMENT_A_START, PriceHistoryState},
     util::DateTimeExt,
 };
 
 use super::{
     super::{
-        super::{RawOperator, SignalOperator, TradeExecutor},
+        super::{
+            RawOperator, SignalOperator, TradeExecutor,
+            context::OperatorContext,
+            events::{OperatorEvent, TradeEventTracker},
+            middleware::{self, TradeExecutorMiddleware},
+        },
         config::BacktestConfig,
         consolidator::MultiResolutionConsolidator,
         error::{BacktestError, Result},
         executor::SimulatedTradeExecutor,
+        operator::{load_operator_state, poll_trade_events, store_operator_state},
         state::{
             BacktestParallelReceiver, BacktestParallelTransmitter, BacktestParallelUpdate,
             BacktestStatus, BacktestStatusManager,
         },
     },
     controller::BacktestParallelController,
-    operator::ParallelOperatorPending,
+    operator::{ParallelOperatorPending, ParallelOperatorRunning},
 };
 
 /// Builder for configuring and executing a parallel backtest simulation.
@@ -36,6 +42,7 @@ pub struct BacktestParallelEngine {
     config: BacktestConfig,
     db: Arc<Database>,
     operators: Vec<(String, ParallelOperatorPending)>,
+    middleware: Vec<Arc<dyn TradeExecutorMiddleware>>,
     shared_resolution_map: HashMap<OhlcResolution, Period>,
     max_lookback: Option<Lookback>,
     start_time: DateTime<Utc>,
@@ -80,6 +87,8 @@ impl BacktestParallelEngine {
             });
         }
 
+        config.validate_persistence(&db)?;
+
         let funding_settlements_state = FundingSettlementsState::evaluate(&db)
             .await
             .map_err(BacktestError::FundingSettlementsStateEvaluation)?;
@@ -110,6 +119,7 @@ impl BacktestParallelEngine {
             config,
             db,
             operators: Vec::new(),
+            middleware: Vec::new(),
             shared_resolution_map: HashMap::new(),
             max_lookback: None,
             start_time,
@@ -159,6 +169,14 @@ impl BacktestParallelEngine {
         Ok(self)
     }
 
+    /// Sets the middleware layered around the trade executor of each operator. Every executor is
+    /// wrapped separately. The first middleware in the list is the outermost layer. Risk limits set
+    /// in the config are applied closest to the executor.
+    pub fn with_middleware(mut self, middleware: Vec<Arc<dyn TradeExecutorMiddleware>>) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
     fn validate_name(&self, name: &str) -> Result<()> {
         if name.is_empty() {
             return Err(BacktestError::ParallelEmptyOperatorName);
@@ -225,11 +243,6 @@ impl BacktestParallelEngine {
 
         let buffer_size = self.config.buffer_size() as i64;
 
-        let max_lookback_duration = self
-            .max_lookback
-            .map(|lb| lb.as_duration())
-            .unwrap_or(Duration::zero());
-
         // Validate price history is available
         let price_history_state = PriceHistoryState::evaluate(&self.db)
             .await
@@ -257,7 +270,12 @@ impl BacktestParallelEngine {
         let settlement_from = self.start_time.ceil_funding_settlement_time();
         let settlement_to = self.end_time.floor_funding_settlement_time();
 
-        let buffer_from = self.start_time - max_lookback_duration;
+        // Completed lookback candles are loaded already consolidated. The minute buffer only
+        // needs to cover the current in-progress buckets.
+        let buffer_from = MultiResolutionConsolidator::earliest_bucket_start(
+            self.shared_resolution_map.keys(),
+            self.start_time,
+        );
         let buffer_to = buffer_from + Duration::minutes(buffer_size);
         let mut minute_buffer = self
             .db
@@ -283,16 +301,48 @@ impl BacktestParallelEngine {
             executors.push((name.clone(), executor));
         }
 
+        // Funding settlements kept as history, covering the latest settlement and the longest
+        // requested funding settlements lookback
+        let settlement_history_len = self
+            .operators
+            .iter()
+            .filter_map(|(_, pending)| pending.funding_settlements_lookback())
+            .max()
+            .map_or(1, |lookback| lookback.as_usize());
+
         // Start all operators
         let mut running_operators: Vec<(
             String,
-            super::operator::ParallelOperatorRunning,
+            ParallelOperatorRunning,
             Arc<SimulatedTradeExecutor>,
         )> = Vec::new();
 
-        for ((name, pending), (_, executor)) in self.operators.into_iter().zip(executors.iter()) {
+        for ((name, mut pending), (_, executor)) in self.operators.into_iter().zip(executors.iter())
+        {
+            if let Some(key) = self.config.operator_state_key()
+                && let Some(state) =
+                    load_operator_state(&self.db, &format!("{key}:{name}"), self.start_time).await?
+            {
+                pending.restore_state(&state).map_err(|e| {
+                    BacktestError::ParallelOperatorFailed {
+                        operator_name: name.clone(),
+                        source: Box::new(e),
+                    }
+                })?;
+            }
+
+            let operator_executor = middleware::wrap_executor(
+                executor.clone(),
+                self.config.risk_limits(),
+                None,
+                &self.middleware,
+            );
+            let signal_recorder = self
+                .config
+                .signal_run()
+                .map(|run| SignalRecorder::new(self.db.clone(), format!("{run}:{name}")));
             let running = pending
-                .start(self.start_time, executor.clone())
+                .start(self.start_time, operator_executor, signal_recorder)
                 .map_err(|e| BacktestError::ParallelOperatorFailed {
                     operator_name: name.clone(),
                     source: Box::new(e),
@@ -308,16 +358,30 @@ impl BacktestParallelEngine {
             .into();
         let mut next_settlement = settlements.pop_front();
 
+        // Funding settlements up to the start time, extended as settlements are applied
+        let mut settlement_history = self
+            .db
+            .funding_settlements
+            .get_latest_settlements(
+                self.start_time - Duration::seconds(1),
+                settlement_history_len as u32,
+            )
+            .await?;
+
         let mut time_cursor = start_candle.time + Duration::seconds(59);
         let mut minute_cursor_idx = start_candle_idx;
 
         let mut consolidator = if !self.shared_resolution_map.is_empty() {
             let initial_candles = &minute_buffer[..=start_candle_idx];
-            Some(MultiResolutionConsolidator::new(
-                self.shared_resolution_map,
-                initial_candles,
-                time_cursor,
-            )?)
+            Some(
+                MultiResolutionConsolidator::load(
+                    &self.db,
+                    self.shared_resolution_map,
+                    initial_candles,
+                    time_cursor,
+                )
+                .await?,
+            )
         } else {
             None
         };
@@ -334,104 +398,215 @@ impl BacktestParallelEngine {
             });
         }
 
+        // Trades closed from now on are reported to the operators
+        let mut trade_events = Vec::with_capacity(running_operators.len());
+        for (name, operator, executor) in &running_operators {
+            let mut tracker = TradeEventTracker::default();
+            poll_trade_events(executor, &mut tracker, start_candle.open, start_candle.open).await?;
+            trade_events.push(tracker);
+
+            operator
+                .on_start()
+                .await
+                .map_err(|e| BacktestError::ParallelOperatorFailed {
+                    operator_name: name.clone(),
+                    source: Box::new(e),
+                })?;
+        }
+
         // Next update will be at end of day (23:59:59), reported as midnight of following day
         let mut send_next_update_at = self.start_time + Duration::days(1) - Duration::seconds(1);
 
         self.status_manager.update(BacktestStatus::Running);
 
-        loop {
-            // Iterate all operators
-            for (name, operator, _) in &mut running_operators {
-                operator
-                    .iterate(time_cursor, consolidator.as_ref())
-                    .await
-                    .map_err(|e| BacktestError::ParallelOperatorFailed {
-                        operator_name: name.clone(),
-                        source: Box::new(e),
-                    })?;
-            }
-
-            if time_cursor >= send_next_update_at {
-                // Report trading state as midnight UTC of each backtested day
-                let update_time = send_next_update_at + Duration::seconds(1);
-
-                for (name, _, executor) in &running_operators {
-                    executor
-                        .update_time(update_time)
+        // Buffered signals are persisted even if the simulation fails
+        let simulation_result: Result<()> = async {
+            loop {
+                let context = OperatorContext::new(
+                    time_cursor,
+                    time_cursor,
+                    minute_buffer[minute_cursor_idx].close,
+                    &settlement_history,
+                );
+
+                let candle = &minute_buffer[minute_cursor_idx];
+                let last_price = candle.close;
+
+                // Iterate all operators
+                for ((name, operator, executor), tracker) in
+                    running_operators.iter_mut().zip(&mut trade_events)
+                {
+                    operator
+                        .iterate(&context, consolidator.as_ref(), candle)
                         .await
-                        .map_err(BacktestError::ExecutorTickUpdate)?;
+                        .map_err(|e| BacktestError::ParallelOperatorFailed {
+                            operator_name: name.clone(),
+                            source: Box::new(e),
+                        })?;
+
+                    for event in
+                        poll_trade_events(executor, tracker, last_price, last_price).await?
+                    {
+                        operator.on_event(&event).await.map_err(|e| {
+                            BacktestError::ParallelOperatorFailed {
+                                operator_name: name.clone(),
+                                source: Box::new(e),
+                            }
+                        })?;
+                    }
+                }
 
-                    let trades_state = executor
-                        .trading_state()
-                        .await
-                        .map_err(BacktestError::ExecutorStateEvaluation)?;
+                if time_cursor >= send_next_update_at {
+                    // Report trading state as midnight UTC of each backtested day
+                    let update_time = send_next_update_at + Duration::seconds(1);
+
+                    for (name, _, executor) in &running_operators {
+                        executor
+                            .update_time(update_time)
+                            .await
+                            .map_err(BacktestError::ExecutorTickUpdate)?;
+
+                        let trades_state = executor
+                            .trading_state()
+                            .await
+                            .map_err(BacktestError::ExecutorStateEvaluation)?;
+
+                        // Ignore no-receivers errors
+                        let _ = self.update_tx.send(BacktestParallelUpdate::TradingState {
+                            operator_name: name.clone(),
+                            state: Box::new(trades_state),
+                        });
+                    }
 
-                    // Ignore no-receivers errors
-                    let _ = self.update_tx.send(BacktestParallelUpdate::TradingState {
-                        operator_name: name.clone(),
-                        state: Box::new(trades_state),
-                    });
+                    if let Some(key) = self.config.operator_state_key() {
+                        save_operator_states(&self.db, key, update_time, &running_operators)
+                            .await?;
+                    }
+
+                    send_next_update_at += Duration::days(1);
                 }
 
-                send_next_update_at += Duration::days(1);
-            }
+                if time_cursor >= self.end_time - Duration::seconds(1) {
+                    break;
+                }
 
-            if time_cursor >= self.end_time - Duration::seconds(1) {
-                break;
-            }
+                minute_cursor_idx += 1;
 
-            minute_cursor_idx += 1;
+                // Refetch buffer when exhausted
+                if minute_cursor_idx >= minute_buffer.len() {
+                    let new_buffer_to =
+                        (time_cursor + Duration::minutes(buffer_size)).min(self.end_time);
 
-            // Refetch buffer when exhausted
-            if minute_cursor_idx >= minute_buffer.len() {
-                let new_buffer_to =
-                    (time_cursor + Duration::minutes(buffer_size)).min(self.end_time);
+                    minute_buffer = self
+                        .db
+                        .ohlc_candles
+                        .get_candles(time_cursor, new_buffer_to)
+                        .await?;
 
-                minute_buffer = self
-                    .db
-                    .ohlc_candles
-                    .get_candles(time_cursor, new_buffer_to)
-                    .await?;
+                    if minute_buffer.is_empty() {
+                        return Err(BacktestError::UnexpectedEmptyBuffer { time: time_cursor });
+                    }
 
-                if minute_buffer.is_empty() {
-                    return Err(BacktestError::UnexpectedEmptyBuffer { time: time_cursor });
+                    minute_cursor_idx = 0;
                 }
 
-                minute_cursor_idx = 0;
-            }
+                // Advance time cursor to the end of the next candle's minute (skips gaps in data)
+                time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+
+                // Apply funding settlements that fall within the new time cursor. Applied before
+                // `candle_update` so that updated margin/leverage/liquidation are visible to the
+                // price-trigger liquidation check.
+                while let Some(settlement) = &next_settlement
+                    && settlement.time <= time_cursor
+                {
+                    for (_, _, executor) in &running_operators {
+                        executor
+                            .apply_funding_settlement(settlement)
+                            .await
+                            .map_err(BacktestError::FundingSettlementApplication)?;
+                    }
 
-            // Advance time cursor to the end of the next candle's minute (skips gaps in data)
-            time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+                    let event = OperatorEvent::FundingSettlement(settlement.clone());
 
-            // Apply funding settlements that fall within the new time cursor. Applied before
-            // `candle_update` so that updated margin/leverage/liquidation are visible to the
-            // price-trigger liquidation check.
-            while let Some(settlement) = &next_settlement
-                && settlement.time <= time_cursor
-            {
-                for (_, _, executor) in &running_operators {
+                    if settlement_history.len() == settlement_history_len {
+                        settlement_history.remove(0);
+                    }
+                    settlement_history.extend(next_settlement.take());
+                    next_settlement = settlements.pop_front();
+
+                    for (name, operator, _) in &running_operators {
+                        operator.on_event(&event).await.map_err(|e| {
+                            BacktestError::ParallelOperatorFailed {
+                                operator_name: name.clone(),
+                                source: Box::new(e),
+                            }
+                        })?;
+                    }
+                }
+
+                let next_minute_candle = &minute_buffer[minute_cursor_idx];
+
+                // Update all executors with the new candle
+                for ((name, operator, executor), tracker) in
+                    running_operators.iter().zip(&mut trade_events)
+                {
                     executor
-                        .apply_funding_settlement(settlement)
+                        .candle_update(next_minute_candle)
                         .await
-                        .map_err(BacktestError::FundingSettlementApplication)?;
+                        .map_err(BacktestError::ExecutorTickUpdate)?;
+
+                    for event in poll_trade_events(
+                        executor,
+                        tracker,
+                        next_minute_candle.low,
+                        next_minute_candle.high,
+                    )
+                    .await?
+                    {
+                        operator.on_event(&event).await.map_err(|e| {
+                            BacktestError::ParallelOperatorFailed {
+                                operator_name: name.clone(),
+                                source: Box::new(e),
+                            }
+                        })?;
+                    }
                 }
 
-                next_settlement = settlements.pop_front();
+                if let Some(consolidator) = &mut consolidator {
+                    consolidator.push(next_minute_candle)?;
+                }
+            }
+            for (name, operator, _) in &running_operators {
+                operator.on_shutdown().await.map_err(|e| {
+                    BacktestError::ParallelOperatorFailed {
+                        operator_name: name.clone(),
+                        source: Box::new(e),
+                    }
+                })?;
             }
 
-            let next_minute_candle = &minute_buffer[minute_cursor_idx];
+            Ok(())
+        }
+        .await;
 
-            // Update all executors with the new candle
-            for (_, _, executor) in &running_operators {
-                executor
-                    .candle_update(next_minute_candle)
+        let mut flush_result = Ok(());
+        for (name, operator, _) in &mut running_operators {
+            let operator_flush_result =
+                operator
+                    .flush_signals()
                     .await
-                    .map_err(BacktestError::ExecutorTickUpdate)?;
-            }
+                    .map_err(|e| BacktestError::ParallelOperatorFailed {
+                        operator_name: name.clone(),
+                        source: Box::new(e),
+                    });
+            flush_result = flush_result.and(operator_flush_result);
+        }
 
-            if let Some(consolidator) = &mut consolidator {
-                consolidator.push(next_minute_candle)?;
-            }
+        simulation_result?;
+        flush_result?;
+
+        if let Some(key) = self.config.operator_state_key() {
+            save_operator_states(&self.db, key, self.end_time, &running_operators).await?;
         }
 
         Ok(())
@@ -458,3 +633,27 @@ impl BacktestParallelEngine {
         BacktestParallelController::new(handle, status_manager)
     }
 }
+
+/// Persists the state of each operator under `"{key}:{operator_name}"`, to be restored by
+/// backtests starting at `checkpoint`, if the operator returns one.
+async fn save_operator_states(
+    db: &Database,
+    key: &str,
+    checkpoint: DateTime<Utc>,
+    running_operators: &[(String, ParallelOperatorRunning, Arc<SimulatedTradeExecutor>)],
+) -> Result<()> {
+    for (name, operator, _) in running_operators {
+        let state = operator
+            .save_state()
+            .map_err(|e| BacktestError::ParallelOperatorFailed {
+                operator_name: name.clone(),
+                source: Box::new(e),
+            })?;
+
+        if let Some(state) = state {
+            store_operator_state(db, &format!("{key}:{name}"), checkpoint, state).await?;
+        }
+    }
+
+    Ok(())
+}
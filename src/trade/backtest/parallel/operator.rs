@@ -4,12 +4,13 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
 use crate::{
+    db::models::OhlcCandleRow,
     shared::{Lookback, OhlcResolution, Period},
-    signal::{Signal, SignalEvaluator},
+    signal::{Signal, SignalEvaluator, SignalRecorder},
 };
 
 use super::super::{
-    super::core::TradeExecutor,
+    super::{context::OperatorContext, core::TradeExecutor, events::OperatorEvent},
     consolidator::MultiResolutionConsolidator,
     error::Result,
     operator::{
@@ -21,21 +22,30 @@ use super::super::{
 pub(super) trait AnySignalOperatorPending: Send {
     fn resolution_to_max_period(&self) -> &HashMap<OhlcResolution, Period>;
     fn max_lookback(&self) -> Option<Lookback>;
+    fn funding_settlements_lookback(&self) -> Option<Period>;
+    fn restore_state(&mut self, state: &[u8]) -> Result<()>;
     fn start(
         self: Box<Self>,
         start_time: DateTime<Utc>,
         trade_executor: Arc<dyn TradeExecutor>,
+        signal_recorder: Option<SignalRecorder>,
     ) -> Result<Box<dyn AnySignalOperatorRunning>>;
 }
 
 /// Type-erased signal operator in running state.
 #[async_trait]
 pub(super) trait AnySignalOperatorRunning: Send + Sync {
+    fn save_state(&self) -> Result<Option<Vec<u8>>>;
     async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()>;
+    async fn flush_signals(&mut self) -> Result<()>;
+    async fn on_start(&self) -> Result<()>;
+    async fn on_event(&self, event: &OperatorEvent) -> Result<()>;
+    async fn on_shutdown(&self) -> Result<()>;
 }
 
 impl<S: Signal> AnySignalOperatorPending for SignalOperatorPending<S> {
@@ -47,24 +57,54 @@ impl<S: Signal> AnySignalOperatorPending for SignalOperatorPending<S> {
         self.max_lookback()
     }
 
+    fn funding_settlements_lookback(&self) -> Option<Period> {
+        self.funding_settlements_lookback()
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        self.restore_state(state)
+    }
+
     fn start(
         self: Box<Self>,
         start_time: DateTime<Utc>,
         trade_executor: Arc<dyn TradeExecutor>,
+        signal_recorder: Option<SignalRecorder>,
     ) -> Result<Box<dyn AnySignalOperatorRunning>> {
-        let running = (*self).start(start_time, trade_executor)?;
+        let running = (*self).start(start_time, trade_executor, signal_recorder)?;
         Ok(Box::new(running))
     }
 }
 
 #[async_trait]
 impl<S: Signal> AnySignalOperatorRunning for SignalOperatorRunning<S> {
+    fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        self.save_state()
+    }
+
     async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()> {
-        self.iterate(time_cursor, consolidator).await
+        self.iterate(context, consolidator, candle).await
+    }
+
+    async fn flush_signals(&mut self) -> Result<()> {
+        self.flush_signals().await
+    }
+
+    async fn on_start(&self) -> Result<()> {
+        self.on_start().await
+    }
+
+    async fn on_event(&self, event: &OperatorEvent) -> Result<()> {
+        self.on_event(event).await
+    }
+
+    async fn on_shutdown(&self) -> Result<()> {
+        self.on_shutdown().await
     }
 }
 
@@ -106,15 +146,33 @@ impl ParallelOperatorPending {
         }
     }
 
-    /// Starts the operator, transitioning it to the running state.
+    /// Returns the number of funding settlements needed by this operator.
+    pub fn funding_settlements_lookback(&self) -> Option<Period> {
+        match self {
+            Self::Signal(pending) => pending.funding_settlements_lookback(),
+            Self::Raw(pending) => pending.funding_settlements_lookback(),
+        }
+    }
+
+    /// Restores the operator state from a persisted snapshot.
+    pub fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        match self {
+            Self::Signal(pending) => pending.restore_state(state),
+            Self::Raw(pending) => pending.restore_state(state),
+        }
+    }
+
+    /// Starts the operator, transitioning it to the running state. The signal recorder is only
+    /// used by signal operators.
     pub fn start(
         self,
         start_time: DateTime<Utc>,
         trade_executor: Arc<dyn TradeExecutor>,
+        signal_recorder: Option<SignalRecorder>,
     ) -> Result<ParallelOperatorRunning> {
         match self {
             Self::Signal(pending) => pending
-                .start(start_time, trade_executor)
+                .start(start_time, trade_executor, signal_recorder)
                 .map(ParallelOperatorRunning::Signal),
             Self::Raw(pending) => pending
                 .start(start_time, trade_executor)
@@ -130,15 +188,56 @@ pub(super) enum ParallelOperatorRunning {
 }
 
 impl ParallelOperatorRunning {
+    /// Returns a snapshot of the operator state to be persisted, if any.
+    pub fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Signal(running) => running.save_state(),
+            Self::Raw(running) => running.save_state(),
+        }
+    }
+
     /// Iterates the operator with the given time cursor and consolidator.
     pub async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()> {
         match self {
-            Self::Signal(running) => running.iterate(time_cursor, consolidator).await,
-            Self::Raw(running) => running.iterate(time_cursor, consolidator).await,
+            Self::Signal(running) => running.iterate(context, consolidator, candle).await,
+            Self::Raw(running) => running.iterate(context, consolidator, candle).await,
+        }
+    }
+
+    /// Persists the signals still buffered by the operator, if any.
+    pub async fn flush_signals(&mut self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.flush_signals().await,
+            Self::Raw(_) => Ok(()),
+        }
+    }
+
+    /// Invokes the operator's start hook.
+    pub async fn on_start(&self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_start().await,
+            Self::Raw(running) => running.on_start().await,
+        }
+    }
+
+    /// Forwards an event to the corresponding operator hook.
+    pub async fn on_event(&self, event: &OperatorEvent) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_event(event).await,
+            Self::Raw(running) => running.on_event(event).await,
+        }
+    }
+
+    /// Invokes the operator's shutdown hook.
+    pub async fn on_shutdown(&self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_shutdown().await,
+            Self::Raw(running) => running.on_shutdown().await,
         }
     }
 }
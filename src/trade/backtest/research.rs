@@ -0,0 +1,779 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    db::{Database, models::FundingSettlementRow},
+    signal::{DirectionalSignal, SignalDirection, SignalEvaluator},
+    sync::{FundingSettlementsState, LNM_SETTLEMENT_A_START, PriceHistoryState},
+    util::DateTimeExt,
+};
+
+use super::{
+    super::context::OperatorContext,
+    config::SignalResearchConfig,
+    consolidator::MultiResolutionConsolidator,
+    error::{BacktestError, Result},
+    operator::SignalEvaluatorsPending,
+};
+
+/// Forward return statistics of a signal class at a given horizon.
+///
+/// Returns are measured in percent, from the close price of the minute a signal was emitted to the
+/// close price of the minute ending at the horizon.
+#[derive(Debug, Clone)]
+pub struct ForwardReturnStats {
+    horizon: Duration,
+    direction: SignalDirection,
+    sample_count: usize,
+    hit_rate: Option<f64>,
+    mean_return: f64,
+    std_dev: f64,
+    min_return: f64,
+    percentile_25: f64,
+    median: f64,
+    percentile_75: f64,
+    max_return: f64,
+}
+
+impl ForwardReturnStats {
+    /// Returns `None` if there are no samples.
+    fn new(horizon: Duration, direction: SignalDirection, mut samples: Vec<f64>) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort_by(f64::total_cmp);
+
+        let sample_count = samples.len();
+        let mean_return = samples.iter().sum::<f64>() / sample_count as f64;
+        let variance = samples
+            .iter()
+            .map(|r| (r - mean_return).powi(2))
+            .sum::<f64>()
+            / sample_count as f64;
+
+        let hit_rate = match direction {
+            SignalDirection::Long => Some(samples.iter().filter(|&&r| r > 0.).count()),
+            SignalDirection::Short => Some(samples.iter().filter(|&&r| r < 0.).count()),
+            SignalDirection::Neutral => None,
+        }
+        .map(|hits| hits as f64 / sample_count as f64);
+
+        // Linear interpolation between the closest ranks
+        let percentile = |p: f64| {
+            let rank = p * (sample_count - 1) as f64;
+            let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+            samples[low] + (samples[high] - samples[low]) * (rank - low as f64)
+        };
+
+        Some(Self {
+            horizon,
+            direction,
+            sample_count,
+            hit_rate,
+            mean_return,
+            std_dev: variance.sqrt(),
+            min_return: samples[0],
+            percentile_25: percentile(0.25),
+            median: percentile(0.5),
+            percentile_75: percentile(0.75),
+            max_return: samples[sample_count - 1],
+        })
+    }
+
+    /// Returns the horizon at which the forward returns were measured.
+    pub fn horizon(&self) -> Duration {
+        self.horizon
+    }
+
+    /// Returns the number of signals whose forward return could be measured at the horizon.
+    pub fn sample_count(&self) -> usize {
+        self.sample_count
+    }
+
+    /// Returns the fraction of signals followed by a move in their direction (rising prices for
+    /// long signals, falling prices for short signals). `None` for neutral signals.
+    pub fn hit_rate(&self) -> Option<f64> {
+        self.hit_rate
+    }
+
+    /// Returns the mean forward return (in percent).
+    pub fn mean_return(&self) -> f64 {
+        self.mean_return
+    }
+
+    /// Returns the mean forward return in the signal's direction (in percent), positive when the
+    /// signal anticipated the move. `None` for neutral signals.
+    pub fn edge(&self) -> Option<f64> {
+        match self.direction {
+            SignalDirection::Neutral => None,
+            direction => Some(self.mean_return * direction.as_vote()),
+        }
+    }
+
+    /// Returns the standard deviation of the forward returns (in percent).
+    pub fn std_dev(&self) -> f64 {
+        self.std_dev
+    }
+
+    /// Returns the lowest forward return (in percent).
+    pub fn min_return(&self) -> f64 {
+        self.min_return
+    }
+
+    /// Returns the 25th percentile of the forward returns (in percent).
+    pub fn percentile_25(&self) -> f64 {
+        self.percentile_25
+    }
+
+    /// Returns the median forward return (in percent).
+    pub fn median(&self) -> f64 {
+        self.median
+    }
+
+    /// Returns the 75th percentile of the forward returns (in percent).
+    pub fn percentile_75(&self) -> f64 {
+        self.percentile_75
+    }
+
+    /// Returns the highest forward return (in percent).
+    pub fn max_return(&self) -> f64 {
+        self.max_return
+    }
+}
+
+/// Forward return statistics of the signals emitted by an evaluator in a given direction.
+#[derive(Debug, Clone)]
+pub struct SignalClassStats {
+    evaluator: String,
+    direction: SignalDirection,
+    signal_count: usize,
+    horizons: Vec<ForwardReturnStats>,
+}
+
+impl SignalClassStats {
+    /// Returns the name of the evaluator that emitted the signals.
+    pub fn evaluator(&self) -> &str {
+        &self.evaluator
+    }
+
+    /// Returns the direction of the signals.
+    pub fn direction(&self) -> SignalDirection {
+        self.direction
+    }
+
+    /// Returns the number of signals emitted, including those too close to the end of the run to
+    /// be measured at every horizon.
+    pub fn signal_count(&self) -> usize {
+        self.signal_count
+    }
+
+    /// Returns the statistics at each horizon, in ascending order. Horizons without any measured
+    /// signal are omitted.
+    pub fn horizons(&self) -> &[ForwardReturnStats] {
+        &self.horizons
+    }
+
+    /// Returns the statistics at the given horizon, if any signal was measured at it.
+    pub fn horizon(&self, horizon: Duration) -> Option<&ForwardReturnStats> {
+        self.horizons.iter().find(|stats| stats.horizon == horizon)
+    }
+
+    /// Returns the [`edge`](ForwardReturnStats::edge) at each horizon, in ascending order,
+    /// describing how the predictive value of the signals decays over time. Empty for neutral
+    /// signals.
+    pub fn decay(&self) -> Vec<(Duration, f64)> {
+        self.horizons
+            .iter()
+            .filter_map(|stats| Some((stats.horizon, stats.edge()?)))
+            .collect()
+    }
+}
+
+/// Forward returns collected during a research run, grouped by evaluator and direction.
+struct ForwardReturns {
+    horizons: Vec<Duration>,
+    /// Signals awaiting measurement at each horizon: `(class_idx, signal_time, signal_price)`
+    pending: Vec<VecDeque<(usize, DateTime<Utc>, f64)>>,
+    class_indices: HashMap<(usize, SignalDirection), usize>,
+    /// `(evaluator_idx, direction, signal_count, samples_per_horizon)`
+    classes: Vec<(usize, SignalDirection, usize, Vec<Vec<f64>>)>,
+}
+
+impl ForwardReturns {
+    fn new(horizons: Vec<Duration>) -> Self {
+        Self {
+            pending: vec![VecDeque::new(); horizons.len()],
+            horizons,
+            class_indices: HashMap::new(),
+            classes: Vec::new(),
+        }
+    }
+
+    /// Registers a signal emitted at `time`, when the price was `price`.
+    fn observe(
+        &mut self,
+        evaluator_idx: usize,
+        direction: SignalDirection,
+        time: DateTime<Utc>,
+        price: f64,
+    ) {
+        let class_idx = *self
+            .class_indices
+            .entry((evaluator_idx, direction))
+            .or_insert_with(|| {
+                let samples = vec![Vec::new(); self.horizons.len()];
+                self.classes.push((evaluator_idx, direction, 0, samples));
+                self.classes.len() - 1
+            });
+
+        self.classes[class_idx].2 += 1;
+
+        for pending in &mut self.pending {
+            pending.push_back((class_idx, time, price));
+        }
+    }
+
+    /// Measures the forward returns of the signals whose horizon was reached by `time`, when the
+    /// price is `price`.
+    fn resolve(&mut self, time: DateTime<Utc>, price: f64) {
+        for (horizon_idx, pending) in self.pending.iter_mut().enumerate() {
+            let horizon = self.horizons[horizon_idx];
+
+            while let Some(&(class_idx, signal_time, signal_price)) = pending.front()
+                && signal_time + horizon <= time
+            {
+                let forward_return = (price - signal_price) / signal_price * 100.;
+                self.classes[class_idx].3[horizon_idx].push(forward_return);
+                pending.pop_front();
+            }
+        }
+    }
+
+    fn into_stats(self, evaluator_names: &[String]) -> Vec<SignalClassStats> {
+        let direction_order = |direction: SignalDirection| match direction {
+            SignalDirection::Long => 0,
+            SignalDirection::Short => 1,
+            SignalDirection::Neutral => 2,
+        };
+
+        let mut classes = self.classes;
+        classes.sort_by_key(|(evaluator_idx, direction, _, _)| {
+            (*evaluator_idx, direction_order(*direction))
+        });
+
+        classes
+            .into_iter()
+            .map(|(evaluator_idx, direction, signal_count, samples)| {
+                let horizons = self
+                    .horizons
+                    .iter()
+                    .zip(samples)
+                    .filter_map(|(horizon, samples)| {
+                        ForwardReturnStats::new(*horizon, direction, samples)
+                    })
+                    .collect();
+
+                SignalClassStats {
+                    evaluator: evaluator_names[evaluator_idx].clone(),
+                    direction,
+                    signal_count,
+                    horizons,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Forward return statistics of the signals emitted during a [`SignalResearchEngine`] run.
+#[derive(Debug, Clone)]
+pub struct SignalResearchReport {
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+    horizons: Vec<Duration>,
+    classes: Vec<SignalClassStats>,
+}
+
+impl SignalResearchReport {
+    /// Returns the start time of the research period.
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+
+    /// Returns the end time of the research period.
+    pub fn end_time(&self) -> DateTime<Utc> {
+        self.end_time
+    }
+
+    /// Returns the horizons at which forward returns were measured, in ascending order.
+    pub fn horizons(&self) -> &[Duration] {
+        &self.horizons
+    }
+
+    /// Returns the statistics of every signal class, ordered by evaluator (in the order they were
+    /// provided) and then by direction. Classes without any emitted signal are omitted.
+    pub fn classes(&self) -> &[SignalClassStats] {
+        &self.classes
+    }
+
+    /// Returns the statistics of the signals emitted by the named evaluator in the given
+    /// direction, if any.
+    pub fn class(&self, evaluator: &str, direction: SignalDirection) -> Option<&SignalClassStats> {
+        self.classes
+            .iter()
+            .find(|class| class.evaluator == evaluator && class.direction == direction)
+    }
+
+    /// Returns a formatted table with the statistics of every signal class at every horizon.
+    pub fn to_table(&self) -> String {
+        if self.classes.is_empty() {
+            return "No signals emitted.".to_string();
+        }
+
+        let name_width = self
+            .classes
+            .iter()
+            .map(|class| class.evaluator.len())
+            .max()
+            .unwrap_or_default()
+            .max("evaluator".len());
+
+        let fmt_opt = |value: Option<f64>, scale: f64| {
+            value.map_or("-".to_string(), |value| format!("{:.3}", value * scale))
+        };
+
+        let mut table = format!(
+            "{:<name_width$} | {:>7} | {:>7} | {:>7} | {:>7} | {:>8} | {:>8} | {:>8} | {:>8} | {:>8} | {:>8}",
+            "evaluator",
+            "class",
+            "horizon",
+            "samples",
+            "hit %",
+            "mean %",
+            "edge %",
+            "std %",
+            "p25 %",
+            "median %",
+            "p75 %"
+        );
+
+        table.push_str(&format!("\n{}", "-".repeat(name_width + 103)));
+
+        for class in &self.classes {
+            for stats in &class.horizons {
+                table.push_str(&format!(
+                    "\n{:<name_width$} | {:>7} | {:>6}m | {:>7} | {:>7} | {:>8.3} | {:>8} | {:>8.3} | {:>8.3} | {:>8.3} | {:>8.3}",
+                    class.evaluator,
+                    class.direction,
+                    stats.horizon.num_minutes(),
+                    stats.sample_count,
+                    fmt_opt(stats.hit_rate, 100.),
+                    stats.mean_return,
+                    fmt_opt(stats.edge(), 1.),
+                    stats.std_dev,
+                    stats.percentile_25,
+                    stats.median,
+                    stats.percentile_75
+                ));
+            }
+        }
+
+        table
+    }
+}
+
+impl fmt::Display for SignalResearchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let horizons = self
+            .horizons
+            .iter()
+            .map(|horizon| format!("{}m", horizon.num_minutes()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let signal_count: usize = self.classes.iter().map(|class| class.signal_count).sum();
+
+        writeln!(f, "Signal Research Report:")?;
+        writeln!(f, "  period: {} to {}", self.start_time, self.end_time)?;
+        writeln!(f, "  horizons: {horizons}")?;
+        writeln!(f, "  signal classes: {}", self.classes.len())?;
+        write!(f, "  signals emitted: {signal_count}")
+    }
+}
+
+/// Evaluator names identify signal classes in the report, so they must be unique.
+fn check_unique_names(names: &[String]) -> Result<()> {
+    let mut seen = HashSet::new();
+
+    for name in names {
+        if !seen.insert(name.as_str()) {
+            return Err(BacktestError::ResearchDuplicateEvaluatorName { name: name.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs signal evaluators over historical candles without an operator or trade executor, and
+/// measures the forward returns that followed each emitted signal.
+///
+/// Signals are grouped into classes by evaluator name and [`SignalDirection`], and each class is
+/// summarized at every horizon of the [`SignalResearchConfig`]. Evaluators are scheduled exactly
+/// as in a [`BacktestEngine`](crate::trade::BacktestEngine) run, so the statistics describe the
+/// signals an operator would receive. Signals emitted too close to the end time to reach a
+/// horizon are not measured at that horizon.
+pub struct SignalResearchEngine<S: DirectionalSignal> {
+    config: SignalResearchConfig,
+    db: Arc<Database>,
+    evaluators: SignalEvaluatorsPending<S>,
+    evaluator_names: Vec<String>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+}
+
+impl<S: DirectionalSignal> SignalResearchEngine<S> {
+    /// Creates a new research engine for the given evaluators over the given time range. Start and
+    /// end times must be rounded to minutes, and the price history must be available for the
+    /// whole range, including the evaluators' lookback.
+    ///
+    /// Evaluator names must be unique. Since [`SignalEvaluator::name`] defaults to the type name,
+    /// evaluators of the same type with different parameters must override it.
+    pub async fn new(
+        config: SignalResearchConfig,
+        db: Arc<Database>,
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        start_time: DateTime<Utc>,
+        end_time: DateTime<Utc>,
+    ) -> Result<Self> {
+        if !start_time.is_round_minute() || !end_time.is_round_minute() {
+            return Err(BacktestError::InvalidTimeRangeNotRounded {
+                start_time,
+                end_time,
+            });
+        }
+
+        if end_time <= start_time {
+            return Err(BacktestError::InvalidTimeRangeSequence {
+                start_time,
+                end_time,
+            });
+        }
+
+        let evaluators = SignalEvaluatorsPending::new(evaluators)?;
+        let evaluator_names = evaluators.names()?;
+        check_unique_names(&evaluator_names)?;
+
+        let price_history_state = PriceHistoryState::evaluate(&db)
+            .await
+            .map_err(BacktestError::PriceHistoryStateEvaluation)?;
+
+        let lookback_time = if let Some(lookback) = evaluators.max_lookback() {
+            start_time.step_back_candles(lookback.resolution(), lookback.period().as_u32() - 1)
+        } else {
+            start_time
+        };
+
+        if !price_history_state
+            .is_range_available(lookback_time, end_time)
+            .map_err(BacktestError::PriceHistoryStateEvaluation)?
+        {
+            return Err(BacktestError::PriceHistoryUnavailable {
+                lookback_time,
+                end_time,
+                history_start: price_history_state.bound_start(),
+                history_end: price_history_state.bound_end(),
+            });
+        }
+
+        if evaluators.funding_settlements_lookback().is_some() {
+            let settlement_from = start_time.ceil_funding_settlement_time();
+            let settlement_to = end_time.floor_funding_settlement_time();
+
+            let funding_settlements_state = FundingSettlementsState::evaluate(&db)
+                .await
+                .map_err(BacktestError::FundingSettlementsStateEvaluation)?;
+
+            // Settlement data is only required for the portion of the range that overlaps with the
+            // data provided by the LNM API
+            if settlement_to >= LNM_SETTLEMENT_A_START
+                && !funding_settlements_state
+                    .is_range_available(settlement_from.max(LNM_SETTLEMENT_A_START), settlement_to)
+            {
+                return Err(BacktestError::FundingSettlementDataUnavailable {
+                    from: settlement_from,
+                    to: settlement_to,
+                    bound_start: funding_settlements_state.bound_start(),
+                    bound_end: funding_settlements_state.bound_end(),
+                });
+            }
+        }
+
+        Ok(Self {
+            config,
+            db,
+            evaluators,
+            evaluator_names,
+            start_time,
+            end_time,
+        })
+    }
+
+    /// Returns the start time of the research period.
+    pub fn start_time(&self) -> DateTime<Utc> {
+        self.start_time
+    }
+
+    /// Returns the end time of the research period.
+    pub fn end_time(&self) -> DateTime<Utc> {
+        self.end_time
+    }
+
+    /// Runs the evaluators over the research period and returns the forward return statistics of
+    /// the emitted signals.
+    pub async fn run(self) -> Result<SignalResearchReport> {
+        let buffer_size = self.config.buffer_size() as i64;
+
+        let resolution_to_max_period = self.evaluators.resolution_to_max_period().clone();
+
+        let buffer_from = MultiResolutionConsolidator::earliest_bucket_start(
+            resolution_to_max_period.keys(),
+            self.start_time,
+        );
+        let buffer_to = buffer_from + Duration::minutes(buffer_size);
+        let mut minute_buffer = self
+            .db
+            .ohlc_candles
+            .get_candles(buffer_from, buffer_to)
+            .await?;
+
+        let mut minute_cursor_idx = minute_buffer
+            .iter()
+            .position(|c| c.time >= self.start_time)
+            .ok_or(BacktestError::UnexpectedEmptyBuffer {
+                time: self.start_time,
+            })?;
+
+        let mut settlements: VecDeque<FundingSettlementRow> =
+            match self.evaluators.funding_settlements_lookback() {
+                Some(_) => self
+                    .db
+                    .funding_settlements
+                    .get_settlements(
+                        self.start_time.ceil_funding_settlement_time(),
+                        self.end_time.floor_funding_settlement_time(),
+                    )
+                    .await?
+                    .into(),
+                None => VecDeque::new(),
+            };
+        let mut next_settlement = settlements.pop_front();
+
+        let settlement_history_len = self
+            .evaluators
+            .funding_settlements_lookback()
+            .map_or(0, |lookback| lookback.as_usize());
+
+        let mut settlement_history = if settlement_history_len > 0 {
+            self.db
+                .funding_settlements
+                .get_latest_settlements(
+                    self.start_time - Duration::seconds(1),
+                    settlement_history_len as u32,
+                )
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        let mut evaluators = self.evaluators.start(self.start_time, None);
+
+        let mut time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+
+        let mut consolidator = if !resolution_to_max_period.is_empty() {
+            let initial_candles = &minute_buffer[..=minute_cursor_idx];
+            Some(
+                MultiResolutionConsolidator::load(
+                    &self.db,
+                    resolution_to_max_period,
+                    initial_candles,
+                    time_cursor,
+                )
+                .await?,
+            )
+        } else {
+            None
+        };
+
+        let mut forward_returns = ForwardReturns::new(self.config.horizons().to_vec());
+
+        loop {
+            let last_price = minute_buffer[minute_cursor_idx].close;
+
+            forward_returns.resolve(time_cursor, last_price);
+
+            let context =
+                OperatorContext::new(time_cursor, time_cursor, last_price, &settlement_history);
+
+            for (evaluator_idx, signal) in
+                evaluators.evaluate(&context, consolidator.as_ref()).await?
+            {
+                forward_returns.observe(evaluator_idx, signal.direction(), time_cursor, last_price);
+            }
+
+            if time_cursor >= self.end_time - Duration::seconds(1) {
+                break;
+            }
+
+            minute_cursor_idx += 1;
+
+            // Refetch buffer when exhausted
+            if minute_cursor_idx >= minute_buffer.len() {
+                let new_buffer_to =
+                    (time_cursor + Duration::minutes(buffer_size)).min(self.end_time);
+
+                minute_buffer = self
+                    .db
+                    .ohlc_candles
+                    .get_candles(time_cursor, new_buffer_to)
+                    .await?;
+
+                if minute_buffer.is_empty() {
+                    return Err(BacktestError::UnexpectedEmptyBuffer { time: time_cursor });
+                }
+
+                minute_cursor_idx = 0;
+            }
+
+            // Advance time cursor to the end of the next candle's minute (skips gaps in data)
+            time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+
+            while let Some(settlement) = &next_settlement
+                && settlement.time <= time_cursor
+            {
+                if settlement_history.len() == settlement_history_len {
+                    settlement_history.remove(0);
+                }
+                settlement_history.extend(next_settlement.take());
+                next_settlement = settlements.pop_front();
+            }
+
+            if let Some(consolidator) = &mut consolidator {
+                consolidator.push(&minute_buffer[minute_cursor_idx])?;
+            }
+        }
+
+        Ok(SignalResearchReport {
+            start_time: self.start_time,
+            end_time: self.end_time,
+            horizons: self.config.horizons().to_vec(),
+            classes: forward_returns.into_stats(&self.evaluator_names),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forward_return_stats_summarize_samples() {
+        let samples = vec![2.0, -1.0, 0.5, 3.0, -0.5];
+
+        let long = ForwardReturnStats::new(Duration::hours(1), SignalDirection::Long, samples)
+            .expect("samples are not empty");
+
+        assert_eq!(long.sample_count(), 5);
+        assert_eq!(long.hit_rate(), Some(0.6));
+        assert!((long.mean_return() - 0.8).abs() < 1e-9);
+        assert_eq!(long.edge(), Some(long.mean_return()));
+        assert_eq!(long.min_return(), -1.0);
+        assert_eq!(long.percentile_25(), -0.5);
+        assert_eq!(long.median(), 0.5);
+        assert_eq!(long.percentile_75(), 2.0);
+        assert_eq!(long.max_return(), 3.0);
+
+        let short =
+            ForwardReturnStats::new(Duration::hours(1), SignalDirection::Short, vec![-1.0, 3.0])
+                .expect("samples are not empty");
+
+        assert_eq!(short.hit_rate(), Some(0.5));
+        assert_eq!(short.edge(), Some(-1.0));
+        assert_eq!(short.median(), 1.0);
+
+        let neutral =
+            ForwardReturnStats::new(Duration::hours(1), SignalDirection::Neutral, vec![1.0])
+                .expect("samples are not empty");
+
+        assert_eq!(neutral.hit_rate(), None);
+        assert_eq!(neutral.edge(), None);
+
+        assert!(
+            ForwardReturnStats::new(Duration::hours(1), SignalDirection::Long, vec![]).is_none()
+        );
+    }
+
+    #[test]
+    fn duplicate_evaluator_names_are_rejected() {
+        let names = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(check_unique_names(&names(&["ema", "rsi"])).is_ok());
+        assert!(matches!(
+            check_unique_names(&names(&["ema", "rsi", "ema"])),
+            Err(BacktestError::ResearchDuplicateEvaluatorName { name }) if name == "ema"
+        ));
+    }
+
+    #[test]
+    fn forward_returns_are_measured_at_each_horizon() {
+        let start = DateTime::from_timestamp(1_700_000_040, 0).unwrap() + Duration::seconds(59);
+        let minute = |min: i64| start + Duration::minutes(min);
+
+        let mut forward_returns =
+            ForwardReturns::new(vec![Duration::minutes(1), Duration::minutes(3)]);
+
+        forward_returns.observe(0, SignalDirection::Long, minute(0), 100.);
+        forward_returns.observe(1, SignalDirection::Short, minute(0), 100.);
+        forward_returns.resolve(minute(1), 110.);
+        forward_returns.observe(0, SignalDirection::Long, minute(1), 110.);
+        forward_returns.resolve(minute(2), 99.);
+        forward_returns.resolve(minute(3), 90.);
+
+        let names = vec!["first".to_string(), "second".to_string()];
+        let stats = forward_returns.into_stats(&names);
+
+        assert_eq!(stats.len(), 2);
+
+        let long = &stats[0];
+        assert_eq!(long.evaluator(), "first");
+        assert_eq!(long.direction(), SignalDirection::Long);
+        assert_eq!(long.signal_count(), 2);
+        assert_eq!(long.horizons().len(), 2);
+
+        // +10% and -10% after one minute
+        let one_min = long.horizon(Duration::minutes(1)).unwrap();
+        assert_eq!(one_min.sample_count(), 2);
+        assert_eq!(one_min.hit_rate(), Some(0.5));
+        assert!(one_min.mean_return().abs() < 1e-9);
+
+        // Only the first signal reached the 3 minute horizon
+        let three_min = long.horizon(Duration::minutes(3)).unwrap();
+        assert_eq!(three_min.sample_count(), 1);
+        assert!((three_min.mean_return() + 10.).abs() < 1e-9);
+
+        let short = &stats[1];
+        assert_eq!(short.evaluator(), "second");
+        assert_eq!(short.signal_count(), 1);
+        assert_eq!(short.decay().len(), 2);
+        assert!((short.decay()[0].1 + 10.).abs() < 1e-9);
+        assert!((short.decay()[1].1 - 10.).abs() < 1e-9);
+    }
+}
@@ -5,25 +5,31 @@ use tokio::sync::broadcast;
 
 use crate::{
     db::{Database, models::FundingSettlementRow},
-    signal::{Signal, SignalEvaluator},
+    signal::{Signal, SignalEvaluator, SignalRecorder},
     sync::{FundingSettlementsState, LNM_SETTLEMENT_A_START, PriceHistoryState},
     util::DateTimeExt,
 };
 
 use super::{
     super::{
-        super::core::{Raw, RawOperator, SignalOperator, TradeExecutor},
+        super::{
+            context::OperatorContext,
+            core::{Raw, RawOperator, SignalOperator, TradeExecutor},
+            events::{OperatorEvent, TradeEventTracker},
+            middleware::{self, TradeExecutorMiddleware},
+        },
         config::BacktestConfig,
         consolidator::MultiResolutionConsolidator,
         error::{BacktestError, Result},
         executor::SimulatedTradeExecutor,
+        operator::{load_operator_state, poll_trade_events, store_operator_state},
         state::{
             BacktestReceiver, BacktestStatus, BacktestStatusManager, BacktestTransmitter,
             BacktestUpdate,
         },
     },
     controller::BacktestController,
-    operator::OperatorPending,
+    operator::{OperatorPending, OperatorRunning},
 };
 
 /// Builder for configuring and executing a backtest simulation. Encapsulates the configuration,
@@ -33,9 +39,11 @@ pub struct BacktestEngine<S: Signal> {
     config: BacktestConfig,
     db: Arc<Database>,
     operator_pending: OperatorPending<S>,
+    middleware: Vec<Arc<dyn TradeExecutorMiddleware>>,
     start_time: DateTime<Utc>,
     start_balance: u64,
     end_time: DateTime<Utc>,
+    state_checkpoint: Option<DateTime<Utc>>,
     status_manager: Arc<BacktestStatusManager<BacktestUpdate>>,
     update_tx: BacktestTransmitter,
 }
@@ -99,6 +107,8 @@ impl<S: Signal> BacktestEngine<S> {
             });
         }
 
+        config.validate_persistence(&db)?;
+
         let max_lookback = operator_pending.max_lookback();
 
         let price_history_state = PriceHistoryState::evaluate(&db)
@@ -154,14 +164,31 @@ impl<S: Signal> BacktestEngine<S> {
             config,
             db,
             operator_pending,
+            middleware: Vec::new(),
             start_time,
             start_balance,
             end_time,
+            state_checkpoint: None,
             status_manager,
             update_tx,
         })
     }
 
+    /// Sets the middleware layered around the trade executor handed to the operator. The first
+    /// middleware in the list is the outermost layer. Risk limits set in the config are applied
+    /// closest to the executor.
+    pub fn with_middleware(mut self, middleware: Vec<Arc<dyn TradeExecutorMiddleware>>) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
+    /// Additionally reports the trading state once the simulation reaches `time`, besides the
+    /// daily updates.
+    pub(crate) fn with_state_checkpoint(mut self, time: DateTime<Utc>) -> Self {
+        self.state_checkpoint = Some(time);
+        self
+    }
+
     /// Returns the start time of the backtest simulation period.
     pub fn start_time(&self) -> DateTime<Utc> {
         self.start_time
@@ -187,13 +214,14 @@ impl<S: Signal> BacktestEngine<S> {
 
         let buffer_size = self.config.buffer_size() as i64;
 
-        let max_lookback = self
-            .operator_pending
-            .max_lookback()
-            .map(|lb| lb.as_duration())
-            .unwrap_or(Duration::zero());
+        let resolution_to_max_period = self.operator_pending.resolution_to_max_period().clone();
 
-        let buffer_from = self.start_time - max_lookback;
+        // Completed lookback candles are loaded already consolidated. The minute buffer only
+        // needs to cover the current in-progress buckets.
+        let buffer_from = MultiResolutionConsolidator::earliest_bucket_start(
+            resolution_to_max_period.keys(),
+            self.start_time,
+        );
         let buffer_to = buffer_from + Duration::minutes(buffer_size);
         let mut minute_buffer = self
             .db
@@ -226,22 +254,60 @@ impl<S: Signal> BacktestEngine<S> {
 
         let mut next_settlement = settlements.pop_front();
 
-        let resolution_to_max_period = self.operator_pending.resolution_to_max_period().clone();
-
-        let mut operator = self
+        // Funding settlements kept as history, covering the latest settlement and the longest
+        // requested funding settlements lookback
+        let settlement_history_len = self
             .operator_pending
-            .start(self.start_time, trades_executor.clone())?;
+            .funding_settlements_lookback()
+            .map_or(1, |lookback| lookback.as_usize());
+
+        // Funding settlements up to the start time, extended as settlements are applied
+        let mut settlement_history = self
+            .db
+            .funding_settlements
+            .get_latest_settlements(
+                self.start_time - Duration::seconds(1),
+                settlement_history_len as u32,
+            )
+            .await?;
+
+        let mut operator_pending = self.operator_pending;
+
+        if let Some(key) = self.config.operator_state_key()
+            && let Some(state) = load_operator_state(&self.db, key, self.start_time).await?
+        {
+            operator_pending.restore_state(&state)?;
+        }
+
+        let operator_executor = middleware::wrap_executor(
+            trades_executor.clone(),
+            self.config.risk_limits(),
+            None,
+            &self.middleware,
+        );
+
+        let signal_recorder = self
+            .config
+            .signal_run()
+            .map(|run| SignalRecorder::new(self.db.clone(), run.to_string()));
+
+        let mut operator =
+            operator_pending.start(self.start_time, operator_executor, signal_recorder)?;
 
         let mut time_cursor = start_candle.time + Duration::seconds(59);
         let mut minute_cursor_idx = start_candle_idx;
 
         let mut consolidator = if !resolution_to_max_period.is_empty() {
             let initial_candles = &minute_buffer[..=start_candle_idx];
-            Some(MultiResolutionConsolidator::new(
-                resolution_to_max_period,
-                initial_candles,
-                time_cursor,
-            )?)
+            Some(
+                MultiResolutionConsolidator::load(
+                    &self.db,
+                    resolution_to_max_period,
+                    initial_candles,
+                    time_cursor,
+                )
+                .await?,
+            )
         } else {
             None
         };
@@ -253,82 +319,178 @@ impl<S: Signal> BacktestEngine<S> {
             .map_err(BacktestError::ExecutorStateEvaluation)?;
         let _ = self.update_tx.send(initial_state.into());
 
+        // Trades closed from now on are reported to the operator
+        let mut trade_events = TradeEventTracker::default();
+        poll_trade_events(
+            &trades_executor,
+            &mut trade_events,
+            start_candle.open,
+            start_candle.open,
+        )
+        .await?;
+
+        operator.on_start().await?;
+
         // Next update will be at end of day (23:59:59), reported as midnight of following day
         let mut send_next_update_at = self.start_time + Duration::days(1) - Duration::seconds(1);
+        let mut state_checkpoint = self.state_checkpoint.filter(|time| *time > self.start_time);
 
         self.status_manager.update(BacktestStatus::Running);
 
-        loop {
-            operator.iterate(time_cursor, consolidator.as_ref()).await?;
+        // Buffered signals are persisted even if the simulation fails
+        let simulation_result: Result<()> = async {
+            loop {
+                let context = OperatorContext::new(
+                    time_cursor,
+                    time_cursor,
+                    minute_buffer[minute_cursor_idx].close,
+                    &settlement_history,
+                );
+
+                operator
+                    .iterate(
+                        &context,
+                        consolidator.as_ref(),
+                        &minute_buffer[minute_cursor_idx],
+                    )
+                    .await?;
 
-            if time_cursor >= send_next_update_at {
-                // Report trading state as midnight UTC of each backtested day
-                let update_time = send_next_update_at + Duration::seconds(1);
-                trades_executor
-                    .update_time(update_time)
-                    .await
-                    .map_err(BacktestError::ExecutorTickUpdate)?;
-                let trades_state = trades_executor
-                    .trading_state()
-                    .await
-                    .map_err(BacktestError::ExecutorStateEvaluation)?;
+                let last_price = minute_buffer[minute_cursor_idx].close;
+                for event in
+                    poll_trade_events(&trades_executor, &mut trade_events, last_price, last_price)
+                        .await?
+                {
+                    operator.on_event(&event).await?;
+                }
 
-                // Ignore no-receivers errors
-                let _ = self.update_tx.send(trades_state.into());
+                if time_cursor >= send_next_update_at {
+                    // Report trading state as midnight UTC of each backtested day
+                    let update_time = send_next_update_at + Duration::seconds(1);
+                    trades_executor
+                        .update_time(update_time)
+                        .await
+                        .map_err(BacktestError::ExecutorTickUpdate)?;
+                    let trades_state = trades_executor
+                        .trading_state()
+                        .await
+                        .map_err(BacktestError::ExecutorStateEvaluation)?;
+
+                    // Ignore no-receivers errors
+                    let _ = self.update_tx.send(trades_state.into());
+
+                    if let Some(key) = self.config.operator_state_key() {
+                        save_operator_state(&self.db, key, update_time, &operator).await?;
+                    }
+
+                    send_next_update_at += Duration::days(1);
+                }
 
-                send_next_update_at += Duration::days(1);
-            }
+                if let Some(checkpoint) = state_checkpoint
+                    && time_cursor >= checkpoint
+                {
+                    let trades_state = trades_executor
+                        .trading_state()
+                        .await
+                        .map_err(BacktestError::ExecutorStateEvaluation)?;
 
-            if time_cursor >= self.end_time - Duration::seconds(1) {
-                break;
-            }
+                    // Ignore no-receivers errors
+                    let _ = self.update_tx.send(trades_state.into());
+
+                    state_checkpoint = None;
+                }
 
-            minute_cursor_idx += 1;
+                if time_cursor >= self.end_time - Duration::seconds(1) {
+                    break;
+                }
 
-            // Refetch buffer when exhausted
-            if minute_cursor_idx >= minute_buffer.len() {
-                let new_buffer_to =
-                    (time_cursor + Duration::minutes(buffer_size)).min(self.end_time);
+                minute_cursor_idx += 1;
 
-                minute_buffer = self
-                    .db
-                    .ohlc_candles
-                    .get_candles(time_cursor, new_buffer_to)
-                    .await?;
+                // Refetch buffer when exhausted
+                if minute_cursor_idx >= minute_buffer.len() {
+                    let new_buffer_to =
+                        (time_cursor + Duration::minutes(buffer_size)).min(self.end_time);
 
-                if minute_buffer.is_empty() {
-                    return Err(BacktestError::UnexpectedEmptyBuffer { time: time_cursor });
-                }
+                    minute_buffer = self
+                        .db
+                        .ohlc_candles
+                        .get_candles(time_cursor, new_buffer_to)
+                        .await?;
 
-                minute_cursor_idx = 0;
-            }
+                    if minute_buffer.is_empty() {
+                        return Err(BacktestError::UnexpectedEmptyBuffer { time: time_cursor });
+                    }
 
-            // Advance time cursor to the end of the next candle's minute (skips gaps in data)
-            time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+                    minute_cursor_idx = 0;
+                }
+
+                // Advance time cursor to the end of the next candle's minute (skips gaps in data)
+                time_cursor = minute_buffer[minute_cursor_idx].time + Duration::seconds(59);
+
+                // Apply funding settlements that fall within the new time cursor. Applied before
+                // `candle_update` so that updated margin/leverage/liquidation are visible to the
+                // price-trigger liquidation check.
+                while let Some(settlement) = &next_settlement
+                    && settlement.time <= time_cursor
+                {
+                    trades_executor
+                        .apply_funding_settlement(settlement)
+                        .await
+                        .map_err(BacktestError::FundingSettlementApplication)?;
+
+                    let event = OperatorEvent::FundingSettlement(settlement.clone());
+
+                    if settlement_history.len() == settlement_history_len {
+                        settlement_history.remove(0);
+                    }
+                    settlement_history.extend(next_settlement.take());
+                    next_settlement = settlements.pop_front();
+
+                    operator.on_event(&event).await?;
+                }
 
-            // Apply funding settlements that fall within the new time cursor. Applied before
-            // `candle_update` so that updated margin/leverage/liquidation are visible to the
-            // price-trigger liquidation check.
-            while let Some(settlement) = &next_settlement
-                && settlement.time <= time_cursor
-            {
+                let next_minute_candle = &minute_buffer[minute_cursor_idx];
                 trades_executor
-                    .apply_funding_settlement(settlement)
+                    .candle_update(next_minute_candle)
                     .await
-                    .map_err(BacktestError::FundingSettlementApplication)?;
+                    .map_err(BacktestError::ExecutorTickUpdate)?;
+
+                for event in poll_trade_events(
+                    &trades_executor,
+                    &mut trade_events,
+                    next_minute_candle.low,
+                    next_minute_candle.high,
+                )
+                .await?
+                {
+                    operator.on_event(&event).await?;
+                }
 
-                next_settlement = settlements.pop_front();
+                if let Some(consolidator) = &mut consolidator {
+                    consolidator.push(next_minute_candle)?;
+                }
             }
 
-            let next_minute_candle = &minute_buffer[minute_cursor_idx];
-            trades_executor
-                .candle_update(next_minute_candle)
+            operator.on_shutdown().await?;
+
+            // Report the final trading state, including trades closed on shutdown
+            let trades_state = trades_executor
+                .trading_state()
                 .await
-                .map_err(BacktestError::ExecutorTickUpdate)?;
+                .map_err(BacktestError::ExecutorStateEvaluation)?;
 
-            if let Some(consolidator) = &mut consolidator {
-                consolidator.push(next_minute_candle)?;
-            }
+            // Ignore no-receivers errors
+            let _ = self.update_tx.send(trades_state.into());
+
+            Ok(())
+        }
+        .await;
+
+        let flush_result = operator.flush_signals().await;
+        simulation_result?;
+        flush_result?;
+
+        if let Some(key) = self.config.operator_state_key() {
+            save_operator_state(&self.db, key, self.end_time, &operator).await?;
         }
 
         Ok(())
@@ -379,3 +541,18 @@ impl BacktestEngine<Raw> {
         .await
     }
 }
+
+/// Persists the operator state under `key`, to be restored by backtests starting at
+/// `checkpoint`, if the operator returns one.
+async fn save_operator_state<S: Signal>(
+    db: &Database,
+    key: &str,
+    checkpoint: DateTime<Utc>,
+    operator: &OperatorRunning<S>,
+) -> Result<()> {
+    if let Some(state) = operator.save_state()? {
+        store_operator_state(db, key, checkpoint, state).await?;
+    }
+
+    Ok(())
+}
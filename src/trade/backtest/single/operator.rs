@@ -3,12 +3,17 @@ use std::{collections::HashMap, sync::Arc};
 use chrono::{DateTime, Utc};
 
 use crate::{
+    db::models::OhlcCandleRow,
     shared::{Lookback, OhlcResolution, Period},
-    signal::{Signal, SignalEvaluator},
+    signal::{Signal, SignalEvaluator, SignalRecorder},
 };
 
 use super::super::{
-    super::core::{TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+    super::{
+        context::OperatorContext,
+        core::{TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+        events::OperatorEvent,
+    },
     consolidator::MultiResolutionConsolidator,
     error::Result,
     operator::{
@@ -48,14 +53,29 @@ impl<S: Signal> OperatorPending<S> {
         }
     }
 
+    pub(super) fn funding_settlements_lookback(&self) -> Option<Period> {
+        match self {
+            Self::Signal(pending) => pending.funding_settlements_lookback(),
+            Self::Raw(pending) => pending.funding_settlements_lookback(),
+        }
+    }
+
+    pub(super) fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        match self {
+            Self::Signal(pending) => pending.restore_state(state),
+            Self::Raw(pending) => pending.restore_state(state),
+        }
+    }
+
     pub(super) fn start(
         self,
         start_time: DateTime<Utc>,
         trade_executor: Arc<dyn TradeExecutor>,
+        signal_recorder: Option<SignalRecorder>,
     ) -> Result<OperatorRunning<S>> {
         match self {
             Self::Signal(pending) => pending
-                .start(start_time, trade_executor)
+                .start(start_time, trade_executor, signal_recorder)
                 .map(OperatorRunning::Signal),
             Self::Raw(pending) => pending
                 .start(start_time, trade_executor)
@@ -71,14 +91,50 @@ pub(super) enum OperatorRunning<S: Signal> {
 }
 
 impl<S: Signal> OperatorRunning<S> {
+    pub(super) fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Signal(running) => running.save_state(),
+            Self::Raw(running) => running.save_state(),
+        }
+    }
+
     pub(super) async fn iterate(
         &mut self,
-        time_cursor: DateTime<Utc>,
+        context: &OperatorContext<'_>,
         consolidator: Option<&MultiResolutionConsolidator>,
+        candle: &OhlcCandleRow,
     ) -> Result<()> {
         match self {
-            Self::Signal(running) => running.iterate(time_cursor, consolidator).await,
-            Self::Raw(running) => running.iterate(time_cursor, consolidator).await,
+            Self::Signal(running) => running.iterate(context, consolidator, candle).await,
+            Self::Raw(running) => running.iterate(context, consolidator, candle).await,
+        }
+    }
+
+    pub(super) async fn flush_signals(&mut self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.flush_signals().await,
+            Self::Raw(_) => Ok(()),
+        }
+    }
+
+    pub(super) async fn on_start(&self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_start().await,
+            Self::Raw(running) => running.on_start().await,
+        }
+    }
+
+    pub(super) async fn on_event(&self, event: &OperatorEvent) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_event(event).await,
+            Self::Raw(running) => running.on_event(event).await,
+        }
+    }
+
+    pub(super) async fn on_shutdown(&self) -> Result<()> {
+        match self {
+            Self::Signal(running) => running.on_shutdown().await,
+            Self::Raw(running) => running.on_shutdown().await,
         }
     }
 }
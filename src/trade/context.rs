@@ -0,0 +1,222 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{
+    db::models::FundingSettlementRow,
+    shared::{LookbackCandles, Period},
+    util::DateTimeExt,
+};
+
+/// Context provided to
+/// [`RawOperator::iterate_with_context`](crate::trade::RawOperator::iterate_with_context) on each
+/// iteration.
+///
+/// All values reflect the time of the iteration. In backtests, this is the simulated time, so
+/// operators should rely on [`time`](Self::time) instead of `Utc::now()`, and no data from after
+/// that time is made available.
+#[derive(Debug, Clone)]
+pub struct OperatorContext<'a> {
+    time: DateTime<Utc>,
+    last_tick_time: DateTime<Utc>,
+    last_price: f64,
+    settlement_history: &'a [FundingSettlementRow],
+    funding_settlements_lookback: usize,
+    next_funding_rate: Option<f64>,
+    candles: LookbackCandles<'a>,
+}
+
+impl<'a> OperatorContext<'a> {
+    /// Creates a new context. `settlement_history` must contain the most recent funding
+    /// settlements at or before `time`, in chronological order.
+    pub(crate) fn new(
+        time: DateTime<Utc>,
+        last_tick_time: DateTime<Utc>,
+        last_price: f64,
+        settlement_history: &'a [FundingSettlementRow],
+    ) -> Self {
+        Self {
+            time,
+            last_tick_time,
+            last_price,
+            settlement_history,
+            funding_settlements_lookback: 0,
+            next_funding_rate: None,
+            candles: LookbackCandles::default(),
+        }
+    }
+
+    pub(crate) fn with_candles<'b>(self, candles: LookbackCandles<'b>) -> OperatorContext<'b>
+    where
+        'a: 'b,
+    {
+        OperatorContext {
+            time: self.time,
+            last_tick_time: self.last_tick_time,
+            last_price: self.last_price,
+            settlement_history: self.settlement_history,
+            funding_settlements_lookback: self.funding_settlements_lookback,
+            next_funding_rate: self.next_funding_rate,
+            candles,
+        }
+    }
+
+    pub(crate) fn with_funding_settlements_lookback(mut self, lookback: Option<Period>) -> Self {
+        self.funding_settlements_lookback = lookback.map_or(0, |period| period.as_usize());
+        self
+    }
+
+    pub(crate) fn with_next_funding_rate(mut self, funding_rate: Option<f64>) -> Self {
+        self.next_funding_rate = funding_rate;
+        self
+    }
+
+    /// Returns the current time of the iteration. Simulated time when backtesting, wall-clock time
+    /// when trading live.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    /// Returns the time of the last price tick.
+    pub fn last_tick_time(&self) -> DateTime<Utc> {
+        self.last_tick_time
+    }
+
+    /// Returns the price of the last price tick.
+    pub fn last_price(&self) -> f64 {
+        self.last_price
+    }
+
+    /// Returns the most recent funding settlement at or before the current time, or `None` if no
+    /// settlement data is available.
+    pub fn latest_funding_settlement(&self) -> Option<&'a FundingSettlementRow> {
+        self.settlement_history.last()
+    }
+
+    /// Returns the funding rate applied at the most recent funding settlement, or `None` if no
+    /// settlement data is available.
+    pub fn latest_funding_rate(&self) -> Option<f64> {
+        self.latest_funding_settlement()
+            .map(|settlement| settlement.funding_rate)
+    }
+
+    /// Returns up to [`RawOperator::funding_settlements_lookback`] most recent funding settlements,
+    /// in chronological order (most recent last). Empty if no funding settlement history was
+    /// requested.
+    ///
+    /// [`RawOperator::funding_settlements_lookback`]:
+    ///     crate::trade::RawOperator::funding_settlements_lookback
+    pub fn funding_settlements(&self) -> &'a [FundingSettlementRow] {
+        let start_idx = self
+            .settlement_history
+            .len()
+            .saturating_sub(self.funding_settlements_lookback);
+
+        &self.settlement_history[start_idx..]
+    }
+
+    /// Returns the time of the next funding settlement, strictly after the current time.
+    pub fn next_funding_settlement_time(&self) -> DateTime<Utc> {
+        (self.time + Duration::nanoseconds(1)).ceil_funding_settlement_time()
+    }
+
+    /// Returns the funding rate published by the exchange ticker for the next funding settlement.
+    /// The rate is an estimate that may change until the settlement takes place.
+    ///
+    /// Only available when trading live, where the rate is refreshed every few minutes. `None` in
+    /// backtests, where only the rates of past settlements are known, and when the ticker couldn't
+    /// be fetched recently.
+    pub fn next_funding_rate(&self) -> Option<f64> {
+        self.next_funding_rate
+    }
+
+    /// Returns the candles requested via
+    /// [`RawOperator::lookbacks`](crate::trade::RawOperator::lookbacks).
+    pub fn candles(&self) -> &LookbackCandles<'a> {
+        &self.candles
+    }
+
+    /// Returns `true` if the most recent candle of the primary lookback is stable, meaning that its
+    /// period has fully elapsed and it will no longer change. Returns `false` if no candles were
+    /// requested.
+    pub fn is_newest_candle_stable(&self) -> bool {
+        self.candles
+            .primary()
+            .last()
+            .is_some_and(|candle| candle.stable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        shared::{Lookback, OhlcResolution},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_operator_context() {
+        let time = Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+        let context = OperatorContext::new(time, time, 100_000., &[]);
+
+        assert_eq!(
+            context.next_funding_settlement_time(),
+            Utc.with_ymd_and_hms(2026, 1, 1, 16, 0, 0).unwrap()
+        );
+        assert_eq!(context.latest_funding_rate(), None);
+        assert_eq!(context.next_funding_rate(), None);
+        assert!(!context.is_newest_candle_stable());
+
+        let context = context.with_next_funding_rate(Some(0.0002));
+        assert_eq!(context.next_funding_rate(), Some(0.0002));
+
+        let lookbacks = [Lookback::new(OhlcResolution::OneMinute, 2).unwrap()];
+        let mut candles = vec![
+            OhlcCandleRow::new_simple(time - Duration::minutes(1), 100_000., 1),
+            OhlcCandleRow::new_simple(time, 100_000., 1),
+        ];
+        let context = context.with_candles(LookbackCandles::from_buffers(&lookbacks, |_| &candles));
+        assert!(context.is_newest_candle_stable());
+
+        candles[1].stable = false;
+        let context = OperatorContext::new(time, time, 100_000., &[])
+            .with_candles(LookbackCandles::from_buffers(&lookbacks, |_| &candles));
+        assert!(!context.is_newest_candle_stable());
+    }
+
+    #[test]
+    fn test_operator_context_funding_settlements() {
+        let time = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        let settlement_history: Vec<FundingSettlementRow> = [-0.0001, 0.0002, 0.0003]
+            .into_iter()
+            .enumerate()
+            .map(|(i, funding_rate)| {
+                let settlement_time = time - Duration::hours(4 + 8 * (2 - i as i64));
+                FundingSettlementRow {
+                    id: uuid::Uuid::nil(),
+                    time: settlement_time,
+                    fixing_price: 100_000.,
+                    funding_rate,
+                    created_at: settlement_time,
+                }
+            })
+            .collect();
+
+        let context = OperatorContext::new(time, time, 100_000., &settlement_history);
+        assert_eq!(context.latest_funding_rate(), Some(0.0003));
+        assert!(context.funding_settlements().is_empty());
+
+        let context = context.with_funding_settlements_lookback(Some(Period::try_from(2).unwrap()));
+        let rates: Vec<f64> = context
+            .funding_settlements()
+            .iter()
+            .map(|settlement| settlement.funding_rate)
+            .collect();
+        assert_eq!(rates, vec![0.0002, 0.0003]);
+
+        let context = context.with_funding_settlements_lookback(Some(Period::try_from(5).unwrap()));
+        assert_eq!(context.funding_settlements().len(), 3);
+    }
+}
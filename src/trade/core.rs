@@ -21,15 +21,17 @@ use lnm_sdk::rest::v3::{
 };
 
 use crate::{
-    db::models::OhlcCandleRow,
+    db::models::{FundingSettlementRow, OhlcCandleRow},
     error::Result as GeneralResult,
-    shared::{Lookback, MinIterationInterval},
+    shared::{IterationSchedule, Lookback, MinIterationInterval, Period, merge_lookbacks},
     signal::Signal,
     util::DateTimeExt,
 };
 
-use super::error::{
-    IsolatedOrderValidationError, TradeCoreError, TradeCoreResult, TradeExecutorResult,
+use super::{
+    context::OperatorContext,
+    error::{IsolatedOrderValidationError, TradeCoreError, TradeCoreResult, TradeExecutorResult},
+    events::{OperatorEvent, TradeCloseReason},
 };
 
 impl crate::sealed::Sealed for Trade {}
@@ -371,6 +373,15 @@ pub trait TradeClosed: TradeCore {
     /// # }
     /// ```
     fn pl(&self) -> i64;
+
+    /// Returns the reason why the trade was closed, if known.
+    ///
+    /// Trades closed by the simulated executor of backtests and paper trading record the exact
+    /// reason. The exchange does not report it, so `None` is returned for trades closed on the
+    /// exchange, whose reason can be inferred with [`TradeCloseReason::from_closed_trade`].
+    fn close_reason(&self) -> Option<TradeCloseReason> {
+        None
+    }
 }
 
 impl TradeClosed for Trade {
@@ -379,6 +390,29 @@ impl TradeClosed for Trade {
     }
 }
 
+impl fmt::Display for dyn TradeClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let exit_price = self
+            .exit_price()
+            .map_or("N/A".to_string(), |price| price.to_string());
+        let closed_at = self
+            .closed_at()
+            .map_or("N/A".to_string(), |time| time.to_rfc3339());
+
+        write!(f, "Closed Trade:")?;
+        write!(f, "\n  id: {}", self.id())?;
+        write!(f, "\n  side: {}", self.side())?;
+        write!(f, "\n  quantity: {}", self.quantity())?;
+        write!(f, "\n  margin: {}", self.margin())?;
+        write!(f, "\n  price: {}", self.price())?;
+        write!(f, "\n  exit_price: {exit_price}")?;
+        write!(f, "\n  created_at: {}", self.created_at().to_rfc3339())?;
+        write!(f, "\n  closed_at: {closed_at}")?;
+        write!(f, "\n  pl: {}", self.pl())?;
+        write!(f, "\n  fees: {}", self.opening_fee() + self.closing_fee())
+    }
+}
+
 /// A reference to a trade, containing `(creation_timestamp, trade_uuid)`.
 pub type TradeReference = (DateTime<Utc>, Uuid);
 
@@ -937,6 +971,23 @@ impl TradingState {
         }
     }
 
+    /// Returns the state with its balance and positions replaced. The cross position is kept if
+    /// `cross_position` is `None`.
+    pub(super) fn with_positions(
+        self,
+        balance: u64,
+        running_map: DynRunningTradesMap,
+        cross_position: Option<Arc<dyn CrossPositionCore>>,
+    ) -> Self {
+        Self {
+            balance,
+            running_map,
+            running_stats: OnceLock::new(),
+            cross_position: cross_position.unwrap_or(self.cross_position),
+            ..self
+        }
+    }
+
     fn get_running_stats(&self) -> &RunningStats {
         self.running_stats.get_or_init(|| {
             let mut long_len = 0;
@@ -1989,7 +2040,90 @@ pub trait SignalOperator<S: Signal>: Send + Sync {
 
     /// Processes a trading signal and executes trading actions via the [`TradeExecutor`] that was
     /// set.
+    ///
+    /// Signals are produced on the evaluators' own schedule. Operators that must react to price
+    /// moves between signals can do so via [`price_thresholds`](Self::price_thresholds).
     async fn process_signal(&self, signal: &S) -> GeneralResult<()>;
+
+    /// Returns the market price levels that should trigger a call to
+    /// [`on_price_threshold`](Self::on_price_threshold) when reached, without waiting for the next
+    /// signal. Queried when the operator starts, and after each processed signal and each call to
+    /// `on_price_threshold`. Thresholds already reached at that time are ignored. Default
+    /// implementation returns no thresholds.
+    ///
+    /// Thresholds are evaluated like those of [`RawOperator::price_thresholds`]. If price ticks are
+    /// missed when trading live, `on_price_threshold` is called on the next tick as if a threshold
+    /// had been reached.
+    fn price_thresholds(&self) -> Vec<PriceThreshold> {
+        Vec::new()
+    }
+
+    /// Called when the market price reaches any of the
+    /// [`price_thresholds`](Self::price_thresholds), with the current market price. When
+    /// backtesting, this is the closing price of the minute in which the threshold was reached.
+    /// Default implementation does nothing.
+    async fn on_price_threshold(&self, market_price: f64) -> GeneralResult<()> {
+        let _ = market_price;
+        Ok(())
+    }
+
+    /// Returns a snapshot of the operator's internal state to be persisted, or `None` if the
+    /// operator is stateless. The encoding of the snapshot is up to the operator.
+    ///
+    /// When an operator state key is configured, this is called periodically and on shutdown
+    /// during live trading, and at the end of each simulated day and of the simulation during
+    /// backtests. Default implementation returns `None`.
+    fn save_state(&self) -> GeneralResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Restores the operator's internal state from a snapshot previously returned by
+    /// [`save_state`](Self::save_state). Called before the operator starts running, only if a
+    /// persisted snapshot is found. Default implementation ignores the snapshot.
+    fn restore_state(&mut self, state: &[u8]) -> GeneralResult<()> {
+        let _ = state;
+        Ok(())
+    }
+
+    /// Called once before the first signal is processed, after the trade executor was set. When
+    /// trading live, the trade executor may not be ready yet. Default implementation does nothing.
+    async fn on_start(&self) -> GeneralResult<()> {
+        Ok(())
+    }
+
+    /// Called when an isolated trade is closed, either by the operator itself or because the market
+    /// price reached its stoploss, takeprofit or liquidation price. Closes are detected as the
+    /// market price is updated, so the operator can react before its next signal.
+    /// Default implementation does nothing.
+    async fn on_trade_closed(
+        &self,
+        trade: &dyn TradeClosed,
+        reason: TradeCloseReason,
+    ) -> GeneralResult<()> {
+        let _ = (trade, reason);
+        Ok(())
+    }
+
+    /// Called when a funding settlement takes place. When trading live, this happens once the
+    /// settlement was synced to the database. Default implementation does nothing.
+    async fn on_funding_settlement(&self, settlement: &FundingSettlementRow) -> GeneralResult<()> {
+        let _ = settlement;
+        Ok(())
+    }
+
+    /// Called when the cross-margin position is liquidated. Liquidations of isolated trades are
+    /// reported via [`on_trade_closed`](Self::on_trade_closed), with
+    /// [`TradeCloseReason::Liquidation`]. Default implementation does nothing.
+    async fn on_cross_liquidation(&self, liquidation_price: Price) -> GeneralResult<()> {
+        let _ = liquidation_price;
+        Ok(())
+    }
+
+    /// Called once before the operator is stopped, when the backtest reaches its end time or the
+    /// live trading engine is shut down. Default implementation does nothing.
+    async fn on_shutdown(&self) -> GeneralResult<()> {
+        Ok(())
+    }
 }
 
 pub(crate) struct WrappedSignalOperator<S: Signal>(Box<dyn SignalOperator<S>>);
@@ -2012,6 +2146,74 @@ impl<S: Signal> WrappedSignalOperator<S> {
             .map_err(|e| TradeCoreError::SignalOperatorProcessSignalPanicked(e.into()))?
             .map_err(|e| TradeCoreError::SignalOperatorProcessSignalError(e.to_string()))
     }
+
+    /// Returns the price thresholds of the operator that weren't already reached at `last_price`.
+    pub fn price_thresholds(&self, last_price: f64) -> TradeCoreResult<Vec<PriceThreshold>> {
+        let mut thresholds = panic::catch_unwind(AssertUnwindSafe(|| self.0.price_thresholds()))
+            .map_err(|e| TradeCoreError::SignalOperatorPriceThresholdsPanicked(e.into()))?;
+        thresholds.retain(|threshold| !threshold.is_reached(last_price, last_price));
+        Ok(thresholds)
+    }
+
+    pub async fn on_price_threshold(&self, market_price: f64) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_price_threshold(market_price)))
+            .await
+            .map_err(|e| TradeCoreError::SignalOperatorOnPriceThresholdPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorOnPriceThresholdError(e.to_string()))
+    }
+
+    pub fn save_state(&self) -> TradeCoreResult<Option<Vec<u8>>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.save_state()))
+            .map_err(|e| TradeCoreError::SignalOperatorSaveStatePanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorSaveStateError(e.to_string()))
+    }
+
+    pub fn restore_state(&mut self, state: &[u8]) -> TradeCoreResult<()> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.restore_state(state)))
+            .map_err(|e| TradeCoreError::SignalOperatorRestoreStatePanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorRestoreStateError(e.to_string()))
+    }
+
+    pub async fn on_start(&self) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_start()))
+            .await
+            .map_err(|e| TradeCoreError::SignalOperatorOnStartPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorOnStartError(e.to_string()))
+    }
+
+    pub async fn on_event(&self, event: &OperatorEvent) -> TradeCoreResult<()> {
+        match event {
+            OperatorEvent::TradeClosed { trade, reason } => FutureExt::catch_unwind(
+                AssertUnwindSafe(self.0.on_trade_closed(trade.as_ref(), *reason)),
+            )
+            .await
+            .map_err(|e| TradeCoreError::SignalOperatorOnTradeClosedPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorOnTradeClosedError(e.to_string())),
+            OperatorEvent::CrossLiquidation { liquidation_price } => FutureExt::catch_unwind(
+                AssertUnwindSafe(self.0.on_cross_liquidation(*liquidation_price)),
+            )
+            .await
+            .map_err(|e| TradeCoreError::SignalOperatorOnCrossLiquidationPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorOnCrossLiquidationError(e.to_string())),
+            OperatorEvent::FundingSettlement(settlement) => {
+                FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_funding_settlement(settlement)))
+                    .await
+                    .map_err(|e| {
+                        TradeCoreError::SignalOperatorOnFundingSettlementPanicked(e.into())
+                    })?
+                    .map_err(|e| {
+                        TradeCoreError::SignalOperatorOnFundingSettlementError(e.to_string())
+                    })
+            }
+        }
+    }
+
+    pub async fn on_shutdown(&self) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_shutdown()))
+            .await
+            .map_err(|e| TradeCoreError::SignalOperatorOnShutdownPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::SignalOperatorOnShutdownError(e.to_string()))
+    }
 }
 
 impl<S: Signal> From<Box<dyn SignalOperator<S>>> for WrappedSignalOperator<S> {
@@ -2030,6 +2232,29 @@ impl fmt::Display for Raw {
     }
 }
 
+/// Market price level that triggers an immediate iteration of a [`RawOperator`] when reached,
+/// regardless of its minimum iteration interval, or a call to
+/// [`SignalOperator::on_price_threshold`]. See [`RawOperator::price_thresholds`] and
+/// [`SignalOperator::price_thresholds`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceThreshold {
+    /// Reached when the market price rises to or above the given price.
+    Above(f64),
+    /// Reached when the market price falls to or below the given price.
+    Below(f64),
+}
+
+impl PriceThreshold {
+    /// Returns `true` if the threshold was reached by any market price within the `[low, high]`
+    /// range.
+    pub fn is_reached(&self, low: f64, high: f64) -> bool {
+        match self {
+            Self::Above(price) => high >= *price,
+            Self::Below(price) => low <= *price,
+        }
+    }
+}
+
 /// Trait for implementing direct trading logic without intermediate signal generation. Raw operators
 /// receive candlestick data and make trading decisions directly, providing more flexible control
 /// over the trading strategy implementation.
@@ -2039,15 +2264,130 @@ pub trait RawOperator: Send + Sync {
     fn set_trade_executor(&mut self, trade_executor: Arc<dyn TradeExecutor>) -> GeneralResult<()>;
 
     /// Returns the lookback configuration for this operator, or `None` if no historical candle
-    /// data is required.
+    /// data is required. Not used if [`lookbacks`](Self::lookbacks) is overridden.
     fn lookback(&self) -> Option<Lookback>;
 
+    /// Returns the candle resolutions and counts needed by this operator. Default implementation
+    /// returns the [`lookback`](Self::lookback), if any.
+    ///
+    /// Multi-timeframe operators can override this to request several resolutions, the first one
+    /// being the primary lookback. Lookbacks sharing a resolution are merged, keeping the longest
+    /// period.
+    fn lookbacks(&self) -> Vec<Lookback> {
+        self.lookback().into_iter().collect()
+    }
+
     /// Returns the minimum interval between successive iterations of the operator.
     fn min_iteration_interval(&self) -> MinIterationInterval;
 
+    /// Returns when the operator should be iterated. Queried once when the operator starts.
+    /// Default implementation returns [`IterationSchedule::MinInterval`].
+    ///
+    /// With [`IterationSchedule::CandleClose`], the operator is iterated once per closed candle of
+    /// its primary lookback resolution, and [`min_iteration_interval`](Self::min_iteration_interval)
+    /// is ignored. [`price_thresholds`](Self::price_thresholds) still trigger iterations.
+    fn iteration_schedule(&self) -> IterationSchedule {
+        IterationSchedule::MinInterval
+    }
+
+    /// Returns the number of most recent funding settlements needed by this operator, made
+    /// available via [`OperatorContext::funding_settlements`], or `None` if no funding settlement
+    /// history is required. Default implementation returns `None`.
+    fn funding_settlements_lookback(&self) -> Option<Period> {
+        None
+    }
+
+    /// Returns the market price levels that should trigger an immediate iteration when reached,
+    /// without waiting for the minimum iteration interval to elapse. Queried when the operator
+    /// starts and after each iteration. Thresholds already reached at the time of the iteration are
+    /// ignored. Default implementation returns no thresholds.
+    ///
+    /// When trading live, thresholds are evaluated on each price tick. If price ticks are missed
+    /// because the operator falls behind the sync process, an iteration is triggered as if a
+    /// threshold had been reached. When backtesting, they are evaluated against the high and low of
+    /// each minute candle, and the iteration happens at the end of that minute.
+    fn price_thresholds(&self) -> Vec<PriceThreshold> {
+        Vec::new()
+    }
+
     /// Processes candlestick data and executes trading actions via the [`TradeExecutor`] that was
-    /// set. Called periodically according to the minimum iteration interval.
+    /// set. Only called by the default implementation of
+    /// [`iterate_with_context`](Self::iterate_with_context).
+    ///
+    /// The candlestick slice contains the candles of the primary lookback, ordered chronologically
+    /// with the most recent candle last.
     async fn iterate(&self, candles: &[OhlcCandleRow]) -> GeneralResult<()>;
+
+    /// Processes market data and executes trading actions via the [`TradeExecutor`] that was set.
+    /// Called periodically according to the minimum iteration interval. Default implementation
+    /// calls [`iterate`](Self::iterate) with the candles of the primary lookback.
+    ///
+    /// Operators needing the iteration time, funding settlement data or several candle
+    /// resolutions should override this. The [`OperatorContext`] provides the current (simulated,
+    /// when backtesting) time, the last price tick, funding settlement data, and one
+    /// chronologically ordered candle slice per resolution requested via
+    /// [`lookbacks`](Self::lookbacks), with the most recent candle last.
+    async fn iterate_with_context(&self, context: &OperatorContext<'_>) -> GeneralResult<()> {
+        self.iterate(context.candles().primary()).await
+    }
+
+    /// Returns a snapshot of the operator's internal state to be persisted, or `None` if the
+    /// operator is stateless. The encoding of the snapshot is up to the operator.
+    ///
+    /// When an operator state key is configured, this is called periodically and on shutdown
+    /// during live trading, and at the end of each simulated day and of the simulation during
+    /// backtests. Default implementation returns `None`.
+    fn save_state(&self) -> GeneralResult<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Restores the operator's internal state from a snapshot previously returned by
+    /// [`save_state`](Self::save_state). Called before the operator starts running, only if a
+    /// persisted snapshot is found. Default implementation ignores the snapshot.
+    fn restore_state(&mut self, state: &[u8]) -> GeneralResult<()> {
+        let _ = state;
+        Ok(())
+    }
+
+    /// Called once before the first iteration, after the trade executor was set. When
+    /// trading live, the trade executor may not be ready yet. Default implementation does nothing.
+    async fn on_start(&self) -> GeneralResult<()> {
+        Ok(())
+    }
+
+    /// Called when an isolated trade is closed, either by the operator itself or because the market
+    /// price reached its stoploss, takeprofit or liquidation price. Closes are detected as the
+    /// market price is updated, so the operator can react before its next iteration.
+    /// Default implementation does nothing.
+    async fn on_trade_closed(
+        &self,
+        trade: &dyn TradeClosed,
+        reason: TradeCloseReason,
+    ) -> GeneralResult<()> {
+        let _ = (trade, reason);
+        Ok(())
+    }
+
+    /// Called when a funding settlement takes place. When trading live, this happens once the
+    /// settlement was synced to the database. Default implementation does nothing.
+    async fn on_funding_settlement(&self, settlement: &FundingSettlementRow) -> GeneralResult<()> {
+        let _ = settlement;
+        Ok(())
+    }
+
+    /// Called when the cross-margin position is liquidated. Liquidations of isolated trades are
+    /// reported via [`on_trade_closed`](Self::on_trade_closed), with
+    /// [`TradeCloseReason::Liquidation`]. Default implementation does nothing.
+    async fn on_cross_liquidation(&self, liquidation_price: Price) -> GeneralResult<()> {
+        let _ = liquidation_price;
+        Ok(())
+    }
+
+    /// Called once before the operator is stopped, when the backtest reaches its end time or the
+    /// live trading engine is shut down. Default implementation does nothing.
+    async fn on_shutdown(&self) -> GeneralResult<()> {
+        Ok(())
+    }
 }
 
 pub(super) struct WrappedRawOperator(Box<dyn RawOperator>);
@@ -2064,10 +2404,10 @@ impl WrappedRawOperator {
         .map_err(|e| TradeCoreError::RawOperatorSetTradeExecutorError(e.to_string()))
     }
 
-    pub fn lookback(&self) -> TradeCoreResult<Option<Lookback>> {
-        let lookback = panic::catch_unwind(AssertUnwindSafe(|| self.0.lookback()))
+    pub fn lookbacks(&self) -> TradeCoreResult<Vec<Lookback>> {
+        let lookbacks = panic::catch_unwind(AssertUnwindSafe(|| self.0.lookbacks()))
             .map_err(|e| TradeCoreError::RawOperatorLookbackPanicked(e.into()))?;
-        Ok(lookback)
+        Ok(merge_lookbacks(lookbacks))
     }
 
     pub fn min_iteration_interval(&self) -> TradeCoreResult<MinIterationInterval> {
@@ -2076,12 +2416,83 @@ impl WrappedRawOperator {
         Ok(interval)
     }
 
-    pub async fn iterate(&self, candles: &[OhlcCandleRow]) -> TradeCoreResult<()> {
-        FutureExt::catch_unwind(AssertUnwindSafe(self.0.iterate(candles)))
+    pub fn iteration_schedule(&self) -> TradeCoreResult<IterationSchedule> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.iteration_schedule()))
+            .map_err(|e| TradeCoreError::RawOperatorIterationSchedulePanicked(e.into()))
+    }
+
+    pub fn funding_settlements_lookback(&self) -> TradeCoreResult<Option<Period>> {
+        let lookback =
+            panic::catch_unwind(AssertUnwindSafe(|| self.0.funding_settlements_lookback()))
+                .map_err(|e| {
+                    TradeCoreError::RawOperatorFundingSettlementsLookbackPanicked(e.into())
+                })?;
+        Ok(lookback)
+    }
+
+    /// Returns the price thresholds of the operator that weren't already reached at `last_price`.
+    pub fn price_thresholds(&self, last_price: f64) -> TradeCoreResult<Vec<PriceThreshold>> {
+        let mut thresholds = panic::catch_unwind(AssertUnwindSafe(|| self.0.price_thresholds()))
+            .map_err(|e| TradeCoreError::RawOperatorPriceThresholdsPanicked(e.into()))?;
+        thresholds.retain(|threshold| !threshold.is_reached(last_price, last_price));
+        Ok(thresholds)
+    }
+
+    pub async fn iterate(&self, context: &OperatorContext<'_>) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.iterate_with_context(context)))
             .await
             .map_err(|e| TradeCoreError::RawOperatorIteratePanicked(e.into()))?
             .map_err(|e| TradeCoreError::RawOperatorIterateError(e.to_string()))
     }
+
+    pub fn save_state(&self) -> TradeCoreResult<Option<Vec<u8>>> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.save_state()))
+            .map_err(|e| TradeCoreError::RawOperatorSaveStatePanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorSaveStateError(e.to_string()))
+    }
+
+    pub fn restore_state(&mut self, state: &[u8]) -> TradeCoreResult<()> {
+        panic::catch_unwind(AssertUnwindSafe(|| self.0.restore_state(state)))
+            .map_err(|e| TradeCoreError::RawOperatorRestoreStatePanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorRestoreStateError(e.to_string()))
+    }
+
+    pub async fn on_start(&self) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_start()))
+            .await
+            .map_err(|e| TradeCoreError::RawOperatorOnStartPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorOnStartError(e.to_string()))
+    }
+
+    pub async fn on_event(&self, event: &OperatorEvent) -> TradeCoreResult<()> {
+        match event {
+            OperatorEvent::TradeClosed { trade, reason } => FutureExt::catch_unwind(
+                AssertUnwindSafe(self.0.on_trade_closed(trade.as_ref(), *reason)),
+            )
+            .await
+            .map_err(|e| TradeCoreError::RawOperatorOnTradeClosedPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorOnTradeClosedError(e.to_string())),
+            OperatorEvent::CrossLiquidation { liquidation_price } => FutureExt::catch_unwind(
+                AssertUnwindSafe(self.0.on_cross_liquidation(*liquidation_price)),
+            )
+            .await
+            .map_err(|e| TradeCoreError::RawOperatorOnCrossLiquidationPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorOnCrossLiquidationError(e.to_string())),
+            OperatorEvent::FundingSettlement(settlement) => {
+                FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_funding_settlement(settlement)))
+                    .await
+                    .map_err(|e| TradeCoreError::RawOperatorOnFundingSettlementPanicked(e.into()))?
+                    .map_err(|e| TradeCoreError::RawOperatorOnFundingSettlementError(e.to_string()))
+            }
+        }
+    }
+
+    pub async fn on_shutdown(&self) -> TradeCoreResult<()> {
+        FutureExt::catch_unwind(AssertUnwindSafe(self.0.on_shutdown()))
+            .await
+            .map_err(|e| TradeCoreError::RawOperatorOnShutdownPanicked(e.into()))?
+            .map_err(|e| TradeCoreError::RawOperatorOnShutdownError(e.to_string()))
+    }
 }
 
 impl From<Box<dyn RawOperator>> for WrappedRawOperator {
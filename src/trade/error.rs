@@ -4,11 +4,11 @@ use thiserror::Error;
 use uuid::Uuid;
 
 use lnm_sdk::rest::v3::{
-    error::PriceValidationError,
+    error::{PriceValidationError, TradeValidationError},
     models::{Percentage, PercentageCapped, Price, TradeSide},
 };
 
-use crate::util::PanicPayload;
+use crate::{shared::Period, util::PanicPayload};
 
 use super::{
     backtest::executor::error::SimulatedTradeExecutorError,
@@ -26,6 +26,9 @@ pub enum TradeExecutorError {
 
     #[error("[Isolated Order] {0}")]
     IsolatedOrder(#[from] IsolatedOrderValidationError),
+
+    #[error("[Risk Limit] {0}")]
+    RiskLimit(#[from] RiskLimitError),
 }
 
 pub(super) type TradeExecutorResult<T> = result::Result<T, TradeExecutorError>;
@@ -43,6 +46,82 @@ pub enum IsolatedOrderValidationError {
     },
 }
 
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum RiskLimitError {
+    #[error("total leverage {leverage:.2} would exceed the maximum of {max:.2}")]
+    MaxTotalLeverage { leverage: f64, max: f64 },
+
+    #[error("position leverage {leverage:.2} would exceed the maximum total leverage of {max:.2}")]
+    MaxPositionLeverage { leverage: f64, max: f64 },
+
+    #[error("{side} notional of {notional} USD would exceed the maximum of {max} USD")]
+    MaxNotionalPerSide {
+        side: TradeSide,
+        notional: u64,
+        max: u64,
+    },
+
+    #[error("running trades would exceed the maximum of {max}")]
+    MaxRunningTrades { max: usize },
+
+    #[error("daily loss of {loss} sats reached the maximum of {max} sats")]
+    MaxDailyLoss { loss: u64, max: u64 },
+
+    #[error("drawdown of {drawdown:.2}% from peak reached the maximum of {max}%")]
+    MaxDrawdown {
+        drawdown: f64,
+        max: PercentageCapped,
+    },
+
+    #[error(
+        "estimated liquidation price {liquidation} is {distance:.2}% away from market price {market_price}, below the minimum of {min}%"
+    )]
+    MinLiquidationDistance {
+        liquidation: Price,
+        market_price: Price,
+        distance: f64,
+        min: PercentageCapped,
+    },
+}
+
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum PositionSizingError {
+    #[error("ATR period {period} requires at least {period} candles, got {available}")]
+    NotEnoughCandles { period: Period, available: usize },
+
+    #[error("ATR multiplier must be a positive finite number, got {0}")]
+    InvalidAtrMultiplier(f64),
+
+    #[error("cannot place a {side} stoploss at {distance:.2} USD from market price {market_price}")]
+    InvalidStoploss {
+        side: TradeSide,
+        market_price: Price,
+        distance: f64,
+    },
+
+    #[error(
+        "{side} stoploss {stoploss} is too far from market price {market_price} to be placed before liquidation"
+    )]
+    StoplossBeyondLiquidation {
+        side: TradeSide,
+        stoploss: Price,
+        market_price: Price,
+    },
+
+    #[error(
+        "risk budget of {risk_budget} sats is below the estimated loss of {min_loss:.0} sats for the minimum quantity"
+    )]
+    RiskBudgetTooSmall { risk_budget: u64, min_loss: f64 },
+
+    #[error("balance of {balance} sats is insufficient for the minimum quantity")]
+    InsufficientBalance { balance: u64 },
+
+    #[error("Invalid sized trade params: {0}")]
+    TradeValidation(#[from] TradeValidationError),
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum TradeCoreError {
@@ -84,24 +163,126 @@ pub enum TradeCoreError {
     #[error("`SignalOperator::process_signal` error: {0}")]
     SignalOperatorProcessSignalError(String),
 
+    #[error("`SignalOperator::price_thresholds` panicked: {0}")]
+    SignalOperatorPriceThresholdsPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_price_threshold` panicked: {0}")]
+    SignalOperatorOnPriceThresholdPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_price_threshold` error: {0}")]
+    SignalOperatorOnPriceThresholdError(String),
+
+    #[error("`SignalOperator::save_state` panicked: {0}")]
+    SignalOperatorSaveStatePanicked(PanicPayload),
+
+    #[error("`SignalOperator::save_state` error: {0}")]
+    SignalOperatorSaveStateError(String),
+
+    #[error("`SignalOperator::restore_state` panicked: {0}")]
+    SignalOperatorRestoreStatePanicked(PanicPayload),
+
+    #[error("`SignalOperator::restore_state` error: {0}")]
+    SignalOperatorRestoreStateError(String),
+
+    #[error("`SignalOperator::on_start` panicked: {0}")]
+    SignalOperatorOnStartPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_start` error: {0}")]
+    SignalOperatorOnStartError(String),
+
+    #[error("`SignalOperator::on_trade_closed` panicked: {0}")]
+    SignalOperatorOnTradeClosedPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_trade_closed` error: {0}")]
+    SignalOperatorOnTradeClosedError(String),
+
+    #[error("`SignalOperator::on_funding_settlement` panicked: {0}")]
+    SignalOperatorOnFundingSettlementPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_funding_settlement` error: {0}")]
+    SignalOperatorOnFundingSettlementError(String),
+
+    #[error("`SignalOperator::on_cross_liquidation` panicked: {0}")]
+    SignalOperatorOnCrossLiquidationPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_cross_liquidation` error: {0}")]
+    SignalOperatorOnCrossLiquidationError(String),
+
+    #[error("`SignalOperator::on_shutdown` panicked: {0}")]
+    SignalOperatorOnShutdownPanicked(PanicPayload),
+
+    #[error("`SignalOperator::on_shutdown` error: {0}")]
+    SignalOperatorOnShutdownError(String),
+
     #[error("`RawOperator::set_trade_executor` panicked: {0}")]
     RawOperatorSetTradeExecutorPanicked(PanicPayload),
 
     #[error("`RawOperator::set_trade_executor` error: {0}")]
     RawOperatorSetTradeExecutorError(String),
 
-    #[error("`RawOperator::lookback` panicked: {0}")]
+    #[error("`RawOperator::lookbacks` panicked: {0}")]
     RawOperatorLookbackPanicked(PanicPayload),
 
     #[error("`RawOperator::min_iteration_interval` panicked: {0}")]
     RawOperatorMinIterationIntervalPanicked(PanicPayload),
 
+    #[error("`RawOperator::iteration_schedule` panicked: {0}")]
+    RawOperatorIterationSchedulePanicked(PanicPayload),
+
+    #[error("`RawOperator::funding_settlements_lookback` panicked: {0}")]
+    RawOperatorFundingSettlementsLookbackPanicked(PanicPayload),
+
+    #[error("`RawOperator::price_thresholds` panicked: {0}")]
+    RawOperatorPriceThresholdsPanicked(PanicPayload),
+
     #[error("`RawOperator::iterate` panicked: {0}")]
     RawOperatorIteratePanicked(PanicPayload),
 
     #[error("`RawOperator::iterate` error: {0}")]
     RawOperatorIterateError(String),
 
+    #[error("`RawOperator::save_state` panicked: {0}")]
+    RawOperatorSaveStatePanicked(PanicPayload),
+
+    #[error("`RawOperator::save_state` error: {0}")]
+    RawOperatorSaveStateError(String),
+
+    #[error("`RawOperator::restore_state` panicked: {0}")]
+    RawOperatorRestoreStatePanicked(PanicPayload),
+
+    #[error("`RawOperator::restore_state` error: {0}")]
+    RawOperatorRestoreStateError(String),
+
+    #[error("`RawOperator::on_start` panicked: {0}")]
+    RawOperatorOnStartPanicked(PanicPayload),
+
+    #[error("`RawOperator::on_start` error: {0}")]
+    RawOperatorOnStartError(String),
+
+    #[error("`RawOperator::on_trade_closed` panicked: {0}")]
+    RawOperatorOnTradeClosedPanicked(PanicPayload),
+
+    #[error("`RawOperator::on_trade_closed` error: {0}")]
+    RawOperatorOnTradeClosedError(String),
+
+    #[error("`RawOperator::on_funding_settlement` panicked: {0}")]
+    RawOperatorOnFundingSettlementPanicked(PanicPayload),
+
+    #[error("`RawOperator::on_funding_settlement` error: {0}")]
+    RawOperatorOnFundingSettlementError(String),
+
+    #[error("`RawOperator::on_cross_liquidation` panicked: {0}")]
+    RawOperatorOnCrossLiquidationPanicked(PanicPayload),
+
+    #[error("`RawOperator::on_cross_liquidation` error: {0}")]
+    RawOperatorOnCrossLiquidationError(String),
+
+    #[error("`RawOperator::on_shutdown` panicked: {0}")]
+    RawOperatorOnShutdownPanicked(PanicPayload),
+
+    #[error("`RawOperator::on_shutdown` error: {0}")]
+    RawOperatorOnShutdownError(String),
+
     #[error("Tried to evaluate next update trigger of trade {trade_id} without stoploss")]
     NoNextTriggerTradeStoplossNotSet { trade_id: Uuid },
 }
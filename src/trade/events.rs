@@ -0,0 +1,318 @@
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{CrossExposure, Price, TradeSide};
+
+use crate::db::models::FundingSettlementRow;
+
+use super::core::{TradeClosed, TradingState};
+
+/// Reason why an isolated trade was closed, as reported to
+/// [`RawOperator::on_trade_closed`](crate::trade::RawOperator::on_trade_closed) and
+/// [`SignalOperator::on_trade_closed`](crate::trade::SignalOperator::on_trade_closed).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeCloseReason {
+    /// The market price reached the trade's stoploss.
+    Stoploss,
+    /// The market price reached the trade's takeprofit.
+    Takeprofit,
+    /// The market price reached the trade's liquidation price.
+    Liquidation,
+    /// The trade was closed at the market price, usually via
+    /// [`TradeExecutor::isolated_order_close`](crate::trade::TradeExecutor::isolated_order_close)
+    /// or a similar method.
+    Market,
+}
+
+/// Relative distance between a trade's exit price and one of its trigger prices within which the
+/// trigger is still considered to have closed the trade. Exchange fills can slip slightly away
+/// from the exact trigger price.
+const CLOSE_REASON_PRICE_TOLERANCE: f64 = 0.001;
+
+impl TradeCloseReason {
+    /// Infers the reason why a trade was closed from its exit price, for trades closed on the
+    /// exchange, which does not report it. Trades closed by a simulated executor record the exact
+    /// reason instead, see [`TradeClosed::close_reason`].
+    ///
+    /// A trade is considered to have been closed by its liquidation price, stoploss or takeprofit
+    /// (checked in that order) when its exit price reached or crossed that trigger, allowing for a
+    /// relative slippage of 0.1% before it. Exits that gap through a trigger are therefore still
+    /// attributed to it, while a manual close within the tolerance of a trigger may be reported as
+    /// that trigger.
+    pub fn from_closed_trade(trade: &dyn TradeClosed) -> Self {
+        let Some(exit_price) = trade.exit_price() else {
+            return Self::Market;
+        };
+
+        Self::from_exit_price(
+            trade.side(),
+            exit_price.as_f64(),
+            trade.liquidation().as_f64(),
+            trade.stoploss().map(|price| price.as_f64()),
+            trade.takeprofit().map(|price| price.as_f64()),
+        )
+    }
+
+    fn from_exit_price(
+        side: TradeSide,
+        exit_price: f64,
+        liquidation: f64,
+        stoploss: Option<f64>,
+        takeprofit: Option<f64>,
+    ) -> Self {
+        // Whether the exit price is at or beyond `trigger` in the adverse direction of the trade
+        let reached_adverse = |trigger: f64| match side {
+            TradeSide::Buy => exit_price <= trigger * (1. + CLOSE_REASON_PRICE_TOLERANCE),
+            TradeSide::Sell => exit_price >= trigger * (1. - CLOSE_REASON_PRICE_TOLERANCE),
+        };
+        // Whether the exit price is at or beyond `trigger` in the favorable direction of the trade
+        let reached_favorable = |trigger: f64| match side {
+            TradeSide::Buy => exit_price >= trigger * (1. - CLOSE_REASON_PRICE_TOLERANCE),
+            TradeSide::Sell => exit_price <= trigger * (1. + CLOSE_REASON_PRICE_TOLERANCE),
+        };
+
+        if reached_adverse(liquidation) {
+            Self::Liquidation
+        } else if stoploss.is_some_and(reached_adverse) {
+            Self::Stoploss
+        } else if takeprofit.is_some_and(reached_favorable) {
+            Self::Takeprofit
+        } else {
+            Self::Market
+        }
+    }
+}
+
+impl fmt::Display for TradeCloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stoploss => write!(f, "Stoploss"),
+            Self::Takeprofit => write!(f, "Takeprofit"),
+            Self::Liquidation => write!(f, "Liquidation"),
+            Self::Market => write!(f, "Market"),
+        }
+    }
+}
+
+/// Event that is forwarded to the operator hooks.
+pub(crate) enum OperatorEvent {
+    TradeClosed {
+        trade: Arc<dyn TradeClosed>,
+        reason: TradeCloseReason,
+    },
+    CrossLiquidation {
+        liquidation_price: Price,
+    },
+    FundingSettlement(FundingSettlementRow),
+}
+
+/// Detects closed trades and cross-margin liquidations by comparing successive trading states.
+///
+/// The first state provided is only used as reference, so trades closed before it are not
+/// reported.
+#[derive(Default)]
+pub(crate) struct TradeEventTracker {
+    seeded: bool,
+    last_trade_time: Option<DateTime<Utc>>,
+    closed_ids: HashSet<Uuid>,
+    cross_liquidation: Option<(TradeSide, Price)>,
+}
+
+impl TradeEventTracker {
+    /// Returns `true` if trades may have been opened or closed since the last update, given the
+    /// current last trade time of the executor.
+    pub fn is_outdated(&self, last_trade_time: Option<DateTime<Utc>>) -> bool {
+        !self.seeded || self.last_trade_time != last_trade_time
+    }
+
+    /// Updates the tracker with a new trading state, returning the events that took place since
+    /// the previous one. `low` and `high` must cover the market prices observed in between, and
+    /// are used to tell cross-margin liquidations apart from regular closes.
+    pub fn update(&mut self, state: &TradingState, low: f64, high: f64) -> Vec<OperatorEvent> {
+        let closed_history = state.closed_history();
+        let mut events = Vec::new();
+
+        if self.seeded {
+            if closed_history.len() != self.closed_ids.len() {
+                for trade in closed_history.iter() {
+                    if !self.closed_ids.contains(&trade.id()) {
+                        events.push(OperatorEvent::TradeClosed {
+                            trade: trade.clone(),
+                            reason: trade.close_reason().unwrap_or_else(|| {
+                                TradeCloseReason::from_closed_trade(trade.as_ref())
+                            }),
+                        });
+                    }
+                }
+            }
+
+            if let Some((side, liquidation_price)) = self.cross_liquidation
+                && matches!(state.cross_position().exposure(), CrossExposure::Neutral)
+            {
+                let liquidation_reached = match side {
+                    TradeSide::Buy => low <= liquidation_price.as_f64(),
+                    TradeSide::Sell => high >= liquidation_price.as_f64(),
+                };
+
+                if liquidation_reached {
+                    events.push(OperatorEvent::CrossLiquidation { liquidation_price });
+                }
+            }
+        }
+
+        if !self.seeded || closed_history.len() != self.closed_ids.len() {
+            self.closed_ids = closed_history.iter().map(|trade| trade.id()).collect();
+        }
+
+        self.cross_liquidation = match state.cross_position().exposure() {
+            CrossExposure::Neutral => None,
+            CrossExposure::Running(exposure) => Some((exposure.side(), exposure.liquidation())),
+        };
+        self.last_trade_time = state.last_trade_time();
+        self.seeded = true;
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use lnm_sdk::rest::v3::models::{Leverage, OrderQuantity};
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        trade::{
+            BacktestConfig, IsolatedOrderRequest, Stoploss, TradeExecutor,
+            backtest::executor::SimulatedTradeExecutor,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_trade_event_tracker() {
+        let start_candle = OhlcCandleRow::new_simple(
+            DateTime::from_timestamp(1_700_000_040, 0).unwrap(),
+            100_000.,
+            1_000,
+        );
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 10_000_000);
+
+        let request = |stoploss: f64, takeprofit: f64| {
+            IsolatedOrderRequest::market(
+                TradeSide::Buy,
+                OrderQuantity::try_from(1_000).unwrap().into(),
+                Leverage::try_from(2.).unwrap(),
+            )
+            .with_stoploss(Stoploss::fixed(Price::try_from(stoploss).unwrap()))
+            .unwrap()
+            .with_takeprofit(Price::try_from(takeprofit).unwrap())
+            .unwrap()
+        };
+        executor
+            .isolated_order(request(95_000., 110_000.))
+            .await
+            .unwrap();
+        executor
+            .isolated_order(request(90_000., 102_000.))
+            .await
+            .unwrap();
+        executor
+            .isolated_order(request(90_000., 110_000.))
+            .await
+            .unwrap();
+
+        let mut tracker = TradeEventTracker::default();
+        let state = executor.trading_state().await.unwrap();
+        assert!(tracker.is_outdated(state.last_trade_time()));
+        assert!(tracker.update(&state, 100_000., 100_000.).is_empty());
+        assert!(!tracker.is_outdated(state.last_trade_time()));
+
+        let mut candle =
+            OhlcCandleRow::new_simple(start_candle.time + Duration::minutes(1), 100_000., 1_000);
+        candle.low = 94_000.;
+        candle.high = 103_000.;
+        executor.candle_update(&candle).await.unwrap();
+
+        let state = executor.trading_state().await.unwrap();
+        assert!(tracker.is_outdated(state.last_trade_time()));
+
+        let mut reasons: Vec<TradeCloseReason> = tracker
+            .update(&state, candle.low, candle.high)
+            .into_iter()
+            .filter_map(|event| match event {
+                OperatorEvent::TradeClosed { reason, .. } => Some(reason),
+                _ => None,
+            })
+            .collect();
+        reasons.sort_by_key(|reason| reason.to_string());
+        assert_eq!(
+            reasons,
+            vec![TradeCloseReason::Stoploss, TradeCloseReason::Takeprofit]
+        );
+
+        // Closed manually within the inference tolerance of its stoploss. The recorded reason wins.
+        executor
+            .isolated_order(request(99_950., 110_000.))
+            .await
+            .unwrap();
+        executor.isolated_order_close_all().await.unwrap();
+
+        let state = executor.trading_state().await.unwrap();
+        let events = tracker.update(&state, 100_000., 100_000.);
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| matches!(
+            event,
+            OperatorEvent::TradeClosed {
+                reason: TradeCloseReason::Market,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn test_trade_close_reason_from_exit_price() {
+        use TradeCloseReason::*;
+
+        let long = |exit_price: f64| {
+            TradeCloseReason::from_exit_price(
+                TradeSide::Buy,
+                exit_price,
+                50_000.,
+                Some(95_000.),
+                Some(110_000.),
+            )
+        };
+        assert_eq!(long(95_000.), Stoploss);
+        assert_eq!(long(95_050.), Stoploss); // Filled slightly before the trigger
+        assert_eq!(long(94_200.), Stoploss); // Gapped through the trigger
+        assert_eq!(long(110_000.), Takeprofit);
+        assert_eq!(long(109_950.), Takeprofit);
+        assert_eq!(long(112_000.), Takeprofit);
+        assert_eq!(long(49_800.), Liquidation);
+        assert_eq!(long(100_000.), Market);
+        assert_eq!(long(96_000.), Market);
+
+        let short = |exit_price: f64| {
+            TradeCloseReason::from_exit_price(
+                TradeSide::Sell,
+                exit_price,
+                150_000.,
+                Some(105_000.),
+                None,
+            )
+        };
+        assert_eq!(short(105_000.), Stoploss);
+        assert_eq!(short(104_950.), Stoploss);
+        assert_eq!(short(106_000.), Stoploss);
+        assert_eq!(short(150_500.), Liquidation);
+        assert_eq!(short(90_000.), Market);
+        assert_eq!(short(100_000.), Market);
+    }
+}
@@ -9,18 +9,27 @@ use lnm_sdk::{
 };
 
 use crate::{
+    db::RESERVED_KEY_PREFIX,
     sync::{LNM_OHLC_CANDLE_START, LNM_SETTLEMENT_A_START},
     util::DateTimeExt,
 };
 
+use super::{
+    super::risk::RiskLimits,
+    dead_man_switch::DeadManSwitchAction,
+    error::{LiveError, Result},
+};
+
 /// Configuration for the [`LiveTradeEngine`](crate::trade::LiveTradeEngine) controlling
 /// synchronization, signal processing, trade execution, and session management.
 #[derive(Clone, Debug)]
 pub struct LiveTradeConfig {
+    rest_api_endpoint: String,
     rest_api_timeout: time::Duration,
     rest_api_rate_limit_auth_requests_per_second: NonZeroU32,
     rest_api_rate_limit_unauth_requests_per_second: NonZeroU32,
     stream_enabled: bool,
+    stream_api_endpoint: String,
     stream_api_disconnect_timeout: time::Duration,
     rest_api_error_cooldown: time::Duration,
     rest_api_error_max_trials: NonZeroU64,
@@ -33,15 +42,31 @@ pub struct LiveTradeConfig {
     price_history_flag_gap_range: Option<Duration>,
     funding_settlement_flag_missing_range: Option<Duration>,
     live_price_tick_max_interval: time::Duration,
+    price_tick_full_resolution_window: Option<Duration>,
+    price_tick_downsample_interval: time::Duration,
+    price_tick_retention_range: Option<Duration>,
+    price_tick_retention_interval: time::Duration,
     funding_sync_retry_interval: time::Duration,
     sync_update_timeout: time::Duration,
     trade_tsl_step_size: PercentageCapped,
     startup_clean_up_trades: bool,
     startup_recover_trades: bool,
+    startup_closed_history_window: Option<Duration>,
     trading_session_refresh_interval: time::Duration,
     shutdown_clean_up_trades: bool,
     trade_estimated_fee: PercentageCapped,
     trade_max_running_qtd: usize,
+    risk_limits: Option<RiskLimits>,
+    risk_state_key: String,
+    kill_switch_max_daily_loss: Option<u64>,
+    kill_switch_max_drawdown: Option<PercentageCapped>,
+    kill_switch_key: String,
+    dead_man_switch_grace_period: Option<Duration>,
+    dead_man_switch_action: DeadManSwitchAction,
+    operator_state_key: Option<String>,
+    operator_state_save_interval: time::Duration,
+    signal_run: Option<String>,
+    paper_ledger_key: Option<String>,
     restart_interval: time::Duration,
     shutdown_timeout: time::Duration,
 }
@@ -51,6 +76,7 @@ impl Default for LiveTradeConfig {
         let rest_config_default = RestClientConfig::default();
         let stream_config_default = StreamClientConfig::default();
         Self {
+            rest_api_endpoint: rest_config_default.endpoint().to_string(),
             rest_api_timeout: rest_config_default.timeout(),
             rest_api_rate_limit_auth_requests_per_second: rest_config_default
                 .rate_limit_auth_requests_per_second()
@@ -61,6 +87,7 @@ impl Default for LiveTradeConfig {
                 .try_into()
                 .expect("not zero"),
             stream_enabled: true,
+            stream_api_endpoint: stream_config_default.endpoint().to_string(),
             stream_api_disconnect_timeout: stream_config_default.disconnect_timeout(),
             rest_api_error_cooldown: time::Duration::from_secs(10),
             rest_api_error_max_trials: 3.try_into().expect("not zero"),
@@ -74,16 +101,32 @@ impl Default for LiveTradeConfig {
             price_history_flag_gap_range: Some(Duration::weeks(4)),
             funding_settlement_flag_missing_range: Some(Duration::weeks(4)),
             live_price_tick_max_interval: time::Duration::from_secs(3 * 60),
+            price_tick_full_resolution_window: None,
+            price_tick_downsample_interval: time::Duration::from_secs(1),
+            price_tick_retention_range: None,
+            price_tick_retention_interval: time::Duration::from_secs(10 * 60),
             funding_sync_retry_interval: time::Duration::from_secs(60),
             sync_update_timeout: time::Duration::from_secs(60),
             trade_tsl_step_size: PercentageCapped::MIN,
             startup_clean_up_trades: false,
             startup_recover_trades: true,
+            startup_closed_history_window: Some(Duration::weeks(1)),
             trading_session_refresh_interval: time::Duration::from_millis(1_000),
             shutdown_clean_up_trades: false,
             trade_estimated_fee: PercentageCapped::try_from(0.1)
                 .expect("must be valid `PercentageCapped`"),
             trade_max_running_qtd: 50,
+            risk_limits: None,
+            risk_state_key: "risk_state".to_string(),
+            kill_switch_max_daily_loss: None,
+            kill_switch_max_drawdown: None,
+            kill_switch_key: "kill_switch".to_string(),
+            dead_man_switch_grace_period: None,
+            dead_man_switch_action: DeadManSwitchAction::Flatten,
+            operator_state_key: None,
+            operator_state_save_interval: time::Duration::from_secs(60),
+            signal_run: None,
+            paper_ledger_key: Some("paper_ledger".to_string()),
             restart_interval: time::Duration::from_secs(10),
             shutdown_timeout: time::Duration::from_secs(6),
         }
@@ -91,6 +134,11 @@ impl Default for LiveTradeConfig {
 }
 
 impl LiveTradeConfig {
+    /// Returns the REST API endpoint.
+    pub fn rest_api_endpoint(&self) -> &str {
+        &self.rest_api_endpoint
+    }
+
     /// Returns the timeout duration for REST API requests.
     pub fn rest_api_timeout(&self) -> time::Duration {
         self.rest_api_timeout
@@ -111,6 +159,11 @@ impl LiveTradeConfig {
         self.stream_enabled
     }
 
+    /// Returns the Stream API endpoint.
+    pub fn stream_api_endpoint(&self) -> &str {
+        &self.stream_api_endpoint
+    }
+
     /// Returns the disconnect timeout for Stream API connections.
     pub fn stream_api_disconnect_timeout(&self) -> time::Duration {
         self.stream_api_disconnect_timeout
@@ -176,6 +229,34 @@ impl LiveTradeConfig {
         self.live_price_tick_max_interval
     }
 
+    /// Returns the time window (looking back from the current time) in which price ticks are kept
+    /// at full resolution. Older ticks are downsampled to one tick per
+    /// [`price_tick_downsample_interval`](Self::price_tick_downsample_interval).
+    ///
+    /// If `None`, price ticks are never downsampled.
+    pub fn price_tick_full_resolution_window(&self) -> Option<Duration> {
+        self.price_tick_full_resolution_window
+    }
+
+    /// Returns the interval to which price ticks older than the full resolution window are
+    /// downsampled. Only the last tick of each interval is kept.
+    pub fn price_tick_downsample_interval(&self) -> time::Duration {
+        self.price_tick_downsample_interval
+    }
+
+    /// Returns the time range (looking back from the current time) in which price ticks are
+    /// retained. Older ticks are removed.
+    ///
+    /// If `None`, price ticks are never removed by the retention policy.
+    pub fn price_tick_retention_range(&self) -> Option<Duration> {
+        self.price_tick_retention_range
+    }
+
+    /// Returns the interval for applying the price tick retention policy in live modes.
+    pub fn price_tick_retention_interval(&self) -> time::Duration {
+        self.price_tick_retention_interval
+    }
+
     /// Returns the retry interval for funding settlement sync when not yet caught up.
     pub fn funding_sync_retry_interval(&self) -> time::Duration {
         self.funding_sync_retry_interval
@@ -201,6 +282,15 @@ impl LiveTradeConfig {
         self.startup_recover_trades
     }
 
+    /// Returns the time window (looking back from the launch time) of closed trades restored from
+    /// the database when starting the live trading session. Restored trades are included in the
+    /// closed trade history and in the realized P/L, fees and closed trades count.
+    ///
+    /// If `None`, the closed trade history starts empty.
+    pub fn startup_closed_history_window(&self) -> Option<Duration> {
+        self.startup_closed_history_window
+    }
+
     /// Returns the interval for refreshing and validating the trading session state.
     pub fn trading_session_refresh_interval(&self) -> time::Duration {
         self.trading_session_refresh_interval
@@ -221,6 +311,65 @@ impl LiveTradeConfig {
         self.trade_max_running_qtd
     }
 
+    /// Returns the portfolio-level risk limits enforced on the operator's orders, if any.
+    pub fn risk_limits(&self) -> Option<&RiskLimits> {
+        self.risk_limits.as_ref()
+    }
+
+    /// Returns the prefix of the database keys under which the net value reference points of the
+    /// risk limits are persisted.
+    pub fn risk_state_key(&self) -> &str {
+        &self.risk_state_key
+    }
+
+    /// Returns the maximum loss (in satoshis) of total net value since the start of the UTC day
+    /// that triggers the kill switch, if any.
+    pub fn kill_switch_max_daily_loss(&self) -> Option<u64> {
+        self.kill_switch_max_daily_loss
+    }
+
+    /// Returns the maximum drawdown of total net value from its peak that triggers the kill
+    /// switch, if any.
+    pub fn kill_switch_max_drawdown(&self) -> Option<PercentageCapped> {
+        self.kill_switch_max_drawdown
+    }
+
+    /// Returns the prefix of the database keys under which the kill switch state is persisted.
+    pub fn kill_switch_key(&self) -> &str {
+        &self.kill_switch_key
+    }
+
+    /// Returns the period the live trading process can remain unhealthy before the dead man's
+    /// switch is triggered, if enabled.
+    pub fn dead_man_switch_grace_period(&self) -> Option<Duration> {
+        self.dead_man_switch_grace_period
+    }
+
+    /// Returns the action taken when the dead man's switch is triggered.
+    pub fn dead_man_switch_action(&self) -> DeadManSwitchAction {
+        self.dead_man_switch_action
+    }
+
+    /// Returns the database key under which the operator state is persisted, if any.
+    pub fn operator_state_key(&self) -> Option<&str> {
+        self.operator_state_key.as_deref()
+    }
+
+    /// Returns the interval for periodically persisting the operator state.
+    pub fn operator_state_save_interval(&self) -> time::Duration {
+        self.operator_state_save_interval
+    }
+
+    /// Returns the run name under which emitted signals are persisted, if any.
+    pub fn signal_run(&self) -> Option<&str> {
+        self.signal_run.as_deref()
+    }
+
+    /// Returns the database key under which the paper trading ledger is persisted, if any.
+    pub fn paper_ledger_key(&self) -> Option<&str> {
+        self.paper_ledger_key.as_deref()
+    }
+
     /// Returns the interval for restarting the live process after recoverable errors.
     pub fn restart_interval(&self) -> time::Duration {
         self.restart_interval
@@ -231,6 +380,36 @@ impl LiveTradeConfig {
         self.shutdown_timeout
     }
 
+    /// Ensures none of the configured database keys uses the prefix reserved for internal state.
+    pub(super) fn validate_keys(&self) -> Result<()> {
+        let keys = [
+            Some(self.risk_state_key.as_str()),
+            Some(self.kill_switch_key.as_str()),
+            self.operator_state_key.as_deref(),
+            self.paper_ledger_key.as_deref(),
+        ];
+
+        match keys
+            .into_iter()
+            .flatten()
+            .find(|key| key.starts_with(RESERVED_KEY_PREFIX))
+        {
+            Some(key) => Err(LiveError::InvalidConfigurationReservedKey {
+                key: key.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Sets the REST API endpoint. Allows pointing the engine to a different server, such as a
+    /// local mock of the LN Markets API.
+    ///
+    /// Default: [`RestClientConfig`](lnm_sdk::rest::v3::RestClientConfig) default
+    pub fn with_rest_api_endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.rest_api_endpoint = endpoint.to_string();
+        self
+    }
+
     /// Sets the timeout duration for REST API requests.
     ///
     /// Default: [`RestClientConfig`](lnm_sdk::rest::v3::RestClientConfig) default
@@ -265,6 +444,15 @@ impl LiveTradeConfig {
         self
     }
 
+    /// Sets the Stream API endpoint. Allows pointing the engine to a different server, such as a
+    /// local mock of the LN Markets API.
+    ///
+    /// Default: [`StreamClientConfig`](lnm_sdk::stream::v1::StreamClientConfig) default
+    pub fn with_stream_api_endpoint(mut self, endpoint: impl ToString) -> Self {
+        self.stream_api_endpoint = endpoint.to_string();
+        self
+    }
+
     /// Sets the disconnect timeout for Stream API connections.
     ///
     /// Default: [`StreamClientConfig`](lnm_sdk::stream::v1::StreamClientConfig) default
@@ -384,6 +572,45 @@ impl LiveTradeConfig {
         self
     }
 
+    /// Sets the time window (looking back from the current time) in which price ticks are kept at
+    /// full resolution. Older ticks are downsampled to one tick per downsample interval.
+    ///
+    /// If `None`, price ticks are never downsampled.
+    ///
+    /// Default: `None`
+    pub fn with_price_tick_full_resolution_window(mut self, hours: Option<u64>) -> Self {
+        self.price_tick_full_resolution_window = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
+    /// Sets the interval to which price ticks older than the full resolution window are
+    /// downsampled. Only the last tick of each interval is kept.
+    ///
+    /// Default: `1` second
+    pub fn with_price_tick_downsample_interval(mut self, secs: NonZeroU64) -> Self {
+        self.price_tick_downsample_interval = time::Duration::from_secs(secs.get());
+        self
+    }
+
+    /// Sets the time range (looking back from the current time) in which price ticks are
+    /// retained. Older ticks are removed.
+    ///
+    /// If `None`, price ticks are never removed by the retention policy.
+    ///
+    /// Default: `None`
+    pub fn with_price_tick_retention_range(mut self, hours: Option<u64>) -> Self {
+        self.price_tick_retention_range = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
+    /// Sets the interval for applying the price tick retention policy in live modes.
+    ///
+    /// Default: `600` seconds (10 minutes)
+    pub fn with_price_tick_retention_interval(mut self, secs: NonZeroU64) -> Self {
+        self.price_tick_retention_interval = time::Duration::from_secs(secs.get());
+        self
+    }
+
     /// Sets the retry interval for funding settlement sync when not yet caught up.
     ///
     /// Default: `60` seconds (1 minute)
@@ -427,6 +654,18 @@ impl LiveTradeConfig {
         self
     }
 
+    /// Sets the time window (looking back from the launch time) of closed trades restored from the
+    /// database when starting the live trading session. Restored trades are included in the closed
+    /// trade history and in the realized P/L, fees and closed trades count.
+    ///
+    /// If `None`, the closed trade history starts empty.
+    ///
+    /// Default: `168` hours (1 week)
+    pub fn with_startup_closed_history_window(mut self, hours: Option<u64>) -> Self {
+        self.startup_closed_history_window = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
     /// Sets the interval for refreshing and validating the trading session state.
     ///
     /// Default: `1000` milliseconds (1 second)
@@ -459,6 +698,141 @@ impl LiveTradeConfig {
         self
     }
 
+    /// Sets the portfolio-level risk limits enforced on the operator's orders. When set, the trade
+    /// executor is wrapped in a [`RiskManager`](crate::trade::RiskManager) before being passed to
+    /// the operator. The net value reference points of the loss limits are persisted in the
+    /// database (see [`with_risk_state_key`](Self::with_risk_state_key)), so they survive
+    /// restarts. If `None`, only the executor limits apply.
+    ///
+    /// Default: `None`
+    pub fn with_risk_limits(mut self, risk_limits: Option<RiskLimits>) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
+    /// Sets the prefix of the database keys under which the net value reference points of the
+    /// risk limits are persisted. Keys are scoped by executor kind and account (e.g.
+    /// `risk_state:live:<account ID>`), like the kill switch state. Paper engines don't have an
+    /// account, and should use different prefixes if several share a database. Keys starting with
+    /// `"quantoxide:"` are reserved for internal state.
+    ///
+    /// Default: `"risk_state"`
+    pub fn with_risk_state_key(mut self, key: impl ToString) -> Self {
+        self.risk_state_key = key.to_string();
+        self
+    }
+
+    /// Sets the maximum loss (in satoshis) of total net value since the start of the UTC day that
+    /// triggers the kill switch. When triggered, all isolated trades and the cross-margin position
+    /// are closed, the live trading process is stopped with
+    /// [`LiveTradeStatus::KillSwitchTriggered`](crate::trade::LiveTradeStatus::KillSwitchTriggered),
+    /// and the trigger is persisted so trading doesn't resume on restart until the kill switch is
+    /// re-armed. If closing the positions fails, it is retried on each restart until it succeeds.
+    /// If `None`, daily loss doesn't trigger the kill switch.
+    ///
+    /// Default: `None`
+    pub fn with_kill_switch_max_daily_loss(mut self, max: Option<u64>) -> Self {
+        self.kill_switch_max_daily_loss = max;
+        self
+    }
+
+    /// Sets the maximum drawdown of total net value from its peak that triggers the kill switch. The
+    /// start of day and peak net values are persisted, so they carry over restarts until the kill
+    /// switch is re-armed. See
+    /// [`with_kill_switch_max_daily_loss`](Self::with_kill_switch_max_daily_loss) for the effects
+    /// of a trigger. If `None`, drawdown doesn't trigger the kill switch.
+    ///
+    /// Default: `None`
+    pub fn with_kill_switch_max_drawdown(mut self, max: Option<PercentageCapped>) -> Self {
+        self.kill_switch_max_drawdown = max;
+        self
+    }
+
+    /// Sets the prefix of the database keys under which the kill switch state is persisted. Keys
+    /// are scoped by executor kind and account (e.g. `kill_switch:live:<account ID>`), so live,
+    /// paper and shadow engines sharing a database never share a kill switch. Paper engines don't
+    /// have an account, and should use different prefixes if several share a database. Keys
+    /// starting with `"quantoxide:"` are reserved for internal state.
+    ///
+    /// Default: `"kill_switch"`
+    pub fn with_kill_switch_key(mut self, key: impl ToString) -> Self {
+        self.kill_switch_key = key.to_string();
+        self
+    }
+
+    /// Sets the period (in seconds) the live trading process can remain unhealthy before the dead
+    /// man's switch is triggered. The process is unhealthy while it isn't running, for instance
+    /// while waiting for a stale price feed to resume (see
+    /// [`with_live_price_tick_max_interval`](Self::with_live_price_tick_max_interval)) or while
+    /// restarting after recoverable errors. When triggered, the configured
+    /// [`DeadManSwitchAction`] is applied (to the simulated ledger when paper trading, and only
+    /// recorded as intercepted actions when shadow trading) and a
+    /// [`LiveTradeUpdate::DeadManSwitchTriggered`](crate::trade::LiveTradeUpdate::DeadManSwitchTriggered)
+    /// is emitted. The switch triggers at most once per unhealthy period, and the action is
+    /// retried after the restart interval if it fails. An unhealthy period only ends once the
+    /// process has been running for the grace period, so brief runs between restarts don't reset
+    /// it. The switch is armed as soon as the process starts, so a launch of the trade executor or
+    /// a start of the operator that doesn't complete within the grace period also triggers it.
+    /// Until the trade executor is launched, the action is retried after the restart interval. If
+    /// `None`, the dead man's switch is disabled.
+    ///
+    /// Default: `None`
+    pub fn with_dead_man_switch_grace_period(mut self, secs: Option<NonZeroU64>) -> Self {
+        self.dead_man_switch_grace_period = secs.map(|secs| Duration::seconds(secs.get() as i64));
+        self
+    }
+
+    /// Sets the action taken when the dead man's switch is triggered.
+    ///
+    /// Default: [`DeadManSwitchAction::Flatten`]
+    pub fn with_dead_man_switch_action(mut self, action: DeadManSwitchAction) -> Self {
+        self.dead_man_switch_action = action;
+        self
+    }
+
+    /// Sets the database key under which the operator state is persisted. When set, the state
+    /// stored under the key (if any) is restored before the operator starts, and the operator
+    /// state is saved periodically and on shutdown. If `None`, operator state is not persisted.
+    /// Keys starting with `"quantoxide:"` are reserved for internal state.
+    ///
+    /// Default: `None`
+    pub fn with_operator_state_key(mut self, key: Option<String>) -> Self {
+        self.operator_state_key = key;
+        self
+    }
+
+    /// Sets the interval for periodically persisting the operator state. Only used if an operator
+    /// state key is set.
+    ///
+    /// Default: `60` seconds
+    pub fn with_operator_state_save_interval(mut self, secs: NonZeroU64) -> Self {
+        self.operator_state_save_interval = time::Duration::from_secs(secs.get());
+        self
+    }
+
+    /// Sets the run name under which the signals emitted by the evaluators are persisted. Only
+    /// used by signal-based operators. If `None`, signals are not persisted.
+    ///
+    /// Default: `None`
+    pub fn with_signal_run(mut self, run: Option<String>) -> Self {
+        self.signal_run = run;
+        self
+    }
+
+    /// Sets the database key under which the ledger of paper trading engines is persisted. When
+    /// set, the ledger is saved after every trading action and whenever it closes trades or settles
+    /// funding fees, and the ledger stored under the key (if any) is restored when the engine
+    /// starts, in which case the start balance is ignored. Paper engines sharing a database should
+    /// use different keys, and a new key starts a fresh ledger. If `None`, the ledger is kept in
+    /// memory only. Not used by live and shadow engines. Keys starting with `"quantoxide:"` are
+    /// reserved for internal state.
+    ///
+    /// Default: `Some("paper_ledger")`
+    pub fn with_paper_ledger_key(mut self, key: Option<String>) -> Self {
+        self.paper_ledger_key = key;
+        self
+    }
+
     /// Sets the interval for restarting the live process after recoverable errors.
     ///
     /// Default: `10` seconds
@@ -480,6 +854,7 @@ impl From<&LiveTradeConfig> for RestClientConfig {
     fn from(value: &LiveTradeConfig) -> Self {
         // FIXME, when a more straighforward constructor is added to `RestClientConfig`
         RestClientConfig::new(value.rest_api_timeout())
+            .with_endpoint(value.rest_api_endpoint())
             .with_rate_limiter_active(true)
             .with_rate_limit_auth_requests_per_second(
                 value.rest_api_rate_limit_auth_requests_per_second(),
@@ -492,7 +867,9 @@ impl From<&LiveTradeConfig> for RestClientConfig {
 
 impl From<&LiveTradeConfig> for StreamClientConfig {
     fn from(value: &LiveTradeConfig) -> Self {
-        StreamClientConfig::default().with_disconnect_timeout(value.stream_api_disconnect_timeout())
+        StreamClientConfig::default()
+            .with_endpoint(value.stream_api_endpoint())
+            .with_disconnect_timeout(value.stream_api_disconnect_timeout())
     }
 }
 
@@ -518,10 +895,55 @@ impl From<&LiveTradeConfig> for LiveTradeControllerConfig {
 #[derive(Debug)]
 pub(super) struct LiveProcessConfig {
     sync_update_timeout: time::Duration,
+    risk_limits: Option<RiskLimits>,
+    risk_state_key: String,
+    kill_switch_max_daily_loss: Option<u64>,
+    kill_switch_max_drawdown: Option<PercentageCapped>,
+    kill_switch_key: String,
+    dead_man_switch_grace_period: Option<Duration>,
+    dead_man_switch_action: DeadManSwitchAction,
+    operator_state_key: Option<String>,
+    operator_state_save_interval: time::Duration,
     restart_interval: time::Duration,
 }
 
 impl LiveProcessConfig {
+    pub fn risk_limits(&self) -> Option<&RiskLimits> {
+        self.risk_limits.as_ref()
+    }
+
+    pub fn risk_state_key(&self) -> &str {
+        &self.risk_state_key
+    }
+
+    pub fn kill_switch_max_daily_loss(&self) -> Option<u64> {
+        self.kill_switch_max_daily_loss
+    }
+
+    pub fn kill_switch_max_drawdown(&self) -> Option<PercentageCapped> {
+        self.kill_switch_max_drawdown
+    }
+
+    pub fn kill_switch_key(&self) -> &str {
+        &self.kill_switch_key
+    }
+
+    pub fn dead_man_switch_grace_period(&self) -> Option<Duration> {
+        self.dead_man_switch_grace_period
+    }
+
+    pub fn dead_man_switch_action(&self) -> DeadManSwitchAction {
+        self.dead_man_switch_action
+    }
+
+    pub fn operator_state_key(&self) -> Option<&str> {
+        self.operator_state_key.as_deref()
+    }
+
+    pub fn operator_state_save_interval(&self) -> time::Duration {
+        self.operator_state_save_interval
+    }
+
     pub fn sync_update_timeout(&self) -> time::Duration {
         self.sync_update_timeout
     }
@@ -535,6 +957,15 @@ impl From<&LiveTradeConfig> for LiveProcessConfig {
     fn from(value: &LiveTradeConfig) -> Self {
         Self {
             sync_update_timeout: value.sync_update_timeout(),
+            risk_limits: value.risk_limits.clone(),
+            risk_state_key: value.risk_state_key.clone(),
+            kill_switch_max_daily_loss: value.kill_switch_max_daily_loss,
+            kill_switch_max_drawdown: value.kill_switch_max_drawdown,
+            kill_switch_key: value.kill_switch_key.clone(),
+            dead_man_switch_grace_period: value.dead_man_switch_grace_period,
+            dead_man_switch_action: value.dead_man_switch_action,
+            operator_state_key: value.operator_state_key.clone(),
+            operator_state_save_interval: value.operator_state_save_interval,
             restart_interval: value.restart_interval(),
         }
     }
@@ -546,6 +977,7 @@ pub struct LiveTradeExecutorConfig {
     trade_tsl_step_size: PercentageCapped,
     startup_clean_up_trades: bool,
     startup_recover_trades: bool,
+    startup_closed_history_window: Option<Duration>,
     trading_session_refresh_interval: time::Duration,
     shutdown_clean_up_trades: bool,
     trade_estimated_fee: PercentageCapped,
@@ -568,6 +1000,15 @@ impl LiveTradeExecutorConfig {
         self.startup_recover_trades
     }
 
+    /// Returns the time window (looking back from the launch time) of closed trades restored from
+    /// the database when starting the live trading session. Restored trades are included in the
+    /// closed trade history and in the realized P/L, fees and closed trades count.
+    ///
+    /// If `None`, the closed trade history starts empty.
+    pub fn startup_closed_history_window(&self) -> Option<Duration> {
+        self.startup_closed_history_window
+    }
+
     /// Returns the interval for refreshing and validating the trading session state.
     pub fn trading_session_refresh_interval(&self) -> time::Duration {
         self.trading_session_refresh_interval
@@ -615,6 +1056,18 @@ impl LiveTradeExecutorConfig {
         self
     }
 
+    /// Sets the time window (looking back from the launch time) of closed trades restored from the
+    /// database when starting the live trading session. Restored trades are included in the closed
+    /// trade history and in the realized P/L, fees and closed trades count.
+    ///
+    /// If `None`, the closed trade history starts empty.
+    ///
+    /// Default: `168` hours (1 week)
+    pub fn with_startup_closed_history_window(mut self, hours: Option<u64>) -> Self {
+        self.startup_closed_history_window = hours.map(|h| Duration::hours(h as i64));
+        self
+    }
+
     /// Sets the interval for refreshing and validating the trading session state.
     ///
     /// Default: `1000` milliseconds (1 second)
@@ -654,6 +1107,7 @@ impl Default for LiveTradeExecutorConfig {
             trade_tsl_step_size: PercentageCapped::MIN,
             startup_clean_up_trades: false,
             startup_recover_trades: true,
+            startup_closed_history_window: Some(Duration::weeks(1)),
             trading_session_refresh_interval: time::Duration::from_millis(1_000),
             shutdown_clean_up_trades: false,
             trade_estimated_fee: PercentageCapped::try_from(0.1)
@@ -669,6 +1123,7 @@ impl From<&LiveTradeConfig> for LiveTradeExecutorConfig {
             trade_tsl_step_size: value.trailing_stoploss_step_size(),
             startup_clean_up_trades: value.startup_clean_up_trades(),
             startup_recover_trades: value.startup_recover_trades(),
+            startup_closed_history_window: value.startup_closed_history_window(),
             trading_session_refresh_interval: value.trading_session_refresh_interval(),
             shutdown_clean_up_trades: value.shutdown_clean_up_trades(),
             trade_estimated_fee: value.trade_estimated_fee(),
@@ -0,0 +1,359 @@
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+
+use lnm_sdk::rest::v3::{
+    error::PriceValidationError,
+    models::{PercentageCapped, Price, TradeSide},
+};
+
+use crate::util::DateTimeExt;
+
+use super::state::LiveTradeStatus;
+
+/// Action taken by the dead man's switch once the live trading process has been unhealthy for
+/// longer than the configured grace period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeadManSwitchAction {
+    /// Closes all isolated trades and the cross-margin position.
+    Flatten,
+    /// Moves the stoploss of every running isolated trade to the given distance from the market
+    /// price, unless its current stoploss (or liquidation price) is already closer. The
+    /// cross-margin position doesn't support stoplosses, so it is closed.
+    TightenStoplosses(PercentageCapped),
+}
+
+impl fmt::Display for DeadManSwitchAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Flatten => write!(f, "Flatten"),
+            Self::TightenStoplosses(distance) => write!(f, "Tighten stoplosses to {distance}%"),
+        }
+    }
+}
+
+/// Record of a dead man's switch trigger, emitted as
+/// [`LiveTradeUpdate::DeadManSwitchTriggered`](crate::trade::LiveTradeUpdate::DeadManSwitchTriggered)
+/// after the action was applied.
+///
+/// With a live executor, the requests sent to the exchange are also persisted in the executor
+/// action audit log. Paper executors apply the action to their simulated ledger, and shadow
+/// executors record the requests that would have been sent as intercepted actions.
+#[derive(Debug, Clone)]
+pub struct DeadManSwitchTrigger {
+    unhealthy_since: DateTime<Utc>,
+    triggered_at: DateTime<Utc>,
+    status: LiveTradeStatus,
+    action: DeadManSwitchAction,
+}
+
+impl DeadManSwitchTrigger {
+    /// Returns the time since which the live trading process has been unhealthy.
+    pub fn unhealthy_since(&self) -> DateTime<Utc> {
+        self.unhealthy_since
+    }
+
+    /// Returns the time at which the action was applied.
+    pub fn triggered_at(&self) -> DateTime<Utc> {
+        self.triggered_at
+    }
+
+    /// Returns the live trading status at the time of the trigger.
+    pub fn status(&self) -> &LiveTradeStatus {
+        &self.status
+    }
+
+    /// Returns the applied action.
+    pub fn action(&self) -> DeadManSwitchAction {
+        self.action
+    }
+}
+
+impl fmt::Display for DeadManSwitchTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (unhealthy since {}, status: {})",
+            self.action,
+            self.unhealthy_since.format_local_millis(),
+            self.status
+        )
+    }
+}
+
+/// Returns `true` if the status indicates that the operator isn't running while the process is
+/// expected to. Stopped and stopping processes are not monitored.
+fn is_unhealthy(status: &LiveTradeStatus) -> bool {
+    matches!(
+        status,
+        LiveTradeStatus::Starting
+            | LiveTradeStatus::WaitingForSync(_)
+            | LiveTradeStatus::WaitingForSignal(_)
+            | LiveTradeStatus::WaitingTradeExecutor(_)
+            | LiveTradeStatus::Failed(_)
+            | LiveTradeStatus::Restarting
+    )
+}
+
+/// Tracks the live trading status, determining when the dead man's switch is due.
+///
+/// The switch triggers at most once per unhealthy period. An unhealthy period only ends once the
+/// process has been running for the grace period without interruption, so a process flapping
+/// between restarts and brief runs still triggers the switch.
+pub(super) struct DeadManSwitchMonitor {
+    grace_period: Duration,
+    action: DeadManSwitchAction,
+    unhealthy_since: Option<DateTime<Utc>>,
+    running_since: Option<DateTime<Utc>>,
+    next_trigger: Option<DateTime<Utc>>,
+}
+
+impl DeadManSwitchMonitor {
+    pub fn new(grace_period: Duration, action: DeadManSwitchAction) -> Self {
+        Self {
+            grace_period,
+            action,
+            unhealthy_since: None,
+            running_since: None,
+            next_trigger: None,
+        }
+    }
+
+    pub fn action(&self) -> DeadManSwitchAction {
+        self.action
+    }
+
+    /// Returns the time at which the switch is due, if the process is unhealthy and the switch
+    /// wasn't triggered yet. The switch is not due while the process is running.
+    pub fn next_trigger(&self) -> Option<DateTime<Utc>> {
+        if self.running_since.is_some() {
+            return None;
+        }
+
+        self.next_trigger
+    }
+
+    pub fn update(&mut self, status: &LiveTradeStatus, now: DateTime<Utc>) {
+        if matches!(status, LiveTradeStatus::Running) {
+            if self.unhealthy_since.is_some() && self.running_since.is_none() {
+                self.running_since = Some(now);
+            }
+            return;
+        }
+
+        if !is_unhealthy(status) {
+            self.unhealthy_since = None;
+            self.running_since = None;
+            self.next_trigger = None;
+            return;
+        }
+
+        // Runs shorter than the grace period don't end the unhealthy period
+        if let Some(running_since) = self.running_since.take()
+            && now - running_since >= self.grace_period
+        {
+            self.unhealthy_since = None;
+        }
+
+        if self.unhealthy_since.is_none() {
+            self.unhealthy_since = Some(now);
+            self.next_trigger = Some(now + self.grace_period);
+        }
+    }
+
+    /// Updates the monitor with the current status, returning a trigger record if the switch is
+    /// due.
+    pub fn check(
+        &mut self,
+        status: &LiveTradeStatus,
+        now: DateTime<Utc>,
+    ) -> Option<DeadManSwitchTrigger> {
+        self.update(status, now);
+
+        let (Some(unhealthy_since), Some(next_trigger)) =
+            (self.unhealthy_since, self.next_trigger())
+        else {
+            return None;
+        };
+
+        if now < next_trigger {
+            return None;
+        }
+
+        self.next_trigger = None;
+
+        Some(DeadManSwitchTrigger {
+            unhealthy_since,
+            triggered_at: now,
+            status: status.clone(),
+            action: self.action,
+        })
+    }
+
+    /// Schedules a new attempt, if the process is still unhealthy. Used when applying the action
+    /// fails.
+    pub fn retry_at(&mut self, time: DateTime<Utc>) {
+        if self.unhealthy_since.is_some() {
+            self.next_trigger = Some(time);
+        }
+    }
+}
+
+/// Returns the stoploss price at `distance` from `market_price` for a trade on the given side, or
+/// `None` if the current stoploss or the liquidation price is already at least as close.
+pub(super) fn tightened_stoploss(
+    side: TradeSide,
+    stoploss: Option<Price>,
+    liquidation: Price,
+    market_price: Price,
+    distance: PercentageCapped,
+) -> Result<Option<Price>, PriceValidationError> {
+    let new_stoploss = match side {
+        TradeSide::Buy => {
+            let new_stoploss = market_price.apply_discount(distance)?;
+            let floor = stoploss.map_or(liquidation, |stoploss| stoploss.max(liquidation));
+
+            (new_stoploss > floor).then_some(new_stoploss)
+        }
+        TradeSide::Sell => {
+            let new_stoploss = market_price.apply_gain(distance.into())?;
+            let ceiling = stoploss.map_or(liquidation, |stoploss| stoploss.min(liquidation));
+
+            (new_stoploss < ceiling).then_some(new_stoploss)
+        }
+    };
+
+    Ok(new_stoploss)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{super::process::error::LiveProcessRecoverableError, *};
+
+    fn price(value: f64) -> Price {
+        Price::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn test_dead_man_switch_monitor_triggers_once_per_unhealthy_period() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut monitor =
+            DeadManSwitchMonitor::new(Duration::seconds(60), DeadManSwitchAction::Flatten);
+
+        assert!(monitor.check(&LiveTradeStatus::Running, start).is_none());
+        assert!(monitor.next_trigger().is_none());
+
+        let failed =
+            LiveTradeStatus::Failed(Arc::new(LiveProcessRecoverableError::ExecutorRecvLagged {
+                skipped: 1,
+            }));
+        assert!(monitor.check(&failed, start).is_none());
+        assert_eq!(monitor.next_trigger(), Some(start + Duration::seconds(60)));
+
+        // Remaining unhealthy across restarts doesn't reset the grace period
+        let restarting_time = start + Duration::seconds(30);
+        assert!(
+            monitor
+                .check(&LiveTradeStatus::Restarting, restarting_time)
+                .is_none()
+        );
+
+        let due_time = start + Duration::seconds(60);
+        let trigger = monitor
+            .check(&LiveTradeStatus::Restarting, due_time)
+            .unwrap();
+        assert_eq!(trigger.unhealthy_since(), start);
+        assert_eq!(trigger.triggered_at(), due_time);
+        assert_eq!(trigger.action(), DeadManSwitchAction::Flatten);
+
+        let later = start + Duration::seconds(600);
+        assert!(monitor.check(&LiveTradeStatus::Restarting, later).is_none());
+
+        // Re-armed once running for the grace period
+        assert!(monitor.check(&LiveTradeStatus::Running, later).is_none());
+        assert!(monitor.next_trigger().is_none());
+
+        let later = later + Duration::seconds(60);
+        assert!(monitor.check(&LiveTradeStatus::Starting, later).is_none());
+        assert_eq!(monitor.next_trigger(), Some(later + Duration::seconds(60)));
+
+        // Stopping processes are not monitored
+        assert!(
+            monitor
+                .check(&LiveTradeStatus::ShutdownInitiated, later)
+                .is_none()
+        );
+        assert!(monitor.next_trigger().is_none());
+    }
+
+    #[test]
+    fn test_dead_man_switch_monitor_triggers_when_flapping() {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let mut monitor =
+            DeadManSwitchMonitor::new(Duration::seconds(60), DeadManSwitchAction::Flatten);
+
+        // Brief runs between restarts don't end the unhealthy period
+        for secs in (0..60).step_by(10) {
+            let time = start + Duration::seconds(secs);
+            assert!(monitor.check(&LiveTradeStatus::Restarting, time).is_none());
+            assert!(monitor.next_trigger().is_some());
+
+            let time = time + Duration::seconds(5);
+            assert!(monitor.check(&LiveTradeStatus::Running, time).is_none());
+            assert!(monitor.next_trigger().is_none());
+        }
+
+        let due_time = start + Duration::seconds(60);
+        let trigger = monitor
+            .check(&LiveTradeStatus::Restarting, due_time)
+            .unwrap();
+        assert_eq!(trigger.unhealthy_since(), start);
+    }
+
+    #[test]
+    fn test_tightened_stoploss() {
+        let market_price = price(100_000.);
+        let distance = PercentageCapped::try_from(2.).unwrap();
+
+        // Long without stoploss, liquidation far below
+        let new_stoploss =
+            tightened_stoploss(TradeSide::Buy, None, price(90_000.), market_price, distance)
+                .unwrap();
+        assert_eq!(new_stoploss, Some(price(98_000.)));
+
+        // Long with a stoploss already closer to the market price
+        let new_stoploss = tightened_stoploss(
+            TradeSide::Buy,
+            Some(price(99_000.)),
+            price(90_000.),
+            market_price,
+            distance,
+        )
+        .unwrap();
+        assert_eq!(new_stoploss, None);
+
+        // Short with a looser stoploss
+        let new_stoploss = tightened_stoploss(
+            TradeSide::Sell,
+            Some(price(105_000.)),
+            price(110_000.),
+            market_price,
+            distance,
+        )
+        .unwrap();
+        assert_eq!(new_stoploss, Some(price(102_000.)));
+
+        // Short with liquidation price already closer to the market price
+        let new_stoploss = tightened_stoploss(
+            TradeSide::Sell,
+            None,
+            price(101_000.),
+            market_price,
+            distance,
+        )
+        .unwrap();
+        assert_eq!(new_stoploss, None);
+    }
+}
@@ -10,8 +10,9 @@ use lnm_sdk::{rest::v3::RestClient, stream::v1::StreamClient};
 
 use crate::{
     db::Database,
+    shared::{Lookback, max_lookback},
     signal::{LiveSignalEngine, Signal, SignalEvaluator},
-    sync::SyncEngine,
+    sync::{SyncEngine, SyncReader},
     tui::{
         TuiControllerShutdown,
         error::{Result as TuiResult, TuiError},
@@ -20,10 +21,14 @@ use crate::{
 };
 
 use super::{
-    super::core::{Raw, RawOperator, SignalOperator, WrappedRawOperator},
+    super::{
+        core::{Raw, RawOperator, SignalOperator, WrappedRawOperator},
+        middleware::TradeExecutorMiddleware,
+    },
     config::{LiveTradeConfig, LiveTradeControllerConfig},
     error::{LiveError, Result},
-    executor::LiveTradeExecutorLauncher,
+    executor::{ExecutorPending, LiveTradeExecutorLauncher, paper::PaperTradeExecutorLauncher},
+    kill_switch::{KillSwitchKeys, KillSwitchTrip},
     process::{
         LiveProcess,
         error::{LiveProcessFatalError, LiveProcessFatalResult},
@@ -171,14 +176,51 @@ impl<S: Signal> TuiControllerShutdown for LiveTradeController<S> {
     }
 }
 
+/// Whether trading actions are sent to the exchange, recorded in a paper trading ledger, or
+/// intercepted in shadow mode.
+enum ExecutorKind {
+    Live,
+    Paper { start_balance: u64 },
+    Shadow,
+}
+
+impl ExecutorKind {
+    fn launcher(
+        self,
+        config: &LiveTradeConfig,
+        db: Arc<Database>,
+        api_rest: Arc<RestClient>,
+        sync_reader: Arc<dyn SyncReader>,
+    ) -> Result<ExecutorPending> {
+        let launcher = match self {
+            Self::Live => LiveTradeExecutorLauncher::new(config, db, api_rest, sync_reader)
+                .map_err(LiveError::SetupExecutor)?
+                .into(),
+            Self::Shadow => ExecutorPending::Shadow(
+                LiveTradeExecutorLauncher::shadow(config, db, api_rest, sync_reader)
+                    .map_err(LiveError::SetupExecutor)?,
+            ),
+            Self::Paper { start_balance } => {
+                PaperTradeExecutorLauncher::new(config, db, start_balance, sync_reader)
+                    .map_err(LiveError::SetupExecutor)?
+                    .into()
+            }
+        };
+
+        Ok(launcher)
+    }
+}
+
 /// Builder for configuring and starting a live trading engine. Encapsulates the configuration,
 /// database connection, API clients, sync engine, trade executor, and operator. The live trading
 /// process is started when [`start`](Self::start) is called, returning a [`LiveTradeController`].
 pub struct LiveTradeEngine<S: Signal> {
     config: LiveTradeConfig,
+    db: Arc<Database>,
     sync_engine: SyncEngine,
-    trade_executor_launcher: LiveTradeExecutorLauncher,
+    trade_executor_launcher: ExecutorPending,
     operator_pending: OperatorPending<S>,
+    middleware: Vec<Arc<dyn TradeExecutorMiddleware>>,
     status_manager: Arc<LiveTradeStatusManager<S>>,
 }
 
@@ -198,10 +240,67 @@ impl<S: Signal> LiveTradeEngine<S> {
         evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
         operator: Box<dyn SignalOperator<S>>,
     ) -> Result<Self> {
-        if evaluators.is_empty() {
-            return Err(LiveError::EmptyEvaluatorsVec);
-        }
+        let api_rest = RestClient::with_credentials(
+            &config,
+            api_key.to_string(),
+            api_secret.to_string(),
+            api_passphrase.to_string(),
+        )
+        .map_err(LiveError::RestApiInit)?;
+
+        Self::new_signal(
+            config,
+            db,
+            api_rest,
+            ExecutorKind::Live,
+            evaluators,
+            operator,
+        )
+    }
+
+    /// Creates a new paper trading engine using signal-based evaluation. Signals are evaluated on
+    /// the live feed, but trading actions are executed against a simulated ledger starting with
+    /// `start_balance` (in satoshis) and driven by real-time price ticks. No API credentials are
+    /// required and no orders are sent to the exchange. The ledger is persisted and restored across
+    /// restarts, see [`LiveTradeConfig::with_paper_ledger_key`].
+    pub fn paper_with_signal_operator(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        start_balance: u64,
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        operator: Box<dyn SignalOperator<S>>,
+    ) -> Result<Self> {
+        let api_rest = RestClient::new(&config).map_err(LiveError::RestApiInit)?;
+
+        Self::new_signal(
+            config,
+            db,
+            api_rest,
+            ExecutorKind::Paper { start_balance },
+            evaluators,
+            operator,
+        )
+    }
 
+    /// Creates a new shadow trading engine using signal-based evaluation. The operator runs against
+    /// the real account state, which can be read with a read-only API key, but trading actions are
+    /// never sent to the exchange. Each action is emitted as a
+    /// [`LiveTradeExecutorAction`](crate::trade::LiveTradeExecutorAction) and answered with a
+    /// hypothetical result, so decisions can be compared with those of a production engine. The
+    /// trading state returned to the operator overlays the hypothetical positions (opened and
+    /// closed trades, cross-margin changes) on the real account state, while the
+    /// [`LiveTradeUpdate::TradingState`](crate::trade::LiveTradeUpdate::TradingState) updates
+    /// report the real account state.
+    #[allow(clippy::too_many_arguments)]
+    pub fn shadow_with_signal_operator(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        api_key: impl ToString,
+        api_secret: impl ToString,
+        api_passphrase: impl ToString,
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        operator: Box<dyn SignalOperator<S>>,
+    ) -> Result<Self> {
         let api_rest = RestClient::with_credentials(
             &config,
             api_key.to_string(),
@@ -210,15 +309,44 @@ impl<S: Signal> LiveTradeEngine<S> {
         )
         .map_err(LiveError::RestApiInit)?;
 
+        Self::new_signal(
+            config,
+            db,
+            api_rest,
+            ExecutorKind::Shadow,
+            evaluators,
+            operator,
+        )
+    }
+
+    fn new_signal(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        api_rest: Arc<RestClient>,
+        executor_kind: ExecutorKind,
+        evaluators: Vec<Box<dyn SignalEvaluator<S>>>,
+        operator: Box<dyn SignalOperator<S>>,
+    ) -> Result<Self> {
+        if evaluators.is_empty() {
+            return Err(LiveError::EmptyEvaluatorsVec);
+        }
+
+        if db.read_only() {
+            return Err(LiveError::ReadOnlyDatabase);
+        }
+
+        config.validate_keys()?;
+
         let api_ws = StreamClient::new(&config);
         let sync_engine = if config.sync_mode_full() {
             SyncEngine::full(&config, db.clone(), api_rest.clone(), api_ws)
         } else {
             // Find the evaluator requiring the most historical data
-            let max_lookback = evaluators
+            let lookbacks: Vec<Lookback> = evaluators
                 .iter()
-                .filter_map(|evaluator| evaluator.lookback())
-                .max_by_key(|lookback| lookback.as_duration());
+                .flat_map(|evaluator| evaluator.lookbacks())
+                .collect();
+            let max_lookback = max_lookback(&lookbacks);
 
             match max_lookback {
                 Some(lookback) => SyncEngine::live_with_lookback(
@@ -236,11 +364,11 @@ impl<S: Signal> LiveTradeEngine<S> {
             LiveSignalEngine::new(&config, db.clone(), sync_engine.reader(), evaluators)
                 .map_err(LiveError::LaunchLiveSignalEngine)?;
 
-        let operator_pending = OperatorPending::signal(signal_engine, operator.into());
+        let operator_pending =
+            OperatorPending::signal(signal_engine, sync_engine.reader(), operator.into());
 
         let trade_executor_launcher =
-            LiveTradeExecutorLauncher::new(&config, db, api_rest, sync_engine.reader())
-                .map_err(LiveError::SetupExecutor)?;
+            executor_kind.launcher(&config, db.clone(), api_rest, sync_engine.reader())?;
 
         let (update_tx, _) = broadcast::channel::<LiveTradeUpdate<S>>(1_000);
 
@@ -248,13 +376,26 @@ impl<S: Signal> LiveTradeEngine<S> {
 
         Ok(Self {
             config,
+            db,
             sync_engine,
             trade_executor_launcher,
             operator_pending,
+            middleware: Vec::new(),
             status_manager,
         })
     }
 
+    /// Sets the middleware layered around the trade executor handed to the operator. The first
+    /// middleware in the list is the outermost layer. Risk limits set in the config are applied
+    /// closest to the executor.
+    ///
+    /// The middleware only wraps the operator's executor. The kill switch and the dead man's switch
+    /// act on the underlying executor directly.
+    pub fn with_middleware(mut self, middleware: Vec<Arc<dyn TradeExecutorMiddleware>>) -> Self {
+        self.middleware = middleware;
+        self
+    }
+
     /// Returns a [`LiveTradeReader`] interface for accessing live status and updates.
     pub fn reader(&self) -> Arc<dyn LiveTradeReader<S>> {
         self.status_manager.clone()
@@ -270,6 +411,52 @@ impl<S: Signal> LiveTradeEngine<S> {
         self.status_manager.status_snapshot()
     }
 
+    async fn kill_switch_keys(&self) -> Result<KillSwitchKeys> {
+        let scope = self
+            .trade_executor_launcher
+            .kill_switch_scope()
+            .await
+            .map_err(LiveError::KillSwitchScope)?;
+
+        Ok(KillSwitchKeys::new(self.config.kill_switch_key(), &scope))
+    }
+
+    /// Returns the persisted kill switch trigger, if the kill switch was triggered and not re-armed
+    /// since. While triggered, the live trading process stops right after starting.
+    ///
+    /// The kill switch state is scoped by executor kind and account, so for live and shadow engines
+    /// the account is resolved via the REST API. A persisted trigger that can't be decoded is
+    /// reported as [`LiveError::KillSwitchDecode`], and can be removed by re-arming the kill switch.
+    pub async fn kill_switch_trip(&self) -> Result<Option<KillSwitchTrip>> {
+        let keys = self.kill_switch_keys().await?;
+
+        let Some(bytes) = self.db.key_values.get_value(keys.trip()).await? else {
+            return Ok(None);
+        };
+
+        let trip = KillSwitchTrip::from_bytes(&bytes).map_err(LiveError::KillSwitchDecode)?;
+
+        Ok(Some(trip))
+    }
+
+    /// Re-arms the kill switch, removing the persisted trigger so the live trading process can
+    /// resume trading. Meant to be called by a human after reviewing the trigger.
+    ///
+    /// The persisted start of day and peak net values are also reset, so daily loss and drawdown
+    /// are measured from the net value observed when trading resumes.
+    pub async fn rearm_kill_switch(&self) -> Result<()> {
+        let keys = self.kill_switch_keys().await?;
+
+        self.db.key_values.remove_value(keys.trip()).await?;
+        self.db.key_values.remove_value(keys.net_value()).await?;
+        self.db
+            .key_values
+            .remove_value(keys.flatten_pending())
+            .await?;
+
+        Ok(())
+    }
+
     /// Starts the live trading process and returns a [`LiveTradeController`] for managing it. This
     /// consumes the engine and spawns the live trading task in the background.
     pub async fn start(self) -> Result<Arc<LiveTradeController<S>>> {
@@ -277,10 +464,12 @@ impl<S: Signal> LiveTradeEngine<S> {
 
         let process_handle = LiveProcess::spawn(
             &self.config,
+            self.db,
             shutdown_tx.clone(),
             self.sync_engine,
             self.operator_pending,
             self.trade_executor_launcher,
+            self.middleware,
             self.status_manager.clone(),
         );
 
@@ -306,8 +495,54 @@ impl LiveTradeEngine<Raw> {
         api_passphrase: impl ToString,
         operator: Box<dyn RawOperator>,
     ) -> Result<Self> {
-        let operator = WrappedRawOperator::from(operator);
+        let api_rest = RestClient::with_credentials(
+            &config,
+            api_key.to_string(),
+            api_secret.to_string(),
+            api_passphrase.to_string(),
+        )
+        .map_err(LiveError::RestApiInit)?;
+
+        Self::new_raw(config, db, api_rest, ExecutorKind::Live, operator)
+    }
 
+    /// Creates a new paper trading engine using a raw operator. Trading actions are executed
+    /// against a simulated ledger starting with `start_balance` (in satoshis) and driven by
+    /// real-time price ticks. No API credentials are required and no orders are sent to the
+    /// exchange. The ledger is persisted and restored across restarts, see
+    /// [`LiveTradeConfig::with_paper_ledger_key`].
+    pub fn paper_with_raw_operator(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        start_balance: u64,
+        operator: Box<dyn RawOperator>,
+    ) -> Result<Self> {
+        let api_rest = RestClient::new(&config).map_err(LiveError::RestApiInit)?;
+
+        Self::new_raw(
+            config,
+            db,
+            api_rest,
+            ExecutorKind::Paper { start_balance },
+            operator,
+        )
+    }
+
+    /// Creates a new shadow trading engine using a raw operator. The operator runs against the real
+    /// account state, which can be read with a read-only API key, but trading actions are never
+    /// sent to the exchange. Each action is emitted as a
+    /// [`LiveTradeExecutorAction`](crate::trade::LiveTradeExecutorAction) and answered with a
+    /// hypothetical result. The trading state returned to the operator overlays the hypothetical
+    /// positions on the real account state, as described in
+    /// [`shadow_with_signal_operator`](LiveTradeEngine::shadow_with_signal_operator).
+    pub fn shadow_with_raw_operator(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        api_key: impl ToString,
+        api_secret: impl ToString,
+        api_passphrase: impl ToString,
+        operator: Box<dyn RawOperator>,
+    ) -> Result<Self> {
         let api_rest = RestClient::with_credentials(
             &config,
             api_key.to_string(),
@@ -316,11 +551,33 @@ impl LiveTradeEngine<Raw> {
         )
         .map_err(LiveError::RestApiInit)?;
 
+        Self::new_raw(config, db, api_rest, ExecutorKind::Shadow, operator)
+    }
+
+    fn new_raw(
+        config: LiveTradeConfig,
+        db: Arc<Database>,
+        api_rest: Arc<RestClient>,
+        executor_kind: ExecutorKind,
+        operator: Box<dyn RawOperator>,
+    ) -> Result<Self> {
+        if db.read_only() {
+            return Err(LiveError::ReadOnlyDatabase);
+        }
+
+        config.validate_keys()?;
+
+        let operator = WrappedRawOperator::from(operator);
+
         let api_ws = StreamClient::new(&config);
         let sync_engine = if config.sync_mode_full() {
             SyncEngine::full(&config, db.clone(), api_rest.clone(), api_ws)
         } else {
-            match operator.lookback().map_err(LiveError::SetupOperatorError)? {
+            let lookbacks = operator
+                .lookbacks()
+                .map_err(LiveError::SetupOperatorError)?;
+
+            match max_lookback(&lookbacks) {
                 Some(lookback) => SyncEngine::live_with_lookback(
                     &config,
                     db.clone(),
@@ -332,11 +589,11 @@ impl LiveTradeEngine<Raw> {
             }
         };
 
-        let operator_pending = OperatorPending::raw(db.clone(), sync_engine.reader(), operator);
+        let operator_pending =
+            OperatorPending::raw(db.clone(), api_rest.clone(), sync_engine.reader(), operator);
 
         let trade_executor_launcher =
-            LiveTradeExecutorLauncher::new(&config, db, api_rest, sync_engine.reader())
-                .map_err(LiveError::SetupExecutor)?;
+            executor_kind.launcher(&config, db.clone(), api_rest, sync_engine.reader())?;
 
         let (update_tx, _) = broadcast::channel::<LiveTradeUpdate<Raw>>(1_000);
 
@@ -344,9 +601,11 @@ impl LiveTradeEngine<Raw> {
 
         Ok(Self {
             config,
+            db,
             sync_engine,
             trade_executor_launcher,
             operator_pending,
+            middleware: Vec::new(),
             status_manager,
         })
     }
@@ -4,7 +4,7 @@ use thiserror::Error;
 
 use lnm_sdk::rest::v3::error::RestApiError;
 
-use crate::signal::error::SignalError;
+use crate::{db::error::DbError, signal::error::SignalError};
 
 use super::{
     super::error::TradeCoreError, executor::error::LiveTradeExecutorError,
@@ -35,8 +35,23 @@ pub enum LiveError {
     #[error("At least one signal evaluator must be provided")]
     EmptyEvaluatorsVec,
 
+    #[error("Database is read-only, so the trading state can't be persisted")]
+    ReadOnlyDatabase,
+
+    #[error("Database key `{key}` starts with the reserved prefix `quantoxide:`")]
+    InvalidConfigurationReservedKey { key: String },
+
+    #[error("Resolve kill switch scope error: {0}")]
+    KillSwitchScope(LiveTradeExecutorError),
+
+    #[error("Persisted kill switch trigger could not be decoded: {0}")]
+    KillSwitchDecode(serde_json::Error),
+
     #[error("Live shutdown procedure failed: {0}")]
     LiveShutdownFailed(Arc<LiveProcessFatalError>),
+
+    #[error("[Db] {0}")]
+    Db(#[from] DbError),
 }
 
 pub(super) type Result<T> = result::Result<T, LiveError>;
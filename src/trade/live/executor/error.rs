@@ -13,7 +13,7 @@ use crate::{
 };
 
 use super::{
-    super::super::error::TradeCoreError,
+    super::super::{backtest::executor::error::SimulatedTradeExecutorError, error::TradeCoreError},
     state::{LiveTradeExecutorStatus, LiveTradeExecutorStatusNotReady},
 };
 
@@ -62,6 +62,9 @@ pub enum ExecutorActionError {
     #[error("Live trade executor is not ready. No session.")]
     ExecutorNotReadyNoSession,
 
+    #[error("Trade executor was not launched yet")]
+    ExecutorNotLaunched,
+
     #[error("Live trade executor is not ready: {0}")]
     ExecutorNotReady(LiveTradeExecutorStatusNotReady),
 
@@ -105,10 +108,32 @@ pub enum ExecutorActionError {
 
     #[error("Closed trade not confirmed by the server. Id: {trade_id}")]
     ClosedTradeNotConfirmed { trade_id: Uuid },
+
+    #[error("Persisted closed trade {trade_id} is invalid: {reason}")]
+    InvalidPersistedClosedTrade { trade_id: Uuid, reason: String },
 }
 
 pub(super) type ExecutorActionResult<T> = result::Result<T, ExecutorActionError>;
 
+/// Non-fatal issue reported by the live trade executor via
+/// [`LiveTradeExecutorUpdate::Warning`](crate::trade::LiveTradeExecutorUpdate::Warning). The
+/// executor keeps running.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum LiveTradeExecutorWarning {
+    #[error("Skipped persisted closed trade that could not be restored: {0}")]
+    ClosedTradeRestoreSkipped(ExecutorActionError),
+
+    #[error("Failed to record executor action in the audit log: {0}")]
+    AuditLogWriteFailed(DbError),
+
+    #[error("Failed to persist the paper trading ledger: {0}")]
+    PaperLedgerSaveFailed(DbError),
+
+    #[error("Failed to persist closed trades: {0}")]
+    ClosedTradesPersistFailed(DbError),
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum ExecutorProcessRecoverableError {
@@ -117,6 +142,12 @@ pub enum ExecutorProcessRecoverableError {
 
     #[error("`SyncRecvLagged` error, skipped: {skipped}")]
     SyncRecvLagged { skipped: u64 },
+
+    #[error("Paper ledger initialization error {0}")]
+    PaperLedgerInitialization(ExecutorActionError),
+
+    #[error("Paper ledger update error {0}")]
+    PaperLedgerUpdate(SimulatedTradeExecutorError),
 }
 
 #[derive(Error, Debug)]
@@ -163,6 +194,15 @@ pub enum LiveTradeExecutorError {
 
     #[error("Executor shutdown procedure failed: {0}")]
     ExecutorShutdownFailed(Arc<ExecutorProcessFatalError>),
+
+    #[error("Paper ledger load error {0}")]
+    PaperLedgerLoad(DbError),
+
+    #[error("Persisted paper ledger could not be decoded: {0}")]
+    PaperLedgerDecode(serde_json::Error),
+
+    #[error("Persisted paper ledger could not be restored: {0}")]
+    PaperLedgerRestore(SimulatedTradeExecutorError),
 }
 
 pub(super) type LiveTradeExecutorResult<T> = result::Result<T, LiveTradeExecutorError>;
@@ -5,11 +5,15 @@ use std::{
 };
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::future;
 use tokio::{
-    sync::broadcast::{
-        self,
-        error::{RecvError, TryRecvError},
+    sync::{
+        OnceCell,
+        broadcast::{
+            self,
+            error::{RecvError, TryRecvError},
+        },
     },
     time,
 };
@@ -18,7 +22,7 @@ use uuid::Uuid;
 use lnm_sdk::rest::v3::{
     RestClient,
     models::{
-        ClientId, CrossLeverage, Leverage, PercentageCapped, Price, TradeSide, TradeSize,
+        ClientId, CrossLeverage, Leverage, PercentageCapped, Price, Trade, TradeSide, TradeSize,
         trade_util,
     },
 };
@@ -33,25 +37,30 @@ use super::{
     super::{
         core::{
             CrossOrderRequest, CrossPositionCore, IsolatedOrderRequest, Stoploss, TradeExecutor,
-            TradingState,
+            TradeTrailingStoploss, TradingState,
         },
         error::TradeExecutorResult,
     },
     config::LiveTradeExecutorConfig,
+    dead_man_switch::{self, DeadManSwitchAction},
 };
 
 pub(crate) mod error;
+pub(in crate::trade) mod paper;
+pub(in crate::trade) mod shadow;
 pub(in crate::trade) mod state;
 pub(in crate::trade) mod update;
 
 use error::{
     ExecutorActionError, ExecutorActionResult, ExecutorProcessFatalError,
     ExecutorProcessFatalResult, ExecutorProcessRecoverableError, LiveTradeExecutorError,
-    LiveTradeExecutorResult,
+    LiveTradeExecutorResult, LiveTradeExecutorWarning,
 };
+use paper::{PaperTradeExecutor, PaperTradeExecutorLauncher};
+use shadow::ShadowTradeExecutor;
 use state::{
-    LiveTradeExecutorState, LiveTradeExecutorStateManager, LiveTradeExecutorStatusNotReady,
-    live_trading_session::LiveTradingSession,
+    LiveTradeExecutorState, LiveTradeExecutorStateManager, LiveTradeExecutorStatus,
+    LiveTradeExecutorStatusNotReady, live_trading_session::LiveTradingSession,
 };
 use update::{
     LiveTradeExecutorReceiver, LiveTradeExecutorTransmitter, LiveTradeExecutorUpdate,
@@ -67,10 +76,12 @@ pub struct LiveTradeExecutor {
     account_id: Uuid,
     update_tx: LiveTradeExecutorTransmitter,
     state_manager: Arc<LiveTradeExecutorStateManager>,
+    shadow: bool,
     handle: Mutex<Option<AbortOnDropHandle<()>>>,
 }
 
 impl LiveTradeExecutor {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         config: LiveTradeExecutorConfig,
         db: Arc<Database>,
@@ -78,6 +89,7 @@ impl LiveTradeExecutor {
         account_id: Uuid,
         update_tx: LiveTradeExecutorTransmitter,
         state_manager: Arc<LiveTradeExecutorStateManager>,
+        shadow: bool,
         handle: AbortOnDropHandle<()>,
     ) -> Arc<Self> {
         Arc::new(Self {
@@ -87,6 +99,7 @@ impl LiveTradeExecutor {
             account_id,
             update_tx,
             state_manager,
+            shadow,
             handle: Mutex::new(Some(handle)),
         })
     }
@@ -117,17 +130,17 @@ impl LiveTradeExecutor {
         Ok(price)
     }
 
-    async fn execute_isolated_order(
+    /// Validates an isolated order against the trading session, returning the evaluated stoploss
+    /// price and trailing stoploss config.
+    async fn validate_isolated_order(
         &self,
+        trading_session: &LiveTradingSession,
         side: TradeSide,
         size: TradeSize,
         leverage: Leverage,
         stoploss: Option<Stoploss>,
         takeprofit: Option<Price>,
-        client_id: Option<ClientId>,
-    ) -> ExecutorActionResult<Uuid> {
-        let locked_ready_state = self.state_manager.try_lock_ready_state().await?;
-
+    ) -> ExecutorActionResult<(Price, Option<Price>, Option<TradeTrailingStoploss>)> {
         let market_price = self.get_estimated_market_price().await?;
 
         let (stoploss_price, trade_tsl) = match stoploss {
@@ -156,8 +169,6 @@ impl LiveTradeExecutor {
             )
             .map_err(ExecutorActionError::InvalidTradeParams)?;
 
-        let trading_session = locked_ready_state.trading_session();
-
         let balance_diff = margin.as_u64() + opening_fee + closing_fee_reserved;
         if balance_diff > trading_session.balance() {
             return Err(ExecutorActionError::BalanceTooLow);
@@ -168,6 +179,31 @@ impl LiveTradeExecutor {
             return Err(ExecutorActionError::MaxRunningTradesReached { max_qtd });
         }
 
+        Ok((market_price, stoploss_price, trade_tsl))
+    }
+
+    async fn execute_isolated_order(
+        &self,
+        side: TradeSide,
+        size: TradeSize,
+        leverage: Leverage,
+        stoploss: Option<Stoploss>,
+        takeprofit: Option<Price>,
+        client_id: Option<ClientId>,
+    ) -> ExecutorActionResult<Uuid> {
+        let locked_ready_state = self.state_manager.try_lock_ready_state().await?;
+
+        let (_, stoploss_price, trade_tsl) = self
+            .validate_isolated_order(
+                locked_ready_state.trading_session(),
+                side,
+                size,
+                leverage,
+                stoploss,
+                takeprofit,
+            )
+            .await?;
+
         let trade = self
             .api
             .isolated_order(side, size, leverage, stoploss_price, takeprofit, client_id)
@@ -203,46 +239,82 @@ impl LiveTradeExecutor {
             }
         }
 
-        let mut all_closed_ids = Vec::with_capacity(to_close.len());
+        let mut closed_trades = Vec::with_capacity(to_close.len());
+        let mut close_res = Ok(());
 
-        // Process in batches of 3
+        // Process in batches of 3. Trades closed before a failure are still registered
         for chunk in to_close.chunks(3) {
             let close_futures = chunk
                 .iter()
                 .map(|&trade_id| self.api.isolated_order_close(trade_id))
                 .collect::<Vec<_>>();
 
-            let closed_trades = future::join_all(close_futures)
-                .await
-                .into_iter()
-                .collect::<result::Result<Vec<_>, _>>()?;
+            for res in future::join_all(close_futures).await {
+                match res {
+                    Ok(closed_trade) => closed_trades.push(closed_trade),
+                    Err(e) => close_res = Err(e),
+                }
+            }
 
-            new_trading_session.close_trades(&closed_trades)?;
+            if close_res.is_err() {
+                break;
+            }
+        }
 
-            let mut closed_ids = Vec::with_capacity(closed_trades.len());
+        new_trading_session.close_trades(&closed_trades)?;
 
-            for closed_trade in closed_trades {
-                closed_ids.push(closed_trade.id());
+        locked_ready_state
+            .update_trading_session(new_trading_session)
+            .await;
 
-                // Ignore no-receiver errors
-                let _ = self
-                    .update_tx
-                    .send(LiveTradeExecutorUpdate::ClosedTrade(closed_trade));
-            }
+        let closed_ids = self.register_closed_trades(closed_trades).await?;
 
-            self.db
-                .running_trades
-                .remove_running_trades(self.account_id, closed_ids.as_slice())
-                .await?;
+        close_res.map(|_| closed_ids)
+    }
+
+    /// Removes the running trade records of the given closed trades, broadcasts them and persists
+    /// them. The trading session must have been updated beforehand.
+    ///
+    /// The trades are closed on the exchange regardless, so failures to persist them are reported
+    /// as [`LiveTradeExecutorWarning::ClosedTradesPersistFailed`] updates. Since closed trades are
+    /// inserted idempotently, a later attempt is safe.
+    async fn register_closed_trades(
+        &self,
+        closed_trades: Vec<Trade>,
+    ) -> ExecutorActionResult<Vec<Uuid>> {
+        let closed_ids: Vec<Uuid> = closed_trades.iter().map(|trade| trade.id()).collect();
+
+        let remove_res = self
+            .db
+            .running_trades
+            .remove_running_trades(self.account_id, closed_ids.as_slice())
+            .await
+            .map_err(ExecutorActionError::Db);
 
-            all_closed_ids.extend(closed_ids);
+        for closed_trade in &closed_trades {
+            // Ignore no-receiver errors
+            let _ = self
+                .update_tx
+                .send(LiveTradeExecutorUpdate::ClosedTrade(Arc::new(
+                    closed_trade.clone(),
+                )));
         }
 
-        locked_ready_state
-            .update_trading_session(new_trading_session)
-            .await;
+        if let Err(e) = self
+            .db
+            .closed_trades
+            .add_closed_trades(self.account_id, &closed_trades)
+            .await
+        {
+            let warning = LiveTradeExecutorWarning::ClosedTradesPersistFailed(e);
 
-        Ok(all_closed_ids)
+            // Ignore no-receiver errors
+            let _ = self
+                .update_tx
+                .send(LiveTradeExecutorUpdate::Warning(Arc::new(warning)));
+        }
+
+        remove_res.map(|_| closed_ids)
     }
 
     async fn clean_up_all_api_trades(api: &WrappedRestClient) -> ExecutorActionResult<()> {
@@ -260,6 +332,100 @@ impl LiveTradeExecutor {
         Self::clean_up_all_api_trades(&self.api).await
     }
 
+    /// Applies the dead man's switch action directly through the API. The executor status is not
+    /// checked, since the switch is meant to be triggered while the live process is unhealthy.
+    async fn apply_dead_man_switch(&self, action: DeadManSwitchAction) -> ExecutorActionResult<()> {
+        // Held so that trading session refreshes cannot interleave with the action
+        let mut locked_state = self.state_manager.lock_state().await;
+
+        let res = match action {
+            DeadManSwitchAction::Flatten => self.dead_man_switch_flatten().await,
+            DeadManSwitchAction::TightenStoplosses(distance) => {
+                self.dead_man_switch_tighten_stoplosses(distance).await
+            }
+        };
+
+        // The stored trading session may no longer match the exchange state
+        locked_state.expire_trading_session();
+
+        res
+    }
+
+    async fn dead_man_switch_flatten(&self) -> ExecutorActionResult<()> {
+        let (_, closed_trades, _, _) = futures::try_join!(
+            self.api.isolated_order_cancel_all(),
+            self.api.isolated_order_close_all(),
+            self.api.cross_cancel_all_orders(),
+            self.api.cross_order_close_position()
+        )?;
+
+        self.register_closed_trades(closed_trades).await?;
+
+        Ok(())
+    }
+
+    async fn dead_man_switch_tighten_stoplosses(
+        &self,
+        distance: PercentageCapped,
+    ) -> ExecutorActionResult<()> {
+        let running_trades = self.api.get_trades_running().await?;
+        let to_update = self
+            .dead_man_switch_stoploss_updates(&running_trades, distance)
+            .await?;
+
+        for chunk in to_update.chunks(3) {
+            let update_futures = chunk
+                .iter()
+                .map(|&(trade_id, new_stoploss)| {
+                    self.api
+                        .isolated_trade_update_stoploss(trade_id, new_stoploss)
+                })
+                .collect::<Vec<_>>();
+
+            future::join_all(update_futures)
+                .await
+                .into_iter()
+                .collect::<result::Result<Vec<_>, _>>()?;
+        }
+
+        // Cross-margin positions don't support stoplosses
+        futures::try_join!(
+            self.api.cross_cancel_all_orders(),
+            self.api.cross_order_close_position()
+        )?;
+
+        Ok(())
+    }
+
+    /// Returns the stoploss updates of the tighten stoplosses action of the dead man's switch for
+    /// the given running trades. The price feed may be stale, so the market price is fetched from
+    /// the exchange.
+    async fn dead_man_switch_stoploss_updates(
+        &self,
+        running_trades: &[Trade],
+        distance: PercentageCapped,
+    ) -> ExecutorActionResult<Vec<(Uuid, Price)>> {
+        let market_price = self.api.get_ticker().await?.last_price();
+
+        let mut to_update = Vec::new();
+        for trade in running_trades {
+            let new_stoploss = dead_man_switch::tightened_stoploss(
+                trade.side(),
+                trade.stoploss(),
+                trade.liquidation(),
+                market_price,
+                distance,
+            )
+            .map_err(ExecutorActionError::InvalidMarketPrice)?;
+
+            if let Some(new_stoploss) = new_stoploss {
+                to_update.push((trade.id(), new_stoploss));
+            }
+        }
+
+        Ok(to_update)
+    }
+
     fn try_consume_handle(&self) -> Option<AbortOnDropHandle<()>> {
         self.handle
             .lock()
@@ -287,7 +453,9 @@ impl LiveTradeExecutor {
 
         handle.abort();
 
-        if !self.config.shutdown_clean_up_trades()
+        // Shadow executors must never send orders, including on shutdown
+        if self.shadow
+            || !self.config.shutdown_clean_up_trades()
             || !self.state_manager.has_registered_running_trades().await
         {
             self.state_manager
@@ -417,25 +585,16 @@ impl TradeExecutor for LiveTradeExecutor {
 
         let closed_trade = self.api.isolated_order_close(trade_id).await?;
 
-        self.db
-            .running_trades
-            .remove_running_trades(self.account_id, &[closed_trade.id()])
-            .await
-            .map_err(ExecutorActionError::Db)?;
-
         let mut new_trading_session = locked_ready_state.trading_session().to_owned();
 
         new_trading_session.close_trade(&closed_trade)?;
 
-        // Ignore no-receiver errors
-        let _ = self
-            .update_tx
-            .send(LiveTradeExecutorUpdate::ClosedTrade(closed_trade));
-
         locked_ready_state
             .update_trading_session(new_trading_session)
             .await;
 
+        self.register_closed_trades(vec![closed_trade]).await?;
+
         Ok(())
     }
 
@@ -459,22 +618,11 @@ impl TradeExecutor for LiveTradeExecutor {
 
         new_trading_session.close_trades(&closed_trades)?;
 
-        let mut closed_ids = Vec::with_capacity(closed_trades.len());
-
-        for closed_trade in closed_trades {
-            closed_ids.push(closed_trade.id());
-
-            // Ignore no-receiver errors
-            let _ = self
-                .update_tx
-                .send(LiveTradeExecutorUpdate::ClosedTrade(closed_trade));
-        }
-
         locked_ready_state
             .update_trading_session(new_trading_session)
             .await;
 
-        Ok(closed_ids)
+        Ok(self.register_closed_trades(closed_trades).await?)
     }
 
     async fn cross_deposit(
@@ -619,6 +767,8 @@ pub struct LiveTradeExecutorLauncher {
     update_tx: LiveTradeExecutorTransmitter,
     state_manager: Arc<LiveTradeExecutorStateManager>,
     sync_reader: Arc<dyn SyncReader>,
+    shadow: bool,
+    account_id: OnceCell<Uuid>,
 }
 
 impl LiveTradeExecutorLauncher {
@@ -629,6 +779,27 @@ impl LiveTradeExecutorLauncher {
         db: Arc<Database>,
         api_rest: Arc<RestClient>,
         sync_reader: Arc<dyn SyncReader>,
+    ) -> LiveTradeExecutorResult<Self> {
+        Self::with_shadow(config, db, api_rest, sync_reader, false)
+    }
+
+    /// Creates a new launcher for a shadow executor. The account state is read from the exchange,
+    /// but no orders are sent on startup, during trailing stoploss management, or on shutdown.
+    pub(in crate::trade) fn shadow(
+        config: impl Into<LiveTradeExecutorConfig>,
+        db: Arc<Database>,
+        api_rest: Arc<RestClient>,
+        sync_reader: Arc<dyn SyncReader>,
+    ) -> LiveTradeExecutorResult<Self> {
+        Self::with_shadow(config, db, api_rest, sync_reader, true)
+    }
+
+    fn with_shadow(
+        config: impl Into<LiveTradeExecutorConfig>,
+        db: Arc<Database>,
+        api_rest: Arc<RestClient>,
+        sync_reader: Arc<dyn SyncReader>,
+        shadow: bool,
     ) -> LiveTradeExecutorResult<Self> {
         if !api_rest.has_credentials {
             return Err(LiveTradeExecutorError::ApiCredentialsNotSet);
@@ -654,6 +825,8 @@ impl LiveTradeExecutorLauncher {
             update_tx,
             state_manager,
             sync_reader,
+            shadow,
+            account_id: OnceCell::new(),
         })
     }
 
@@ -662,9 +835,28 @@ impl LiveTradeExecutorLauncher {
         self.update_tx.subscribe()
     }
 
+    /// Resolves the account of the API credentials. The account is only requested once.
+    pub async fn account_id(&self) -> LiveTradeExecutorResult<Uuid> {
+        self.account_id
+            .get_or_try_init(|| async {
+                let user = self
+                    .api_rest
+                    .get_user()
+                    .await
+                    .map_err(LiveTradeExecutorError::LaunchAccountResolution)?;
+
+                Ok(user.id())
+            })
+            .await
+            .copied()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn spawn_sync_processor(
         startup_recover_trades: bool,
+        recover_trades_tsl: bool,
+        persist_closed_trades: bool,
+        closed_history_from: Option<DateTime<Utc>>,
         trade_tsl_step_size: PercentageCapped,
         trading_session_refresh_interval: time::Duration,
         db: Arc<Database>,
@@ -685,41 +877,59 @@ impl LiveTradeExecutorLauncher {
                     Some(old_trading_session) if !old_trading_session.is_expired() => {
                         let mut restored_trading_session = old_trading_session;
 
-                        match restored_trading_session
-                            .reevaluate(db.as_ref(), &api)
+                        // If persisting the closed trades fails, the previous session is kept
+                        // and they are detected again on the next refresh
+                        let reevaluation = async {
+                            let closed_trades =
+                                restored_trading_session.reevaluate(db.as_ref(), &api).await?;
+
+                            if persist_closed_trades {
+                                db.closed_trades
+                                    .add_closed_trades(account_id, &closed_trades)
+                                    .await
+                                    .map_err(ExecutorActionError::Db)?;
+                            }
+
+                            Ok(closed_trades)
+                        };
+
+                        match reevaluation
                             .await
                             .map_err(ExecutorProcessRecoverableError::LiveTradeSessionEvaluation)
                         {
-                            Ok(closed_trades) => Ok((restored_trading_session, closed_trades)),
-                            Err(e) => Err(e),
-                        }
-                    }
-                    prev_session => {
-                        match LiveTradingSession::new(
-                            startup_recover_trades,
-                            trade_tsl_step_size,
-                            db.as_ref(),
-                            &api,
-                            account_id,
-                            prev_session,
-                        )
-                        .await
-                        .map_err(ExecutorProcessRecoverableError::LiveTradeSessionEvaluation)
-                        {
-                            Ok(new_trading_session) => Ok((new_trading_session, Vec::new())),
+                            Ok(closed_trades) => {
+                                Ok((restored_trading_session, closed_trades, Vec::new()))
+                            }
                             Err(e) => Err(e),
                         }
                     }
+                    prev_session => LiveTradingSession::new(
+                        startup_recover_trades,
+                        recover_trades_tsl,
+                        persist_closed_trades,
+                        closed_history_from,
+                        trade_tsl_step_size,
+                        db.as_ref(),
+                        &api,
+                        account_id,
+                        prev_session,
+                    )
+                    .await
+                    .map_err(ExecutorProcessRecoverableError::LiveTradeSessionEvaluation),
                 };
 
                 match result {
-                    Ok((trading_session, closed_trades)) => {
+                    Ok((trading_session, closed_trades, warnings)) => {
                         locked_state.update_status_ready(trading_session);
 
+                        // Ignore no-receiver errors
+                        for warning in warnings {
+                            let _ = update_tx.send(LiveTradeExecutorUpdate::Warning(Arc::new(warning)));
+                        }
+
                         for closed_trade in closed_trades {
-                            // Ignore no-receiver errors
                             let _ = update_tx
-                                .send(LiveTradeExecutorUpdate::ClosedTrade(closed_trade));
+                                .send(LiveTradeExecutorUpdate::ClosedTrade(Arc::new(closed_trade)));
                         }
                     }
                     Err(e) => {
@@ -828,25 +1038,32 @@ impl LiveTradeExecutorLauncher {
     /// Launches the live trade executor after optionally cleaning up existing trades. Returns a
     /// running executor instance.
     pub async fn launch(self) -> LiveTradeExecutorResult<Arc<LiveTradeExecutor>> {
-        if self.config.startup_clean_up_trades() {
-            LiveTradeExecutor::clean_up_all_api_trades(&self.api_rest)
+        let account_id = self.account_id().await?;
+
+        let api_rest = self.api_rest.with_audit_log(self.db.clone(), account_id);
+
+        if !self.shadow && self.config.startup_clean_up_trades() {
+            LiveTradeExecutor::clean_up_all_api_trades(&api_rest)
                 .await
                 .map_err(LiveTradeExecutorError::LaunchCleanUp)?;
         }
 
-        let account_id = self
-            .api_rest
-            .get_user()
-            .await
-            .map_err(LiveTradeExecutorError::LaunchAccountResolution)?
-            .id();
+        let closed_history_from = self
+            .config
+            .startup_closed_history_window()
+            .map(|window| Utc::now() - window);
 
+        // Trailing stoplosses and closed trades of a shadowed account are managed by its own
+        // executor
         let handle = Self::spawn_sync_processor(
             self.config.startup_recover_trades(),
+            !self.shadow,
+            !self.shadow,
+            closed_history_from,
             self.config.trailing_stoploss_step_size(),
             self.config.trading_session_refresh_interval(),
             self.db.clone(),
-            self.api_rest.clone(),
+            api_rest.clone(),
             account_id,
             self.update_tx.clone(),
             self.state_manager.clone(),
@@ -856,11 +1073,107 @@ impl LiveTradeExecutorLauncher {
         Ok(LiveTradeExecutor::new(
             self.config,
             self.db,
-            self.api_rest,
+            api_rest,
             account_id,
             self.update_tx,
             self.state_manager,
+            self.shadow,
             handle,
         ))
     }
 }
+
+/// Trade executor launcher used by the live trade process, either sending orders to the exchange,
+/// recording them in a paper trading ledger, or intercepting them in shadow mode.
+pub(in crate::trade) enum ExecutorPending {
+    Live(LiveTradeExecutorLauncher),
+    Paper(PaperTradeExecutorLauncher),
+    Shadow(LiveTradeExecutorLauncher),
+}
+
+impl ExecutorPending {
+    pub fn update_receiver(&self) -> LiveTradeExecutorReceiver {
+        match self {
+            Self::Live(launcher) | Self::Shadow(launcher) => launcher.update_receiver(),
+            Self::Paper(launcher) => launcher.update_receiver(),
+        }
+    }
+
+    /// Returns the scope of the kill switch state, composed of the executor kind and, for executors
+    /// acting on an exchange account, the account ID.
+    pub async fn kill_switch_scope(&self) -> LiveTradeExecutorResult<String> {
+        match self {
+            Self::Live(launcher) => Ok(format!("live:{}", launcher.account_id().await?)),
+            Self::Paper(_) => Ok("paper".to_string()),
+            Self::Shadow(launcher) => Ok(format!("shadow:{}", launcher.account_id().await?)),
+        }
+    }
+
+    pub async fn launch(self) -> LiveTradeExecutorResult<ExecutorRunning> {
+        match self {
+            Self::Live(launcher) => Ok(ExecutorRunning::Live(launcher.launch().await?)),
+            Self::Paper(launcher) => Ok(ExecutorRunning::Paper(launcher.launch().await?)),
+            Self::Shadow(launcher) => Ok(ExecutorRunning::Shadow(ShadowTradeExecutor::new(
+                launcher.launch().await?,
+            ))),
+        }
+    }
+}
+
+impl From<LiveTradeExecutorLauncher> for ExecutorPending {
+    fn from(value: LiveTradeExecutorLauncher) -> Self {
+        Self::Live(value)
+    }
+}
+
+impl From<PaperTradeExecutorLauncher> for ExecutorPending {
+    fn from(value: PaperTradeExecutorLauncher) -> Self {
+        Self::Paper(value)
+    }
+}
+
+#[derive(Clone)]
+pub(in crate::trade) enum ExecutorRunning {
+    Live(Arc<LiveTradeExecutor>),
+    Paper(Arc<PaperTradeExecutor>),
+    Shadow(Arc<ShadowTradeExecutor>),
+}
+
+impl ExecutorRunning {
+    pub async fn status_snapshot(&self) -> LiveTradeExecutorStatus {
+        match self {
+            Self::Live(executor) => executor.state_snapshot().await.status().clone(),
+            Self::Paper(executor) => executor.status_snapshot().await,
+            Self::Shadow(executor) => executor.status_snapshot().await,
+        }
+    }
+
+    pub fn trade_executor(&self) -> Arc<dyn TradeExecutor> {
+        match self {
+            Self::Live(executor) => executor.clone(),
+            Self::Paper(executor) => executor.clone(),
+            Self::Shadow(executor) => executor.clone(),
+        }
+    }
+
+    /// Applies the dead man's switch action. Paper executors apply it to their ledger, and shadow
+    /// executors record the requests that would have been sent.
+    pub async fn apply_dead_man_switch(
+        &self,
+        action: DeadManSwitchAction,
+    ) -> TradeExecutorResult<()> {
+        match self {
+            Self::Live(executor) => Ok(executor.apply_dead_man_switch(action).await?),
+            Self::Paper(executor) => executor.apply_dead_man_switch(action).await,
+            Self::Shadow(executor) => Ok(executor.apply_dead_man_switch(action).await?),
+        }
+    }
+
+    pub async fn shutdown(&self) -> LiveTradeExecutorResult<()> {
+        match self {
+            Self::Live(executor) => executor.shutdown().await,
+            Self::Paper(executor) => executor.shutdown().await,
+            Self::Shadow(executor) => executor.shutdown().await,
+        }
+    }
+}
@@ -0,0 +1,1207 @@
+use std::{
+    collections::HashSet,
+    num::NonZeroU64,
+    result,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::{
+        Mutex as AsyncMutex,
+        broadcast::{
+            self,
+            error::{RecvError, TryRecvError},
+        },
+    },
+    time,
+};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{CrossLeverage, TradeSide};
+
+use crate::{
+    db::Database,
+    sync::{SyncReader, SyncStatus, SyncUpdate},
+    util::{AbortOnDropHandle, DateTimeExt, Never},
+};
+
+use super::{
+    super::{
+        super::{
+            backtest::{
+                config::SimulatedTradeExecutorConfig,
+                executor::{SimulatedTradeExecutor, SimulatedTradeExecutorSnapshot},
+            },
+            core::{
+                CrossOrderRequest, CrossPositionCore, IsolatedOrderRequest, Stoploss,
+                TradeExecutor, TradingState,
+            },
+            error::TradeExecutorResult,
+        },
+        config::LiveTradeConfig,
+        dead_man_switch::{self, DeadManSwitchAction},
+    },
+    error::{
+        ExecutorActionError, ExecutorActionResult, ExecutorProcessFatalError,
+        ExecutorProcessFatalResult, ExecutorProcessRecoverableError, LiveTradeExecutorError,
+        LiveTradeExecutorResult, LiveTradeExecutorWarning,
+    },
+    state::{LiveTradeExecutorStatus, LiveTradeExecutorStatusNotReady},
+    update::{
+        LiveTradeExecutorAction, LiveTradeExecutorReceiver, LiveTradeExecutorTransmitter,
+        LiveTradeExecutorUpdate,
+    },
+};
+
+/// Persisted state of a paper trading ledger.
+#[derive(Serialize, Deserialize)]
+struct PaperLedgerRecord {
+    settled_until: DateTime<Utc>,
+    ledger: SimulatedTradeExecutorSnapshot,
+}
+
+/// Destination of the persisted paper trading ledger.
+struct PaperLedgerStore {
+    db: Arc<Database>,
+    key: String,
+}
+
+struct PaperTradeExecutorState {
+    status: LiveTradeExecutorStatus,
+    ledger: Option<Arc<SimulatedTradeExecutor>>,
+    settled_until: Option<DateTime<Utc>>,
+    reported_closed: HashSet<Uuid>,
+}
+
+struct PaperTradeExecutorStateManager {
+    state: AsyncMutex<PaperTradeExecutorState>,
+    update_tx: LiveTradeExecutorTransmitter,
+    ledger_store: Option<PaperLedgerStore>,
+}
+
+impl PaperTradeExecutorStateManager {
+    fn new(
+        update_tx: LiveTradeExecutorTransmitter,
+        ledger_store: Option<PaperLedgerStore>,
+    ) -> Arc<Self> {
+        let initial_state = PaperTradeExecutorState {
+            status: LiveTradeExecutorStatusNotReady::Starting.into(),
+            ledger: None,
+            settled_until: None,
+            reported_closed: HashSet::new(),
+        };
+
+        Arc::new(Self {
+            state: AsyncMutex::new(initial_state),
+            update_tx,
+            ledger_store,
+        })
+    }
+
+    async fn status_snapshot(&self) -> LiveTradeExecutorStatus {
+        self.state.lock().await.status.clone()
+    }
+
+    async fn ledger(&self) -> Option<Arc<SimulatedTradeExecutor>> {
+        self.state.lock().await.ledger.clone()
+    }
+
+    async fn try_get_ready_ledger(&self) -> ExecutorActionResult<Arc<SimulatedTradeExecutor>> {
+        let state_guard = self.state.lock().await;
+
+        match (&state_guard.status, &state_guard.ledger) {
+            (LiveTradeExecutorStatus::Ready, Some(ledger)) => Ok(ledger.clone()),
+            (LiveTradeExecutorStatus::Ready, None) => {
+                Err(ExecutorActionError::ExecutorNotReadyNoSession)
+            }
+            (LiveTradeExecutorStatus::NotReady(not_ready_status), _) => Err(
+                ExecutorActionError::ExecutorNotReady(not_ready_status.clone()),
+            ),
+        }
+    }
+
+    async fn update_status_not_ready(&self, new_status_not_ready: LiveTradeExecutorStatusNotReady) {
+        let mut state_guard = self.state.lock().await;
+
+        if let LiveTradeExecutorStatus::NotReady(current) = &state_guard.status
+            && *current == new_status_not_ready
+        {
+            return;
+        }
+
+        let new_status: LiveTradeExecutorStatus = new_status_not_ready.into();
+        state_guard.status = new_status.clone();
+
+        // Ignore no-receivers errors
+        let _ = self.update_tx.send(new_status.into());
+    }
+
+    /// Loads the persisted ledger, if any, along with the cursor it was saved at. The trades
+    /// closed by the restored ledger are not reported again.
+    async fn restore_ledger(
+        &self,
+        config: &SimulatedTradeExecutorConfig,
+    ) -> LiveTradeExecutorResult<Option<PaperLedgerCursor>> {
+        let Some(store) = self.ledger_store.as_ref() else {
+            return Ok(None);
+        };
+
+        let Some(bytes) = store
+            .db
+            .key_values
+            .get_value(&store.key)
+            .await
+            .map_err(LiveTradeExecutorError::PaperLedgerLoad)?
+        else {
+            return Ok(None);
+        };
+
+        let record: PaperLedgerRecord =
+            serde_json::from_slice(&bytes).map_err(LiveTradeExecutorError::PaperLedgerDecode)?;
+
+        let cursor = PaperLedgerCursor {
+            last_tick_time: record.ledger.time(),
+            settled_until: record.settled_until,
+        };
+        let reported_closed = record.ledger.closed_ids().collect();
+
+        let ledger = SimulatedTradeExecutor::from_snapshot(config.clone(), record.ledger)
+            .map_err(LiveTradeExecutorError::PaperLedgerRestore)?;
+
+        let mut state_guard = self.state.lock().await;
+
+        state_guard.reported_closed = reported_closed;
+        state_guard.ledger = Some(ledger);
+        state_guard.settled_until = Some(cursor.settled_until);
+
+        Ok(Some(cursor))
+    }
+
+    /// Persists the ledger, if enabled, so that it can be restored when the executor is launched
+    /// again. Failures are reported as [`LiveTradeExecutorWarning::PaperLedgerSaveFailed`]
+    /// updates.
+    async fn save_ledger(&self) {
+        let Some(store) = self.ledger_store.as_ref() else {
+            return;
+        };
+
+        let (ledger, settled_until) = {
+            let state_guard = self.state.lock().await;
+            match (&state_guard.ledger, state_guard.settled_until) {
+                (Some(ledger), Some(settled_until)) => (ledger.clone(), settled_until),
+                _ => return,
+            }
+        };
+
+        let record = PaperLedgerRecord {
+            settled_until,
+            ledger: ledger.snapshot().await,
+        };
+        let bytes = serde_json::to_vec(&record).expect("paper ledger record is serializable");
+
+        if let Err(e) = store.db.key_values.set_value(&store.key, &bytes).await {
+            let warning = LiveTradeExecutorWarning::PaperLedgerSaveFailed(e);
+
+            // Ignore no-receivers errors
+            let _ = self
+                .update_tx
+                .send(LiveTradeExecutorUpdate::Warning(Arc::new(warning)));
+        }
+    }
+
+    async fn update_settled_until(&self, settled_until: DateTime<Utc>) {
+        self.state.lock().await.settled_until = Some(settled_until);
+    }
+
+    async fn update_status_ready(
+        &self,
+        ledger: Arc<SimulatedTradeExecutor>,
+        settled_until: DateTime<Utc>,
+    ) {
+        let mut state_guard = self.state.lock().await;
+
+        state_guard.ledger = Some(ledger);
+        state_guard.settled_until = Some(settled_until);
+
+        if !matches!(state_guard.status, LiveTradeExecutorStatus::Ready) {
+            state_guard.status = LiveTradeExecutorStatus::Ready;
+
+            // Ignore no-receivers errors
+            let _ = self.update_tx.send(LiveTradeExecutorStatus::Ready.into());
+        }
+    }
+
+    /// Sends the current trading state of the ledger, preceded by a closed trade update for each
+    /// trade closed by the ledger since the last call.
+    async fn send_trading_state(
+        &self,
+        ledger: &SimulatedTradeExecutor,
+    ) -> TradeExecutorResult<TradingState> {
+        let trading_state = ledger.trading_state().await?;
+
+        let mut state_guard = self.state.lock().await;
+
+        // Closed trades are only ever added to the ledger history
+        if trading_state.closed_len() > state_guard.reported_closed.len() {
+            for closed_trade in trading_state.closed_history().iter() {
+                if state_guard.reported_closed.insert(closed_trade.id()) {
+                    // Ignore no-receivers errors
+                    let _ = self
+                        .update_tx
+                        .send(LiveTradeExecutorUpdate::ClosedTrade(closed_trade.clone()));
+                }
+            }
+        }
+
+        // Ignore no-receivers errors
+        let _ = self
+            .update_tx
+            .send(LiveTradeExecutorUpdate::TradingState(trading_state.clone()));
+
+        Ok(trading_state)
+    }
+}
+
+/// Paper trade executor implementing the [`TradeExecutor`] trait on top of a simulated ledger
+/// driven by the live price feed. No orders are sent to the exchange.
+///
+/// Executor actions, trading state and closed trade updates are emitted in the same format as the
+/// [`LiveTradeExecutor`](super::LiveTradeExecutor). Trades closed by the ledger, either by an action
+/// or by the price feed, are reported along with the next trading state update.
+pub(in crate::trade) struct PaperTradeExecutor {
+    config: SimulatedTradeExecutorConfig,
+    update_tx: LiveTradeExecutorTransmitter,
+    state_manager: Arc<PaperTradeExecutorStateManager>,
+    handle: Mutex<Option<AbortOnDropHandle<()>>>,
+}
+
+impl PaperTradeExecutor {
+    pub async fn status_snapshot(&self) -> LiveTradeExecutorStatus {
+        self.state_manager.status_snapshot().await
+    }
+
+    fn send_action_update(&self, action: LiveTradeExecutorAction) {
+        // Ignore no-receivers errors
+        let _ = self.update_tx.send(action.into());
+    }
+
+    async fn ready_ledger(&self) -> ExecutorActionResult<Arc<SimulatedTradeExecutor>> {
+        self.state_manager.try_get_ready_ledger().await
+    }
+
+    async fn after_action<T>(
+        &self,
+        ledger: &SimulatedTradeExecutor,
+        result: TradeExecutorResult<T>,
+    ) -> TradeExecutorResult<T> {
+        let value = result?;
+        self.state_manager.send_trading_state(ledger).await?;
+        self.state_manager.save_ledger().await;
+
+        Ok(value)
+    }
+
+    /// Sends a close action for each running isolated trade of the given side, mirroring the
+    /// requests of the [`LiveTradeExecutor`](super::LiveTradeExecutor).
+    async fn send_close_side_actions(
+        &self,
+        ledger: &SimulatedTradeExecutor,
+        side: TradeSide,
+    ) -> TradeExecutorResult<()> {
+        let trading_state = ledger.trading_state().await?;
+
+        for (trade, _) in trading_state.running_map().trades_desc() {
+            if trade.side() == side {
+                self.send_action_update(LiveTradeExecutorAction::IsolatedOrderClose {
+                    id: trade.id(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the dead man's switch action to the ledger, sending the actions the
+    /// [`LiveTradeExecutor`](super::LiveTradeExecutor) would send. The executor status is not
+    /// checked, since the switch is meant to be triggered while the live process is unhealthy.
+    pub async fn apply_dead_man_switch(
+        &self,
+        action: DeadManSwitchAction,
+    ) -> TradeExecutorResult<()> {
+        let Some(ledger) = self.state_manager.ledger().await else {
+            return Err(ExecutorActionError::ExecutorNotReadyNoSession)?;
+        };
+
+        match action {
+            DeadManSwitchAction::Flatten => {
+                self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCancelAll);
+                self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCloseAll);
+
+                ledger.isolated_order_close_all().await?;
+            }
+            DeadManSwitchAction::TightenStoplosses(distance) => {
+                let trading_state = ledger.trading_state().await?;
+
+                for (trade, _) in trading_state.running_map().trades_desc() {
+                    let new_stoploss = dead_man_switch::tightened_stoploss(
+                        trade.side(),
+                        trade.stoploss(),
+                        trade.liquidation(),
+                        trading_state.market_price(),
+                        distance,
+                    )
+                    .map_err(ExecutorActionError::InvalidMarketPrice)?;
+
+                    if let Some(stoploss) = new_stoploss {
+                        self.send_action_update(
+                            LiveTradeExecutorAction::IsolatedTradeUpdateStoploss {
+                                id: trade.id(),
+                                stoploss,
+                            },
+                        );
+
+                        ledger.update_stoploss(trade.id(), stoploss).await?;
+                    }
+                }
+            }
+        }
+
+        // Cross-margin positions don't support stoplosses, so they are closed by both actions
+        self.send_action_update(LiveTradeExecutorAction::CrossOrderCancelAll);
+        self.send_action_update(LiveTradeExecutorAction::CrossOrderClosePosition);
+
+        let result = ledger.cross_order_close_position().await.map(|_| ());
+        self.after_action(&ledger, result).await
+    }
+
+    fn try_consume_handle(&self) -> Option<AbortOnDropHandle<()>> {
+        self.handle
+            .lock()
+            .expect("`PaperTradeExecutor` mutex can't be poisoned")
+            .take()
+    }
+
+    /// Shuts down the paper trade executor. The simulated ledger is persisted, if enabled, and
+    /// discarded. This method can only be called once per executor instance.
+    pub async fn shutdown(&self) -> LiveTradeExecutorResult<()> {
+        let Some(handle) = self.try_consume_handle() else {
+            return Err(LiveTradeExecutorError::ExecutorProcessAlreadyConsumed);
+        };
+
+        if handle.is_finished() {
+            let status = self.state_manager.status_snapshot().await;
+            return Err(LiveTradeExecutorError::ExecutorProcessAlreadyTerminated(
+                status,
+            ));
+        }
+
+        self.state_manager
+            .update_status_not_ready(LiveTradeExecutorStatusNotReady::ShutdownInitiated)
+            .await;
+
+        self.state_manager.save_ledger().await;
+
+        handle.abort();
+
+        self.state_manager
+            .update_status_not_ready(LiveTradeExecutorStatusNotReady::Shutdown)
+            .await;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for PaperTradeExecutor {
+    async fn isolated_order(&self, request: IsolatedOrderRequest) -> TradeExecutorResult<Uuid> {
+        let ledger = self.ready_ledger().await?;
+
+        let stoploss = match request.stoploss() {
+            Some(Stoploss::Fixed(price)) => Some(*price),
+            Some(stoploss) => {
+                let market_price = ledger.trading_state().await?.market_price();
+                stoploss
+                    .evaluate(
+                        self.config.trailing_stoploss_step_size(),
+                        request.side(),
+                        market_price,
+                    )
+                    .ok()
+                    .map(|(stoploss_price, _)| stoploss_price)
+            }
+            None => None,
+        };
+
+        self.send_action_update(LiveTradeExecutorAction::IsolatedOrder {
+            side: request.side(),
+            size: request.size(),
+            leverage: request.leverage(),
+            stoploss,
+            takeprofit: request.takeprofit(),
+            client_id: request.client_id().cloned(),
+        });
+
+        let result = ledger.isolated_order(request).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_trade_add_margin(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::IsolatedTradeAddMargin {
+            id: trade_id,
+            amount,
+        });
+
+        let result = ledger.isolated_trade_add_margin(trade_id, amount).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_trade_cash_in(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::IsolatedTradeCashIn {
+            id: trade_id,
+            amount,
+        });
+
+        let result = ledger.isolated_trade_cash_in(trade_id, amount).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_order_close(&self, trade_id: Uuid) -> TradeExecutorResult<()> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderClose { id: trade_id });
+
+        let result = ledger.isolated_order_close(trade_id).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_order_close_longs(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_close_side_actions(&ledger, TradeSide::Buy)
+            .await?;
+
+        let result = ledger.isolated_order_close_longs().await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_order_close_shorts(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_close_side_actions(&ledger, TradeSide::Sell)
+            .await?;
+
+        let result = ledger.isolated_order_close_shorts().await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn isolated_order_close_all(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCancelAll);
+        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCloseAll);
+
+        let result = ledger.isolated_order_close_all().await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn cross_deposit(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::CrossDeposit { amount });
+
+        let result = ledger.cross_deposit(amount).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn cross_withdraw(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::CrossWithdraw { amount });
+
+        let result = ledger.cross_withdraw(amount).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn cross_set_leverage(
+        &self,
+        leverage: CrossLeverage,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::CrossSetLeverage { leverage });
+
+        let result = ledger.cross_set_leverage(leverage).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn cross_order(&self, request: CrossOrderRequest) -> TradeExecutorResult<Uuid> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::CrossOrder {
+            side: request.side(),
+            quantity: request.quantity(),
+            client_id: request.client_id().cloned(),
+        });
+
+        let result = ledger.cross_order(request).await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn cross_order_close_position(&self) -> TradeExecutorResult<Option<Uuid>> {
+        let ledger = self.ready_ledger().await?;
+
+        self.send_action_update(LiveTradeExecutorAction::CrossOrderClosePosition);
+
+        let result = ledger.cross_order_close_position().await;
+        self.after_action(&ledger, result).await
+    }
+
+    async fn trading_state(&self) -> TradeExecutorResult<TradingState> {
+        let ledger = self.ready_ledger().await?;
+
+        ledger.trading_state().await
+    }
+}
+
+/// Tracks the progress of the simulated ledger along the live price feed.
+struct PaperLedgerCursor {
+    last_tick_time: DateTime<Utc>,
+    settled_until: DateTime<Utc>,
+}
+
+/// Launcher for initializing and starting a paper trade executor. Validates that the sync engine
+/// has an active live feed before launching the executor process.
+pub(in crate::trade) struct PaperTradeExecutorLauncher {
+    config: SimulatedTradeExecutorConfig,
+    trading_state_refresh_interval: time::Duration,
+    start_balance: u64,
+    db: Arc<Database>,
+    update_tx: LiveTradeExecutorTransmitter,
+    state_manager: Arc<PaperTradeExecutorStateManager>,
+    sync_reader: Arc<dyn SyncReader>,
+}
+
+impl PaperTradeExecutorLauncher {
+    pub fn new(
+        config: &LiveTradeConfig,
+        db: Arc<Database>,
+        start_balance: u64,
+        sync_reader: Arc<dyn SyncReader>,
+    ) -> LiveTradeExecutorResult<Self> {
+        let sync_mode = sync_reader.mode();
+        if !sync_mode.live_feed_active() {
+            return Err(LiveTradeExecutorError::SyncEngineLiveFeedInactive(
+                sync_mode,
+            ));
+        }
+
+        let (update_tx, _) = broadcast::channel::<LiveTradeExecutorUpdate>(1_000);
+
+        let ledger_store = config.paper_ledger_key().map(|key| PaperLedgerStore {
+            db: db.clone(),
+            key: key.to_string(),
+        });
+        let state_manager = PaperTradeExecutorStateManager::new(update_tx.clone(), ledger_store);
+
+        Ok(Self {
+            config: config.into(),
+            trading_state_refresh_interval: config.trading_session_refresh_interval(),
+            start_balance,
+            db,
+            update_tx,
+            state_manager,
+            sync_reader,
+        })
+    }
+
+    pub fn update_receiver(&self) -> LiveTradeExecutorReceiver {
+        self.update_tx.subscribe()
+    }
+
+    /// Opens the simulated ledger at the latest price tick, or catches an existing ledger up to it.
+    /// Ticks missed by an existing ledger are replayed from the db as a single price range.
+    async fn sync_ledger(
+        config: &SimulatedTradeExecutorConfig,
+        start_balance: u64,
+        db: &Database,
+        state_manager: &PaperTradeExecutorStateManager,
+        cursor: &mut Option<PaperLedgerCursor>,
+    ) -> result::Result<Arc<SimulatedTradeExecutor>, ExecutorProcessRecoverableError> {
+        let (tick_time, tick_price) = db
+            .price_ticks
+            .get_latest_entry()
+            .await
+            .map_err(ExecutorActionError::Db)
+            .and_then(|entry| entry.ok_or(ExecutorActionError::DbIsEmpty))
+            .map_err(ExecutorProcessRecoverableError::PaperLedgerInitialization)?;
+
+        match (state_manager.ledger().await, cursor.as_mut()) {
+            (Some(ledger), Some(cursor)) => {
+                Self::replay_ledger(db, &ledger, cursor).await?;
+                Self::tick_ledger(&ledger, cursor, tick_time, tick_price).await?;
+                Self::settle_ledger(db, &ledger, cursor).await?;
+                Ok(ledger)
+            }
+            _ => {
+                let ledger = SimulatedTradeExecutor::with_market_price(
+                    config.clone(),
+                    tick_time,
+                    tick_price,
+                    start_balance,
+                );
+
+                *cursor = Some(PaperLedgerCursor {
+                    last_tick_time: tick_time,
+                    settled_until: tick_time,
+                });
+
+                Ok(ledger)
+            }
+        }
+    }
+
+    /// Applies the price range of the ticks recorded since the last applied tick, so that
+    /// stoplosses, takeprofits and liquidations reached by skipped ticks are triggered.
+    async fn replay_ledger(
+        db: &Database,
+        ledger: &SimulatedTradeExecutor,
+        cursor: &mut PaperLedgerCursor,
+    ) -> result::Result<(), ExecutorProcessRecoverableError> {
+        let range = db
+            .price_ticks
+            .get_price_range_from(cursor.last_tick_time)
+            .await
+            .map_err(|e| ExecutorProcessRecoverableError::PaperLedgerUpdate(e.into()))?;
+
+        let Some((range_min, range_max, latest_time, latest_price)) = range else {
+            return Ok(());
+        };
+
+        if latest_time <= cursor.last_tick_time {
+            return Ok(());
+        }
+
+        ledger
+            .tick_range_update(latest_time, range_min, range_max, latest_price)
+            .await
+            .map_err(ExecutorProcessRecoverableError::PaperLedgerUpdate)?;
+
+        cursor.last_tick_time = latest_time;
+
+        Ok(())
+    }
+
+    async fn tick_ledger(
+        ledger: &SimulatedTradeExecutor,
+        cursor: &mut PaperLedgerCursor,
+        tick_time: DateTime<Utc>,
+        tick_price: f64,
+    ) -> result::Result<(), ExecutorProcessRecoverableError> {
+        // Ticks may be redelivered after a re-sync
+        if tick_time <= cursor.last_tick_time {
+            return Ok(());
+        }
+
+        ledger
+            .tick_update(tick_time, tick_price)
+            .await
+            .map_err(ExecutorProcessRecoverableError::PaperLedgerUpdate)?;
+
+        cursor.last_tick_time = tick_time;
+
+        Ok(())
+    }
+
+    async fn settle_ledger(
+        db: &Database,
+        ledger: &SimulatedTradeExecutor,
+        cursor: &mut PaperLedgerCursor,
+    ) -> result::Result<(), ExecutorProcessRecoverableError> {
+        let settlement_from =
+            (cursor.settled_until + Duration::seconds(1)).ceil_funding_settlement_time();
+        let settlement_to = cursor.last_tick_time.floor_funding_settlement_time();
+
+        if settlement_from > settlement_to {
+            return Ok(());
+        }
+
+        let settlements = db
+            .funding_settlements
+            .get_settlements(settlement_from, settlement_to)
+            .await
+            .map_err(|e| ExecutorProcessRecoverableError::PaperLedgerUpdate(e.into()))?;
+
+        for settlement in settlements {
+            ledger
+                .apply_funding_settlement(&settlement)
+                .await
+                .map_err(ExecutorProcessRecoverableError::PaperLedgerUpdate)?;
+
+            cursor.settled_until = settlement.time;
+        }
+
+        Ok(())
+    }
+
+    fn spawn_sync_processor(
+        config: SimulatedTradeExecutorConfig,
+        trading_state_refresh_interval: time::Duration,
+        start_balance: u64,
+        db: Arc<Database>,
+        state_manager: Arc<PaperTradeExecutorStateManager>,
+        sync_reader: Arc<dyn SyncReader>,
+        restored_cursor: Option<PaperLedgerCursor>,
+    ) -> AbortOnDropHandle<()> {
+        tokio::spawn(async move {
+            let mut sync_rx = sync_reader.update_receiver();
+            let mut cursor = restored_cursor;
+
+            let mut handler = async || -> ExecutorProcessFatalResult<Never> {
+                let mut is_synced = false;
+                let mut should_resync = matches!(sync_reader.status_snapshot(), SyncStatus::Synced);
+                let mut should_send_state = false;
+                let new_refresh_timer = || Box::pin(time::sleep(trading_state_refresh_interval));
+                let mut refresh_timer = new_refresh_timer();
+
+                loop {
+                    if should_resync {
+                        should_resync = false;
+
+                        match Self::sync_ledger(
+                            &config,
+                            start_balance,
+                            db.as_ref(),
+                            &state_manager,
+                            &mut cursor,
+                        )
+                        .await
+                        {
+                            Ok(ledger) => {
+                                is_synced = true;
+                                should_send_state = true;
+
+                                let settled_until = cursor
+                                    .as_ref()
+                                    .expect("cursor is set once the ledger is synced")
+                                    .settled_until;
+                                state_manager.update_status_ready(ledger, settled_until).await;
+                                state_manager.save_ledger().await;
+                            }
+                            Err(e) => {
+                                state_manager
+                                    .update_status_not_ready(
+                                        LiveTradeExecutorStatusNotReady::Failed(Arc::new(e)),
+                                    )
+                                    .await;
+                            }
+                        }
+                    }
+
+                    tokio::select! {
+                        recv_result = sync_rx.recv() => {
+                            match recv_result {
+                                Ok(sync_update) => match sync_update {
+                                    SyncUpdate::Status(sync_status) => match sync_status {
+                                        SyncStatus::NotSynced(sync_status_not_synced) => {
+                                            is_synced = false;
+
+                                            let new_status_not_ready =
+                                                LiveTradeExecutorStatusNotReady::WaitingForSync(
+                                                    sync_status_not_synced,
+                                                );
+                                            state_manager
+                                                .update_status_not_ready(new_status_not_ready)
+                                                .await;
+                                        }
+                                        SyncStatus::Terminated(err) => {
+                                            return Err(
+                                                ExecutorProcessFatalError::SyncProcessTerminated(
+                                                    err,
+                                                ),
+                                            );
+                                        }
+                                        SyncStatus::ShutdownInitiated | SyncStatus::Shutdown => {
+                                            return Err(
+                                                ExecutorProcessFatalError::SyncProcessShutdown,
+                                            );
+                                        }
+                                        SyncStatus::Backfilled => {}
+                                        SyncStatus::Synced => {
+                                            should_resync = !is_synced;
+                                        }
+                                    },
+                                    SyncUpdate::PriceTick(tick) => {
+                                        let (Some(ledger), Some(cursor)) =
+                                            (state_manager.ledger().await, cursor.as_mut())
+                                        else {
+                                            continue;
+                                        };
+
+                                        if !is_synced {
+                                            continue;
+                                        }
+
+                                        let last_trade_time = ledger.last_trade_time().await;
+
+                                        if let Err(e) = Self::tick_ledger(
+                                            &ledger,
+                                            cursor,
+                                            tick.time,
+                                            tick.last_price,
+                                        )
+                                        .await
+                                        {
+                                            state_manager
+                                                .update_status_not_ready(
+                                                    LiveTradeExecutorStatusNotReady::Failed(
+                                                        Arc::new(e),
+                                                    ),
+                                                )
+                                                .await;
+                                            is_synced = false;
+                                            should_resync = true;
+                                            continue;
+                                        }
+
+                                        // The tick closed some trades
+                                        if ledger.last_trade_time().await != last_trade_time {
+                                            state_manager.save_ledger().await;
+                                        }
+
+                                        should_send_state = true;
+                                    }
+                                    SyncUpdate::FundingSettlementsState(_) => {
+                                        let (Some(ledger), Some(cursor)) =
+                                            (state_manager.ledger().await, cursor.as_mut())
+                                        else {
+                                            continue;
+                                        };
+
+                                        let settled_until = cursor.settled_until;
+
+                                        if let Err(e) =
+                                            Self::settle_ledger(db.as_ref(), &ledger, cursor).await
+                                        {
+                                            state_manager
+                                                .update_status_not_ready(
+                                                    LiveTradeExecutorStatusNotReady::Failed(
+                                                        Arc::new(e),
+                                                    ),
+                                                )
+                                                .await;
+                                            is_synced = false;
+                                            should_resync = true;
+                                            continue;
+                                        }
+
+                                        if cursor.settled_until != settled_until {
+                                            state_manager
+                                                .update_settled_until(cursor.settled_until)
+                                                .await;
+                                            state_manager.save_ledger().await;
+                                        }
+
+                                        should_send_state = true;
+                                    }
+                                    SyncUpdate::PriceHistoryState(_) => {}
+                                },
+                                Err(RecvError::Lagged(skipped)) => {
+                                    state_manager
+                                        .update_status_not_ready(LiveTradeExecutorStatusNotReady::Failed(
+                                            Arc::new(ExecutorProcessRecoverableError::SyncRecvLagged {
+                                                skipped,
+                                            }),
+                                        ))
+                                        .await;
+
+                                    // Drain all remaining messages to catch up to current state
+                                    loop {
+                                        match sync_rx.try_recv() {
+                                            Ok(_) | Err(TryRecvError::Lagged(_)) => continue,
+                                            Err(TryRecvError::Empty) => break,
+                                            Err(TryRecvError::Closed) => {
+                                                return Err(ExecutorProcessFatalError::SyncRecvClosed);
+                                            }
+                                        }
+                                    }
+
+                                    // Skipped ticks are replayed from the db on resync
+                                    is_synced = false;
+                                    should_resync =
+                                        matches!(sync_reader.status_snapshot(), SyncStatus::Synced);
+                                }
+                                Err(RecvError::Closed) => {
+                                    return Err(ExecutorProcessFatalError::SyncRecvClosed);
+                                }
+                            }
+                        }
+                        _ = &mut refresh_timer => {
+                            if should_send_state
+                                && let Some(ledger) = state_manager.ledger().await
+                            {
+                                should_send_state = false;
+                                // Trading state evaluation of the simulated ledger is infallible in
+                                // practice, errors are surfaced on the next operator action
+                                let _ = state_manager.send_trading_state(&ledger).await;
+                            }
+                            refresh_timer = new_refresh_timer();
+                        }
+                    }
+                }
+            };
+
+            let Err(e) = handler().await;
+
+            let new_status_not_ready = LiveTradeExecutorStatusNotReady::Terminated(Arc::new(e));
+            state_manager
+                .update_status_not_ready(new_status_not_ready)
+                .await;
+        })
+        .into()
+    }
+
+    /// Launches the paper trade executor. The persisted ledger is restored, if any. Otherwise, the
+    /// simulated ledger is opened at the latest price tick once the sync engine reaches a synced
+    /// state.
+    pub async fn launch(self) -> LiveTradeExecutorResult<Arc<PaperTradeExecutor>> {
+        let restored_cursor = self.state_manager.restore_ledger(&self.config).await?;
+
+        let handle = Self::spawn_sync_processor(
+            self.config.clone(),
+            self.trading_state_refresh_interval,
+            self.start_balance,
+            self.db,
+            self.state_manager.clone(),
+            self.sync_reader,
+            restored_cursor,
+        );
+
+        Ok(Arc::new(PaperTradeExecutor {
+            config: self.config,
+            update_tx: self.update_tx,
+            state_manager: self.state_manager,
+            handle: Mutex::new(Some(handle)),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lnm_sdk::rest::v3::models::{Leverage, OrderQuantity, PercentageCapped};
+
+    use super::*;
+
+    async fn ready_executor() -> (
+        PaperTradeExecutor,
+        Arc<SimulatedTradeExecutor>,
+        LiveTradeExecutorReceiver,
+    ) {
+        let config: SimulatedTradeExecutorConfig = (&LiveTradeConfig::default()).into();
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let ledger =
+            SimulatedTradeExecutor::with_market_price(config.clone(), time, 100_000., 10_000_000);
+
+        let (update_tx, _) = broadcast::channel::<LiveTradeExecutorUpdate>(1_000);
+        let state_manager = PaperTradeExecutorStateManager::new(update_tx.clone(), None);
+        state_manager
+            .update_status_ready(ledger.clone(), time)
+            .await;
+
+        let update_rx = update_tx.subscribe();
+        let executor = PaperTradeExecutor {
+            config,
+            update_tx,
+            state_manager,
+            handle: Mutex::new(None),
+        };
+
+        (executor, ledger, update_rx)
+    }
+
+    fn market_request(side: TradeSide, leverage: f64) -> IsolatedOrderRequest {
+        IsolatedOrderRequest::market(
+            side,
+            OrderQuantity::try_from(1_000).unwrap().into(),
+            Leverage::try_from(leverage).unwrap(),
+        )
+    }
+
+    fn drain_updates(update_rx: &mut LiveTradeExecutorReceiver) -> Vec<LiveTradeExecutorUpdate> {
+        let mut updates = Vec::new();
+        while let Ok(update) = update_rx.try_recv() {
+            updates.push(update);
+        }
+        updates
+    }
+
+    fn closed_trade_ids(updates: &[LiveTradeExecutorUpdate]) -> HashSet<Uuid> {
+        updates
+            .iter()
+            .filter_map(|update| match update {
+                LiveTradeExecutorUpdate::ClosedTrade(trade) => Some(trade.id()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_paper_executor_close_longs_emits_actions_and_closed_trades() {
+        let (executor, _, mut update_rx) = ready_executor().await;
+
+        let long_a = executor
+            .isolated_order(market_request(TradeSide::Buy, 2.))
+            .await
+            .unwrap();
+        let long_b = executor
+            .isolated_order(market_request(TradeSide::Buy, 2.))
+            .await
+            .unwrap();
+        executor
+            .isolated_order(market_request(TradeSide::Sell, 2.))
+            .await
+            .unwrap();
+        drain_updates(&mut update_rx);
+
+        let closed_ids = executor.isolated_order_close_longs().await.unwrap();
+        let expected_ids = HashSet::from([long_a, long_b]);
+        assert_eq!(closed_ids.into_iter().collect::<HashSet<_>>(), expected_ids);
+
+        let updates = drain_updates(&mut update_rx);
+
+        let close_action_ids: HashSet<Uuid> = updates
+            .iter()
+            .filter_map(|update| match update {
+                LiveTradeExecutorUpdate::Action(LiveTradeExecutorAction::IsolatedOrderClose {
+                    id,
+                }) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(close_action_ids, expected_ids);
+        assert_eq!(closed_trade_ids(&updates), expected_ids);
+        assert!(matches!(
+            updates.last(),
+            Some(LiveTradeExecutorUpdate::TradingState(state)) if state.running_map().len() == 1
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_paper_executor_reports_ledger_closures_once() {
+        let (executor, ledger, mut update_rx) = ready_executor().await;
+
+        // Liquidation of a 50x long is about 2% below the entry price
+        let trade_id = executor
+            .isolated_order(market_request(TradeSide::Buy, 50.))
+            .await
+            .unwrap();
+        drain_updates(&mut update_rx);
+
+        // Skipped ticks reached the liquidation, even though the latest price recovered
+        let time = DateTime::from_timestamp(1_700_000_100, 0).unwrap();
+        ledger
+            .tick_range_update(time, 95_000., 100_000., 99_500.)
+            .await
+            .unwrap();
+
+        executor
+            .state_manager
+            .send_trading_state(&ledger)
+            .await
+            .unwrap();
+        let updates = drain_updates(&mut update_rx);
+        assert_eq!(closed_trade_ids(&updates), HashSet::from([trade_id]));
+
+        executor
+            .state_manager
+            .send_trading_state(&ledger)
+            .await
+            .unwrap();
+        let updates = drain_updates(&mut update_rx);
+        assert!(closed_trade_ids(&updates).is_empty());
+        assert_eq!(updates.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_paper_executor_applies_dead_man_switch_while_not_ready() {
+        let (executor, ledger, mut update_rx) = ready_executor().await;
+
+        let long = executor
+            .isolated_order(market_request(TradeSide::Buy, 2.))
+            .await
+            .unwrap();
+        let short = executor
+            .isolated_order(market_request(TradeSide::Sell, 2.))
+            .await
+            .unwrap();
+        executor
+            .state_manager
+            .update_status_not_ready(LiveTradeExecutorStatusNotReady::Starting)
+            .await;
+        drain_updates(&mut update_rx);
+
+        let distance = PercentageCapped::bounded(1.);
+        executor
+            .apply_dead_man_switch(DeadManSwitchAction::TightenStoplosses(distance))
+            .await
+            .unwrap();
+
+        let trading_state = ledger.trading_state().await.unwrap();
+        let (long_trade, _) = trading_state.running_map().get_by_id(long).unwrap();
+        assert_eq!(long_trade.stoploss().unwrap().as_f64(), 99_000.);
+        let (short_trade, _) = trading_state.running_map().get_by_id(short).unwrap();
+        assert_eq!(short_trade.stoploss().unwrap().as_f64(), 101_000.);
+
+        let updates = drain_updates(&mut update_rx);
+        let updated_ids: HashSet<Uuid> = updates
+            .iter()
+            .filter_map(|update| match update {
+                LiveTradeExecutorUpdate::Action(
+                    LiveTradeExecutorAction::IsolatedTradeUpdateStoploss { id, .. },
+                ) => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(updated_ids, HashSet::from([long, short]));
+
+        executor
+            .apply_dead_man_switch(DeadManSwitchAction::Flatten)
+            .await
+            .unwrap();
+
+        let updates = drain_updates(&mut update_rx);
+        assert!(updates.iter().any(|update| matches!(
+            update,
+            LiveTradeExecutorUpdate::Action(LiveTradeExecutorAction::IsolatedOrderCloseAll)
+        )));
+        assert_eq!(closed_trade_ids(&updates), HashSet::from([long, short]));
+        assert!(
+            ledger
+                .trading_state()
+                .await
+                .unwrap()
+                .running_map()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,667 @@
+use std::{
+    collections::{HashMap, HashSet},
+    num::NonZeroU64,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{Value, json};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{
+    CrossExposure, CrossLeverage, CrossQuantity, Price, TradeSide, trade_util,
+};
+
+use super::{
+    super::super::{
+        backtest::executor::models::{SimulatedCrossPosition, SimulatedTradeRunning},
+        core::{
+            CrossOrderRequest, CrossPositionCore, DynRunningTradesMap, IsolatedOrderRequest,
+            RunningTradesMap, TradeCore, TradeExecutor, TradeRunning, TradeTrailingStoploss,
+            TradingState,
+        },
+        error::TradeExecutorResult,
+    },
+    LiveTradeExecutor,
+    error::{ExecutorActionError, ExecutorActionResult, LiveTradeExecutorResult},
+    state::{LiveTradeExecutorStatus, live_trading_session::LiveTradingSession},
+    update::LiveTradeExecutorAction,
+};
+
+use super::super::dead_man_switch::DeadManSwitchAction;
+
+/// Shadow trade executor implementing the [`TradeExecutor`] trait on top of a
+/// [`LiveTradeExecutor`] that tracks a real account without ever sending orders.
+///
+/// Read operations reflect the real account state, overlaid with the hypothetical positions
+/// described below. Mutating operations are validated against that state, emitted as
+/// [`LiveTradeExecutorAction`]s, and answered with hypothetical results: new orders get freshly
+/// generated ids, closing operations return the ids of the matching running trades, and
+/// cross-margin operations return the hypothetical cross position. The account state is not
+/// modified. Intercepted actions are recorded in the executor action audit log of the account,
+/// marked as shadow, while closed trades are left to be persisted by the account's own executor.
+///
+/// Hypothetical trades opened by intercepted isolated orders are tracked, so follow-up operations
+/// on their ids are accepted, and they are included in the results of closing operations. The
+/// quantity, margin and leverage changes of intercepted cross-margin operations are tracked as
+/// well. The trading state overlays these hypothetical positions on the real account state: opened
+/// trades are added to the running trades (with their margin deducted from the balance), running
+/// trades closed by intercepted actions are removed (with their margin and estimated P/L credited
+/// to the balance), and the cross position reflects the tracked changes. Hypothetical trades are
+/// not evaluated against the market (their stoplosses, takeprofits and liquidations are never
+/// triggered), and margin or cash in changes on them are not tracked.
+pub(in crate::trade) struct ShadowTradeExecutor {
+    inner: Arc<LiveTradeExecutor>,
+    hypothetical: Mutex<HypotheticalPositions>,
+}
+
+/// Positions as they would be if the intercepted actions had been executed.
+#[derive(Default)]
+struct HypotheticalPositions {
+    /// Isolated trades opened by intercepted orders and not closed since.
+    opened: HashMap<Uuid, (Arc<dyn TradeRunning>, Option<TradeTrailingStoploss>)>,
+    /// Running trades of the real account closed by intercepted actions.
+    closed: HashSet<Uuid>,
+    /// Quantity added to the real cross position by intercepted actions.
+    cross_quantity_delta: i64,
+    /// Margin deposited to the real cross account by intercepted actions, net of withdrawals.
+    cross_margin_delta: i64,
+    /// Cross leverage set by intercepted actions, if any.
+    cross_leverage: Option<CrossLeverage>,
+}
+
+/// Returns the balance locked by a running trade.
+fn locked_balance(trade: &dyn TradeRunning) -> u64 {
+    trade.margin().as_u64() + trade.maintenance_margin().max(0) as u64
+}
+
+impl HypotheticalPositions {
+    /// Forgets the closed trades that are no longer running in the real account.
+    fn retain_running(&mut self, running_map: &DynRunningTradesMap) {
+        self.closed.retain(|id| running_map.contains(id));
+    }
+
+    fn running_len(&self, running_map: &DynRunningTradesMap) -> usize {
+        running_map.len() - self.closed.len() + self.opened.len()
+    }
+
+    /// Returns the real account balance adjusted by the hypothetically opened and closed trades.
+    fn balance(&self, balance: u64, running_map: &DynRunningTradesMap, market_price: Price) -> u64 {
+        let released = self
+            .closed
+            .iter()
+            .filter_map(|id| running_map.get_by_id(*id))
+            .fold(0_i64, |released, (trade, _)| {
+                let pl = trade.est_pl(market_price).floor() as i64;
+                released + locked_balance(trade.as_ref()) as i64 + pl
+            });
+        let locked = self
+            .opened
+            .values()
+            .map(|(trade, _)| locked_balance(trade.as_ref()))
+            .sum::<u64>();
+
+        balance
+            .saturating_add_signed(released)
+            .saturating_add_signed(-self.cross_margin_delta)
+            .saturating_sub(locked)
+    }
+
+    /// Returns the running trades of the real account, without the hypothetically closed trades
+    /// and with the hypothetically opened ones.
+    fn running_map(&self, running_map: &DynRunningTradesMap) -> DynRunningTradesMap {
+        let mut overlay = RunningTradesMap::new();
+
+        let real_trades = running_map
+            .values()
+            .filter(|(trade, _)| !self.closed.contains(&trade.id()));
+        for (trade, trade_tsl) in real_trades.chain(self.opened.values()) {
+            overlay.add(trade.clone(), *trade_tsl);
+        }
+
+        overlay
+    }
+
+    /// Returns the real cross position with the tracked changes applied, with quantity changes
+    /// filled at `market_price`, or `None` if no changes are tracked.
+    fn cross_position(
+        &self,
+        cross_position: &dyn CrossPositionCore,
+        market_price: Price,
+    ) -> ExecutorActionResult<Option<Arc<dyn CrossPositionCore>>> {
+        if self.cross_quantity_delta == 0
+            && self.cross_margin_delta == 0
+            && self.cross_leverage.is_none()
+        {
+            return Ok(None);
+        }
+
+        let quantity = cross_position.quantity() + self.cross_quantity_delta;
+
+        let (margin, exposure_running) = if self.cross_quantity_delta == 0 {
+            (
+                cross_position.margin(),
+                cross_position.exposure().as_running_params(),
+            )
+        } else if quantity == 0 {
+            let realized_pl = match cross_position.exposure() {
+                CrossExposure::Running(exposure) => trade_util::estimate_pl(
+                    exposure.side(),
+                    exposure.quantity(),
+                    exposure.entry_price(),
+                    market_price,
+                )
+                .floor() as i64,
+                CrossExposure::Neutral => 0,
+            };
+
+            (
+                cross_position.margin().saturating_add_signed(realized_pl),
+                None,
+            )
+        } else {
+            let side = if quantity > 0 {
+                TradeSide::Buy
+            } else {
+                TradeSide::Sell
+            };
+            let quantity = CrossQuantity::try_from(quantity.unsigned_abs())
+                .map_err(|_| ExecutorActionError::CrossPositionOverflow)?;
+
+            let (Some(entry_price), Some(margin)) = (
+                cross_position.est_entry_price_for_exposure(side, quantity, market_price),
+                cross_position.est_margin_for_exposure(side, quantity, market_price),
+            ) else {
+                return Err(ExecutorActionError::CrossPositionOverflow);
+            };
+
+            (margin.max(0) as u64, Some((side, quantity, entry_price)))
+        };
+
+        let margin = u64::try_from(margin as i64 + self.cross_margin_delta)
+            .map_err(|_| ExecutorActionError::CrossPositionOverflow)?;
+        let leverage = self.cross_leverage.unwrap_or(cross_position.leverage());
+
+        CrossExposure::new(margin, leverage, exposure_running)
+            .map_err(ExecutorActionError::CrossPositionValidation)?;
+
+        let overlay = SimulatedCrossPosition::new(
+            margin,
+            leverage,
+            exposure_running,
+            cross_position.trading_fees(),
+            cross_position.session_funding_fees(),
+            cross_position.realized_pl(),
+        )
+        .expect("cross exposure was validated");
+
+        Ok(Some(Arc::new(overlay)))
+    }
+}
+
+impl ShadowTradeExecutor {
+    pub fn new(inner: Arc<LiveTradeExecutor>) -> Arc<Self> {
+        Arc::new(Self {
+            inner,
+            hypothetical: Mutex::new(HypotheticalPositions::default()),
+        })
+    }
+
+    fn lock_hypothetical(&self) -> MutexGuard<'_, HypotheticalPositions> {
+        self.hypothetical
+            .lock()
+            .expect("`ShadowTradeExecutor` mutex can't be poisoned")
+    }
+
+    pub async fn status_snapshot(&self) -> LiveTradeExecutorStatus {
+        self.inner.state_snapshot().await.status().clone()
+    }
+
+    pub async fn shutdown(&self) -> LiveTradeExecutorResult<()> {
+        self.inner.shutdown().await
+    }
+
+    async fn record_action(&self, action: LiveTradeExecutorAction, response: Value) {
+        self.inner.api.intercept(action, response).await;
+    }
+
+    async fn intercept_isolated_order(
+        &self,
+        request: IsolatedOrderRequest,
+    ) -> ExecutorActionResult<Uuid> {
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+
+        let (side, size, leverage, stoploss, takeprofit, client_id) =
+            request.into_isolated_order_parts();
+
+        let trading_session = locked_ready_state.trading_session();
+        let (market_price, stoploss_price, trade_tsl) = self
+            .inner
+            .validate_isolated_order(trading_session, side, size, leverage, stoploss, takeprofit)
+            .await?;
+
+        let trade = SimulatedTradeRunning::new(
+            side,
+            size,
+            leverage,
+            Utc::now(),
+            market_price,
+            stoploss_price,
+            takeprofit,
+            self.inner.config.trade_estimated_fee(),
+            client_id.clone(),
+        )
+        .expect("trade params were validated");
+        let trade_id = trade.id();
+
+        {
+            let running_map = trading_session.running_map();
+            let mut hypothetical = self.lock_hypothetical();
+            hypothetical.retain_running(running_map);
+
+            let max_qtd = self.inner.config.trade_max_running_qtd();
+            if hypothetical.running_len(running_map) >= max_qtd {
+                return Err(ExecutorActionError::MaxRunningTradesReached { max_qtd });
+            }
+
+            let balance =
+                hypothetical.balance(trading_session.balance(), running_map, market_price);
+            if locked_balance(trade.as_ref()) > balance {
+                return Err(ExecutorActionError::BalanceTooLow);
+            }
+
+            hypothetical.opened.insert(trade_id, (trade, trade_tsl));
+        }
+
+        let action = LiveTradeExecutorAction::IsolatedOrder {
+            side,
+            size,
+            leverage,
+            stoploss: stoploss_price,
+            takeprofit,
+            client_id,
+        };
+        self.record_action(action, json!({ "id": trade_id })).await;
+
+        Ok(trade_id)
+    }
+
+    async fn intercept_trade_action(
+        &self,
+        trade_id: Uuid,
+        action: LiveTradeExecutorAction,
+    ) -> ExecutorActionResult<()> {
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+        let running_map = locked_ready_state.trading_session().running_map();
+
+        {
+            let mut hypothetical = self.lock_hypothetical();
+            hypothetical.retain_running(running_map);
+
+            let is_running = if hypothetical.opened.contains_key(&trade_id) {
+                true
+            } else {
+                running_map.contains(&trade_id) && !hypothetical.closed.contains(&trade_id)
+            };
+
+            if !is_running {
+                return Err(ExecutorActionError::TradeNotRegistered { trade_id });
+            }
+
+            if matches!(action, LiveTradeExecutorAction::IsolatedOrderClose { .. })
+                && hypothetical.opened.remove(&trade_id).is_none()
+            {
+                hypothetical.closed.insert(trade_id);
+            }
+        }
+
+        self.record_action(action, json!({ "id": trade_id })).await;
+
+        Ok(())
+    }
+
+    async fn intercept_close_trades(
+        &self,
+        side: Option<TradeSide>,
+    ) -> ExecutorActionResult<Vec<Uuid>> {
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+        let running_map = locked_ready_state.trading_session().running_map();
+
+        let to_close = {
+            let mut hypothetical = self.lock_hypothetical();
+            hypothetical.retain_running(running_map);
+
+            let mut to_close = running_map
+                .trades_desc()
+                .filter(|(trade, _)| side.is_none_or(|side| trade.side() == side))
+                .map(|(trade, _)| trade.id())
+                .filter(|id| !hypothetical.closed.contains(id))
+                .collect::<Vec<_>>();
+            hypothetical.closed.extend(to_close.iter().copied());
+
+            let opened_to_close = hypothetical
+                .opened
+                .iter()
+                .filter(|(_, (trade, _))| side.is_none_or(|side| trade.side() == side))
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+            for id in &opened_to_close {
+                hypothetical.opened.remove(id);
+            }
+            to_close.extend(opened_to_close);
+
+            to_close
+        };
+
+        match side {
+            Some(_) => {
+                for &id in &to_close {
+                    let action = LiveTradeExecutorAction::IsolatedOrderClose { id };
+                    self.record_action(action, json!({ "id": id })).await;
+                }
+            }
+            None => {
+                let closed = to_close
+                    .iter()
+                    .map(|id| json!({ "id": id }))
+                    .collect::<Vec<_>>();
+
+                self.record_action(LiveTradeExecutorAction::IsolatedOrderCancelAll, json!([]))
+                    .await;
+                self.record_action(
+                    LiveTradeExecutorAction::IsolatedOrderCloseAll,
+                    json!(closed),
+                )
+                .await;
+            }
+        }
+
+        Ok(to_close)
+    }
+
+    /// Applies `update` to the hypothetical positions and returns the resulting cross position.
+    /// The update is reverted if the resulting cross position is invalid.
+    fn update_cross_position(
+        &self,
+        trading_session: &LiveTradingSession,
+        update: impl FnOnce(&mut HypotheticalPositions),
+    ) -> ExecutorActionResult<Arc<dyn CrossPositionCore>> {
+        let real_cross_position = trading_session.cross_position();
+
+        let mut hypothetical = self.lock_hypothetical();
+        let previous = (
+            hypothetical.cross_quantity_delta,
+            hypothetical.cross_margin_delta,
+            hypothetical.cross_leverage,
+        );
+
+        update(&mut hypothetical);
+
+        match hypothetical
+            .cross_position(real_cross_position.as_ref(), trading_session.market_price())
+        {
+            Ok(cross_position) => Ok(cross_position.unwrap_or(real_cross_position)),
+            Err(e) => {
+                (
+                    hypothetical.cross_quantity_delta,
+                    hypothetical.cross_margin_delta,
+                    hypothetical.cross_leverage,
+                ) = previous;
+                Err(e)
+            }
+        }
+    }
+
+    async fn intercept_cross_action(
+        &self,
+        action: LiveTradeExecutorAction,
+    ) -> ExecutorActionResult<Arc<dyn CrossPositionCore>> {
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+
+        let cross_position =
+            self.update_cross_position(locked_ready_state.trading_session(), |hypothetical| {
+                match &action {
+                    LiveTradeExecutorAction::CrossDeposit { amount } => {
+                        hypothetical.cross_margin_delta += amount.get() as i64;
+                    }
+                    LiveTradeExecutorAction::CrossWithdraw { amount } => {
+                        hypothetical.cross_margin_delta -= amount.get() as i64;
+                    }
+                    LiveTradeExecutorAction::CrossSetLeverage { leverage } => {
+                        hypothetical.cross_leverage = Some(*leverage);
+                    }
+                    _ => {}
+                }
+            })?;
+
+        let response = json!({
+            "quantity": cross_position.quantity(),
+            "margin": cross_position.margin(),
+            "leverage": cross_position.leverage().as_u64(),
+        });
+        self.record_action(action, response).await;
+
+        Ok(cross_position)
+    }
+
+    /// Records the requests the dead man's switch action would send, without sending them. The
+    /// trading session may be outdated while the live process is unhealthy, so the running trades
+    /// and cross position are fetched from the exchange. Positions closed by the action are
+    /// tracked as hypothetical positions, while tightened stoplosses are not tracked.
+    pub async fn apply_dead_man_switch(
+        &self,
+        action: DeadManSwitchAction,
+    ) -> ExecutorActionResult<()> {
+        let api = &self.inner.api;
+        let (running_trades, cross_position) =
+            futures::try_join!(api.get_trades_running(), api.cross_get_position())?;
+
+        match action {
+            DeadManSwitchAction::Flatten => {
+                let closed = {
+                    let mut hypothetical = self.lock_hypothetical();
+
+                    let mut closed = running_trades
+                        .iter()
+                        .map(|trade| trade.id())
+                        .filter(|id| !hypothetical.closed.contains(id))
+                        .collect::<Vec<_>>();
+                    hypothetical.closed.extend(closed.iter().copied());
+                    closed.extend(hypothetical.opened.drain().map(|(id, _)| id));
+
+                    closed
+                        .into_iter()
+                        .map(|id| json!({ "id": id }))
+                        .collect::<Vec<_>>()
+                };
+
+                self.record_action(LiveTradeExecutorAction::IsolatedOrderCancelAll, json!([]))
+                    .await;
+                self.record_action(
+                    LiveTradeExecutorAction::IsolatedOrderCloseAll,
+                    json!(closed),
+                )
+                .await;
+            }
+            DeadManSwitchAction::TightenStoplosses(distance) => {
+                let to_update = self
+                    .inner
+                    .dead_man_switch_stoploss_updates(&running_trades, distance)
+                    .await?;
+
+                for (id, stoploss) in to_update {
+                    let action =
+                        LiveTradeExecutorAction::IsolatedTradeUpdateStoploss { id, stoploss };
+                    self.record_action(action, json!({ "id": id })).await;
+                }
+            }
+        }
+
+        // Cross-margin positions don't support stoplosses, so they are closed by both actions
+        self.record_action(LiveTradeExecutorAction::CrossOrderCancelAll, json!([]))
+            .await;
+
+        let real_quantity = cross_position.quantity();
+        let close_cross = {
+            let mut hypothetical = self.lock_hypothetical();
+            let close_cross = real_quantity + hypothetical.cross_quantity_delta != 0;
+            hypothetical.cross_quantity_delta = -real_quantity;
+            close_cross
+        };
+
+        if close_cross {
+            self.record_action(
+                LiveTradeExecutorAction::CrossOrderClosePosition,
+                json!({ "id": Uuid::new_v4() }),
+            )
+            .await;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for ShadowTradeExecutor {
+    async fn isolated_order(&self, request: IsolatedOrderRequest) -> TradeExecutorResult<Uuid> {
+        Ok(self.intercept_isolated_order(request).await?)
+    }
+
+    async fn isolated_trade_add_margin(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let action = LiveTradeExecutorAction::IsolatedTradeAddMargin {
+            id: trade_id,
+            amount,
+        };
+
+        Ok(self.intercept_trade_action(trade_id, action).await?)
+    }
+
+    async fn isolated_trade_cash_in(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let action = LiveTradeExecutorAction::IsolatedTradeCashIn {
+            id: trade_id,
+            amount,
+        };
+
+        Ok(self.intercept_trade_action(trade_id, action).await?)
+    }
+
+    async fn isolated_order_close(&self, trade_id: Uuid) -> TradeExecutorResult<()> {
+        let action = LiveTradeExecutorAction::IsolatedOrderClose { id: trade_id };
+
+        Ok(self.intercept_trade_action(trade_id, action).await?)
+    }
+
+    async fn isolated_order_close_longs(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        Ok(self.intercept_close_trades(Some(TradeSide::Buy)).await?)
+    }
+
+    async fn isolated_order_close_shorts(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        Ok(self.intercept_close_trades(Some(TradeSide::Sell)).await?)
+    }
+
+    async fn isolated_order_close_all(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        Ok(self.intercept_close_trades(None).await?)
+    }
+
+    async fn cross_deposit(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let action = LiveTradeExecutorAction::CrossDeposit { amount };
+
+        Ok(self.intercept_cross_action(action).await?)
+    }
+
+    async fn cross_withdraw(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let action = LiveTradeExecutorAction::CrossWithdraw { amount };
+
+        Ok(self.intercept_cross_action(action).await?)
+    }
+
+    async fn cross_set_leverage(
+        &self,
+        leverage: CrossLeverage,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let action = LiveTradeExecutorAction::CrossSetLeverage { leverage };
+
+        Ok(self.intercept_cross_action(action).await?)
+    }
+
+    async fn cross_order(&self, request: CrossOrderRequest) -> TradeExecutorResult<Uuid> {
+        let (side, quantity, client_id) = request.into_cross_order_parts();
+        let action = LiveTradeExecutorAction::CrossOrder {
+            side,
+            quantity,
+            client_id,
+        };
+
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+
+        let quantity = quantity.as_u64() as i64;
+        self.update_cross_position(locked_ready_state.trading_session(), |hypothetical| {
+            hypothetical.cross_quantity_delta += match side {
+                TradeSide::Buy => quantity,
+                TradeSide::Sell => -quantity,
+            };
+        })?;
+
+        let order_id = Uuid::new_v4();
+        self.record_action(action, json!({ "id": order_id })).await;
+
+        Ok(order_id)
+    }
+
+    async fn cross_order_close_position(&self) -> TradeExecutorResult<Option<Uuid>> {
+        let locked_ready_state = self.inner.state_manager.try_lock_ready_state().await?;
+        let trading_session = locked_ready_state.trading_session();
+        let real_quantity = trading_session.cross_position().quantity();
+
+        if real_quantity + self.lock_hypothetical().cross_quantity_delta == 0 {
+            return Ok(None);
+        }
+
+        self.update_cross_position(trading_session, |hypothetical| {
+            hypothetical.cross_quantity_delta = -real_quantity;
+        })?;
+
+        let order_id = Uuid::new_v4();
+        self.record_action(
+            LiveTradeExecutorAction::CrossOrderClosePosition,
+            json!({ "id": order_id }),
+        )
+        .await;
+
+        Ok(Some(order_id))
+    }
+
+    async fn trading_state(&self) -> TradeExecutorResult<TradingState> {
+        let trading_state = self.inner.trading_state().await?;
+        let market_price = trading_state.market_price();
+
+        let (balance, running_map, cross_position) = {
+            let mut hypothetical = self.lock_hypothetical();
+            let real_running_map = trading_state.running_map();
+            hypothetical.retain_running(real_running_map);
+
+            (
+                hypothetical.balance(trading_state.balance(), real_running_map, market_price),
+                hypothetical.running_map(real_running_map),
+                hypothetical.cross_position(trading_state.cross_position(), market_price)?,
+            )
+        };
+
+        Ok(trading_state.with_positions(balance, running_map, cross_position))
+    }
+}
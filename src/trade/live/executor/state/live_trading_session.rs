@@ -12,21 +12,24 @@ use uuid::Uuid;
 use lnm_sdk::rest::v3::{
     error::CrossExposureValidationError,
     models::{
-        CrossExposure, CrossLeverage, CrossOrder, CrossPosition, PercentageCapped, Price, Trade,
-        TradeSide,
+        ClientId, CrossExposure, CrossLeverage, CrossOrder, CrossPosition, Leverage, Margin,
+        OrderQuantity, PercentageCapped, Price, Trade, TradeSide,
     },
 };
 
-use crate::{db::Database, util::DateTimeExt};
+use crate::{
+    db::{Database, models::ClosedTradeRow},
+    util::DateTimeExt,
+};
 
 use super::super::super::{
     super::core::{
         ClosedTradeHistory, CrossPositionCore, DynRunningTradesMap, PriceTrigger, RunningTradesMap,
-        TradeRunningExt, TradeTrailingStoploss, TradingState,
+        TradeClosed, TradeCore, TradeRunningExt, TradeTrailingStoploss, TradingState,
     },
     executor::{
         WrappedRestClient,
-        error::{ExecutorActionError, ExecutorActionResult},
+        error::{ExecutorActionError, ExecutorActionResult, LiveTradeExecutorWarning},
     },
 };
 
@@ -201,6 +204,174 @@ impl CrossPositionCore for LiveCrossPosition {
     }
 }
 
+/// Closed trade restored from the database.
+///
+/// Trades closed in previous runs are persisted as [`ClosedTradeRow`]s, since the SDK `Trade` can't
+/// be rebuilt from stored values. Fields are validated when the row is converted.
+#[derive(Debug, Clone)]
+pub(in crate::trade) struct LiveTradeClosed {
+    id: Uuid,
+    side: TradeSide,
+    opening_fee: u64,
+    closing_fee: u64,
+    maintenance_margin: i64,
+    quantity: OrderQuantity,
+    margin: Margin,
+    leverage: Leverage,
+    price: Price,
+    liquidation: Price,
+    stoploss: Option<Price>,
+    takeprofit: Option<Price>,
+    exit_price: Price,
+    sum_funding_fees: i64,
+    pl: i64,
+    client_id: Option<ClientId>,
+    created_at: DateTime<Utc>,
+    filled_at: Option<DateTime<Utc>>,
+    closed_at: DateTime<Utc>,
+}
+
+impl TryFrom<ClosedTradeRow> for LiveTradeClosed {
+    type Error = ExecutorActionError;
+
+    fn try_from(row: ClosedTradeRow) -> Result<Self, Self::Error> {
+        let trade_id = row.trade_id;
+        let invalid =
+            |reason: String| ExecutorActionError::InvalidPersistedClosedTrade { trade_id, reason };
+        let price = |field: &str, value: f64| {
+            Price::try_from(value).map_err(|e| invalid(format!("`{field}` ({value}): {e}")))
+        };
+
+        let side = match row.side.as_str() {
+            "Buy" => TradeSide::Buy,
+            "Sell" => TradeSide::Sell,
+            side => return Err(invalid(format!("`side` ({side}) is not `Buy` or `Sell`"))),
+        };
+        let fee = |field: &str, value: i64| {
+            u64::try_from(value).map_err(|e| invalid(format!("`{field}` ({value}): {e}")))
+        };
+        let exit_price = row
+            .exit_price
+            .ok_or_else(|| invalid("`exit_price` is not set".to_string()))?;
+
+        Ok(Self {
+            id: trade_id,
+            side,
+            opening_fee: fee("opening_fee", row.opening_fee)?,
+            closing_fee: fee("closing_fee", row.closing_fee)?,
+            maintenance_margin: row.maintenance_margin,
+            quantity: OrderQuantity::try_from(row.quantity)
+                .map_err(|e| invalid(format!("`quantity` ({}): {e}", row.quantity)))?,
+            margin: Margin::try_from(row.margin)
+                .map_err(|e| invalid(format!("`margin` ({}): {e}", row.margin)))?,
+            leverage: Leverage::try_from(row.leverage)
+                .map_err(|e| invalid(format!("`leverage` ({}): {e}", row.leverage)))?,
+            price: price("price", row.price)?,
+            liquidation: price("liquidation", row.liquidation)?,
+            stoploss: row.stoploss.map(|sl| price("stoploss", sl)).transpose()?,
+            takeprofit: row
+                .takeprofit
+                .map(|tp| price("takeprofit", tp))
+                .transpose()?,
+            exit_price: price("exit_price", exit_price)?,
+            sum_funding_fees: row.sum_funding_fees,
+            pl: row.pl,
+            client_id: row
+                .client_id
+                .map(|id| {
+                    ClientId::try_from(id.as_str())
+                        .map_err(|e| invalid(format!("`client_id` ({id}): {e}")))
+                })
+                .transpose()?,
+            created_at: row.created_at,
+            filled_at: row.filled_at,
+            closed_at: row.closed_at,
+        })
+    }
+}
+
+impl crate::sealed::Sealed for LiveTradeClosed {}
+
+impl TradeCore for LiveTradeClosed {
+    fn id(&self) -> Uuid {
+        self.id
+    }
+
+    fn side(&self) -> TradeSide {
+        self.side
+    }
+
+    fn opening_fee(&self) -> u64 {
+        self.opening_fee
+    }
+
+    fn closing_fee(&self) -> u64 {
+        self.closing_fee
+    }
+
+    fn maintenance_margin(&self) -> i64 {
+        self.maintenance_margin
+    }
+
+    fn quantity(&self) -> OrderQuantity {
+        self.quantity
+    }
+
+    fn margin(&self) -> Margin {
+        self.margin
+    }
+
+    fn leverage(&self) -> Leverage {
+        self.leverage
+    }
+
+    fn price(&self) -> Price {
+        self.price
+    }
+
+    fn liquidation(&self) -> Price {
+        self.liquidation
+    }
+
+    fn stoploss(&self) -> Option<Price> {
+        self.stoploss
+    }
+
+    fn takeprofit(&self) -> Option<Price> {
+        self.takeprofit
+    }
+
+    fn exit_price(&self) -> Option<Price> {
+        Some(self.exit_price)
+    }
+
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    fn filled_at(&self) -> Option<DateTime<Utc>> {
+        self.filled_at
+    }
+
+    fn closed_at(&self) -> Option<DateTime<Utc>> {
+        Some(self.closed_at)
+    }
+
+    fn closed(&self) -> bool {
+        true
+    }
+
+    fn client_id(&self) -> Option<&ClientId> {
+        self.client_id.as_ref()
+    }
+}
+
+impl TradeClosed for LiveTradeClosed {
+    fn pl(&self) -> i64 {
+        self.pl
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(in crate::trade) struct LiveTradingSession {
     expires_at: DateTime<Utc>,
@@ -220,14 +391,25 @@ pub(in crate::trade) struct LiveTradingSession {
 }
 
 impl LiveTradingSession {
+    /// Creates a new trading session from the current API state. When there is no previous session,
+    /// trades closed since `closed_history_from` (if set) are restored from the database.
+    ///
+    /// Trades of the previous session found closed, and trades registered as running in the
+    /// database but found closed on a fresh start, are persisted to the database if
+    /// `persist_closed_trades` is set, and returned along with the new session and the warnings
+    /// raised while restoring it.
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         recover_trades_on_startup: bool,
+        recover_trades_tsl: bool,
+        persist_closed_trades: bool,
+        closed_history_from: Option<DateTime<Utc>>,
         tsl_step_size: PercentageCapped,
         db: &Database,
         api: &WrappedRestClient,
         account_id: Uuid,
         prev_trading_session: Option<Self>,
-    ) -> ExecutorActionResult<Self> {
+    ) -> ExecutorActionResult<(Self, Vec<Trade>, Vec<LiveTradeExecutorWarning>)> {
         let (lastest_entry_time, lastest_entry_price) = db
             .price_ticks
             .get_latest_entry()
@@ -287,12 +469,23 @@ impl LiveTradingSession {
             cross_position,
         };
 
+        let mut warnings = Vec::new();
+
+        if prev_trading_session.is_none()
+            && let Some(from) = closed_history_from
+        {
+            let closed_trade_rows = db.closed_trades.get_closed_trades(account_id, from).await?;
+            warnings = session.restore_closed_trades(closed_trade_rows)?;
+        }
+
         if !recover_trades_on_startup {
-            return Ok(session);
+            return Ok((session, Vec::new(), warnings));
         }
 
         let running_trades = api.get_trades_running().await?;
 
+        let mut missed_closed_trades = Vec::new();
+
         // Reconcile trades that closed between the last `reevaluate()` and the fresh API
         // state fetched above. Once the session expires, `reevaluate()` stops running, so any
         // closure during that window is missed.
@@ -316,46 +509,49 @@ impl LiveTradingSession {
 
                 let mut unconfirmed = missed_ids.clone();
 
-                let closed_history = Arc::make_mut(&mut session.closed_history);
-
-                for closed_trade in &closed_trades {
+                for closed_trade in closed_trades {
                     if !missed_ids.contains(&closed_trade.id()) {
                         continue;
                     }
 
                     unconfirmed.remove(&closed_trade.id());
 
-                    session.realized_pl += closed_trade.pl();
-                    session.closed_fees += closed_trade.opening_fee() + closed_trade.closing_fee();
-
-                    closed_history
-                        .add(Arc::new(closed_trade.clone()))
-                        .map_err(ExecutorActionError::ClosedHistoryUpdate)?;
-
                     let baseline = prev_funding_snapshot
                         .get(&closed_trade.id())
                         .copied()
                         .unwrap_or(0);
-                    session.funding_fees += closed_trade.sum_funding_fees() - baseline;
+                    session.record_found_closed_trade(&closed_trade, baseline)?;
 
-                    let closed_at = closed_trade.closed_at();
-                    if let Some(closed_at) = closed_at
-                        && session.last_trade_time.is_none_or(|last| closed_at > last)
-                    {
-                        session.last_trade_time = Some(closed_at);
-                    }
+                    missed_closed_trades.push(closed_trade);
                 }
 
                 if let Some(&trade_id) = unconfirmed.iter().next() {
                     return Err(ExecutorActionError::ClosedTradeNotConfirmed { trade_id });
                 }
+
+                if persist_closed_trades {
+                    db.closed_trades
+                        .add_closed_trades(account_id, &missed_closed_trades)
+                        .await?;
+                }
             }
         }
 
         // Try to recover trades 'trailing stoploss' config from db
 
-        let mut registered_trades_map =
-            db.running_trades.get_running_trades_map(account_id).await?;
+        let registration_from = if recover_trades_tsl {
+            db.running_trades
+                .get_earliest_registration_time(account_id)
+                .await?
+        } else {
+            None
+        };
+
+        let mut registered_trades_map = if recover_trades_tsl {
+            db.running_trades.get_running_trades_map(account_id).await?
+        } else {
+            HashMap::new()
+        };
 
         for trade in running_trades {
             let trade_tsl = registered_trades_map.remove(&trade.id()).flatten();
@@ -373,17 +569,102 @@ impl LiveTradingSession {
         }
 
         if !registered_trades_map.is_empty() {
-            // Trades still on the map are not running
+            // Trades still on the map are not running. Unless already reconciled above, they
+            // closed while the executor wasn't running (e.g. on a fresh start after downtime), so
+            // their outcome is fetched and persisted before the registrations are removed.
 
             let dangling_registered_trades: Vec<Uuid> =
                 registered_trades_map.keys().cloned().collect();
 
+            let unreconciled: HashSet<Uuid> = dangling_registered_trades
+                .iter()
+                .filter(|id| session.closed_history.get_by_id(**id).is_none())
+                .copied()
+                .collect();
+
+            if let Some(from) = registration_from
+                && !unreconciled.is_empty()
+            {
+                let closed_trades = api.find_trades_closed(from, &unreconciled).await?;
+
+                for closed_trade in &closed_trades {
+                    session.record_found_closed_trade(closed_trade, 0)?;
+                }
+
+                if persist_closed_trades {
+                    db.closed_trades
+                        .add_closed_trades(account_id, &closed_trades)
+                        .await?;
+                }
+
+                missed_closed_trades.extend(closed_trades);
+            }
+
             db.running_trades
                 .remove_running_trades(account_id, dangling_registered_trades.as_slice())
                 .await?;
         }
 
-        Ok(session)
+        Ok((session, missed_closed_trades, warnings))
+    }
+
+    /// Records a trade found closed on the API while rebuilding the session. `funding_baseline` is
+    /// the part of its funding fees already accounted for by a previous session.
+    fn record_found_closed_trade(
+        &mut self,
+        closed_trade: &Trade,
+        funding_baseline: i64,
+    ) -> ExecutorActionResult<()> {
+        self.realized_pl += closed_trade.pl();
+        self.closed_fees += closed_trade.opening_fee() + closed_trade.closing_fee();
+        self.funding_fees += closed_trade.sum_funding_fees() - funding_baseline;
+
+        if let Some(closed_at) = closed_trade.closed_at()
+            && self.last_trade_time.is_none_or(|last| closed_at > last)
+        {
+            self.last_trade_time = Some(closed_at);
+        }
+
+        Arc::make_mut(&mut self.closed_history)
+            .add(Arc::new(closed_trade.clone()))
+            .map_err(ExecutorActionError::ClosedHistoryUpdate)
+    }
+
+    /// Restores the given closed trades into the session. Rows that can't be decoded are skipped,
+    /// and reported as warnings.
+    fn restore_closed_trades(
+        &mut self,
+        rows: Vec<ClosedTradeRow>,
+    ) -> ExecutorActionResult<Vec<LiveTradeExecutorWarning>> {
+        let closed_history = Arc::make_mut(&mut self.closed_history);
+        let mut warnings = Vec::new();
+
+        for row in rows {
+            let closed_trade = match LiveTradeClosed::try_from(row) {
+                Ok(closed_trade) => closed_trade,
+                Err(e) => {
+                    warnings.push(LiveTradeExecutorWarning::ClosedTradeRestoreSkipped(e));
+                    continue;
+                }
+            };
+
+            self.realized_pl += closed_trade.pl();
+            self.closed_fees += closed_trade.opening_fee() + closed_trade.closing_fee();
+            self.funding_fees += closed_trade.sum_funding_fees;
+
+            if self
+                .last_trade_time
+                .is_none_or(|last| closed_trade.closed_at > last)
+            {
+                self.last_trade_time = Some(closed_trade.closed_at);
+            }
+
+            closed_history
+                .add(Arc::new(closed_trade))
+                .map_err(ExecutorActionError::ClosedHistoryUpdate)?;
+        }
+
+        Ok(warnings)
     }
 
     pub fn is_expired(&self) -> bool {
@@ -400,6 +681,10 @@ impl LiveTradingSession {
         self.balance
     }
 
+    pub fn market_price(&self) -> Price {
+        Price::bounded(self.last_price)
+    }
+
     pub fn running_map(&self) -> &DynRunningTradesMap {
         &self.running_map
     }
@@ -787,3 +1072,124 @@ impl From<LiveTradingSession> for TradingState {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn closed_trade_row(side: &str, exit_price: Option<f64>) -> ClosedTradeRow {
+        let created_at = Utc::now() - Duration::hours(2);
+
+        ClosedTradeRow {
+            account_id: Uuid::new_v4(),
+            trade_id: Uuid::new_v4(),
+            side: side.to_string(),
+            opening_fee: 10,
+            closing_fee: 12,
+            maintenance_margin: 0,
+            quantity: 100,
+            margin: 10_000,
+            leverage: 10.0,
+            price: 100_000.0,
+            liquidation: 91_000.0,
+            stoploss: Some(95_000.0),
+            takeprofit: None,
+            exit_price,
+            sum_funding_fees: 3,
+            pl: 500,
+            client_id: None,
+            created_at,
+            filled_at: Some(created_at),
+            closed_at: created_at + Duration::hours(1),
+            recorded_at: created_at + Duration::hours(1),
+        }
+    }
+
+    #[test]
+    fn test_live_trade_closed_from_row() {
+        let row = closed_trade_row("Sell", Some(99_500.0));
+        let trade = LiveTradeClosed::try_from(row.clone()).expect("valid row");
+
+        assert_eq!(trade.id(), row.trade_id);
+        assert_eq!(trade.side(), TradeSide::Sell);
+        assert_eq!(trade.quantity().as_i64(), 100);
+        assert_eq!(trade.exit_price().map(|p| p.as_f64()), Some(99_500.0));
+        assert_eq!(trade.closed_at(), Some(row.closed_at));
+        assert_eq!(TradeClosed::pl(&trade), 500);
+        assert!(trade.closed());
+
+        let mut history = ClosedTradeHistory::new();
+        history
+            .add(Arc::new(trade))
+            .expect("restored trade is closed");
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_live_trade_closed_from_invalid_row() {
+        let invalid_side = LiveTradeClosed::try_from(closed_trade_row("Long", Some(99_500.0)));
+        assert!(matches!(
+            invalid_side,
+            Err(ExecutorActionError::InvalidPersistedClosedTrade { .. })
+        ));
+
+        let missing_exit_price = LiveTradeClosed::try_from(closed_trade_row("Buy", None));
+        assert!(matches!(
+            missing_exit_price,
+            Err(ExecutorActionError::InvalidPersistedClosedTrade { .. })
+        ));
+    }
+
+    #[test]
+    fn test_restore_closed_trades_skips_invalid_rows() {
+        let now = Utc::now();
+        let mut session = LiveTradingSession {
+            expires_at: now + Duration::hours(1),
+            tsl_step_size: PercentageCapped::MIN,
+            last_trade_time: None,
+            balance: 1_000_000,
+            last_evaluation_time: now,
+            last_price: 100_000.0,
+            trigger: PriceTrigger::NotSet,
+            running_map: RunningTradesMap::new(),
+            realized_pl: 0,
+            closed_history: Arc::new(ClosedTradeHistory::new()),
+            closed_fees: 0,
+            funding_fees: 0,
+            funding_snapshot: HashMap::new(),
+            cross_position: LiveCrossPosition {
+                id: Uuid::new_v4(),
+                margin: 0,
+                leverage: CrossLeverage::try_from(1_u64).unwrap(),
+                exposure: CrossExposure::Neutral,
+                realized_pl: 0,
+                start_funding_fees: 0,
+                curr_funding_fees: 0,
+                start_trading_fees: 0,
+                curr_trading_fees: 0,
+            },
+        };
+
+        let valid = closed_trade_row("Buy", Some(100_500.0));
+        let warnings = session
+            .restore_closed_trades(vec![
+                closed_trade_row("Long", Some(99_500.0)),
+                valid.clone(),
+                closed_trade_row("Sell", None),
+            ])
+            .expect("invalid rows are skipped");
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().all(|warning| matches!(
+            warning,
+            LiveTradeExecutorWarning::ClosedTradeRestoreSkipped(
+                ExecutorActionError::InvalidPersistedClosedTrade { .. }
+            )
+        )));
+        assert_eq!(session.closed_history.len(), 1);
+        assert_eq!(session.realized_pl, valid.pl);
+        assert_eq!(session.closed_fees, 22);
+        assert_eq!(session.funding_fees, 3);
+        assert_eq!(session.last_trade_time, Some(valid.closed_at));
+    }
+}
@@ -155,6 +155,14 @@ impl<'a> LockedLiveTradeExecutorState<'a> {
         let _ = self.update_tx.send(new_status.into());
     }
 
+    /// Expires the stored trading session, if any, so the next refresh rebuilds state from a fresh
+    /// exchange fetch. The status is left unchanged.
+    pub fn expire_trading_session(&mut self) {
+        if let Some(session) = self.state_guard.trading_session.as_mut() {
+            session.expire();
+        }
+    }
+
     pub fn update_status_ready(mut self, new_trading_session: LiveTradingSession) {
         if !matches!(self.state_guard.status, LiveTradeExecutorStatus::Ready) {
             self.state_guard.status = LiveTradeExecutorStatus::Ready;
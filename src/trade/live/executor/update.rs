@@ -1,5 +1,7 @@
-use std::{fmt, num::NonZeroU64, sync::Arc};
+use std::{collections::HashSet, fmt, num::NonZeroU64, sync::Arc};
 
+use chrono::{DateTime, Duration, Utc};
+use serde_json::{Value, json};
 use tokio::sync::broadcast;
 use uuid::Uuid;
 
@@ -7,13 +9,15 @@ use lnm_sdk::rest::v3::{
     RestClient,
     models::{
         Account, ClientId, CrossLeverage, CrossOrder, CrossPosition, Leverage, OrderQuantity,
-        Price, Trade, TradeExecution, TradeSide, TradeSize,
+        Price, Ticker, Trade, TradeExecution, TradeSide, TradeSize,
     },
 };
 
+use crate::db::Database;
+
 use super::{
-    super::super::core::TradingState,
-    error::{ExecutorActionError, ExecutorActionResult},
+    super::super::core::{TradeClosed, TradingState},
+    error::{ExecutorActionError, ExecutorActionResult, LiveTradeExecutorWarning},
     state::{LiveTradeExecutorStatus, live_trading_session::LiveTradingSession},
 };
 
@@ -95,6 +99,81 @@ pub enum LiveTradeExecutorAction {
     CrossOrderClosePosition,
 }
 
+impl LiveTradeExecutorAction {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IsolatedOrder { .. } => "isolated_order",
+            Self::IsolatedTradeUpdateStoploss { .. } => "isolated_trade_update_stoploss",
+            Self::IsolatedTradeAddMargin { .. } => "isolated_trade_add_margin",
+            Self::IsolatedTradeCashIn { .. } => "isolated_trade_cash_in",
+            Self::IsolatedOrderClose { .. } => "isolated_order_close",
+            Self::IsolatedOrderCancelAll => "isolated_order_cancel_all",
+            Self::IsolatedOrderCloseAll => "isolated_order_close_all",
+            Self::CrossDeposit { .. } => "cross_deposit",
+            Self::CrossWithdraw { .. } => "cross_withdraw",
+            Self::CrossSetLeverage { .. } => "cross_set_leverage",
+            Self::CrossOrder { .. } => "cross_order",
+            Self::CrossOrderCancelAll => "cross_order_cancel_all",
+            Self::CrossOrderClosePosition => "cross_order_close_position",
+        }
+    }
+
+    fn to_audit_json(&self) -> Value {
+        match self {
+            Self::IsolatedOrder {
+                side,
+                size,
+                leverage,
+                stoploss,
+                takeprofit,
+                client_id,
+            } => {
+                let (quantity, margin) = match size {
+                    TradeSize::Quantity(quantity) => (Some(quantity.as_u64()), None),
+                    TradeSize::Margin(margin) => (None, Some(margin.as_u64())),
+                };
+
+                json!({
+                    "side": side.to_string(),
+                    "quantity": quantity,
+                    "margin": margin,
+                    "leverage": leverage.as_f64(),
+                    "stoploss": stoploss.map(|price| price.as_f64()),
+                    "takeprofit": takeprofit.map(|price| price.as_f64()),
+                    "client_id": client_id.as_ref().map(|id| id.as_str()),
+                })
+            }
+            Self::IsolatedTradeUpdateStoploss { id, stoploss } => json!({
+                "id": id,
+                "stoploss": stoploss.as_f64(),
+            }),
+            Self::IsolatedTradeAddMargin { id, amount }
+            | Self::IsolatedTradeCashIn { id, amount } => json!({
+                "id": id,
+                "amount": amount,
+            }),
+            Self::IsolatedOrderClose { id } => json!({ "id": id }),
+            Self::CrossDeposit { amount } | Self::CrossWithdraw { amount } => {
+                json!({ "amount": amount })
+            }
+            Self::CrossSetLeverage { leverage } => json!({ "leverage": leverage.as_u64() }),
+            Self::CrossOrder {
+                side,
+                quantity,
+                client_id,
+            } => json!({
+                "side": side.to_string(),
+                "quantity": quantity.as_u64(),
+                "client_id": client_id.as_ref().map(|id| id.as_str()),
+            }),
+            Self::IsolatedOrderCancelAll
+            | Self::IsolatedOrderCloseAll
+            | Self::CrossOrderCancelAll
+            | Self::CrossOrderClosePosition => json!({}),
+        }
+    }
+}
+
 impl fmt::Display for LiveTradeExecutorAction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -190,7 +269,9 @@ pub enum LiveTradeExecutorUpdate {
     /// The trading state was updated.
     TradingState(TradingState),
     /// A trade was closed.
-    ClosedTrade(Trade),
+    ClosedTrade(Arc<dyn TradeClosed>),
+    /// A non-fatal issue was encountered.
+    Warning(Arc<LiveTradeExecutorWarning>),
 }
 
 impl From<LiveTradeExecutorAction> for LiveTradeExecutorUpdate {
@@ -217,10 +298,99 @@ pub(super) type LiveTradeExecutorTransmitter = broadcast::Sender<LiveTradeExecut
 /// changes, trading state, and closed trades.
 pub type LiveTradeExecutorReceiver = broadcast::Receiver<LiveTradeExecutorUpdate>;
 
+const CLOSED_TRADES_PAGE_LIMIT: NonZeroU64 = NonZeroU64::new(1_000).expect("is not zero");
+
+/// API response that can be recorded in the executor action audit log as structured JSON.
+trait AuditResponse {
+    fn to_audit_json(&self) -> Value;
+}
+
+impl AuditResponse for Trade {
+    fn to_audit_json(&self) -> Value {
+        json!({
+            "id": self.id(),
+            "trade_type": self.trade_type().to_string(),
+            "side": self.side().to_string(),
+            "quantity": self.quantity().as_u64(),
+            "margin": self.margin().as_u64(),
+            "leverage": self.leverage().as_f64(),
+            "price": self.price().as_f64(),
+            "liquidation": self.liquidation().as_f64(),
+            "stoploss": self.stoploss().map(|price| price.as_f64()),
+            "takeprofit": self.takeprofit().map(|price| price.as_f64()),
+            "exit_price": self.exit_price().map(|price| price.as_f64()),
+            "pl": self.pl(),
+            "opening_fee": self.opening_fee(),
+            "closing_fee": self.closing_fee(),
+            "sum_funding_fees": self.sum_funding_fees(),
+            "open": self.open(),
+            "running": self.running(),
+            "canceled": self.canceled(),
+            "closed": self.closed(),
+            "created_at": self.created_at(),
+            "filled_at": self.filled_at(),
+            "closed_at": self.closed_at(),
+            "client_id": self.client_id().map(|id| id.as_str()),
+        })
+    }
+}
+
+impl AuditResponse for CrossOrder {
+    fn to_audit_json(&self) -> Value {
+        json!({
+            "id": self.id(),
+            "trade_type": self.trade_type().to_string(),
+            "side": self.side().to_string(),
+            "quantity": self.quantity().as_u64(),
+            "price": self.price().as_f64(),
+            "trading_fee": self.trading_fee(),
+            "open": self.open(),
+            "filled": self.filled(),
+            "canceled": self.canceled(),
+            "created_at": self.created_at(),
+            "filled_at": self.filled_at(),
+            "canceled_at": self.canceled_at(),
+            "client_id": self.client_id().map(|id| id.as_str()),
+        })
+    }
+}
+
+impl AuditResponse for CrossPosition {
+    fn to_audit_json(&self) -> Value {
+        json!({
+            "id": self.id(),
+            "quantity": self.quantity(),
+            "margin": self.margin(),
+            "leverage": self.leverage().as_u64(),
+            "entry_price": self.entry_price().map(|price| price.as_f64()),
+            "liquidation": self.liquidation().map(|price| price.as_f64()),
+            "running_margin": self.running_margin(),
+            "initial_margin": self.initial_margin(),
+            "maintenance_margin": self.maintenance_margin(),
+            "trading_fees": self.trading_fees(),
+            "funding_fees": self.funding_fees(),
+        })
+    }
+}
+
+impl<T: AuditResponse> AuditResponse for Vec<T> {
+    fn to_audit_json(&self) -> Value {
+        Value::Array(self.iter().map(AuditResponse::to_audit_json).collect())
+    }
+}
+
+/// Destination of the executor action audit log.
+#[derive(Clone)]
+struct ActionAuditLog {
+    db: Arc<Database>,
+    account_id: Uuid,
+}
+
 #[derive(Clone)]
 pub(in crate::trade) struct WrappedRestClient {
     api_rest: Arc<RestClient>,
     update_tx: LiveTradeExecutorTransmitter,
+    audit_log: Option<ActionAuditLog>,
 }
 
 impl WrappedRestClient {
@@ -228,6 +398,16 @@ impl WrappedRestClient {
         Self {
             api_rest,
             update_tx,
+            audit_log: None,
+        }
+    }
+
+    /// Returns a client that persists every executor action sent to the API, along with its
+    /// response or error and latency, in the audit log of the given account.
+    pub fn with_audit_log(self, db: Arc<Database>, account_id: Uuid) -> Self {
+        Self {
+            audit_log: Some(ActionAuditLog { db, account_id }),
+            ..self
         }
     }
 
@@ -239,6 +419,42 @@ impl WrappedRestClient {
             .map_err(ExecutorActionError::RestApi)
     }
 
+    /// Pages back through the trades closed since `from`, newest first, until all the trades with
+    /// the given IDs are found. Returns the found trades.
+    pub async fn find_trades_closed(
+        &self,
+        from: DateTime<Utc>,
+        trade_ids: &HashSet<Uuid>,
+    ) -> ExecutorActionResult<Vec<Trade>> {
+        let mut pending = trade_ids.clone();
+        let mut found = Vec::new();
+        let mut cursor = None;
+
+        while !pending.is_empty() {
+            let trade_page = self
+                .api_rest
+                .futures_isolated
+                .get_closed_trades(Some(from), None, Some(CLOSED_TRADES_PAGE_LIMIT), cursor)
+                .await
+                .map_err(ExecutorActionError::RestApi)?;
+
+            cursor = trade_page.next_cursor();
+
+            let trades: Vec<Trade> = trade_page.into();
+            for trade in trades {
+                if pending.remove(&trade.id()) {
+                    found.push(trade);
+                }
+            }
+
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        Ok(found)
+    }
+
     pub async fn get_trades_closed(&self, limit: NonZeroU64) -> ExecutorActionResult<Vec<Trade>> {
         let trade_page = self
             .api_rest
@@ -250,6 +466,14 @@ impl WrappedRestClient {
         Ok(trade_page.into())
     }
 
+    pub async fn get_ticker(&self) -> ExecutorActionResult<Ticker> {
+        self.api_rest
+            .futures_data
+            .get_ticker()
+            .await
+            .map_err(ExecutorActionError::RestApi)
+    }
+
     pub async fn get_user(&self) -> ExecutorActionResult<Account> {
         self.api_rest
             .account
@@ -262,6 +486,86 @@ impl WrappedRestClient {
         let _ = self.update_tx.send(action.into());
     }
 
+    /// Records an action in the audit log, if enabled. Audit log write failures are reported as
+    /// [`LiveTradeExecutorWarning::AuditLogWriteFailed`] updates.
+    async fn add_audit_entry(
+        &self,
+        action: &LiveTradeExecutorAction,
+        outcome: Result<Value, String>,
+        latency: Duration,
+        requested_at: DateTime<Utc>,
+        shadow: bool,
+    ) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+
+        let (response, error) = match outcome {
+            Ok(response) => (Some(response.to_string()), None),
+            Err(e) => (None, Some(e)),
+        };
+
+        // Audit log failures must not affect the outcome of the action
+        if let Err(e) = audit_log
+            .db
+            .executor_actions
+            .add_action(
+                audit_log.account_id,
+                action.kind(),
+                shadow,
+                &action.to_audit_json().to_string(),
+                response.as_deref(),
+                error.as_deref(),
+                latency,
+                requested_at,
+            )
+            .await
+        {
+            let warning = LiveTradeExecutorWarning::AuditLogWriteFailed(e);
+            let _ = self
+                .update_tx
+                .send(LiveTradeExecutorUpdate::Warning(Arc::new(warning)));
+        }
+    }
+
+    /// Broadcasts the action, awaits its request and records the outcome in the audit log, if
+    /// enabled.
+    async fn execute<T: AuditResponse>(
+        &self,
+        action: LiveTradeExecutorAction,
+        request: impl Future<Output = ExecutorActionResult<T>>,
+    ) -> ExecutorActionResult<T> {
+        let audited_action = self.audit_log.is_some().then(|| action.clone());
+
+        self.send_action_update(action);
+
+        let requested_at = Utc::now();
+        let result = request.await;
+
+        if let Some(action) = audited_action {
+            let latency = Utc::now() - requested_at;
+            let outcome = match &result {
+                Ok(response) => Ok(response.to_audit_json()),
+                Err(e) => Err(e.to_string()),
+            };
+
+            self.add_audit_entry(&action, outcome, latency, requested_at, false)
+                .await;
+        }
+
+        result
+    }
+
+    /// Records an action intercepted by a shadow executor in the audit log, if enabled, marked as
+    /// shadow, and broadcasts it. `response` is the hypothetical result returned in place of the
+    /// API response.
+    pub async fn intercept(&self, action: LiveTradeExecutorAction, response: Value) {
+        self.add_audit_entry(&action, Ok(response), Duration::zero(), Utc::now(), true)
+            .await;
+
+        self.send_action_update(action);
+    }
+
     pub async fn isolated_order(
         &self,
         side: TradeSide,
@@ -271,28 +575,31 @@ impl WrappedRestClient {
         takeprofit: Option<Price>,
         client_id: Option<ClientId>,
     ) -> ExecutorActionResult<Trade> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedOrder {
+        let action = LiveTradeExecutorAction::IsolatedOrder {
             side,
             size,
             leverage,
             stoploss,
             takeprofit,
             client_id: client_id.clone(),
-        });
+        };
 
-        self.api_rest
-            .futures_isolated
-            .new_trade(
-                side,
-                size,
-                leverage,
-                TradeExecution::Market,
-                stoploss,
-                takeprofit,
-                client_id,
-            )
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .new_trade(
+                    side,
+                    size,
+                    leverage,
+                    TradeExecution::Market,
+                    stoploss,
+                    takeprofit,
+                    client_id,
+                )
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_trade_update_stoploss(
@@ -300,16 +607,16 @@ impl WrappedRestClient {
         id: Uuid,
         stoploss: Price,
     ) -> ExecutorActionResult<Trade> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedTradeUpdateStoploss {
-            id,
-            stoploss,
-        });
+        let action = LiveTradeExecutorAction::IsolatedTradeUpdateStoploss { id, stoploss };
 
-        self.api_rest
-            .futures_isolated
-            .update_stoploss(id, Some(stoploss))
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .update_stoploss(id, Some(stoploss))
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_trade_add_margin(
@@ -317,13 +624,16 @@ impl WrappedRestClient {
         id: Uuid,
         amount: NonZeroU64,
     ) -> ExecutorActionResult<Trade> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedTradeAddMargin { id, amount });
+        let action = LiveTradeExecutorAction::IsolatedTradeAddMargin { id, amount };
 
-        self.api_rest
-            .futures_isolated
-            .add_margin_to_trade(id, amount)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .add_margin_to_trade(id, amount)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_trade_cash_in(
@@ -331,59 +641,71 @@ impl WrappedRestClient {
         id: Uuid,
         amount: NonZeroU64,
     ) -> ExecutorActionResult<Trade> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedTradeCashIn { id, amount });
+        let action = LiveTradeExecutorAction::IsolatedTradeCashIn { id, amount };
 
-        self.api_rest
-            .futures_isolated
-            .cash_in_trade(id, amount)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .cash_in_trade(id, amount)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_order_close(&self, id: Uuid) -> ExecutorActionResult<Trade> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderClose { id });
+        let action = LiveTradeExecutorAction::IsolatedOrderClose { id };
 
-        self.api_rest
-            .futures_isolated
-            .close_trade(id)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .close_trade(id)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_order_cancel_all(&self) -> ExecutorActionResult<Vec<Trade>> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCancelAll);
+        let action = LiveTradeExecutorAction::IsolatedOrderCancelAll;
 
-        self.api_rest
-            .futures_isolated
-            .cancel_all_trades()
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_isolated
+                .cancel_all_trades()
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn isolated_order_close_all(&self) -> ExecutorActionResult<Vec<Trade>> {
-        self.send_action_update(LiveTradeExecutorAction::IsolatedOrderCloseAll);
+        let action = LiveTradeExecutorAction::IsolatedOrderCloseAll;
 
-        let running_trades = self
-            .api_rest
-            .futures_isolated
-            .get_running_trades()
-            .await
-            .map_err(ExecutorActionError::RestApi)?;
-
-        let mut closed_trades = Vec::new();
-
-        for trade in running_trades {
-            let closed_trade = self
+        self.execute(action, async {
+            let running_trades = self
                 .api_rest
                 .futures_isolated
-                .close_trade(trade.id())
+                .get_running_trades()
                 .await
                 .map_err(ExecutorActionError::RestApi)?;
 
-            closed_trades.push(closed_trade);
-        }
+            let mut closed_trades = Vec::new();
+
+            for trade in running_trades {
+                let closed_trade = self
+                    .api_rest
+                    .futures_isolated
+                    .close_trade(trade.id())
+                    .await
+                    .map_err(ExecutorActionError::RestApi)?;
 
-        Ok(closed_trades)
+                closed_trades.push(closed_trade);
+            }
+
+            Ok(closed_trades)
+        })
+        .await
     }
 
     pub async fn cross_get_position(&self) -> ExecutorActionResult<CrossPosition> {
@@ -395,36 +717,45 @@ impl WrappedRestClient {
     }
 
     pub async fn cross_deposit(&self, amount: NonZeroU64) -> ExecutorActionResult<CrossPosition> {
-        self.send_action_update(LiveTradeExecutorAction::CrossDeposit { amount });
+        let action = LiveTradeExecutorAction::CrossDeposit { amount };
 
-        self.api_rest
-            .futures_cross
-            .deposit(amount)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .deposit(amount)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn cross_withdraw(&self, amount: NonZeroU64) -> ExecutorActionResult<CrossPosition> {
-        self.send_action_update(LiveTradeExecutorAction::CrossWithdraw { amount });
+        let action = LiveTradeExecutorAction::CrossWithdraw { amount };
 
-        self.api_rest
-            .futures_cross
-            .withdraw(amount)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .withdraw(amount)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn cross_set_leverage(
         &self,
         leverage: CrossLeverage,
     ) -> ExecutorActionResult<CrossPosition> {
-        self.send_action_update(LiveTradeExecutorAction::CrossSetLeverage { leverage });
+        let action = LiveTradeExecutorAction::CrossSetLeverage { leverage };
 
-        self.api_rest
-            .futures_cross
-            .set_leverage(leverage)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .set_leverage(leverage)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn cross_order(
@@ -433,36 +764,45 @@ impl WrappedRestClient {
         quantity: OrderQuantity,
         client_id: Option<ClientId>,
     ) -> ExecutorActionResult<CrossOrder> {
-        self.send_action_update(LiveTradeExecutorAction::CrossOrder {
+        let action = LiveTradeExecutorAction::CrossOrder {
             side,
             quantity,
             client_id: client_id.clone(),
-        });
+        };
 
-        self.api_rest
-            .futures_cross
-            .place_order(side, quantity, TradeExecution::Market, client_id)
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .place_order(side, quantity, TradeExecution::Market, client_id)
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn cross_cancel_all_orders(&self) -> ExecutorActionResult<Vec<CrossOrder>> {
-        self.send_action_update(LiveTradeExecutorAction::CrossOrderCancelAll);
+        let action = LiveTradeExecutorAction::CrossOrderCancelAll;
 
-        self.api_rest
-            .futures_cross
-            .cancel_all_orders()
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .cancel_all_orders()
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 
     pub async fn cross_order_close_position(&self) -> ExecutorActionResult<CrossOrder> {
-        self.send_action_update(LiveTradeExecutorAction::CrossOrderClosePosition);
+        let action = LiveTradeExecutorAction::CrossOrderClosePosition;
 
-        self.api_rest
-            .futures_cross
-            .close_position()
-            .await
-            .map_err(ExecutorActionError::RestApi)
+        self.execute(action, async {
+            self.api_rest
+                .futures_cross
+                .close_position()
+                .await
+                .map_err(ExecutorActionError::RestApi)
+        })
+        .await
     }
 }
@@ -0,0 +1,278 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use lnm_sdk::rest::v3::models::PercentageCapped;
+
+use crate::util::DateTimeExt;
+
+use super::super::{
+    core::TradingState,
+    risk::{NetValueReferences, NetValueTracker, RiskLimits},
+};
+
+/// Record of a live trading kill switch trigger.
+///
+/// Once triggered, the record is persisted in the database and the live trading process refuses to
+/// start until the kill switch is re-armed via
+/// [`LiveTradeEngine::rearm_kill_switch`](crate::trade::LiveTradeEngine::rearm_kill_switch).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KillSwitchTrip {
+    triggered_at: DateTime<Utc>,
+    reason: String,
+}
+
+impl KillSwitchTrip {
+    fn new(triggered_at: DateTime<Utc>, reason: String) -> Self {
+        Self {
+            triggered_at,
+            reason,
+        }
+    }
+
+    /// Returns the time of the trading state update that triggered the kill switch.
+    pub fn triggered_at(&self) -> DateTime<Utc> {
+        self.triggered_at
+    }
+
+    /// Returns the description of the breached limit.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub(super) fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("kill switch trip is serializable")
+    }
+
+    pub(super) fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+}
+
+impl fmt::Display for KillSwitchTrip {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (triggered at {})",
+            self.reason,
+            self.triggered_at.format_local_millis()
+        )
+    }
+}
+
+/// Database keys of the kill switch state, scoped by executor kind and account so that engines
+/// sharing a database don't share a kill switch.
+#[derive(Debug, Clone)]
+pub(super) struct KillSwitchKeys {
+    trip: String,
+    net_value: String,
+    flatten_pending: String,
+}
+
+impl KillSwitchKeys {
+    pub fn new(prefix: &str, scope: &str) -> Self {
+        let trip = format!("{prefix}:{scope}");
+        let net_value = format!("{trip}:net_value");
+        let flatten_pending = format!("{trip}:flatten_pending");
+
+        Self {
+            trip,
+            net_value,
+            flatten_pending,
+        }
+    }
+
+    /// Key of the persisted [`KillSwitchTrip`].
+    pub fn trip(&self) -> &str {
+        &self.trip
+    }
+
+    /// Key of the persisted net value reference points (start of day value and peak value).
+    pub fn net_value(&self) -> &str {
+        &self.net_value
+    }
+
+    /// Key of the marker persisted alongside the trip until all positions have been closed.
+    pub fn flatten_pending(&self) -> &str {
+        &self.flatten_pending
+    }
+}
+
+/// Watches the trading state updates of the live trade executor, tripping when the configured
+/// daily loss or drawdown limit is reached.
+///
+/// The limits are evaluated like the loss limits of a [`RiskManager`](crate::trade::RiskManager).
+pub(super) struct KillSwitchMonitor {
+    limits: RiskLimits,
+    persisted: Option<NetValueReferences>,
+    tracker: Option<NetValueTracker>,
+}
+
+impl KillSwitchMonitor {
+    /// Returns `None` if no limit is configured. Tracking is resumed from the `persisted`
+    /// reference points, if any.
+    pub fn new(
+        max_daily_loss: Option<u64>,
+        max_drawdown: Option<PercentageCapped>,
+        persisted: Option<NetValueReferences>,
+    ) -> Option<Self> {
+        let limits = RiskLimits::default()
+            .with_max_daily_loss(max_daily_loss)
+            .with_max_drawdown(max_drawdown);
+
+        if !limits.has_loss_limits() {
+            return None;
+        }
+
+        Some(Self {
+            limits,
+            persisted,
+            tracker: None,
+        })
+    }
+
+    pub fn check(&mut self, state: &TradingState) -> Option<KillSwitchTrip> {
+        let tracker = match self.tracker.as_mut() {
+            Some(tracker) => {
+                tracker.update(state);
+                tracker
+            }
+            None => {
+                let tracker = match self.persisted.clone() {
+                    Some(references) => NetValueTracker::resume(references, state),
+                    None => NetValueTracker::new(state),
+                };
+                self.tracker.insert(tracker)
+            }
+        };
+
+        self.limits
+            .check_loss_limits(tracker)
+            .err()
+            .map(|e| KillSwitchTrip::new(state.last_tick_time(), e.to_string()))
+    }
+
+    /// Returns the reference points if they changed since they were last persisted, and marks
+    /// them as persisted.
+    pub fn take_reference_update(&mut self) -> Option<NetValueReferences> {
+        let references = self.tracker.as_ref()?.references();
+
+        if self.persisted.as_ref() == Some(references) {
+            return None;
+        }
+
+        self.persisted = Some(references.clone());
+        Some(references.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use lnm_sdk::rest::v3::models::{Leverage, OrderQuantity, TradeSide};
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        trade::{
+            BacktestConfig, IsolatedOrderRequest, TradeExecutor,
+            backtest::executor::SimulatedTradeExecutor,
+        },
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_kill_switch_monitor_trips_on_drawdown() {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let start_candle = OhlcCandleRow::new_simple(time, 100_000., 1_000);
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 1_000_000);
+
+        assert!(KillSwitchMonitor::new(None, None, None).is_none());
+
+        let mut monitor =
+            KillSwitchMonitor::new(Some(500_000), Some(PercentageCapped::bounded(20.)), None)
+                .unwrap();
+
+        let request = IsolatedOrderRequest::market(
+            TradeSide::Buy,
+            OrderQuantity::try_from(5_000).unwrap().into(),
+            Leverage::try_from(10).unwrap(),
+        );
+        executor.isolated_order(request).await.unwrap();
+        assert!(
+            monitor
+                .check(&executor.trading_state().await.unwrap())
+                .is_none()
+        );
+
+        // A 5% price drop on a 5,000 USD long loses ~250k sats, a ~25% drawdown
+        let drop_candle = OhlcCandleRow::new_simple(time + Duration::minutes(1), 95_000., 1_000);
+        executor.candle_update(&drop_candle).await.unwrap();
+
+        let trip = monitor
+            .check(&executor.trading_state().await.unwrap())
+            .unwrap();
+        assert!(trip.reason().starts_with("drawdown"));
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_monitor_resumes_persisted_peak() {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let start_candle = OhlcCandleRow::new_simple(time, 100_000., 1_000);
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 1_000_000);
+
+        let max_drawdown = Some(PercentageCapped::bounded(20.));
+        let mut monitor = KillSwitchMonitor::new(None, max_drawdown, None).unwrap();
+
+        let request = IsolatedOrderRequest::market(
+            TradeSide::Buy,
+            OrderQuantity::try_from(5_000).unwrap().into(),
+            Leverage::try_from(10).unwrap(),
+        );
+        executor.isolated_order(request).await.unwrap();
+        assert!(
+            monitor
+                .check(&executor.trading_state().await.unwrap())
+                .is_none()
+        );
+
+        let persisted = monitor.take_reference_update().unwrap();
+        assert!(monitor.take_reference_update().is_none());
+
+        let drop_candle = OhlcCandleRow::new_simple(time + Duration::minutes(1), 95_000., 1_000);
+        executor.candle_update(&drop_candle).await.unwrap();
+        let dropped_state = executor.trading_state().await.unwrap();
+
+        // Without the persisted reference points, the peak is reset to the current net value
+        let mut fresh_monitor = KillSwitchMonitor::new(None, max_drawdown, None).unwrap();
+        assert!(fresh_monitor.check(&dropped_state).is_none());
+
+        let mut resumed_monitor =
+            KillSwitchMonitor::new(None, max_drawdown, Some(persisted)).unwrap();
+        let trip = resumed_monitor.check(&dropped_state).unwrap();
+        assert!(trip.reason().starts_with("drawdown"));
+    }
+
+    #[test]
+    fn test_kill_switch_keys_are_scoped() {
+        let live = KillSwitchKeys::new("kill_switch", "live:account");
+        let paper = KillSwitchKeys::new("kill_switch", "paper");
+
+        assert_eq!(live.trip(), "kill_switch:live:account");
+        assert_eq!(live.net_value(), "kill_switch:live:account:net_value");
+        assert_ne!(live.trip(), paper.trip());
+    }
+
+    #[test]
+    fn test_kill_switch_trip_bytes_roundtrip() {
+        let triggered_at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let trip = KillSwitchTrip::new(triggered_at, "daily loss\nreached".to_string());
+
+        assert_eq!(KillSwitchTrip::from_bytes(&trip.to_bytes()).unwrap(), trip);
+        assert!(KillSwitchTrip::from_bytes(b"not a record").is_err());
+    }
+}
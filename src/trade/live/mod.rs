@@ -1,6 +1,8 @@
 pub(super) mod config;
+pub(super) mod dead_man_switch;
 pub(super) mod engine;
 pub(crate) mod error;
 pub(crate) mod executor;
+pub(super) mod kill_switch;
 pub(crate) mod process;
 pub(super) mod state;
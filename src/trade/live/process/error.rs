@@ -13,7 +13,7 @@ use crate::{
 };
 
 use super::super::{
-    super::error::TradeCoreError,
+    super::error::{TradeCoreError, TradeExecutorError},
     executor::error::{ExecutorProcessFatalError, LiveTradeExecutorError},
 };
 
@@ -26,6 +26,12 @@ pub enum LiveProcessRecoverableError {
     #[error("Operator error: {0}")]
     OperatorError(TradeCoreError),
 
+    #[error("Evaluate trading state error: {0}")]
+    TradingState(TradeExecutorError),
+
+    #[error("Save operator state error: {0}")]
+    SaveOperatorState(TradeCoreError),
+
     #[error("`SyncRecvLagged` error, skipped: {skipped}")]
     SyncRecvLagged { skipped: u64 },
 
@@ -34,6 +40,12 @@ pub enum LiveProcessRecoverableError {
 
     #[error("`ExecutorRecvLagged` error, skipped: {skipped}")]
     ExecutorRecvLagged { skipped: u64 },
+
+    #[error("Apply dead man's switch action error: {0}")]
+    DeadManSwitch(TradeExecutorError),
+
+    #[error("Persist kill switch net value reference points error: {0}")]
+    KillSwitchPersistNetValue(DbError),
     // #[error("Operator iteration time too long for iteration interval")]
     // OperatorIterationTimeTooLong,
 }
@@ -47,6 +59,46 @@ pub enum LiveProcessFatalError {
     #[error("Setup operator error: {0}")]
     StartOperatorError(TradeCoreError),
 
+    #[error("Load operator state error: {0}")]
+    LoadOperatorState(DbError),
+
+    #[error("Restore operator state error: {0}")]
+    RestoreOperatorState(TradeCoreError),
+
+    #[error("Operator shutdown hook error: {0}")]
+    OperatorShutdownHook(TradeCoreError),
+
+    #[error("Save operator state on shutdown error: {0}")]
+    ShutdownSaveOperatorState(LiveProcessRecoverableError),
+
+    #[error("Resolve kill switch scope error: {0}")]
+    KillSwitchScope(LiveTradeExecutorError),
+
+    #[error("Load kill switch state error: {0}")]
+    LoadKillSwitch(DbError),
+
+    #[error("Persisted kill switch state could not be decoded: {0}")]
+    KillSwitchDecode(serde_json::Error),
+
+    #[error("Load risk limits state error: {0}")]
+    LoadRiskLimits(DbError),
+
+    #[error("Persisted risk limits state could not be decoded: {0}")]
+    RiskLimitsDecode(serde_json::Error),
+
+    #[error("Persist kill switch trigger error: {0}")]
+    KillSwitchPersist(DbError),
+
+    #[error(
+        "Close positions on kill switch trigger error, isolated trades: {}, cross-margin position: {}",
+        fmt_flatten_error(isolated.as_deref()),
+        fmt_flatten_error(cross.as_deref())
+    )]
+    KillSwitchFlatten {
+        isolated: Option<Box<TradeExecutorError>>,
+        cross: Option<Box<TradeExecutorError>>,
+    },
+
     #[error("[TaskJoin] {0}")]
     LiveProcessTaskJoin(JoinError),
 
@@ -101,6 +153,10 @@ pub enum LiveProcessFatalError {
 
 pub(crate) type LiveProcessFatalResult<T> = result::Result<T, LiveProcessFatalError>;
 
+fn fmt_flatten_error(error: Option<&TradeExecutorError>) -> String {
+    error.map_or_else(|| "closed".to_string(), |e| e.to_string())
+}
+
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum LiveProcessError {
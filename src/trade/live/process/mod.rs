@@ -1,23 +1,44 @@
-use std::sync::Arc;
+use std::{
+    result,
+    sync::{Arc, OnceLock},
+};
 
+use chrono::Utc;
 use tokio::{
-    sync::broadcast::{self, error::RecvError},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
     time,
 };
 
 use crate::{
+    db::Database,
     signal::Signal,
     sync::{SyncController, SyncEngine},
     util::AbortOnDropHandle,
 };
 
 use super::{
+    super::{
+        core::TradeExecutor,
+        error::TradeExecutorError,
+        middleware::{self, TradeExecutorMiddleware},
+        risk::{NetValueReferences, RiskPersistence},
+    },
     config::{LiveProcessConfig, LiveTradeConfig},
+    dead_man_switch::{DeadManSwitchAction, DeadManSwitchMonitor},
     executor::{
-        LiveTradeExecutor, LiveTradeExecutorLauncher,
+        ExecutorPending, ExecutorRunning,
+        error::ExecutorActionError,
+        state::{LiveTradeExecutorStatus, LiveTradeExecutorStatusNotReady},
         update::{LiveTradeExecutorReceiver, LiveTradeExecutorUpdate},
     },
-    state::{LiveTradeStatus, LiveTradeStatusManager, LiveTradeTransmitter, LiveTradeUpdate},
+    kill_switch::{KillSwitchKeys, KillSwitchMonitor, KillSwitchTrip},
+    state::{
+        LiveTradeReader, LiveTradeStatus, LiveTradeStatusManager, LiveTradeTransmitter,
+        LiveTradeUpdate,
+    },
 };
 
 pub(crate) mod error;
@@ -27,41 +48,176 @@ use error::{
     LiveProcessError, LiveProcessFatalError, LiveProcessFatalResult, LiveProcessRecoverableError,
 };
 
-use operator::{OperatorPending, OperatorRunning};
+use operator::{OperatorEvents, OperatorPending, OperatorRunning};
+
+/// Outcome of a run of the operator in the recovery loop.
+enum RunExit {
+    Error(LiveProcessError),
+    Shutdown,
+    KillSwitch(KillSwitchTrip),
+}
 
 pub(super) struct LiveProcess<S: Signal> {
     config: LiveProcessConfig,
+    db: Arc<Database>,
+    kill_switch_keys: KillSwitchKeys,
     shutdown_tx: broadcast::Sender<()>,
     sync_controller: Arc<SyncController>,
     operator_running: OperatorRunning<S>,
     executor_updates_handle: AbortOnDropHandle<()>,
-    trade_executor: Arc<LiveTradeExecutor>,
+    dead_man_switch_handle: Option<AbortOnDropHandle<()>>,
+    trade_executor: ExecutorRunning,
     status_manager: Arc<LiveTradeStatusManager<S>>,
     update_tx: LiveTradeTransmitter<S>,
 }
 
 impl<S: Signal> LiveProcess<S> {
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         config: &LiveTradeConfig,
+        db: Arc<Database>,
         shutdown_tx: broadcast::Sender<()>,
         sync_engine: SyncEngine,
         operator_pending: OperatorPending<S>,
-        trade_executor_launcher: LiveTradeExecutorLauncher,
+        trade_executor_launcher: ExecutorPending,
+        middleware: Vec<Arc<dyn TradeExecutorMiddleware>>,
         status_manager: Arc<LiveTradeStatusManager<S>>,
     ) -> AbortOnDropHandle<LiveProcessFatalResult<()>> {
-        let config = config.into();
+        let config: LiveProcessConfig = config.into();
 
         tokio::spawn(async move {
+            let scope = match trade_executor_launcher.kill_switch_scope().await {
+                Ok(scope) => scope,
+                Err(e) => {
+                    status_manager.update(LiveProcessFatalError::KillSwitchScope(e).into());
+                    return Ok(());
+                }
+            };
+            let kill_switch_keys = KillSwitchKeys::new(config.kill_switch_key(), &scope);
+
+            // A kill switch trigger persists until re-armed, so trading doesn't resume on restart
+            match db.key_values.get_value(kill_switch_keys.trip()).await {
+                Ok(Some(bytes)) => {
+                    let trip = match KillSwitchTrip::from_bytes(&bytes) {
+                        Ok(trip) => trip,
+                        Err(e) => {
+                            status_manager
+                                .update(LiveProcessFatalError::KillSwitchDecode(e).into());
+                            return Ok(());
+                        }
+                    };
+
+                    // Positions left open by a failed flatten are closed before stopping again
+                    let flatten_res = match db
+                        .key_values
+                        .get_value(kill_switch_keys.flatten_pending())
+                        .await
+                    {
+                        Ok(Some(_)) => {
+                            status_manager.update(LiveTradeStatus::Starting);
+                            Self::resume_flatten(
+                                &db,
+                                &kill_switch_keys,
+                                sync_engine,
+                                trade_executor_launcher,
+                            )
+                            .await
+                        }
+                        Ok(None) => Ok(()),
+                        Err(e) => Err(LiveProcessFatalError::LoadKillSwitch(e)),
+                    };
+
+                    match flatten_res {
+                        Ok(()) => status_manager
+                            .update(LiveTradeStatus::KillSwitchTriggered(Arc::new(trip))),
+                        Err(e) => status_manager.update(e.into()),
+                    }
+
+                    return Ok(());
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    status_manager.update(LiveProcessFatalError::LoadKillSwitch(e).into());
+                    return Ok(());
+                }
+            }
+
+            let kill_switch_limits_set = config.kill_switch_max_daily_loss().is_some()
+                || config.kill_switch_max_drawdown().is_some();
+
+            // Daily loss and drawdown are evaluated against the reference points observed before
+            // the restart
+            let net_value = if kill_switch_limits_set {
+                let load_res = match db.key_values.get_value(kill_switch_keys.net_value()).await {
+                    Ok(Some(bytes)) => NetValueReferences::from_bytes(&bytes)
+                        .map(Some)
+                        .map_err(LiveProcessFatalError::KillSwitchDecode),
+                    Ok(None) => Ok(None),
+                    Err(e) => Err(LiveProcessFatalError::LoadKillSwitch(e)),
+                };
+
+                match load_res {
+                    Ok(net_value) => net_value,
+                    Err(e) => {
+                        status_manager.update(e.into());
+                        return Ok(());
+                    }
+                }
+            } else {
+                None
+            };
+
+            let update_tx = status_manager.transmitter().clone();
+
+            status_manager.update(LiveTradeStatus::Starting);
+
+            // Armed before the executor is launched and the operator is started, so that a startup
+            // that doesn't complete is covered as well. The action is retried after the restart
+            // interval until the executor is launched.
+            let launched_executor: Arc<OnceLock<ExecutorRunning>> = Arc::new(OnceLock::new());
+            let dead_man_switch_handle =
+                config.dead_man_switch_grace_period().map(|grace_period| {
+                    let launched_executor = launched_executor.clone();
+                    Self::spawn_dead_man_switch(
+                        DeadManSwitchMonitor::new(grace_period, config.dead_man_switch_action()),
+                        config.restart_interval(),
+                        status_manager.clone(),
+                        update_tx.clone(),
+                        move |action| {
+                            let trade_executor = launched_executor.get().cloned();
+                            async move {
+                                match trade_executor {
+                                    Some(trade_executor) => {
+                                        trade_executor.apply_dead_man_switch(action).await
+                                    }
+                                    None => Err(ExecutorActionError::ExecutorNotLaunched.into()),
+                                }
+                            }
+                        },
+                    )
+                });
+
             let sync_controller = sync_engine.start();
 
             let executor_rx = trade_executor_launcher.update_receiver();
+            let operator_events =
+                OperatorEvents::new(db.clone(), trade_executor_launcher.update_receiver());
 
-            let update_tx = status_manager.transmitter().clone();
+            let (kill_switch_tx, kill_switch_rx) = mpsc::channel(1);
+            let kill_switch = KillSwitchMonitor::new(
+                config.kill_switch_max_daily_loss(),
+                config.kill_switch_max_drawdown(),
+                net_value,
+            )
+            .map(|monitor| (monitor, kill_switch_tx));
 
             let executor_updates_handle = Self::spawn_executor_update_handler(
+                db.clone(),
+                kill_switch_keys.clone(),
                 status_manager.clone(),
                 update_tx.clone(),
                 executor_rx,
+                kill_switch,
             );
 
             let trade_executor = match trade_executor_launcher
@@ -76,7 +232,54 @@ impl<S: Signal> LiveProcess<S> {
                 }
             };
 
-            let operator_running = match operator_pending.start(trade_executor.clone()) {
+            let _ = launched_executor.set(trade_executor.clone());
+
+            let mut operator_pending = operator_pending;
+
+            if let Some(key) = config.operator_state_key() {
+                let restore_res = match db.key_values.get_value(key).await {
+                    Ok(Some(state)) => operator_pending.restore_state(&state),
+                    Ok(None) => Ok(()),
+                    Err(e) => Err(LiveProcessFatalError::LoadOperatorState(e)),
+                };
+
+                if let Err(e) = restore_res {
+                    status_manager.update(e.into());
+                    return Ok(());
+                }
+            }
+
+            // Loss limits are evaluated against the reference points observed before the restart
+            let risk_persistence = match config.risk_limits() {
+                Some(limits) if limits.has_loss_limits() => {
+                    let key = format!("{}:{scope}", config.risk_state_key());
+                    let load_res = match db.key_values.get_value(&key).await {
+                        Ok(Some(bytes)) => NetValueReferences::from_bytes(&bytes)
+                            .map(Some)
+                            .map_err(LiveProcessFatalError::RiskLimitsDecode),
+                        Ok(None) => Ok(None),
+                        Err(e) => Err(LiveProcessFatalError::LoadRiskLimits(e)),
+                    };
+
+                    match load_res {
+                        Ok(persisted) => Some(RiskPersistence::new(db.clone(), key, persisted)),
+                        Err(e) => {
+                            status_manager.update(e.into());
+                            return Ok(());
+                        }
+                    }
+                }
+                _ => None,
+            };
+
+            let operator_executor = middleware::wrap_executor(
+                trade_executor.trade_executor(),
+                config.risk_limits(),
+                risk_persistence,
+                &middleware,
+            );
+
+            let operator_running = match operator_pending.start(operator_executor) {
                 Ok(op) => op,
                 Err(e) => {
                     status_manager.update(e.into());
@@ -84,26 +287,37 @@ impl<S: Signal> LiveProcess<S> {
                 }
             };
 
+            if let Err(e) = operator_running.on_start().await {
+                status_manager.update(LiveProcessFatalError::StartOperatorError(e).into());
+                return Ok(());
+            }
+
             let process = Self {
                 config,
+                db,
+                kill_switch_keys,
                 shutdown_tx,
                 sync_controller,
                 operator_running,
                 executor_updates_handle,
+                dead_man_switch_handle,
                 trade_executor,
                 status_manager,
                 update_tx,
             };
 
-            process.recovery_loop().await
+            process.recovery_loop(operator_events, kill_switch_rx).await
         })
         .into()
     }
 
     fn spawn_executor_update_handler(
+        db: Arc<Database>,
+        kill_switch_keys: KillSwitchKeys,
         status_manager: Arc<LiveTradeStatusManager<S>>,
         update_tx: LiveTradeTransmitter<S>,
         mut executor_rx: LiveTradeExecutorReceiver,
+        mut kill_switch: Option<(KillSwitchMonitor, mpsc::Sender<KillSwitchTrip>)>,
     ) -> AbortOnDropHandle<()> {
         tokio::spawn(async move {
             loop {
@@ -114,11 +328,35 @@ impl<S: Signal> LiveProcess<S> {
                             let _ = update_tx.send(executor_action.into());
                         }
                         LiveTradeExecutorUpdate::TradingState(trading_state) => {
+                            if let Some((monitor, kill_switch_tx)) = &mut kill_switch {
+                                if let Some(trip) = monitor.check(&trading_state) {
+                                    let _ = kill_switch_tx.try_send(trip);
+                                    // Triggered only once. The process stops after handling the
+                                    // trip
+                                    kill_switch = None;
+                                } else if let Some(net_value) = monitor.take_reference_update()
+                                    && let Err(e) = db
+                                        .key_values
+                                        .set_value(
+                                            kill_switch_keys.net_value(),
+                                            &net_value.to_bytes(),
+                                        )
+                                        .await
+                                {
+                                    let e =
+                                        LiveProcessRecoverableError::KillSwitchPersistNetValue(e);
+                                    status_manager.update(e.into());
+                                }
+                            }
+
                             let _ = update_tx.send(trading_state.into());
                         }
                         LiveTradeExecutorUpdate::ClosedTrade(closed_trade) => {
                             let _ = update_tx.send(LiveTradeUpdate::ClosedTrade(closed_trade));
                         }
+                        LiveTradeExecutorUpdate::Warning(warning) => {
+                            let _ = update_tx.send(LiveTradeUpdate::ExecutorWarning(warning));
+                        }
                     },
                     Err(RecvError::Lagged(skipped)) => {
                         let e = LiveProcessRecoverableError::ExecutorRecvLagged { skipped };
@@ -134,28 +372,115 @@ impl<S: Signal> LiveProcess<S> {
         .into()
     }
 
-    async fn recovery_loop(self) -> LiveProcessFatalResult<()> {
-        self.status_manager.update(LiveTradeStatus::Starting);
+    fn spawn_dead_man_switch<F, Fut>(
+        mut monitor: DeadManSwitchMonitor,
+        retry_interval: time::Duration,
+        status_manager: Arc<LiveTradeStatusManager<S>>,
+        update_tx: LiveTradeTransmitter<S>,
+        apply_action: F,
+    ) -> AbortOnDropHandle<()>
+    where
+        F: Fn(DeadManSwitchAction) -> Fut + Send + 'static,
+        Fut: Future<Output = result::Result<(), TradeExecutorError>> + Send,
+    {
+        tokio::spawn(async move {
+            let mut live_rx = status_manager.update_receiver();
+
+            monitor.update(&status_manager.status_snapshot(), Utc::now());
+
+            loop {
+                let until_due = monitor.next_trigger().map(|next_trigger| {
+                    (next_trigger - Utc::now()).to_std().unwrap_or_default()
+                });
 
+                tokio::select! {
+                    recv_res = live_rx.recv() => match recv_res {
+                        Ok(LiveTradeUpdate::Status(status)) => monitor.update(&status, Utc::now()),
+                        Ok(_) => {}
+                        Err(RecvError::Lagged(_)) => {
+                            monitor.update(&status_manager.status_snapshot(), Utc::now());
+                        }
+                        Err(RecvError::Closed) => return,
+                    },
+                    _ = time::sleep(until_due.unwrap_or_default()), if until_due.is_some() => {
+                        let status = status_manager.status_snapshot();
+                        let Some(trigger) = monitor.check(&status, Utc::now()) else {
+                            continue;
+                        };
+
+                        match apply_action(monitor.action()).await {
+                            Ok(()) => {
+                                let update = LiveTradeUpdate::DeadManSwitchTriggered(Arc::new(trigger));
+                                let _ = update_tx.send(update);
+                            }
+                            Err(e) => {
+                                let retry_at = Utc::now()
+                                    + chrono::Duration::from_std(retry_interval)
+                                        .expect("restart interval must be valid");
+                                monitor.retry_at(retry_at);
+
+                                let e = LiveProcessRecoverableError::DeadManSwitch(e);
+                                status_manager.update(e.into());
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .into()
+    }
+
+    async fn recovery_loop(
+        self,
+        mut operator_events: OperatorEvents,
+        mut kill_switch_rx: mpsc::Receiver<KillSwitchTrip>,
+    ) -> LiveProcessFatalResult<()> {
         let mut shutdown_rx = self.shutdown_tx.subscribe();
 
         loop {
-            let live_process_error = tokio::select! {
-                Err(e) = self.operator_running.run(
+            let run_res = {
+                let operator_run = self.operator_running.run(
                     &self.config,
                     &self.trade_executor,
+                    &mut operator_events,
                     &self.status_manager,
                     &self.update_tx,
-                ) => e,
-                shutdown_res = shutdown_rx.recv() => {
-                    let Err(e) = shutdown_res else {
-                        return self.shutdown().await;
-                    };
+                );
+                tokio::pin!(operator_run);
+
+                let mut save_interval = self.config.operator_state_key().map(|_| {
+                    let save_period = self.config.operator_state_save_interval();
+                    time::interval_at(time::Instant::now() + save_period, save_period)
+                });
+
+                // The operator future is polled across iterations so that periodic state saves
+                // don't interrupt it.
+                loop {
+                    tokio::select! {
+                        Err(e) = &mut operator_run => break RunExit::Error(e),
+                        Some(_) = async { Some(save_interval.as_mut()?.tick().await) }, if save_interval.is_some() => {
+                            if let Err(e) = self.save_operator_state().await {
+                                break RunExit::Error(e.into());
+                            }
+                        }
+                        Some(trip) = kill_switch_rx.recv() => break RunExit::KillSwitch(trip),
+                        shutdown_res = shutdown_rx.recv() => {
+                            let Err(e) = shutdown_res else {
+                                break RunExit::Shutdown;
+                            };
 
-                    LiveProcessFatalError::ShutdownSignalRecv(e).into()
+                            break RunExit::Error(LiveProcessFatalError::ShutdownSignalRecv(e).into());
+                        }
+                    }
                 }
             };
 
+            let live_process_error = match run_res {
+                RunExit::Error(e) => e,
+                RunExit::Shutdown => return self.shutdown().await,
+                RunExit::KillSwitch(trip) => return self.trip_kill_switch(trip).await,
+            };
+
             match live_process_error {
                 LiveProcessError::Fatal(e) => {
                     self.status_manager.update(e.into());
@@ -168,6 +493,7 @@ impl<S: Signal> LiveProcess<S> {
 
             tokio::select! {
                 _ = time::sleep(self.config.restart_interval()) => {}
+                Some(trip) = kill_switch_rx.recv() => return self.trip_kill_switch(trip).await,
                 shutdown_res = shutdown_rx.recv() => {
                     let Err(e) = shutdown_res else {
                         return self.shutdown().await;
@@ -184,9 +510,184 @@ impl<S: Signal> LiveProcess<S> {
         }
     }
 
+    async fn save_operator_state(&self) -> result::Result<(), LiveProcessRecoverableError> {
+        let Some(key) = self.config.operator_state_key() else {
+            return Ok(());
+        };
+
+        let state = self
+            .operator_running
+            .save_state()
+            .map_err(LiveProcessRecoverableError::SaveOperatorState)?;
+
+        if let Some(state) = state {
+            self.db.key_values.set_value(key, &state).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Closes all isolated trades and the cross-margin position. Both closes are attempted even if
+    /// the first one fails.
+    async fn flatten(trade_executor: Arc<dyn TradeExecutor>) -> LiveProcessFatalResult<()> {
+        let isolated = trade_executor
+            .isolated_order_close_all()
+            .await
+            .err()
+            .map(Box::new);
+        let cross = trade_executor
+            .cross_order_close_position()
+            .await
+            .err()
+            .map(Box::new);
+
+        if isolated.is_none() && cross.is_none() {
+            return Ok(());
+        }
+
+        Err(LiveProcessFatalError::KillSwitchFlatten { isolated, cross })
+    }
+
+    /// Closes all positions and clears the pending flatten marker once they are closed, so that a
+    /// failed flatten is retried on restart.
+    async fn flatten_and_clear(
+        db: &Database,
+        kill_switch_keys: &KillSwitchKeys,
+        trade_executor: Arc<dyn TradeExecutor>,
+    ) -> LiveProcessFatalResult<()> {
+        Self::flatten(trade_executor).await?;
+
+        db.key_values
+            .remove_value(kill_switch_keys.flatten_pending())
+            .await
+            .map_err(LiveProcessFatalError::KillSwitchPersist)
+    }
+
+    /// Launches the trade executor only to close the positions left open when flattening failed
+    /// after a kill switch trigger, then shuts it down.
+    async fn resume_flatten(
+        db: &Database,
+        kill_switch_keys: &KillSwitchKeys,
+        sync_engine: SyncEngine,
+        trade_executor_launcher: ExecutorPending,
+    ) -> LiveProcessFatalResult<()> {
+        let sync_controller = sync_engine.start();
+        let mut executor_rx = trade_executor_launcher.update_receiver();
+
+        let flatten_res = match trade_executor_launcher.launch().await {
+            Ok(trade_executor) => {
+                let flatten_res =
+                    match Self::wait_for_executor_ready(&trade_executor, &mut executor_rx).await {
+                        Ok(()) => {
+                            Self::flatten_and_clear(
+                                db,
+                                kill_switch_keys,
+                                trade_executor.trade_executor(),
+                            )
+                            .await
+                        }
+                        Err(e) => Err(e),
+                    };
+
+                let shutdown_res = trade_executor
+                    .shutdown()
+                    .await
+                    .map_err(LiveProcessFatalError::ExecutorShutdownError);
+
+                flatten_res.and(shutdown_res)
+            }
+            Err(e) => Err(LiveProcessFatalError::LaunchExecutor(e)),
+        };
+
+        let sync_shutdown_res = sync_controller
+            .shutdown()
+            .await
+            .map_err(LiveProcessFatalError::SyncShutdown);
+
+        flatten_res.and(sync_shutdown_res)
+    }
+
+    async fn wait_for_executor_ready(
+        trade_executor: &ExecutorRunning,
+        executor_rx: &mut LiveTradeExecutorReceiver,
+    ) -> LiveProcessFatalResult<()> {
+        loop {
+            match trade_executor.status_snapshot().await {
+                LiveTradeExecutorStatus::Ready => return Ok(()),
+                LiveTradeExecutorStatus::NotReady(LiveTradeExecutorStatusNotReady::Terminated(
+                    e,
+                )) => return Err(LiveProcessFatalError::ExecutorProcessTerminated(e)),
+                LiveTradeExecutorStatus::NotReady(
+                    LiveTradeExecutorStatusNotReady::ShutdownInitiated
+                    | LiveTradeExecutorStatusNotReady::Shutdown,
+                ) => return Err(LiveProcessFatalError::ExecutorProcessShutdown),
+                LiveTradeExecutorStatus::NotReady(_) => {}
+            }
+
+            match executor_rx.recv().await {
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return Err(LiveProcessFatalError::ExecutorRecvClosed),
+            }
+        }
+    }
+
+    /// Persists the kill switch trigger, closes all positions, and shuts down the process.
+    async fn trip_kill_switch(self, trip: KillSwitchTrip) -> LiveProcessFatalResult<()> {
+        let status_manager = self.status_manager.clone();
+
+        // Persisted first, so trading doesn't resume on restart even if closing positions fails.
+        // The flatten is retried on restart until it succeeds
+        let persist_res = async {
+            self.db
+                .key_values
+                .set_value(self.kill_switch_keys.flatten_pending(), &[])
+                .await?;
+            self.db
+                .key_values
+                .set_value(self.kill_switch_keys.trip(), &trip.to_bytes())
+                .await
+        }
+        .await
+        .map_err(LiveProcessFatalError::KillSwitchPersist);
+
+        let flatten_res = Self::flatten_and_clear(
+            &self.db,
+            &self.kill_switch_keys,
+            self.trade_executor.trade_executor(),
+        )
+        .await;
+
+        let shutdown_res = self.shutdown().await;
+
+        match persist_res.and(flatten_res).and(shutdown_res) {
+            Ok(()) => status_manager.update(LiveTradeStatus::KillSwitchTriggered(Arc::new(trip))),
+            Err(e) => status_manager.update(e.into()),
+        }
+
+        Ok(())
+    }
+
     async fn shutdown(self) -> LiveProcessFatalResult<()> {
         self.executor_updates_handle.abort();
 
+        if let Some(handle) = &self.dead_man_switch_handle {
+            handle.abort();
+        }
+
+        // Invoked before saving the operator state, so that changes made by the hook are persisted
+        let on_shutdown_res = self
+            .operator_running
+            .on_shutdown()
+            .await
+            .map_err(LiveProcessFatalError::OperatorShutdownHook);
+
+        // Saved before shutting down the executor, so the persisted state matches the trades left
+        // running when no shutdown clean up is configured.
+        let save_state_res = self
+            .save_operator_state()
+            .await
+            .map_err(LiveProcessFatalError::ShutdownSaveOperatorState);
+
         let executor_shutdown_res = self
             .trade_executor
             .shutdown()
@@ -201,8 +702,75 @@ impl<S: Signal> LiveProcess<S> {
             .await
             .map_err(LiveProcessFatalError::SyncShutdown);
 
-        executor_shutdown_res
+        on_shutdown_res
+            .and(save_state_res)
+            .and(executor_shutdown_res)
             .and(operator_shutdown_res)
             .and(sync_shutdown_res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{super::super::core::Raw, *};
+
+    #[tokio::test]
+    async fn test_dead_man_switch_triggers_when_flapping() {
+        let (update_tx, _) = broadcast::channel::<LiveTradeUpdate<Raw>>(1_000);
+        let status_manager = LiveTradeStatusManager::new(update_tx.clone());
+        let mut live_rx = update_tx.subscribe();
+
+        let applied = Arc::new(AtomicUsize::new(0));
+        let applied_counter = applied.clone();
+
+        let _handle = LiveProcess::spawn_dead_man_switch(
+            DeadManSwitchMonitor::new(
+                chrono::Duration::milliseconds(300),
+                DeadManSwitchAction::Flatten,
+            ),
+            time::Duration::from_secs(1),
+            status_manager.clone(),
+            update_tx,
+            move |_| {
+                applied_counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(()) }
+            },
+        );
+
+        // Restart loop with runs much shorter than the grace period
+        let flapping = async {
+            loop {
+                let failed = LiveProcessRecoverableError::ExecutorRecvLagged { skipped: 1 };
+                status_manager.update(LiveTradeStatus::Failed(Arc::new(failed)));
+                time::sleep(time::Duration::from_millis(40)).await;
+                status_manager.update(LiveTradeStatus::Restarting);
+                time::sleep(time::Duration::from_millis(20)).await;
+                status_manager.update(LiveTradeStatus::Running);
+                time::sleep(time::Duration::from_millis(20)).await;
+            }
+        };
+
+        let trigger = async {
+            loop {
+                if let Ok(LiveTradeUpdate::DeadManSwitchTriggered(trigger)) = live_rx.recv().await {
+                    return trigger;
+                }
+            }
+        };
+
+        let trigger = tokio::select! {
+            trigger = trigger => trigger,
+            _ = flapping => unreachable!(),
+            _ = time::sleep(time::Duration::from_secs(5)) => panic!("dead man's switch not triggered"),
+        };
+
+        assert_eq!(trigger.action(), DeadManSwitchAction::Flatten);
+        assert!(
+            trigger.triggered_at() - trigger.unhealthy_since()
+                >= chrono::Duration::milliseconds(300)
+        );
+        assert_eq!(applied.load(Ordering::SeqCst), 1);
+    }
+}
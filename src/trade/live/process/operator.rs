@@ -1,36 +1,199 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use tokio::{sync::broadcast::error::RecvError, time};
 
+use lnm_sdk::rest::v3::RestClient;
+
 use crate::{
     db::{Database, models::OhlcCandleRow},
+    shared::{Lookback, LookbackCandles, OhlcResolution},
     signal::{LiveSignalController, LiveSignalEngine, LiveSignalStatus, LiveSignalUpdate, Signal},
     sync::{SyncReader, SyncStatus, SyncUpdate},
-    util::{DateTimeExt, Never},
+    util::{AbortOnDropHandle, DateTimeExt, Never},
 };
 
 use super::{
     super::{
-        super::core::{TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+        super::{
+            context::OperatorContext,
+            core::{PriceThreshold, TradeExecutor, WrappedRawOperator, WrappedSignalOperator},
+            error::{TradeCoreError, TradeCoreResult},
+            events::{OperatorEvent, TradeEventTracker},
+        },
         config::LiveProcessConfig,
         executor::{
-            LiveTradeExecutor,
+            ExecutorRunning,
             state::{LiveTradeExecutorStatus, LiveTradeExecutorStatusNotReady},
+            update::{LiveTradeExecutorReceiver, LiveTradeExecutorUpdate},
         },
         state::{LiveTradeStatus, LiveTradeStatusManager, LiveTradeTransmitter, LiveTradeUpdate},
     },
     error::{LiveProcessFatalError, LiveProcessFatalResult, LiveProcessRecoverableError, Result},
 };
 
+/// Interval between checks for a due funding settlement that wasn't synced yet.
+const FUNDING_SETTLEMENT_RETRY_INTERVAL: Duration = Duration::seconds(30);
+
+/// Interval between refreshes of the next funding rate published by the exchange ticker.
+const NEXT_FUNDING_RATE_REFRESH_INTERVAL: time::Duration = time::Duration::from_secs(300);
+
+/// Age after which a next funding rate that couldn't be refreshed is no longer provided.
+const NEXT_FUNDING_RATE_MAX_AGE: Duration = Duration::minutes(15);
+
+#[derive(Clone, Copy)]
+struct FetchedFundingRate {
+    fetched_at: DateTime<Utc>,
+    rate: f64,
+}
+
+/// Next funding rate published by the exchange ticker, refreshed in the background so that
+/// operator iterations don't wait on ticker requests.
+pub(in crate::trade::live) struct NextFundingRate {
+    latest: Arc<Mutex<Option<FetchedFundingRate>>>,
+    _refresh_handle: AbortOnDropHandle<()>,
+}
+
+impl NextFundingRate {
+    fn spawn(api_rest: Arc<RestClient>) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+
+        let refresh_handle = tokio::spawn({
+            let latest = latest.clone();
+            async move {
+                let mut interval = time::interval(NEXT_FUNDING_RATE_REFRESH_INTERVAL);
+                interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+                loop {
+                    interval.tick().await;
+
+                    // Failed requests are retried on the next refresh
+                    if let Ok(ticker) = api_rest.futures_data.get_ticker().await {
+                        *latest
+                            .lock()
+                            .expect("`NextFundingRate` mutex can't be poisoned") =
+                            Some(FetchedFundingRate {
+                                fetched_at: Utc::now(),
+                                rate: ticker.funding_rate(),
+                            });
+                    }
+                }
+            }
+        })
+        .into();
+
+        Self {
+            latest,
+            _refresh_handle: refresh_handle,
+        }
+    }
+
+    /// Returns the latest rate, unless it couldn't be refreshed recently.
+    fn get(&self, now: DateTime<Utc>) -> Option<f64> {
+        self.latest
+            .lock()
+            .expect("`NextFundingRate` mutex can't be poisoned")
+            .filter(|fetched| now - fetched.fetched_at <= NEXT_FUNDING_RATE_MAX_AGE)
+            .map(|fetched| fetched.rate)
+    }
+}
+
+/// Detects the events forwarded to the operator hooks from the trade executor updates and the
+/// synced funding settlements. Kept across operator restarts, so that trades closed in between are
+/// still reported.
+pub(in crate::trade::live) struct OperatorEvents {
+    db: Arc<Database>,
+    executor_rx: LiveTradeExecutorReceiver,
+    trade_events: TradeEventTracker,
+    last_settlement_time: Option<DateTime<Utc>>,
+    next_settlement_check: Option<DateTime<Utc>>,
+}
+
+impl OperatorEvents {
+    pub fn new(db: Arc<Database>, executor_rx: LiveTradeExecutorReceiver) -> Self {
+        Self {
+            db,
+            executor_rx,
+            trade_events: TradeEventTracker::default(),
+            last_settlement_time: None,
+            next_settlement_check: None,
+        }
+    }
+
+    /// Skips the executor updates received while the operator wasn't running. Events are detected
+    /// by comparing trading states, so none are lost.
+    pub fn resubscribe(&mut self) {
+        self.executor_rx = self.executor_rx.resubscribe();
+    }
+
+    /// Waits for the next executor update, returning the trade events it reveals. Cancel safe.
+    async fn recv(&mut self) -> Result<Vec<OperatorEvent>> {
+        match self.executor_rx.recv().await {
+            Ok(LiveTradeExecutorUpdate::TradingState(trading_state)) => {
+                let market_price = trading_state.market_price().as_f64();
+                Ok(self
+                    .trade_events
+                    .update(&trading_state, market_price, market_price))
+            }
+            Ok(_) => Ok(Vec::new()),
+            // Skipped trading states are caught up on the next one
+            Err(RecvError::Lagged(_)) => Ok(Vec::new()),
+            Err(RecvError::Closed) => Err(LiveProcessFatalError::ExecutorRecvClosed.into()),
+        }
+    }
+
+    /// Returns the funding settlement that took place since the last check, if any. The database
+    /// is only queried once a new settlement is due.
+    async fn funding_settlement(&mut self, now: DateTime<Utc>) -> Result<Option<OperatorEvent>> {
+        let is_seeded = match self.next_settlement_check {
+            Some(next_check) if now < next_check => return Ok(None),
+            Some(_) => true,
+            None => false,
+        };
+
+        let latest = self
+            .db
+            .funding_settlements
+            .get_latest_settlements(now, 1)
+            .await
+            .map_err(LiveProcessRecoverableError::Db)?
+            .pop();
+
+        match latest {
+            Some(settlement)
+                if self
+                    .last_settlement_time
+                    .is_none_or(|last_time| settlement.time > last_time) =>
+            {
+                self.last_settlement_time = Some(settlement.time);
+                self.next_settlement_check = Some(
+                    (settlement.time + Duration::nanoseconds(1)).ceil_funding_settlement_time(),
+                );
+
+                Ok(is_seeded.then_some(OperatorEvent::FundingSettlement(settlement)))
+            }
+            _ => {
+                // The due settlement wasn't synced yet
+                self.next_settlement_check = Some(now + FUNDING_SETTLEMENT_RETRY_INTERVAL);
+                Ok(None)
+            }
+        }
+    }
+}
+
 /// Pending operator state before starting.
 pub(in crate::trade::live) enum OperatorPending<S: Signal> {
     Signal {
         signal_engine: LiveSignalEngine<S>,
+        sync_reader: Arc<dyn SyncReader>,
         signal_operator: WrappedSignalOperator<S>,
     },
     Raw {
         db: Arc<Database>,
+        api_rest: Arc<RestClient>,
         sync_reader: Arc<dyn SyncReader>,
         raw_operator: WrappedRawOperator,
     },
@@ -39,26 +202,40 @@ pub(in crate::trade::live) enum OperatorPending<S: Signal> {
 impl<S: Signal> OperatorPending<S> {
     pub fn signal(
         signal_engine: LiveSignalEngine<S>,
+        sync_reader: Arc<dyn SyncReader>,
         signal_operator: WrappedSignalOperator<S>,
     ) -> Self {
         Self::Signal {
             signal_engine,
+            sync_reader,
             signal_operator,
         }
     }
 
     pub fn raw(
         db: Arc<Database>,
+        api_rest: Arc<RestClient>,
         sync_reader: Arc<dyn SyncReader>,
         raw_operator: WrappedRawOperator,
     ) -> Self {
         Self::Raw {
             db,
+            api_rest,
             sync_reader,
             raw_operator,
         }
     }
 
+    pub fn restore_state(&mut self, state: &[u8]) -> LiveProcessFatalResult<()> {
+        match self {
+            OperatorPending::Signal {
+                signal_operator, ..
+            } => signal_operator.restore_state(state),
+            OperatorPending::Raw { raw_operator, .. } => raw_operator.restore_state(state),
+        }
+        .map_err(LiveProcessFatalError::RestoreOperatorState)
+    }
+
     pub fn start(
         self,
         trade_executor: Arc<dyn TradeExecutor>,
@@ -66,6 +243,7 @@ impl<S: Signal> OperatorPending<S> {
         match self {
             OperatorPending::Signal {
                 signal_engine,
+                sync_reader,
                 mut signal_operator,
             } => {
                 signal_operator
@@ -76,11 +254,13 @@ impl<S: Signal> OperatorPending<S> {
 
                 Ok(OperatorRunning::Signal {
                     signal_controller,
+                    sync_reader,
                     signal_operator,
                 })
             }
             OperatorPending::Raw {
                 db,
+                api_rest,
                 sync_reader,
                 mut raw_operator,
             } => {
@@ -90,6 +270,7 @@ impl<S: Signal> OperatorPending<S> {
 
                 Ok(OperatorRunning::Raw {
                     db,
+                    next_funding_rate: NextFundingRate::spawn(api_rest),
                     sync_reader,
                     raw_operator,
                 })
@@ -102,10 +283,12 @@ impl<S: Signal> OperatorPending<S> {
 pub(in crate::trade::live) enum OperatorRunning<S: Signal> {
     Signal {
         signal_controller: Arc<LiveSignalController<S>>,
+        sync_reader: Arc<dyn SyncReader>,
         signal_operator: WrappedSignalOperator<S>,
     },
     Raw {
         db: Arc<Database>,
+        next_funding_rate: NextFundingRate,
         sync_reader: Arc<dyn SyncReader>,
         raw_operator: WrappedRawOperator,
     },
@@ -115,19 +298,25 @@ impl<S: Signal> OperatorRunning<S> {
     pub async fn run(
         &self,
         config: &LiveProcessConfig,
-        trade_executor: &Arc<LiveTradeExecutor>,
+        trade_executor: &ExecutorRunning,
+        operator_events: &mut OperatorEvents,
         status_manager: &Arc<LiveTradeStatusManager<S>>,
         update_tx: &LiveTradeTransmitter<S>,
     ) -> Result<Never> {
+        operator_events.resubscribe();
+
         match self {
             OperatorRunning::Signal {
                 signal_controller,
+                sync_reader,
                 signal_operator,
             } => {
-                Self::run_signal(
+                self.run_signal(
                     signal_controller,
+                    sync_reader,
                     signal_operator,
                     trade_executor,
+                    operator_events,
                     status_manager,
                     update_tx,
                 )
@@ -135,15 +324,18 @@ impl<S: Signal> OperatorRunning<S> {
             }
             OperatorRunning::Raw {
                 db,
+                next_funding_rate,
                 sync_reader,
                 raw_operator,
             } => {
-                Self::run_raw(
+                self.run_raw(
                     db,
+                    next_funding_rate,
                     sync_reader,
                     raw_operator,
                     config,
                     trade_executor,
+                    operator_events,
                     status_manager,
                 )
                 .await
@@ -151,6 +343,60 @@ impl<S: Signal> OperatorRunning<S> {
         }
     }
 
+    pub async fn on_start(&self) -> TradeCoreResult<()> {
+        match self {
+            OperatorRunning::Signal {
+                signal_operator, ..
+            } => signal_operator.on_start().await,
+            OperatorRunning::Raw { raw_operator, .. } => raw_operator.on_start().await,
+        }
+    }
+
+    async fn on_event(&self, event: &OperatorEvent) -> TradeCoreResult<()> {
+        match self {
+            OperatorRunning::Signal {
+                signal_operator, ..
+            } => signal_operator.on_event(event).await,
+            OperatorRunning::Raw { raw_operator, .. } => raw_operator.on_event(event).await,
+        }
+    }
+
+    pub async fn on_shutdown(&self) -> TradeCoreResult<()> {
+        match self {
+            OperatorRunning::Signal {
+                signal_operator, ..
+            } => signal_operator.on_shutdown().await,
+            OperatorRunning::Raw { raw_operator, .. } => raw_operator.on_shutdown().await,
+        }
+    }
+
+    /// Forwards the given events to the operator hooks, along with the funding settlement that
+    /// took place since the last check, if any.
+    async fn dispatch_events(
+        &self,
+        operator_events: &mut OperatorEvents,
+        mut events: Vec<OperatorEvent>,
+    ) -> Result<()> {
+        events.extend(operator_events.funding_settlement(Utc::now()).await?);
+
+        for event in events {
+            self.on_event(&event)
+                .await
+                .map_err(LiveProcessRecoverableError::OperatorError)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save_state(&self) -> std::result::Result<Option<Vec<u8>>, TradeCoreError> {
+        match self {
+            OperatorRunning::Signal {
+                signal_operator, ..
+            } => signal_operator.save_state(),
+            OperatorRunning::Raw { raw_operator, .. } => raw_operator.save_state(),
+        }
+    }
+
     pub async fn shutdown(&self) -> LiveProcessFatalResult<()> {
         match self {
             OperatorRunning::Signal {
@@ -163,16 +409,114 @@ impl<S: Signal> OperatorRunning<S> {
         }
     }
 
+    /// Returns whether the trade executor is ready, updating the status accordingly. Errors if the
+    /// executor was terminated or shut down.
+    async fn executor_ready(
+        trade_executor: &ExecutorRunning,
+        status_manager: &Arc<LiveTradeStatusManager<S>>,
+    ) -> Result<bool> {
+        match trade_executor.status_snapshot().await {
+            LiveTradeExecutorStatus::Ready => {
+                status_manager.update_if_not_running(LiveTradeStatus::Running);
+                Ok(true)
+            }
+            LiveTradeExecutorStatus::NotReady(tex_status_not_ready) => match tex_status_not_ready {
+                LiveTradeExecutorStatusNotReady::Terminated(e) => {
+                    Err(LiveProcessFatalError::ExecutorProcessTerminated(e.clone()).into())
+                }
+                LiveTradeExecutorStatusNotReady::ShutdownInitiated
+                | LiveTradeExecutorStatusNotReady::Shutdown => {
+                    Err(LiveProcessFatalError::ExecutorProcessShutdown.into())
+                }
+                LiveTradeExecutorStatusNotReady::Starting
+                | LiveTradeExecutorStatusNotReady::WaitingForSync(_)
+                | LiveTradeExecutorStatusNotReady::Failed(_) => {
+                    status_manager.update(LiveTradeStatus::WaitingTradeExecutor(
+                        tex_status_not_ready.clone(),
+                    ));
+                    Ok(false)
+                }
+            },
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn run_signal(
+        &self,
         signal_controller: &Arc<LiveSignalController<S>>,
+        sync_reader: &Arc<dyn SyncReader>,
         signal_operator: &WrappedSignalOperator<S>,
-        trade_executor: &Arc<LiveTradeExecutor>,
+        trade_executor: &ExecutorRunning,
+        operator_events: &mut OperatorEvents,
         status_manager: &Arc<LiveTradeStatusManager<S>>,
         update_tx: &LiveTradeTransmitter<S>,
     ) -> Result<Never> {
         let mut signal_rx = signal_controller.update_receiver();
+        let mut sync_rx = sync_reader.update_receiver();
+
+        // Queried on the first price tick, and after each processed signal and threshold hook call
+        let mut price_thresholds: Option<Vec<PriceThreshold>> = None;
+        // Set when price ticks were skipped, so the next tick is handled as reaching a threshold
+        let mut ticks_skipped = false;
+
         loop {
-            match signal_rx.recv().await {
+            let signal_recv_res = tokio::select! {
+                signal_recv_res = signal_rx.recv() => signal_recv_res,
+                events_res = operator_events.recv() => {
+                    self.dispatch_events(operator_events, events_res?).await?;
+                    continue;
+                }
+                sync_update_res = sync_rx.recv() => {
+                    let tick = match sync_update_res {
+                        Ok(SyncUpdate::PriceTick(tick)) => tick,
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => {
+                            ticks_skipped = true;
+                            continue;
+                        }
+                        Err(RecvError::Closed) => {
+                            return Err(LiveProcessFatalError::SyncRecvClosed.into());
+                        }
+                    };
+
+                    let Some(thresholds) = &price_thresholds else {
+                        price_thresholds = Some(
+                            signal_operator
+                                .price_thresholds(tick.last_price)
+                                .map_err(LiveProcessRecoverableError::OperatorError)?,
+                        );
+                        continue;
+                    };
+
+                    let threshold_reached = (ticks_skipped && !thresholds.is_empty())
+                        || thresholds.iter().any(|threshold| {
+                            threshold.is_reached(tick.last_price, tick.last_price)
+                        });
+                    ticks_skipped = false;
+
+                    if !threshold_reached
+                        || !Self::executor_ready(trade_executor, status_manager).await?
+                    {
+                        continue;
+                    }
+
+                    self.dispatch_events(operator_events, Vec::new()).await?;
+
+                    signal_operator
+                        .on_price_threshold(tick.last_price)
+                        .await
+                        .map_err(LiveProcessRecoverableError::OperatorError)?;
+
+                    price_thresholds = Some(
+                        signal_operator
+                            .price_thresholds(tick.last_price)
+                            .map_err(LiveProcessRecoverableError::OperatorError)?,
+                    );
+                    continue;
+                }
+            };
+
+            match signal_recv_res {
                 Ok(signal_update) => match signal_update {
                     LiveSignalUpdate::Status(signal_status) => match signal_status {
                         LiveSignalStatus::NotRunning(signal_status_not_running) => {
@@ -190,50 +534,23 @@ impl<S: Signal> OperatorRunning<S> {
                             return Err(LiveProcessFatalError::LiveSignalProcessShutdown.into());
                         }
                     },
+                    LiveSignalUpdate::PersistenceFailed { .. } => {}
                     LiveSignalUpdate::Signal(new_signal) => {
-                        let tex_state = trade_executor.state_snapshot().await;
-                        let tex_status = tex_state.status();
-
-                        match tex_status {
-                            LiveTradeExecutorStatus::Ready => {
-                                status_manager.update_if_not_running(LiveTradeStatus::Running);
-                            }
-                            LiveTradeExecutorStatus::NotReady(tex_status_not_ready) => {
-                                match tex_status_not_ready {
-                                    LiveTradeExecutorStatusNotReady::Terminated(e) => {
-                                        return Err(
-                                            LiveProcessFatalError::ExecutorProcessTerminated(
-                                                e.clone(),
-                                            )
-                                            .into(),
-                                        );
-                                    }
-                                    LiveTradeExecutorStatusNotReady::ShutdownInitiated
-                                    | LiveTradeExecutorStatusNotReady::Shutdown => {
-                                        return Err(
-                                            LiveProcessFatalError::ExecutorProcessShutdown.into()
-                                        );
-                                    }
-                                    LiveTradeExecutorStatusNotReady::Starting
-                                    | LiveTradeExecutorStatusNotReady::WaitingForSync(_)
-                                    | LiveTradeExecutorStatusNotReady::Failed(_) => {
-                                        status_manager.update(
-                                            LiveTradeStatus::WaitingTradeExecutor(
-                                                tex_status_not_ready.clone(),
-                                            ),
-                                        );
-                                        continue;
-                                    }
-                                }
-                            }
+                        if !Self::executor_ready(trade_executor, status_manager).await? {
+                            continue;
                         }
 
+                        self.dispatch_events(operator_events, Vec::new()).await?;
+
                         let _ = update_tx.send(LiveTradeUpdate::Signal(new_signal.clone()));
 
                         signal_operator
                             .process_signal(&new_signal)
                             .await
                             .map_err(LiveProcessRecoverableError::OperatorError)?;
+
+                        // Re-queried on the next price tick, since the signal may change them
+                        price_thresholds = None;
                     }
                 },
                 Err(RecvError::Lagged(skipped)) => {
@@ -246,28 +563,100 @@ impl<S: Signal> OperatorRunning<S> {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn run_raw(
+        &self,
         db: &Arc<Database>,
+        next_funding_rate: &NextFundingRate,
         sync_reader: &Arc<dyn SyncReader>,
         raw_operator: &WrappedRawOperator,
         config: &LiveProcessConfig,
-        trade_executor: &Arc<LiveTradeExecutor>,
+        trade_executor: &ExecutorRunning,
+        operator_events: &mut OperatorEvents,
         status_manager: &Arc<LiveTradeStatusManager<S>>,
     ) -> Result<Never> {
         let mut last_eval = Utc::now();
+        let mut sync_rx = sync_reader.update_receiver();
 
-        loop {
-            let min_iteration_interval = raw_operator
-                .min_iteration_interval()
-                .map_err(LiveProcessRecoverableError::OperatorError)?
-                .as_duration();
+        let mut candle_close_tracker = raw_operator
+            .iteration_schedule()
+            .map_err(LiveProcessRecoverableError::OperatorError)?
+            .candle_close_tracker(
+                &raw_operator
+                    .lookbacks()
+                    .map_err(LiveProcessRecoverableError::OperatorError)?,
+                last_eval,
+            );
+
+        // Queried on the first price tick, and after each iteration
+        let mut price_thresholds: Option<Vec<PriceThreshold>> = None;
 
-            let target_exec = (last_eval + min_iteration_interval).ceil_sec();
+        loop {
+            let target_exec = match &candle_close_tracker {
+                Some(tracker) => tracker.next_due(),
+                None => {
+                    let min_iteration_interval = raw_operator
+                        .min_iteration_interval()
+                        .map_err(LiveProcessRecoverableError::OperatorError)?
+                        .as_duration();
+
+                    (last_eval + min_iteration_interval).ceil_sec()
+                }
+            };
             let now = Utc::now();
 
             if now < target_exec {
                 let wait_duration = (target_exec - now).to_std().expect("valid duration");
-                time::sleep(wait_duration).await;
+                let sleep = time::sleep(wait_duration);
+                tokio::pin!(sleep);
+
+                // Events are forwarded to the operator hooks as soon as they are detected, and
+                // price ticks reaching any of the operator's thresholds trigger an early iteration
+                loop {
+                    tokio::select! {
+                        _ = &mut sleep => break,
+                        events_res = operator_events.recv() => {
+                            self.dispatch_events(operator_events, events_res?).await?;
+                        }
+                        sync_update_res = sync_rx.recv() => {
+                            let tick = match sync_update_res {
+                                Ok(SyncUpdate::PriceTick(tick)) => tick,
+                                Ok(_) => continue,
+                                Err(RecvError::Lagged(_)) => {
+                                    // Skipped ticks may have reached a threshold, so an early
+                                    // iteration is triggered rather than risking a missed one
+                                    if price_thresholds
+                                        .as_ref()
+                                        .is_some_and(|thresholds| !thresholds.is_empty())
+                                    {
+                                        break;
+                                    }
+                                    continue;
+                                }
+                                Err(RecvError::Closed) => {
+                                    return Err(LiveProcessFatalError::SyncRecvClosed.into());
+                                }
+                            };
+
+                            match &price_thresholds {
+                                Some(thresholds) => {
+                                    if thresholds.iter().any(|threshold| {
+                                        threshold.is_reached(tick.last_price, tick.last_price)
+                                    }) {
+                                        break;
+                                    }
+                                }
+                                None => {
+                                    price_thresholds = Some(
+                                        raw_operator
+                                            .price_thresholds(tick.last_price)
+                                            .map_err(LiveProcessRecoverableError::OperatorError)?,
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
             }
 
             if let SyncStatus::NotSynced(sync_status_not_synced) = sync_reader.status_snapshot() {
@@ -279,43 +668,82 @@ impl<S: Signal> OperatorRunning<S> {
 
             last_eval = Utc::now();
 
-            let tex_state = trade_executor.state_snapshot().await;
-            let tex_status = tex_state.status();
+            if let Some(tracker) = &mut candle_close_tracker {
+                tracker.poll(last_eval);
+            }
 
-            match tex_status {
-                LiveTradeExecutorStatus::Ready => {
-                    status_manager.update_if_not_running(LiveTradeStatus::Running);
-                }
-                LiveTradeExecutorStatus::NotReady(tex_status_not_ready) => {
-                    match tex_status_not_ready {
-                        LiveTradeExecutorStatusNotReady::Terminated(e) => {
-                            return Err(LiveProcessFatalError::ExecutorProcessTerminated(
-                                e.clone(),
-                            )
-                            .into());
-                        }
-                        LiveTradeExecutorStatusNotReady::ShutdownInitiated
-                        | LiveTradeExecutorStatusNotReady::Shutdown => {
-                            return Err(LiveProcessFatalError::ExecutorProcessShutdown.into());
-                        }
-                        LiveTradeExecutorStatusNotReady::Starting
-                        | LiveTradeExecutorStatusNotReady::WaitingForSync(_)
-                        | LiveTradeExecutorStatusNotReady::Failed(_) => {
-                            status_manager.update(LiveTradeStatus::WaitingTradeExecutor(
-                                tex_status_not_ready.clone(),
-                            ));
-                            continue;
-                        }
-                    }
-                }
+            if !Self::executor_ready(trade_executor, status_manager).await? {
+                continue;
             }
 
-            let candles = Self::fetch_candles(db, raw_operator, last_eval).await?;
+            self.dispatch_events(operator_events, Vec::new()).await?;
+
+            let lookbacks = raw_operator
+                .lookbacks()
+                .map_err(LiveProcessRecoverableError::OperatorError)?;
+
+            let candles_to_fetch = match &candle_close_tracker {
+                // The in-progress candle is excluded, so one extra is fetched
+                Some(_) => lookbacks
+                    .iter()
+                    .map(Lookback::with_in_progress_candle)
+                    .collect(),
+                None => lookbacks.clone(),
+            };
+
+            let candle_buffers = Self::fetch_candles(db, &candles_to_fetch, last_eval).await?;
+
+            let candles = LookbackCandles::from_buffers(&lookbacks, |resolution| {
+                let buffer = candle_buffers
+                    .get(&resolution)
+                    .map(|v| v.as_slice())
+                    .expect("resolution must be available");
+
+                match &candle_close_tracker {
+                    Some(tracker) => tracker.closed_candles(resolution, buffer),
+                    None => buffer,
+                }
+            });
+
+            let trading_state = trade_executor
+                .trade_executor()
+                .trading_state()
+                .await
+                .map_err(LiveProcessRecoverableError::TradingState)?;
+
+            let funding_settlements_lookback = raw_operator
+                .funding_settlements_lookback()
+                .map_err(LiveProcessRecoverableError::OperatorError)?;
+
+            let settlement_history = db
+                .funding_settlements
+                .get_latest_settlements(
+                    last_eval,
+                    funding_settlements_lookback.map_or(1, |lookback| lookback.as_u32()),
+                )
+                .await
+                .map_err(LiveProcessRecoverableError::Db)?;
+
+            let context = OperatorContext::new(
+                last_eval,
+                trading_state.last_tick_time(),
+                trading_state.market_price().as_f64(),
+                &settlement_history,
+            )
+            .with_funding_settlements_lookback(funding_settlements_lookback)
+            .with_next_funding_rate(next_funding_rate.get(last_eval))
+            .with_candles(candles);
 
             raw_operator
-                .iterate(candles.as_slice())
+                .iterate(&context)
                 .await
                 .map_err(LiveProcessRecoverableError::OperatorError)?;
+
+            price_thresholds = Some(
+                raw_operator
+                    .price_thresholds(context.last_price())
+                    .map_err(LiveProcessRecoverableError::OperatorError)?,
+            );
         }
     }
 
@@ -384,25 +812,25 @@ impl<S: Signal> OperatorRunning<S> {
 
     async fn fetch_candles(
         db: &Arc<Database>,
-        raw_operator: &WrappedRawOperator,
+        lookbacks: &[Lookback],
         now: DateTime<Utc>,
-    ) -> Result<Vec<OhlcCandleRow>> {
-        let lookback = raw_operator
-            .lookback()
-            .map_err(LiveProcessRecoverableError::OperatorError)?;
+    ) -> Result<HashMap<OhlcResolution, Vec<OhlcCandleRow>>> {
+        let mut candle_buffers = HashMap::new();
 
-        if let Some(lookback) = lookback {
+        for lookback in lookbacks {
             let resolution = lookback.resolution();
             let current_bucket = now.floor_to_resolution(resolution);
             let from = current_bucket.step_back_candles(resolution, lookback.period().as_u32() - 1);
 
-            db.ohlc_candles
+            let candles = db
+                .ohlc_candles
                 .get_candles_consolidated(from, now, resolution)
                 .await
-                .map_err(LiveProcessRecoverableError::Db)
-                .map_err(Into::into)
-        } else {
-            Ok(Vec::new())
+                .map_err(LiveProcessRecoverableError::Db)?;
+
+            candle_buffers.insert(resolution, candles);
         }
+
+        Ok(candle_buffers)
     }
 }
@@ -5,16 +5,19 @@ use std::{
 
 use tokio::sync::broadcast;
 
-use lnm_sdk::rest::v3::models::Trade;
-
 use crate::{
     signal::{LiveSignalStatusNotRunning, Signal},
     sync::SyncStatusNotSynced,
 };
 
 use super::{
-    super::core::TradingState,
-    executor::{state::LiveTradeExecutorStatusNotReady, update::LiveTradeExecutorAction},
+    super::core::{TradeClosed, TradingState},
+    dead_man_switch::DeadManSwitchTrigger,
+    executor::{
+        error::LiveTradeExecutorWarning, state::LiveTradeExecutorStatusNotReady,
+        update::LiveTradeExecutorAction,
+    },
+    kill_switch::KillSwitchTrip,
     process::error::{LiveProcessFatalError, LiveProcessRecoverableError},
 };
 
@@ -43,6 +46,9 @@ pub enum LiveTradeStatus {
     Shutdown,
     /// Live trading process encountered a fatal error and terminated.
     Terminated(Arc<LiveProcessFatalError>),
+    /// The kill switch was triggered. Positions were closed and the live trading process was
+    /// stopped. Trading will not resume until the kill switch is re-armed.
+    KillSwitchTriggered(Arc<KillSwitchTrip>),
 }
 
 impl PartialEq for LiveTradeStatus {
@@ -59,15 +65,20 @@ impl PartialEq for LiveTradeStatus {
             (Self::WaitingTradeExecutor(a), Self::WaitingTradeExecutor(b)) => a == b,
             (Self::Failed(a), Self::Failed(b)) => Arc::ptr_eq(a, b),
             (Self::Terminated(a), Self::Terminated(b)) => Arc::ptr_eq(a, b),
+            (Self::KillSwitchTriggered(a), Self::KillSwitchTriggered(b)) => Arc::ptr_eq(a, b),
             _ => false,
         }
     }
 }
 
 impl LiveTradeStatus {
-    /// Returns `true` if the live trade process has stopped (either shut down or terminated).
+    /// Returns `true` if the live trade process has stopped (either shut down, terminated, or
+    /// stopped by the kill switch).
     pub fn is_stopped(&self) -> bool {
-        matches!(self, Self::Shutdown | Self::Terminated(_))
+        matches!(
+            self,
+            Self::Shutdown | Self::Terminated(_) | Self::KillSwitchTriggered(_)
+        )
     }
 }
 
@@ -87,6 +98,7 @@ impl fmt::Display for LiveTradeStatus {
             Self::ShutdownInitiated => write!(f, "Shutdown initiated"),
             Self::Shutdown => write!(f, "Shutdown"),
             Self::Terminated(error) => write!(f, "Terminated: {error}"),
+            Self::KillSwitchTriggered(trip) => write!(f, "Kill switch triggered: {trip}"),
         }
     }
 }
@@ -110,7 +122,7 @@ impl From<LiveProcessFatalError> for LiveTradeStatus {
 }
 
 /// Update events emitted during live trading including status changes, signals, executor actions,
-/// trading state, and closed trades.
+/// trading state, closed trades, and dead man's switch triggers.
 #[derive(Clone)]
 pub enum LiveTradeUpdate<S: Signal> {
     /// Live trading status changed.
@@ -122,7 +134,12 @@ pub enum LiveTradeUpdate<S: Signal> {
     /// The trading state was updated.
     TradingState(TradingState),
     /// A trade was closed.
-    ClosedTrade(Trade),
+    ClosedTrade(Arc<dyn TradeClosed>),
+    /// The trade executor reported a non-fatal issue.
+    ExecutorWarning(Arc<LiveTradeExecutorWarning>),
+    /// The dead man's switch was triggered after the live trading process remained unhealthy
+    /// for longer than the configured grace period, and its action was applied.
+    DeadManSwitchTriggered(Arc<DeadManSwitchTrigger>),
 }
 
 impl<S: Signal> From<LiveTradeStatus> for LiveTradeUpdate<S> {
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use super::{
+    core::TradeExecutor,
+    risk::{RiskLimits, RiskManager, RiskPersistence},
+};
+
+/// Decorator layered around the [`TradeExecutor`] handed to an operator, allowing cross-cutting
+/// behaviour (action logging, risk checks, rate limiting, dry-run, metrics, tagging...) to be added
+/// without changing operator code.
+///
+/// Middleware is registered on the engines via
+/// [`BacktestEngine::with_middleware`](crate::trade::BacktestEngine::with_middleware),
+/// [`BacktestParallelEngine::with_middleware`](crate::trade::BacktestParallelEngine::with_middleware)
+/// and [`LiveTradeEngine::with_middleware`](crate::trade::LiveTradeEngine::with_middleware).
+/// [`wrap`](Self::wrap) is called once for every executor handed to an operator, so a parallel
+/// backtest wraps each operator's executor separately.
+///
+/// Implemented for closures taking and returning an `Arc<dyn TradeExecutor>`, and for
+/// [`RiskLimits`], which wraps the executor in a [`RiskManager`].
+pub trait TradeExecutorMiddleware: Send + Sync + 'static {
+    /// Wraps the given executor, returning the executor passed to the next layer.
+    fn wrap(&self, executor: Arc<dyn TradeExecutor>) -> Arc<dyn TradeExecutor>;
+}
+
+impl<F> TradeExecutorMiddleware for F
+where
+    F: Fn(Arc<dyn TradeExecutor>) -> Arc<dyn TradeExecutor> + Send + Sync + 'static,
+{
+    fn wrap(&self, executor: Arc<dyn TradeExecutor>) -> Arc<dyn TradeExecutor> {
+        self(executor)
+    }
+}
+
+impl TradeExecutorMiddleware for RiskLimits {
+    fn wrap(&self, executor: Arc<dyn TradeExecutor>) -> Arc<dyn TradeExecutor> {
+        RiskManager::new(executor, self.clone())
+    }
+}
+
+/// Builds the executor handed to an operator. The configured risk limits (if any) are applied
+/// closest to the executor, persisting their net value reference points if `risk_persistence` is
+/// set, followed by the middleware in reverse order, so that the first middleware in the list is
+/// the outermost layer and the first one called by the operator.
+pub(super) fn wrap_executor(
+    executor: Arc<dyn TradeExecutor>,
+    risk_limits: Option<&RiskLimits>,
+    risk_persistence: Option<RiskPersistence>,
+    middleware: &[Arc<dyn TradeExecutorMiddleware>],
+) -> Arc<dyn TradeExecutor> {
+    let executor = match risk_limits {
+        Some(limits) => RiskManager::with_persistence(executor, limits.clone(), risk_persistence),
+        None => executor,
+    };
+
+    middleware
+        .iter()
+        .rev()
+        .fold(executor, |executor, layer| layer.wrap(executor))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::DateTime;
+
+    use lnm_sdk::rest::v3::models::{Leverage, OrderQuantity, TradeSide};
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        error::{RiskLimitError, TradeExecutorError},
+        trade::{BacktestConfig, IsolatedOrderRequest, backtest::executor::SimulatedTradeExecutor},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wrap_executor_layers_middleware_in_order() {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let start_candle = OhlcCandleRow::new_simple(time, 100_000., 1_000);
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 10_000_000);
+
+        let wrapped_layers = Arc::new(Mutex::new(Vec::new()));
+        let recording_layer = |name: &'static str| -> Arc<dyn TradeExecutorMiddleware> {
+            let wrapped_layers = wrapped_layers.clone();
+            Arc::new(move |executor: Arc<dyn TradeExecutor>| {
+                wrapped_layers.lock().unwrap().push(name);
+                executor
+            })
+        };
+
+        let middleware = vec![
+            recording_layer("outer"),
+            Arc::new(RiskLimits::default().with_max_running_trades(Some(1))) as _,
+            recording_layer("inner"),
+        ];
+
+        let operator_executor = wrap_executor(executor, None, None, &middleware);
+
+        assert_eq!(*wrapped_layers.lock().unwrap(), vec!["inner", "outer"]);
+
+        let request = || {
+            IsolatedOrderRequest::market(
+                TradeSide::Buy,
+                OrderQuantity::try_from(1_000).unwrap().into(),
+                Leverage::try_from(2).unwrap(),
+            )
+        };
+
+        operator_executor.isolated_order(request()).await.unwrap();
+
+        let err = operator_executor
+            .isolated_order(request())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxRunningTrades { max: 1 })
+        ));
+    }
+}
@@ -1,33 +1,49 @@
 pub(crate) mod backtest;
+mod context;
 mod core;
 pub(crate) mod error;
+mod events;
 pub(crate) mod live;
+mod middleware;
+mod parity;
+mod risk;
+mod sizing;
 
 pub use backtest::{
-    config::{BacktestConfig, MIN_BUFFER_SIZE},
+    config::{BacktestConfig, MIN_BUFFER_SIZE, SignalResearchConfig},
     parallel::{controller::BacktestParallelController, engine::BacktestParallelEngine},
+    research::{ForwardReturnStats, SignalClassStats, SignalResearchEngine, SignalResearchReport},
     single::{controller::BacktestController, engine::BacktestEngine},
     state::{
         BacktestParallelReceiver, BacktestParallelUpdate, BacktestReceiver, BacktestStatus,
         BacktestUpdate,
     },
 };
+pub use context::OperatorContext;
 pub use core::{
     ClosedTradeHistory, CrossOrderRequest, CrossPositionCore, DynRunningTradesMap,
-    IsolatedOrderRequest, Raw, RawOperator, RunningTradesMap, SignalOperator, Stoploss,
-    TradeClosed, TradeCore, TradeExecutor, TradeReference, TradeRunning, TradeTrailingStoploss,
-    TradingState,
+    IsolatedOrderRequest, PriceThreshold, Raw, RawOperator, RunningTradesMap, SignalOperator,
+    Stoploss, TradeClosed, TradeCore, TradeExecutor, TradeReference, TradeRunning,
+    TradeTrailingStoploss, TradingState,
 };
+pub use events::TradeCloseReason;
 pub use live::{
     config::{LiveTradeConfig, LiveTradeExecutorConfig},
+    dead_man_switch::{DeadManSwitchAction, DeadManSwitchTrigger},
     engine::{LiveTradeController, LiveTradeEngine},
     executor::{
         LiveTradeExecutor, LiveTradeExecutorLauncher,
         state::{LiveTradeExecutorStatus, LiveTradeExecutorStatusNotReady},
         update::{LiveTradeExecutorAction, LiveTradeExecutorReceiver, LiveTradeExecutorUpdate},
     },
+    kill_switch::KillSwitchTrip,
     state::{LiveTradeReader, LiveTradeReceiver, LiveTradeStatus, LiveTradeUpdate},
 };
+pub use middleware::TradeExecutorMiddleware;
+pub use parity::{LiveSessionRecord, ParityConfig, ParityReport, ParityTrade, TradeParity};
+pub use risk::{RiskBreachAction, RiskLimits, RiskManager};
+pub use sizing::{CrossPositionSize, IsolatedPositionSize, PositionSizer, SizingMode};
+
 pub use lnm_sdk::rest::v3::{
     error::{CrossExposureValidationError, CrossQuantityValidationError},
     models::{CrossExposure, CrossExposureRunning, CrossQuantity},
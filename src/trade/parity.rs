@@ -0,0 +1,1005 @@
+use std::{fmt, sync::Arc};
+
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{OrderQuantity, Price, TradeSide};
+
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{signal::Signal, util::DateTimeExt};
+
+use super::{
+    backtest::{
+        error::BacktestError,
+        single::engine::BacktestEngine,
+        state::{BacktestStatus, BacktestUpdate},
+    },
+    core::{TradeClosed, TradingState},
+    live::{executor::update::LiveTradeExecutorAction, state::LiveTradeUpdate},
+};
+
+/// Executor actions, closed trades and the first and latest trading states recorded during a live
+/// session, used as the live side of a [`ParityReport`].
+///
+/// Feed it every [`LiveTradeUpdate`] received from the live engine via [`record`](Self::record).
+#[derive(Debug, Clone, Default)]
+pub struct LiveSessionRecord {
+    actions: Vec<(DateTime<Utc>, LiveTradeExecutorAction)>,
+    closed_trades: Vec<Arc<dyn TradeClosed>>,
+    initial_trading_state: Option<TradingState>,
+    trading_state: Option<TradingState>,
+}
+
+impl LiveSessionRecord {
+    /// Creates an empty record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a [`LiveTradeUpdate`]. Executor actions are timestamped on receipt, closed trades
+    /// are stored as reported by the executor, and only the latest trading state is kept. Other
+    /// updates are ignored.
+    pub fn record<S: Signal>(&mut self, update: &LiveTradeUpdate<S>) {
+        match update {
+            LiveTradeUpdate::ExecutorAction(action) => {
+                self.record_action(Utc::now(), action.clone());
+            }
+            LiveTradeUpdate::ClosedTrade(trade) => self.record_closed_trade(trade.clone()),
+            LiveTradeUpdate::TradingState(state) => self.record_trading_state(state.clone()),
+            LiveTradeUpdate::Status(_)
+            | LiveTradeUpdate::Signal(_)
+            | LiveTradeUpdate::ExecutorWarning(_)
+            | LiveTradeUpdate::DeadManSwitchTriggered(_) => {}
+        }
+    }
+
+    /// Records an executor action sent at `time`.
+    pub fn record_action(&mut self, time: DateTime<Utc>, action: LiveTradeExecutorAction) {
+        self.actions.push((time, action));
+    }
+
+    /// Records a trade closed during the live session.
+    pub fn record_closed_trade(&mut self, trade: Arc<dyn TradeClosed>) {
+        self.closed_trades.push(trade);
+    }
+
+    /// Records the trading state of the live session, replacing the previously recorded one. The
+    /// first recorded state is also kept as the reference for session totals.
+    pub fn record_trading_state(&mut self, state: TradingState) {
+        if self.initial_trading_state.is_none() {
+            self.initial_trading_state = Some(state.clone());
+        }
+        self.trading_state = Some(state);
+    }
+
+    /// Returns the recorded executor actions, in order of receipt.
+    pub fn actions(&self) -> &[(DateTime<Utc>, LiveTradeExecutorAction)] {
+        &self.actions
+    }
+
+    /// Returns the recorded closed trades, in order of receipt.
+    pub fn closed_trades(&self) -> &[Arc<dyn TradeClosed>] {
+        &self.closed_trades
+    }
+
+    /// Returns the first recorded trading state, if any.
+    pub fn initial_trading_state(&self) -> Option<&TradingState> {
+        self.initial_trading_state.as_ref()
+    }
+
+    /// Returns the latest recorded trading state, if any.
+    pub fn trading_state(&self) -> Option<&TradingState> {
+        self.trading_state.as_ref()
+    }
+
+    /// Returns the time range of the live session, covered by the recorded actions, trade closures
+    /// and trading states. Entry times of trades opened before the session are not included.
+    /// Returns `None` if nothing was recorded.
+    pub fn period(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let times = self
+            .actions
+            .iter()
+            .map(|(time, _)| *time)
+            .chain(
+                self.closed_trades
+                    .iter()
+                    .filter_map(|trade| trade.closed_at()),
+            )
+            .chain(
+                [&self.initial_trading_state, &self.trading_state]
+                    .into_iter()
+                    .flatten()
+                    .map(TradingState::last_tick_time),
+            );
+
+        times.fold(None, |range, time| match range {
+            None => Some((time, time)),
+            Some((start, end)) => Some((start.min(time), end.max(time))),
+        })
+    }
+
+    /// Returns the [`period`](Self::period) rounded outwards to minutes, with its start moved back
+    /// if needed to cover the one day minimum of a [`BacktestEngine`]. Suitable as the time range
+    /// of the [`ParityReport::replay`] engine. Returns `None` if nothing was recorded.
+    ///
+    /// The replay trades over the whole returned range, so its operator state may diverge from the
+    /// live one before the session starts. The [`ParityReport`] only compares the part of the replay
+    /// that overlaps the live session.
+    pub fn backtest_period(&self) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let (start, end) = self.period()?;
+
+        let end = if end.is_round_minute() {
+            end
+        } else {
+            end.floor_minute() + Duration::minutes(1)
+        };
+        let start = start.floor_minute().min(end - Duration::days(1));
+
+        Some((start, end))
+    }
+}
+
+/// Session totals of a [`TradingState`] compared by a [`ParityReport`]. Cross-margin figures have
+/// no per-trade breakdown, so they are only compared as totals.
+#[derive(Debug, Clone, Copy, Default)]
+struct SessionTotals {
+    funding_fees: i64,
+    cross_pl: i64,
+    cross_fees: u64,
+}
+
+impl SessionTotals {
+    fn from_state(state: &TradingState) -> Self {
+        let cross_position = state.cross_position();
+
+        Self {
+            funding_fees: state.funding_fees() + cross_position.session_funding_fees(),
+            cross_pl: cross_position.realized_pl(),
+            cross_fees: cross_position.trading_fees(),
+        }
+    }
+
+    /// Returns the totals accumulated from `reference` to `state`. If there is no reference, the
+    /// totals of `state` are returned.
+    fn between(reference: Option<&TradingState>, state: &TradingState) -> Self {
+        let end = Self::from_state(state);
+        let Some(start) = reference.map(Self::from_state) else {
+            return end;
+        };
+
+        Self {
+            funding_fees: end.funding_fees - start.funding_fees,
+            cross_pl: end.cross_pl - start.cross_pl,
+            cross_fees: end.cross_fees.saturating_sub(start.cross_fees),
+        }
+    }
+}
+
+/// Tolerances used by a [`ParityReport`] to pair live and backtest trades and to flag divergences.
+#[derive(Debug, Clone)]
+pub struct ParityConfig {
+    match_window: Duration,
+    time_tolerance: Duration,
+    price_tolerance_perc: f64,
+    sats_tolerance: u64,
+}
+
+impl Default for ParityConfig {
+    fn default() -> Self {
+        Self {
+            match_window: Duration::minutes(5),
+            time_tolerance: Duration::minutes(1),
+            price_tolerance_perc: 0.1,
+            sats_tolerance: 10,
+        }
+    }
+}
+
+impl ParityConfig {
+    /// Returns the maximum entry time difference for a live and a backtest trade to be paired.
+    pub fn match_window(&self) -> Duration {
+        self.match_window
+    }
+
+    /// Returns the maximum entry or exit time difference not flagged as a divergence.
+    pub fn time_tolerance(&self) -> Duration {
+        self.time_tolerance
+    }
+
+    /// Returns the maximum entry or exit price difference, as a percentage of the live price, not
+    /// flagged as a divergence.
+    pub fn price_tolerance_perc(&self) -> f64 {
+        self.price_tolerance_perc
+    }
+
+    /// Returns the maximum fees or P&L difference (in satoshis) not flagged as a divergence.
+    pub fn sats_tolerance(&self) -> u64 {
+        self.sats_tolerance
+    }
+
+    /// Sets the maximum entry time difference, in seconds, for a live and a backtest trade to be
+    /// paired.
+    ///
+    /// Default: `300` (5 minutes)
+    pub fn with_match_window(mut self, secs: u64) -> Self {
+        self.match_window = Duration::seconds(secs as i64);
+        self
+    }
+
+    /// Sets the maximum entry or exit time difference, in seconds, not flagged as a divergence.
+    ///
+    /// Default: `60`
+    pub fn with_time_tolerance(mut self, secs: u64) -> Self {
+        self.time_tolerance = Duration::seconds(secs as i64);
+        self
+    }
+
+    /// Sets the maximum entry or exit price difference, as a percentage of the live price, not
+    /// flagged as a divergence.
+    ///
+    /// Default: `0.1`
+    pub fn with_price_tolerance_perc(mut self, price_tolerance_perc: f64) -> Self {
+        self.price_tolerance_perc = price_tolerance_perc.abs();
+        self
+    }
+
+    /// Sets the maximum fees or P&L difference (in satoshis) not flagged as a divergence.
+    ///
+    /// Default: `10`
+    pub fn with_sats_tolerance(mut self, sats_tolerance: u64) -> Self {
+        self.sats_tolerance = sats_tolerance;
+        self
+    }
+}
+
+/// Closed trade figures compared by a [`ParityReport`].
+#[derive(Debug, Clone)]
+pub struct ParityTrade {
+    id: Uuid,
+    side: TradeSide,
+    quantity: OrderQuantity,
+    entry_time: DateTime<Utc>,
+    exit_time: DateTime<Utc>,
+    entry_price: Price,
+    exit_price: Price,
+    fees: u64,
+    pl: i64,
+}
+
+impl ParityTrade {
+    /// Extracts the compared figures from a closed trade. Returns `None` if the trade has no exit
+    /// price or close time.
+    fn from_closed(trade: &dyn TradeClosed) -> Option<Self> {
+        Some(Self {
+            id: trade.id(),
+            side: trade.side(),
+            quantity: trade.quantity(),
+            entry_time: trade.filled_at().unwrap_or(trade.created_at()),
+            exit_time: trade.closed_at()?,
+            entry_price: trade.price(),
+            exit_price: trade.exit_price()?,
+            fees: trade.opening_fee() + trade.closing_fee(),
+            pl: trade.pl(),
+        })
+    }
+
+    /// Returns the trade identifier.
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Returns the trade side.
+    pub fn side(&self) -> TradeSide {
+        self.side
+    }
+
+    /// Returns the trade quantity.
+    pub fn quantity(&self) -> OrderQuantity {
+        self.quantity
+    }
+
+    /// Returns the time the trade was filled.
+    pub fn entry_time(&self) -> DateTime<Utc> {
+        self.entry_time
+    }
+
+    /// Returns the time the trade was closed.
+    pub fn exit_time(&self) -> DateTime<Utc> {
+        self.exit_time
+    }
+
+    /// Returns the entry price.
+    pub fn entry_price(&self) -> Price {
+        self.entry_price
+    }
+
+    /// Returns the exit price.
+    pub fn exit_price(&self) -> Price {
+        self.exit_price
+    }
+
+    /// Returns the sum of opening and closing fees (in satoshis).
+    pub fn fees(&self) -> u64 {
+        self.fees
+    }
+
+    /// Returns the realized P&L before fees (in satoshis).
+    pub fn pl(&self) -> i64 {
+        self.pl
+    }
+
+    /// Returns the realized P&L net of fees (in satoshis).
+    pub fn net_pl(&self) -> i64 {
+        self.pl - self.fees as i64
+    }
+}
+
+/// Trade by trade comparison entry of a [`ParityReport`].
+#[derive(Debug, Clone)]
+pub enum TradeParity {
+    /// A live trade paired with the backtest trade closest to its entry time.
+    Matched {
+        /// Live trade.
+        live: ParityTrade,
+        /// Backtest trade.
+        backtest: ParityTrade,
+    },
+    /// A live trade without a backtest counterpart.
+    LiveOnly(ParityTrade),
+    /// A backtest trade without a live counterpart.
+    BacktestOnly(ParityTrade),
+}
+
+impl TradeParity {
+    /// Returns the live trade, if any.
+    pub fn live(&self) -> Option<&ParityTrade> {
+        match self {
+            Self::Matched { live, .. } | Self::LiveOnly(live) => Some(live),
+            Self::BacktestOnly(_) => None,
+        }
+    }
+
+    /// Returns the backtest trade, if any.
+    pub fn backtest(&self) -> Option<&ParityTrade> {
+        match self {
+            Self::Matched { backtest, .. } | Self::BacktestOnly(backtest) => Some(backtest),
+            Self::LiveOnly(_) => None,
+        }
+    }
+
+    fn diff<T>(&self, f: impl Fn(&ParityTrade, &ParityTrade) -> T) -> Option<T> {
+        match self {
+            Self::Matched { live, backtest } => Some(f(live, backtest)),
+            Self::LiveOnly(_) | Self::BacktestOnly(_) => None,
+        }
+    }
+
+    /// Returns the backtest entry time minus the live entry time, for matched trades.
+    pub fn entry_time_diff(&self) -> Option<Duration> {
+        self.diff(|live, backtest| backtest.entry_time - live.entry_time)
+    }
+
+    /// Returns the backtest exit time minus the live exit time, for matched trades.
+    pub fn exit_time_diff(&self) -> Option<Duration> {
+        self.diff(|live, backtest| backtest.exit_time - live.exit_time)
+    }
+
+    /// Returns the backtest entry price minus the live entry price, for matched trades.
+    pub fn entry_price_diff(&self) -> Option<f64> {
+        self.diff(|live, backtest| backtest.entry_price.as_f64() - live.entry_price.as_f64())
+    }
+
+    /// Returns the backtest exit price minus the live exit price, for matched trades.
+    pub fn exit_price_diff(&self) -> Option<f64> {
+        self.diff(|live, backtest| backtest.exit_price.as_f64() - live.exit_price.as_f64())
+    }
+
+    /// Returns the backtest fees minus the live fees (in satoshis), for matched trades.
+    pub fn fees_diff(&self) -> Option<i64> {
+        self.diff(|live, backtest| backtest.fees as i64 - live.fees as i64)
+    }
+
+    /// Returns the backtest net P&L minus the live net P&L (in satoshis), for matched trades.
+    pub fn net_pl_diff(&self) -> Option<i64> {
+        self.diff(|live, backtest| backtest.net_pl() - live.net_pl())
+    }
+
+    /// Returns `true` if the trade is unmatched, or if any compared figure differs by more than
+    /// the tolerances of `config`.
+    pub fn is_divergent(&self, config: &ParityConfig) -> bool {
+        let Self::Matched { live, backtest } = self else {
+            return true;
+        };
+
+        let time_diverges = |diff: Duration| diff.abs() > config.time_tolerance;
+        let price_diverges = |live: Price, backtest: Price| {
+            let diff_perc = (backtest.as_f64() - live.as_f64()).abs() / live.as_f64() * 100.;
+            diff_perc > config.price_tolerance_perc
+        };
+        let sats_diverge = |diff: i64| diff.unsigned_abs() > config.sats_tolerance;
+
+        time_diverges(backtest.entry_time - live.entry_time)
+            || time_diverges(backtest.exit_time - live.exit_time)
+            || price_diverges(live.entry_price, backtest.entry_price)
+            || price_diverges(live.exit_price, backtest.exit_price)
+            || sats_diverge(backtest.fees as i64 - live.fees as i64)
+            || sats_diverge(backtest.net_pl() - live.net_pl())
+    }
+}
+
+/// Pairs each live trade with the unmatched backtest trade of the same side whose entry time is
+/// closest, within the match window. Entries are ordered by entry time.
+fn match_trades(
+    config: &ParityConfig,
+    mut live_trades: Vec<ParityTrade>,
+    backtest_trades: Vec<ParityTrade>,
+) -> Vec<TradeParity> {
+    live_trades.sort_by_key(|trade| trade.entry_time);
+
+    let mut backtest_trades: Vec<Option<ParityTrade>> =
+        backtest_trades.into_iter().map(Some).collect();
+
+    let mut trades = Vec::with_capacity(live_trades.len() + backtest_trades.len());
+
+    for live in live_trades {
+        let closest = backtest_trades
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, backtest)| {
+                let backtest = backtest.as_ref()?;
+                if backtest.side != live.side {
+                    return None;
+                }
+
+                let distance = (backtest.entry_time - live.entry_time).abs();
+                (distance <= config.match_window).then_some((idx, distance))
+            })
+            .min_by_key(|(_, distance)| *distance);
+
+        match closest.and_then(|(idx, _)| backtest_trades[idx].take()) {
+            Some(backtest) => trades.push(TradeParity::Matched { live, backtest }),
+            None => trades.push(TradeParity::LiveOnly(live)),
+        }
+    }
+
+    trades.extend(
+        backtest_trades
+            .into_iter()
+            .flatten()
+            .map(TradeParity::BacktestOnly),
+    );
+
+    let entry_time = |trade: &TradeParity| {
+        trade
+            .live()
+            .or(trade.backtest())
+            .map(|trade| trade.entry_time)
+    };
+    trades.sort_by_key(entry_time);
+
+    trades
+}
+
+/// Trade by trade comparison between a live session and a backtest replay of the same period with
+/// the same operator.
+///
+/// The live side is a [`LiveSessionRecord`], and the backtest side is the final [`TradingState`]
+/// of a [`BacktestEngine`] run. Only the overlap of the live session and the replay is compared:
+/// isolated trades entered within it are compared trade by trade, while funding fees of isolated
+/// trades and of the cross-margin position, and the cross-margin realized P&L and trading fees, are
+/// compared as totals accumulated over it. Closed trades and totals restored by the live engine
+/// from before the session are excluded.
+#[derive(Debug, Clone)]
+pub struct ParityReport {
+    config: ParityConfig,
+    trades: Vec<TradeParity>,
+    live_orders_sent: usize,
+    live_totals: SessionTotals,
+    backtest_totals: SessionTotals,
+}
+
+impl ParityReport {
+    /// Compares the closed trades of a live session with the closed trades of the final trading
+    /// state of a backtest replay, over the overlap of the session [`period`] and the replay.
+    ///
+    /// Live totals are accumulated between the first and the latest recorded trading states, and
+    /// are zero if none was recorded. Backtest totals are accumulated from `backtest_reference`,
+    /// the replay trading state at the start of the live session, or from the start of the replay
+    /// if `None`.
+    ///
+    /// [`period`]: LiveSessionRecord::period
+    pub fn new(
+        config: ParityConfig,
+        live: &LiveSessionRecord,
+        backtest_reference: Option<&TradingState>,
+        backtest: &TradingState,
+    ) -> Self {
+        let overlap = live
+            .period()
+            .map(|(start, end)| (start, end.min(backtest.last_tick_time())));
+        let in_overlap = |trade: &ParityTrade| {
+            overlap.is_none_or(|(start, end)| trade.entry_time >= start && trade.entry_time <= end)
+        };
+
+        let live_trades = live
+            .closed_trades()
+            .iter()
+            .filter_map(|trade| ParityTrade::from_closed(trade.as_ref()))
+            .filter(in_overlap)
+            .collect();
+
+        let backtest_trades = backtest
+            .closed_history()
+            .iter()
+            .filter_map(|trade| ParityTrade::from_closed(trade.as_ref()))
+            .filter(in_overlap)
+            .collect();
+
+        let trades = match_trades(&config, live_trades, backtest_trades);
+
+        let live_orders_sent = live
+            .actions()
+            .iter()
+            .filter(|(_, action)| matches!(action, LiveTradeExecutorAction::IsolatedOrder { .. }))
+            .count();
+
+        Self {
+            config,
+            trades,
+            live_orders_sent,
+            live_totals: live
+                .trading_state()
+                .map(|state| SessionTotals::between(live.initial_trading_state(), state))
+                .unwrap_or_default(),
+            backtest_totals: SessionTotals::between(backtest_reference, backtest),
+        }
+    }
+
+    /// Runs a backtest replay of a live session to completion and compares it with the session.
+    ///
+    /// `engine` must be built with the same operator as the live session, typically over the
+    /// [`backtest_period`](LiveSessionRecord::backtest_period) of `live`. Trades opened by the
+    /// replay outside of the live session are not compared, and its totals are accumulated from
+    /// the start of the live session.
+    ///
+    /// Returns an error if trading state updates of the replay are missed.
+    pub async fn replay<S: Signal>(
+        config: ParityConfig,
+        live: &LiveSessionRecord,
+        engine: BacktestEngine<S>,
+    ) -> Result<Self, BacktestError> {
+        let session_start = live.period().map(|(start, _)| start);
+
+        let engine = match session_start {
+            Some(start) => engine.with_state_checkpoint(start),
+            None => engine,
+        };
+
+        let mut backtest_rx = engine.receiver();
+        let controller = engine.start();
+
+        // First replay state at or after the start of the live session
+        let mut reference_state = None;
+        let mut final_state = None;
+        let status = loop {
+            match backtest_rx.recv().await {
+                Ok(BacktestUpdate::TradingState(state)) => {
+                    if reference_state.is_none()
+                        && session_start.is_some_and(|start| state.last_tick_time() >= start)
+                    {
+                        reference_state = Some((*state).clone());
+                    }
+                    final_state = Some(*state);
+                }
+                Ok(BacktestUpdate::Status(status)) if status.is_stopped() => break status,
+                Ok(BacktestUpdate::Status(_)) => {}
+                Err(RecvError::Lagged(skipped)) => {
+                    let _ = controller.abort().await;
+                    return Err(BacktestError::ParityReplayLagged { skipped });
+                }
+                Err(RecvError::Closed) => break controller.status_snapshot(),
+            }
+        };
+
+        match status {
+            BacktestStatus::Finished => {
+                let backtest = final_state.ok_or(BacktestError::ParityReplayMissingTradingState)?;
+                Ok(Self::new(config, live, reference_state.as_ref(), &backtest))
+            }
+            BacktestStatus::Failed(e) => Err(BacktestError::ParityReplayFailed(e)),
+            _ => Err(BacktestError::ParityReplayAborted),
+        }
+    }
+
+    /// Returns the tolerances used by the report.
+    pub fn config(&self) -> &ParityConfig {
+        &self.config
+    }
+
+    /// Returns all comparison entries, ordered by entry time.
+    pub fn trades(&self) -> &[TradeParity] {
+        &self.trades
+    }
+
+    /// Returns the comparison entries flagged as divergent.
+    pub fn divergent_trades(&self) -> impl Iterator<Item = &TradeParity> {
+        self.trades
+            .iter()
+            .filter(|trade| trade.is_divergent(&self.config))
+    }
+
+    /// Returns the number of isolated orders sent to the exchange during the live session.
+    pub fn live_orders_sent(&self) -> usize {
+        self.live_orders_sent
+    }
+
+    /// Returns the number of live trades paired with a backtest trade.
+    pub fn matched_len(&self) -> usize {
+        self.trades
+            .iter()
+            .filter(|trade| matches!(trade, TradeParity::Matched { .. }))
+            .count()
+    }
+
+    fn sum_side(&self, side: impl Fn(&TradeParity) -> Option<&ParityTrade>) -> (u64, i64) {
+        self.trades
+            .iter()
+            .filter_map(side)
+            .fold((0, 0), |(fees, pl), trade| {
+                (fees + trade.fees, pl + trade.pl)
+            })
+    }
+
+    /// Returns the funding fees (in satoshis) of the live session, for isolated trades and the
+    /// cross-margin position.
+    pub fn live_funding_fees(&self) -> i64 {
+        self.live_totals.funding_fees
+    }
+
+    /// Returns the funding fees (in satoshis) of the backtest replay, for isolated trades and the
+    /// cross-margin position.
+    pub fn backtest_funding_fees(&self) -> i64 {
+        self.backtest_totals.funding_fees
+    }
+
+    /// Returns the realized cross-margin P&L before fees (in satoshis) of the live session.
+    pub fn live_cross_pl(&self) -> i64 {
+        self.live_totals.cross_pl
+    }
+
+    /// Returns the realized cross-margin P&L before fees (in satoshis) of the backtest replay.
+    pub fn backtest_cross_pl(&self) -> i64 {
+        self.backtest_totals.cross_pl
+    }
+
+    /// Returns the cross-margin trading fees (in satoshis) of the live session.
+    pub fn live_cross_fees(&self) -> u64 {
+        self.live_totals.cross_fees
+    }
+
+    /// Returns the cross-margin trading fees (in satoshis) of the backtest replay.
+    pub fn backtest_cross_fees(&self) -> u64 {
+        self.backtest_totals.cross_fees
+    }
+
+    fn net_pl(
+        &self,
+        side: impl Fn(&TradeParity) -> Option<&ParityTrade>,
+        totals: SessionTotals,
+    ) -> i64 {
+        let (fees, pl) = self.sum_side(side);
+        pl - fees as i64 + totals.cross_pl - totals.cross_fees as i64 - totals.funding_fees
+    }
+
+    /// Returns the final P&L of the live session, from closed isolated trades and the cross-margin
+    /// position, net of fees and funding (in satoshis).
+    pub fn live_net_pl(&self) -> i64 {
+        self.net_pl(TradeParity::live, self.live_totals)
+    }
+
+    /// Returns the final P&L of the backtest replay, from closed isolated trades and the
+    /// cross-margin position, net of fees and funding (in satoshis).
+    pub fn backtest_net_pl(&self) -> i64 {
+        self.net_pl(TradeParity::backtest, self.backtest_totals)
+    }
+
+    /// Returns a formatted table of every comparison entry, marking divergent entries with `*`.
+    pub fn to_table(&self) -> String {
+        if self.trades.is_empty() {
+            return "No closed trades.".to_string();
+        }
+
+        let fmt_time = |time: DateTime<Utc>| {
+            time.with_timezone(&chrono::Local)
+                .format("%y-%m-%d %H:%M:%S")
+                .to_string()
+        };
+
+        let mut table = format!(
+            "{:>1} | {:>8} | {:>5} | {:>17} | {:>17} | {:>11} | {:>11} | {:>9} | {:>11}",
+            "",
+            "source",
+            "side",
+            "entry time",
+            "exit time",
+            "entry price",
+            "exit price",
+            "fees",
+            "net P/L"
+        );
+
+        table.push_str(&format!("\n{}", "-".repeat(115)));
+
+        for trade in &self.trades {
+            let marker = if trade.is_divergent(&self.config) {
+                "*"
+            } else {
+                ""
+            };
+
+            let sources = [("live", trade.live()), ("backtest", trade.backtest())];
+            for (source, parity_trade) in sources {
+                let Some(t) = parity_trade else {
+                    continue;
+                };
+
+                table.push_str(&format!(
+                    "\n{:>1} | {:>8} | {:>5} | {:>17} | {:>17} | {:>11.1} | {:>11.1} | {:>9} | {:>11}",
+                    marker,
+                    source,
+                    t.side,
+                    fmt_time(t.entry_time),
+                    fmt_time(t.exit_time),
+                    t.entry_price,
+                    t.exit_price,
+                    t.fees,
+                    t.net_pl()
+                ));
+            }
+        }
+
+        table
+    }
+}
+
+impl fmt::Display for ParityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let live_len = self.trades.iter().filter(|t| t.live().is_some()).count();
+        let backtest_len = self
+            .trades
+            .iter()
+            .filter(|t| t.backtest().is_some())
+            .count();
+
+        writeln!(f, "Parity Report:")?;
+        writeln!(f, "  live orders sent: {}", self.live_orders_sent)?;
+        writeln!(f, "  live closed trades: {live_len}")?;
+        writeln!(f, "  backtest closed trades: {backtest_len}")?;
+        writeln!(f, "  matched trades: {}", self.matched_len())?;
+        writeln!(f, "  divergent trades: {}", self.divergent_trades().count())?;
+        writeln!(f, "  live funding fees: {}", self.live_funding_fees())?;
+        writeln!(
+            f,
+            "  backtest funding fees: {}",
+            self.backtest_funding_fees()
+        )?;
+        writeln!(f, "  live cross P/L: {}", self.live_cross_pl())?;
+        writeln!(f, "  backtest cross P/L: {}", self.backtest_cross_pl())?;
+        writeln!(f, "  live cross fees: {}", self.live_cross_fees())?;
+        writeln!(f, "  backtest cross fees: {}", self.backtest_cross_fees())?;
+        writeln!(f, "  live net P/L: {}", self.live_net_pl())?;
+        write!(f, "  backtest net P/L: {}", self.backtest_net_pl())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use lnm_sdk::rest::v3::models::{CrossLeverage, Leverage};
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        trade::{
+            BacktestConfig, CrossOrderRequest, IsolatedOrderRequest, TradeExecutor,
+            backtest::executor::SimulatedTradeExecutor,
+        },
+    };
+
+    use super::*;
+
+    fn parity_trade(side: TradeSide, entry_offset_secs: i64, pl: i64) -> ParityTrade {
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        ParityTrade {
+            id: Uuid::new_v4(),
+            side,
+            quantity: OrderQuantity::try_from(100).unwrap(),
+            entry_time: start + Duration::seconds(entry_offset_secs),
+            exit_time: start + Duration::seconds(entry_offset_secs + 3_600),
+            entry_price: Price::try_from(100_000.0).unwrap(),
+            exit_price: Price::try_from(101_000.0).unwrap(),
+            fees: 200,
+            pl,
+        }
+    }
+
+    #[test]
+    fn match_trades_pairs_closest_trade_of_same_side() {
+        let config = ParityConfig::default();
+
+        let live = vec![
+            parity_trade(TradeSide::Buy, 0, 1_000),
+            parity_trade(TradeSide::Sell, 7_200, -500),
+            parity_trade(TradeSide::Buy, 20_000, 300),
+        ];
+        let backtest = vec![
+            parity_trade(TradeSide::Buy, 240, 1_000),
+            parity_trade(TradeSide::Buy, 30, 1_005),
+            parity_trade(TradeSide::Buy, 7_200, -500),
+            parity_trade(TradeSide::Sell, 40_000, 0),
+        ];
+        let closest_id = backtest[1].id;
+
+        let trades = match_trades(&config, live, backtest);
+
+        assert_eq!(trades.len(), 6);
+
+        let TradeParity::Matched {
+            backtest: first_backtest,
+            ..
+        } = &trades[0]
+        else {
+            panic!("first live trade must be matched");
+        };
+        assert_eq!(first_backtest.id, closest_id);
+        assert!(!trades[0].is_divergent(&config));
+        assert_eq!(trades[0].entry_time_diff(), Some(Duration::seconds(30)));
+        assert_eq!(trades[0].net_pl_diff(), Some(5));
+
+        // The remaining buy at +240s is unmatched, as the live trade was paired with the closer one
+        assert!(matches!(trades[1], TradeParity::BacktestOnly(_)));
+
+        // The backtest buy at +7200s has the wrong side for the live sell
+        let live_only = trades
+            .iter()
+            .filter(|t| matches!(t, TradeParity::LiveOnly(_)))
+            .count();
+        let backtest_only = trades
+            .iter()
+            .filter(|t| matches!(t, TradeParity::BacktestOnly(_)))
+            .count();
+        assert_eq!(live_only, 2);
+        assert_eq!(backtest_only, 3);
+        assert!(trades.iter().skip(1).all(|t| t.is_divergent(&config)));
+    }
+
+    #[test]
+    fn backtest_period_is_rounded_and_covers_one_day() {
+        let start = DateTime::from_timestamp(1_700_000_030, 0).unwrap();
+
+        let mut record = LiveSessionRecord::new();
+        assert!(record.backtest_period().is_none());
+
+        record.record_action(start, LiveTradeExecutorAction::IsolatedOrderCloseAll);
+        record.record_action(
+            start + Duration::hours(2),
+            LiveTradeExecutorAction::IsolatedOrderCloseAll,
+        );
+
+        let (backtest_start, backtest_end) = record.backtest_period().unwrap();
+        assert!(backtest_start.is_round_minute() && backtest_end.is_round_minute());
+        assert_eq!(backtest_end, start.floor_minute() + Duration::minutes(121));
+        assert_eq!(backtest_end - backtest_start, Duration::days(1));
+
+        record.record_action(
+            start + Duration::days(2),
+            LiveTradeExecutorAction::IsolatedOrderCloseAll,
+        );
+
+        let (backtest_start, _) = record.backtest_period().unwrap();
+        assert_eq!(backtest_start, start.floor_minute());
+    }
+
+    #[test]
+    fn is_divergent_respects_tolerances() {
+        let live = parity_trade(TradeSide::Buy, 0, 1_000);
+        let mut backtest = parity_trade(TradeSide::Buy, 90, 1_000);
+        backtest.exit_time = live.exit_time;
+
+        let trade = TradeParity::Matched {
+            live: live.clone(),
+            backtest: backtest.clone(),
+        };
+
+        assert!(trade.is_divergent(&ParityConfig::default()));
+        assert!(!trade.is_divergent(&ParityConfig::default().with_time_tolerance(90)));
+
+        backtest.entry_time = live.entry_time;
+        backtest.exit_price = Price::try_from(101_500.0).unwrap();
+        let trade = TradeParity::Matched { live, backtest };
+
+        assert!(trade.is_divergent(&ParityConfig::default()));
+        assert!(!trade.is_divergent(&ParityConfig::default().with_price_tolerance_perc(0.5)));
+    }
+
+    #[tokio::test]
+    async fn new_only_compares_the_live_session() {
+        let start_candle = OhlcCandleRow::new_simple(
+            DateTime::from_timestamp(1_700_000_040, 0).unwrap(),
+            100_000.,
+            1_000,
+        );
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 10_000_000);
+        let open_and_close = async || {
+            executor
+                .isolated_order(IsolatedOrderRequest::market(
+                    TradeSide::Buy,
+                    OrderQuantity::try_from(1_000).unwrap().into(),
+                    Leverage::try_from(2.).unwrap(),
+                ))
+                .await
+                .unwrap();
+            executor.isolated_order_close_all().await.unwrap();
+        };
+
+        // Activity before the live session, restored by the live engine
+        open_and_close().await;
+        executor
+            .cross_deposit(NonZeroU64::new(500_000).unwrap())
+            .await
+            .unwrap();
+        executor
+            .cross_set_leverage(CrossLeverage::try_from(10).unwrap())
+            .await
+            .unwrap();
+        executor
+            .cross_order(CrossOrderRequest::market(
+                TradeSide::Buy,
+                OrderQuantity::try_from(1_000).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        let candle =
+            OhlcCandleRow::new_simple(start_candle.time + Duration::minutes(10), 100_000., 1_000);
+        executor.candle_update(&candle).await.unwrap();
+        let session_start_state = executor.trading_state().await.unwrap();
+
+        open_and_close().await;
+        let final_state = executor.trading_state().await.unwrap();
+
+        let mut live = LiveSessionRecord::new();
+        live.record_trading_state(session_start_state.clone());
+        let session_trade = final_state.closed_history().iter_desc().next().unwrap();
+        live.record_closed_trade(session_trade.clone());
+        live.record_trading_state(final_state.clone());
+
+        let (session_start, _) = live.period().unwrap();
+        assert_eq!(session_start, session_start_state.last_tick_time());
+
+        let report = ParityReport::new(
+            ParityConfig::default(),
+            &live,
+            Some(&session_start_state),
+            &final_state,
+        );
+
+        assert_eq!(report.trades().len(), 1);
+        assert_eq!(report.matched_len(), 1);
+        assert_eq!(report.divergent_trades().count(), 0);
+        assert!(final_state.cross_position().trading_fees() > 0);
+        assert_eq!(report.live_cross_fees(), 0);
+        assert_eq!(report.backtest_cross_fees(), 0);
+        assert_eq!(report.live_net_pl(), report.backtest_net_pl());
+
+        // Without a reference, the replay totals include the activity before the session
+        let report = ParityReport::new(ParityConfig::default(), &live, None, &final_state);
+        assert_eq!(
+            report.backtest_cross_fees(),
+            final_state.cross_position().trading_fees()
+        );
+    }
+}
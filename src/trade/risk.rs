@@ -0,0 +1,1167 @@
+use std::{
+    num::NonZeroU64,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex as AsyncMutex;
+use uuid::Uuid;
+
+use lnm_sdk::rest::v3::models::{
+    CrossExposure, CrossLeverage, Margin, OrderQuantity, PercentageCapped, Price, SATS_PER_BTC,
+    TradeSide, trade_util,
+};
+
+use crate::{db::Database, util::DateTimeExt};
+
+use super::{
+    core::{
+        CrossOrderRequest, CrossPositionCore, IsolatedOrderRequest, TradeExecutor, TradingState,
+    },
+    error::{RiskLimitError, TradeExecutorResult},
+};
+
+/// Action taken by the [`RiskManager`] when an account-level loss limit (maximum daily loss or
+/// maximum drawdown) is breached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskBreachAction {
+    /// Rejects orders that would increase exposure. Open positions are left untouched.
+    Reject,
+    /// Closes all isolated trades and the cross-margin position, and rejects orders that would
+    /// increase exposure.
+    Flatten,
+}
+
+/// Limits enforced by the [`RiskManager`]. All limits are disabled by default.
+#[derive(Debug, Clone)]
+pub struct RiskLimits {
+    max_total_leverage: Option<f64>,
+    max_notional_per_side: Option<u64>,
+    max_running_trades: Option<usize>,
+    max_daily_loss: Option<u64>,
+    max_drawdown: Option<PercentageCapped>,
+    min_liquidation_distance: Option<PercentageCapped>,
+    breach_action: RiskBreachAction,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_total_leverage: None,
+            max_notional_per_side: None,
+            max_running_trades: None,
+            max_daily_loss: None,
+            max_drawdown: None,
+            min_liquidation_distance: None,
+            breach_action: RiskBreachAction::Reject,
+        }
+    }
+}
+
+impl RiskLimits {
+    /// Returns the maximum total leverage, computed as the gross notional of isolated trades and
+    /// the cross-margin position over the total net value.
+    pub fn max_total_leverage(&self) -> Option<f64> {
+        self.max_total_leverage
+    }
+
+    /// Returns the maximum notional (in USD) per side, including isolated trades and the
+    /// cross-margin position.
+    pub fn max_notional_per_side(&self) -> Option<u64> {
+        self.max_notional_per_side
+    }
+
+    /// Returns the maximum number of running isolated trades.
+    pub fn max_running_trades(&self) -> Option<usize> {
+        self.max_running_trades
+    }
+
+    /// Returns the maximum loss (in satoshis) of total net value since the start of the UTC day.
+    pub fn max_daily_loss(&self) -> Option<u64> {
+        self.max_daily_loss
+    }
+
+    /// Returns the maximum drawdown of total net value from its peak.
+    pub fn max_drawdown(&self) -> Option<PercentageCapped> {
+        self.max_drawdown
+    }
+
+    /// Returns the minimum distance between the estimated liquidation price of new positions and
+    /// the market price, as a percentage of the market price.
+    pub fn min_liquidation_distance(&self) -> Option<PercentageCapped> {
+        self.min_liquidation_distance
+    }
+
+    /// Returns whether an account-level loss limit (maximum daily loss or maximum drawdown) is
+    /// set.
+    pub(in crate::trade) fn has_loss_limits(&self) -> bool {
+        self.max_daily_loss.is_some() || self.max_drawdown.is_some()
+    }
+
+    /// Returns the action taken when an account-level loss limit is breached.
+    pub fn breach_action(&self) -> RiskBreachAction {
+        self.breach_action
+    }
+
+    /// Sets the maximum total leverage, computed as the gross notional of isolated trades and the
+    /// cross-margin position over the total net value. Cash-ins, cross-margin withdrawals and cross
+    /// leverage changes leave the total leverage unchanged, and are instead rejected if they would
+    /// raise the leverage of the affected position above the maximum. If `None`, total leverage is
+    /// not limited.
+    ///
+    /// Default: `None`
+    pub fn with_max_total_leverage(mut self, max: Option<f64>) -> Self {
+        self.max_total_leverage = max;
+        self
+    }
+
+    /// Sets the maximum notional (in USD) per side, including isolated trades and the cross-margin
+    /// position. If `None`, notional is not limited.
+    ///
+    /// Default: `None`
+    pub fn with_max_notional_per_side(mut self, max: Option<u64>) -> Self {
+        self.max_notional_per_side = max;
+        self
+    }
+
+    /// Sets the maximum number of running isolated trades. If `None`, only the executor limit
+    /// applies.
+    ///
+    /// Default: `None`
+    pub fn with_max_running_trades(mut self, max: Option<usize>) -> Self {
+        self.max_running_trades = max;
+        self
+    }
+
+    /// Sets the maximum loss (in satoshis) of total net value since the start of the UTC day. If
+    /// `None`, daily loss is not limited.
+    ///
+    /// Default: `None`
+    pub fn with_max_daily_loss(mut self, max: Option<u64>) -> Self {
+        self.max_daily_loss = max;
+        self
+    }
+
+    /// Sets the maximum drawdown of total net value from its peak. If `None`, drawdown is not
+    /// limited.
+    ///
+    /// Default: `None`
+    pub fn with_max_drawdown(mut self, max: Option<PercentageCapped>) -> Self {
+        self.max_drawdown = max;
+        self
+    }
+
+    /// Sets the minimum distance between the estimated liquidation price of new positions and the
+    /// market price, as a percentage of the market price. Cash-ins, cross-margin withdrawals and
+    /// cross leverage changes moving the liquidation price of a position below the minimum distance
+    /// are also rejected. If `None`, liquidation distance is not checked.
+    ///
+    /// Default: `None`
+    pub fn with_min_liquidation_distance(mut self, min: Option<PercentageCapped>) -> Self {
+        self.min_liquidation_distance = min;
+        self
+    }
+
+    /// Sets the action taken when an account-level loss limit is breached.
+    ///
+    /// Default: [`RiskBreachAction::Reject`]
+    pub fn with_breach_action(mut self, breach_action: RiskBreachAction) -> Self {
+        self.breach_action = breach_action;
+        self
+    }
+
+    /// Checks the daily loss and drawdown limits against the tracked net value.
+    pub(in crate::trade) fn check_loss_limits(
+        &self,
+        tracker: &NetValueTracker,
+    ) -> Result<(), RiskLimitError> {
+        if let Some(max) = self.max_daily_loss {
+            let loss = tracker.daily_loss();
+            if loss >= max {
+                return Err(RiskLimitError::MaxDailyLoss { loss, max });
+            }
+        }
+
+        if let Some(max) = self.max_drawdown {
+            let drawdown = tracker.drawdown();
+            if drawdown >= max.as_f64() {
+                return Err(RiskLimitError::MaxDrawdown { drawdown, max });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Total net value reference points (start of day value and peak value), persisted so that daily
+/// loss and drawdown tracking can be resumed after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(in crate::trade) struct NetValueReferences {
+    day_start: DateTime<Utc>,
+    day_start_value: u64,
+    peak_value: u64,
+}
+
+impl NetValueReferences {
+    pub fn from_bytes(bytes: &[u8]) -> serde_json::Result<Self> {
+        serde_json::from_slice(bytes)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("net value references are serializable")
+    }
+}
+
+/// Tracks the total net value against its reference points, to evaluate daily loss and drawdown
+/// limits.
+pub(in crate::trade) struct NetValueTracker {
+    references: NetValueReferences,
+    net_value: u64,
+}
+
+impl NetValueTracker {
+    pub fn new(state: &TradingState) -> Self {
+        let net_value = state.total_net_value();
+        Self {
+            references: NetValueReferences {
+                day_start: state.last_tick_time().floor_day(),
+                day_start_value: net_value,
+                peak_value: net_value,
+            },
+            net_value,
+        }
+    }
+
+    /// Resumes tracking from persisted reference points, updated with the given state.
+    pub fn resume(references: NetValueReferences, state: &TradingState) -> Self {
+        let mut tracker = Self {
+            references,
+            net_value: state.total_net_value(),
+        };
+        tracker.update(state);
+
+        tracker
+    }
+
+    pub fn references(&self) -> &NetValueReferences {
+        &self.references
+    }
+
+    /// Updates the reference points with the given state. The daily reference is reset on the
+    /// first update of each UTC day.
+    pub fn update(&mut self, state: &TradingState) {
+        let net_value = state.total_net_value();
+        let day_start = state.last_tick_time().floor_day();
+        let references = &mut self.references;
+
+        if references.day_start != day_start {
+            references.day_start = day_start;
+            references.day_start_value = net_value;
+        }
+        references.peak_value = references.peak_value.max(net_value);
+        self.net_value = net_value;
+    }
+
+    /// Returns the loss (in satoshis) of total net value since the start of the current UTC day.
+    pub fn daily_loss(&self) -> u64 {
+        self.references
+            .day_start_value
+            .saturating_sub(self.net_value)
+    }
+
+    /// Returns the drawdown of total net value from its peak, as a percentage.
+    pub fn drawdown(&self) -> f64 {
+        let peak_value = self.references.peak_value;
+        if peak_value == 0 {
+            return 0.;
+        }
+        peak_value.saturating_sub(self.net_value) as f64 / peak_value as f64 * 100.
+    }
+}
+
+/// Gross notional (in USD) per side of the isolated trades and the cross-margin position.
+#[derive(Debug, Clone, Copy)]
+struct Exposure {
+    long: u64,
+    short: u64,
+}
+
+impl Exposure {
+    fn from_state(state: &TradingState, cross_quantity: i64) -> Self {
+        Self {
+            long: state.running_long_quantity() + cross_quantity.max(0) as u64,
+            short: state.running_short_quantity() + cross_quantity.min(0).unsigned_abs(),
+        }
+    }
+
+    fn side(&self, side: TradeSide) -> u64 {
+        match side {
+            TradeSide::Buy => self.long,
+            TradeSide::Sell => self.short,
+        }
+    }
+
+    fn gross(&self) -> u64 {
+        self.long + self.short
+    }
+}
+
+/// Leverage and liquidation price of a single position.
+#[derive(Debug, Clone, Copy)]
+struct PositionRisk {
+    leverage: f64,
+    liquidation: Option<Price>,
+}
+
+/// Distance between the liquidation price and the market price, as a percentage of the market
+/// price.
+fn liquidation_distance(liquidation: Price, market_price: Price) -> f64 {
+    (market_price.as_f64() - liquidation.as_f64()).abs() / market_price.as_f64() * 100.
+}
+
+/// Estimates the liquidation price of the cross-margin position with the given margin and
+/// leverage. Returns `None` if there is no position, or if it would be invalid.
+fn projected_cross_liquidation(
+    cross_position: &dyn CrossPositionCore,
+    margin: u64,
+    leverage: CrossLeverage,
+) -> Option<Price> {
+    match CrossExposure::new(
+        margin,
+        leverage,
+        cross_position.exposure().as_running_params(),
+    ) {
+        Ok(CrossExposure::Running(exposure)) => Some(exposure.liquidation()),
+        _ => None,
+    }
+}
+
+/// [`TradeExecutor`] wrapper enforcing portfolio-level [`RiskLimits`] on top of any executor,
+/// simulated or live.
+///
+/// Orders that would increase exposure are checked against the limits before being forwarded, and
+/// rejected with [`TradeExecutorError::RiskLimit`](crate::error::TradeExecutorError::RiskLimit)
+/// on violation. Cash-ins, cross-margin withdrawals and cross leverage changes are checked against
+/// the projected state of the affected position. Orders that reduce exposure, closes, and margin
+/// additions are always forwarded. Checked calls are serialized, so concurrent orders can't all
+/// pass a check against the same state before any of them reaches the wrapped executor.
+///
+/// The account-level loss limits are evaluated against the total net value observed whenever the
+/// trading state is fetched through the manager, including before each checked order. The manager
+/// doesn't observe the trading state on its own, so a breach happening between two calls is only
+/// detected on the next one. With [`RiskBreachAction::Flatten`], a breach detected while fetching
+/// the trading state also closes all positions. Operators relying on the breach action should fetch
+/// the trading state on each iteration. In live trading, the
+/// [kill switch](crate::trade::LiveTradeConfig::with_kill_switch_max_daily_loss) evaluates its
+/// limits on every trading state update instead.
+///
+/// The net value reference points (start of day value and peak value) are tracked in memory.
+/// Managers created by the [`LiveTradeEngine`](crate::trade::LiveTradeEngine) persist them in the
+/// database, so the loss limits survive restarts. Managers created with [`Self::new`] start from
+/// the first observed trading state.
+pub struct RiskManager {
+    executor: Arc<dyn TradeExecutor>,
+    limits: RiskLimits,
+    tracker: Mutex<Option<NetValueTracker>>,
+    persistence: Option<RiskPersistence>,
+    // Held across the check and forwarding of calls that are checked against the limits
+    check_lock: AsyncMutex<()>,
+}
+
+/// Database record of the net value reference points of a [`RiskManager`].
+pub(in crate::trade) struct RiskPersistence {
+    db: Arc<Database>,
+    key: String,
+    persisted: Mutex<Option<NetValueReferences>>,
+}
+
+impl RiskPersistence {
+    /// Tracking is resumed from the `persisted` reference points, if any.
+    pub fn new(db: Arc<Database>, key: String, persisted: Option<NetValueReferences>) -> Self {
+        Self {
+            db,
+            key,
+            persisted: Mutex::new(persisted),
+        }
+    }
+}
+
+impl RiskManager {
+    /// Creates a new risk manager wrapping the given executor.
+    pub fn new(executor: Arc<dyn TradeExecutor>, limits: RiskLimits) -> Arc<Self> {
+        Self::with_persistence(executor, limits, None)
+    }
+
+    /// Creates a new risk manager wrapping the given executor, persisting the net value reference
+    /// points if `persistence` is set.
+    pub(in crate::trade) fn with_persistence(
+        executor: Arc<dyn TradeExecutor>,
+        limits: RiskLimits,
+        persistence: Option<RiskPersistence>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            executor,
+            limits,
+            tracker: Mutex::new(None),
+            persistence,
+            check_lock: AsyncMutex::new(()),
+        })
+    }
+
+    /// Returns the enforced limits.
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Fetches the trading state from the wrapped executor, updating the net value reference
+    /// points.
+    async fn observe_state(&self) -> TradeExecutorResult<TradingState> {
+        let state = self.executor.trading_state().await?;
+
+        let reference_update = {
+            let mut tracker = self
+                .tracker
+                .lock()
+                .expect("`RiskManager` mutex can't be poisoned");
+
+            let tracker = match tracker.as_mut() {
+                Some(tracker) => {
+                    tracker.update(&state);
+                    tracker
+                }
+                None => {
+                    let resumed = self.persistence.as_ref().and_then(|persistence| {
+                        let persisted = persistence
+                            .persisted
+                            .lock()
+                            .expect("`RiskManager` mutex can't be poisoned");
+                        Some(NetValueTracker::resume(persisted.clone()?, &state))
+                    });
+                    tracker.insert(resumed.unwrap_or_else(|| NetValueTracker::new(&state)))
+                }
+            };
+
+            self.persistence.as_ref().and_then(|persistence| {
+                let references = tracker.references();
+                let persisted = persistence
+                    .persisted
+                    .lock()
+                    .expect("`RiskManager` mutex can't be poisoned");
+
+                (persisted.as_ref() != Some(references)).then(|| (persistence, references.clone()))
+            })
+        };
+
+        // Best effort. On failure, the write is retried on the next observation, and the limits
+        // are still enforced against the reference points tracked in memory.
+        if let Some((persistence, references)) = reference_update
+            && persistence
+                .db
+                .key_values
+                .set_value(&persistence.key, &references.to_bytes())
+                .await
+                .is_ok()
+        {
+            *persistence
+                .persisted
+                .lock()
+                .expect("`RiskManager` mutex can't be poisoned") = Some(references);
+        }
+
+        Ok(state)
+    }
+
+    /// Checks the account-level loss limits against the last observed trading state.
+    fn check_loss_limits(&self) -> Result<(), RiskLimitError> {
+        let tracker = self
+            .tracker
+            .lock()
+            .expect("`RiskManager` mutex can't be poisoned");
+        match tracker.as_ref() {
+            Some(tracker) => self.limits.check_loss_limits(tracker),
+            None => Ok(()),
+        }
+    }
+
+    /// Closes all isolated trades and the cross-margin position.
+    async fn flatten(&self, state: &TradingState) -> TradeExecutorResult<()> {
+        if !state.running_map().is_empty() {
+            self.executor.isolated_order_close_all().await?;
+        }
+        if state.cross_position().quantity() != 0 {
+            self.executor.cross_order_close_position().await?;
+        }
+        Ok(())
+    }
+
+    /// Applies the configured breach action to a loss limit violation.
+    async fn handle_loss_limits(&self, state: &TradingState) -> TradeExecutorResult<()> {
+        if let Err(e) = self.check_loss_limits() {
+            if self.limits.breach_action == RiskBreachAction::Flatten {
+                self.flatten(state).await?;
+            }
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    fn check_exposure(
+        &self,
+        state: &TradingState,
+        current: Exposure,
+        projected: Exposure,
+        side: TradeSide,
+    ) -> Result<(), RiskLimitError> {
+        if let Some(max) = self.limits.max_notional_per_side {
+            let notional = projected.side(side);
+            if notional > max {
+                return Err(RiskLimitError::MaxNotionalPerSide {
+                    side,
+                    notional,
+                    max,
+                });
+            }
+        }
+
+        if let Some(max) = self.limits.max_total_leverage
+            && projected.gross() > current.gross()
+        {
+            let market_price = state.market_price().as_f64();
+            let notional_sats = projected.gross() as f64 * SATS_PER_BTC / market_price;
+            let leverage = notional_sats / state.total_net_value().max(1) as f64;
+            if leverage > max {
+                return Err(RiskLimitError::MaxTotalLeverage { leverage, max });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_liquidation_distance(
+        &self,
+        liquidation: Price,
+        market_price: Price,
+    ) -> Result<(), RiskLimitError> {
+        let Some(min) = self.limits.min_liquidation_distance else {
+            return Ok(());
+        };
+
+        let distance = liquidation_distance(liquidation, market_price);
+        if distance < min.as_f64() {
+            return Err(RiskLimitError::MinLiquidationDistance {
+                liquidation,
+                market_price,
+                distance,
+                min,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Checks a position whose margin or leverage would change. Only the limits the change moves
+    /// the position closer to are checked, so de-risking changes are always allowed.
+    fn check_position_change(
+        &self,
+        market_price: Price,
+        current: PositionRisk,
+        projected: PositionRisk,
+    ) -> Result<(), RiskLimitError> {
+        if let Some(max) = self.limits.max_total_leverage
+            && projected.leverage > current.leverage
+            && projected.leverage > max
+        {
+            return Err(RiskLimitError::MaxPositionLeverage {
+                leverage: projected.leverage,
+                max,
+            });
+        }
+
+        if let Some(liquidation) = projected.liquidation
+            && current.liquidation.is_none_or(|current| {
+                liquidation_distance(liquidation, market_price)
+                    < liquidation_distance(current, market_price)
+            })
+        {
+            self.check_liquidation_distance(liquidation, market_price)?;
+        }
+
+        Ok(())
+    }
+
+    async fn check_isolated_cash_in(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let state = self.observe_state().await?;
+        let market_price = state.market_price();
+
+        // Unknown trades and invalid amounts are left to be rejected by the wrapped executor
+        let Some((trade, _)) = state.running_map().get_by_id(trade_id) else {
+            return Ok(());
+        };
+        let Ok((_, _, leverage, liquidation, _)) = trade_util::evaluate_cash_in(
+            trade.side(),
+            trade.quantity(),
+            trade.margin(),
+            trade.price(),
+            trade.stoploss(),
+            market_price,
+            amount,
+        ) else {
+            return Ok(());
+        };
+
+        let current = PositionRisk {
+            leverage: trade.leverage().as_f64(),
+            liquidation: Some(trade.liquidation()),
+        };
+        let projected = PositionRisk {
+            leverage: leverage.as_f64(),
+            liquidation: Some(liquidation),
+        };
+
+        Ok(self.check_position_change(market_price, current, projected)?)
+    }
+
+    async fn check_cross_withdraw(&self, amount: NonZeroU64) -> TradeExecutorResult<()> {
+        let state = self.observe_state().await?;
+        let cross_position = state.cross_position();
+        let market_price = state.market_price();
+
+        if cross_position.quantity() == 0 {
+            return Ok(());
+        }
+
+        // Margin backing the position, as leverage of its notional (in satoshis)
+        let notional_sats =
+            cross_position.quantity().unsigned_abs() as f64 * SATS_PER_BTC / market_price.as_f64();
+        let leverage = |margin: u64| notional_sats / margin.max(1) as f64;
+
+        let projected_margin = cross_position.margin().saturating_sub(amount.get());
+
+        let current = PositionRisk {
+            leverage: leverage(cross_position.margin()),
+            liquidation: cross_position.liquidation(),
+        };
+        let projected = PositionRisk {
+            leverage: leverage(projected_margin),
+            liquidation: projected_cross_liquidation(
+                cross_position,
+                projected_margin,
+                cross_position.leverage(),
+            ),
+        };
+
+        Ok(self.check_position_change(market_price, current, projected)?)
+    }
+
+    async fn check_cross_set_leverage(&self, leverage: CrossLeverage) -> TradeExecutorResult<()> {
+        let state = self.observe_state().await?;
+        let cross_position = state.cross_position();
+
+        let current = PositionRisk {
+            leverage: cross_position.leverage().as_f64(),
+            liquidation: cross_position.liquidation(),
+        };
+        let projected = PositionRisk {
+            leverage: leverage.as_f64(),
+            liquidation: projected_cross_liquidation(
+                cross_position,
+                cross_position.margin(),
+                leverage,
+            ),
+        };
+
+        Ok(self.check_position_change(state.market_price(), current, projected)?)
+    }
+
+    async fn check_isolated_order(
+        &self,
+        request: &IsolatedOrderRequest,
+    ) -> TradeExecutorResult<()> {
+        let state = self.observe_state().await?;
+
+        self.handle_loss_limits(&state).await?;
+
+        if let Some(max) = self.limits.max_running_trades
+            && state.running_map().len() >= max
+        {
+            return Err(RiskLimitError::MaxRunningTrades { max }.into());
+        }
+
+        let market_price = state.market_price();
+
+        // Invalid sizes are left to be rejected by the wrapped executor
+        let Ok((quantity, _)) = request
+            .size()
+            .to_quantity_and_margin(market_price, request.leverage())
+        else {
+            return Ok(());
+        };
+
+        let cross_quantity = state.cross_position().quantity();
+        let current = Exposure::from_state(&state, cross_quantity);
+        let mut projected = current;
+        match request.side() {
+            TradeSide::Buy => projected.long += quantity.as_u64(),
+            TradeSide::Sell => projected.short += quantity.as_u64(),
+        }
+
+        self.check_exposure(&state, current, projected, request.side())?;
+
+        let liquidation = trade_util::est_liquidation_from_leverage(
+            request.side(),
+            quantity,
+            market_price,
+            request.leverage(),
+        );
+        self.check_liquidation_distance(liquidation, market_price)?;
+
+        Ok(())
+    }
+
+    async fn check_cross_order(&self, request: &CrossOrderRequest) -> TradeExecutorResult<()> {
+        let state = self.observe_state().await?;
+
+        let cross_quantity = state.cross_position().quantity();
+        let order_quantity = request.quantity().as_u64() as i64;
+        let projected_cross_quantity = match request.side() {
+            TradeSide::Buy => cross_quantity + order_quantity,
+            TradeSide::Sell => cross_quantity - order_quantity,
+        };
+
+        // Orders that only reduce the cross-margin position are always allowed. Orders flipping
+        // its side open new exposure on the other side, and are checked as such.
+        let flips_side = cross_quantity.signum() * projected_cross_quantity.signum() < 0;
+        if !flips_side && projected_cross_quantity.unsigned_abs() <= cross_quantity.unsigned_abs() {
+            return Ok(());
+        }
+
+        self.handle_loss_limits(&state).await?;
+
+        let projected_side = if projected_cross_quantity > 0 {
+            TradeSide::Buy
+        } else {
+            TradeSide::Sell
+        };
+
+        let current = Exposure::from_state(&state, cross_quantity);
+        let projected = Exposure::from_state(&state, projected_cross_quantity);
+
+        self.check_exposure(&state, current, projected, projected_side)?;
+
+        let cross_margin = state.cross_position().margin();
+        if cross_margin > 0
+            && let Ok(margin) = Margin::try_from(cross_margin)
+            && let Ok(quantity) = OrderQuantity::try_from(projected_cross_quantity.unsigned_abs())
+        {
+            let liquidation = trade_util::est_liquidation_from_margin(
+                projected_side,
+                quantity,
+                state.market_price(),
+                margin,
+            );
+            self.check_liquidation_distance(liquidation, state.market_price())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl TradeExecutor for RiskManager {
+    async fn isolated_order(&self, request: IsolatedOrderRequest) -> TradeExecutorResult<Uuid> {
+        let _guard = self.check_lock.lock().await;
+        self.check_isolated_order(&request).await?;
+        self.executor.isolated_order(request).await
+    }
+
+    async fn isolated_trade_add_margin(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        self.executor
+            .isolated_trade_add_margin(trade_id, amount)
+            .await
+    }
+
+    async fn isolated_trade_cash_in(
+        &self,
+        trade_id: Uuid,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<()> {
+        let _guard = self.check_lock.lock().await;
+        self.check_isolated_cash_in(trade_id, amount).await?;
+        self.executor.isolated_trade_cash_in(trade_id, amount).await
+    }
+
+    async fn isolated_order_close(&self, trade_id: Uuid) -> TradeExecutorResult<()> {
+        self.executor.isolated_order_close(trade_id).await
+    }
+
+    async fn isolated_order_close_longs(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        self.executor.isolated_order_close_longs().await
+    }
+
+    async fn isolated_order_close_shorts(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        self.executor.isolated_order_close_shorts().await
+    }
+
+    async fn isolated_order_close_all(&self) -> TradeExecutorResult<Vec<Uuid>> {
+        self.executor.isolated_order_close_all().await
+    }
+
+    async fn cross_deposit(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        self.executor.cross_deposit(amount).await
+    }
+
+    async fn cross_withdraw(
+        &self,
+        amount: NonZeroU64,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let _guard = self.check_lock.lock().await;
+        self.check_cross_withdraw(amount).await?;
+        self.executor.cross_withdraw(amount).await
+    }
+
+    async fn cross_set_leverage(
+        &self,
+        leverage: CrossLeverage,
+    ) -> TradeExecutorResult<Arc<dyn CrossPositionCore>> {
+        let _guard = self.check_lock.lock().await;
+        self.check_cross_set_leverage(leverage).await?;
+        self.executor.cross_set_leverage(leverage).await
+    }
+
+    async fn cross_order(&self, request: CrossOrderRequest) -> TradeExecutorResult<Uuid> {
+        let _guard = self.check_lock.lock().await;
+        self.check_cross_order(&request).await?;
+        self.executor.cross_order(request).await
+    }
+
+    async fn cross_order_close_position(&self) -> TradeExecutorResult<Option<Uuid>> {
+        self.executor.cross_order_close_position().await
+    }
+
+    async fn trading_state(&self) -> TradeExecutorResult<TradingState> {
+        let state = self.observe_state().await?;
+
+        if self.limits.breach_action == RiskBreachAction::Flatten
+            && self.check_loss_limits().is_err()
+            && (!state.running_map().is_empty() || state.cross_position().quantity() != 0)
+        {
+            self.flatten(&state).await?;
+            return self.executor.trading_state().await;
+        }
+
+        Ok(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use lnm_sdk::rest::v3::models::Leverage;
+
+    use crate::{
+        db::models::OhlcCandleRow,
+        error::TradeExecutorError,
+        trade::{BacktestConfig, backtest::executor::SimulatedTradeExecutor},
+    };
+
+    use super::*;
+
+    fn start_candle() -> OhlcCandleRow {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        OhlcCandleRow::new_simple(time, 100_000., 1_000)
+    }
+
+    fn long_request(quantity: u64, leverage: f64) -> IsolatedOrderRequest {
+        IsolatedOrderRequest::market(
+            TradeSide::Buy,
+            OrderQuantity::try_from(quantity).unwrap().into(),
+            Leverage::try_from(leverage).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_rejects_orders_over_limits() {
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle(), 10_000_000);
+        let limits = RiskLimits::default()
+            .with_max_running_trades(Some(2))
+            .with_max_notional_per_side(Some(5_000))
+            .with_min_liquidation_distance(Some(PercentageCapped::bounded(5.)));
+        let risk_manager = RiskManager::new(executor, limits);
+
+        risk_manager
+            .isolated_order(long_request(2_000, 2.))
+            .await
+            .unwrap();
+
+        let err = risk_manager
+            .isolated_order(long_request(4_000, 2.))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxNotionalPerSide {
+                side: TradeSide::Buy,
+                notional: 6_000,
+                max: 5_000,
+            })
+        ));
+
+        let err = risk_manager
+            .isolated_order(long_request(1_000, 50.))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MinLiquidationDistance { .. })
+        ));
+
+        risk_manager
+            .isolated_order(long_request(1_000, 2.))
+            .await
+            .unwrap();
+
+        let err = risk_manager
+            .isolated_order(long_request(1_000, 2.))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxRunningTrades { max: 2 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_serializes_concurrent_orders() {
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle(), 10_000_000);
+        let limits = RiskLimits::default().with_max_running_trades(Some(1));
+        let risk_manager = RiskManager::new(executor, limits);
+
+        let (first, second) = tokio::join!(
+            risk_manager.isolated_order(long_request(1_000, 2.)),
+            risk_manager.isolated_order(long_request(1_000, 2.)),
+        );
+
+        assert_eq!(
+            [&first, &second].iter().filter(|res| res.is_ok()).count(),
+            1
+        );
+        assert!(matches!(
+            first.and(second),
+            Err(TradeExecutorError::RiskLimit(
+                RiskLimitError::MaxRunningTrades { max: 1 }
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_checks_cross_position_flips() {
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle(), 10_000_000);
+        let limits = RiskLimits::default().with_max_notional_per_side(Some(5_000));
+        let risk_manager = RiskManager::new(executor, limits);
+
+        risk_manager
+            .cross_deposit(NonZeroU64::new(5_000_000).unwrap())
+            .await
+            .unwrap();
+        risk_manager
+            .cross_order_market_long(OrderQuantity::try_from(4_000).unwrap())
+            .await
+            .unwrap();
+        risk_manager
+            .isolated_order(IsolatedOrderRequest::market(
+                TradeSide::Sell,
+                OrderQuantity::try_from(3_000).unwrap().into(),
+                Leverage::try_from(2.).unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        // Flipping the 4,000 USD long into a 3,000 USD short adds to the 3,000 USD isolated short
+        let err = risk_manager
+            .cross_order_market_short(OrderQuantity::try_from(7_000).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxNotionalPerSide {
+                side: TradeSide::Sell,
+                notional: 6_000,
+                max: 5_000,
+            })
+        ));
+
+        // Reducing the long without flipping is allowed
+        risk_manager
+            .cross_order_market_short(OrderQuantity::try_from(4_000).unwrap())
+            .await
+            .unwrap();
+
+        let state = risk_manager.trading_state().await.unwrap();
+        assert_eq!(state.cross_position().quantity(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_checks_isolated_cash_ins() {
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle(), 10_000_000);
+        let limits = RiskLimits::default()
+            .with_max_total_leverage(Some(20.))
+            .with_min_liquidation_distance(Some(PercentageCapped::bounded(5.)));
+        let risk_manager = RiskManager::new(executor, limits);
+
+        let trade_id = risk_manager
+            .isolated_order(long_request(1_000, 10.))
+            .await
+            .unwrap();
+
+        // Cashing in 60k of the 100k sats margin would leave the trade at 25x
+        let err = risk_manager
+            .isolated_trade_cash_in(trade_id, NonZeroU64::new(60_000).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxPositionLeverage { .. })
+        ));
+
+        risk_manager
+            .isolated_trade_cash_in(trade_id, NonZeroU64::new(30_000).unwrap())
+            .await
+            .unwrap();
+
+        // Margin additions are always allowed
+        risk_manager
+            .isolated_trade_add_margin(trade_id, NonZeroU64::new(10_000).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_checks_cross_margin_changes() {
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle(), 10_000_000);
+        let limits = RiskLimits::default()
+            .with_max_total_leverage(Some(20.))
+            .with_min_liquidation_distance(Some(PercentageCapped::bounded(15.)));
+        let risk_manager = RiskManager::new(executor, limits);
+
+        let err = risk_manager
+            .cross_set_leverage(CrossLeverage::try_from(25_u64).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxPositionLeverage { .. })
+        ));
+
+        risk_manager
+            .cross_set_leverage(CrossLeverage::try_from(10_u64).unwrap())
+            .await
+            .unwrap();
+        risk_manager
+            .cross_deposit(NonZeroU64::new(1_000_000).unwrap())
+            .await
+            .unwrap();
+        risk_manager
+            .cross_order_market_long(OrderQuantity::try_from(2_000).unwrap())
+            .await
+            .unwrap();
+
+        // Leaving 300k sats of margin would move the liquidation price ~13% below the market
+        let err = risk_manager
+            .cross_withdraw(NonZeroU64::new(700_000).unwrap())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MinLiquidationDistance { .. })
+        ));
+
+        risk_manager
+            .cross_withdraw(NonZeroU64::new(600_000).unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_reports_loss_limits_before_running_trades() {
+        let start_candle = start_candle();
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 1_000_000);
+        let limits = RiskLimits::default()
+            .with_max_running_trades(Some(1))
+            .with_max_drawdown(Some(PercentageCapped::bounded(10.)));
+        let risk_manager = RiskManager::new(executor.clone(), limits);
+
+        risk_manager
+            .isolated_order(long_request(5_000, 10.))
+            .await
+            .unwrap();
+
+        let drop_candle =
+            OhlcCandleRow::new_simple(start_candle.time + Duration::minutes(1), 95_000., 1_000);
+        executor.candle_update(&drop_candle).await.unwrap();
+
+        let err = risk_manager
+            .isolated_order(long_request(1_000, 2.))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxDrawdown { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_risk_manager_flattens_on_drawdown() {
+        let start_candle = start_candle();
+        let executor =
+            SimulatedTradeExecutor::new(&BacktestConfig::default(), &start_candle, 1_000_000);
+        let limits = RiskLimits::default()
+            .with_max_drawdown(Some(PercentageCapped::bounded(10.)))
+            .with_breach_action(RiskBreachAction::Flatten);
+        let risk_manager = RiskManager::new(executor.clone(), limits);
+
+        risk_manager
+            .isolated_order(long_request(5_000, 10.))
+            .await
+            .unwrap();
+
+        let state = risk_manager.trading_state().await.unwrap();
+        assert_eq!(state.running_map().len(), 1);
+
+        // A 5% price drop on a 5,000 USD long loses ~250k sats, a ~25% drawdown
+        let drop_candle =
+            OhlcCandleRow::new_simple(start_candle.time + Duration::minutes(1), 95_000., 1_000);
+        executor.candle_update(&drop_candle).await.unwrap();
+
+        let state = risk_manager.trading_state().await.unwrap();
+        assert!(state.running_map().is_empty());
+
+        let err = risk_manager
+            .isolated_order(long_request(1_000, 2.))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TradeExecutorError::RiskLimit(RiskLimitError::MaxDrawdown { .. })
+        ));
+    }
+}
@@ -0,0 +1,538 @@
+use lnm_sdk::rest::v3::models::{
+    Leverage, Margin, OrderQuantity, PercentageCapped, Price, SATS_PER_BTC, TradeSide, trade_util,
+};
+
+use crate::{
+    db::models::OhlcCandleRow,
+    indicator::{Atr, Indicator},
+    shared::Period,
+};
+
+use super::{
+    core::{IsolatedOrderRequest, Stoploss, TradingState},
+    error::PositionSizingError,
+};
+
+type Result<T> = std::result::Result<T, PositionSizingError>;
+
+/// Determines the stoploss distance used by the [`PositionSizer`]. In both modes, the quantity is
+/// chosen so that hitting the stoploss loses the configured risk percentage of the total net value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SizingMode {
+    /// Fixed-fractional sizing, with the stoploss placed at a fixed percentage from the market
+    /// price.
+    FixedFractional { stoploss_distance: PercentageCapped },
+    /// Volatility-targeted sizing, with the stoploss placed at a multiple of the Average True Range
+    /// (in USD) from the market price. Positions get smaller as volatility increases.
+    VolatilityTargeted { atr: f64, atr_multiplier: f64 },
+}
+
+impl SizingMode {
+    /// Creates a fixed-fractional sizing mode with the given stoploss distance.
+    pub fn fixed_fractional(stoploss_distance: PercentageCapped) -> Self {
+        Self::FixedFractional { stoploss_distance }
+    }
+
+    /// Creates a volatility-targeted sizing mode, computing the [`Atr`] of the given candles. At
+    /// least `period` candles are required.
+    pub fn volatility_targeted(
+        candles: &[OhlcCandleRow],
+        period: Period,
+        atr_multiplier: f64,
+    ) -> Result<Self> {
+        if !atr_multiplier.is_finite() || atr_multiplier <= 0. {
+            return Err(PositionSizingError::InvalidAtrMultiplier(atr_multiplier));
+        }
+
+        let atr =
+            Atr::new(period)
+                .extend(candles)
+                .ok_or(PositionSizingError::NotEnoughCandles {
+                    period,
+                    available: candles.len(),
+                })?;
+
+        Ok(Self::VolatilityTargeted {
+            atr,
+            atr_multiplier,
+        })
+    }
+
+    fn stoploss(&self, side: TradeSide, market_price: Price) -> Result<Price> {
+        let distance = match self {
+            Self::FixedFractional { stoploss_distance } => {
+                market_price.as_f64() * stoploss_distance.as_f64() / 100.
+            }
+            Self::VolatilityTargeted {
+                atr,
+                atr_multiplier,
+            } => atr * atr_multiplier,
+        };
+
+        let invalid_stoploss = || PositionSizingError::InvalidStoploss {
+            side,
+            market_price,
+            distance,
+        };
+
+        let stoploss = match side {
+            TradeSide::Buy => Price::round(market_price.as_f64() - distance),
+            TradeSide::Sell => Price::round(market_price.as_f64() + distance),
+        }
+        .map_err(|_| invalid_stoploss())?;
+
+        let valid = match side {
+            TradeSide::Buy => stoploss < market_price,
+            TradeSide::Sell => stoploss > market_price,
+        };
+        if !valid {
+            return Err(invalid_stoploss());
+        }
+
+        Ok(stoploss)
+    }
+}
+
+/// Isolated-margin position derived by the [`PositionSizer`].
+#[derive(Debug, Clone)]
+pub struct IsolatedPositionSize {
+    side: TradeSide,
+    quantity: OrderQuantity,
+    leverage: Leverage,
+    margin: Margin,
+    stoploss: Price,
+    liquidation: Price,
+    est_max_loss: u64,
+}
+
+impl IsolatedPositionSize {
+    /// Returns the position side.
+    pub fn side(&self) -> TradeSide {
+        self.side
+    }
+
+    /// Returns the position quantity (in USD).
+    pub fn quantity(&self) -> OrderQuantity {
+        self.quantity
+    }
+
+    /// Returns the leverage, the highest one (up to the configured maximum) that keeps the
+    /// estimated liquidation price beyond the stoploss.
+    pub fn leverage(&self) -> Leverage {
+        self.leverage
+    }
+
+    /// Returns the margin (in satoshis) locked by the position.
+    pub fn margin(&self) -> Margin {
+        self.margin
+    }
+
+    /// Returns the stoploss price.
+    pub fn stoploss(&self) -> Price {
+        self.stoploss
+    }
+
+    /// Returns the estimated liquidation price.
+    pub fn liquidation(&self) -> Price {
+        self.liquidation
+    }
+
+    /// Returns the estimated loss (in satoshis) if the stoploss is hit, including trading fees.
+    pub fn est_max_loss(&self) -> u64 {
+        self.est_max_loss
+    }
+
+    /// Returns a market order request for the position, with a fixed stoploss.
+    pub fn to_order_request(&self) -> IsolatedOrderRequest {
+        IsolatedOrderRequest::market(self.side, self.quantity.into(), self.leverage)
+            .with_stoploss(Stoploss::Fixed(self.stoploss))
+            .expect("request without takeprofit must accept a stoploss")
+    }
+}
+
+/// Cross-margin order quantity derived by the [`PositionSizer`].
+///
+/// Cross-margin positions don't support stoplosses, so the operator is responsible for closing the
+/// position once the market price reaches [`stoploss`](Self::stoploss).
+#[derive(Debug, Clone)]
+pub struct CrossPositionSize {
+    side: TradeSide,
+    quantity: OrderQuantity,
+    stoploss: Price,
+    est_max_loss: u64,
+}
+
+impl CrossPositionSize {
+    /// Returns the order side.
+    pub fn side(&self) -> TradeSide {
+        self.side
+    }
+
+    /// Returns the order quantity (in USD).
+    pub fn quantity(&self) -> OrderQuantity {
+        self.quantity
+    }
+
+    /// Returns the price at which the position should be closed.
+    pub fn stoploss(&self) -> Price {
+        self.stoploss
+    }
+
+    /// Returns the estimated loss (in satoshis) if the position is closed at the stoploss,
+    /// including trading fees.
+    pub fn est_max_loss(&self) -> u64 {
+        self.est_max_loss
+    }
+}
+
+/// Derives position size and leverage from the account value, a risk percentage and the stoploss
+/// distance, respecting LN Markets quantity and leverage limits.
+///
+/// The quantity is chosen so that the estimated loss at the stoploss, including opening and
+/// closing fees, doesn't exceed the risk percentage of the total net value. Isolated positions are
+/// further reduced to fit the available balance.
+#[derive(Debug, Clone)]
+pub struct PositionSizer {
+    risk: PercentageCapped,
+    estimated_fee: PercentageCapped,
+    max_leverage: Leverage,
+    liquidation_buffer: PercentageCapped,
+}
+
+impl PositionSizer {
+    /// Creates a new sizer risking the given percentage of the total net value per position.
+    pub fn new(risk: PercentageCapped) -> Self {
+        Self {
+            risk,
+            estimated_fee: PercentageCapped::try_from(0.1)
+                .expect("must be a valid `PercentageCapped`"),
+            max_leverage: Leverage::MAX,
+            liquidation_buffer: PercentageCapped::try_from(10)
+                .expect("must be a valid `PercentageCapped`"),
+        }
+    }
+
+    /// Returns the percentage of the total net value risked per position.
+    pub fn risk(&self) -> PercentageCapped {
+        self.risk
+    }
+
+    /// Returns the estimated fee percentage used to evaluate opening and closing fees.
+    pub fn estimated_fee(&self) -> PercentageCapped {
+        self.estimated_fee
+    }
+
+    /// Returns the maximum leverage of isolated positions.
+    pub fn max_leverage(&self) -> Leverage {
+        self.max_leverage
+    }
+
+    /// Returns the margin buffer, as a percentage of the estimated loss at the stoploss, kept on
+    /// top of said loss so that the liquidation price stays beyond the stoploss.
+    pub fn liquidation_buffer(&self) -> PercentageCapped {
+        self.liquidation_buffer
+    }
+
+    /// Sets the estimated fee percentage used to evaluate opening and closing fees. Should match
+    /// the `trade_estimated_fee` of the backtest or live trading config.
+    ///
+    /// Default: `0.1` (0.1%)
+    pub fn with_estimated_fee(mut self, estimated_fee: PercentageCapped) -> Self {
+        self.estimated_fee = estimated_fee;
+        self
+    }
+
+    /// Sets the maximum leverage of isolated positions.
+    ///
+    /// Default: [`Leverage::MAX`]
+    pub fn with_max_leverage(mut self, max_leverage: Leverage) -> Self {
+        self.max_leverage = max_leverage;
+        self
+    }
+
+    /// Sets the margin buffer, as a percentage of the estimated loss at the stoploss, kept on top
+    /// of said loss so that the liquidation price stays beyond the stoploss.
+    ///
+    /// Default: `10` (10%)
+    pub fn with_liquidation_buffer(mut self, liquidation_buffer: PercentageCapped) -> Self {
+        self.liquidation_buffer = liquidation_buffer;
+        self
+    }
+
+    /// Sizes an isolated-margin position from the given trading state.
+    pub fn isolated_size(
+        &self,
+        state: &TradingState,
+        side: TradeSide,
+        mode: SizingMode,
+    ) -> Result<IsolatedPositionSize> {
+        self.isolated_size_at(
+            side,
+            state.total_net_value(),
+            state.balance(),
+            state.market_price(),
+            mode,
+        )
+    }
+
+    /// Sizes an isolated-margin position given the total net value and available balance (in
+    /// satoshis), and the market price.
+    pub fn isolated_size_at(
+        &self,
+        side: TradeSide,
+        total_net_value: u64,
+        balance: u64,
+        market_price: Price,
+        mode: SizingMode,
+    ) -> Result<IsolatedPositionSize> {
+        let stoploss = mode.stoploss(side, market_price)?;
+        let loss_per_usd = self.loss_per_usd(market_price, stoploss);
+        let mut quantity = self.risk_quantity(total_net_value, loss_per_usd)?;
+
+        loop {
+            let leverage = self.isolated_leverage(side, quantity, market_price, stoploss)?;
+
+            let (sized_quantity, margin, liquidation, opening_fee, closing_fee_reserved) =
+                trade_util::evaluate_open_trade_params(
+                    side,
+                    quantity.into(),
+                    leverage,
+                    market_price,
+                    Some(stoploss),
+                    None,
+                    self.estimated_fee,
+                )?;
+
+            let cost = margin.as_u64() + opening_fee + closing_fee_reserved;
+            if cost <= balance {
+                return Ok(IsolatedPositionSize {
+                    side,
+                    quantity: sized_quantity,
+                    leverage,
+                    margin,
+                    stoploss,
+                    liquidation,
+                    est_max_loss: (sized_quantity.as_f64() * loss_per_usd).ceil() as u64,
+                });
+            }
+
+            // Scale the quantity down to the balance, ensuring progress despite rounding
+            let fitted = (quantity.as_f64() * balance as f64 / cost as f64).floor() as u64;
+            let fitted = fitted.min(quantity.as_u64() - 1);
+
+            if fitted < OrderQuantity::MIN.as_u64() {
+                return Err(PositionSizingError::InsufficientBalance { balance });
+            }
+
+            quantity = OrderQuantity::bounded(fitted as f64);
+        }
+    }
+
+    /// Sizes a cross-margin order from the given trading state.
+    pub fn cross_size(
+        &self,
+        state: &TradingState,
+        side: TradeSide,
+        mode: SizingMode,
+    ) -> Result<CrossPositionSize> {
+        self.cross_size_at(side, state.total_net_value(), state.market_price(), mode)
+    }
+
+    /// Sizes a cross-margin order given the total net value (in satoshis) and the market price.
+    ///
+    /// The margin required by the order depends on the account cross leverage, so the available
+    /// balance is validated by the executor when the order is placed.
+    pub fn cross_size_at(
+        &self,
+        side: TradeSide,
+        total_net_value: u64,
+        market_price: Price,
+        mode: SizingMode,
+    ) -> Result<CrossPositionSize> {
+        let stoploss = mode.stoploss(side, market_price)?;
+        let loss_per_usd = self.loss_per_usd(market_price, stoploss);
+        let quantity = self.risk_quantity(total_net_value, loss_per_usd)?;
+
+        Ok(CrossPositionSize {
+            side,
+            quantity,
+            stoploss,
+            est_max_loss: (quantity.as_f64() * loss_per_usd).ceil() as u64,
+        })
+    }
+
+    /// Returns the estimated loss (in satoshis) per USD of quantity when closing at the stoploss,
+    /// including opening and closing fees.
+    fn loss_per_usd(&self, market_price: Price, stoploss: Price) -> f64 {
+        let entry_inverse = SATS_PER_BTC / market_price.as_f64();
+        let stoploss_inverse = SATS_PER_BTC / stoploss.as_f64();
+        let fee = self.estimated_fee.as_f64() / 100.;
+
+        (entry_inverse - stoploss_inverse).abs() + fee * (entry_inverse + stoploss_inverse)
+    }
+
+    fn risk_quantity(&self, total_net_value: u64, loss_per_usd: f64) -> Result<OrderQuantity> {
+        let risk_budget = total_net_value as f64 * self.risk.as_f64() / 100.;
+        let quantity = (risk_budget / loss_per_usd).floor();
+
+        if quantity < OrderQuantity::MIN.as_f64() {
+            return Err(PositionSizingError::RiskBudgetTooSmall {
+                risk_budget: risk_budget.floor() as u64,
+                min_loss: loss_per_usd * OrderQuantity::MIN.as_f64(),
+            });
+        }
+
+        Ok(OrderQuantity::bounded(quantity))
+    }
+
+    /// Returns the highest leverage, up to the configured maximum, whose (conservatively floored)
+    /// margin covers the loss at the stoploss plus the liquidation buffer.
+    fn isolated_leverage(
+        &self,
+        side: TradeSide,
+        quantity: OrderQuantity,
+        market_price: Price,
+        stoploss: Price,
+    ) -> Result<Leverage> {
+        let quantity = quantity.as_f64();
+        let inverse_delta =
+            (SATS_PER_BTC / market_price.as_f64() - SATS_PER_BTC / stoploss.as_f64()).abs();
+        let buffer = 1. + self.liquidation_buffer.as_f64() / 100.;
+
+        // One extra sat compensates the margin flooring of the liquidation estimate
+        let required_margin = (quantity * inverse_delta * buffer).ceil() + 1.;
+        let notional = quantity * SATS_PER_BTC / market_price.as_f64();
+        let leverage =
+            ((notional / required_margin * 100.).floor() / 100.).min(self.max_leverage.as_f64());
+
+        if leverage < Leverage::MIN.as_f64() {
+            // Shorts can't be liquidated at the minimum leverage
+            if side == TradeSide::Sell {
+                return Ok(Leverage::MIN);
+            }
+
+            return Err(PositionSizingError::StoplossBeyondLiquidation {
+                side,
+                stoploss,
+                market_price,
+            });
+        }
+
+        Ok(Leverage::bounded(leverage))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+
+    use super::*;
+
+    fn price(value: f64) -> Price {
+        Price::try_from(value).unwrap()
+    }
+
+    #[test]
+    fn test_isolated_size_fixed_fractional() {
+        let sizer = PositionSizer::new(PercentageCapped::try_from(1).unwrap());
+        let mode = SizingMode::fixed_fractional(PercentageCapped::try_from(2).unwrap());
+        let market_price = price(100_000.);
+
+        for side in [TradeSide::Buy, TradeSide::Sell] {
+            let size = sizer
+                .isolated_size_at(side, 10_000_000, 10_000_000, market_price, mode)
+                .unwrap();
+
+            // Risking 100_000 sats at ~2.2% loss per USD (including fees)
+            assert!(size.est_max_loss() <= 100_000);
+            assert!(size.est_max_loss() > 95_000);
+            assert!(size.leverage().as_f64() > 1.);
+
+            match side {
+                TradeSide::Buy => {
+                    assert_eq!(size.stoploss(), price(98_000.));
+                    assert!(size.liquidation() < size.stoploss());
+                }
+                TradeSide::Sell => {
+                    assert_eq!(size.stoploss(), price(102_000.));
+                    assert!(size.liquidation() > size.stoploss());
+                }
+            }
+
+            let request = size.to_order_request();
+            assert_eq!(request.side(), side);
+        }
+
+        // Limited by the available balance
+        let size = sizer
+            .isolated_size_at(TradeSide::Buy, 10_000_000, 50_000, market_price, mode)
+            .unwrap();
+        assert!(size.est_max_loss() < 50_000);
+
+        let err = sizer
+            .isolated_size_at(TradeSide::Buy, 10_000_000, 10, market_price, mode)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PositionSizingError::InsufficientBalance { balance: 10 }
+        ));
+
+        // Stoploss beyond the liquidation price at the minimum leverage
+        let mode = SizingMode::fixed_fractional(PercentageCapped::try_from(60).unwrap());
+        let err = sizer
+            .isolated_size_at(TradeSide::Buy, 10_000_000, 10_000_000, market_price, mode)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PositionSizingError::StoplossBeyondLiquidation { .. }
+        ));
+    }
+
+    #[test]
+    fn test_volatility_targeted_sizing() {
+        let time = DateTime::from_timestamp(1_700_000_040, 0).unwrap();
+        let candle = |close: f64, range: f64| {
+            let mut candle = OhlcCandleRow::new_simple(time, close, 1_000);
+            candle.high = close + range / 2.;
+            candle.low = close - range / 2.;
+            candle
+        };
+        let calm: Vec<_> = (0..15).map(|_| candle(100_000., 500.)).collect();
+        let volatile: Vec<_> = (0..15).map(|_| candle(100_000., 2_000.)).collect();
+
+        let period = Period::try_from(14).unwrap();
+
+        let err = SizingMode::volatility_targeted(&calm[..13], period, 2.).unwrap_err();
+        assert!(matches!(
+            err,
+            PositionSizingError::NotEnoughCandles { available: 13, .. }
+        ));
+
+        let calm_mode = SizingMode::volatility_targeted(&calm, period, 2.).unwrap();
+        assert_eq!(
+            calm_mode,
+            SizingMode::VolatilityTargeted {
+                atr: 500.,
+                atr_multiplier: 2.
+            }
+        );
+        let volatile_mode = SizingMode::volatility_targeted(&volatile, period, 2.).unwrap();
+
+        let sizer = PositionSizer::new(PercentageCapped::try_from(1).unwrap());
+        let market_price = price(100_000.);
+
+        let calm_size = sizer
+            .cross_size_at(TradeSide::Sell, 10_000_000, market_price, calm_mode)
+            .unwrap();
+        assert_eq!(calm_size.stoploss(), price(101_000.));
+
+        let volatile_size = sizer
+            .cross_size_at(TradeSide::Sell, 10_000_000, market_price, volatile_mode)
+            .unwrap();
+        assert_eq!(volatile_size.stoploss(), price(104_000.));
+
+        assert!(volatile_size.quantity().as_u64() < calm_size.quantity().as_u64());
+        assert!(calm_size.est_max_loss() <= 100_000);
+        assert!(volatile_size.est_max_loss() <= 100_000);
+    }
+}
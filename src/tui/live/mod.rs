@@ -199,6 +199,22 @@ impl LiveTui {
                                 )
                                 .await
                             }
+                            LiveTradeUpdate::ExecutorWarning(warning) => {
+                                send_ui_msg(
+                                    &ui_tx,
+                                    LiveUiMessage::LogEntry(format!("Executor warning: {warning}")),
+                                )
+                                .await
+                            }
+                            LiveTradeUpdate::DeadManSwitchTriggered(trigger) => {
+                                send_ui_msg(
+                                    &ui_tx,
+                                    LiveUiMessage::LogEntry(format!(
+                                        "Dead man's switch triggered: {trigger}"
+                                    )),
+                                )
+                                .await
+                            }
                         };
 
                         if let Err(e) = result {
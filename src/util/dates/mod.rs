@@ -43,6 +43,10 @@ pub(crate) trait DateTimeExt {
     /// Rounds down to the previous valid funding settlement time (or returns self if already on-grid).
     fn floor_funding_settlement_time(&self) -> DateTime<Utc>;
 
+    /// Steps back a number of funding settlements from the settlement time at or before this
+    /// timestamp, following the funding settlement grid phase transitions.
+    fn step_back_funding_settlements(&self, settlements: u32) -> DateTime<Utc>;
+
     /// Floors this timestamp to the start of the current hour.
     fn floor_hour(&self) -> DateTime<Utc>;
 
@@ -218,6 +222,16 @@ impl DateTimeExt for DateTime<Utc> {
         }
     }
 
+    fn step_back_funding_settlements(&self, settlements: u32) -> DateTime<Utc> {
+        let mut time = self.floor_funding_settlement_time();
+
+        for _ in 0..settlements {
+            time = (time - Duration::nanoseconds(1)).floor_funding_settlement_time();
+        }
+
+        time
+    }
+
     fn floor_hour(&self) -> DateTime<Utc> {
         self.trunc_subsecs(0)
             .with_second(0)
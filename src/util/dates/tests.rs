@@ -843,3 +843,33 @@ mod floor_funding_settlement_time {
         assert_eq!(time.floor_funding_settlement_time(), LNM_SETTLEMENT_B_END);
     }
 }
+
+mod step_back_funding_settlements {
+    use super::*;
+
+    #[test]
+    fn zero_settlements_floors() {
+        let time = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            time.step_back_funding_settlements(0),
+            Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn phase_c_steps_back_8h() {
+        let time = Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap();
+        assert_eq!(
+            time.step_back_funding_settlements(3),
+            Utc.with_ymd_and_hms(2025, 12, 31, 8, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn crosses_dead_zone_into_phase_b() {
+        assert_eq!(
+            LNM_SETTLEMENT_C_START.step_back_funding_settlements(1),
+            LNM_SETTLEMENT_B_END
+        );
+    }
+}
@@ -0,0 +1,1139 @@
+//! End-to-end tests running the sync and live trading engines against the offline mock LN Markets
+//! server and its price stream. Requires a Postgres database set via `POSTGRES_DB_URL`, so the
+//! tests are ignored by default and run with `cargo test -- --ignored`. Each test uses a dedicated
+//! schema, dropped when the test ends.
+
+use std::{
+    env,
+    num::NonZeroU64,
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use sqlx::{AssertSqlSafe, Connection, PgConnection};
+use tokio::{runtime, time};
+use uuid::Uuid;
+
+use quantoxide::{
+    Database, DatabaseConfig,
+    error::{BacktestError, LiveError, Result, SyncError},
+    models::{
+        Leverage, Lookback, MinIterationInterval, OhlcCandleRow, OrderQuantity, Price, TradeSide,
+        TradeSize,
+    },
+    sync::{
+        FundingSettlementsState, PriceHistoryState, SyncConfig, SyncEngine, SyncMode, SyncStatus,
+        SyncUpdate,
+    },
+    testing::{MockLnmConfig, MockLnmServer, MockPriceFeed},
+    trade::{
+        BacktestConfig, BacktestEngine, BacktestParallelEngine, BacktestStatus,
+        IsolatedOrderRequest, LiveTradeConfig, LiveTradeEngine, LiveTradeExecutorAction,
+        LiveTradeStatus, LiveTradeUpdate, OperatorContext, RawOperator, RiskLimits, Stoploss,
+        TradeCloseReason, TradeClosed, TradeExecutor,
+    },
+};
+
+const TIMEOUT: time::Duration = time::Duration::from_secs(90);
+
+const REQUIRES_DB: &str = "requires a Postgres database set via `POSTGRES_DB_URL`";
+
+/// Database scoped to a dedicated schema, which is dropped when the guard goes out of scope.
+struct TestDb {
+    pg_url: String,
+    schema: String,
+    db: Arc<Database>,
+}
+
+impl TestDb {
+    async fn new() -> Self {
+        dotenvy::dotenv().ok();
+
+        let pg_url = env::var("POSTGRES_DB_URL").expect(REQUIRES_DB);
+
+        let schema = format!("e2e_{}", Uuid::new_v4().simple());
+        let config = DatabaseConfig::default()
+            .with_schema(schema.clone())
+            .expect("schema name is valid");
+
+        let db = Database::with_config(&pg_url, &config)
+            .await
+            .expect("database is available");
+
+        Self { pg_url, schema, db }
+    }
+
+    fn db(&self) -> Arc<Database> {
+        self.db.clone()
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let pg_url = self.pg_url.clone();
+        let query = format!(r#"DROP SCHEMA IF EXISTS "{}" CASCADE"#, self.schema);
+
+        // The test runtime can't be blocked on, so the schema is dropped from a separate thread
+        let drop_res = thread::spawn(move || {
+            let runtime = runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("runtime builds");
+
+            runtime.block_on(async {
+                let mut conn = PgConnection::connect(&pg_url).await?;
+                // The schema name is generated by the test
+                sqlx::query(AssertSqlSafe(query)).execute(&mut conn).await?;
+                conn.close().await
+            })
+        })
+        .join();
+
+        if let Ok(Err(e)) = drop_res {
+            eprintln!("failed to drop test schema {}: {e}", self.schema);
+        }
+    }
+}
+
+fn feed_start() -> DateTime<Utc> {
+    (Utc::now() - Duration::days(1))
+        .duration_trunc(Duration::days(1))
+        .expect("is valid")
+}
+
+/// Runs a backfill against the mock server until the price history is complete.
+async fn backfill(db: Arc<Database>, server: &MockLnmServer, start: DateTime<Utc>) {
+    let config = SyncConfig::default()
+        .with_rest_api_endpoint(server.rest_api_endpoint())
+        .with_stream_enabled(false)
+        .with_price_history_reach(start)
+        .with_funding_settlement_reach(start);
+
+    let engine = SyncEngine::new(config, db, SyncMode::backfill()).expect("engine is valid");
+    let mut sync_rx = engine.update_receiver();
+    let controller = engine.start();
+
+    time::timeout(TIMEOUT, async {
+        loop {
+            match sync_rx.recv().await.expect("channel is open") {
+                SyncUpdate::Status(SyncStatus::Backfilled) => break,
+                SyncUpdate::Status(SyncStatus::Terminated(err)) => {
+                    panic!("sync terminated: {err}")
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("sync completes");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+}
+
+fn live_config(server: &MockLnmServer, start: DateTime<Utc>) -> LiveTradeConfig {
+    LiveTradeConfig::default()
+        .with_rest_api_endpoint(server.rest_api_endpoint())
+        .with_stream_api_endpoint(server.stream_api_endpoint())
+        .with_price_history_reach(start)
+        .with_funding_settlement_reach(start)
+        .with_price_history_re_sync_interval(1)
+        .with_trading_session_refresh_interval(500)
+}
+
+/// Opens a single isolated long with a fixed stoploss and records closed trades.
+struct StoplossOperator {
+    trade_executor: OnceLock<Arc<dyn TradeExecutor>>,
+    stoploss: f64,
+    opened: AtomicBool,
+    closed: Arc<Mutex<Vec<(Uuid, TradeCloseReason)>>>,
+}
+
+impl StoplossOperator {
+    fn boxed(stoploss: f64, closed: Arc<Mutex<Vec<(Uuid, TradeCloseReason)>>>) -> Box<Self> {
+        Box::new(Self {
+            trade_executor: OnceLock::new(),
+            stoploss,
+            opened: AtomicBool::new(false),
+            closed,
+        })
+    }
+}
+
+#[async_trait]
+impl RawOperator for StoplossOperator {
+    fn set_trade_executor(&mut self, trade_executor: Arc<dyn TradeExecutor>) -> Result<()> {
+        if self.trade_executor.set(trade_executor).is_err() {
+            return Err("trade executor was already set".into());
+        }
+        Ok(())
+    }
+
+    fn lookback(&self) -> Option<Lookback> {
+        None
+    }
+
+    fn min_iteration_interval(&self) -> MinIterationInterval {
+        MinIterationInterval::MIN
+    }
+
+    async fn iterate(&self, _candles: &[OhlcCandleRow]) -> Result<()> {
+        if self.opened.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let trade_executor = self
+            .trade_executor
+            .get()
+            .ok_or("trade executor was not set")?;
+
+        let request = IsolatedOrderRequest::market(
+            TradeSide::Buy,
+            TradeSize::quantity(100)?,
+            Leverage::try_from(10)?,
+        )
+        .with_stoploss(Stoploss::fixed(Price::try_from(self.stoploss)?))?;
+
+        trade_executor.isolated_order(request).await?;
+
+        Ok(())
+    }
+
+    async fn on_trade_closed(
+        &self,
+        trade: &dyn TradeClosed,
+        reason: TradeCloseReason,
+    ) -> Result<()> {
+        self.closed
+            .lock()
+            .expect("not poisoned")
+            .push((trade.id(), reason));
+        Ok(())
+    }
+}
+
+/// Persists the time of its last iteration as its state, and records the state it was restored
+/// from. Fails when iterating at or after `fail_from`, if set.
+struct StatefulOperator {
+    last_iteration: Arc<Mutex<Option<DateTime<Utc>>>>,
+    restored: Arc<Mutex<Option<DateTime<Utc>>>>,
+    fail_from: Option<DateTime<Utc>>,
+}
+
+impl StatefulOperator {
+    fn boxed(
+        last_iteration: Arc<Mutex<Option<DateTime<Utc>>>>,
+        restored: Arc<Mutex<Option<DateTime<Utc>>>>,
+        fail_from: Option<DateTime<Utc>>,
+    ) -> Box<Self> {
+        Box::new(Self {
+            last_iteration,
+            restored,
+            fail_from,
+        })
+    }
+}
+
+#[async_trait]
+impl RawOperator for StatefulOperator {
+    fn set_trade_executor(&mut self, _trade_executor: Arc<dyn TradeExecutor>) -> Result<()> {
+        Ok(())
+    }
+
+    fn lookback(&self) -> Option<Lookback> {
+        None
+    }
+
+    fn min_iteration_interval(&self) -> MinIterationInterval {
+        MinIterationInterval::MIN
+    }
+
+    async fn iterate(&self, _candles: &[OhlcCandleRow]) -> Result<()> {
+        Ok(())
+    }
+
+    async fn iterate_with_context(&self, context: &OperatorContext<'_>) -> Result<()> {
+        if self.fail_from.is_some_and(|from| context.time() >= from) {
+            return Err("operator failed".into());
+        }
+
+        *self.last_iteration.lock().expect("not poisoned") = Some(context.time());
+
+        Ok(())
+    }
+
+    fn save_state(&self) -> Result<Option<Vec<u8>>> {
+        let last_iteration = *self.last_iteration.lock().expect("not poisoned");
+        Ok(last_iteration.map(|time| time.to_rfc3339().into_bytes()))
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> Result<()> {
+        let time = DateTime::parse_from_rfc3339(std::str::from_utf8(state)?)?.with_timezone(&Utc);
+        *self.restored.lock().expect("not poisoned") = Some(time);
+        Ok(())
+    }
+}
+
+fn shared_time() -> Arc<Mutex<Option<DateTime<Utc>>>> {
+    Arc::new(Mutex::new(None))
+}
+
+/// Runs a fixed script of trading actions once, recording the outcome of each of them.
+struct ShadowScriptOperator {
+    trade_executor: OnceLock<Arc<dyn TradeExecutor>>,
+    ran: AtomicBool,
+    outcomes: Arc<Mutex<Vec<(&'static str, bool)>>>,
+}
+
+impl ShadowScriptOperator {
+    fn boxed(outcomes: Arc<Mutex<Vec<(&'static str, bool)>>>) -> Box<Self> {
+        Box::new(Self {
+            trade_executor: OnceLock::new(),
+            ran: AtomicBool::new(false),
+            outcomes,
+        })
+    }
+}
+
+#[async_trait]
+impl RawOperator for ShadowScriptOperator {
+    fn set_trade_executor(&mut self, trade_executor: Arc<dyn TradeExecutor>) -> Result<()> {
+        if self.trade_executor.set(trade_executor).is_err() {
+            return Err("trade executor was already set".into());
+        }
+        Ok(())
+    }
+
+    fn lookback(&self) -> Option<Lookback> {
+        None
+    }
+
+    fn min_iteration_interval(&self) -> MinIterationInterval {
+        MinIterationInterval::MIN
+    }
+
+    async fn iterate(&self, _candles: &[OhlcCandleRow]) -> Result<()> {
+        if self.ran.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let trade_executor = self
+            .trade_executor
+            .get()
+            .ok_or("trade executor was not set")?;
+
+        let mut outcomes = Vec::new();
+
+        let request = IsolatedOrderRequest::market(
+            TradeSide::Buy,
+            TradeSize::quantity(100)?,
+            Leverage::try_from(10)?,
+        );
+        let trade_id = trade_executor.isolated_order(request).await?;
+
+        let state = trade_executor.trading_state().await?;
+        outcomes.push(("state_opened", state.running_map().contains(&trade_id)));
+
+        let add_margin = trade_executor
+            .isolated_trade_add_margin(trade_id, NonZeroU64::new(1_000).expect("not zero"))
+            .await;
+        outcomes.push(("add_margin", add_margin.is_ok()));
+
+        let close = trade_executor.isolated_order_close(trade_id).await;
+        outcomes.push(("close", close.is_ok()));
+
+        let close_again = trade_executor.isolated_order_close(trade_id).await;
+        outcomes.push(("close_again", close_again.is_ok()));
+
+        let state = trade_executor.trading_state().await?;
+        outcomes.push(("state_closed", !state.running_map().contains(&trade_id)));
+
+        trade_executor
+            .cross_deposit(NonZeroU64::new(1_000_000).expect("not zero"))
+            .await?;
+        trade_executor
+            .cross_order_market_long(OrderQuantity::try_from(100)?)
+            .await?;
+
+        let state = trade_executor.trading_state().await?;
+        outcomes.push(("state_cross_long", state.cross_position().quantity() == 100));
+
+        let close_position = trade_executor.cross_order_close_position().await?;
+        outcomes.push(("close_position", close_position.is_some()));
+
+        let close_position_again = trade_executor.cross_order_close_position().await?;
+        outcomes.push(("close_position_again", close_position_again.is_some()));
+
+        let state = trade_executor.trading_state().await?;
+        outcomes.push(("state_cross_flat", state.cross_position().quantity() == 0));
+
+        *self.outcomes.lock().expect("not poisoned") = outcomes;
+
+        Ok(())
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn sync_engine_backfills_from_mock_server() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let config = SyncConfig::default()
+        .with_rest_api_endpoint(server.rest_api_endpoint())
+        .with_stream_enabled(false)
+        .with_price_history_reach(start)
+        .with_funding_settlement_reach(start);
+
+    let engine = SyncEngine::new(config, db, SyncMode::backfill()).expect("engine is valid");
+    let mut sync_rx = engine.update_receiver();
+    let controller = engine.start();
+
+    let mut backfilled = false;
+    let mut price_history: Option<PriceHistoryState> = None;
+    let mut funding_settlements: Option<FundingSettlementsState> = None;
+
+    // State updates are forwarded by separate tasks, and may be received after the status update
+    time::timeout(TIMEOUT, async {
+        while !backfilled
+            || price_history.as_ref().is_none_or(|state| {
+                state
+                    .bound_end()
+                    .is_none_or(|end| end < Utc::now() - Duration::minutes(2))
+            })
+            || funding_settlements
+                .as_ref()
+                .is_none_or(|state| state.bound_start().is_none_or(|bound| bound > start))
+        {
+            match sync_rx.recv().await.expect("channel is open") {
+                SyncUpdate::Status(SyncStatus::Backfilled) => backfilled = true,
+                SyncUpdate::Status(SyncStatus::Terminated(err)) => {
+                    panic!("sync terminated: {err}")
+                }
+                SyncUpdate::PriceHistoryState(state) => price_history = Some(state),
+                SyncUpdate::FundingSettlementsState(state) => funding_settlements = Some(state),
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("sync completes");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    let price_history = price_history.expect("price history state was reported");
+    assert!(
+        price_history
+            .bound_start()
+            .is_some_and(|bound| bound <= start)
+    );
+    assert!(price_history.gaps().is_empty());
+
+    let funding_settlements = funding_settlements.expect("funding settlements state was reported");
+    assert!(funding_settlements.missing().is_empty());
+
+    assert!(server.requests().iter().all(|req| !req.authenticated()));
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn sync_engine_collects_price_ticks_from_mock_stream_across_reconnects() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let config = SyncConfig::default()
+        .with_rest_api_endpoint(server.rest_api_endpoint())
+        .with_stream_api_endpoint(server.stream_api_endpoint())
+        .with_price_history_reach(start)
+        .with_funding_settlement_reach(start)
+        .with_restart_interval(1);
+
+    let engine =
+        SyncEngine::new(config, db, SyncMode::live_no_lookback()).expect("engine is valid");
+    let mut sync_rx = engine.update_receiver();
+    let controller = engine.start();
+
+    // Ticks are only collected from the stream, so the first one signals the engine is listening
+    time::timeout(TIMEOUT, async {
+        loop {
+            match sync_rx.recv().await.expect("channel is open") {
+                SyncUpdate::PriceTick(tick) => {
+                    assert_eq!(tick.last_price, 100_000.);
+                    break;
+                }
+                SyncUpdate::Status(SyncStatus::Terminated(err)) => {
+                    panic!("sync terminated: {err}")
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("first tick is collected");
+
+    server.set_price(101_000.).await.expect("price is set");
+
+    time::timeout(TIMEOUT, async {
+        loop {
+            if let SyncUpdate::PriceTick(tick) = sync_rx.recv().await.expect("channel is open")
+                && tick.last_price == 101_000.
+            {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("new price is collected");
+
+    // The engine restarts after the connection is closed, and collects ticks again once it
+    // reconnects
+    server.disconnect_stream_clients();
+    server.set_price(102_000.).await.expect("price is set");
+
+    time::timeout(TIMEOUT, async {
+        loop {
+            if let SyncUpdate::PriceTick(tick) = sync_rx.recv().await.expect("channel is open")
+                && tick.last_price == 102_000.
+            {
+                break;
+            }
+        }
+    })
+    .await
+    .expect("price is collected after reconnecting");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    let requests = server.requests();
+    let stream_connections = requests.iter().filter(|req| req.path() == "/v1").count();
+    assert!(stream_connections >= 2);
+    assert!(requests.iter().all(|req| !req.authenticated()));
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn live_trade_engine_reports_stoploss_closed_by_mock_server() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let closed = Arc::new(Mutex::new(Vec::new()));
+    let engine = LiveTradeEngine::with_raw_operator(
+        live_config(&server, start),
+        db.clone(),
+        "key",
+        "secret",
+        "passphrase",
+        StoplossOperator::boxed(98_000., closed.clone()),
+    )
+    .expect("engine is valid");
+
+    let mut live_rx = engine.update_receiver();
+    let controller = engine.start().await.expect("engine starts");
+
+    time::timeout(TIMEOUT, async {
+        while server
+            .running_trades()
+            .await
+            .expect("valid state")
+            .is_empty()
+        {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("trade is opened on the mock server");
+
+    server.set_price(97_000.).await.expect("price is set");
+
+    let closed_trade = time::timeout(TIMEOUT, async {
+        loop {
+            match live_rx.recv().await.expect("channel is open") {
+                LiveTradeUpdate::ClosedTrade(trade) => break trade,
+                LiveTradeUpdate::Status(LiveTradeStatus::Terminated(err)) => {
+                    panic!("live trade terminated: {err}")
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("closed trade is reported");
+
+    assert_eq!(
+        closed_trade.exit_price(),
+        Some(Price::try_from(98_000.).unwrap())
+    );
+
+    let server_closed = server.closed_trades().await.expect("valid state");
+    assert_eq!(server_closed.len(), 1);
+    assert_eq!(server_closed[0].id(), closed_trade.id());
+
+    time::timeout(TIMEOUT, async {
+        while closed.lock().expect("not poisoned").is_empty() {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("operator is notified");
+
+    assert_eq!(
+        closed.lock().expect("not poisoned")[0],
+        (closed_trade.id(), TradeCloseReason::Stoploss)
+    );
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    let actions = db
+        .get_executor_actions(server.account_id().await, start, Utc::now())
+        .await
+        .expect("actions are available");
+    let order = actions
+        .iter()
+        .find(|action| action.kind == "isolated_order")
+        .expect("order is audited");
+    let request: serde_json::Value =
+        serde_json::from_str(&order.request).expect("request is valid JSON");
+    let response: serde_json::Value =
+        serde_json::from_str(order.response.as_deref().expect("order succeeded"))
+            .expect("response is valid JSON");
+
+    assert!(!order.shadow);
+    assert_eq!(request["side"], "Buy");
+    assert_eq!(request["stoploss"], 98_000.);
+    assert_eq!(response["id"], closed_trade.id().to_string());
+    assert_eq!(response["side"], "Buy");
+    assert_eq!(response["stoploss"], 98_000.);
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn live_trade_engine_persists_risk_limit_references() {
+    let test_db = TestDb::new().await;
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let config = live_config(&server, start).with_risk_limits(Some(
+        RiskLimits::default().with_max_daily_loss(Some(1_000_000)),
+    ));
+    let engine = LiveTradeEngine::with_raw_operator(
+        config,
+        test_db.db(),
+        "key",
+        "secret",
+        "passphrase",
+        StoplossOperator::boxed(95_000., Arc::new(Mutex::new(Vec::new()))),
+    )
+    .expect("engine is valid");
+
+    let controller = engine.start().await.expect("engine starts");
+
+    time::timeout(TIMEOUT, async {
+        while server
+            .running_trades()
+            .await
+            .expect("valid state")
+            .is_empty()
+        {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("trade is opened on the mock server");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    // The order was checked against the loss limits, recording the reference points
+    let mut conn = PgConnection::connect(&test_db.pg_url)
+        .await
+        .expect("database is available");
+    let query = format!(
+        r#"SELECT key FROM "{}".key_values WHERE key LIKE 'risk_state:%'"#,
+        test_db.schema
+    );
+    let keys: Vec<String> = sqlx::query_scalar(AssertSqlSafe(query))
+        .fetch_all(&mut conn)
+        .await
+        .expect("keys are available");
+    conn.close().await.expect("connection closes");
+
+    assert_eq!(keys.len(), 1);
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn shadow_trade_engine_sends_no_trading_actions() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let engine = LiveTradeEngine::shadow_with_raw_operator(
+        live_config(&server, start),
+        db.clone(),
+        "key",
+        "secret",
+        "passphrase",
+        StoplossOperator::boxed(98_000., Arc::new(Mutex::new(Vec::new()))),
+    )
+    .expect("engine is valid");
+
+    let mut live_rx = engine.update_receiver();
+    let controller = engine.start().await.expect("engine starts");
+
+    let action = time::timeout(TIMEOUT, async {
+        loop {
+            match live_rx.recv().await.expect("channel is open") {
+                LiveTradeUpdate::ExecutorAction(action) => break action,
+                LiveTradeUpdate::Status(LiveTradeStatus::Terminated(err)) => {
+                    panic!("shadow trade terminated: {err}")
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("suppressed action is reported");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    assert!(matches!(
+        action,
+        LiveTradeExecutorAction::IsolatedOrder { .. }
+    ));
+    assert!(server.requests().iter().all(|req| !req.is_trading_action()));
+    assert!(
+        server
+            .running_trades()
+            .await
+            .expect("valid state")
+            .is_empty()
+    );
+
+    let actions = db
+        .get_executor_actions(server.account_id().await, start, Utc::now())
+        .await
+        .expect("actions are available");
+    let order = actions
+        .iter()
+        .find(|action| action.kind == "isolated_order")
+        .expect("intercepted order is audited");
+    let request: serde_json::Value =
+        serde_json::from_str(&order.request).expect("request is valid JSON");
+
+    assert!(actions.iter().all(|action| action.shadow));
+    assert_eq!(request["stoploss"], 98_000.);
+    assert!(order.response.is_some());
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn shadow_trade_engine_tracks_hypothetical_positions() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let engine = LiveTradeEngine::shadow_with_raw_operator(
+        live_config(&server, start),
+        db,
+        "key",
+        "secret",
+        "passphrase",
+        ShadowScriptOperator::boxed(outcomes.clone()),
+    )
+    .expect("engine is valid");
+
+    let mut live_rx = engine.update_receiver();
+    let controller = engine.start().await.expect("engine starts");
+
+    let actions = time::timeout(TIMEOUT, async {
+        let mut actions = Vec::new();
+        loop {
+            match live_rx.recv().await.expect("channel is open") {
+                LiveTradeUpdate::ExecutorAction(action) => {
+                    let done = matches!(action, LiveTradeExecutorAction::CrossOrderClosePosition);
+                    actions.push(action);
+                    if done {
+                        break actions;
+                    }
+                }
+                LiveTradeUpdate::Status(LiveTradeStatus::Terminated(err)) => {
+                    panic!("shadow trade terminated: {err}")
+                }
+                _ => {}
+            }
+        }
+    })
+    .await
+    .expect("suppressed actions are reported");
+
+    time::timeout(TIMEOUT, async {
+        while outcomes.lock().expect("not poisoned").is_empty() {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("operator script completes");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    let LiveTradeExecutorAction::IsolatedOrder { .. } = &actions[0] else {
+        panic!("unexpected first action: {:?}", actions[0]);
+    };
+    assert!(matches!(
+        &actions[1..],
+        [
+            LiveTradeExecutorAction::IsolatedTradeAddMargin { .. },
+            LiveTradeExecutorAction::IsolatedOrderClose { .. },
+            LiveTradeExecutorAction::CrossDeposit { .. },
+            LiveTradeExecutorAction::CrossOrder { .. },
+            LiveTradeExecutorAction::CrossOrderClosePosition,
+        ]
+    ));
+
+    // Follow-up actions on the hypothetical trade are accepted until it is closed, and the trading
+    // state reflects the hypothetical positions
+    assert_eq!(
+        *outcomes.lock().expect("not poisoned"),
+        vec![
+            ("state_opened", true),
+            ("add_margin", true),
+            ("close", true),
+            ("close_again", false),
+            ("state_closed", true),
+            ("state_cross_long", true),
+            ("close_position", true),
+            ("close_position_again", false),
+            ("state_cross_flat", true),
+        ]
+    );
+
+    assert!(server.requests().iter().all(|req| !req.is_trading_action()));
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn live_trade_engine_restores_saved_operator_state() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start();
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    let config =
+        || live_config(&server, start).with_operator_state_key(Some("operator_state".to_string()));
+
+    let last_iteration = shared_time();
+    let restored = shared_time();
+    let engine = LiveTradeEngine::with_raw_operator(
+        config(),
+        db.clone(),
+        "key",
+        "secret",
+        "passphrase",
+        StatefulOperator::boxed(last_iteration.clone(), restored.clone(), None),
+    )
+    .expect("engine is valid");
+
+    let controller = engine.start().await.expect("engine starts");
+
+    time::timeout(TIMEOUT, async {
+        while last_iteration.lock().expect("not poisoned").is_none() {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("operator iterates");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    assert!(restored.lock().expect("not poisoned").is_none());
+    let saved = *last_iteration.lock().expect("not poisoned");
+
+    let last_iteration = shared_time();
+    let engine = LiveTradeEngine::with_raw_operator(
+        config(),
+        db,
+        "key",
+        "secret",
+        "passphrase",
+        StatefulOperator::boxed(last_iteration.clone(), restored.clone(), None),
+    )
+    .expect("engine is valid");
+
+    let controller = engine.start().await.expect("engine starts");
+
+    time::timeout(TIMEOUT, async {
+        while last_iteration.lock().expect("not poisoned").is_none() {
+            time::sleep(time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("operator iterates");
+
+    controller.shutdown().await.expect("shutdown succeeds");
+
+    assert_eq!(*restored.lock().expect("not poisoned"), saved);
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn backtest_engines_checkpoint_and_restore_operator_state() {
+    let test_db = TestDb::new().await;
+    let db = test_db.db();
+
+    let start = feed_start() - Duration::days(1);
+    let server = MockLnmServer::start(
+        MockLnmConfig::default(),
+        MockPriceFeed::constant(start, 100_000.),
+    )
+    .await
+    .expect("server starts");
+
+    backfill(db.clone(), &server, start).await;
+
+    let config = |key: &str| BacktestConfig::default().with_operator_state_key(Some(key.into()));
+    let first_day_end = start + Duration::days(1) - Duration::seconds(1);
+
+    // The simulation fails during the second day, after the first daily checkpoint
+    let restored = shared_time();
+    let controller = BacktestEngine::with_raw_operator(
+        config("single"),
+        db.clone(),
+        StatefulOperator::boxed(
+            shared_time(),
+            restored.clone(),
+            Some(start + Duration::days(1) + Duration::hours(1)),
+        ),
+        start,
+        1_000_000,
+        start + Duration::days(2),
+    )
+    .await
+    .expect("engine is valid")
+    .start();
+
+    let status = time::timeout(TIMEOUT, controller.until_stopped())
+        .await
+        .expect("backtest stops");
+    assert!(matches!(status, BacktestStatus::Failed(_)));
+    assert!(restored.lock().expect("not poisoned").is_none());
+
+    // The run is resumed from the checkpoint of the first day
+    let last_iteration = shared_time();
+    let controller = BacktestEngine::with_raw_operator(
+        config("single"),
+        db.clone(),
+        StatefulOperator::boxed(last_iteration.clone(), restored.clone(), None),
+        start + Duration::days(1),
+        1_000_000,
+        start + Duration::days(2),
+    )
+    .await
+    .expect("engine is valid")
+    .start();
+
+    let status = time::timeout(TIMEOUT, controller.until_stopped())
+        .await
+        .expect("backtest stops");
+    assert!(matches!(status, BacktestStatus::Finished));
+    assert_eq!(*restored.lock().expect("not poisoned"), Some(first_day_end));
+    assert_eq!(
+        *last_iteration.lock().expect("not poisoned"),
+        Some(first_day_end + Duration::days(1))
+    );
+
+    // The state saved at the end of the run can't be restored by a run starting elsewhere
+    let controller = BacktestEngine::with_raw_operator(
+        config("single"),
+        db.clone(),
+        StatefulOperator::boxed(shared_time(), shared_time(), None),
+        start,
+        1_000_000,
+        start + Duration::days(2),
+    )
+    .await
+    .expect("engine is valid")
+    .start();
+
+    let status = time::timeout(TIMEOUT, controller.until_stopped())
+        .await
+        .expect("backtest stops");
+    assert!(matches!(
+        status,
+        BacktestStatus::Failed(e)
+            if matches!(e.as_ref(), BacktestError::OperatorStateCheckpointMismatch { .. })
+    ));
+
+    // Parallel backtests persist the state of each operator under its own key
+    let run_parallel = |run_start: DateTime<Utc>, restored_a, restored_b| {
+        let db = db.clone();
+        async move {
+            let controller = BacktestParallelEngine::new(
+                config("parallel"),
+                db,
+                run_start,
+                run_start + Duration::days(1),
+                1_000_000,
+            )
+            .await
+            .expect("engine is valid")
+            .add_raw_operator(
+                "a",
+                StatefulOperator::boxed(shared_time(), restored_a, None),
+            )
+            .expect("operator is valid")
+            .add_raw_operator(
+                "b",
+                StatefulOperator::boxed(shared_time(), restored_b, None),
+            )
+            .expect("operator is valid")
+            .start();
+
+            let status = time::timeout(TIMEOUT, controller.until_stopped())
+                .await
+                .expect("backtest stops");
+            assert!(matches!(status, BacktestStatus::Finished));
+        }
+    };
+
+    let (restored_a, restored_b) = (shared_time(), shared_time());
+    run_parallel(start, restored_a.clone(), restored_b.clone()).await;
+    assert!(restored_a.lock().expect("not poisoned").is_none());
+    assert!(restored_b.lock().expect("not poisoned").is_none());
+
+    run_parallel(
+        start + Duration::days(1),
+        restored_a.clone(),
+        restored_b.clone(),
+    )
+    .await;
+    assert_eq!(
+        *restored_a.lock().expect("not poisoned"),
+        Some(first_day_end)
+    );
+    assert_eq!(
+        *restored_b.lock().expect("not poisoned"),
+        Some(first_day_end)
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn backtest_engines_reject_persistence_on_read_only_database() {
+    let test_db = TestDb::new().await;
+
+    let config = DatabaseConfig::default()
+        .with_schema(test_db.schema.clone())
+        .expect("schema name is valid")
+        .with_read_only(true);
+    let db = Database::with_config(&test_db.pg_url, &config)
+        .await
+        .expect("database is available");
+    assert!(db.read_only());
+
+    let start = feed_start() - Duration::days(1);
+    let end = start + Duration::days(1);
+    let is_rejected = |res: std::result::Result<_, BacktestError>| {
+        matches!(
+            res,
+            Err(BacktestError::InvalidConfigurationReadOnlyDatabase { .. })
+        )
+    };
+
+    let state_config = || BacktestConfig::default().with_operator_state_key(Some("state".into()));
+    let signals_config = || BacktestConfig::default().with_signal_run(Some("run".into()));
+
+    let res = BacktestEngine::with_raw_operator(
+        state_config(),
+        db.clone(),
+        StatefulOperator::boxed(shared_time(), shared_time(), None),
+        start,
+        1_000_000,
+        end,
+    )
+    .await;
+    assert!(is_rejected(res.map(|_| ())));
+
+    for config in [state_config(), signals_config()] {
+        let res = BacktestParallelEngine::new(config, db.clone(), start, end, 1_000_000).await;
+        assert!(is_rejected(res.map(|_| ())));
+    }
+}
+
+#[tokio::test]
+#[ignore = "requires a Postgres database set via `POSTGRES_DB_URL`"]
+async fn sync_and_live_engines_reject_read_only_database() {
+    let test_db = TestDb::new().await;
+
+    let config = DatabaseConfig::default()
+        .with_schema(test_db.schema.clone())
+        .expect("schema name is valid")
+        .with_read_only(true);
+    let db = Database::with_config(&test_db.pg_url, &config)
+        .await
+        .expect("database is available");
+
+    let res = SyncEngine::new(SyncConfig::default(), db.clone(), SyncMode::backfill());
+    assert!(matches!(res, Err(SyncError::ReadOnlyDatabase)));
+
+    let res = LiveTradeEngine::paper_with_raw_operator(
+        LiveTradeConfig::default(),
+        db,
+        1_000_000,
+        StoplossOperator::boxed(95_000., Arc::new(Mutex::new(Vec::new()))),
+    );
+    assert!(matches!(res, Err(LiveError::ReadOnlyDatabase)));
+}